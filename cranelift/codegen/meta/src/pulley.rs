@@ -26,6 +26,18 @@ macro_rules! define {
 const OPS: &[Inst<'_>] = pulley_interpreter::for_each_op!(define);
 const EXTENDED_OPS: &[Inst<'_>] = pulley_interpreter::for_each_extended_op!(define);
 
+macro_rules! define_super {
+    (
+        $(
+            $snake_name:ident = $name:ident { $( $component:tt )* };
+        )*
+    ) => {
+        &[$(stringify!($name)),*]
+    };
+}
+
+const SUPER_OPS: &[&str] = pulley_interpreter::for_each_super_op!(define_super);
+
 enum Operand<'a> {
     Normal {
         name: &'a str,
@@ -95,6 +107,10 @@ impl Inst<'_> {
             // Skip special instructions not used in Cranelift.
             "XPush32Many" | "XPush64Many" | "XPop32Many" | "XPop64Many" => true,
 
+            // Skip superinstructions, which are formed by fusing
+            // instructions after they have been emitted.
+            n if SUPER_OPS.contains(&n) => true,
+
             // Skip more branching-related instructions.
             n => n.starts_with("Br"),
         }
@@ -69,6 +69,30 @@ pub(crate) fn define() -> SettingGroup {
         true,
     );
 
+    settings.add_bool(
+        "enable_loop_opts",
+        "Enable loop unrolling and induction-variable strength reduction.",
+        r#"
+            This replaces multiplications of induction variables by loop-invariant values with
+            additions when `opt_level` is `speed` or `speed_and_size`. When `opt_level` is
+            `speed`, small loops with a constant trip count are additionally unrolled, which
+            trades code size for fewer branches and more optimization opportunities.
+        "#,
+        false,
+    );
+
+    settings.add_bool(
+        "enable_slp_vectorizer",
+        "Enable SLP vectorization of straight-line code.",
+        r#"
+            This combines groups of four isomorphic `i32` or `f32` computations within a
+            block, starting from stores to consecutive addresses, into `i32x4` or `f32x4`
+            computations. It's only done on little-endian targets with SIMD support, when
+            `opt_level` is not `none`.
+        "#,
+        false,
+    );
+
     settings.add_bool(
         "enable_verifier",
         "Run the Cranelift IR verifier at strategic times during compilation.",
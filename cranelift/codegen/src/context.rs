@@ -15,15 +15,17 @@ use crate::dominator_tree::DominatorTreePreorder;
 use crate::egraph::EgraphPass;
 use crate::flowgraph::ControlFlowGraph;
 use crate::inline::{Inline, do_inlining};
-use crate::ir::Function;
+use crate::ir::{Endianness, Function};
 use crate::isa::TargetIsa;
 use crate::legalizer::simple_legalize;
 use crate::loop_analysis::LoopAnalysis;
+use crate::loop_opts::do_loop_opts;
 use crate::machinst::{CompiledCode, CompiledCodeStencil};
 use crate::nan_canonicalization::do_nan_canonicalization;
 use crate::remove_constant_phis::do_remove_constant_phis;
 use crate::result::{CodegenResult, CompileResult};
 use crate::settings::{FlagsOrIsa, OptLevel};
+use crate::slp::do_slp_vectorize;
 use crate::trace;
 use crate::unreachable_code::eliminate_unreachable_code;
 use crate::verifier::{VerifierErrors, VerifierResult, verify_context};
@@ -188,6 +190,12 @@ impl Context {
         self.func.dfg.resolve_all_aliases();
 
         if opt_level != OptLevel::None {
+            if isa.flags().enable_loop_opts() {
+                self.loop_opts(isa)?;
+            }
+            if isa.flags().enable_slp_vectorizer() {
+                self.slp_vectorize(isa)?;
+            }
             self.egraph_pass(isa, ctrl_plane)?;
         }
 
@@ -284,17 +292,38 @@ impl Context {
         Ok(())
     }
 
+    /// Perform loop unrolling and induction-variable strength reduction on the
+    /// function.
+    pub fn loop_opts(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
+        self.compute_loop_analysis();
+        let opt_level = isa.flags().opt_level();
+        if do_loop_opts(&mut self.func, &self.cfg, &self.loop_analysis, opt_level) {
+            // Unrolling removes back edges, and leaves header parameters of
+            // completely unrolled loops with a single constant incoming value.
+            self.flowgraph();
+            self.loop_analysis.clear();
+            self.remove_constant_phis(isa)?;
+            self.func.dfg.resolve_all_aliases();
+            self.verify_if(isa)?;
+        }
+        Ok(())
+    }
+
+    /// Perform SLP vectorization on the function.
+    ///
+    /// This does nothing on big-endian targets and targets without SIMD support.
+    pub fn slp_vectorize(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
+        if has_vector_support(isa) && isa.endianness() == Endianness::Little {
+            if do_slp_vectorize(&mut self.func) {
+                self.verify_if(isa)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Perform NaN canonicalizing rewrites on the function.
     pub fn canonicalize_nans(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
-        // Currently only RiscV64 is the only arch that may not have vector support.
-        let has_vector_support = match isa.triple().architecture {
-            Architecture::Riscv64(_) => match isa.isa_flags().iter().find(|f| f.name == "has_v") {
-                Some(value) => value.as_bool().unwrap_or(false),
-                None => false,
-            },
-            _ => true,
-        };
-        do_nan_canonicalization(&mut self.func, has_vector_support);
+        do_nan_canonicalization(&mut self.func, has_vector_support(isa));
         self.verify_if(isa)
     }
 
@@ -397,3 +426,15 @@ impl Context {
         self.verify_if(fisa)
     }
 }
+
+/// Whether `isa` supports vector instructions.
+fn has_vector_support(isa: &dyn TargetIsa) -> bool {
+    // Currently only RiscV64 is the only arch that may not have vector support.
+    match isa.triple().architecture {
+        Architecture::Riscv64(_) => match isa.isa_flags().iter().find(|f| f.name == "has_v") {
+            Some(value) => value.as_bool().unwrap_or(false),
+            None => false,
+        },
+        _ => true,
+    }
+}
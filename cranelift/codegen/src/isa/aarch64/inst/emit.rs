@@ -2922,6 +2922,120 @@ impl MachInstEmit for Inst {
                 // Nothing: this is a pseudoinstruction that serves
                 // only to constrain registers at a certain point.
             }
+            &Inst::StackSwitchBasic {
+                store_context_ptr,
+                load_context_ptr,
+                in_payload0: _,
+                out_payload0: _,
+                bti,
+            } => {
+                // Note that we do not emit anything for preserving and restoring
+                // ordinary registers here: That's taken care of by regalloc for us,
+                // since we marked this instruction as clobbering all registers.
+                //
+                // Also note that we do nothing about passing the single payload
+                // value: We've informed regalloc that it is sent and received via
+                // the fixed register given by [stack_switch::payload_register]
+                //
+                // The two temporaries used below are never handed out by
+                // regalloc, so they can't alias any of the operands.
+                let tmp1 = writable_spilltmp_reg();
+                let tmp2 = writable_tmp2_reg();
+
+                let layout = stack_switch::control_context_layout();
+                let sp_offset = i64::try_from(layout.stack_pointer_offset).unwrap();
+                let fp_offset = i64::try_from(layout.frame_pointer_offset).unwrap();
+                let pc_offset = i64::try_from(layout.ip_offset).unwrap();
+                // The frame pointer and the PC are loaded with a single `ldp`.
+                debug_assert_eq!(fp_offset + 8, pc_offset);
+
+                let flags = MemFlags::trusted();
+                let amode = |rn, off| AMode::UnsignedOffset {
+                    rn,
+                    uimm12: UImm12Scaled::maybe_from_i64(off, I64).unwrap(),
+                };
+
+                // Since `load_context_ptr` and `store_context_ptr` are allowed
+                // to be equal, every field is read from `load_context_ptr`
+                // before the corresponding field of `store_context_ptr` is
+                // overwritten.
+
+                // Exchange SP.
+                Inst::ULoad64 {
+                    rd: tmp1,
+                    mem: amode(load_context_ptr, sp_offset),
+                    flags,
+                }
+                .emit(sink, emit_info, state);
+                Inst::Mov {
+                    size: OperandSize::Size64,
+                    rd: tmp2,
+                    rm: stack_reg(),
+                }
+                .emit(sink, emit_info, state);
+                Inst::Store64 {
+                    rd: tmp2.to_reg(),
+                    mem: amode(store_context_ptr, sp_offset),
+                    flags,
+                }
+                .emit(sink, emit_info, state);
+                Inst::AluRRImm12 {
+                    alu_op: ALUOp::Add,
+                    size: OperandSize::Size64,
+                    rd: writable_stack_reg(),
+                    rn: tmp1.to_reg(),
+                    imm12: Imm12::maybe_from_u64(0).unwrap(),
+                }
+                .emit(sink, emit_info, state);
+
+                // Load the new FP and the target PC, then store the current FP.
+                Inst::LoadP64 {
+                    rt: tmp1,
+                    rt2: tmp2,
+                    mem: PairAMode::SignedOffset {
+                        reg: load_context_ptr,
+                        simm7: SImm7Scaled::maybe_from_i64(fp_offset, I64).unwrap(),
+                    },
+                    flags,
+                }
+                .emit(sink, emit_info, state);
+                Inst::Store64 {
+                    rd: fp_reg(),
+                    mem: amode(store_context_ptr, fp_offset),
+                    flags,
+                }
+                .emit(sink, emit_info, state);
+                Inst::Mov {
+                    size: OperandSize::Size64,
+                    rd: writable_fp_reg(),
+                    rm: tmp1.to_reg(),
+                }
+                .emit(sink, emit_info, state);
+
+                // Store the resume PC and jump to the target PC. The resume
+                // point is 3 instructions (`adr`, `str`, `br`) after the `adr`.
+                Inst::Adr { rd: tmp1, off: 12 }.emit(sink, emit_info, state);
+                Inst::Store64 {
+                    rd: tmp1.to_reg(),
+                    mem: amode(store_context_ptr, pc_offset),
+                    flags,
+                }
+                .emit(sink, emit_info, state);
+                Inst::IndirectBr {
+                    rn: tmp2.to_reg(),
+                    targets: vec![],
+                }
+                .emit(sink, emit_info, state);
+
+                // This is the location to which someone switch-ing back to
+                // this stack will jump to.
+                if bti {
+                    Inst::Bti {
+                        targets: BranchTargetType::J,
+                    }
+                    .emit(sink, emit_info, state);
+                }
+            }
             &Inst::Ret {} => {
                 sink.put4(0xd65f03c0);
             }
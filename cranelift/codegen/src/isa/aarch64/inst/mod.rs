@@ -25,6 +25,7 @@ pub mod emit;
 pub(crate) use self::emit::*;
 use crate::isa::aarch64::abi::AArch64MachineDeps;
 
+mod stack_switch;
 pub(crate) mod unwind;
 
 #[cfg(test)]
@@ -799,6 +800,28 @@ fn aarch64_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
                 collector.reg_fixed_use(vreg, *preg);
             }
         }
+        Inst::StackSwitchBasic {
+            store_context_ptr,
+            load_context_ptr,
+            in_payload0,
+            out_payload0,
+            ..
+        } => {
+            collector.reg_use(load_context_ptr);
+            collector.reg_use(store_context_ptr);
+            collector.reg_fixed_use(in_payload0, stack_switch::payload_register());
+            collector.reg_fixed_def(out_payload0, stack_switch::payload_register());
+
+            let mut clobbers = crate::isa::aarch64::abi::ALL_CLOBBERS;
+            // The return/payload reg must not be included in the clobber set
+            clobbers.remove(
+                stack_switch::payload_register()
+                    .to_real_reg()
+                    .unwrap()
+                    .into(),
+            );
+            collector.reg_clobbers(clobbers);
+        }
         Inst::Ret { .. } | Inst::AuthenticatedRet { .. } => {}
         Inst::Jump { .. } => {}
         Inst::Call { info, .. } => {
@@ -2615,6 +2638,21 @@ impl Inst {
                 }
                 s
             }
+            &Inst::StackSwitchBasic {
+                store_context_ptr,
+                load_context_ptr,
+                in_payload0,
+                out_payload0,
+                ..
+            } => {
+                let store_context_ptr = pretty_print_reg(store_context_ptr);
+                let load_context_ptr = pretty_print_reg(load_context_ptr);
+                let in_payload0 = pretty_print_reg(in_payload0);
+                let out_payload0 = pretty_print_reg(out_payload0.to_reg());
+                format!(
+                    "{out_payload0} = stack_switch_basic {store_context_ptr}, {load_context_ptr}, {in_payload0}"
+                )
+            }
             &Inst::Ret {} => "ret".to_string(),
             &Inst::AuthenticatedRet { key, is_hint } => {
                 let key = match key {
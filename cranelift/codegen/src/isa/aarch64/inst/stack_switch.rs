@@ -0,0 +1,41 @@
+use crate::{isa::aarch64::inst::regs, machinst::Reg};
+
+/// The `stack_switch` instruction loads information about the stack to switch
+/// to and stores information about the current stack by receiving pointers to
+/// memory laid out as in the struct `ControlContext` below.
+///
+/// ```
+/// #[repr(C)]
+/// pub struct ControlContext {
+///     pub stack_pointer: *mut u8,
+///     pub frame_pointer: *mut u8,
+///     pub instruction_pointer: *mut u8,
+/// }
+/// ```
+///
+/// This is the same layout as used on x64. Keeping the frame pointer right
+/// next to the instruction pointer makes the `ControlContext` look like a
+/// frame record (`x29`, `x30` pair), so frame pointer walking can continue from
+/// a continuation into its parent.
+pub struct ControlContextLayout {
+    pub stack_pointer_offset: usize,
+    pub frame_pointer_offset: usize,
+    pub ip_offset: usize,
+}
+
+pub fn control_context_layout() -> ControlContextLayout {
+    ControlContextLayout {
+        stack_pointer_offset: 0,
+        frame_pointer_offset: 8,
+        ip_offset: 16,
+    }
+}
+
+/// The register used for handing over the payload when switching stacks.
+///
+/// Both sides of a switch, including the `wasmtime_continuation_start`
+/// trampoline used to enter a freshly initialized stack, must agree on this
+/// register.
+pub fn payload_register() -> Reg {
+    regs::xreg(0)
+}
@@ -180,6 +180,10 @@ impl Context for IsleContext<'_, '_, MInst, AArch64Backend> {
         self.backend.isa_flags.has_fp16()
     }
 
+    fn use_bti(&mut self) -> bool {
+        self.backend.isa_flags.use_bti()
+    }
+
     fn move_wide_const_from_u64(&mut self, ty: Type, n: u64) -> Option<MoveWideConst> {
         let bits = ty.bits();
         let n = if bits < 64 {
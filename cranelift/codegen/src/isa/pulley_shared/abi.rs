@@ -849,7 +849,7 @@ const DEFAULT_CLOBBERS: PRegSet = PRegSet::empty()
     .with(pv_reg(30))
     .with(pv_reg(31));
 
-const ALL_CLOBBERS: PRegSet = PRegSet::empty()
+pub(crate) const ALL_CLOBBERS: PRegSet = PRegSet::empty()
     .with(px_reg(0))
     .with(px_reg(1))
     .with(px_reg(2))
@@ -8,6 +8,7 @@ use crate::isa::pulley_shared::abi::PulleyMachineDeps;
 use core::marker::PhantomData;
 use cranelift_control::ControlPlane;
 use pulley_interpreter::encode as enc;
+use pulley_interpreter::opcode::Opcode;
 use pulley_interpreter::regs::BinaryOperands;
 
 pub struct EmitInfo {
@@ -44,6 +45,9 @@ where
     ctrl_plane: ControlPlane,
     user_stack_map: Option<ir::UserStackMap>,
     frame_layout: FrameLayout,
+    /// The offset and opcode of the last emitted instruction, if it was a
+    /// single op which may be fused into a superinstruction.
+    last_op: Option<(CodeOffset, Opcode)>,
 }
 
 impl<P> EmitState<P>
@@ -65,6 +69,7 @@ where
             ctrl_plane,
             user_stack_map: None,
             frame_layout: abi.frame_layout().clone(),
+            last_op: None,
         }
     }
 
@@ -108,6 +113,8 @@ where
             end - start,
             InstAndKind::<P>::worst_case_size()
         );
+
+        fuse_superinstruction(sink, state, start, end);
     }
 
     fn pretty_print_inst(&self, state: &mut Self::State) -> String {
@@ -115,6 +122,55 @@ where
     }
 }
 
+/// Fuses the instruction just emitted at `start..end` with the previous one
+/// into a superinstruction, if there is one for this sequence of ops.
+///
+/// The encoding of a superinstruction is the encoding of its components
+/// without the opcode of the second one, so fusing amounts to replacing the
+/// first opcode and removing the second one. This is only done if no label,
+/// trap, relocation, etc. refers to the second instruction.
+fn fuse_superinstruction<P>(
+    sink: &mut MachBuffer<InstAndKind<P>>,
+    state: &mut EmitState<P>,
+    start: CodeOffset,
+    end: CodeOffset,
+) where
+    P: PulleyTargetKind,
+{
+    // Pseudo-instructions which don't emit anything don't prevent fusing.
+    if start == end {
+        return;
+    }
+
+    let last_op = state.last_op.take();
+
+    // Only instructions consisting of exactly one op can be fused.
+    let Some(opcode) = Opcode::new(sink.data()[start as usize]).filter(|op| {
+        op.width()
+            .is_some_and(|w| CodeOffset::from(w) == end - start)
+    }) else {
+        return;
+    };
+
+    if let Some((last_start, last_opcode)) = last_op {
+        let contiguous = last_opcode
+            .width()
+            .is_some_and(|w| last_start + CodeOffset::from(w) == start);
+        if let Some(fused) = Opcode::superinstruction(&[last_opcode, opcode]) {
+            if contiguous && sink.is_unreferenced_tail(start) {
+                let operands: SmallVec<[u8; 16]> =
+                    sink.data()[start as usize + 1..end as usize].into();
+                sink.truncate_unreferenced_tail(start);
+                sink.put_data(&operands);
+                sink.data_mut()[last_start as usize] = fused as u8;
+                return;
+            }
+        }
+    }
+
+    state.last_op = Some((start, opcode));
+}
+
 fn pulley_emit<P>(
     inst: &Inst,
     sink: &mut MachBuffer<InstAndKind<P>>,
@@ -295,6 +351,18 @@ fn pulley_emit<P>(
             assert!(info.callee_pop_size == 0);
         }
 
+        Inst::StackSwitch {
+            store_context_ptr,
+            load_context_ptr,
+            ..
+        } => {
+            // Saving and restoring all other registers is taken care of by
+            // regalloc since this instruction clobbers everything, and the
+            // payload is already in `x0`. The interpreter itself takes care of
+            // saving the resume `pc`, which is just after this instruction.
+            enc::stack_switch(sink, store_context_ptr, load_context_ptr);
+        }
+
         Inst::Jump { label } => {
             sink.use_label_at_offset(*start_offset + 1, *label, LabelUse::Jump(1));
             sink.add_uncond_branch(*start_offset, *start_offset + 5, *label);
@@ -191,6 +191,22 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
             }
             collector.reg_clobbers(info.clobbers);
         }
+        Inst::StackSwitch {
+            store_context_ptr,
+            load_context_ptr,
+            in_payload0,
+            out_payload0,
+        } => {
+            collector.reg_use(load_context_ptr);
+            collector.reg_use(store_context_ptr);
+            collector.reg_fixed_use(in_payload0, x0());
+            collector.reg_fixed_def(out_payload0, x0());
+
+            let mut clobbers = crate::isa::pulley_shared::abi::ALL_CLOBBERS;
+            // The return/payload reg must not be included in the clobber set
+            clobbers.remove(px_reg(0));
+            collector.reg_clobbers(clobbers);
+        }
         Inst::IndirectCall { info } => {
             collector.reg_use(&mut info.dest);
             let CallInfo { uses, defs, .. } = &mut **info;
@@ -680,6 +696,21 @@ impl Inst {
                 format!("indirect_call_host {info:?}")
             }
 
+            Inst::StackSwitch {
+                store_context_ptr,
+                load_context_ptr,
+                in_payload0,
+                out_payload0,
+            } => {
+                let store_context_ptr = format_reg(**store_context_ptr);
+                let load_context_ptr = format_reg(**load_context_ptr);
+                let in_payload0 = format_reg(**in_payload0);
+                let out_payload0 = format_reg(*out_payload0.to_reg());
+                format!(
+                    "{out_payload0} = stack_switch {store_context_ptr}, {load_context_ptr}, {in_payload0}"
+                )
+            }
+
             Inst::Jump { label } => format!("jump {}", label.to_string()),
 
             Inst::BrIf {
@@ -14,6 +14,7 @@ use crate::ir::LibCall;
 use crate::ir::Signature;
 use crate::isa::riscv64::settings::Flags as RiscvFlags;
 use crate::isa::unwind::UnwindInst;
+use crate::isa::winch;
 use crate::settings;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
@@ -94,11 +95,7 @@ impl ABIMachineSpec for Riscv64MachineDeps {
     ) -> CodegenResult<(u32, Option<usize>)> {
         // This implements the LP64D RISC-V ABI.
 
-        assert_ne!(
-            call_conv,
-            isa::CallConv::Winch,
-            "riscv64 does not support the 'winch' calling convention yet"
-        );
+        let is_winch_return = call_conv == isa::CallConv::Winch && args_or_rets == ArgsOrRets::Rets;
 
         // All registers that can be used as parameters or rets.
         // both start and end are included.
@@ -124,7 +121,7 @@ impl ABIMachineSpec for Riscv64MachineDeps {
             None
         };
 
-        for param in params {
+        for (i, param) in params.iter().enumerate() {
             if let ir::ArgumentPurpose::StructArgument(_) = param.purpose {
                 panic!(
                     "StructArgument parameters are not supported on riscv64. \
@@ -136,7 +133,12 @@ impl ABIMachineSpec for Riscv64MachineDeps {
             let (rcs, reg_tys) = Inst::rc_for_type(param.value_type)?;
             let mut slots = ABIArgSlotVec::new();
             for (rc, reg_ty) in rcs.iter().zip(reg_tys.iter()) {
-                let next_reg = if (next_x_reg <= x_end) && *rc == RegClass::Int {
+                // Winch uses the first register to return the last result and
+                // passes all the other results on the stack.
+                let in_reg = !is_winch_return || i == params.len() - 1;
+                let next_reg = if !in_reg {
+                    None
+                } else if (next_x_reg <= x_end) && *rc == RegClass::Int {
                     let x = Some(x_reg(next_x_reg));
                     next_x_reg += 1;
                     x
@@ -148,10 +150,17 @@ impl ABIMachineSpec for Riscv64MachineDeps {
                     None
                 };
                 if let Some(reg) = next_reg {
+                    // Winch expects 32-bit integers in registers to be
+                    // zero-extended to the full register width.
+                    let extension = if call_conv == isa::CallConv::Winch && reg_ty.is_int() {
+                        ir::ArgumentExtension::Uext
+                    } else {
+                        param.extension
+                    };
                     slots.push(ABIArgSlot::Reg {
                         reg: reg.to_real_reg().unwrap(),
                         ty: *reg_ty,
-                        extension: param.extension,
+                        extension,
                     });
                 } else {
                     if args_or_rets == ArgsOrRets::Rets && !flags.enable_multi_ret_implicit_sret() {
@@ -165,10 +174,17 @@ impl ABIMachineSpec for Riscv64MachineDeps {
                     // Compute size and 16-byte stack alignment happens
                     // separately after all args.
                     let size = reg_ty.bits() / 8;
-                    let size = std::cmp::max(size, 8);
-                    // Align.
-                    debug_assert!(size.is_power_of_two());
-                    next_stack = align_to(next_stack, size);
+                    let size = if is_winch_return {
+                        // Winch results on the stack are packed with their
+                        // natural size, without any alignment.
+                        size
+                    } else {
+                        let size = std::cmp::max(size, 8);
+                        // Align.
+                        debug_assert!(size.is_power_of_two());
+                        next_stack = align_to(next_stack, size);
+                        size
+                    };
                     slots.push(ABIArgSlot::Stack {
                         offset: next_stack as i64,
                         ty: *reg_ty,
@@ -189,6 +205,10 @@ impl ABIMachineSpec for Riscv64MachineDeps {
             None
         };
 
+        if is_winch_return {
+            winch::reverse_stack(args, next_stack, false);
+        }
+
         next_stack = align_to(next_stack, Self::stack_align(call_conv));
 
         Ok((next_stack, pos))
@@ -619,6 +639,7 @@ impl ABIMachineSpec for Riscv64MachineDeps {
     ) -> PRegSet {
         match call_conv_of_callee {
             isa::CallConv::Tail if is_exception => ALL_CLOBBERS,
+            isa::CallConv::Winch => ALL_CLOBBERS,
             _ => DEFAULT_CLOBBERS,
         }
     }
@@ -837,7 +858,7 @@ const DEFAULT_CLOBBERS: PRegSet = PRegSet::empty()
     .with(pv_reg(30))
     .with(pv_reg(31));
 
-const ALL_CLOBBERS: PRegSet = PRegSet::empty()
+pub(crate) const ALL_CLOBBERS: PRegSet = PRegSet::empty()
     // Specials: x0 is the zero register; x1 is the return address; x2 is SP.
     .with(px_reg(3))
     .with(px_reg(4))
@@ -213,9 +213,12 @@ impl From<StackAMode> for AMode {
 /// risc-v always take two register to compare
 #[derive(Clone, Copy, Debug)]
 pub struct IntegerCompare {
-    pub(crate) kind: IntCC,
-    pub(crate) rs1: Reg,
-    pub(crate) rs2: Reg,
+    /// The condition code.
+    pub kind: IntCC,
+    /// The left-hand side of the comparison.
+    pub rs1: Reg,
+    /// The right-hand side of the comparison.
+    pub rs2: Reg,
 }
 
 pub(crate) enum BranchFunct3 {
@@ -312,6 +315,7 @@ impl IntegerCompare {
     }
 }
 
+/// An index into the table of constants loadable with `fli`.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FliConstant(u8);
 
@@ -739,6 +743,7 @@ impl AluOPRRR {
         }
     }
 
+    /// The `funct3` field of the instruction encoding.
     pub fn funct3(self) -> u32 {
         match self {
             AluOPRRR::Add => 0b000,
@@ -813,6 +818,7 @@ impl AluOPRRR {
         }
     }
 
+    /// The opcode of the instruction encoding.
     pub fn op_code(self) -> u32 {
         match self {
             AluOPRRR::Add
@@ -883,6 +889,7 @@ impl AluOPRRR {
         }
     }
 
+    /// The `funct7` field of the instruction encoding.
     pub const fn funct7(self) -> u32 {
         match self {
             AluOPRRR::Add => 0b0000000,
@@ -1060,6 +1067,7 @@ impl AluOPRRI {
         }
     }
 
+    /// The `funct3` field of the instruction encoding.
     pub fn funct3(self) -> u32 {
         match self {
             AluOPRRI::Addi => 0b000,
@@ -1097,6 +1105,7 @@ impl AluOPRRI {
         }
     }
 
+    /// The opcode of the instruction encoding.
     pub fn op_code(self) -> u32 {
         match self {
             AluOPRRI::Addi
@@ -1563,9 +1572,13 @@ impl AtomicOP {
 ///Atomic Memory ordering.
 #[derive(Copy, Clone, Debug)]
 pub enum AMO {
+    /// No ordering constraints.
     Relax = 0b00,
+    /// Release semantics.
     Release = 0b01,
+    /// Acquire semantics.
     Acquire = 0b10,
+    /// Sequentially consistent.
     SeqCst = 0b11,
 }
 
@@ -7,13 +7,15 @@ use crate::isa::riscv64::lower::isle::generated_code::{
 };
 use cranelift_control::ControlPlane;
 
+/// Information needed to emit instructions.
 pub struct EmitInfo {
     shared_flag: settings::Flags,
     isa_flags: super::super::riscv_settings::Flags,
 }
 
 impl EmitInfo {
-    pub(crate) fn new(
+    /// Create the emission information from the shared and ISA flags.
+    pub fn new(
         shared_flag: settings::Flags,
         isa_flags: super::super::riscv_settings::Flags,
     ) -> Self {
@@ -35,10 +37,13 @@ pub(crate) fn reg_to_compressed_gpr_num(m: Reg) -> u32 {
     u32::from(compressed_reg)
 }
 
+/// The known state of the vector unit during emission.
 #[derive(Clone, Debug, PartialEq, Default)]
 pub enum EmitVState {
+    /// The state is unknown, e.g. at the start of a block.
     #[default]
     Unknown,
+    /// The vector unit is known to be in the given state.
     Known(VState),
 }
 
@@ -162,6 +167,7 @@ impl Inst {
             | Inst::Store { .. }
             | Inst::Args { .. }
             | Inst::Rets { .. }
+            | Inst::StackSwitchBasic { .. }
             | Inst::Ret { .. }
             | Inst::Extend { .. }
             | Inst::Call { .. }
@@ -1136,6 +1142,88 @@ impl Inst {
                 // Nothing: this is a pseudoinstruction that serves
                 // only to constrain registers at a certain point.
             }
+            &Inst::StackSwitchBasic {
+                store_context_ptr,
+                load_context_ptr,
+                in_payload0: _,
+                out_payload0: _,
+            } => {
+                // Note that we do not emit anything for preserving and restoring
+                // ordinary registers here: That's taken care of by regalloc for us,
+                // since we marked this instruction as clobbering all registers.
+                //
+                // Also note that we do nothing about passing the single payload
+                // value: We've informed regalloc that it is sent and received via
+                // the fixed register given by [stack_switch::payload_register]
+                //
+                // The two temporaries used below are never handed out by
+                // regalloc, so they can't alias any of the operands.
+                let tmp1 = writable_spilltmp_reg();
+                let tmp2 = writable_spilltmp_reg2();
+
+                let layout = stack_switch::control_context_layout();
+                let sp_offset = i64::try_from(layout.stack_pointer_offset).unwrap();
+                let fp_offset = i64::try_from(layout.frame_pointer_offset).unwrap();
+                let pc_offset = i64::try_from(layout.ip_offset).unwrap();
+
+                let flags = MemFlags::trusted();
+                let load = |rd, offset| Inst::Load {
+                    rd,
+                    op: LoadOP::Ld,
+                    flags,
+                    from: AMode::RegOffset(load_context_ptr, offset),
+                };
+                let store = |src, offset| Inst::Store {
+                    to: AMode::RegOffset(store_context_ptr, offset),
+                    op: StoreOP::Sd,
+                    flags,
+                    src,
+                };
+
+                // Location to which someone switch-ing back to this stack will jump
+                // to: Right behind the `StackSwitch` instruction
+                let resume = sink.get_label();
+
+                //
+                // For SP and FP we do the following:
+                // - Load new value for register from `load_context_ptr` +
+                // corresponding offset.
+                // - Store previous (!) value of register at `store_context_ptr` +
+                // corresponding offset.
+                //
+                // Since `load_context_ptr` and `store_context_ptr` are allowed to be
+                // equal, we need to use a temporary register here.
+                //
+                for (offset, reg) in [(sp_offset, stack_reg()), (fp_offset, fp_reg())] {
+                    load(tmp1, offset).emit(sink, emit_info, state);
+                    store(reg, offset).emit(sink, emit_info, state);
+                    Inst::Mov {
+                        rd: Writable::from_reg(reg),
+                        rm: tmp1.to_reg(),
+                        ty: I64,
+                    }
+                    .emit(sink, emit_info, state);
+                }
+
+                //
+                // Load target PC, store resume PC, jump to target PC
+                //
+                load(tmp2, pc_offset).emit(sink, emit_info, state);
+                Inst::LoadAddr {
+                    rd: tmp1,
+                    mem: AMode::Label(resume),
+                }
+                .emit(sink, emit_info, state);
+                store(tmp1.to_reg(), pc_offset).emit(sink, emit_info, state);
+                Inst::Jalr {
+                    rd: writable_zero_reg(),
+                    base: tmp2.to_reg(),
+                    offset: Imm12::ZERO,
+                }
+                .emit(sink, emit_info, state);
+
+                sink.bind_label(resume, &mut state.ctrl_plane);
+            }
             &Inst::Ret {} => {
                 // RISC-V does not have a dedicated ret instruction, instead we emit the equivalent
                 // `jalr x0, x1, 0` that jumps to the return address.
@@ -153,6 +153,7 @@ pub fn encode_valu_rr_imm(
     )
 }
 
+/// Encode a vector ALU instruction with three register operands.
 pub fn encode_valu_rrrr(
     op: VecAluOpRRRR,
     vd: WritableReg,
@@ -171,6 +172,7 @@ pub fn encode_valu_rrrr(
     )
 }
 
+/// Encode a vector ALU instruction with two registers and an immediate.
 pub fn encode_valu_rrr_imm(
     op: VecAluOpRRRImm5,
     vd: WritableReg,
@@ -190,6 +192,7 @@ pub fn encode_valu_rrr_imm(
     )
 }
 
+/// Encode a vector ALU instruction with two register operands.
 pub fn encode_valu_rr(op: VecAluOpRR, vd: WritableReg, vs: Reg, masking: VecOpMasking) -> u32 {
     let funct7 = (op.funct6() << 1) | masking.encode();
 
@@ -209,6 +212,7 @@ pub fn encode_valu_rr(op: VecAluOpRR, vd: WritableReg, vs: Reg, masking: VecOpMa
     )
 }
 
+/// Encode a vector ALU instruction with a register and an immediate.
 pub fn encode_valu_r_imm(
     op: VecAluOpRImm5,
     vd: WritableReg,
@@ -305,14 +309,14 @@ pub fn encode_vmem_store(
     encode_vmem_load(opcode, vs3, width, rs1, sumop, masking, mop, nf)
 }
 
-// The CSR Reg instruction is really just an I type instruction with the CSR in
-// the immediate field.
+/// The CSR Reg instruction is really just an I type instruction with the CSR in
+/// the immediate field.
 pub fn encode_csr_reg(op: CsrRegOP, rd: WritableReg, rs: Reg, csr: CSR) -> u32 {
     encode_i_type(op.opcode(), rd, op.funct3(), rs, csr.bits())
 }
 
-// The CSR Imm instruction is an I type instruction with the CSR in
-// the immediate field and the value to be set in the `rs1` field.
+/// The CSR Imm instruction is an I type instruction with the CSR in
+/// the immediate field and the value to be set in the `rs1` field.
 pub fn encode_csr_imm(op: CsrImmOP, rd: WritableReg, csr: CSR, imm: UImm5) -> u32 {
     encode_i_type_bits(
         op.opcode(),
@@ -323,10 +327,10 @@ pub fn encode_csr_imm(op: CsrImmOP, rd: WritableReg, csr: CSR, imm: UImm5) -> u3
     )
 }
 
-// Encode a CR type instruction.
-//
-// 0--1-2-----6-7-------11-12-------15
-// |op |  rs2  |  rd/rs1  |  funct4  |
+/// Encode a CR type instruction.
+///
+/// 0--1-2-----6-7-------11-12-------15
+/// |op |  rs2  |  rd/rs1  |  funct4  |
 pub fn encode_cr_type(op: CrOp, rd: WritableReg, rs2: Reg) -> u16 {
     let mut bits = 0;
     bits |= unsigned_field_width(op.op().bits(), 2);
@@ -336,18 +340,18 @@ pub fn encode_cr_type(op: CrOp, rd: WritableReg, rs2: Reg) -> u16 {
     bits.try_into().unwrap()
 }
 
-// This isn't technically a instruction format that exists. It's just a CR type
-// where the source is rs1, rs2 is zero. rs1 is never written to.
-//
-// Used for C.JR and C.JALR
+/// This isn't technically a instruction format that exists. It's just a CR type
+/// where the source is rs1, rs2 is zero. rs1 is never written to.
+///
+/// Used for C.JR and C.JALR
 pub fn encode_cr2_type(op: CrOp, rs1: Reg) -> u16 {
     encode_cr_type(op, WritableReg::from_reg(rs1), zero_reg())
 }
 
-// Encode a CA type instruction.
-//
-// 0--1-2-----4-5--------6-7--------9-10------15
-// |op |  rs2  |  funct2  |  rd/rs1  | funct6 |
+/// Encode a CA type instruction.
+///
+/// 0--1-2-----4-5--------6-7--------9-10------15
+/// |op |  rs2  |  funct2  |  rd/rs1  | funct6 |
 pub fn encode_ca_type(op: CaOp, rd: WritableReg, rs2: Reg) -> u16 {
     let mut bits = 0;
     bits |= unsigned_field_width(op.op().bits(), 2);
@@ -358,12 +362,12 @@ pub fn encode_ca_type(op: CaOp, rd: WritableReg, rs2: Reg) -> u16 {
     bits.try_into().unwrap()
 }
 
-// Encode a CJ type instruction.
-//
-// The imm field is a 11 bit signed immediate that is shifted left by 1.
-//
-// 0--1-2-----12-13--------15
-// |op |  imm   |  funct3  |
+/// Encode a CJ type instruction.
+///
+/// The imm field is a 11 bit signed immediate that is shifted left by 1.
+///
+/// 0--1-2-----12-13--------15
+/// |op |  imm   |  funct3  |
 pub fn encode_cj_type(op: CjOp, imm: Imm12) -> u16 {
     let imm = imm.bits();
     debug_assert!(imm & 1 == 0);
@@ -387,12 +391,12 @@ pub fn encode_cj_type(op: CjOp, imm: Imm12) -> u16 {
     bits.try_into().unwrap()
 }
 
-// Encode a CI type instruction.
-//
-// The imm field is a 6 bit signed immediate.
-//
-// 0--1-2-------6-7-------11-12-----12-13-----15
-// |op | imm[4:0] |   src   | imm[5]  | funct3  |
+/// Encode a CI type instruction.
+///
+/// The imm field is a 6 bit signed immediate.
+///
+/// 0--1-2-------6-7-------11-12-----12-13-----15
+/// |op | imm[4:0] |   src   | imm[5]  | funct3  |
 pub fn encode_ci_type(op: CiOp, rd: WritableReg, imm: Imm6) -> u16 {
     let imm = imm.bits();
 
@@ -405,8 +409,8 @@ pub fn encode_ci_type(op: CiOp, rd: WritableReg, imm: Imm6) -> u16 {
     bits.try_into().unwrap()
 }
 
-// Stack-Pointer relative loads are regular CI instructions, but, the immediate
-// is zero extended, and with a slightly different immediate field encoding.
+/// Stack-Pointer relative loads are regular CI instructions, but, the immediate
+/// is zero extended, and with a slightly different immediate field encoding.
 pub fn encode_ci_sp_load(op: CiOp, rd: WritableReg, imm: Uimm6) -> u16 {
     let imm = imm.bits();
 
@@ -456,10 +460,10 @@ pub fn encode_c_addi16sp(imm: Imm6) -> u16 {
     encode_ci_type(CiOp::CAddi16sp, writable_stack_reg(), enc_imm)
 }
 
-// Encode a CIW type instruction.
-//
-// 0--1-2------4-5------12-13--------15
-// |op |   rd   |   imm   |  funct3  |
+/// Encode a CIW type instruction.
+///
+/// 0--1-2------4-5------12-13--------15
+/// |op |   rd   |   imm   |  funct3  |
 pub fn encode_ciw_type(op: CiwOp, rd: WritableReg, imm: u8) -> u16 {
     // [3:2|7:4|0|1]
     let mut imm_field = 0;
@@ -476,12 +480,12 @@ pub fn encode_ciw_type(op: CiwOp, rd: WritableReg, imm: u8) -> u16 {
     bits.try_into().unwrap()
 }
 
-// Encode a CB type instruction.
-//
-// The imm field is a 6 bit signed immediate.
-//
-// 0--1-2-------6-7-------9-10-------11-12-------13--------15
-// |op | imm[4:0] |   dst  |  funct2   |  imm[5]  | funct3 |
+/// Encode a CB type instruction.
+///
+/// The imm field is a 6 bit signed immediate.
+///
+/// 0--1-2-------6-7-------9-10-------11-12-------13--------15
+/// |op | imm[4:0] |   dst  |  funct2   |  imm[5]  | funct3 |
 pub fn encode_cb_type(op: CbOp, rd: WritableReg, imm: Imm6) -> u16 {
     let imm = imm.bits();
 
@@ -495,12 +499,12 @@ pub fn encode_cb_type(op: CbOp, rd: WritableReg, imm: Imm6) -> u16 {
     bits.try_into().unwrap()
 }
 
-// Encode a CSS type instruction.
-//
-// The imm field is a 6 bit unsigned immediate.
-//
-// 0--1-2-------6-7--------12-13-------15
-// |op |   src   |    imm    |  funct3  |
+/// Encode a CSS type instruction.
+///
+/// The imm field is a 6 bit unsigned immediate.
+///
+/// 0--1-2-------6-7--------12-13-------15
+/// |op |   src   |    imm    |  funct3  |
 pub fn encode_css_type(op: CssOp, src: Reg, imm: Uimm6) -> u16 {
     let imm = imm.bits();
 
@@ -530,12 +534,12 @@ pub fn encode_css_type(op: CssOp, src: Reg, imm: Uimm6) -> u16 {
     bits.try_into().unwrap()
 }
 
-// Encode a CS type instruction.
-//
-// The imm field is a 5 bit unsigned immediate.
-//
-// 0--1-2-----4-5----------6-7---------9-10----------12-13-----15
-// |op |  src  | imm(2-bit) |   base    |  imm(3-bit)  | funct3  |
+/// Encode a CS type instruction.
+///
+/// The imm field is a 5 bit unsigned immediate.
+///
+/// 0--1-2-----4-5----------6-7---------9-10----------12-13-----15
+/// |op |  src  | imm(2-bit) |   base    |  imm(3-bit)  | funct3  |
 pub fn encode_cs_type(op: CsOp, src: Reg, base: Reg, imm: Uimm5) -> u16 {
     let size = match op {
         CsOp::CFsd | CsOp::CSd => 8,
@@ -545,12 +549,12 @@ pub fn encode_cs_type(op: CsOp, src: Reg, base: Reg, imm: Uimm5) -> u16 {
     encode_cs_cl_type_bits(op.op(), op.funct3(), size, src, base, imm)
 }
 
-// Encode a CL type instruction.
-//
-// The imm field is a 5 bit unsigned immediate.
-//
-// 0--1-2------4-5----------6-7---------9-10----------12-13-----15
-// |op |  dest  | imm(2-bit) |   base    |  imm(3-bit)  | funct3  |
+/// Encode a CL type instruction.
+///
+/// The imm field is a 5 bit unsigned immediate.
+///
+/// 0--1-2------4-5----------6-7---------9-10----------12-13-----15
+/// |op |  dest  | imm(2-bit) |   base    |  imm(3-bit)  | funct3  |
 pub fn encode_cl_type(op: ClOp, dest: WritableReg, base: Reg, imm: Uimm5) -> u16 {
     let size = match op {
         ClOp::CFld | ClOp::CLd => 8,
@@ -602,12 +606,12 @@ fn encode_cs_cl_type_bits(
     bits.try_into().unwrap()
 }
 
-// Encode a CSZN type instruction.
-//
-// This is an additional encoding format that is introduced in the Zcb extension.
-//
-// 0--1-2---------6-7--------9-10------15
-// |op |   funct5  |  rd/rs1  | funct6 |
+/// Encode a CSZN type instruction.
+///
+/// This is an additional encoding format that is introduced in the Zcb extension.
+///
+/// 0--1-2---------6-7--------9-10------15
+/// |op |   funct5  |  rd/rs1  | funct6 |
 pub fn encode_cszn_type(op: CsznOp, rd: WritableReg) -> u16 {
     let mut bits = 0;
     bits |= unsigned_field_width(op.op().bits(), 2);
@@ -645,14 +649,17 @@ fn encode_zcbmem_bits(op: ZcbMemOp, dest_src: Reg, base: Reg, imm: Uimm2) -> u16
     bits.try_into().unwrap()
 }
 
+/// Encode a Zcb compressed load.
 pub fn encode_zcbmem_load(op: ZcbMemOp, rd: WritableReg, base: Reg, imm: Uimm2) -> u16 {
     encode_zcbmem_bits(op, rd.to_reg(), base, imm)
 }
 
+/// Encode a Zcb compressed store.
 pub fn encode_zcbmem_store(op: ZcbMemOp, src: Reg, base: Reg, imm: Uimm2) -> u16 {
     encode_zcbmem_bits(op, src, base, imm)
 }
 
+/// Encode a `fli` instruction.
 pub fn encode_fli(width: FpuOPWidth, imm: FliConstant, rd: WritableReg) -> u32 {
     // FLI.{H,S,D} is encoded as a FMV.{H,W,D} instruction with rs2 set to the
     // immediate value to be loaded.
@@ -670,6 +677,7 @@ pub fn encode_fli(width: FpuOPWidth, imm: FliConstant, rd: WritableReg) -> u32 {
     bits
 }
 
+/// Encode a float instruction with two register operands.
 pub fn encode_fp_rr(op: FpuOPRR, width: FpuOPWidth, frm: FRM, rd: WritableReg, rs: Reg) -> u32 {
     encode_r_type_bits(
         op.opcode(),
@@ -681,6 +689,7 @@ pub fn encode_fp_rr(op: FpuOPRR, width: FpuOPWidth, frm: FRM, rd: WritableReg, r
     )
 }
 
+/// Encode a float instruction with three register operands.
 pub fn encode_fp_rrr(
     op: FpuOPRRR,
     width: FpuOPWidth,
@@ -699,6 +708,7 @@ pub fn encode_fp_rrr(
     )
 }
 
+/// Encode a float instruction with four register operands.
 pub fn encode_fp_rrrr(
     op: FpuOPRRRR,
     width: FpuOPWidth,
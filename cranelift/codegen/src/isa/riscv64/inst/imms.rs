@@ -4,6 +4,7 @@
 use super::Inst;
 use std::fmt::{Debug, Display, Formatter, Result};
 
+/// A signed 12-bit immediate.
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Imm12 {
     /// 16-bit container where the low 12 bits are the data payload.
@@ -16,10 +17,12 @@ impl Imm12 {
     pub(crate) const ZERO: Self = Self { bits: 0 };
     pub(crate) const ONE: Self = Self { bits: 1 };
 
+    /// Create a 12-bit immediate from a `u64`, if it fits.
     pub fn maybe_from_u64(val: u64) -> Option<Imm12> {
         Self::maybe_from_i64(val as i64)
     }
 
+    /// Create a 12-bit immediate from an `i64`, if it fits.
     pub fn maybe_from_i64(val: i64) -> Option<Imm12> {
         if val >= -2048 && val <= 2047 {
             Some(Imm12 {
@@ -30,6 +33,7 @@ impl Imm12 {
         }
     }
 
+    /// Create a 12-bit immediate from an `i16`, panicking if it doesn't fit.
     #[inline]
     pub fn from_i16(bits: i16) -> Self {
         assert!(bits >= -2048 && bits <= 2047);
@@ -38,11 +42,13 @@ impl Imm12 {
         }
     }
 
+    /// Returns the sign-extended value of the immediate.
     #[inline]
     pub fn as_i16(self) -> i16 {
         (self.bits << 4) as i16 >> 4
     }
 
+    /// Bits for encoding.
     #[inline]
     pub fn bits(&self) -> u32 {
         self.bits.into()
@@ -62,6 +68,7 @@ impl Display for Imm12 {
 }
 
 // signed
+/// A signed 20-bit immediate.
 #[derive(Clone, Copy, Default)]
 pub struct Imm20 {
     /// 32-bit container where the low 20 bits are the data payload.
@@ -73,10 +80,12 @@ pub struct Imm20 {
 impl Imm20 {
     pub(crate) const ZERO: Self = Self { bits: 0 };
 
+    /// Create a 20-bit immediate from a `u64`, if it fits.
     pub fn maybe_from_u64(val: u64) -> Option<Imm20> {
         Self::maybe_from_i64(val as i64)
     }
 
+    /// Create a 20-bit immediate from an `i64`, if it fits.
     pub fn maybe_from_i64(val: i64) -> Option<Imm20> {
         if val >= -(0x7_ffff + 1) && val <= 0x7_ffff {
             Some(Imm20 { bits: val as u32 })
@@ -85,6 +94,7 @@ impl Imm20 {
         }
     }
 
+    /// Create a 20-bit immediate from an `i32`, panicking if it doesn't fit.
     #[inline]
     pub fn from_i32(bits: i32) -> Self {
         assert!(bits >= -(0x7_ffff + 1) && bits <= 0x7_ffff);
@@ -93,11 +103,13 @@ impl Imm20 {
         }
     }
 
+    /// Returns the sign-extended value of the immediate.
     #[inline]
     pub fn as_i32(&self) -> i32 {
         ((self.bits << 12) as i32) >> 12
     }
 
+    /// Bits for encoding.
     #[inline]
     pub fn bits(&self) -> u32 {
         self.bits
@@ -160,6 +172,7 @@ impl Imm5 {
         }
     }
 
+    /// Create a 5-bit immediate from its encoding bits.
     pub fn from_bits(value: u8) -> Imm5 {
         assert_eq!(value & 0x1f, value);
         let signed = ((value << 3) as i8) >> 3;
@@ -194,10 +207,12 @@ impl Imm6 {
         }
     }
 
+    /// Create an signed 6-bit immediate from an i32
     pub fn maybe_from_i32(value: i32) -> Option<Self> {
         value.try_into().ok().and_then(Imm6::maybe_from_i16)
     }
 
+    /// Create an signed 6-bit immediate from a 12-bit immediate
     pub fn maybe_from_imm12(value: Imm12) -> Option<Self> {
         Imm6::maybe_from_i16(value.as_i16())
     }
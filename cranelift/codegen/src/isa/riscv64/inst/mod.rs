@@ -27,10 +27,13 @@ pub mod args;
 pub use self::args::*;
 pub mod emit;
 pub use self::emit::*;
+/// Vector instruction definitions.
 pub mod vector;
 pub use self::vector::*;
 pub mod encode;
 pub use self::encode::*;
+mod stack_switch;
+/// Unwind information for riscv64.
 pub mod unwind;
 
 use crate::isa::riscv64::abi::Riscv64MachineDeps;
@@ -47,7 +50,7 @@ pub(crate) type VecU8 = Vec<u8>;
 
 pub use crate::isa::riscv64::lower::isle::generated_code::{
     AluOPRRI, AluOPRRR, AtomicOP, CSR, CsrImmOP, CsrRegOP, FClassResult, FFlagsException, FRM,
-    FpuOPRR, FpuOPRRR, FpuOPRRRR, LoadOP, MInst as Inst, StoreOP,
+    FpuOPRR, FpuOPRRR, FpuOPRRRR, FpuOPWidth, LoadOP, MInst as Inst, StoreOP,
 };
 use crate::isa::riscv64::lower::isle::generated_code::{CjOp, MInst, VecAluOpRRImm5, VecAluOpRRR};
 
@@ -55,8 +58,11 @@ use crate::isa::riscv64::lower::isle::generated_code::{CjOp, MInst, VecAluOpRRIm
 /// line to lower the size of the `Inst` enum.
 #[derive(Clone, Debug)]
 pub struct ReturnCallInfo<T> {
+    /// The target of the call.
     pub dest: T,
+    /// The arguments passed to the callee.
     pub uses: CallArgList,
+    /// The size of the stack arguments of the callee.
     pub new_stack_arg_size: u32,
 }
 
@@ -93,12 +99,14 @@ impl Display for CondBrTarget {
     }
 }
 
-pub(crate) fn enc_auipc(rd: Writable<Reg>, imm: Imm20) -> u32 {
+/// Encode an `auipc` instruction.
+pub fn enc_auipc(rd: Writable<Reg>, imm: Imm20) -> u32 {
     let x = 0b0010111 | reg_to_gpr_num(rd.to_reg()) << 7 | imm.bits() << 12;
     x
 }
 
-pub(crate) fn enc_jalr(rd: Writable<Reg>, base: Reg, offset: Imm12) -> u32 {
+/// Encode a `jalr` instruction.
+pub fn enc_jalr(rd: Writable<Reg>, base: Reg, offset: Imm12) -> u32 {
     let x = 0b1100111
         | reg_to_gpr_num(rd.to_reg()) << 7
         | 0b000 << 12
@@ -176,6 +184,7 @@ impl Inst {
         })
     }
 
+    /// Create instructions that load a 64-bit constant into `rd`.
     pub fn load_constant_u64(rd: Writable<Reg>, value: u64) -> SmallInstVec<Inst> {
         let insts = Inst::load_const_imm(rd, value);
         insts.unwrap_or_else(|| {
@@ -326,6 +335,28 @@ fn riscv64_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
         }
         Inst::Ret { .. } => {}
 
+        Inst::StackSwitchBasic {
+            store_context_ptr,
+            load_context_ptr,
+            in_payload0,
+            out_payload0,
+        } => {
+            collector.reg_use(load_context_ptr);
+            collector.reg_use(store_context_ptr);
+            collector.reg_fixed_use(in_payload0, stack_switch::payload_register());
+            collector.reg_fixed_def(out_payload0, stack_switch::payload_register());
+
+            let mut clobbers = crate::isa::riscv64::abi::ALL_CLOBBERS;
+            // The return/payload reg must not be included in the clobber set
+            clobbers.remove(
+                stack_switch::payload_register()
+                    .to_real_reg()
+                    .unwrap()
+                    .into(),
+            );
+            collector.reg_clobbers(clobbers);
+        }
+
         Inst::Extend { rd, rn, .. } => {
             collector.reg_use(rn);
             collector.reg_def(rd);
@@ -845,6 +876,7 @@ impl MachInst for Inst {
 
 //=============================================================================
 // Pretty-printing of instructions.
+/// Returns the assembly name of a register.
 pub fn reg_name(reg: Reg) -> String {
     match reg.to_real_reg() {
         Some(real) => match real.class() {
@@ -1295,6 +1327,20 @@ impl Inst {
                 s
             }
             &Inst::Ret {} => "ret".to_string(),
+            &Inst::StackSwitchBasic {
+                store_context_ptr,
+                load_context_ptr,
+                in_payload0,
+                out_payload0,
+            } => {
+                let store_context_ptr = format_reg(store_context_ptr);
+                let load_context_ptr = format_reg(load_context_ptr);
+                let in_payload0 = format_reg(in_payload0);
+                let out_payload0 = format_reg(out_payload0.to_reg());
+                format!(
+                    "{out_payload0} = stack_switch_basic {store_context_ptr}, {load_context_ptr}, {in_payload0}"
+                )
+            }
 
             &MInst::Extend {
                 rd,
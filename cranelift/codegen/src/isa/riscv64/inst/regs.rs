@@ -6,53 +6,61 @@ use crate::machinst::{Reg, Writable};
 use regalloc2::{PReg, RegClass, VReg};
 
 // first argument of function call
+/// Get a reference to the first argument register (a0).
 #[inline]
 pub const fn a0() -> Reg {
     x_reg(10)
 }
 
 // second argument of function call
+/// Get a reference to the second argument register (a1).
 #[inline]
 pub const fn a1() -> Reg {
     x_reg(11)
 }
 
 // third argument of function call
+/// Get a reference to the third argument register (a2).
 #[inline]
 pub fn a2() -> Reg {
     x_reg(12)
 }
 
+/// Get a writable reference to a0.
 #[inline]
 pub fn writable_a0() -> Writable<Reg> {
     Writable::from_reg(a0())
 }
+/// Get a writable reference to a1.
 #[inline]
 #[cfg(test)]
 pub fn writable_a1() -> Writable<Reg> {
     Writable::from_reg(a1())
 }
+/// Get a writable reference to a2.
 #[inline]
-#[expect(dead_code, reason = "here if needed in the future")]
 pub fn writable_a2() -> Writable<Reg> {
     Writable::from_reg(a2())
 }
 
+/// Get a reference to the first float argument register (fa0).
 #[inline]
 #[cfg(test)]
 pub fn fa0() -> Reg {
     f_reg(10)
 }
+/// Get a writable reference to fa0.
 #[inline]
 #[cfg(test)]
 pub fn writable_fa0() -> Writable<Reg> {
     Writable::from_reg(fa0())
 }
+/// Get a writable reference to fa1.
 #[inline]
-#[expect(dead_code, reason = "here if needed in the future")]
 pub fn writable_fa1() -> Writable<Reg> {
     Writable::from_reg(fa1())
 }
+/// Get a reference to the second float argument register (fa1).
 #[inline]
 pub fn fa1() -> Reg {
     f_reg(11)
@@ -69,6 +77,7 @@ pub fn zero_reg() -> Reg {
 pub fn writable_zero_reg() -> Writable<Reg> {
     Writable::from_reg(zero_reg())
 }
+/// Get a reference to the stack-pointer register (x2).
 #[inline]
 pub fn stack_reg() -> Reg {
     x_reg(2)
@@ -128,26 +137,31 @@ pub fn writable_spilltmp_reg2() -> Writable<Reg> {
     Writable::from_reg(spilltmp_reg2())
 }
 
+/// Get a reference to the integer register with the given encoding.
 #[inline]
 pub const fn x_reg(enc: usize) -> Reg {
     let p_reg = PReg::new(enc, RegClass::Int);
     let v_reg = VReg::new(p_reg.index(), p_reg.class());
     Reg::from_virtual_reg(v_reg)
 }
+/// Get the physical integer register with the given encoding.
 pub const fn px_reg(enc: usize) -> PReg {
     PReg::new(enc, RegClass::Int)
 }
 
+/// Get a reference to the float register with the given encoding.
 #[inline]
 pub fn f_reg(enc: usize) -> Reg {
     let p_reg = PReg::new(enc, RegClass::Float);
     let v_reg = VReg::new(p_reg.index(), p_reg.class());
     Reg::from(v_reg)
 }
+/// Get the physical float register with the given encoding.
 pub const fn pf_reg(enc: usize) -> PReg {
     PReg::new(enc, RegClass::Float)
 }
 
+/// Get the physical vector register with the given encoding.
 pub const fn pv_reg(enc: usize) -> PReg {
     PReg::new(enc, RegClass::Vector)
 }
@@ -158,14 +158,19 @@ impl fmt::Display for VecMaskMode {
 /// vtype provides the default type used to interpret the contents of the vector register file.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct VType {
+    /// The selected element width.
     pub sew: VecElementWidth,
+    /// The vector register group multiplier.
     pub lmul: VecLmul,
+    /// The tail elements policy.
     pub tail_mode: VecTailMode,
+    /// The masked-off elements policy.
     pub mask_mode: VecMaskMode,
 }
 
 impl VType {
     // https://github.com/riscv/riscv-v-spec/blob/master/vtype-format.adoc
+    /// Encode the vector type as the immediate of `vsetivli`.
     pub fn encode(&self) -> u32 {
         let mut bits = 0;
         bits |= self.lmul.encode();
@@ -193,11 +198,14 @@ impl fmt::Display for VType {
 /// used by our instruction emission code to ensure that the vector unit is in the correct state.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct VState {
+    /// The application vector length.
     pub avl: VecAvl,
+    /// The vector type.
     pub vtype: VType,
 }
 
 impl VState {
+    /// Create the vector state needed to operate on values of type `ty`.
     pub fn from_type(ty: Type) -> Self {
         VState {
             avl: VecAvl::_static(ty.lane_count()),
@@ -1109,6 +1117,7 @@ impl VecAMode {
     }
 }
 
+/// Information about the register overlap constraints of a vector instruction.
 pub trait VecInstOverlapInfo {
     /// § 5.2 Vector Operands states:
     ///
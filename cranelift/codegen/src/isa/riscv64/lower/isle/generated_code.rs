@@ -3,6 +3,7 @@
 // depends on an environment variable; and also because of this, we can't do the `#[path = "..."]
 // mod generated_code;` trick either.
 #![expect(
+    missing_docs,
     dead_code,
     unreachable_patterns,
     unused_imports,
@@ -19,9 +19,9 @@ use cranelift_control::ControlPlane;
 use std::string::String;
 use target_lexicon::{Architecture, Triple};
 mod abi;
-pub(crate) mod inst;
+pub mod inst;
 mod lower;
-mod settings;
+pub mod settings;
 #[cfg(feature = "unwind")]
 use crate::isa::unwind::systemv;
 
@@ -68,8 +68,8 @@ pub use crate::machinst::buffer::{
 };
 pub use crate::machinst::{
     CallInfo, CompiledCode, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit,
-    MachInstEmitState, MachLabel, RealReg, Reg, RelocDistance, TextSectionBuilder, VCodeConstant,
-    VCodeConstantData, VCodeConstants, VCodeInst, Writable,
+    MachInstEmitState, MachLabel, RealReg, Reg, RelocDistance, TextSectionBuilder, TryCallInfo,
+    VCodeConstant, VCodeConstantData, VCodeConstants, VCodeInst, Writable,
 };
 
 mod alias_analysis;
@@ -80,12 +80,14 @@ mod egraph;
 mod inst_predicates;
 mod isle_prelude;
 mod legalizer;
+mod loop_opts;
 mod nan_canonicalization;
 mod opts;
 mod ranges;
 mod remove_constant_phis;
 mod result;
 mod scoped_hash_map;
+mod slp;
 mod take_and_replace;
 mod unreachable_code;
 mod value_label;
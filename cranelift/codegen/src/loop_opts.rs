@@ -0,0 +1,621 @@
+//! Loop unrolling and induction-variable strength reduction.
+//!
+//! This pass runs right before the egraph pass, which cleans up after it:
+//! constants are propagated through fully unrolled loops, dead loop conditions
+//! of unrolled iterations are removed, and loop-invariant values computed by
+//! this pass are hoisted out of their loops.
+//!
+//! Two transformations are performed:
+//!
+//! * Strength reduction: a multiplication `imul iv, n` of a basic induction
+//!   variable `iv`, i.e. a loop header parameter which is incremented by a
+//!   constant on each iteration, by a loop-invariant `n` is replaced with a new
+//!   induction variable. That variable starts at `init * n` and is incremented
+//!   by `step * n` on each iteration, which turns the multiplication in the
+//!   loop into an addition. Multiplications by powers of two are left alone,
+//!   as they are turned into shifts by the egraph pass anyway.
+//!
+//! * Unrolling: loops consisting of a single block whose trip count is a known
+//!   constant are unrolled. Small loops are unrolled completely, and others by
+//!   a factor which divides the trip count. As the trip count is known, the
+//!   loop condition only needs to be tested by the last copy of the body in
+//!   each unrolled iteration.
+//!
+//! Unrolling increases code size, so it's only done at `opt_level=speed`.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::InstBuilder;
+use crate::ir::condcodes::{CondCode, IntCC};
+use crate::ir::{
+    Block, BlockArg, BlockCall, Function, Inst, InstructionData, Opcode, Value, ValueDef,
+};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::settings::OptLevel;
+use crate::timing;
+use crate::trace;
+use alloc::vec::Vec;
+use cranelift_entity::SecondaryMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+
+/// The maximum number of instructions in the body of a completely unrolled
+/// loop.
+const MAX_FULL_UNROLL_INSTS: u64 = 64;
+
+/// The maximum number of instructions in the body of a partially unrolled
+/// loop.
+const MAX_PARTIAL_UNROLL_INSTS: u64 = 64;
+
+/// The factors by which loops are partially unrolled, in order of preference.
+const UNROLL_FACTORS: [u64; 2] = [4, 2];
+
+/// Loops with a larger trip count than this are not considered counted loops.
+const MAX_TRIP_COUNT: u64 = 1 << 16;
+
+/// Perform loop optimizations on `func`.
+///
+/// Returns whether the function was changed, in which case the CFG, dominator
+/// tree and loop analysis are invalid.
+pub fn do_loop_opts(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    opt_level: OptLevel,
+) -> bool {
+    let _tt = timing::loop_opts();
+
+    let mut loop_blocks = SecondaryMap::<Loop, Vec<Block>>::new();
+    for block in func.layout.blocks() {
+        let mut lp = loop_analysis.innermost_loop(block);
+        while let Some(l) = lp {
+            loop_blocks[l].push(block);
+            lp = loop_analysis.loop_parent(l);
+        }
+    }
+
+    // Strength reduction doesn't change the CFG, so the loop analysis stays
+    // valid while it runs.
+    let mut changed = false;
+    for lp in loop_analysis.loops() {
+        if let Some(shape) = LoopShape::new(func, cfg, loop_analysis, lp) {
+            changed |= strength_reduce(func, loop_analysis, lp, &shape, &loop_blocks[lp]);
+        }
+    }
+    if changed {
+        func.dfg.resolve_all_aliases();
+    }
+
+    // Unrolling only changes the back edge of the unrolled loop, which is
+    // never part of the shape of another single-block loop.
+    if opt_level == OptLevel::Speed {
+        for lp in loop_analysis.loops() {
+            if let Some(shape) = LoopShape::new(func, cfg, loop_analysis, lp) {
+                changed |= unroll(func, &shape, &loop_blocks[lp]);
+            }
+        }
+    }
+
+    changed
+}
+
+/// The edges into the header of a loop which has a unique entry edge and a
+/// unique back edge, both of which are `jump` or `brif` instructions.
+struct LoopShape {
+    header: Block,
+    /// The branch entering the loop from outside of it.
+    entry: Inst,
+    /// The branch back to the header from within the loop.
+    latch: Inst,
+}
+
+impl LoopShape {
+    fn new(
+        func: &Function,
+        cfg: &ControlFlowGraph,
+        loop_analysis: &LoopAnalysis,
+        lp: Loop,
+    ) -> Option<Self> {
+        let header = loop_analysis.loop_header(lp);
+        let mut entry = None;
+        let mut latch = None;
+        for pred in cfg.pred_iter(header) {
+            if !matches!(
+                func.dfg.insts[pred.inst].opcode(),
+                Opcode::Jump | Opcode::Brif
+            ) || branch_calls(func, pred.inst, header).count() != 1
+            {
+                return None;
+            }
+            let edge = if loop_analysis.is_in_loop(pred.block, lp) {
+                &mut latch
+            } else {
+                &mut entry
+            };
+            if edge.replace(pred.inst).is_some() {
+                return None;
+            }
+        }
+        Some(LoopShape {
+            header,
+            entry: entry?,
+            latch: latch?,
+        })
+    }
+
+    /// The arguments passed to the header by `branch`.
+    fn args(&self, func: &Function, branch: Inst) -> SmallVec<[BlockArg; 8]> {
+        branch_calls(func, branch, self.header)
+            .next()
+            .unwrap()
+            .args(&func.dfg.value_lists)
+            .collect()
+    }
+
+    /// Append `arg` to the arguments passed to the header by `branch`.
+    fn append_arg(&self, func: &mut Function, branch: Inst, arg: Value) {
+        let dfg = &mut func.dfg;
+        for call in dfg.insts[branch]
+            .branch_destination_mut(&mut dfg.jump_tables, &mut dfg.exception_tables)
+        {
+            if call.block(&dfg.value_lists) == self.header {
+                call.append_argument(arg, &mut dfg.value_lists);
+            }
+        }
+    }
+}
+
+/// The calls to `block` made by the branch `inst`.
+fn branch_calls(func: &Function, inst: Inst, block: Block) -> impl Iterator<Item = BlockCall> + '_ {
+    func.dfg.insts[inst]
+        .branch_destination(&func.dfg.jump_tables, &func.dfg.exception_tables)
+        .iter()
+        .copied()
+        .filter(move |call| call.block(&func.dfg.value_lists) == block)
+}
+
+/// A basic induction variable: a loop header parameter which is incremented
+/// by a constant on each iteration.
+struct InductionVar {
+    /// The header parameter.
+    param: Value,
+    /// The initial value of the parameter, passed by the entry edge.
+    init: Value,
+    /// The incremented value of the parameter, passed by the back edge.
+    next: Value,
+    /// The `iconst` which is added to the parameter.
+    step: Value,
+}
+
+fn induction_vars(func: &Function, shape: &LoopShape) -> SmallVec<[InductionVar; 4]> {
+    let params = func.dfg.block_params(shape.header);
+    let inits = shape.args(func, shape.entry);
+    let nexts = shape.args(func, shape.latch);
+    let mut ivs = SmallVec::new();
+    for ((&param, init), next) in params.iter().zip(inits).zip(nexts) {
+        let ty = func.dfg.value_type(param);
+        if !ty.is_int() || ty.bits() > 64 {
+            continue;
+        }
+        let (Some(init), Some(next)) = (init.as_value(), next.as_value()) else {
+            continue;
+        };
+        let Some(inst) = func.dfg.value_def(next).inst() else {
+            continue;
+        };
+        let InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args,
+        } = func.dfg.insts[inst]
+        else {
+            continue;
+        };
+        let step = match args {
+            [a, step] | [step, a] if a == param => step,
+            _ => continue,
+        };
+        if iconst(func, step).is_some() {
+            ivs.push(InductionVar {
+                param,
+                init,
+                next,
+                step,
+            });
+        }
+    }
+    ivs
+}
+
+/// The immediate of `value` if it's defined by an `iconst`.
+fn iconst(func: &Function, value: Value) -> Option<u64> {
+    let inst = func.dfg.value_def(value).inst()?;
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => {
+            let bits = func.dfg.value_type(value).bits();
+            Some((imm.bits() as u64) & (u64::MAX >> (64 - bits)))
+        }
+        _ => None,
+    }
+}
+
+fn is_invariant(func: &Function, loop_analysis: &LoopAnalysis, lp: Loop, value: Value) -> bool {
+    let block = match func.dfg.value_def(value) {
+        ValueDef::Result(inst, _) => func.layout.inst_block(inst).unwrap(),
+        ValueDef::Param(block, _) => block,
+        ValueDef::Union(..) => return false,
+    };
+    !loop_analysis.is_in_loop(block, lp)
+}
+
+/// Replace multiplications of basic induction variables by loop-invariant
+/// values with new induction variables.
+fn strength_reduce(
+    func: &mut Function,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    shape: &LoopShape,
+    blocks: &[Block],
+) -> bool {
+    let ivs = induction_vars(func, shape);
+    if ivs.is_empty() {
+        return false;
+    }
+
+    let mut reduced = FxHashMap::<(Value, Value), Value>::default();
+    let mut pos = FuncCursor::new(func);
+    for &block in blocks {
+        pos.goto_top(block);
+        while let Some(inst) = pos.next_inst() {
+            let InstructionData::Binary {
+                opcode: Opcode::Imul,
+                args,
+            } = pos.func.dfg.insts[inst]
+            else {
+                continue;
+            };
+            let candidate = [(args[0], args[1]), (args[1], args[0])]
+                .into_iter()
+                .find_map(|(iv, factor)| {
+                    let iv = ivs.iter().find(|v| v.param == iv)?;
+                    // Multiplications by zero or by powers of two are
+                    // simplified by the egraph pass instead.
+                    let reducible = match iconst(pos.func, factor) {
+                        Some(c) => c != 0 && !c.is_power_of_two(),
+                        None => is_invariant(pos.func, loop_analysis, lp, factor),
+                    };
+                    reducible.then_some((iv, factor))
+                });
+            let Some((iv, factor)) = candidate else {
+                continue;
+            };
+
+            let replacement = match reduced.get(&(iv.param, factor)) {
+                Some(&v) => v,
+                None => {
+                    let v = add_induction_var(pos.func, shape, iv, factor);
+                    reduced.insert((iv.param, factor), v);
+                    v
+                }
+            };
+            trace!("strength-reducing {inst} to {replacement}");
+            let result = pos.func.dfg.first_result(inst);
+            pos.func.dfg.clear_results(inst);
+            pos.func.dfg.change_to_alias(result, replacement);
+            pos.remove_inst_and_step_back();
+        }
+    }
+    !reduced.is_empty()
+}
+
+/// Add a new induction variable to the loop holding `iv * factor`.
+fn add_induction_var(
+    func: &mut Function,
+    shape: &LoopShape,
+    iv: &InductionVar,
+    factor: Value,
+) -> Value {
+    let ty = func.dfg.value_type(iv.param);
+    let param = func.dfg.append_block_param(shape.header, ty);
+
+    // Constant factors may be defined within the loop, so rematerialize them
+    // at the entry edge. Other factors are defined outside of the loop, and
+    // thus dominate it.
+    let mut pos = FuncCursor::new(func).at_inst(shape.entry);
+    let factor = match iconst(pos.func, factor) {
+        Some(c) => pos.ins().iconst(ty, c as i64),
+        None => factor,
+    };
+    let init = pos.ins().imul(iv.init, factor);
+    shape.append_arg(pos.func, shape.entry, init);
+
+    // Both `factor` and `step` dominate the back edge, the latter because it's
+    // used to compute the next value of the induction variable.
+    pos.goto_inst(shape.latch);
+    let delta = pos.ins().imul(iv.step, factor);
+    let next = pos.ins().iadd(param, delta);
+    shape.append_arg(pos.func, shape.latch, next);
+
+    param
+}
+
+/// Compute the number of times the body of a single-block loop is executed,
+/// if it's a constant.
+fn trip_count(func: &Function, shape: &LoopShape) -> Option<u64> {
+    let InstructionData::Brif { arg, blocks, .. } = func.dfg.insts[shape.latch] else {
+        return None;
+    };
+    let continue_if = blocks[0].block(&func.dfg.value_lists) == shape.header;
+
+    // Look through the extension of a boolean to a wider integer type.
+    let mut inst = func.dfg.value_def(arg).inst()?;
+    if let InstructionData::Unary {
+        opcode: Opcode::Uextend,
+        arg,
+    } = func.dfg.insts[inst]
+    {
+        inst = func.dfg.value_def(arg).inst()?;
+    }
+    let InstructionData::IntCompare { cond, args, .. } = func.dfg.insts[inst] else {
+        return None;
+    };
+
+    let ivs = induction_vars(func, shape);
+    let (iv, compare_next, iv_is_lhs) = ivs.iter().find_map(|iv| {
+        let [lhs, rhs] = args;
+        if lhs == iv.param || lhs == iv.next {
+            Some((iv, lhs == iv.next, true))
+        } else if rhs == iv.param || rhs == iv.next {
+            Some((iv, rhs == iv.next, false))
+        } else {
+            None
+        }
+    })?;
+    let bound = iconst(func, if iv_is_lhs { args[1] } else { args[0] })?;
+    let bits = func.dfg.value_type(iv.param).bits();
+    let mask = u64::MAX >> (64 - bits);
+    let step = iconst(func, iv.step)?;
+    let init = iconst(func, iv.init)?;
+
+    // Normalize the exit condition to `x cond bound`, continuing the loop
+    // while it holds, where `x` is the compared value of the induction
+    // variable.
+    let cond = if continue_if { cond } else { cond.complement() };
+    let cond = if iv_is_lhs { cond } else { cond.swap_args() };
+    let first = if compare_next {
+        init.wrapping_add(step) & mask
+    } else {
+        init
+    };
+    let count = solve_trip_count(cond, first, step, bound, bits)?;
+    (count <= MAX_TRIP_COUNT).then_some(count)
+}
+
+/// Compute the number of times the body of a loop is executed, which continues
+/// while `first + n * step` compares `cond` to `bound` in its `n`th iteration,
+/// counting from zero, on `bits`-wide integers.
+///
+/// Returns `None` if the loop doesn't exit before the compared value wraps
+/// around.
+fn solve_trip_count(cond: IntCC, first: u64, step: u64, bound: u64, bits: u32) -> Option<u64> {
+    let shift = 64 - bits;
+    let signed = |x: u64| i128::from(((x << shift) as i64) >> shift);
+    let (min, max, x, bound) = if cond == cond.unsigned() {
+        (0, (1 << bits) - 1, i128::from(first), i128::from(bound))
+    } else {
+        let max = (1 << (bits - 1)) - 1;
+        (-max - 1, max, signed(first), signed(bound))
+    };
+    let step = signed(step);
+
+    // The number of iterations until the compared value reaches `exit` when
+    // counting up, or zero if it already has.
+    let count_up = |exit: i128| -> Option<i128> {
+        if x >= exit {
+            return Some(0);
+        }
+        if step <= 0 {
+            return None;
+        }
+        let n = (exit - x + step - 1) / step;
+        (x + n * step <= max).then_some(n)
+    };
+    // Likewise when counting down.
+    let count_down = |exit: i128| -> Option<i128> {
+        if x <= exit {
+            return Some(0);
+        }
+        if step >= 0 {
+            return None;
+        }
+        let n = (x - exit - step - 1) / -step;
+        (x + n * step >= min).then_some(n)
+    };
+
+    let n = match cond {
+        IntCC::Equal if x != bound => 0,
+        IntCC::Equal if step != 0 => 1,
+        IntCC::Equal => return None,
+        IntCC::NotEqual => {
+            let distance = bound - x;
+            if distance == 0 {
+                0
+            } else if step != 0 && distance % step == 0 && distance / step > 0 {
+                distance / step
+            } else {
+                return None;
+            }
+        }
+        IntCC::SignedLessThan | IntCC::UnsignedLessThan => count_up(bound)?,
+        IntCC::SignedLessThanOrEqual | IntCC::UnsignedLessThanOrEqual => count_up(bound + 1)?,
+        IntCC::SignedGreaterThan | IntCC::UnsignedGreaterThan => count_down(bound)?,
+        IntCC::SignedGreaterThanOrEqual | IntCC::UnsignedGreaterThanOrEqual => {
+            count_down(bound - 1)?
+        }
+    };
+    u64::try_from(n + 1).ok()
+}
+
+/// Unroll a single-block loop with a constant trip count.
+fn unroll(func: &mut Function, shape: &LoopShape, blocks: &[Block]) -> bool {
+    if blocks != [shape.header] {
+        return false;
+    }
+    let Some(trip_count) = trip_count(func, shape) else {
+        return false;
+    };
+    let header = shape.header;
+    let body: SmallVec<[Inst; 16]> = func
+        .layout
+        .block_insts(header)
+        .filter(|&inst| inst != shape.latch)
+        .collect();
+    let body_len = body.len() as u64;
+
+    let full = trip_count * body_len <= MAX_FULL_UNROLL_INSTS;
+    let copies = if full {
+        trip_count
+    } else {
+        match UNROLL_FACTORS
+            .into_iter()
+            .find(|f| trip_count % f == 0 && f * body_len <= MAX_PARTIAL_UNROLL_INSTS)
+        {
+            Some(factor) => factor,
+            None => return false,
+        }
+    };
+    trace!("unrolling loop {header} with trip count {trip_count} {copies} times");
+
+    // Insert copies of the body before the original one, each of which uses
+    // the values passed to the back edge by the previous one instead of the
+    // header parameters. The original body becomes the last copy.
+    let params: SmallVec<[Value; 8]> = func.dfg.block_params(header).into();
+    let mut current = params.clone();
+    let mut clones = FxHashSet::default();
+    let mut map = FxHashMap::default();
+    let before = body.first().copied().unwrap_or(shape.latch);
+    for _ in 1..copies {
+        map.clear();
+        map.extend(params.iter().copied().zip(current.iter().copied()));
+        for &inst in &body {
+            let clone = func.dfg.clone_inst(inst);
+            func.dfg
+                .map_inst_values(clone, |v| map.get(&v).copied().unwrap_or(v));
+            if let Some(entries) = func.dfg.user_stack_map_entries(inst) {
+                let entries: SmallVec<[_; 4]> = entries.into();
+                func.dfg.append_user_stack_map_entries(clone, entries);
+            }
+            func.layout.insert_inst(clone, before);
+            let srcloc = func.srclocs[inst];
+            if !srcloc.is_default() {
+                func.srclocs[clone] = srcloc;
+            }
+            for (&old, &new) in func
+                .dfg
+                .inst_results(inst)
+                .iter()
+                .zip(func.dfg.inst_results(clone))
+            {
+                map.insert(old, new);
+            }
+            clones.insert(clone);
+        }
+        current = shape
+            .args(func, shape.latch)
+            .iter()
+            .map(|arg| {
+                let v = arg.as_value().unwrap();
+                map.get(&v).copied().unwrap_or(v)
+            })
+            .collect();
+    }
+
+    // Everything else, including uses of the header parameters after the
+    // loop, now sees the parameters of the last iteration.
+    map.clear();
+    map.extend(params.iter().copied().zip(current.iter().copied()));
+    let mut pos = FuncCursor::new(func);
+    while let Some(_block) = pos.next_block() {
+        while let Some(inst) = pos.next_inst() {
+            if !clones.contains(&inst) {
+                pos.func
+                    .dfg
+                    .map_inst_values(inst, |v| map.get(&v).copied().unwrap_or(v));
+            }
+        }
+    }
+
+    // A completely unrolled loop always exits after the last copy.
+    if full {
+        let exit = func.dfg.insts[shape.latch]
+            .branch_destination(&func.dfg.jump_tables, &func.dfg.exception_tables)
+            .iter()
+            .copied()
+            .find(|call| call.block(&func.dfg.value_lists) != header)
+            .unwrap();
+        let exit_block = exit.block(&func.dfg.value_lists);
+        let args: SmallVec<[BlockArg; 8]> = exit.args(&func.dfg.value_lists).collect();
+        func.dfg.replace(shape.latch).jump(exit_block, &args);
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Count the iterations of a loop by simulating it on 8-bit integers, or
+    /// return `None` if the compared value wraps around before it exits.
+    fn simulate(cond: IntCC, first: u64, step: u64, bound: u64) -> Option<u64> {
+        let signed = cond != cond.unsigned();
+        let value = |x: u64| {
+            if signed {
+                i32::from(x as u8 as i8)
+            } else {
+                i32::from(x as u8)
+            }
+        };
+        let (x, step, bound) = (value(first), i32::from(step as u8 as i8), value(bound));
+        let range = if signed { -128..=127 } else { 0..=255 };
+        let mut x = x;
+        for count in 1.. {
+            let continues = match cond {
+                IntCC::Equal => x == bound,
+                IntCC::NotEqual => x != bound,
+                IntCC::SignedLessThan | IntCC::UnsignedLessThan => x < bound,
+                IntCC::SignedGreaterThanOrEqual | IntCC::UnsignedGreaterThanOrEqual => x >= bound,
+                IntCC::SignedGreaterThan | IntCC::UnsignedGreaterThan => x > bound,
+                IntCC::SignedLessThanOrEqual | IntCC::UnsignedLessThanOrEqual => x <= bound,
+            };
+            if !continues {
+                return Some(count);
+            }
+            x += step;
+            // A wrapped value can't equal the bound, as the step is nonzero.
+            if (!range.contains(&x) && cond != IntCC::Equal) || count > 256 {
+                return None;
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn solve_trip_count_matches_simulation() {
+        for cond in IntCC::all() {
+            for first in (0..256).step_by(7) {
+                for step in [0, 1, 2, 3, 5, 0xff, 0xfe, 0xfd] {
+                    for bound in (0..256).step_by(5) {
+                        assert_eq!(
+                            solve_trip_count(*cond, first, step, bound, 8),
+                            simulate(*cond, first, step, bound),
+                            "{cond} first={first} step={step} bound={bound}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
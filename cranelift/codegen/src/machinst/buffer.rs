@@ -528,6 +528,62 @@ impl<I: VCodeInst> MachBuffer<I> {
         // Post-invariant: as for `put1()`.
     }
 
+    /// The code emitted so far.
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data[..]
+    }
+
+    /// Mutable access to the code emitted so far.
+    ///
+    /// The caller is responsible for keeping any metadata (labels, fixups,
+    /// relocations, traps, etc.) referring to the modified code valid.
+    pub(crate) fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data[..]
+    }
+
+    /// Returns whether no metadata refers to the code at or after `offset`:
+    /// no labels are bound there, and no branches, label fixups,
+    /// relocations, traps, call sites, stack maps, or unwind info are
+    /// recorded for it.
+    ///
+    /// Such code can be removed again with
+    /// [`MachBuffer::truncate_unreferenced_tail`], e.g. to fuse instructions.
+    pub(crate) fn is_unreferenced_tail(&self, offset: CodeOffset) -> bool {
+        // A label bound at or after `offset` moves `labels_at_tail_off` there.
+        // Conservatively treat it moving past `offset` for any other reason as
+        // a reference too, so that truncating keeps it <= `cur_offset()`.
+        let labels = self.labels_at_tail_off > offset
+            || (self.labels_at_tail_off == offset && !self.labels_at_tail.is_empty());
+        let branches = self.latest_branches.last().is_some_and(|b| b.end > offset)
+            || self
+                .pending_fixup_records
+                .last()
+                .is_some_and(|f| f.offset >= offset);
+        let relocs = self.relocs.last().is_some_and(|r| r.offset >= offset);
+        let traps = self.traps.last().is_some_and(|t| t.offset >= offset);
+        let call_sites = self.call_sites.last().is_some_and(|c| c.ret_addr >= offset);
+        let stack_maps = self
+            .user_stack_maps
+            .last()
+            .is_some_and(|(o, _, _)| *o >= offset);
+        let unwind = self.unwind_info.last().is_some_and(|(o, _)| *o >= offset);
+        offset <= self.cur_offset()
+            && !self.open_patchable
+            && !(labels || branches || relocs || traps || call_sites || stack_maps || unwind)
+    }
+
+    /// Removes the code at and after `offset`, which must not be referenced
+    /// by any metadata (see [`MachBuffer::is_unreferenced_tail`]).
+    pub(crate) fn truncate_unreferenced_tail(&mut self, offset: CodeOffset) {
+        assert!(self.is_unreferenced_tail(offset));
+        debug_assert!(self.srclocs.last().is_none_or(|s| s.end <= offset));
+        debug_assert!(self.cur_srcloc.is_none_or(|(start, _)| start <= offset));
+        self.data.truncate(offset as usize);
+
+        // Post-invariant: `labels_at_tail_off` is still <= `cur_offset()`, as
+        // no labels are bound at or after `offset`.
+    }
+
     /// Align up to the given alignment.
     pub fn align_to(&mut self, align_to: CodeOffset) {
         trace!("MachBuffer: align to {}", align_to);
@@ -0,0 +1,765 @@
+//! Superword-level parallelism (SLP) vectorization.
+//!
+//! This pass combines isomorphic scalar computations within a block into
+//! vector computations. It starts from groups of four `i32` or `f32` stores to
+//! consecutive addresses, and follows the stored values upwards as long as the
+//! values of the four lanes are computed by the same operation. Such a tree of
+//! operations is replaced by `i32x4` or `f32x4` operations on vectors built
+//! from:
+//!
+//! * loads of four consecutive values,
+//! * a value which is the same in all lanes, which is splatted, and
+//! * constants, which are combined into a `vconst`.
+//!
+//! The vector code is inserted at the last store of the group, which moves the
+//! loads and stores of the group past other instructions. This is only done
+//! when it doesn't change the behavior of the function: the vectorized memory
+//! accesses must not be moved past calls or other memory barriers, past stores
+//! they may alias, or in the case of stores, past instructions which may trap.
+//!
+//! The lanes of a vectorized access must have the same trap code, if any.
+//! Accesses which may trap aren't moved past any other instruction which may
+//! trap or store, so that they still trap before the same side effects. A
+//! vector store which traps doesn't store any of its lanes, so stores which
+//! may trap are only vectorized if the first of them stores the highest lane.
+//! This assumes that, as for Wasm linear memories, an access which traps at
+//! some address would also trap at all higher addresses.
+//!
+//! The scalar instructions of a vectorized tree must not be used outside of the
+//! tree, as they are removed. The egraph pass running after this pass cleans up
+//! address computations which are no longer used.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::inst_predicates::inst_addr_offset_type;
+use crate::ir::immediates::Offset32;
+use crate::ir::{
+    Block, ConstantData, Function, Inst, InstBuilder, InstructionData, MemFlags, Opcode, StackSlot,
+    Type, Value, types,
+};
+use crate::timing;
+use crate::trace;
+use alloc::vec::Vec;
+use cranelift_entity::SecondaryMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+
+/// The number of lanes of the vectors produced by this pass.
+const LANES: usize = 4;
+
+/// The maximum depth of a vectorized tree of operations.
+const MAX_DEPTH: usize = 8;
+
+/// The maximum number of instructions past which loads and stores are moved.
+const MAX_WINDOW: usize = 128;
+
+/// Perform SLP vectorization on `func`.
+///
+/// Returns whether the function was changed.
+pub fn do_slp_vectorize(func: &mut Function) -> bool {
+    let _tt = timing::slp_vectorize();
+
+    let mut changed = false;
+    let mut uses = use_counts(func);
+    let blocks: SmallVec<[Block; 16]> = func.layout.blocks().collect();
+    for block in blocks {
+        // Vectorizing a group changes the instructions of the block, so look
+        // for groups again after each one.
+        let mut failed = FxHashSet::default();
+        loop {
+            let mut vectorized = false;
+            for group in store_groups(func, block) {
+                if failed.contains(&group[0]) {
+                    continue;
+                }
+                if vectorize_group(func, block, &mut uses, group) {
+                    vectorized = true;
+                    break;
+                }
+                failed.insert(group[0]);
+            }
+            if !vectorized {
+                break;
+            }
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// The number of uses of each value.
+fn use_counts(func: &Function) -> SecondaryMap<Value, u32> {
+    let mut uses = SecondaryMap::new();
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            for arg in func.dfg.inst_values(inst) {
+                uses[arg] += 1;
+            }
+        }
+    }
+    uses
+}
+
+/// A load or store at a constant offset from a base address.
+#[derive(Clone, Copy)]
+struct Access {
+    base: Value,
+    offset: i64,
+    size: u32,
+    flags: MemFlags,
+    /// The stack slot containing the accessed memory, if the base address is
+    /// the address of a stack slot.
+    slot: Option<StackSlot>,
+}
+
+impl Access {
+    fn new(func: &Function, inst: Inst) -> Option<Self> {
+        let flags = func.dfg.insts[inst].memflags()?;
+        let (addr, offset, ty) = inst_addr_offset_type(func, inst)?;
+        let size = match func.dfg.insts[inst].opcode() {
+            Opcode::Uload8 | Opcode::Sload8 | Opcode::Istore8 => 1,
+            Opcode::Uload16 | Opcode::Sload16 | Opcode::Istore16 => 2,
+            Opcode::Uload32 | Opcode::Sload32 | Opcode::Istore32 => 4,
+            Opcode::Uload8x8
+            | Opcode::Sload8x8
+            | Opcode::Uload16x4
+            | Opcode::Sload16x4
+            | Opcode::Uload32x2
+            | Opcode::Sload32x2 => 8,
+            _ => ty.bytes(),
+        };
+        let (base, base_offset) = split_address(func, addr);
+        let slot = match func
+            .dfg
+            .value_def(base)
+            .inst()
+            .map(|inst| &func.dfg.insts[inst])
+        {
+            Some(&InstructionData::StackLoad {
+                opcode: Opcode::StackAddr,
+                stack_slot,
+                ..
+            }) => Some(stack_slot),
+            _ => None,
+        };
+        Some(Access {
+            base,
+            offset: base_offset.wrapping_add(i64::from(offset)),
+            size,
+            flags,
+            slot,
+        })
+    }
+
+    /// Whether this access may access the same memory as `other`.
+    fn may_alias(&self, other: &Access) -> bool {
+        if self.flags.readonly() || other.flags.readonly() {
+            return false;
+        }
+        if let (Some(a), Some(b)) = (self.flags.alias_region(), other.flags.alias_region()) {
+            if a != b {
+                return false;
+            }
+        }
+        if let (Some(a), Some(b)) = (self.slot, other.slot) {
+            if a != b {
+                return false;
+            }
+        }
+        if self.base != other.base {
+            return true;
+        }
+        self.offset < other.offset.wrapping_add(i64::from(other.size))
+            && other.offset < self.offset.wrapping_add(i64::from(self.size))
+    }
+
+    /// Whether this access may trap.
+    fn can_trap(&self) -> bool {
+        self.flags.trap_code().is_some()
+    }
+}
+
+/// Split `addr` into a base address and a constant offset.
+fn split_address(func: &Function, addr: Value) -> (Value, i64) {
+    if let Some(inst) = func.dfg.value_def(addr).inst() {
+        if let InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args,
+        } = func.dfg.insts[inst]
+        {
+            if let Some(c) = iconst(func, args[1]) {
+                return (args[0], c);
+            }
+            if let Some(c) = iconst(func, args[0]) {
+                return (args[1], c);
+            }
+        }
+    }
+    (addr, 0)
+}
+
+/// The sign-extended immediate of `value` if it's defined by an `iconst`.
+fn iconst(func: &Function, value: Value) -> Option<i64> {
+    let inst = func.dfg.value_def(value).inst()?;
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => {
+            let shift = 64 - func.dfg.value_type(value).bits();
+            Some((imm.bits() << shift) >> shift)
+        }
+        _ => None,
+    }
+}
+
+/// The type of the values stored or loaded by `inst`, if it's a plain `store`
+/// or `load` of an `i32` or `f32` which can be vectorized.
+///
+/// Accesses which may trap can be vectorized, as long as all lanes have the
+/// same flags and thus the same trap code.
+fn lane_type(func: &Function, inst: Inst) -> Option<Type> {
+    let (flags, ty) = match func.dfg.insts[inst] {
+        InstructionData::Store {
+            opcode: Opcode::Store,
+            args,
+            flags,
+            ..
+        } => (flags, func.dfg.value_type(args[0])),
+        InstructionData::Load {
+            opcode: Opcode::Load,
+            flags,
+            ..
+        } => (flags, func.dfg.value_type(func.dfg.first_result(inst))),
+        _ => return None,
+    };
+    (!flags.checked() && (ty == types::I32 || ty == types::F32)).then_some(ty)
+}
+
+/// Find groups of stores to `LANES` consecutive addresses in `block`, sorted
+/// by address.
+fn store_groups(func: &Function, block: Block) -> Vec<[Inst; LANES]> {
+    let mut stores = FxHashMap::<(Value, MemFlags, Type), Vec<(i64, Inst)>>::default();
+    for inst in func.layout.block_insts(block) {
+        let Some(ty) = lane_type(func, inst) else {
+            continue;
+        };
+        if func.dfg.insts[inst].opcode() != Opcode::Store {
+            continue;
+        }
+        let access = Access::new(func, inst).unwrap();
+        stores
+            .entry((access.base, access.flags, ty))
+            .or_default()
+            .push((access.offset, inst));
+    }
+
+    let mut groups = Vec::new();
+    for (_, mut candidates) in stores {
+        candidates.sort_by_key(|&(offset, _)| offset);
+        let mut i = 0;
+        while i + LANES <= candidates.len() {
+            let window = &candidates[i..i + LANES];
+            let consecutive = window
+                .iter()
+                .enumerate()
+                .all(|(lane, &(offset, _))| offset == window[0].0 + 4 * lane as i64);
+            if consecutive {
+                groups.push(core::array::from_fn(|lane| window[lane].1));
+                i += LANES;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    // Visit the groups in program order, to make the result deterministic.
+    groups.sort_by(|a, b| func.layout.pp_cmp(a[0], b[0]));
+    groups
+}
+
+/// A vector operation computing the values of `LANES` scalar values.
+enum Node {
+    /// A load of consecutive values.
+    Load {
+        insts: [Inst; LANES],
+        base: Value,
+        offset: i64,
+        flags: MemFlags,
+    },
+    /// A value which is the same in all lanes.
+    Splat(Value),
+    /// Constants.
+    Const(ConstantData),
+    Unary {
+        opcode: Opcode,
+        insts: [Inst; LANES],
+        arg: usize,
+    },
+    Binary {
+        opcode: Opcode,
+        insts: [Inst; LANES],
+        args: [usize; 2],
+    },
+    /// A shift of all lanes by the same amount.
+    Shift {
+        opcode: Opcode,
+        insts: [Inst; LANES],
+        arg: usize,
+        amount: Value,
+    },
+}
+
+/// A tree of vector operations, built bottom-up from the values stored by a
+/// group of stores.
+struct Tree<'a> {
+    func: &'a Function,
+    block: Block,
+    uses: &'a SecondaryMap<Value, u32>,
+    nodes: Vec<Node>,
+}
+
+impl<'a> Tree<'a> {
+    /// Add the node computing `lanes` and its operands to the tree, returning
+    /// its index.
+    fn build(&mut self, lanes: [Value; LANES], depth: usize) -> Option<usize> {
+        let func = self.func;
+        let ty = func.dfg.value_type(lanes[0]);
+        if lanes.iter().any(|&v| func.dfg.value_type(v) != ty) {
+            return None;
+        }
+        if lanes.iter().all(|&v| v == lanes[0]) {
+            return Some(self.push(Node::Splat(lanes[0])));
+        }
+        if let Some(data) = constants(func, lanes) {
+            return Some(self.push(Node::Const(data)));
+        }
+        if depth == MAX_DEPTH {
+            return None;
+        }
+
+        // All other lanes must be the only result of an instruction in the
+        // block whose only use is in the tree.
+        let mut insts = SmallVec::<[Inst; LANES]>::new();
+        for &v in &lanes {
+            let inst = func.dfg.value_def(v).inst()?;
+            if func.layout.inst_block(inst) != Some(self.block)
+                || func.dfg.inst_results(inst).len() != 1
+                || self.uses[v] != 1
+            {
+                return None;
+            }
+            insts.push(inst);
+        }
+        let insts: [Inst; LANES] = insts.into_inner().unwrap();
+
+        let opcode = func.dfg.insts[insts[0]].opcode();
+        if insts
+            .iter()
+            .any(|&inst| func.dfg.insts[inst].opcode() != opcode)
+        {
+            return None;
+        }
+
+        if opcode == Opcode::Load {
+            return self.build_load(insts);
+        }
+        if !is_vectorizable_op(opcode, ty) {
+            return None;
+        }
+        let args = insts.map(|inst| func.dfg.inst_args(inst));
+        let node = match opcode {
+            Opcode::Ishl | Opcode::Ushr | Opcode::Sshr => {
+                let amount = args[0][1];
+                if args.iter().any(|args| args[1] != amount) {
+                    return None;
+                }
+                let arg = self.build(args.map(|args| args[0]), depth + 1)?;
+                Node::Shift {
+                    opcode,
+                    insts,
+                    arg,
+                    amount,
+                }
+            }
+            _ if args[0].len() == 1 => {
+                let arg = self.build(args.map(|args| args[0]), depth + 1)?;
+                Node::Unary { opcode, insts, arg }
+            }
+            _ => {
+                let lhs = self.build(args.map(|args| args[0]), depth + 1)?;
+                let rhs = self.build(args.map(|args| args[1]), depth + 1)?;
+                Node::Binary {
+                    opcode,
+                    insts,
+                    args: [lhs, rhs],
+                }
+            }
+        };
+        Some(self.push(node))
+    }
+
+    fn build_load(&mut self, insts: [Inst; LANES]) -> Option<usize> {
+        let func = self.func;
+        if insts.iter().any(|&inst| lane_type(func, inst).is_none()) {
+            return None;
+        }
+        let first = Access::new(func, insts[0]).unwrap();
+        if i32::try_from(first.offset).is_err() {
+            return None;
+        }
+        for (lane, &inst) in insts.iter().enumerate() {
+            let access = Access::new(func, inst).unwrap();
+            if access.base != first.base
+                || access.flags != first.flags
+                || access.offset != first.offset + 4 * lane as i64
+            {
+                return None;
+            }
+        }
+        Some(self.push(Node::Load {
+            insts,
+            base: first.base,
+            offset: first.offset,
+            flags: first.flags,
+        }))
+    }
+
+    fn push(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// The loads of the tree.
+    fn loads(&self) -> impl Iterator<Item = Inst> + '_ {
+        self.nodes
+            .iter()
+            .flat_map(|node| match node {
+                Node::Load { insts, .. } => &insts[..],
+                _ => &[],
+            })
+            .copied()
+    }
+
+    /// The scalar instructions replaced by the tree.
+    fn scalar_insts(&self) -> impl Iterator<Item = Inst> + '_ {
+        self.nodes
+            .iter()
+            .flat_map(|node| match node {
+                Node::Load { insts, .. }
+                | Node::Unary { insts, .. }
+                | Node::Binary { insts, .. }
+                | Node::Shift { insts, .. } => &insts[..],
+                Node::Splat(_) | Node::Const(_) => &[],
+            })
+            .copied()
+    }
+}
+
+/// The vector constant holding `lanes`, if they are all constants.
+fn constants(func: &Function, lanes: [Value; LANES]) -> Option<ConstantData> {
+    let mut bytes = Vec::with_capacity(16);
+    for v in lanes {
+        let inst = func.dfg.value_def(v).inst()?;
+        let bits = match func.dfg.insts[inst] {
+            InstructionData::UnaryImm {
+                opcode: Opcode::Iconst,
+                imm,
+            } => imm.bits() as u32,
+            InstructionData::UnaryIeee32 {
+                opcode: Opcode::F32const,
+                imm,
+            } => imm.bits(),
+            _ => return None,
+        };
+        bytes.extend_from_slice(&bits.to_le_bytes());
+    }
+    Some(bytes.into())
+}
+
+/// Whether the scalar operation `opcode` on values of type `ty` can be
+/// vectorized.
+fn is_vectorizable_op(opcode: Opcode, ty: Type) -> bool {
+    match ty {
+        types::I32 => matches!(
+            opcode,
+            Opcode::Iadd
+                | Opcode::Isub
+                | Opcode::Imul
+                | Opcode::Ineg
+                | Opcode::Band
+                | Opcode::Bor
+                | Opcode::Bxor
+                | Opcode::Bnot
+                | Opcode::Ishl
+                | Opcode::Ushr
+                | Opcode::Sshr
+        ),
+        types::F32 => matches!(
+            opcode,
+            Opcode::Fadd
+                | Opcode::Fsub
+                | Opcode::Fmul
+                | Opcode::Fdiv
+                | Opcode::Fmin
+                | Opcode::Fmax
+                | Opcode::Fneg
+                | Opcode::Fabs
+                | Opcode::Sqrt
+        ),
+        _ => false,
+    }
+}
+
+/// The effect of an instruction on memory, as far as moving loads and stores
+/// past it is concerned.
+enum Effect {
+    None,
+    /// An instruction which may trap, but doesn't access memory.
+    Trap,
+    Load(Access),
+    Store(Access),
+    /// An instruction with unknown effects.
+    Barrier,
+}
+
+impl Effect {
+    fn of(func: &Function, inst: Inst) -> Self {
+        let opcode = func.dfg.insts[inst].opcode();
+        let is_plain_access = matches!(
+            func.dfg.insts[inst],
+            InstructionData::Load { .. }
+                | InstructionData::LoadNoOffset { .. }
+                | InstructionData::Store { .. }
+                | InstructionData::StoreNoOffset { .. }
+        );
+        if opcode.is_call() || opcode.other_side_effects() {
+            Effect::Barrier
+        } else if opcode.can_load() || opcode.can_store() {
+            match Access::new(func, inst) {
+                Some(access) if is_plain_access && opcode.can_store() => Effect::Store(access),
+                Some(access) if is_plain_access => Effect::Load(access),
+                _ => Effect::Barrier,
+            }
+        } else if opcode.can_trap() {
+            Effect::Trap
+        } else {
+            Effect::None
+        }
+    }
+}
+
+/// Check whether the loads of `tree` and the `stores` can all be moved to the
+/// last of the stores.
+fn can_move_accesses(func: &Function, block: Block, tree: &Tree, stores: &[Inst; LANES]) -> bool {
+    let positions: FxHashMap<Inst, usize> = func
+        .layout
+        .block_insts(block)
+        .enumerate()
+        .map(|(i, inst)| (inst, i))
+        .collect();
+    let moved_loads: SmallVec<[(usize, Access); 8]> = tree
+        .loads()
+        .map(|inst| (positions[&inst], Access::new(func, inst).unwrap()))
+        .collect();
+    let moved_stores: SmallVec<[(usize, Access); LANES]> = stores
+        .iter()
+        .map(|&inst| (positions[&inst], Access::new(func, inst).unwrap()))
+        .collect();
+    let end = moved_stores.iter().map(|&(pos, _)| pos).max().unwrap();
+    let start = moved_loads
+        .iter()
+        .chain(&moved_stores)
+        .map(|&(pos, _)| pos)
+        .min()
+        .unwrap();
+    if end - start > MAX_WINDOW {
+        return false;
+    }
+
+    // Loads of the tree end up before the stores, so they must not depend on
+    // any of the stores which preceded them, and if they may trap, they must
+    // not trap before these stores.
+    for &(load_pos, load) in &moved_loads {
+        for &(store_pos, store) in &moved_stores {
+            if store_pos < load_pos && (load.can_trap() || store.may_alias(&load)) {
+                return false;
+            }
+        }
+    }
+
+    // The vector store traps whenever any of the scalar stores would, without
+    // storing any lane. The scalar stores behave the same only if the first of
+    // them stores the highest lane, see the module documentation.
+    if moved_stores[0].1.can_trap() {
+        let first = moved_stores.iter().map(|&(pos, _)| pos).min().unwrap();
+        if moved_stores[LANES - 1].0 != first {
+            return false;
+        }
+    }
+
+    let moved: FxHashSet<Inst> = tree.loads().chain(stores.iter().copied()).collect();
+    for (pos, inst) in func.layout.block_insts(block).enumerate() {
+        if pos <= start || pos >= end || moved.contains(&inst) {
+            continue;
+        }
+        let effect = Effect::of(func, inst);
+        for &(_, load) in moved_loads.iter().filter(|&&(p, _)| p < pos) {
+            match &effect {
+                Effect::None => {}
+                Effect::Barrier => return false,
+                Effect::Trap => {
+                    if load.can_trap() {
+                        return false;
+                    }
+                }
+                Effect::Load(access) => {
+                    if load.can_trap() && access.can_trap() {
+                        return false;
+                    }
+                }
+                Effect::Store(store) => {
+                    if load.can_trap() || store.may_alias(&load) {
+                        return false;
+                    }
+                }
+            }
+        }
+        for &(_, store) in moved_stores.iter().filter(|&&(p, _)| p < pos) {
+            match &effect {
+                Effect::None => {}
+                Effect::Trap | Effect::Barrier => return false,
+                Effect::Load(access) => {
+                    if access.can_trap() || access.may_alias(&store) {
+                        return false;
+                    }
+                }
+                Effect::Store(access) => {
+                    if store.can_trap() || access.can_trap() || access.may_alias(&store) {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+    true
+}
+
+/// The flags of a vector access combining scalar accesses with `flags`.
+fn vector_flags(flags: MemFlags) -> MemFlags {
+    // The scalar accesses are only known to be aligned to the size of a lane,
+    // so the vector access isn't marked as aligned.
+    let mut vector = MemFlags::new()
+        .with_trap_code(flags.trap_code())
+        .with_alias_region(flags.alias_region());
+    if let Some(endianness) = flags.explicit_endianness() {
+        vector.set_endianness(endianness);
+    }
+    if flags.readonly() {
+        vector.set_readonly();
+    }
+    if flags.can_move() {
+        vector.set_can_move();
+    }
+    vector
+}
+
+/// Try to vectorize the group of `stores` and the computation of the stored
+/// values.
+///
+/// The number of `uses` of each value is updated when the group is vectorized.
+fn vectorize_group(
+    func: &mut Function,
+    block: Block,
+    uses: &mut SecondaryMap<Value, u32>,
+    stores: [Inst; LANES],
+) -> bool {
+    let first = Access::new(func, stores[0]).unwrap();
+    let Ok(offset) = i32::try_from(first.offset) else {
+        return false;
+    };
+    let values = stores.map(|inst| func.dfg.inst_args(inst)[0]);
+    let mut tree = Tree {
+        func,
+        block,
+        uses,
+        nodes: Vec::new(),
+    };
+    let Some(root) = tree.build(values, 0) else {
+        return false;
+    };
+    if !can_move_accesses(func, block, &tree, &stores) {
+        return false;
+    }
+    let removed: SmallVec<[Inst; 16]> = tree.scalar_insts().chain(stores).collect();
+    let nodes = tree.nodes;
+
+    let last = *stores
+        .iter()
+        .max_by(|&&a, &&b| func.layout.pp_cmp(a, b))
+        .unwrap();
+    trace!("vectorizing stores {stores:?} at {last}");
+    let ty = func.dfg.value_type(values[0]).by(LANES as u32).unwrap();
+
+    let srcloc = func.srcloc(last);
+    let mut pos = FuncCursor::new(func).at_inst(last).with_srcloc(srcloc);
+    let mut results: SmallVec<[Value; 16]> = SmallVec::new();
+    let mut inserted: SmallVec<[Inst; 16]> = SmallVec::new();
+    // Operands are always added to the tree before their users.
+    for node in nodes {
+        let result = match node {
+            Node::Load {
+                base,
+                offset,
+                flags,
+                ..
+            } => {
+                let offset = Offset32::new(i32::try_from(offset).unwrap());
+                pos.ins().load(ty, vector_flags(flags), base, offset)
+            }
+            Node::Splat(value) => pos.ins().splat(ty, value),
+            Node::Const(data) => {
+                let constant = pos.func.dfg.constants.insert(data);
+                pos.ins().vconst(ty, constant)
+            }
+            Node::Unary { opcode, arg, .. } => {
+                let (inst, dfg) = pos.ins().Unary(opcode, ty, results[arg]);
+                dfg.first_result(inst)
+            }
+            Node::Binary { opcode, args, .. } => {
+                let (inst, dfg) = pos
+                    .ins()
+                    .Binary(opcode, ty, results[args[0]], results[args[1]]);
+                dfg.first_result(inst)
+            }
+            Node::Shift {
+                opcode,
+                arg,
+                amount,
+                ..
+            } => {
+                let (inst, dfg) = pos.ins().Binary(opcode, ty, results[arg], amount);
+                dfg.first_result(inst)
+            }
+        };
+        results.push(result);
+        inserted.push(pos.func.dfg.value_def(result).unwrap_inst());
+    }
+    inserted.push(pos.ins().store(
+        vector_flags(first.flags),
+        results[root],
+        first.base,
+        Offset32::new(offset),
+    ));
+
+    for inst in inserted {
+        for arg in pos.func.dfg.inst_values(inst) {
+            uses[arg] += 1;
+        }
+    }
+    for inst in removed {
+        for arg in pos.func.dfg.inst_values(inst) {
+            uses[arg] -= 1;
+        }
+        pos.func.layout.remove_inst(inst);
+    }
+    true
+}
@@ -62,6 +62,8 @@ define_passes! {
     egraph: "Egraph based optimizations",
     gvn: "Global value numbering",
     licm: "Loop invariant code motion",
+    loop_opts: "Loop unrolling and strength reduction",
+    slp_vectorize: "SLP vectorization",
     unreachable_code: "Remove unreachable blocks",
     remove_constant_phis: "Remove constant phi-nodes",
 
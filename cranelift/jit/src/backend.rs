@@ -3,6 +3,7 @@
 use crate::{
     compiled_blob::CompiledBlob,
     memory::{BranchProtection, JITMemoryProvider, SystemMemoryProvider},
+    tls::{self, TlsObject},
 };
 use cranelift_codegen::binemit::Reloc;
 use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
@@ -14,13 +15,16 @@ use cranelift_module::{
     DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
     ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
+#[cfg(feature = "debug-info")]
+use cranelift_module::{DwarfBuilder, FunctionDebugInfo};
 use log::info;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::io::Write;
 use std::ptr;
-use target_lexicon::PointerWidth;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use target_lexicon::{Architecture, PointerWidth};
 
 const WRITABLE_DATA_ALIGNMENT: u64 = 0x8;
 const READONLY_DATA_ALIGNMENT: u64 = 0x1;
@@ -32,6 +36,7 @@ pub struct JITBuilder {
     lookup_symbols: Vec<Box<dyn Fn(&str) -> Option<*const u8> + Send>>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     memory: Option<Box<dyn JITMemoryProvider>>,
+    hotswap_enabled: bool,
 }
 
 impl JITBuilder {
@@ -49,6 +54,11 @@ impl JITBuilder {
 
     /// Create a new `JITBuilder` with the given flags.
     ///
+    /// On x86-64 and AArch64 Linux, `tls_model` defaults to `elf_gd` rather than `none`, as
+    /// `JITModule` supports thread-local data objects only with this TLS model. It only affects
+    /// the code of functions accessing thread-local data objects, which fail to compile with
+    /// `none`. Passing `tls_model` in `flags` overrides this default.
+    ///
     /// The `libcall_names` function provides a way to translate `cranelift_codegen`'s `ir::LibCall`
     /// enum to symbols. LibCalls are inserted in the IR as part of the legalization for certain
     /// floating point instructions, and for stack probes. If you don't know what to use for this
@@ -58,6 +68,12 @@ impl JITBuilder {
         libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     ) -> ModuleResult<Self> {
         let mut flag_builder = settings::builder();
+        if cfg!(all(
+            target_os = "linux",
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        )) {
+            flag_builder.set("tls_model", "elf_gd").unwrap();
+        }
         for (name, value) in flags {
             flag_builder.set(name, value)?;
         }
@@ -80,6 +96,9 @@ impl JITBuilder {
     /// To create a `JITBuilder` for native use, use the `new` or `with_flags`
     /// constructors instead.
     ///
+    /// Thread-local data objects are only supported with `tls_model` set to `elf_gd` on x86-64
+    /// and AArch64 Linux.
+    ///
     /// The `libcall_names` function provides a way to translate `cranelift_codegen`'s `ir::LibCall`
     /// enum to symbols. LibCalls are inserted in the IR as part of the legalization for certain
     /// floating point instructions, and for stack probes. If you don't know what to use for this
@@ -96,6 +115,7 @@ impl JITBuilder {
             lookup_symbols,
             libcall_names,
             memory: None,
+            hotswap_enabled: false,
         }
     }
 
@@ -147,6 +167,31 @@ impl JITBuilder {
         self
     }
 
+    /// Enable or disable hot-swapping of function definitions.
+    ///
+    /// In hot-swap mode, all references to functions defined in the module go
+    /// through a PLT stub which jumps to the current definition of the function
+    /// through a GOT entry. This allows redefining functions after they have
+    /// been finalized, at the cost of an indirect jump on each call.
+    ///
+    /// Hot-swapping is supported on x86-64, AArch64 and RISC-V 64. Enabling it
+    /// on any other architecture returns an error.
+    pub fn hotswap(&mut self, enabled: bool) -> ModuleResult<&mut Self> {
+        let arch = self.isa.triple().architecture;
+        if enabled
+            && !matches!(
+                arch,
+                Architecture::X86_64 | Architecture::Aarch64(_) | Architecture::Riscv64(_)
+            )
+        {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "hotswap is not supported on {arch}"
+            )));
+        }
+        self.hotswap_enabled = enabled;
+        Ok(self)
+    }
+
     /// Set the memory provider for the module.
     ///
     /// If unset, defaults to [`SystemMemoryProvider`].
@@ -174,11 +219,26 @@ pub struct JITModule {
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     memory: Box<dyn JITMemoryProvider>,
     declarations: ModuleDeclarations,
+    hotswap_enabled: bool,
+    function_got_entries: SecondaryMap<FuncId, Option<SendWrapper<*const AtomicPtr<u8>>>>,
+    function_plt_entries: SecondaryMap<FuncId, Option<SendWrapper<*const u8>>>,
     compiled_functions: SecondaryMap<FuncId, Option<CompiledBlob>>,
     compiled_data_objects: SecondaryMap<DataId, Option<CompiledBlob>>,
     code_ranges: Vec<(usize, usize, FuncId)>,
     functions_to_finalize: Vec<FuncId>,
     data_objects_to_finalize: Vec<DataId>,
+    /// Previous definitions of redefined functions which are still in use
+    /// until the new definitions are finalized.
+    functions_to_retire: Vec<CompiledBlob>,
+    /// Previous definitions of redefined functions which are no longer
+    /// referenced by the module.
+    retired_functions: Vec<CompiledBlob>,
+    /// Debug info of functions which are not yet finalized.
+    #[cfg(feature = "debug-info")]
+    pending_debug_info: HashMap<FuncId, DwarfBuilder>,
+    /// Debug info of finalized functions registered with debuggers.
+    #[cfg(feature = "debug-info")]
+    debug_registrations: HashMap<FuncId, wasmtime_jit_debug::gdb_jit_int::GdbJitImageRegistration>,
 }
 
 impl JITModule {
@@ -191,6 +251,13 @@ impl JITModule {
     /// from that module are currently executing and none of the `fn` pointers
     /// are called afterwards.
     pub unsafe fn free_memory(mut self) {
+        for (data_id, compiled) in self.compiled_data_objects.iter() {
+            if let Some(compiled) = compiled {
+                if self.declarations.get_data_decl(data_id).tls {
+                    (*compiled.ptr.cast::<TlsObject>()).free();
+                }
+            }
+        }
         self.memory.free_memory();
     }
 
@@ -216,6 +283,9 @@ impl JITModule {
             ModuleRelocTarget::User { .. } => {
                 let (name, linkage) = if ModuleDeclarations::is_function(name) {
                     let func_id = FuncId::from_name(name);
+                    if let Some(plt_entry) = self.function_plt_entries[func_id] {
+                        return plt_entry.0;
+                    }
                     match &self.compiled_functions[func_id] {
                         Some(compiled) => return compiled.ptr,
                         None => {
@@ -255,23 +325,135 @@ impl JITModule {
 
     /// Returns the address of a finalized function.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual function is used.
+    /// The pointer remains valid until either [`JITModule::free_memory`] or
+    /// [`JITModule::free_function`] is called.
+    ///
+    /// In hot-swap mode this is the address of the PLT stub of the function, which always calls
+    /// the latest finalized definition of the function.
     pub fn get_finalized_function(&self, func_id: FuncId) -> *const u8 {
         let info = &self.compiled_functions[func_id];
         assert!(
             !self.functions_to_finalize.iter().any(|x| *x == func_id),
             "function not yet finalized"
         );
-        info.as_ref()
+        let ptr = info
+            .as_ref()
             .expect("function must be compiled before it can be finalized")
-            .ptr
+            .ptr;
+        match self.function_plt_entries[func_id] {
+            Some(plt_entry) => plt_entry.0,
+            None => ptr,
+        }
+    }
+
+    /// Free the code of the previous definitions of functions which have been redefined in
+    /// hot-swap mode, once their new definitions have been finalized.
+    ///
+    /// # Safety
+    ///
+    /// None of the previous definitions may be executing, and no pointers to them may be used
+    /// afterwards. This includes return addresses into them on the stack of any thread. Pointers
+    /// returned by [`JITModule::get_finalized_function`] point to PLT stubs in hot-swap mode, and
+    /// thus remain valid.
+    ///
+    /// Returns ModuleError if the memory provider fails to free the code.
+    pub unsafe fn free_retired_functions(&mut self) -> ModuleResult<()> {
+        for blob in self.retired_functions.drain(..) {
+            self.memory
+                .free_readexec(blob.ptr, blob.code_size())
+                .map_err(|e| free_error(e, "unable to free function"))?;
+        }
+        Ok(())
+    }
+
+    /// Free the code of a finalized function.
+    ///
+    /// Afterwards the function is no longer defined, and it may be defined again. Whether the
+    /// memory is actually reclaimed depends on the [`JITMemoryProvider`]; with the built-in
+    /// providers this happens once all functions and data objects finalized together with this
+    /// function have been freed.
+    ///
+    /// # Safety
+    ///
+    /// The function must not be executing, and no pointers to it may be used afterwards. This
+    /// includes references to it from other functions and data objects of the module, which
+    /// must be freed or redefined first.
+    ///
+    /// Returns ModuleError if the memory provider fails to free the code.
+    pub unsafe fn free_function(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        assert!(
+            !self.functions_to_finalize.contains(&func_id),
+            "function not yet finalized"
+        );
+        let blob = self.compiled_functions[func_id]
+            .take()
+            .expect("function must be compiled before it can be freed");
+        #[cfg(feature = "debug-info")]
+        self.debug_registrations.remove(&func_id);
+        self.code_ranges
+            .retain(|&(start, _, _)| start != blob.ptr as usize);
+        self.memory
+            .free_readexec(blob.ptr, blob.code_size())
+            .map_err(|e| free_error(e, "unable to free function"))?;
+
+        if let Some(got_entry) = self.function_got_entries[func_id].take() {
+            let plt_entry = self.function_plt_entries[func_id].take().unwrap();
+            let plt_size = plt_stub(self.isa.triple().architecture, got_entry.0).len();
+            self.memory
+                .free_readexec(plt_entry.0.cast_mut(), plt_size)
+                .map_err(|e| free_error(e, "unable to free PLT entry"))?;
+            self.memory
+                .free_readwrite(
+                    got_entry.0.cast_mut().cast(),
+                    std::mem::size_of::<AtomicPtr<u8>>(),
+                )
+                .map_err(|e| free_error(e, "unable to free GOT entry"))?;
+        }
+        Ok(())
+    }
+
+    /// Free the memory of a finalized data object.
+    ///
+    /// Afterwards the data object is no longer defined, and it may be defined again. The copy
+    /// of a thread-local data object of the current thread is freed right away. The copies of
+    /// other threads are freed when they exit, or when they first access a thread-local data
+    /// object defined later.
+    ///
+    /// # Safety
+    ///
+    /// No pointers to the data object may be used afterwards, including references to it from
+    /// functions and other data objects of the module.
+    ///
+    /// Returns ModuleError if the memory provider fails to free the data object.
+    pub unsafe fn free_data_object(&mut self, data_id: DataId) -> ModuleResult<()> {
+        assert!(
+            !self.data_objects_to_finalize.contains(&data_id),
+            "data object not yet finalized"
+        );
+        let blob = self.compiled_data_objects[data_id]
+            .take()
+            .expect("data object must be compiled before it can be freed");
+        // Data objects always occupy at least one byte, see `define_data`.
+        let size = std::cmp::max(blob.size, 1);
+        let decl = self.declarations.get_data_decl(data_id);
+        if decl.tls {
+            (*blob.ptr.cast::<TlsObject>()).free();
+        }
+        if decl.writable && !decl.tls {
+            self.memory.free_readwrite(blob.ptr, size)
+        } else {
+            self.memory.free_readonly(blob.ptr, size)
+        }
+        .map_err(|e| free_error(e, "unable to free data object"))
     }
 
     /// Returns the address and size of a finalized data object.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual data object is used.
+    /// The pointer remains valid until either [`JITModule::free_memory`] or
+    /// [`JITModule::free_data_object`] is called.
+    ///
+    /// For thread-local data objects this is the address of the copy of the current thread,
+    /// which remains valid until the thread exits.
     pub fn get_finalized_data(&self, data_id: DataId) -> (*const u8, usize) {
         let info = &self.compiled_data_objects[data_id];
         assert!(
@@ -282,6 +464,10 @@ impl JITModule {
             .as_ref()
             .expect("data object must be compiled before it can be finalized");
 
+        if self.declarations.get_data_decl(data_id).tls {
+            let object = unsafe { &*compiled.ptr.cast::<TlsObject>() };
+            return (object.get(), object.size());
+        }
         (compiled.ptr, compiled.size)
     }
 
@@ -311,7 +497,8 @@ impl JITModule {
     ///
     /// Returns ModuleError in case of allocation or syscall failure
     pub fn finalize_definitions(&mut self) -> ModuleResult<()> {
-        for func in std::mem::take(&mut self.functions_to_finalize) {
+        let functions = std::mem::take(&mut self.functions_to_finalize);
+        for &func in &functions {
             let decl = self.declarations.get_function_decl(func);
             assert!(decl.linkage.is_definable());
             let func = self.compiled_functions[func]
@@ -340,6 +527,109 @@ impl JITModule {
         };
         self.memory.finalize(branch_protection)?;
 
+        // Now that the new definitions are executable, redirect calls to them.
+        for func in functions {
+            if let Some(got_entry) = self.function_got_entries[func] {
+                let ptr = self.compiled_functions[func].as_ref().unwrap().ptr;
+                unsafe { (*got_entry.0).store(ptr, Ordering::Release) };
+            }
+            #[cfg(feature = "debug-info")]
+            self.register_debug_info(func)?;
+        }
+        self.retired_functions.append(&mut self.functions_to_retire);
+
+        Ok(())
+    }
+
+    /// Register the debug info of the newly finalized function `func` with debuggers, replacing
+    /// the debug info of any previous definition.
+    #[cfg(feature = "debug-info")]
+    fn register_debug_info(&mut self, func: FuncId) -> ModuleResult<()> {
+        self.debug_registrations.remove(&func);
+        let Some(debug_info) = self.pending_debug_info.remove(&func) else {
+            return Ok(());
+        };
+        let decl = self.declarations.get_function_decl(func);
+        let name = decl.linkage_name(func);
+        let blob = self.compiled_functions[func].as_ref().unwrap();
+        let registration = crate::debug::register_function(
+            &*self.isa,
+            &name,
+            blob.ptr,
+            blob.size,
+            debug_info.emit(&name)?,
+        )?;
+        self.debug_registrations.insert(func, registration);
+        Ok(())
+    }
+
+    /// Check whether the function `id` can be defined.
+    ///
+    /// Finalized functions can be redefined in hot-swap mode.
+    fn check_function_definable(&self, id: FuncId) -> ModuleResult<()> {
+        let decl = self.declarations.get_function_decl(id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(id).into_owned(),
+            ));
+        }
+
+        if self.compiled_functions[id].is_some()
+            && (!self.hotswap_enabled || self.functions_to_finalize.contains(&id))
+        {
+            return Err(ModuleError::DuplicateDefinition(
+                decl.linkage_name(id).into_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Record the compiled code of the function `id`, to be finalized by the next call to
+    /// `finalize_definitions`.
+    ///
+    /// In hot-swap mode, this allocates the GOT and PLT entries of the function on its first
+    /// definition, and keeps the previous definition around until the new one is finalized.
+    fn insert_function(&mut self, id: FuncId, blob: CompiledBlob) -> ModuleResult<()> {
+        if self.hotswap_enabled && self.function_got_entries[id].is_none() {
+            let got_entry = self
+                .memory
+                .allocate_readwrite(
+                    std::mem::size_of::<AtomicPtr<u8>>(),
+                    std::mem::align_of::<AtomicPtr<u8>>() as u64,
+                )
+                .map_err(|e| ModuleError::Allocation {
+                    message: "unable to alloc GOT entry",
+                    err: e,
+                })?
+                .cast::<AtomicPtr<u8>>();
+            unsafe { got_entry.write(AtomicPtr::new(ptr::null_mut())) };
+
+            let stub = plt_stub(self.isa.triple().architecture, got_entry);
+            let align = (self.isa.function_alignment().minimum as u64)
+                .max(self.isa.symbol_alignment())
+                .max(8);
+            let plt_entry = self
+                .memory
+                .allocate_readexec(stub.len(), align)
+                .map_err(|e| ModuleError::Allocation {
+                    message: "unable to alloc PLT entry",
+                    err: e,
+                })?;
+            unsafe { ptr::copy_nonoverlapping(stub.as_ptr(), plt_entry, stub.len()) };
+
+            self.function_got_entries[id] = Some(SendWrapper(got_entry));
+            self.function_plt_entries[id] = Some(SendWrapper(plt_entry));
+        }
+
+        if let Some(previous) = self.compiled_functions[id].replace(blob) {
+            // The previous definition is no longer registered for exception
+            // lookups, as its `FuncId` now refers to the new definition.
+            self.code_ranges
+                .retain(|&(start, _, _)| start != previous.ptr as usize);
+            self.functions_to_retire.push(previous);
+        }
+        self.functions_to_finalize.push(id);
         Ok(())
     }
 
@@ -360,11 +650,20 @@ impl JITModule {
             libcall_names: builder.libcall_names,
             memory,
             declarations: ModuleDeclarations::default(),
+            hotswap_enabled: builder.hotswap_enabled,
+            function_got_entries: SecondaryMap::new(),
+            function_plt_entries: SecondaryMap::new(),
             compiled_functions: SecondaryMap::new(),
             compiled_data_objects: SecondaryMap::new(),
             code_ranges: Vec::new(),
             functions_to_finalize: Vec::new(),
             data_objects_to_finalize: Vec::new(),
+            functions_to_retire: Vec::new(),
+            retired_functions: Vec::new(),
+            #[cfg(feature = "debug-info")]
+            pending_debug_info: HashMap::new(),
+            #[cfg(feature = "debug-info")]
+            debug_registrations: HashMap::new(),
         }
     }
 
@@ -441,7 +740,14 @@ impl Module for JITModule {
         writable: bool,
         tls: bool,
     ) -> ModuleResult<DataId> {
-        assert!(!tls, "JIT doesn't yet support TLS");
+        if tls {
+            check_tls_supported()?;
+        }
+        if tls && linkage == Linkage::Import {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "JIT doesn't support importing thread-local data object {name}"
+            )));
+        }
         let (id, _linkage) = self
             .declarations
             .declare_data(name, linkage, writable, tls)?;
@@ -449,7 +755,9 @@ impl Module for JITModule {
     }
 
     fn declare_anonymous_data(&mut self, writable: bool, tls: bool) -> ModuleResult<DataId> {
-        assert!(!tls, "JIT doesn't yet support TLS");
+        if tls {
+            check_tls_supported()?;
+        }
         let id = self.declarations.declare_anonymous_data(writable, tls)?;
         Ok(id)
     }
@@ -461,18 +769,7 @@ impl Module for JITModule {
         ctrl_plane: &mut ControlPlane,
     ) -> ModuleResult<()> {
         info!("defining function {}: {}", id, ctx.func.display());
-        let decl = self.declarations.get_function_decl(id);
-        if !decl.linkage.is_definable() {
-            return Err(ModuleError::InvalidImportDefinition(
-                decl.linkage_name(id).into_owned(),
-            ));
-        }
-
-        if !self.compiled_functions[id].is_none() {
-            return Err(ModuleError::DuplicateDefinition(
-                decl.linkage_name(id).into_owned(),
-            ));
-        }
+        self.check_function_definable(id)?;
 
         // work around borrow-checker to allow reuse of ctx below
         let res = ctx.compile(self.isa(), ctrl_plane)?;
@@ -480,37 +777,42 @@ impl Module for JITModule {
         let compiled_code = ctx.compiled_code().unwrap();
 
         let size = compiled_code.code_info().total_size as usize;
+        let relocs = compiled_code
+            .buffer
+            .relocs()
+            .iter()
+            .map(|reloc| ModuleReloc::from_mach_reloc(reloc, &ctx.func, id))
+            .collect::<Vec<_>>();
+
         let align = alignment
             .max(self.isa.function_alignment().minimum as u64)
             .max(self.isa.symbol_alignment());
-        let ptr =
-            self.memory
-                .allocate_readexec(size, align)
-                .map_err(|e| ModuleError::Allocation {
-                    message: "unable to alloc function",
-                    err: e,
-                })?;
+        let (alloc_size, align) = CompiledBlob::code_layout(size, align, &relocs);
+        let ptr = self
+            .memory
+            .allocate_readexec(alloc_size, align)
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc function",
+                err: e,
+            })?;
 
         {
             let mem = unsafe { std::slice::from_raw_parts_mut(ptr, size) };
             mem.copy_from_slice(compiled_code.code_buffer());
         }
 
-        let relocs = compiled_code
-            .buffer
-            .relocs()
-            .iter()
-            .map(|reloc| ModuleReloc::from_mach_reloc(reloc, &ctx.func, id))
-            .collect();
-
+        let decl = self.declarations.get_function_decl(id);
         self.record_function_for_perf(ptr, size, &decl.linkage_name(id));
-        self.compiled_functions[id] = Some(CompiledBlob {
-            ptr,
-            size,
-            relocs,
-            #[cfg(feature = "wasmtime-unwinder")]
-            exception_data: None,
-        });
+        self.insert_function(
+            id,
+            CompiledBlob {
+                ptr,
+                size,
+                relocs,
+                #[cfg(feature = "wasmtime-unwinder")]
+                exception_data: None,
+            },
+        )?;
 
         let range_start = ptr as usize;
         let range_end = range_start + size;
@@ -531,8 +833,27 @@ impl Module for JITModule {
                 Some(exception_builder.to_vec());
         }
 
-        self.functions_to_finalize.push(id);
+        Ok(())
+    }
 
+    #[cfg(feature = "debug-info")]
+    fn define_function_with_debug_info(
+        &mut self,
+        id: FuncId,
+        ctx: &mut cranelift_codegen::Context,
+        debug_info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        crate::debug::image_architecture(&*self.isa)?;
+        self.define_function(id, ctx)?;
+        let mut builder = DwarfBuilder::new(&*self.isa);
+        builder.add_function(
+            &*self.isa,
+            id,
+            self.declarations.get_function_decl(id),
+            ctx.compiled_code().unwrap(),
+            debug_info,
+        )?;
+        self.pending_debug_info.insert(id, builder);
         Ok(())
     }
 
@@ -544,47 +865,37 @@ impl Module for JITModule {
         relocs: &[ModuleReloc],
     ) -> ModuleResult<()> {
         info!("defining function {id} with bytes");
-        let decl = self.declarations.get_function_decl(id);
-        if !decl.linkage.is_definable() {
-            return Err(ModuleError::InvalidImportDefinition(
-                decl.linkage_name(id).into_owned(),
-            ));
-        }
-
-        if !self.compiled_functions[id].is_none() {
-            return Err(ModuleError::DuplicateDefinition(
-                decl.linkage_name(id).into_owned(),
-            ));
-        }
+        self.check_function_definable(id)?;
 
         let size = bytes.len();
         let align = alignment
             .max(self.isa.function_alignment().minimum as u64)
             .max(self.isa.symbol_alignment());
-        let ptr =
-            self.memory
-                .allocate_readexec(size, align)
-                .map_err(|e| ModuleError::Allocation {
-                    message: "unable to alloc function bytes",
-                    err: e,
-                })?;
+        let (alloc_size, align) = CompiledBlob::code_layout(size, align, relocs);
+        let ptr = self
+            .memory
+            .allocate_readexec(alloc_size, align)
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc function bytes",
+                err: e,
+            })?;
 
         unsafe {
             ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, size);
         }
 
+        let decl = self.declarations.get_function_decl(id);
         self.record_function_for_perf(ptr, size, &decl.linkage_name(id));
-        self.compiled_functions[id] = Some(CompiledBlob {
-            ptr,
-            size,
-            relocs: relocs.to_owned(),
-            #[cfg(feature = "wasmtime-unwinder")]
-            exception_data: None,
-        });
-
-        self.functions_to_finalize.push(id);
-
-        Ok(())
+        self.insert_function(
+            id,
+            CompiledBlob {
+                ptr,
+                size,
+                relocs: relocs.to_owned(),
+                #[cfg(feature = "wasmtime-unwinder")]
+                exception_data: None,
+            },
+        )
     }
 
     fn define_data(&mut self, id: DataId, data: &DataDescription) -> ModuleResult<()> {
@@ -601,7 +912,9 @@ impl Module for JITModule {
             ));
         }
 
-        assert!(!decl.tls, "JIT doesn't yet support TLS");
+        if decl.tls {
+            check_tls_supported()?;
+        }
 
         let &DataDescription {
             ref init,
@@ -614,22 +927,36 @@ impl Module for JITModule {
             used: _,
         } = data;
 
+        // Thread-local data objects are allocated as a `TlsObject` header followed by their
+        // initial contents, from which each thread initializes its own copy.
+        let (init_offset, alloc_align) = if decl.tls {
+            let align = align.unwrap_or(WRITABLE_DATA_ALIGNMENT);
+            (
+                TlsObject::init_offset(align.try_into().unwrap()),
+                align.max(align_of::<TlsObject>() as u64),
+            )
+        } else if decl.writable {
+            (0, align.unwrap_or(WRITABLE_DATA_ALIGNMENT))
+        } else {
+            (0, align.unwrap_or(READONLY_DATA_ALIGNMENT))
+        };
+
         // Make sure to allocate at least 1 byte. Allocating 0 bytes is UB. Previously a dummy
         // value was used, however as it turns out this will cause pc-relative relocations to
         // fail on architectures where pc-relative offsets are range restricted as the dummy
         // value is not close enough to the code that has the pc-relative relocation.
-        let alloc_size = std::cmp::max(init.size(), 1);
+        let alloc_size = std::cmp::max(init_offset + init.size(), 1);
 
-        let ptr = if decl.writable {
+        let ptr = if decl.writable && !decl.tls {
             self.memory
-                .allocate_readwrite(alloc_size, align.unwrap_or(WRITABLE_DATA_ALIGNMENT))
+                .allocate_readwrite(alloc_size, alloc_align)
                 .map_err(|e| ModuleError::Allocation {
                     message: "unable to alloc writable data",
                     err: e,
                 })?
         } else {
             self.memory
-                .allocate_readonly(alloc_size, align.unwrap_or(READONLY_DATA_ALIGNMENT))
+                .allocate_readonly(alloc_size, alloc_align)
                 .map_err(|e| ModuleError::Allocation {
                     message: "unable to alloc readonly data",
                     err: e,
@@ -639,24 +966,30 @@ impl Module for JITModule {
         if ptr.is_null() {
             // FIXME pass a Layout to allocate and only compute the layout once.
             std::alloc::handle_alloc_error(
-                std::alloc::Layout::from_size_align(
-                    alloc_size,
-                    align.unwrap_or(READONLY_DATA_ALIGNMENT).try_into().unwrap(),
-                )
-                .unwrap(),
+                std::alloc::Layout::from_size_align(alloc_size, alloc_align.try_into().unwrap())
+                    .unwrap(),
             );
         }
 
+        if decl.tls {
+            let object = TlsObject::new(
+                init.size(),
+                align.unwrap_or(WRITABLE_DATA_ALIGNMENT).try_into().unwrap(),
+            );
+            unsafe { ptr.cast::<TlsObject>().write(object) };
+        }
+
+        let init_ptr = unsafe { ptr.add(init_offset) };
         match *init {
             Init::Uninitialized => {
                 panic!("data is not initialized yet");
             }
             Init::Zeros { size } => {
-                unsafe { ptr::write_bytes(ptr, 0, size) };
+                unsafe { ptr::write_bytes(init_ptr, 0, size) };
             }
             Init::Bytes { ref contents } => {
                 let src = contents.as_ptr();
-                unsafe { ptr::copy_nonoverlapping(src, ptr, contents.len()) };
+                unsafe { ptr::copy_nonoverlapping(src, init_ptr, contents.len()) };
             }
         }
 
@@ -665,11 +998,17 @@ impl Module for JITModule {
             PointerWidth::U32 => Reloc::Abs4,
             PointerWidth::U64 => Reloc::Abs8,
         };
-        let relocs = data.all_relocs(pointer_reloc).collect::<Vec<_>>();
+        let relocs = data
+            .all_relocs(pointer_reloc)
+            .map(|reloc| ModuleReloc {
+                offset: reloc.offset + init_offset as u32,
+                ..reloc
+            })
+            .collect::<Vec<_>>();
 
         self.compiled_data_objects[id] = Some(CompiledBlob {
             ptr,
-            size: init.size(),
+            size: init_offset + init.size(),
             relocs,
             #[cfg(feature = "wasmtime-unwinder")]
             exception_data: None,
@@ -750,9 +1089,65 @@ fn lookup_with_dlsym(name: &str) -> Option<*const u8> {
     }
 }
 
+/// Returns the code of a PLT stub which jumps to the address stored in
+/// `got_entry`.
+///
+/// The stubs only clobber registers which the ABI reserves for use by such
+/// veneers, so they don't disturb the arguments of the call.
+fn plt_stub(arch: Architecture, got_entry: *const AtomicPtr<u8>) -> Vec<u8> {
+    let got_entry = (got_entry as u64).to_le_bytes();
+    let mut code = Vec::new();
+    match arch {
+        Architecture::X86_64 => {
+            // movabs r11, got_entry
+            code.extend([0x49, 0xbb]);
+            code.extend(got_entry);
+            // jmp qword ptr [r11]
+            code.extend([0x41, 0xff, 0x23]);
+        }
+        Architecture::Aarch64(_) => {
+            for inst in [
+                0xd503245f_u32, // bti c
+                0x58000070,     // ldr x16, got_entry
+                0xf9400210,     // ldr x16, [x16]
+                0xd61f0200,     // br x16
+            ] {
+                code.extend(inst.to_le_bytes());
+            }
+            code.extend(got_entry);
+        }
+        Architecture::Riscv64(_) => {
+            for inst in [
+                0x00000e17_u32, // auipc t3, 0
+                0x010e3e03,     // ld t3, 16(t3)
+                0x000e3e03,     // ld t3, 0(t3)
+                0x000e0067,     // jr t3
+            ] {
+                code.extend(inst.to_le_bytes());
+            }
+            code.extend(got_entry);
+        }
+        _ => unreachable!("hotswap is not supported on {arch}"),
+    }
+    code
+}
+
 fn use_bti(isa_flags: &Vec<settings::Value>) -> bool {
     isa_flags
         .iter()
         .find(|&f| f.name == "use_bti")
         .map_or(false, |f| f.as_bool().unwrap_or(false))
 }
+
+fn check_tls_supported() -> ModuleResult<()> {
+    if !tls::SUPPORTED {
+        return Err(ModuleError::Backend(anyhow::anyhow!(
+            "JIT doesn't support thread-local data objects on this host"
+        )));
+    }
+    Ok(())
+}
+
+fn free_error(err: std::io::Error, message: &'static str) -> ModuleError {
+    ModuleError::Backend(anyhow::Error::new(err).context(message))
+}
@@ -1,4 +1,7 @@
+use crate::tls::{TlsDescriptor, TlsObject};
 use cranelift_codegen::binemit::Reloc;
+use cranelift_codegen::ir::LibCall;
+use cranelift_module::DataId;
 use cranelift_module::ModuleReloc;
 use cranelift_module::ModuleRelocTarget;
 
@@ -10,6 +13,31 @@ unsafe fn modify_inst32(iptr: *mut u32, modifier: impl FnOnce(u32) -> u32) {
     iptr.write_unaligned(new_inst);
 }
 
+/// The code of the stub through which JIT code calls the resolver of a TLS descriptor on
+/// x86-64: `jmp *(%rdi)`.
+const X86_64_TLS_STUB: [u8; 2] = [0xff, 0x27];
+
+/// Returns the thread-local data objects accessed through `relocs`, in the order of their TLS
+/// descriptors.
+fn tls_objects(relocs: &[ModuleReloc]) -> Vec<DataId> {
+    let mut objects = Vec::new();
+    for reloc in relocs {
+        if let Reloc::ElfX86_64TlsGd | Reloc::Aarch64TlsDescAdrPage21 = reloc.kind {
+            let data_id = DataId::from_name(&reloc.name);
+            if !objects.contains(&data_id) {
+                objects.push(data_id);
+            }
+        }
+    }
+    objects
+}
+
+/// Code and data of a function or data object.
+///
+/// The code of functions which access thread-local data objects is followed by a
+/// [`TlsDescriptor`] for each of these objects, and on x86-64 by a stub calling the resolver of
+/// a descriptor. Placing them next to the code keeps them within range of the pc-relative
+/// relocations of the accesses.
 #[derive(Clone)]
 pub(crate) struct CompiledBlob {
     pub(crate) ptr: *mut u8,
@@ -22,12 +50,55 @@ pub(crate) struct CompiledBlob {
 unsafe impl Send for CompiledBlob {}
 
 impl CompiledBlob {
+    /// Returns the size and alignment of the memory needed for code of `size` bytes with
+    /// alignment `align` and relocations `relocs`.
+    pub(crate) fn code_layout(size: usize, align: u64, relocs: &[ModuleReloc]) -> (usize, u64) {
+        let objects = tls_objects(relocs).len();
+        if objects == 0 {
+            return (size, align);
+        }
+        let mut size = Self::tls_descriptors_offset(size) + objects * size_of::<TlsDescriptor>();
+        if relocs
+            .iter()
+            .any(|reloc| reloc.kind == Reloc::ElfX86_64TlsGd)
+        {
+            size += X86_64_TLS_STUB.len();
+        }
+        (size, align.max(align_of::<TlsDescriptor>() as u64))
+    }
+
+    /// Returns the size of the memory of this function.
+    pub(crate) fn code_size(&self) -> usize {
+        Self::code_layout(self.size, 1, &self.relocs).0
+    }
+
+    fn tls_descriptors_offset(size: usize) -> usize {
+        size.next_multiple_of(align_of::<TlsDescriptor>())
+    }
+
     pub(crate) fn perform_relocations(
         &self,
         get_address: impl Fn(&ModuleRelocTarget) -> *const u8,
     ) {
         use std::ptr::write_unaligned;
 
+        let tls_objects = tls_objects(&self.relocs);
+        let tls_descriptors = unsafe {
+            self.ptr
+                .add(Self::tls_descriptors_offset(self.size))
+                .cast::<TlsDescriptor>()
+        };
+        let tls_stub = unsafe { tls_descriptors.add(tls_objects.len()).cast::<u8>() };
+        for (i, &data_id) in tls_objects.iter().enumerate() {
+            let object = get_address(&data_id.into()).cast::<TlsObject>();
+            unsafe { tls_descriptors.add(i).write(TlsDescriptor::new(object)) };
+        }
+        let tls_descriptor = |name: &ModuleRelocTarget| {
+            let data_id = DataId::from_name(name);
+            let i = tls_objects.iter().position(|&id| id == data_id).unwrap();
+            unsafe { tls_descriptors.add(i).cast::<u8>() }
+        };
+
         for &ModuleReloc {
             kind,
             offset,
@@ -61,6 +132,26 @@ impl CompiledBlob {
                 Reloc::X86GOTPCRel4 => {
                     panic!("GOT relocation shouldn't be generated when !is_pic");
                 }
+                Reloc::ElfX86_64TlsGd => {
+                    let what = tls_descriptor(name).wrapping_offset(addend as isize);
+                    let pcrel = i32::try_from((what as isize) - (at as isize)).unwrap();
+                    unsafe { write_unaligned(at as *mut i32, pcrel) };
+                }
+                Reloc::X86CallPLTRel4
+                    if matches!(name, ModuleRelocTarget::LibCall(LibCall::ElfTlsGetAddr)) =>
+                {
+                    // Call the resolver of the descriptor passed in `rdi` through the stub.
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(
+                            X86_64_TLS_STUB.as_ptr(),
+                            tls_stub,
+                            X86_64_TLS_STUB.len(),
+                        )
+                    };
+                    let what = tls_stub.wrapping_offset(addend as isize);
+                    let pcrel = i32::try_from((what as isize) - (at as isize)).unwrap();
+                    unsafe { write_unaligned(at as *mut i32, pcrel) };
+                }
                 Reloc::X86CallPLTRel4 => {
                     panic!("PLT relocation shouldn't be generated when !is_pic");
                 }
@@ -94,6 +185,28 @@ impl CompiledBlob {
                 Reloc::Aarch64Ld64GotLo12Nc => {
                     panic!("GOT relocation shouldn't be generated when !is_pic");
                 }
+                Reloc::Aarch64TlsDescAdrPage21 => {
+                    // The offset encoded in the `adrp` instruction is the
+                    // number of 4K pages, split into 2 low and 19 high bits.
+                    let what = tls_descriptor(name) as isize;
+                    let diff = ((what & !0xfff) - ((at as isize) & !0xfff)) >> 12;
+                    assert!((diff >> 20 == -1) || (diff >> 20 == 0));
+                    let immlo = (diff as u32 & 0b11) << 29;
+                    let immhi = (diff as u32 >> 2 & 0x7ffff) << 5;
+                    unsafe { modify_inst32(at as *mut u32, |inst| inst | immlo | immhi) };
+                }
+                Reloc::Aarch64TlsDescLd64Lo12 => {
+                    // The offset of the 64-bit load is scaled by 8.
+                    let imm12 = (tls_descriptor(name) as u32 & 0xfff) >> 3;
+                    unsafe { modify_inst32(at as *mut u32, |inst| inst | imm12 << 10) };
+                }
+                Reloc::Aarch64TlsDescAddLo12 => {
+                    let imm12 = tls_descriptor(name) as u32 & 0xfff;
+                    unsafe { modify_inst32(at as *mut u32, |inst| inst | imm12 << 10) };
+                }
+                Reloc::Aarch64TlsDescCall => {
+                    // Only a hint for linkers relaxing the TLS access.
+                }
                 Reloc::RiscvCallPlt => {
                     // A R_RISCV_CALL_PLT relocation expects auipc+jalr instruction pair.
                     // It is the equivalent of two relocations:
@@ -0,0 +1,137 @@
+//! Registration of DWARF debug info with debuggers through the GDB JIT interface.
+
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_module::{DwarfRelocTarget, DwarfSection, ModuleError, ModuleResult};
+use object::elf::{FileHeader64, SHF_ALLOC, SHF_EXECINSTR, SectionHeader64};
+use object::read::elf::FileHeader;
+use object::write::{Object, Symbol, SymbolSection};
+use object::{Endianness, SectionFlags, SectionKind, SymbolFlags, SymbolKind, SymbolScope};
+use wasmtime_jit_debug::gdb_jit_int::GdbJitImageRegistration;
+
+/// Returns the architecture of ELF images for functions compiled for `isa`.
+pub(crate) fn image_architecture(isa: &dyn TargetIsa) -> ModuleResult<object::Architecture> {
+    match isa.triple().architecture {
+        target_lexicon::Architecture::X86_64 => Ok(object::Architecture::X86_64),
+        target_lexicon::Architecture::Aarch64(_) => Ok(object::Architecture::Aarch64),
+        target_lexicon::Architecture::Riscv64(_) => Ok(object::Architecture::Riscv64),
+        target_lexicon::Architecture::S390x => Ok(object::Architecture::S390x),
+        architecture => Err(ModuleError::Backend(anyhow::anyhow!(
+            "debug info is not supported on {architecture}"
+        ))),
+    }
+}
+
+/// Register the debug info of the function `name` whose code is at `ptr`.
+///
+/// The function is described by an ELF image containing `sections`, with all addresses
+/// relocated to the location of the function in memory. The image also contains a `.text`
+/// section without any data whose address and size cover the code of the function, so that
+/// debuggers can tell which code the image describes.
+pub(crate) fn register_function(
+    isa: &dyn TargetIsa,
+    name: &str,
+    ptr: *const u8,
+    size: usize,
+    sections: Vec<DwarfSection>,
+) -> ModuleResult<GdbJitImageRegistration> {
+    let mut object = Object::new(
+        object::BinaryFormat::Elf,
+        image_architecture(isa)?,
+        Endianness::default(),
+    );
+    let text = object.add_section(vec![], b".text".to_vec(), SectionKind::UninitializedData);
+    object.section_mut(text).flags = SectionFlags::Elf {
+        sh_flags: (SHF_ALLOC | SHF_EXECINSTR).into(),
+    };
+    object.append_section_bss(text, size as u64, 1);
+    object.add_symbol(Symbol {
+        name: name.as_bytes().to_vec(),
+        value: ptr as u64,
+        size: size as u64,
+        kind: SymbolKind::Text,
+        scope: SymbolScope::Compilation,
+        weak: false,
+        section: SymbolSection::Absolute,
+        flags: SymbolFlags::None,
+    });
+
+    for DwarfSection {
+        name,
+        mut data,
+        relocs,
+    } in sections
+    {
+        for reloc in relocs {
+            // Offsets into the other debug sections are already correct, as the image contains
+            // only the debug info of this function.
+            let DwarfRelocTarget::Function(_) = reloc.target else {
+                continue;
+            };
+            let value = (ptr as u64).wrapping_add(reloc.addend as u64);
+            let offset = reloc.offset as usize;
+            match reloc.size {
+                4 => data[offset..][..4].copy_from_slice(&(value as u32).to_ne_bytes()),
+                8 => data[offset..][..8].copy_from_slice(&value.to_ne_bytes()),
+                size => panic!("unexpected relocation size {size}"),
+            }
+        }
+
+        let section = object.add_section(vec![], name.as_bytes().to_vec(), SectionKind::Debug);
+        object.set_section_data(section, data, 1);
+    }
+
+    let mut image = object
+        .write()
+        .map_err(|e| ModuleError::Backend(anyhow::Error::new(e)))?;
+    set_text_address(&mut image, ptr as u64).map_err(ModuleError::Backend)?;
+    Ok(GdbJitImageRegistration::register(image))
+}
+
+/// Set the address of the `.text` section of the ELF `image` to `address`.
+///
+/// The `object` crate always writes relocatable images with section addresses of zero.
+fn set_text_address(image: &mut [u8], address: u64) -> anyhow::Result<()> {
+    let header = FileHeader64::<Endianness>::parse(&*image)?;
+    let e = header.endian()?;
+    let (index, _) = header
+        .sections(e, &*image)?
+        .section_by_name(e, b".text")
+        .ok_or_else(|| anyhow::anyhow!("missing .text section"))?;
+    let offset = header.e_shoff(e) as usize + index.0 * usize::from(header.e_shentsize(e));
+    let (section, _) = object::from_bytes_mut::<SectionHeader64<Endianness>>(&mut image[offset..])
+        .map_err(|()| anyhow::anyhow!("invalid section header"))?;
+    section.sh_addr.set(e, address);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::{Object as _, ObjectSection, ObjectSymbol};
+
+    #[test]
+    fn text_section_covers_function() {
+        let Ok(isa_builder) = cranelift_native::builder() else {
+            return;
+        };
+        let isa = isa_builder
+            .finish(cranelift_codegen::settings::Flags::new(
+                cranelift_codegen::settings::builder(),
+            ))
+            .unwrap();
+        if image_architecture(&*isa).is_err() {
+            return;
+        }
+
+        let code = [0u8; 64];
+        let registration =
+            register_function(&*isa, "func", code.as_ptr(), code.len(), vec![]).unwrap();
+
+        let file = object::File::parse(registration.file()).unwrap();
+        let text = file.section_by_name(".text").unwrap();
+        assert_eq!(text.address(), code.as_ptr() as u64);
+        assert_eq!(text.size(), code.len() as u64);
+        let symbol = file.symbols().find(|s| s.name() == Ok("func")).unwrap();
+        assert_eq!(symbol.address(), code.as_ptr() as u64);
+    }
+}
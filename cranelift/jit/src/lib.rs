@@ -8,7 +8,10 @@
 
 mod backend;
 mod compiled_blob;
+#[cfg(feature = "debug-info")]
+mod debug;
 mod memory;
+mod tls;
 
 pub use crate::backend::{JITBuilder, JITModule};
 pub use crate::memory::{
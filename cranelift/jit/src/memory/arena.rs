@@ -18,6 +18,11 @@ struct Segment {
     position: usize,
     target_prot: region::Protection,
     finalized: bool,
+    /// The number of allocations in this segment which haven't been freed.
+    live: usize,
+    /// Whether all allocations in this finalized segment have been freed,
+    /// making it available for reuse.
+    released: bool,
 }
 
 impl Segment {
@@ -31,6 +36,8 @@ impl Segment {
             target_prot,
             position: 0,
             finalized: false,
+            live: 0,
+            released: false,
         };
         // Set segment to read-write for initialization. The target permissions
         // will be applied in `finalize`.
@@ -71,9 +78,34 @@ impl Segment {
         self.position = align_up(self.position, align);
         let ptr = unsafe { self.ptr.add(self.position) };
         self.position += size;
+        self.live += 1;
         ptr
     }
 
+    fn contains(&self, ptr: *mut u8) -> bool {
+        (self.ptr as usize..self.ptr as usize + self.len).contains(&(ptr as usize))
+    }
+
+    /// Make the memory of this segment inaccessible until it is reused.
+    fn release(&mut self) {
+        debug_assert!(self.finalized && self.live == 0);
+        unsafe {
+            region::protect(self.ptr, self.len, region::Protection::NONE)
+                .expect("unable to change memory protection for jit memory segment");
+        }
+        self.released = true;
+    }
+
+    /// Reuse this released segment for allocations with the given protection.
+    fn reuse(&mut self, target_prot: region::Protection) {
+        debug_assert!(self.released);
+        self.target_prot = target_prot;
+        self.position = 0;
+        self.finalized = false;
+        self.released = false;
+        self.set_rw();
+    }
+
     fn has_space_for(&self, size: usize, align: usize) -> bool {
         !self.finalized && align_up(self.position, align) + size <= self.len
     }
@@ -93,6 +125,10 @@ impl Segment {
 /// Note: Memory will be leaked by default unless
 /// [`JITMemoryProvider::free_memory`] is called to ensure function pointers
 /// remain valid for the remainder of the program's life.
+///
+/// Segments whose allocations have all been freed through
+/// [`JITMemoryProvider::free_readexec`] and friends after being finalized are
+/// made inaccessible and reused for later allocations.
 pub struct ArenaMemoryProvider {
     alloc: ManuallyDrop<Option<region::Allocation>>,
     ptr: *mut u8,
@@ -142,6 +178,16 @@ impl ArenaMemoryProvider {
             return Ok(segment.allocate(size, align));
         }
 
+        // Can we reuse a segment whose allocations have all been freed?
+        if let Some(segment) = self
+            .segments
+            .iter_mut()
+            .find(|seg| seg.released && size <= seg.len)
+        {
+            segment.reuse(protection);
+            return Ok(segment.allocate(size, align));
+        }
+
         // Can we resize the last segment?
         if let Some(segment) = self.segments.iter_mut().last() {
             if segment.target_prot == protection && !segment.finalized {
@@ -191,6 +237,33 @@ impl ArenaMemoryProvider {
         wasmtime_jit_icache_coherence::pipeline_flush_mt().expect("Failed pipeline flush");
     }
 
+    /// Frees a single allocation, releasing the segment containing it for
+    /// reuse once all allocations in the segment have been freed. Returns an
+    /// error if `ptr` wasn't allocated by this arena.
+    unsafe fn free(&mut self, ptr: *mut u8) -> io::Result<()> {
+        let segment = self
+            .segments
+            .iter_mut()
+            .find(|seg| seg.contains(ptr))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "pointer not allocated by this arena",
+                )
+            })?;
+        segment.live -= 1;
+        if segment.live == 0 {
+            if segment.finalized {
+                segment.release();
+            } else {
+                // Nothing in the segment has been protected yet, so it can be
+                // allocated from again right away.
+                segment.position = 0;
+            }
+        }
+        Ok(())
+    }
+
     /// Frees the allocated memory region, which would be leaked otherwise.
     /// Likely to invalidate existing function pointers, causing unsafety.
     pub(crate) unsafe fn free_memory(&mut self) {
@@ -209,7 +282,10 @@ impl Drop for ArenaMemoryProvider {
         if self.ptr == ptr::null_mut() {
             return;
         }
-        let is_live = self.segments.iter().any(|seg| seg.finalized);
+        let is_live = self
+            .segments
+            .iter()
+            .any(|seg| seg.finalized && !seg.released);
         if !is_live {
             // Only free memory if it's not been finalized yet.
             // Otherwise, leak it since JIT memory may still be in use.
@@ -235,6 +311,18 @@ impl JITMemoryProvider for ArenaMemoryProvider {
         self.free_memory();
     }
 
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, _size: usize) -> io::Result<()> {
+        self.free(ptr)
+    }
+
+    unsafe fn free_readwrite(&mut self, ptr: *mut u8, _size: usize) -> io::Result<()> {
+        self.free(ptr)
+    }
+
+    unsafe fn free_readonly(&mut self, ptr: *mut u8, _size: usize) -> io::Result<()> {
+        self.free(ptr)
+    }
+
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()> {
         self.finalize(branch_protection);
         Ok(())
@@ -274,6 +362,29 @@ mod tests {
         unsafe { arena.free_memory() };
     }
 
+    #[test]
+    fn reuse_freed_segment() {
+        let page_size = region::page::size();
+        let mut arena = ArenaMemoryProvider::new_with_size(2 * page_size).unwrap();
+
+        let ptr = arena.allocate_readexec(page_size, 1).unwrap();
+        let _ = arena.allocate_readwrite(page_size, 1).unwrap();
+        arena.finalize(BranchProtection::None);
+        let _ = arena.allocate_readonly(1, 1).unwrap_err();
+
+        unsafe { arena.free_readexec(ptr, page_size).unwrap() };
+        assert_eq!(arena.allocate_readonly(1, 1).unwrap(), ptr);
+    }
+
+    #[test]
+    fn free_foreign_pointer() {
+        let page_size = region::page::size();
+        let mut arena = ArenaMemoryProvider::new_with_size(page_size).unwrap();
+
+        let mut byte = 0u8;
+        unsafe { arena.free_readwrite(&mut byte, 1).unwrap_err() };
+    }
+
     #[test]
     fn over_capacity() {
         let mut arena = ArenaMemoryProvider::new_with_size(1 << 20).unwrap(); // 1 MB
@@ -27,6 +27,32 @@ pub trait JITMemoryProvider {
 
     /// Free the memory region.
     unsafe fn free_memory(&mut self);
+    /// Free a single allocation made by `allocate_readexec`.
+    ///
+    /// Providers which can't free individual allocations may ignore this, in
+    /// which case the memory is only reclaimed by `free_memory`.
+    ///
+    /// # Safety
+    ///
+    /// The memory must not be used afterwards.
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, size: usize) -> io::Result<()> {
+        let _ = (ptr, size);
+        Ok(())
+    }
+    /// Free a single allocation made by `allocate_readwrite`.
+    ///
+    /// See [`JITMemoryProvider::free_readexec`].
+    unsafe fn free_readwrite(&mut self, ptr: *mut u8, size: usize) -> io::Result<()> {
+        let _ = (ptr, size);
+        Ok(())
+    }
+    /// Free a single allocation made by `allocate_readonly`.
+    ///
+    /// See [`JITMemoryProvider::free_readexec`].
+    unsafe fn free_readonly(&mut self, ptr: *mut u8, size: usize) -> io::Result<()> {
+        let _ = (ptr, size);
+        Ok(())
+    }
     /// Finalize the memory region and apply memory protections.
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()>;
 }
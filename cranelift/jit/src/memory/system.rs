@@ -19,6 +19,8 @@ struct PtrLen {
 
     ptr: *mut u8,
     len: usize,
+    /// The number of allocations in this region which haven't been freed.
+    live: usize,
 }
 
 impl PtrLen {
@@ -30,9 +32,15 @@ impl PtrLen {
 
             ptr: ptr::null_mut(),
             len: 0,
+            live: 0,
         }
     }
 
+    /// Returns whether `ptr` points into this region.
+    fn contains(&self, ptr: *mut u8) -> bool {
+        (self.ptr as usize..self.ptr as usize + self.len).contains(&(ptr as usize))
+    }
+
     /// Create a new `PtrLen` pointing to at least `size` bytes of memory,
     /// suitably sized and aligned for memory protection.
     #[cfg(all(not(target_os = "windows"), feature = "selinux-fix"))]
@@ -45,6 +53,7 @@ impl PtrLen {
                 ptr: mmap.as_mut_ptr(),
                 map: Some(mmap),
                 len: alloc_size,
+                live: 0,
             }
         })
     }
@@ -62,6 +71,7 @@ impl PtrLen {
             Ok(Self {
                 ptr,
                 len: alloc_size,
+                live: 0,
             })
         } else {
             Err(io::Error::from(io::ErrorKind::OutOfMemory))
@@ -87,6 +97,7 @@ impl PtrLen {
             Ok(Self {
                 ptr: ptr as *mut u8,
                 len: region::page::ceil(size as *const ()) as usize,
+                live: 0,
             })
         } else {
             Err(io::Error::last_os_error())
@@ -110,7 +121,17 @@ impl Drop for PtrLen {
     }
 }
 
-// TODO: add a `Drop` impl for `cfg(target_os = "windows")`
+#[cfg(target_os = "windows")]
+impl Drop for PtrLen {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Memory::{MEM_RELEASE, VirtualFree};
+
+        if !self.ptr.is_null() {
+            let ok = unsafe { VirtualFree(self.ptr.cast(), 0, MEM_RELEASE) };
+            assert!(ok != 0, "unable to free memory");
+        }
+    }
+}
 
 /// JIT memory manager. This manages pages of suitably aligned and
 /// accessible memory. Memory will be leaked by default to have
@@ -136,8 +157,11 @@ impl Memory {
     }
 
     fn finish_current(&mut self) {
-        self.allocations
-            .push(mem::replace(&mut self.current, PtrLen::new()));
+        let current = mem::replace(&mut self.current, PtrLen::new());
+        // Regions without any live allocations are freed right away.
+        if current.live != 0 {
+            self.allocations.push(current);
+        }
         self.position = 0;
     }
 
@@ -152,6 +176,7 @@ impl Memory {
             // TODO: Ensure overflow is not possible.
             let ptr = unsafe { self.current.ptr.add(self.position) };
             self.position += size;
+            self.current.live += 1;
             return Ok(ptr);
         }
 
@@ -159,6 +184,7 @@ impl Memory {
 
         // TODO: Allocate more at a time.
         self.current = PtrLen::with_size(size)?;
+        self.current.live = 1;
         self.position = size;
 
         Ok(self.current.ptr)
@@ -211,6 +237,44 @@ impl Memory {
         return iter.filter(|&PtrLen { len, .. }| *len != 0);
     }
 
+    /// Frees a single allocation made by `allocate`.
+    ///
+    /// The memory region containing the allocation is freed once all of its
+    /// allocations have been freed. Returns an error if `ptr` wasn't allocated
+    /// by this `Memory`.
+    pub(crate) unsafe fn free(&mut self, ptr: *mut u8) -> io::Result<()> {
+        if self.current.contains(ptr) {
+            self.current.live -= 1;
+            if self.current.live == 0 {
+                // Nothing in the current region has been protected yet, so it
+                // can be freed right away.
+                self.current = PtrLen::new();
+                self.position = 0;
+            }
+            return Ok(());
+        }
+
+        let index = self
+            .allocations
+            .iter()
+            .rposition(|allocation| allocation.contains(ptr))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "pointer not allocated by this memory",
+                )
+            })?;
+        let allocation = &mut self.allocations[index];
+        allocation.live -= 1;
+        if allocation.live == 0 {
+            self.allocations.remove(index);
+            if index < self.already_protected {
+                self.already_protected -= 1;
+            }
+        }
+        Ok(())
+    }
+
     /// Frees all allocated memory regions that would be leaked otherwise.
     /// Likely to invalidate existing function pointers, causing unsafety.
     pub(crate) unsafe fn free_memory(&mut self) {
@@ -234,6 +298,12 @@ impl Drop for Memory {
 /// Note: Memory will be leaked by default unless
 /// [`JITMemoryProvider::free_memory`] is called to ensure function pointers
 /// remain valid for the remainder of the program's life.
+///
+/// Memory pages are returned to the system allocator once all allocations on
+/// them have been freed through [`JITMemoryProvider::free_readexec`] and
+/// friends. As allocations made before a call to
+/// [`JITMemoryProvider::finalize`] never share pages with later ones, freeing
+/// all functions and data objects finalized together reclaims their memory.
 pub struct SystemMemoryProvider {
     code: Memory,
     readonly: Memory,
@@ -258,6 +328,18 @@ impl JITMemoryProvider for SystemMemoryProvider {
         self.writable.free_memory();
     }
 
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, _size: usize) -> io::Result<()> {
+        self.code.free(ptr)
+    }
+
+    unsafe fn free_readwrite(&mut self, ptr: *mut u8, _size: usize) -> io::Result<()> {
+        self.writable.free(ptr)
+    }
+
+    unsafe fn free_readonly(&mut self, ptr: *mut u8, _size: usize) -> io::Result<()> {
+        self.readonly.free(ptr)
+    }
+
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()> {
         self.readonly.set_readonly()?;
         self.code.set_readable_and_executable(branch_protection)
@@ -0,0 +1,295 @@
+//! Thread-local data objects.
+//!
+//! A thread-local data object is represented by a [`TlsObject`] header, followed in memory by
+//! the initial contents of the object. The first time a thread accesses the object, it
+//! allocates its own copy of the object, initialized from these contents. The copies of a
+//! thread are freed when it exits.
+//!
+//! JIT code accesses thread-local data objects through the ELF general dynamic TLS model. The
+//! relocations of each access are resolved to a [`TlsDescriptor`] stored after the code of the
+//! function, see [`CompiledBlob`](crate::compiled_blob::CompiledBlob).
+
+use std::alloc::{self, Layout};
+use std::cell::RefCell;
+use std::ptr::{self, NonNull};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Whether thread-local data objects are supported on the host.
+pub(crate) const SUPPORTED: bool = cfg!(any(
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_os = "linux")
+));
+
+/// The keys of thread-local data objects.
+///
+/// The key of an object indexes the copies of each thread. Keys of freed objects are reused.
+static KEYS: Mutex<Keys> = Mutex::new(Keys {
+    next: 0,
+    free: Vec::new(),
+});
+
+struct Keys {
+    /// The smallest key which has never been used.
+    next: usize,
+    /// The keys of freed objects.
+    free: Vec<usize>,
+}
+
+/// The serial number of the next thread-local data object.
+///
+/// The copies of an object are tagged with its serial number, so that a thread never observes
+/// the copy of a freed object whose key has been reused. Such stale copies are freed once the
+/// thread accesses the object reusing the key, or when it exits.
+static NEXT_SERIAL: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static THREAD_OBJECTS: RefCell<Vec<Option<ThreadCopy>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The copy of a thread-local data object of a thread.
+struct ThreadCopy {
+    serial: u64,
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Drop for ThreadCopy {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+    }
+}
+
+/// The header of a thread-local data object.
+#[repr(C)]
+pub(crate) struct TlsObject {
+    key: usize,
+    serial: u64,
+    size: usize,
+    align: usize,
+}
+
+impl TlsObject {
+    /// Create the header of a new object of `size` bytes.
+    pub(crate) fn new(size: usize, align: usize) -> Self {
+        let mut keys = KEYS.lock().unwrap();
+        let key = keys.free.pop().unwrap_or_else(|| {
+            keys.next += 1;
+            keys.next - 1
+        });
+        Self {
+            key,
+            serial: NEXT_SERIAL.fetch_add(1, Ordering::Relaxed),
+            size,
+            align,
+        }
+    }
+
+    /// Free the copy of the object of the current thread, and make the key of the object
+    /// available for reuse.
+    ///
+    /// The copies of other threads are freed lazily, see [`NEXT_SERIAL`].
+    ///
+    /// # Safety
+    ///
+    /// The object must not be accessed afterwards.
+    pub(crate) unsafe fn free(&self) {
+        // The thread-local storage may already be destroyed if this runs during thread exit.
+        let _ = THREAD_OBJECTS.try_with(|objects| {
+            if let Some(copy) = objects.borrow_mut().get_mut(self.key) {
+                if copy.as_ref().is_some_and(|copy| copy.serial == self.serial) {
+                    *copy = None;
+                }
+            }
+        });
+        KEYS.lock().unwrap().free.push(self.key);
+    }
+
+    /// Returns the offset of the initial contents of an object with alignment `align` from the
+    /// start of its header.
+    pub(crate) fn init_offset(align: usize) -> usize {
+        size_of::<Self>().next_multiple_of(align)
+    }
+
+    /// Returns the size of the object.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the address of the copy of the object of the current thread, allocating it on
+    /// the first access.
+    pub(crate) fn get(&self) -> *mut u8 {
+        THREAD_OBJECTS.with(|objects| {
+            let objects = &mut *objects.borrow_mut();
+            if objects.len() <= self.key {
+                objects.resize_with(self.key + 1, || None);
+            }
+            let copy = &mut objects[self.key];
+            if copy.as_ref().is_some_and(|copy| copy.serial != self.serial) {
+                // The copy of a freed object with the same key.
+                *copy = None;
+            }
+            copy.get_or_insert_with(|| {
+                // Make sure to allocate at least 1 byte. Allocating 0 bytes is UB.
+                let layout = Layout::from_size_align(self.size.max(1), self.align).unwrap();
+                let ptr = unsafe { alloc::alloc(layout) };
+                let Some(ptr) = NonNull::new(ptr) else {
+                    alloc::handle_alloc_error(layout);
+                };
+                unsafe {
+                    let init = ptr::from_ref(self)
+                        .cast::<u8>()
+                        .add(Self::init_offset(self.align));
+                    ptr::copy_nonoverlapping(init, ptr.as_ptr(), self.size);
+                }
+                ThreadCopy {
+                    serial: self.serial,
+                    ptr,
+                    layout,
+                }
+            })
+            .ptr
+            .as_ptr()
+        })
+    }
+}
+
+/// A TLS descriptor, through which JIT code accesses a thread-local data object.
+///
+/// JIT code calls `resolver` with the address of the descriptor. On x86-64 the resolver
+/// returns the address of the object, and on AArch64 its offset from `tpidr_el0`.
+#[repr(C)]
+pub(crate) struct TlsDescriptor {
+    resolver: *const u8,
+    object: *const TlsObject,
+}
+
+impl TlsDescriptor {
+    /// Create a descriptor for the object with the header at `object`.
+    pub(crate) fn new(object: *const TlsObject) -> Self {
+        Self {
+            resolver: resolver(),
+            object,
+        }
+    }
+}
+
+/// Returns the resolver of TLS descriptors of the host.
+#[cfg(target_arch = "x86_64")]
+fn resolver() -> *const u8 {
+    resolve_x86_64 as *const u8
+}
+
+#[cfg(all(target_arch = "aarch64", target_os = "linux"))]
+fn resolver() -> *const u8 {
+    cranelift_jit_tlsdesc_resolver as *const u8
+}
+
+/// `JITModule` rejects thread-local data objects on other hosts, so no descriptors are
+/// created there.
+#[cfg(not(any(
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_os = "linux")
+)))]
+fn resolver() -> *const u8 {
+    ptr::null()
+}
+
+/// The resolver of TLS descriptors on x86-64.
+///
+/// JIT code calls it through a stub which jumps to the resolver of the descriptor in `rdi`,
+/// see [`CompiledBlob`](crate::compiled_blob::CompiledBlob).
+#[cfg(target_arch = "x86_64")]
+unsafe extern "C" fn resolve_x86_64(descriptor: *const TlsDescriptor) -> *mut u8 {
+    (*(*descriptor).object).get()
+}
+
+/// Returns the address of the copy of the thread-local data object `object` of the current
+/// thread.
+#[cfg(all(target_arch = "aarch64", target_os = "linux"))]
+unsafe extern "C" fn tls_get_addr(object: *const TlsObject) -> *mut u8 {
+    (*object).get()
+}
+
+#[cfg(all(target_arch = "aarch64", target_os = "linux"))]
+unsafe extern "C" {
+    /// The resolver of TLS descriptors on AArch64.
+    ///
+    /// The TLSDESC calling convention requires the resolver to preserve all registers except
+    /// `x0` and `x30`, so it saves all registers which `tls_get_addr` may clobber, including
+    /// the upper halves of the callee-saved vector registers.
+    fn cranelift_jit_tlsdesc_resolver();
+}
+
+#[cfg(all(target_arch = "aarch64", target_os = "linux"))]
+core::arch::global_asm!(
+    ".text",
+    ".p2align 2",
+    ".globl cranelift_jit_tlsdesc_resolver",
+    ".hidden cranelift_jit_tlsdesc_resolver",
+    ".type cranelift_jit_tlsdesc_resolver, %function",
+    "cranelift_jit_tlsdesc_resolver:",
+    // bti c
+    "hint #34",
+    "stp x29, x30, [sp, #-16]!",
+    "mov x29, sp",
+    "stp x1, x2, [sp, #-16]!",
+    "stp x3, x4, [sp, #-16]!",
+    "stp x5, x6, [sp, #-16]!",
+    "stp x7, x8, [sp, #-16]!",
+    "stp x9, x10, [sp, #-16]!",
+    "stp x11, x12, [sp, #-16]!",
+    "stp x13, x14, [sp, #-16]!",
+    "stp x15, x16, [sp, #-16]!",
+    "stp x17, x18, [sp, #-16]!",
+    "stp q0, q1, [sp, #-32]!",
+    "stp q2, q3, [sp, #-32]!",
+    "stp q4, q5, [sp, #-32]!",
+    "stp q6, q7, [sp, #-32]!",
+    "stp q8, q9, [sp, #-32]!",
+    "stp q10, q11, [sp, #-32]!",
+    "stp q12, q13, [sp, #-32]!",
+    "stp q14, q15, [sp, #-32]!",
+    "stp q16, q17, [sp, #-32]!",
+    "stp q18, q19, [sp, #-32]!",
+    "stp q20, q21, [sp, #-32]!",
+    "stp q22, q23, [sp, #-32]!",
+    "stp q24, q25, [sp, #-32]!",
+    "stp q26, q27, [sp, #-32]!",
+    "stp q28, q29, [sp, #-32]!",
+    "stp q30, q31, [sp, #-32]!",
+    // Load `TlsDescriptor::object`.
+    "ldr x0, [x0, #8]",
+    "bl {get_addr}",
+    "mrs x1, tpidr_el0",
+    "sub x0, x0, x1",
+    "ldp q30, q31, [sp], #32",
+    "ldp q28, q29, [sp], #32",
+    "ldp q26, q27, [sp], #32",
+    "ldp q24, q25, [sp], #32",
+    "ldp q22, q23, [sp], #32",
+    "ldp q20, q21, [sp], #32",
+    "ldp q18, q19, [sp], #32",
+    "ldp q16, q17, [sp], #32",
+    "ldp q14, q15, [sp], #32",
+    "ldp q12, q13, [sp], #32",
+    "ldp q10, q11, [sp], #32",
+    "ldp q8, q9, [sp], #32",
+    "ldp q6, q7, [sp], #32",
+    "ldp q4, q5, [sp], #32",
+    "ldp q2, q3, [sp], #32",
+    "ldp q0, q1, [sp], #32",
+    "ldp x17, x18, [sp], #16",
+    "ldp x15, x16, [sp], #16",
+    "ldp x13, x14, [sp], #16",
+    "ldp x11, x12, [sp], #16",
+    "ldp x9, x10, [sp], #16",
+    "ldp x7, x8, [sp], #16",
+    "ldp x5, x6, [sp], #16",
+    "ldp x3, x4, [sp], #16",
+    "ldp x1, x2, [sp], #16",
+    "ldp x29, x30, [sp], #16",
+    "ret",
+    ".size cranelift_jit_tlsdesc_resolver, . - cranelift_jit_tlsdesc_resolver",
+    get_addr = sym tls_get_addr,
+);
@@ -215,3 +215,372 @@ fn empty_data_object() {
     data.define(Box::new([]));
     module.define_data(data_id, &data).unwrap();
 }
+
+fn define_const_function(module: &mut JITModule, func_id: FuncId, value: i64) {
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(
+        UserFuncName::user(0, func_id.as_u32()),
+        module
+            .declarations()
+            .get_function_decl(func_id)
+            .signature
+            .clone(),
+    );
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let value = bcx.ins().iconst(types::I64, value);
+        bcx.ins().return_(&[value]);
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+}
+
+#[test]
+fn hotswap_redefine_function() {
+    let Some(isa) = isa() else {
+        return;
+    };
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.hotswap(true).unwrap();
+    let mut module = JITModule::new(builder);
+
+    let sig = Signature {
+        params: vec![],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: module.isa().default_call_conv(),
+    };
+    let callee_id = module
+        .declare_function("callee", Linkage::Local, &sig)
+        .unwrap();
+    let caller_id = module
+        .declare_function("caller", Linkage::Local, &sig)
+        .unwrap();
+
+    define_const_function(&mut module, callee_id, 1);
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, caller_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let callee = module.declare_func_in_func(callee_id, &mut bcx.func);
+        let call = bcx.ins().call(callee, &[]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+    }
+    module.define_function(caller_id, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let callee_ptr = module.get_finalized_function(callee_id);
+    let caller = unsafe {
+        std::mem::transmute::<*const u8, extern "C" fn() -> i64>(
+            module.get_finalized_function(caller_id),
+        )
+    };
+    assert_eq!(caller(), 1);
+
+    // The old definition stays in use until the new one is finalized.
+    define_const_function(&mut module, callee_id, 2);
+    assert_eq!(caller(), 1);
+
+    // Redefining a function which isn't finalized yet is still an error.
+    let mut ctx = Context::new();
+    ctx.func.signature = module
+        .declarations()
+        .get_function_decl(callee_id)
+        .signature
+        .clone();
+    assert!(matches!(
+        module.define_function(callee_id, &mut ctx),
+        Err(ModuleError::DuplicateDefinition(_))
+    ));
+
+    module.finalize_definitions().unwrap();
+    assert_eq!(caller(), 2);
+    assert_eq!(module.get_finalized_function(callee_id), callee_ptr);
+
+    unsafe {
+        module.free_retired_functions().unwrap();
+        module.free_memory();
+    }
+}
+
+fn free_and_redefine(memory: Box<dyn JITMemoryProvider>) {
+    let Some(isa) = isa() else {
+        return;
+    };
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.memory_provider(memory);
+    let mut module = JITModule::new(builder);
+
+    let sig = Signature {
+        params: vec![],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: module.isa().default_call_conv(),
+    };
+    let func_id = module
+        .declare_function("function", Linkage::Local, &sig)
+        .unwrap();
+    let data_id = module
+        .declare_data("data", Linkage::Local, false, false)
+        .unwrap();
+
+    for i in 0..1000 {
+        define_const_function(&mut module, func_id, i);
+        let mut data = DataDescription::new();
+        data.define(Box::new([i as u8]));
+        module.define_data(data_id, &data).unwrap();
+        module.finalize_definitions().unwrap();
+
+        let function = unsafe {
+            std::mem::transmute::<*const u8, extern "C" fn() -> i64>(
+                module.get_finalized_function(func_id),
+            )
+        };
+        assert_eq!(function(), i);
+        let (data_ptr, _) = module.get_finalized_data(data_id);
+        assert_eq!(unsafe { *data_ptr }, i as u8);
+
+        unsafe {
+            module.free_function(func_id).unwrap();
+            module.free_data_object(data_id).unwrap();
+        }
+    }
+}
+
+#[test]
+fn free_functions_and_data_objects() {
+    free_and_redefine(Box::new(SystemMemoryProvider::new()));
+    // Without reclaiming freed memory, each iteration would use up at least
+    // one page of this arena.
+    free_and_redefine(Box::new(
+        ArenaMemoryProvider::new_with_size(1 << 20).unwrap(),
+    ));
+}
+
+#[test]
+#[cfg(feature = "debug-info")]
+fn define_function_with_debug_info() {
+    let Some(isa) = isa() else {
+        return;
+    };
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    let sig = Signature {
+        params: vec![AbiParam::new(types::I64)],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: module.isa().default_call_conv(),
+    };
+    let func_id = module
+        .declare_function("double", Linkage::Local, &sig)
+        .unwrap();
+
+    let mut debug_info = FunctionDebugInfo::new();
+    let file = debug_info.add_file("double.foo");
+    debug_info.set_source_location(
+        SourceLoc::new(1),
+        SourceLocation {
+            file,
+            line: 1,
+            column: 1,
+        },
+    );
+    debug_info.add_variable("x", ValueLabel::new(0), types::I64);
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    ctx.func.dfg.collect_debug_info();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        bcx.set_val_label(x, ValueLabel::new(0));
+        bcx.set_srcloc(SourceLoc::new(1));
+        let doubled = bcx.ins().iadd(x, x);
+        bcx.ins().return_(&[doubled]);
+    }
+    module
+        .define_function_with_debug_info(func_id, &mut ctx, &debug_info)
+        .unwrap();
+    module.finalize_definitions().unwrap();
+
+    let double = unsafe {
+        std::mem::transmute::<*const u8, extern "C" fn(i64) -> i64>(
+            module.get_finalized_function(func_id),
+        )
+    };
+    assert_eq!(double(21), 42);
+
+    unsafe { module.free_function(func_id).unwrap() };
+}
+
+#[test]
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn thread_local_data_objects() {
+    let mut module = JITModule::new(JITBuilder::new(default_libcall_names()).unwrap());
+
+    let sig = Signature {
+        params: vec![],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: module.isa().default_call_conv(),
+    };
+    let func_id = module
+        .declare_function("bump", Linkage::Local, &sig)
+        .unwrap();
+    let a_id = module
+        .declare_data("a", Linkage::Local, true, true)
+        .unwrap();
+    let b_id = module
+        .declare_data("b", Linkage::Export, true, true)
+        .unwrap();
+
+    let mut data = DataDescription::new();
+    data.define(Box::new(5i64.to_ne_bytes()));
+    module.define_data(a_id, &data).unwrap();
+    let mut data = DataDescription::new();
+    data.define(Box::new(100i64.to_ne_bytes()));
+    module.define_data(b_id, &data).unwrap();
+
+    // Increment `a` by 1 and `b` by 10, and return their sum.
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let a = module.declare_data_in_func(a_id, &mut ctx.func);
+        let b = module.declare_data_in_func(b_id, &mut ctx.func);
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let mut sum = bcx.ins().iconst(types::I64, 0);
+        for (gv, increment) in [(a, 1), (b, 10)] {
+            let addr = bcx.ins().tls_value(types::I64, gv);
+            let value = bcx.ins().load(types::I64, MemFlags::trusted(), addr, 0);
+            let value = bcx.ins().iadd_imm(value, increment);
+            bcx.ins().store(MemFlags::trusted(), value, addr, 0);
+            sum = bcx.ins().iadd(sum, value);
+        }
+        bcx.ins().return_(&[sum]);
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let bump = unsafe {
+        std::mem::transmute::<*const u8, extern "C" fn() -> i64>(
+            module.get_finalized_function(func_id),
+        )
+    };
+    assert_eq!(bump(), 116);
+    assert_eq!(bump(), 127);
+
+    // Each thread has its own copy of the data objects.
+    std::thread::spawn(move || {
+        assert_eq!(bump(), 116);
+        assert_eq!(bump(), 127);
+        assert_eq!(bump(), 138);
+    })
+    .join()
+    .unwrap();
+
+    let (a_ptr, a_size) = module.get_finalized_data(a_id);
+    assert_eq!(a_size, 8);
+    assert_eq!(unsafe { *a_ptr.cast::<i64>() }, 7);
+    let (b_ptr, _) = module.get_finalized_data(b_id);
+    assert_eq!(unsafe { *b_ptr.cast::<i64>() }, 120);
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn define_thread_local_counter(module: &mut JITModule, func_id: FuncId, data_id: DataId) {
+    let mut data = DataDescription::new();
+    data.define(Box::new(5i64.to_ne_bytes()));
+    module.define_data(data_id, &data).unwrap();
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(
+        UserFuncName::user(0, func_id.as_u32()),
+        module
+            .declarations()
+            .get_function_decl(func_id)
+            .signature
+            .clone(),
+    );
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let counter = module.declare_data_in_func(data_id, &mut ctx.func);
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let addr = bcx.ins().tls_value(types::I64, counter);
+        let value = bcx.ins().load(types::I64, MemFlags::trusted(), addr, 0);
+        let value = bcx.ins().iadd_imm(value, 1);
+        bcx.ins().store(MemFlags::trusted(), value, addr, 0);
+        bcx.ins().return_(&[value]);
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+}
+
+#[test]
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn free_thread_local_data_object() {
+    use std::sync::mpsc;
+
+    let mut module = JITModule::new(JITBuilder::new(default_libcall_names()).unwrap());
+
+    let sig = Signature {
+        params: vec![],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: module.isa().default_call_conv(),
+    };
+    let func_id = module
+        .declare_function("count", Linkage::Local, &sig)
+        .unwrap();
+    let data_id = module
+        .declare_data("counter", Linkage::Local, true, true)
+        .unwrap();
+
+    // Another thread keeps running while the data object is freed and defined again, so its
+    // copy of the previous definition is only reclaimed lazily.
+    let (fn_tx, fn_rx) = mpsc::channel::<extern "C" fn() -> i64>();
+    let (result_tx, result_rx) = mpsc::channel();
+    let thread = std::thread::spawn(move || {
+        for count in fn_rx {
+            result_tx.send((count(), count())).unwrap();
+        }
+    });
+
+    for _ in 0..3 {
+        define_thread_local_counter(&mut module, func_id, data_id);
+        let count = unsafe {
+            std::mem::transmute::<*const u8, extern "C" fn() -> i64>(
+                module.get_finalized_function(func_id),
+            )
+        };
+        assert_eq!(count(), 6);
+        fn_tx.send(count).unwrap();
+        assert_eq!(result_rx.recv().unwrap(), (6, 7));
+
+        unsafe {
+            module.free_function(func_id).unwrap();
+            module.free_data_object(data_id).unwrap();
+        }
+    }
+
+    drop(fn_tx);
+    thread.join().unwrap();
+}
@@ -0,0 +1,84 @@
+//! Source-level debug information which frontends attach to functions.
+//!
+//! See [`Module::define_function_with_debug_info`](crate::Module::define_function_with_debug_info).
+
+use super::HashMap;
+use cranelift_codegen::entity::{PrimaryMap, entity_impl};
+use cranelift_codegen::ir;
+use std::string::String;
+use std::vec::Vec;
+
+/// A source file referenced by the debug info of a function.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DebugFileId(u32);
+entity_impl!(DebugFileId, "file");
+
+/// A position in a source file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The source file.
+    pub file: DebugFileId,
+    /// The line number, starting at 1. Line 0 means no particular line.
+    pub line: u32,
+    /// The column number, starting at 1. Column 0 means no particular column.
+    pub column: u32,
+}
+
+/// A source-level variable.
+///
+/// The value of the variable is tracked through the values labeled with `label`, see
+/// [`DataFlowGraph::collect_debug_info`](ir::DataFlowGraph::collect_debug_info).
+#[derive(Clone, Debug)]
+pub struct VariableDescription {
+    /// The name of the variable.
+    pub name: String,
+    /// The label of the values holding the variable.
+    pub label: ir::ValueLabel,
+    /// The type of the values holding the variable.
+    pub ty: ir::Type,
+}
+
+/// Debug information for a function.
+///
+/// Instructions of the function are mapped to source locations through their
+/// [`ir::SourceLoc`], which the frontend chooses freely.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionDebugInfo {
+    /// The source files referenced by this function.
+    pub files: PrimaryMap<DebugFileId, String>,
+    /// The location at which the function is declared.
+    pub decl_location: Option<SourceLocation>,
+    /// The source locations of the instructions of the function.
+    pub source_locations: HashMap<ir::SourceLoc, SourceLocation>,
+    /// The variables of the function.
+    pub variables: Vec<VariableDescription>,
+}
+
+impl FunctionDebugInfo {
+    /// Create empty debug information.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source file, returning the existing id if `path` was already added.
+    pub fn add_file(&mut self, path: &str) -> DebugFileId {
+        match self.files.iter().find(|(_, file)| *file == path) {
+            Some((id, _)) => id,
+            None => self.files.push(path.into()),
+        }
+    }
+
+    /// Map instructions with the source location `srcloc` to `location`.
+    pub fn set_source_location(&mut self, srcloc: ir::SourceLoc, location: SourceLocation) {
+        self.source_locations.insert(srcloc, location);
+    }
+
+    /// Add a variable whose values are labeled with `label`.
+    pub fn add_variable(&mut self, name: &str, label: ir::ValueLabel, ty: ir::Type) {
+        self.variables.push(VariableDescription {
+            name: name.into(),
+            label,
+            ty,
+        });
+    }
+}
@@ -0,0 +1,464 @@
+//! Emission of DWARF debug info for functions defined with [`FunctionDebugInfo`].
+
+use super::HashMap;
+use crate::debug::{DebugFileId, FunctionDebugInfo, SourceLocation};
+use crate::module::{FuncId, FunctionDeclaration, Linkage, ModuleError, ModuleResult};
+use cranelift_codegen::binemit::CodeOffset;
+use cranelift_codegen::entity::{EntityRef, PrimaryMap};
+use cranelift_codegen::gimli::write::{
+    Address, AttributeValue, DwarfUnit, EndianVec, Expression, FileId, FrameTable, LineProgram,
+    LineString, Location, LocationList, Range, RangeList, Sections, UnitEntryId, Writer,
+};
+use cranelift_codegen::gimli::{self, Encoding, Format, LineEncoding, Register, RunTimeEndian};
+use cranelift_codegen::ir::{self, Endianness};
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::isa::unwind::{CfaUnwindInfo, UnwindInfo};
+use cranelift_codegen::{CompiledCode, LabelValueLoc};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// A section of DWARF debug info produced by [`DwarfBuilder::emit`].
+pub struct DwarfSection {
+    /// The name of the section, e.g. `.debug_info`.
+    pub name: &'static str,
+    /// The contents of the section.
+    pub data: Vec<u8>,
+    /// The relocations to apply to the contents.
+    pub relocs: Vec<DwarfReloc>,
+}
+
+/// A relocation in a [`DwarfSection`].
+#[derive(Clone, Debug)]
+pub struct DwarfReloc {
+    /// The offset of the relocated value in the section.
+    pub offset: u32,
+    /// The size of the relocated value in bytes.
+    pub size: u8,
+    /// The target of the relocation.
+    pub target: DwarfRelocTarget,
+    /// The addend. This value is also stored at `offset`.
+    pub addend: i64,
+}
+
+/// The target of a [`DwarfReloc`].
+#[derive(Clone, Debug)]
+pub enum DwarfRelocTarget {
+    /// The address of the start of a function.
+    Function(FuncId),
+    /// The start of another debug section, given by its name. Offsets into other debug sections
+    /// are already correct when all sections are emitted together, but need relocating when they
+    /// may be merged with the debug sections of other objects by a linker.
+    Section(&'static str),
+}
+
+/// The debug info of a function, with the offsets into its compiled code resolved.
+struct DwarfFunction {
+    func_id: FuncId,
+    name: String,
+    external: bool,
+    size: CodeOffset,
+    files: PrimaryMap<DebugFileId, String>,
+    decl_location: Option<SourceLocation>,
+    rows: Vec<(CodeOffset, Option<SourceLocation>)>,
+    variables: Vec<DwarfVariable>,
+    unwind_info: Option<CfaUnwindInfo>,
+}
+
+struct DwarfVariable {
+    name: String,
+    ty: ir::Type,
+    ranges: Vec<(CodeOffset, CodeOffset, Expression)>,
+}
+
+/// Collects the debug info of compiled functions and emits it as DWARF sections.
+///
+/// The emitted sections contain a single compilation unit with `.debug_line`, `.debug_info` and
+/// supporting sections, and a `.debug_frame` section with the call frame information of the
+/// functions.
+pub struct DwarfBuilder {
+    encoding: Encoding,
+    endian: RunTimeEndian,
+    cie: Option<gimli::write::CommonInformationEntry>,
+    functions: Vec<DwarfFunction>,
+}
+
+impl DwarfBuilder {
+    /// Create a new builder for functions compiled for `isa`.
+    pub fn new(isa: &dyn TargetIsa) -> Self {
+        Self {
+            encoding: Encoding {
+                format: Format::Dwarf32,
+                version: 4,
+                address_size: isa.pointer_bytes(),
+            },
+            endian: match isa.endianness() {
+                Endianness::Little => RunTimeEndian::Little,
+                Endianness::Big => RunTimeEndian::Big,
+            },
+            cie: isa.create_systemv_cie(),
+            functions: Vec::new(),
+        }
+    }
+
+    /// Returns whether no functions have been added.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Add the debug info of the function `func_id`, whose compiled code is `compiled`.
+    ///
+    /// Variables are only described if the value labels of the function were collected before
+    /// compiling it.
+    pub fn add_function(
+        &mut self,
+        isa: &dyn TargetIsa,
+        func_id: FuncId,
+        decl: &FunctionDeclaration,
+        compiled: &CompiledCode,
+        debug_info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        let mut rows = Vec::new();
+        for srcloc in compiled.buffer.get_srclocs_sorted() {
+            let location = debug_info.source_locations.get(&srcloc.loc).copied();
+            if rows.last().map(|&(_, prev)| prev) != Some(location) {
+                rows.push((srcloc.start, location));
+            }
+        }
+
+        let variables = debug_info
+            .variables
+            .iter()
+            .map(|var| {
+                let ranges = compiled
+                    .value_labels_ranges
+                    .get(&var.label)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|range| {
+                        let mut expr = Expression::new();
+                        match range.loc {
+                            LabelValueLoc::Reg(reg) => {
+                                let reg = isa.map_regalloc_reg_to_dwarf(reg).ok()?;
+                                expr.op_reg(Register(reg));
+                            }
+                            LabelValueLoc::CFAOffset(offset) => expr.op_fbreg(offset),
+                        }
+                        Some((range.start, range.end, expr))
+                    })
+                    .collect();
+                DwarfVariable {
+                    name: var.name.clone(),
+                    ty: var.ty,
+                    ranges,
+                }
+            })
+            .collect();
+
+        let unwind_info = match compiled.create_unwind_info(isa)? {
+            Some(UnwindInfo::SystemV(info)) => Some(info),
+            _ => None,
+        };
+
+        self.functions.push(DwarfFunction {
+            func_id,
+            name: decl.linkage_name(func_id).into_owned(),
+            external: decl.linkage != Linkage::Local,
+            size: compiled.buffer.total_size(),
+            files: debug_info.files.clone(),
+            decl_location: debug_info.decl_location,
+            rows,
+            variables,
+            unwind_info,
+        });
+        Ok(())
+    }
+
+    /// Emit the debug info of all added functions, using `name` as the name of the compilation
+    /// unit.
+    pub fn emit(&self, name: &str) -> ModuleResult<Vec<DwarfSection>> {
+        let mut dwarf = DwarfUnit::new(self.encoding);
+        let mut line_program = LineProgram::new(
+            self.encoding,
+            LineEncoding::default(),
+            LineString::String(b".".to_vec()),
+            None,
+            LineString::String(name.as_bytes().to_vec()),
+            None,
+        );
+
+        let root = dwarf.unit.root();
+        let producer = dwarf.strings.add("cranelift");
+        let cu_name = dwarf.strings.add(name);
+        let comp_dir = dwarf.strings.add(".");
+        let root_entry = dwarf.unit.get_mut(root);
+        root_entry.set(gimli::DW_AT_producer, AttributeValue::StringRef(producer));
+        root_entry.set(gimli::DW_AT_name, AttributeValue::StringRef(cu_name));
+        root_entry.set(gimli::DW_AT_comp_dir, AttributeValue::StringRef(comp_dir));
+        root_entry.set(
+            gimli::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0)),
+        );
+
+        let mut base_types = HashMap::new();
+        let mut ranges = Vec::new();
+        for func in &self.functions {
+            let address = function_address(func.func_id, 0);
+            ranges.push(Range::StartLength {
+                begin: address,
+                length: u64::from(func.size),
+            });
+
+            let files = func
+                .files
+                .values()
+                .map(|path| add_file(&mut line_program, path))
+                .collect::<Vec<_>>();
+
+            line_program.begin_sequence(Some(address));
+            for &(offset, location) in &func.rows {
+                let row = line_program.row();
+                row.address_offset = u64::from(offset);
+                match location {
+                    Some(location) => {
+                        row.file = files[location.file.index()];
+                        row.line = u64::from(location.line);
+                        row.column = u64::from(location.column);
+                    }
+                    None => {
+                        row.line = 0;
+                        row.column = 0;
+                    }
+                }
+                line_program.generate_row();
+            }
+            line_program.end_sequence(u64::from(func.size));
+
+            let subprogram = dwarf.unit.add(root, gimli::DW_TAG_subprogram);
+            let func_name = dwarf.strings.add(func.name.as_str());
+            let mut frame_base = Expression::new();
+            frame_base.op(gimli::DW_OP_call_frame_cfa);
+            let entry = dwarf.unit.get_mut(subprogram);
+            entry.set(gimli::DW_AT_name, AttributeValue::StringRef(func_name));
+            entry.set(gimli::DW_AT_low_pc, AttributeValue::Address(address));
+            entry.set(
+                gimli::DW_AT_high_pc,
+                AttributeValue::Udata(u64::from(func.size)),
+            );
+            entry.set(gimli::DW_AT_frame_base, AttributeValue::Exprloc(frame_base));
+            if func.external {
+                entry.set(gimli::DW_AT_external, AttributeValue::Flag(true));
+            }
+            if let Some(location) = func.decl_location {
+                entry.set(
+                    gimli::DW_AT_decl_file,
+                    AttributeValue::FileIndex(Some(files[location.file.index()])),
+                );
+                entry.set(
+                    gimli::DW_AT_decl_line,
+                    AttributeValue::Udata(u64::from(location.line)),
+                );
+            }
+
+            for var in &func.variables {
+                let locations = var
+                    .ranges
+                    .iter()
+                    .map(|(start, end, expr)| Location::StartEnd {
+                        begin: function_address(func.func_id, *start),
+                        end: function_address(func.func_id, *end),
+                        data: expr.clone(),
+                    })
+                    .collect();
+                let locations = dwarf.unit.locations.add(LocationList(locations));
+                let ty = base_type(&mut dwarf, &mut base_types, var.ty);
+                let var_name = dwarf.strings.add(var.name.as_str());
+
+                let variable = dwarf.unit.add(subprogram, gimli::DW_TAG_variable);
+                let entry = dwarf.unit.get_mut(variable);
+                entry.set(gimli::DW_AT_name, AttributeValue::StringRef(var_name));
+                entry.set(
+                    gimli::DW_AT_location,
+                    AttributeValue::LocationListRef(locations),
+                );
+                if let Some(ty) = ty {
+                    entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(ty));
+                }
+            }
+        }
+        let ranges = dwarf.unit.ranges.add(RangeList(ranges));
+        dwarf
+            .unit
+            .get_mut(root)
+            .set(gimli::DW_AT_ranges, AttributeValue::RangeListRef(ranges));
+        dwarf.unit.line_program = line_program;
+
+        let mut sections = Sections::new(WriterRelocate {
+            relocs: Vec::new(),
+            writer: EndianVec::new(self.endian),
+        });
+        dwarf.write(&mut sections).map_err(dwarf_error)?;
+
+        if let Some(cie) = &self.cie {
+            let mut frames = FrameTable::default();
+            let cie = frames.add_cie(cie.clone());
+            for func in &self.functions {
+                if let Some(info) = &func.unwind_info {
+                    frames.add_fde(cie, info.to_fde(function_address(func.func_id, 0)));
+                }
+            }
+            frames
+                .write_debug_frame(&mut sections.debug_frame)
+                .map_err(dwarf_error)?;
+        }
+
+        let mut result = Vec::new();
+        sections
+            .for_each_mut(|id, section| -> gimli::write::Result<()> {
+                let data = section.writer.take();
+                if !data.is_empty() {
+                    result.push(DwarfSection {
+                        name: id.name(),
+                        data,
+                        relocs: std::mem::take(&mut section.relocs),
+                    });
+                }
+                Ok(())
+            })
+            .map_err(dwarf_error)?;
+        Ok(result)
+    }
+}
+
+fn dwarf_error(err: gimli::write::Error) -> ModuleError {
+    ModuleError::Backend(anyhow::Error::new(err).context("failed to emit DWARF"))
+}
+
+fn function_address(func_id: FuncId, offset: CodeOffset) -> Address {
+    Address::Symbol {
+        symbol: func_id.as_u32() as usize,
+        addend: i64::from(offset),
+    }
+}
+
+fn add_file(line_program: &mut LineProgram, path: &str) -> FileId {
+    let (directory, file) = match path.rfind('/') {
+        Some(0) => (Some("/"), &path[1..]),
+        Some(index) => (Some(&path[..index]), &path[index + 1..]),
+        None => (None, path),
+    };
+    let directory = match directory {
+        Some(directory) => {
+            line_program.add_directory(LineString::String(directory.as_bytes().to_vec()))
+        }
+        None => line_program.default_directory(),
+    };
+    line_program.add_file(
+        LineString::String(file.as_bytes().to_vec()),
+        directory,
+        None,
+    )
+}
+
+/// Returns the base type describing values of type `ty`, if any.
+fn base_type(
+    dwarf: &mut DwarfUnit,
+    base_types: &mut HashMap<ir::Type, UnitEntryId>,
+    ty: ir::Type,
+) -> Option<UnitEntryId> {
+    let encoding = if ty.is_int() {
+        gimli::DW_ATE_signed
+    } else if ty.is_float() {
+        gimli::DW_ATE_float
+    } else {
+        return None;
+    };
+    if let Some(&id) = base_types.get(&ty) {
+        return Some(id);
+    }
+
+    let root = dwarf.unit.root();
+    let id = dwarf.unit.add(root, gimli::DW_TAG_base_type);
+    let name = dwarf.strings.add(ty.to_string());
+    let entry = dwarf.unit.get_mut(id);
+    entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name));
+    entry.set(gimli::DW_AT_encoding, AttributeValue::Encoding(encoding));
+    entry.set(
+        gimli::DW_AT_byte_size,
+        AttributeValue::Udata(u64::from(ty.bytes())),
+    );
+    base_types.insert(ty, id);
+    Some(id)
+}
+
+/// A `Writer` which records relocations for addresses and section offsets.
+#[derive(Clone)]
+struct WriterRelocate {
+    relocs: Vec<DwarfReloc>,
+    writer: EndianVec<RunTimeEndian>,
+}
+
+impl Writer for WriterRelocate {
+    type Endian = RunTimeEndian;
+
+    fn endian(&self) -> Self::Endian {
+        self.writer.endian()
+    }
+
+    fn len(&self) -> usize {
+        self.writer.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.writer.write(bytes)
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.writer.write_at(offset, bytes)
+    }
+
+    fn write_address(&mut self, address: Address, size: u8) -> gimli::write::Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata(val, size),
+            Address::Symbol { symbol, addend } => {
+                self.relocs.push(DwarfReloc {
+                    offset: self.len() as u32,
+                    size,
+                    target: DwarfRelocTarget::Function(FuncId::from_u32(symbol as u32)),
+                    addend,
+                });
+                self.write_udata(addend as u64, size)
+            }
+        }
+    }
+
+    fn write_offset(
+        &mut self,
+        val: usize,
+        section: gimli::SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        self.relocs.push(DwarfReloc {
+            offset: self.len() as u32,
+            size,
+            target: DwarfRelocTarget::Section(section.name()),
+            addend: val as i64,
+        });
+        self.write_udata(val as u64, size)
+    }
+
+    fn write_offset_at(
+        &mut self,
+        offset: usize,
+        val: usize,
+        section: gimli::SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        self.relocs.push(DwarfReloc {
+            offset: offset as u32,
+            size,
+            target: DwarfRelocTarget::Section(section.name()),
+            addend: val as i64,
+        });
+        self.write_udata_at(offset, val as u64, size)
+    }
+}
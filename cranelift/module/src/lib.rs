@@ -21,10 +21,16 @@ use std::string::String;
 use cranelift_codegen::ir;
 
 mod data_context;
+mod debug;
+#[cfg(feature = "debug")]
+mod dwarf;
 mod module;
 mod traps;
 
 pub use crate::data_context::{DataDescription, Init};
+pub use crate::debug::{DebugFileId, FunctionDebugInfo, SourceLocation, VariableDescription};
+#[cfg(feature = "debug")]
+pub use crate::dwarf::{DwarfBuilder, DwarfReloc, DwarfRelocTarget, DwarfSection};
 pub use crate::module::{
     DataDeclaration, DataId, FuncId, FuncOrDataId, FunctionDeclaration, Linkage, Module,
     ModuleDeclarations, ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
@@ -7,6 +7,7 @@
 
 use super::HashMap;
 use crate::data_context::DataDescription;
+use crate::debug::FunctionDebugInfo;
 use core::fmt::Display;
 use cranelift_codegen::binemit::{CodeOffset, Reloc};
 use cranelift_codegen::entity::{PrimaryMap, entity_impl};
@@ -964,6 +965,22 @@ pub trait Module {
         ctrl_plane: &mut ControlPlane,
     ) -> ModuleResult<()>;
 
+    /// Define a function, producing the function body from the given `Context`, and attach the
+    /// source-level debug info `debug_info` to it.
+    ///
+    /// Modules which don't support debug info ignore `debug_info`.
+    ///
+    /// Note: After calling this function the given `Context` will contain the compiled function.
+    fn define_function_with_debug_info(
+        &mut self,
+        func: FuncId,
+        ctx: &mut Context,
+        debug_info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        let _ = debug_info;
+        self.define_function(func, ctx)
+    }
+
     /// Define a function, taking the function body from the given `bytes`.
     ///
     /// This function is generally only useful if you need to precisely specify
@@ -1072,6 +1089,15 @@ impl<M: Module + ?Sized> Module for &mut M {
         (**self).define_function_with_control_plane(func, ctx, ctrl_plane)
     }
 
+    fn define_function_with_debug_info(
+        &mut self,
+        func: FuncId,
+        ctx: &mut Context,
+        debug_info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        (**self).define_function_with_debug_info(func, ctx, debug_info)
+    }
+
     fn define_function_bytes(
         &mut self,
         func_id: FuncId,
@@ -1176,6 +1202,15 @@ impl<M: Module + ?Sized> Module for Box<M> {
         (**self).define_function_with_control_plane(func, ctx, ctrl_plane)
     }
 
+    fn define_function_with_debug_info(
+        &mut self,
+        func: FuncId,
+        ctx: &mut Context,
+        debug_info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        (**self).define_function_with_debug_info(func, ctx, debug_info)
+    }
+
     fn define_function_bytes(
         &mut self,
         func_id: FuncId,
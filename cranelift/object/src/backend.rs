@@ -10,6 +10,8 @@ use cranelift_module::{
     DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
     ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
+#[cfg(feature = "debug-info")]
+use cranelift_module::{DwarfBuilder, DwarfRelocTarget, FunctionDebugInfo};
 use log::info;
 use object::write::{
     Object, Relocation, SectionId, StandardSection, Symbol, SymbolId, SymbolSection,
@@ -156,6 +158,10 @@ pub struct ObjectModule {
     known_labels: HashMap<(FuncId, CodeOffset), SymbolId>,
     per_function_section: bool,
     per_data_object_section: bool,
+    #[cfg(feature = "debug-info")]
+    name: String,
+    #[cfg(feature = "debug-info")]
+    debug_info: DwarfBuilder,
 }
 
 impl ObjectModule {
@@ -164,7 +170,11 @@ impl ObjectModule {
         let mut object = Object::new(builder.binary_format, builder.architecture, builder.endian);
         object.flags = builder.flags;
         object.set_subsections_via_symbols();
+        #[cfg(feature = "debug-info")]
+        let name = String::from_utf8_lossy(&builder.name).into_owned();
         object.add_file_symbol(builder.name);
+        #[cfg(feature = "debug-info")]
+        let debug_info = DwarfBuilder::new(&*builder.isa);
         Self {
             isa: builder.isa,
             object,
@@ -178,6 +188,10 @@ impl ObjectModule {
             known_labels: HashMap::new(),
             per_function_section: builder.per_function_section,
             per_data_object_section: builder.per_data_object_section,
+            #[cfg(feature = "debug-info")]
+            name,
+            #[cfg(feature = "debug-info")]
+            debug_info,
         }
     }
 }
@@ -356,6 +370,29 @@ impl Module for ObjectModule {
         self.define_function_inner(func_id, alignment, buffer.data(), relocs)
     }
 
+    #[cfg(feature = "debug-info")]
+    fn define_function_with_debug_info(
+        &mut self,
+        func_id: FuncId,
+        ctx: &mut cranelift_codegen::Context,
+        debug_info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        if self.object.format() != object::BinaryFormat::Elf {
+            return Err(ModuleError::Backend(anyhow!(
+                "debug info is only supported for ELF objects"
+            )));
+        }
+
+        self.define_function(func_id, ctx)?;
+        self.debug_info.add_function(
+            &*self.isa,
+            func_id,
+            self.declarations.get_function_decl(func_id),
+            ctx.compiled_code().unwrap(),
+            debug_info,
+        )
+    }
+
     fn define_function_bytes(
         &mut self,
         func_id: FuncId,
@@ -561,6 +598,9 @@ impl ObjectModule {
     }
 
     /// Finalize all relocations and output an object.
+    ///
+    /// The debug info of functions defined with debug info is only included if
+    /// [`ObjectModule::emit_debug_info`] has been called before.
     pub fn finish(mut self) -> ObjectProduct {
         let symbol_relocs = mem::take(&mut self.relocs);
         for symbol in symbol_relocs {
@@ -602,6 +642,60 @@ impl ObjectModule {
         }
     }
 
+    /// Add the DWARF sections describing the functions defined with debug info.
+    ///
+    /// This must be called after all functions with debug info have been
+    /// defined, and before [`ObjectModule::finish`].
+    ///
+    /// Returns ModuleError if the debug info can't be encoded.
+    #[cfg(feature = "debug-info")]
+    pub fn emit_debug_info(&mut self) -> ModuleResult<()> {
+        if self.debug_info.is_empty() {
+            return Ok(());
+        }
+        let sections = self.debug_info.emit(&self.name)?;
+        self.debug_info = DwarfBuilder::new(&*self.isa);
+
+        let mut section_ids = HashMap::new();
+        for section in &sections {
+            let section_id = self.object.add_section(
+                vec![],
+                section.name.as_bytes().to_vec(),
+                SectionKind::Debug,
+            );
+            self.object
+                .set_section_data(section_id, section.data.clone(), 1);
+            section_ids.insert(section.name, section_id);
+        }
+
+        for section in &sections {
+            for reloc in &section.relocs {
+                let symbol = match reloc.target {
+                    DwarfRelocTarget::Function(func_id) => self.functions[func_id].unwrap().0,
+                    DwarfRelocTarget::Section(name) => {
+                        self.object.section_symbol(section_ids[name])
+                    }
+                };
+                self.object
+                    .add_relocation(
+                        section_ids[section.name],
+                        Relocation {
+                            offset: u64::from(reloc.offset),
+                            flags: RelocationFlags::Generic {
+                                kind: RelocationKind::Absolute,
+                                encoding: RelocationEncoding::Generic,
+                                size: reloc.size * 8,
+                            },
+                            symbol,
+                            addend: reloc.addend,
+                        },
+                    )
+                    .map_err(|e| ModuleError::Backend(anyhow::Error::new(e)))?;
+            }
+        }
+        Ok(())
+    }
+
     /// This should only be called during finish because it creates
     /// symbols for missing libcalls.
     fn get_symbol(&mut self, name: &ModuleRelocTarget) -> SymbolId {
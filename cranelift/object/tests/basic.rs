@@ -239,3 +239,107 @@ fn reject_nul_byte_symbol_for_data() {
         )
         .unwrap();
 }
+
+#[test]
+#[cfg(feature = "debug-info")]
+fn debug_info() {
+    use object::{Object, ObjectSection};
+
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut module =
+        ObjectModule::new(ObjectBuilder::new(isa, "foo", default_libcall_names()).unwrap());
+
+    let sig = Signature {
+        params: vec![AbiParam::new(types::I64)],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: CallConv::SystemV,
+    };
+    let func_id = module
+        .declare_function("double", Linkage::Export, &sig)
+        .unwrap();
+
+    let mut debug_info = FunctionDebugInfo::new();
+    let file = debug_info.add_file("src/double.foo");
+    debug_info.decl_location = Some(SourceLocation {
+        file,
+        line: 1,
+        column: 1,
+    });
+    for line in [2, 3] {
+        debug_info.set_source_location(
+            SourceLoc::new(line),
+            SourceLocation {
+                file,
+                line,
+                column: 5,
+            },
+        );
+    }
+    debug_info.add_variable("x", ValueLabel::new(0), types::I64);
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    ctx.func.dfg.collect_debug_info();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        bcx.set_val_label(x, ValueLabel::new(0));
+        bcx.set_srcloc(SourceLoc::new(2));
+        let doubled = bcx.ins().iadd(x, x);
+        bcx.set_srcloc(SourceLoc::new(3));
+        bcx.ins().return_(&[doubled]);
+    }
+    module
+        .define_function_with_debug_info(func_id, &mut ctx, &debug_info)
+        .unwrap();
+
+    module.emit_debug_info().unwrap();
+    let bytes = module.finish().emit().unwrap();
+    let file = object::File::parse(&*bytes).unwrap();
+    let dwarf = gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+        let data = file
+            .section_by_name(id.name())
+            .map(|section| section.data().unwrap())
+            .unwrap_or(&[]);
+        Ok(gimli::EndianSlice::new(data, gimli::LittleEndian))
+    })
+    .unwrap();
+    assert!(file.section_by_name(".debug_frame").is_some());
+
+    let header = dwarf.units().next().unwrap().unwrap();
+    let unit = dwarf.unit(header).unwrap();
+
+    let program = unit.line_program.clone().unwrap();
+    let mut rows = program.rows();
+    let mut lines = Vec::new();
+    while let Some((header, row)) = rows.next_row().unwrap() {
+        if row.end_sequence() {
+            continue;
+        }
+        let file = row.file(header).unwrap();
+        let name = dwarf.attr_string(&unit, file.path_name()).unwrap();
+        lines.push((name.to_string().unwrap().to_owned(), row.line()));
+    }
+    assert!(lines.contains(&("double.foo".to_owned(), std::num::NonZeroU64::new(2))));
+    assert!(lines.contains(&("double.foo".to_owned(), std::num::NonZeroU64::new(3))));
+
+    let mut names = Vec::new();
+    let mut entries = unit.entries();
+    while let Some((_, entry)) = entries.next_dfs().unwrap() {
+        if let Some(name) = entry.attr_value(gimli::DW_AT_name).unwrap() {
+            let name = dwarf.attr_string(&unit, name).unwrap();
+            names.push((entry.tag(), name.to_string().unwrap().to_owned()));
+        }
+    }
+    assert!(names.contains(&(gimli::DW_TAG_subprogram, "double".to_owned())));
+    assert!(names.contains(&(gimli::DW_TAG_variable, "x".to_owned())));
+    assert!(names.contains(&(gimli::DW_TAG_base_type, "i64".to_owned())));
+}
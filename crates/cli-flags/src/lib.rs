@@ -211,13 +211,15 @@ wasmtime_option_group! {
         #[serde(default)]
         #[serde(deserialize_with = "crate::opt::cli_parse_wrapper")]
         pub compiler: Option<wasmtime::Strategy>,
-        /// Which garbage collector to use: `drc` or `null`.
+        /// Which garbage collector to use: `drc`, `null`, or `generational`.
         ///
         /// `drc` is the deferred reference-counting collector.
         ///
         /// `null` is the null garbage collector, which does not collect any
         /// garbage.
         ///
+        /// `generational` is the generational, compacting collector.
+        ///
         /// Note that not all builds of Wasmtime will have support for garbage
         /// collection included.
         #[serde(default)]
@@ -236,6 +238,9 @@ wasmtime_option_group! {
         /// Controls whether native unwind information is present in compiled
         /// object files.
         pub native_unwind_info: Option<bool>,
+        /// Whether or not to recompile hot functions compiled with Winch with
+        /// Cranelift at runtime.
+        pub tiered_compilation: Option<bool>,
 
         #[prefixed = "cranelift"]
         #[serde(default)]
@@ -474,6 +479,11 @@ wasmtime_option_group! {
         /// Preset data for the In-Memory provider of WASI key-value API.
         #[serde(skip)]
         pub keyvalue_in_memory_data: Vec<KeyValuePair>,
+        /// Make a store of the WASI key-value API available under an
+        /// identifier, where the store is either `memory` or `file:<path>`
+        /// to persist its contents to the host file `<path>`.
+        #[serde(skip)]
+        pub keyvalue_store: Vec<WasiKeyValueStore>,
     }
 
     enum Wasi {
@@ -487,6 +497,18 @@ pub struct WasiNnGraph {
     pub dir: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasiKeyValueStore {
+    pub identifier: String,
+    pub backend: WasiKeyValueBackend,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WasiKeyValueBackend {
+    Memory,
+    File(PathBuf),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct KeyValuePair {
     pub key: String,
@@ -719,6 +741,11 @@ impl CommonOptions {
             enable => config.cranelift_pcc(enable),
             true => err,
         }
+        match_feature! {
+            ["cranelift" : self.codegen.tiered_compilation]
+            enable => config.tiered_compilation(enable),
+            true => err,
+        }
 
         self.enable_wasm_features(&mut config)?;
 
@@ -1168,6 +1195,7 @@ mod tests {
                 Some(wasmtime::Collector::DeferredReferenceCounting),
             ),
             ("\"null\"", Some(wasmtime::Collector::Null)),
+            ("\"generational\"", Some(wasmtime::Collector::Generational)),
             ("\"hello\"", None), // should fail
             ("5", None),         // should fail
             ("true", None),      // should fail
@@ -5,7 +5,7 @@
 //! specifying options in a struct-like syntax where all other boilerplate about
 //! option parsing is contained exclusively within this module.
 
-use crate::{KeyValuePair, WasiNnGraph};
+use crate::{KeyValuePair, WasiKeyValueBackend, WasiKeyValueStore, WasiNnGraph};
 use anyhow::{Result, bail};
 use clap::builder::{StringValueParser, TypedValueParser, ValueParserFactory};
 use clap::error::{Error, ErrorKind};
@@ -517,12 +517,15 @@ impl WasmtimeOptionValue for wasmtime::Strategy {
 }
 
 impl WasmtimeOptionValue for wasmtime::Collector {
-    const VAL_HELP: &'static str = "=drc|null";
+    const VAL_HELP: &'static str = "=drc|null|generational";
     fn parse(val: Option<&str>) -> Result<Self> {
         match String::parse(val)?.as_str() {
             "drc" => Ok(wasmtime::Collector::DeferredReferenceCounting),
             "null" => Ok(wasmtime::Collector::Null),
-            other => bail!("unknown collector `{other}` only `drc` and `null` accepted",),
+            "generational" => Ok(wasmtime::Collector::Generational),
+            other => {
+                bail!("unknown collector `{other}` only `drc`, `null`, and `generational` accepted",)
+            }
         }
     }
 
@@ -530,6 +533,7 @@ impl WasmtimeOptionValue for wasmtime::Collector {
         match *self {
             wasmtime::Collector::DeferredReferenceCounting => f.write_str("drc"),
             wasmtime::Collector::Null => f.write_str("null"),
+            wasmtime::Collector::Generational => f.write_str("generational"),
             _ => unreachable!(),
         }
     }
@@ -574,6 +578,33 @@ impl WasmtimeOptionValue for WasiNnGraph {
     }
 }
 
+impl WasmtimeOptionValue for WasiKeyValueStore {
+    const VAL_HELP: &'static str = "=<identifier>=<memory|file:path>";
+    fn parse(val: Option<&str>) -> Result<Self> {
+        let val = String::parse(val)?;
+        let Some((identifier, backend)) = val.split_once('=') else {
+            bail!("store does not contain `=` separator for its backend");
+        };
+        let backend = match backend.split_once(':') {
+            _ if backend == "memory" => WasiKeyValueBackend::Memory,
+            Some(("file", path)) if !path.is_empty() => WasiKeyValueBackend::File(path.into()),
+            _ => bail!("unknown key-value store `{backend}`, only memory,file:<path> accepted"),
+        };
+        Ok(WasiKeyValueStore {
+            identifier: identifier.to_string(),
+            backend,
+        })
+    }
+
+    fn display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}=", self.identifier)?;
+        match &self.backend {
+            WasiKeyValueBackend::Memory => f.write_str("memory"),
+            WasiKeyValueBackend::File(path) => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
 impl WasmtimeOptionValue for KeyValuePair {
     const VAL_HELP: &'static str = "=<name>=<val>";
     fn parse(val: Option<&str>) -> Result<Self> {
@@ -681,10 +712,25 @@ where
 #[cfg(test)]
 mod tests {
     use super::WasmtimeOptionValue;
+    use crate::{WasiKeyValueBackend, WasiKeyValueStore};
 
     #[test]
     fn numbers_with_underscores() {
         assert!(<u32 as WasmtimeOptionValue>::parse(Some("123")).is_ok_and(|v| v == 123));
         assert!(<u32 as WasmtimeOptionValue>::parse(Some("1_2_3")).is_ok_and(|v| v == 123));
     }
+
+    #[test]
+    fn keyvalue_stores() {
+        let parse = |s| WasiKeyValueStore::parse(Some(s)).ok().map(|s| s.backend);
+        assert_eq!(parse("cache=memory"), Some(WasiKeyValueBackend::Memory));
+        assert_eq!(parse("=memory"), Some(WasiKeyValueBackend::Memory));
+        assert_eq!(
+            parse("data=file:a=b:c.kv"),
+            Some(WasiKeyValueBackend::File("a=b:c.kv".into()))
+        );
+        assert_eq!(parse("data"), None);
+        assert_eq!(parse("data=file:"), None);
+        assert_eq!(parse("data=sqlite:x"), None);
+    }
 }
@@ -50,6 +50,12 @@ pub struct CompiledFunctionMetadata {
     pub start_srcloc: FilePos,
     /// End source location.
     pub end_srcloc: FilePos,
+    /// Stack maps that weren't recorded in the machine buffer, as
+    /// `(return address, frame size, GC reference offsets from SP)` triples.
+    ///
+    /// Compilers that don't lower through Cranelift IR, like Winch, describe
+    /// their safepoints here.
+    pub stack_maps: Vec<(u32, u32, Vec<u32>)>,
 }
 
 /// Compiled function: machine code body, jump table offsets, and unwind information.
@@ -159,6 +165,11 @@ impl CompiledFunction {
     pub fn set_sized_stack_slots(&mut self, slots: ir::StackSlots) {
         self.metadata.sized_stack_slots = slots;
     }
+
+    /// Set the stack maps that weren't recorded in the machine buffer.
+    pub fn set_stack_maps(&mut self, stack_maps: Vec<(u32, u32, Vec<u32>)>) {
+        self.metadata.stack_maps = stack_maps;
+    }
 }
 
 // Collects an iterator of `InstructionAddressMap` into a `Vec` for insertion
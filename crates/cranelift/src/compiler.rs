@@ -31,7 +31,9 @@ use wasmtime_environ::{
     DefinedFuncIndex, FlagValue, FunctionBodyData, FunctionLoc, HostCall, ModuleTranslation,
     ModuleTypesBuilder, PtrSize, RelocationTarget, StackMapSection, StaticModuleIndex,
     TrapEncodingBuilder, TrapSentinel, TripleExt, Tunables, VMOffsets, WasmFuncType, WasmValType,
+    obj::ELF_WASMTIME_EXCEPTIONS,
 };
+use wasmtime_unwinder::ExceptionTableBuilder;
 
 #[cfg(feature = "component-model")]
 mod component;
@@ -455,6 +457,7 @@ impl wasmtime_environ::Compiler for Compiler {
         let mut addrs = AddressMapSection::default();
         let mut traps = TrapEncodingBuilder::default();
         let mut stack_maps = StackMapSection::default();
+        let mut exception_tables = ExceptionTableBuilder::default();
 
         let mut ret = Vec::with_capacity(funcs.len());
         for (i, (sym, func)) in funcs.iter().enumerate() {
@@ -469,7 +472,16 @@ impl wasmtime_environ::Compiler for Compiler {
                 range.clone(),
                 func.buffer.user_stack_maps(),
             );
+            for (offset, frame_size, frame_offsets) in &func.metadata().stack_maps {
+                let code_offset = range.start + u64::from(*offset);
+                assert!(code_offset < range.end);
+                stack_maps.push(code_offset, *frame_size, frame_offsets.iter().copied());
+            }
             traps.push(range.clone(), &func.traps().collect::<Vec<_>>());
+            exception_tables.add_func(
+                u32::try_from(range.start).unwrap(),
+                func.buffer.call_sites(),
+            )?;
             builder.append_padding(self.linkopts.padding_between_functions);
             let info = FunctionLoc {
                 start: u32::try_from(range.start).unwrap(),
@@ -486,6 +498,15 @@ impl wasmtime_environ::Compiler for Compiler {
         stack_maps.append_to(obj);
         traps.append_to(obj);
 
+        let section = obj.add_section(
+            obj.segment_name(StandardSegment::Data).to_vec(),
+            ELF_WASMTIME_EXCEPTIONS.as_bytes().to_vec(),
+            SectionKind::ReadOnlyData,
+        );
+        exception_tables.serialize(|bytes| {
+            obj.append_section_data(section, bytes, 1);
+        });
+
         Ok(ret)
     }
 
@@ -22,11 +22,11 @@ use smallvec::SmallVec;
 use std::mem;
 use wasmparser::{Operator, WasmFeatures};
 use wasmtime_environ::{
-    BuiltinFunctionIndex, DataIndex, ElemIndex, EngineOrModuleTypeIndex, FuncIndex, GlobalIndex,
-    IndexType, Memory, MemoryIndex, Module, ModuleInternedTypeIndex, ModuleTranslation,
-    ModuleTypesBuilder, PtrSize, Table, TableIndex, TripleExt, Tunables, TypeConvert, TypeIndex,
-    VMOffsets, WasmCompositeInnerType, WasmFuncType, WasmHeapTopType, WasmHeapType, WasmRefType,
-    WasmResult, WasmValType,
+    BuiltinFunctionIndex, DEBUG_VALUE_SLOT_SIZE, DataIndex, DebugValueKind, ElemIndex,
+    EngineOrModuleTypeIndex, FuncIndex, GlobalIndex, IndexType, Memory, MemoryIndex, Module,
+    ModuleInternedTypeIndex, ModuleTranslation, ModuleTypesBuilder, PtrSize, Table, TableIndex,
+    TripleExt, Tunables, TypeConvert, TypeIndex, VMOffsets, WasmCompositeInnerType, WasmFuncType,
+    WasmHeapTopType, WasmHeapType, WasmRefType, WasmResult, WasmValType, wasm_unsupported,
 };
 use wasmtime_environ::{FUNCREF_INIT_BIT, FUNCREF_MASK};
 use wasmtime_math::f64_cvt_to_int_bounds;
@@ -83,6 +83,7 @@ macro_rules! declare_function_signatures {
     )*) => {
         $(impl BuiltinFunctions {
             $( #[$attr] )*
+            #[allow(dead_code, reason = "some builtins are only called by Winch")]
             pub(crate) fn $name(&mut self, func: &mut Function) -> ir::FuncRef {
                 self.load_builtin(func, BuiltinFunctionIndex::$name())
             }
@@ -173,6 +174,32 @@ pub struct FuncEnvironment<'module_environment> {
     /// always present even if this is a "leaf" function, as we have to call
     /// into the host to trap when signal handlers are disabled.
     pub(crate) stack_limit_at_function_entry: Option<ir::GlobalValue>,
+
+    /// The frame that guest debugging instrumentation spills values to, when
+    /// `Tunables::debug_guest` is enabled.
+    debug_frame: Option<DebugFrame>,
+}
+
+/// The stack slots that guest debugging instrumentation spills a function's
+/// locals and operand stack to.
+#[derive(Clone, Copy)]
+struct DebugFrame {
+    /// One `ValRaw` per local, followed by the operand stack.
+    values: ir::StackSlot,
+    /// One `DebugValueKind` byte per value in `values`.
+    tags: ir::StackSlot,
+    num_locals: u32,
+}
+
+/// Get the kinds used to spill values of the given types for guest debugging.
+fn debug_value_kinds(tys: &[WasmValType]) -> WasmResult<Vec<DebugValueKind>> {
+    tys.iter()
+        .map(|ty| {
+            DebugValueKind::from_wasm_type(ty).ok_or_else(|| {
+                wasm_unsupported!("guest debugging of functions using `{ty}` values")
+            })
+        })
+        .collect()
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -226,6 +253,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             translation,
 
             stack_limit_at_function_entry: None,
+            debug_frame: None,
         }
     }
 
@@ -716,6 +744,189 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         builder.switch_to_block(continuation_block);
     }
 
+    /// Set up the frame that guest debugging instrumentation spills values
+    /// to, and store the initial values of all locals and their tags into it.
+    ///
+    /// Locals are then written through to the frame whenever they're set, see
+    /// `debug_def_local`, so that the hook before each instruction only has to
+    /// spill the operand stack.
+    pub fn debug_init_frame(
+        &mut self,
+        local_types: &[WasmValType],
+        builder: &mut FunctionBuilder<'_>,
+    ) -> WasmResult<()> {
+        debug_assert!(self.tunables.debug_guest);
+        let kinds = debug_value_kinds(local_types)?;
+        let num_locals = u32::try_from(kinds.len()).unwrap();
+        let values = builder.func.create_sized_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            num_locals * DEBUG_VALUE_SLOT_SIZE,
+            4,
+        ));
+        let tags = builder.func.create_sized_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            num_locals,
+            0,
+        ));
+        self.debug_frame = Some(DebugFrame {
+            values,
+            tags,
+            num_locals,
+        });
+
+        for i in 0..num_locals {
+            let val = builder.use_var(Variable::from_u32(i));
+            self.debug_def_local(builder, i, val);
+        }
+        self.debug_store_tags(builder, 0, &kinds);
+        Ok(())
+    }
+
+    /// Write the new value `val` of the local `index` through to the guest
+    /// debugging frame, if there is one.
+    pub fn debug_def_local(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        index: u32,
+        val: ir::Value,
+    ) {
+        if let Some(frame) = self.debug_frame {
+            self.debug_store_value(builder, frame, index, val);
+        }
+    }
+
+    /// Store `val` into the `index`th `ValRaw` of the guest debugging frame.
+    fn debug_store_value(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        frame: DebugFrame,
+        index: u32,
+        val: ir::Value,
+    ) {
+        // `ValRaw` is always little-endian.
+        let flags = ir::MemFlags::trusted().with_endianness(ir::Endianness::Little);
+        let addr = builder
+            .ins()
+            .stack_addr(self.pointer_type(), frame.values, 0);
+        let offset = i32::try_from(index * DEBUG_VALUE_SLOT_SIZE).unwrap();
+        builder.ins().store(flags, val, addr, offset);
+    }
+
+    /// Store the tags of `kinds` into the guest debugging frame, starting at
+    /// the `start`th tag. Tags are packed into 64-bit stores where possible.
+    fn debug_store_tags(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        start: u32,
+        kinds: &[DebugValueKind],
+    ) {
+        let frame = self.debug_frame.unwrap();
+        let flags = ir::MemFlags::trusted().with_endianness(ir::Endianness::Little);
+        let addr = builder.ins().stack_addr(self.pointer_type(), frame.tags, 0);
+        let mut offset = i32::try_from(start).unwrap();
+        let mut chunks = kinds.chunks_exact(8);
+        for chunk in chunks.by_ref() {
+            let bytes: [u8; 8] = core::array::from_fn(|i| chunk[i] as u8);
+            let tags = builder
+                .ins()
+                .iconst(ir::types::I64, i64::from_le_bytes(bytes));
+            builder.ins().store(flags, tags, addr, offset);
+            offset += 8;
+        }
+        for kind in chunks.remainder() {
+            let tag = builder.ins().iconst(ir::types::I8, i64::from(*kind as u8));
+            builder.ins().store(flags, tag, addr, offset);
+            offset += 1;
+        }
+    }
+
+    /// Emit the guest debugging hook that runs before the instruction at
+    /// `offset`.
+    ///
+    /// When the store's `debug_active` flag is set, the operand stack values
+    /// are spilled to the frame set up by `debug_init_frame`, next to the
+    /// locals, along with a tag byte per value describing its type, and the
+    /// `debug_hook` builtin is invoked. The flag is clear unless breakpoints or
+    /// single-stepping are configured, so the common case is a single load and
+    /// branch.
+    pub fn debug_before_op(
+        &mut self,
+        offset: u32,
+        stack_types: &[WasmValType],
+        builder: &mut FunctionBuilder<'_>,
+        state: &FuncTranslationState,
+    ) -> WasmResult<()> {
+        debug_assert_eq!(stack_types.len(), state.stack.len());
+        let frame = self.debug_frame.unwrap();
+        let kinds = debug_value_kinds(stack_types)?;
+
+        // Grow the frame to fit this instruction's operand stack.
+        let len = frame.num_locals + u32::try_from(kinds.len()).unwrap();
+        let values = &mut builder.func.sized_stack_slots[frame.values];
+        values.size = values.size.max(len * DEBUG_VALUE_SLOT_SIZE);
+        let tags = &mut builder.func.sized_stack_slots[frame.tags];
+        tags.size = tags.size.max(len);
+
+        let hook_block = builder.create_block();
+        let continuation_block = builder.create_block();
+        builder.set_cold_block(hook_block);
+
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let active = builder.ins().load(
+            ir::types::I32,
+            ir::MemFlags::trusted(),
+            vmstore_ctx,
+            i32::from(self.offsets.ptr.vmstore_context_debug_active()),
+        );
+        builder
+            .ins()
+            .brif(active, hook_block, &[], continuation_block, &[]);
+        builder.seal_block(hook_block);
+        builder.switch_to_block(hook_block);
+
+        for (i, val) in state.stack.iter().enumerate() {
+            let index = frame.num_locals + u32::try_from(i).unwrap();
+            self.debug_store_value(builder, frame, index, *val);
+        }
+        self.debug_store_tags(builder, frame.num_locals, &kinds);
+
+        // The hook may run arbitrary host code, so make sure that the store
+        // observes an up-to-date amount of fuel.
+        if self.tunables.consume_fuel {
+            self.fuel_save_from_var(builder);
+        }
+
+        let func_index = match &builder.func.name {
+            ir::UserFuncName::User(user) => user.index,
+            _ => unreachable!(),
+        };
+        let debug_hook = self.builtin_functions.debug_hook(builder.func);
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let func_index = builder.ins().iconst(ir::types::I32, i64::from(func_index));
+        let offset = builder.ins().iconst(ir::types::I32, i64::from(offset));
+        let values = builder
+            .ins()
+            .stack_addr(self.pointer_type(), frame.values, 0);
+        let tags = builder.ins().stack_addr(self.pointer_type(), frame.tags, 0);
+        let len = builder.ins().iconst(ir::types::I32, i64::from(len));
+        let num_locals = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(frame.num_locals));
+        builder.ins().call(
+            debug_hook,
+            &[vmctx, func_index, offset, values, tags, len, num_locals],
+        );
+
+        if self.tunables.consume_fuel {
+            self.fuel_load_into_var(builder);
+        }
+        builder.ins().jump(continuation_block, &[]);
+        builder.seal_block(continuation_block);
+
+        builder.switch_to_block(continuation_block);
+        Ok(())
+    }
+
     /// Get the Memory for the given index.
     fn memory(&self, index: MemoryIndex) -> Memory {
         self.module.memories[index]
@@ -0,0 +1,459 @@
+//! Compiler for the generational collector and its barriers.
+//!
+//! The generational collector moves objects, so every GC reference that is
+//! live across a safepoint must be included in stack maps. Allocation is an
+//! inline bump of the nursery's allocation finger with an out-of-line fallback
+//! to the `gc_alloc_raw` libcall. Writes of GC references into GC objects
+//! perform a card-marking write barrier so that the collector can find
+//! old-to-young edges without scanning the whole old generation.
+
+use super::*;
+use crate::func_environ::FuncEnvironment;
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{self, InstBuilder};
+use cranelift_frontend::FunctionBuilder;
+use wasmtime_environ::generational::{
+    CARD_SIZE_LOG2, GenerationalTypeLayouts, HEADER_ALIGN, HEAP_DATA_CARD_TABLE_OFFSET,
+    heap_data_next_offset,
+};
+use wasmtime_environ::{
+    GcTypeLayouts, ModuleInternedTypeIndex, PtrSize, TypeIndex, VMGcKind, WasmHeapTopType,
+    WasmRefType, WasmResult, WasmStorageType, WasmValType,
+};
+
+#[derive(Default)]
+pub struct GenerationalCompiler {
+    layouts: GenerationalTypeLayouts,
+}
+
+impl GenerationalCompiler {
+    /// Load the pointer to the generational heap's data, which is stored in
+    /// the `VMContext`.
+    fn load_heap_data(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+    ) -> ir::Value {
+        let pointer_type = func_env.pointer_type();
+        let vmctx = func_env.vmctx_val(&mut builder.cursor());
+        builder.ins().load(
+            pointer_type,
+            ir::MemFlags::trusted().with_readonly(),
+            vmctx,
+            i32::from(func_env.offsets.ptr.vmctx_gc_heap_data()),
+        )
+    }
+
+    /// Emit code to allocate a new object, bump allocating inline when there
+    /// is room in the GC heap and falling back to the `gc_alloc_raw` libcall
+    /// (which may collect garbage or grow the heap) otherwise.
+    ///
+    /// The resulting `VMGcRef` has already been declared as needing inclusion
+    /// in stack maps.
+    ///
+    /// ```text
+    /// current_block:
+    ///     rounded_size = round_up(size, HEADER_ALIGN)
+    ///     next = load heap_data.next
+    ///     end, overflowed = uadd_overflow next, rounded_size
+    ///     brif !overflowed && end <= bound, fast_block, slow_block
+    ///
+    /// fast_block:
+    ///     write header at base + next
+    ///     store heap_data.next, end
+    ///     jump continue_block(next)
+    ///
+    /// cold slow_block:
+    ///     gc_ref = call gc_alloc_raw(vmctx, kind, ty, size, align)
+    ///     jump continue_block(gc_ref)
+    ///
+    /// continue_block(gc_ref):
+    ///     ...
+    /// ```
+    fn emit_inline_alloc(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        kind: VMGcKind,
+        ty: ModuleInternedTypeIndex,
+        size: ir::Value,
+    ) -> ir::Value {
+        log::trace!("emit_inline_alloc(kind={kind:?}, ty={ty:?}, size={size})");
+        assert_eq!(builder.func.dfg.value_type(size), ir::types::I32);
+
+        let pointer_type = func_env.pointer_type();
+        let next_offset = i32::try_from(heap_data_next_offset(&func_env.offsets.ptr)).unwrap();
+
+        let current_block = builder.current_block().unwrap();
+        let fast_block = builder.create_block();
+        let slow_block = builder.create_block();
+        let continue_block = builder.create_block();
+
+        builder.ensure_inserted_block();
+        builder.insert_block_after(fast_block, current_block);
+        builder.insert_block_after(slow_block, fast_block);
+        builder.insert_block_after(continue_block, slow_block);
+        builder.set_cold_block(slow_block);
+
+        // Round the object's size up to the heap's alignment. Every object in
+        // the generational heap is `HEADER_ALIGN`-aligned and is a multiple of
+        // `HEADER_ALIGN` in size, so the bump finger never needs re-aligning.
+        let align_minus_one = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(HEADER_ALIGN - 1));
+        let size_plus_align_minus_one = func_env.uadd_overflow_trap(
+            builder,
+            size,
+            align_minus_one,
+            crate::TRAP_ALLOCATION_TOO_LARGE,
+        );
+        let rounded_size = builder
+            .ins()
+            .band_imm(size_plus_align_minus_one, !i64::from(HEADER_ALIGN - 1));
+
+        // Check whether the object fits between the bump finger and the end of
+        // the GC heap.
+        let heap_data = self.load_heap_data(func_env, builder);
+        let next = builder.ins().load(
+            ir::types::I32,
+            ir::MemFlags::trusted(),
+            heap_data,
+            next_offset,
+        );
+
+        // Note that overflowing here is not a trap: it just means that this
+        // allocation must take the slow path, which will report the
+        // appropriate error if the object can never fit in the heap.
+        let (end_of_object, overflowed) = builder.ins().uadd_overflow(next, rounded_size);
+        let uext_end_of_object = uextend_i32_to_pointer_type(builder, pointer_type, end_of_object);
+        let bound = func_env.get_gc_heap_bound(builder);
+        let fits = builder
+            .ins()
+            .icmp(IntCC::UnsignedLessThanOrEqual, uext_end_of_object, bound);
+        let not_overflowed = builder.ins().icmp_imm(IntCC::Equal, overflowed, 0);
+        let is_in_bounds = builder.ins().band(fits, not_overflowed);
+        builder
+            .ins()
+            .brif(is_in_bounds, fast_block, &[], slow_block, &[]);
+
+        // Fast path: write the object's header and bump the finger.
+        //
+        // Note: we don't need to bounds-check these writes, since we just
+        // checked that the whole object is within the GC heap's bounds.
+        builder.switch_to_block(fast_block);
+        builder.seal_block(fast_block);
+        log::trace!("emit_inline_alloc: fast_block");
+        let base = func_env.get_gc_heap_base(builder);
+        let uext_next = uextend_i32_to_pointer_type(builder, pointer_type, next);
+        let ptr_to_object = builder.ins().iadd(base, uext_next);
+        let kind_bits = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(kind.as_u32()));
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            kind_bits,
+            ptr_to_object,
+            i32::try_from(wasmtime_environ::VM_GC_HEADER_KIND_OFFSET).unwrap(),
+        );
+        let shared_ty = func_env.module_interned_to_shared_ty(&mut builder.cursor(), ty);
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            shared_ty,
+            ptr_to_object,
+            i32::try_from(wasmtime_environ::VM_GC_HEADER_TYPE_INDEX_OFFSET).unwrap(),
+        );
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            rounded_size,
+            ptr_to_object,
+            i32::try_from(wasmtime_environ::generational::HEADER_OBJECT_SIZE_OFFSET).unwrap(),
+        );
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            end_of_object,
+            heap_data,
+            next_offset,
+        );
+        builder.ins().jump(continue_block, &[next.into()]);
+
+        // Slow path: call out to the runtime, which will collect garbage
+        // and/or grow the GC heap as necessary.
+        builder.switch_to_block(slow_block);
+        builder.seal_block(slow_block);
+        log::trace!("emit_inline_alloc: slow_block");
+        let gc_alloc_raw_builtin = func_env.builtin_functions.gc_alloc_raw(builder.func);
+        let vmctx = func_env.vmctx_val(&mut builder.cursor());
+        let kind_bits = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(kind.as_u32()));
+        let ty_bits = builder.ins().iconst(ir::types::I32, i64::from(ty.as_u32()));
+        let align = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(HEADER_ALIGN));
+        let call_inst = builder.ins().call(
+            gc_alloc_raw_builtin,
+            &[vmctx, kind_bits, ty_bits, size, align],
+        );
+        let slow_gc_ref = builder.func.dfg.first_result(call_inst);
+        builder.ins().jump(continue_block, &[slow_gc_ref.into()]);
+
+        builder.switch_to_block(continue_block);
+        builder.seal_block(continue_block);
+        let gc_ref = builder.append_block_param(continue_block, ir::types::I32);
+        builder.declare_value_needs_stack_map(gc_ref);
+        log::trace!("emit_inline_alloc(..) -> {gc_ref}");
+        gc_ref
+    }
+
+    /// Get a raw pointer to the start of the given, freshly-allocated object.
+    ///
+    /// Note: we don't need to bounds-check the GC ref access here, since we
+    /// trust the results of the allocation.
+    fn raw_ptr_to_new_object(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        gc_ref: ir::Value,
+    ) -> ir::Value {
+        let base = func_env.get_gc_heap_base(builder);
+        let extended_gc_ref = uextend_i32_to_pointer_type(builder, func_env.pointer_type(), gc_ref);
+        builder.ins().iadd(base, extended_gc_ref)
+    }
+
+    /// Write to an uninitialized field or element inside a freshly-allocated
+    /// GC object.
+    ///
+    /// Freshly-allocated objects are always in the nursery, which is collected
+    /// in its entirety during every collection, so initializing writes never
+    /// need the card-marking write barrier.
+    fn init_field(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        field_addr: ir::Value,
+        ty: WasmStorageType,
+        val: ir::Value,
+    ) -> WasmResult<()> {
+        // Data inside GC objects is always little endian.
+        let flags = ir::MemFlags::trusted().with_endianness(ir::Endianness::Little);
+
+        match ty {
+            WasmStorageType::Val(WasmValType::Ref(r))
+                if r.heap_type.top() == WasmHeapTopType::Func =>
+            {
+                write_func_ref_at_addr(func_env, builder, r, flags, field_addr, val)?;
+            }
+            WasmStorageType::Val(WasmValType::Ref(r)) => {
+                unbarriered_store_gc_ref(builder, r.heap_type, field_addr, val, flags)?;
+            }
+            WasmStorageType::I8 => {
+                assert_eq!(builder.func.dfg.value_type(val), ir::types::I32);
+                builder.ins().istore8(flags, val, field_addr, 0);
+            }
+            WasmStorageType::I16 => {
+                assert_eq!(builder.func.dfg.value_type(val), ir::types::I32);
+                builder.ins().istore16(flags, val, field_addr, 0);
+            }
+            WasmStorageType::Val(_) => {
+                let size_of_access = wasmtime_environ::byte_size_of_wasm_ty_in_gc_heap(&ty);
+                assert_eq!(builder.func.dfg.value_type(val).bytes(), size_of_access);
+                builder.ins().store(flags, val, field_addr, 0);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl GcCompiler for GenerationalCompiler {
+    fn layouts(&self) -> &dyn GcTypeLayouts {
+        &self.layouts
+    }
+
+    fn alloc_array(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        array_type_index: TypeIndex,
+        init: super::ArrayInit<'_>,
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index =
+            func_env.module.types[array_type_index].unwrap_module_type_index();
+        let ptr_ty = func_env.pointer_type();
+
+        let len_offset = gc_compiler(func_env)?.layouts().array_length_field_offset();
+        let array_layout = func_env.array_layout(interned_type_index).clone();
+        let base_size = array_layout.base_size;
+        let len_to_elems_delta = base_size.checked_sub(len_offset).unwrap();
+
+        // First, compute the array's total size from its base size, element
+        // size, and length.
+        let len = init.len(&mut builder.cursor());
+        let size = emit_array_size(func_env, builder, &array_layout, len);
+
+        // Next, allocate the array.
+        let array_ref = self.emit_inline_alloc(
+            func_env,
+            builder,
+            VMGcKind::ArrayRef,
+            interned_type_index,
+            size,
+        );
+
+        // Write the array's length into the appropriate slot.
+        let object_addr = self.raw_ptr_to_new_object(func_env, builder, array_ref);
+        let len_addr = builder.ins().iadd_imm(object_addr, i64::from(len_offset));
+        let len = init.len(&mut builder.cursor());
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), len, len_addr, 0);
+
+        // Finally, initialize the elements.
+        let len_to_elems_delta = builder.ins().iconst(ptr_ty, i64::from(len_to_elems_delta));
+        let elems_addr = builder.ins().iadd(len_addr, len_to_elems_delta);
+        init.initialize(
+            func_env,
+            builder,
+            interned_type_index,
+            base_size,
+            size,
+            elems_addr,
+            |func_env, builder, elem_ty, elem_addr, val| {
+                self.init_field(func_env, builder, elem_addr, elem_ty, val)
+            },
+        )?;
+
+        Ok(array_ref)
+    }
+
+    fn alloc_struct(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        struct_type_index: TypeIndex,
+        field_vals: &[ir::Value],
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index =
+            func_env.module.types[struct_type_index].unwrap_module_type_index();
+        let struct_size = func_env.struct_layout(interned_type_index).size;
+        let struct_size_val = builder.ins().iconst(ir::types::I32, i64::from(struct_size));
+
+        let struct_ref = self.emit_inline_alloc(
+            func_env,
+            builder,
+            VMGcKind::StructRef,
+            interned_type_index,
+            struct_size_val,
+        );
+
+        let raw_ptr_to_struct = self.raw_ptr_to_new_object(func_env, builder, struct_ref);
+        initialize_struct_fields(
+            func_env,
+            builder,
+            interned_type_index,
+            raw_ptr_to_struct,
+            field_vals,
+            |func_env, builder, ty, field_addr, val| {
+                self.init_field(func_env, builder, field_addr, ty, val)
+            },
+        )?;
+
+        Ok(struct_ref)
+    }
+
+    fn translate_read_gc_reference(
+        &mut self,
+        _func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        ty: WasmRefType,
+        src: ir::Value,
+        flags: ir::MemFlags,
+    ) -> WasmResult<ir::Value> {
+        // No read barrier is necessary, but the reference must be included in
+        // stack maps so that the collector can find and update it if the
+        // object it refers to is moved.
+        unbarriered_load_gc_ref(builder, ty.heap_type, src, flags)
+    }
+
+    fn translate_write_gc_reference(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        ty: WasmRefType,
+        dst: ir::Value,
+        new_val: ir::Value,
+        flags: ir::MemFlags,
+    ) -> WasmResult<()> {
+        assert!(ty.is_vmgcref_type());
+
+        unbarriered_store_gc_ref(builder, ty.heap_type, dst, new_val, flags)?;
+
+        // Writes of `i31ref`s can never create an old-to-young edge.
+        if let WasmHeapType::I31 = ty.heap_type {
+            return Ok(());
+        }
+
+        // Our card-marking write barrier is roughly equivalent to the
+        // following pseudo-CLIF:
+        //
+        // ```
+        // current_block:
+        //     ...
+        //     store dst, new_val
+        //     let offset = isub dst, gc_heap_base
+        //     brif offset < gc_heap_bound, mark_card_block, continue_block
+        //
+        // mark_card_block:
+        //     let card_table = load heap_data.card_table
+        //     let card = ushr offset, CARD_SIZE_LOG2
+        //     store.i8 card_table[card], 1
+        //     jump continue_block
+        //
+        // continue_block:
+        //     ...
+        // ```
+        //
+        // This barrier is shared by writes into globals and tables, which are
+        // not inside the GC heap and are always treated as roots. Those writes
+        // fail the bounds check and skip marking any card.
+        let current_block = builder.current_block().unwrap();
+        let mark_card_block = builder.create_block();
+        let continue_block = builder.create_block();
+
+        builder.ensure_inserted_block();
+        builder.insert_block_after(mark_card_block, current_block);
+        builder.insert_block_after(continue_block, mark_card_block);
+
+        log::trace!("generational write barrier: check whether dst is in the GC heap");
+        let base = func_env.get_gc_heap_base(builder);
+        let bound = func_env.get_gc_heap_bound(builder);
+        let offset = builder.ins().isub(dst, base);
+        let is_in_gc_heap = builder.ins().icmp(IntCC::UnsignedLessThan, offset, bound);
+        builder
+            .ins()
+            .brif(is_in_gc_heap, mark_card_block, &[], continue_block, &[]);
+
+        builder.switch_to_block(mark_card_block);
+        builder.seal_block(mark_card_block);
+        log::trace!("generational write barrier: mark dst's card");
+        let heap_data = self.load_heap_data(func_env, builder);
+        let card_table = builder.ins().load(
+            func_env.pointer_type(),
+            ir::MemFlags::trusted(),
+            heap_data,
+            i32::try_from(HEAP_DATA_CARD_TABLE_OFFSET).unwrap(),
+        );
+        let card = builder.ins().ushr_imm(offset, i64::from(CARD_SIZE_LOG2));
+        let card_addr = builder.ins().iadd(card_table, card);
+        let dirty = builder.ins().iconst(ir::types::I8, 1);
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), dirty, card_addr, 0);
+        builder.ins().jump(continue_block, &[]);
+
+        builder.switch_to_block(continue_block);
+        builder.seal_block(continue_block);
+        log::trace!("generational write barrier: finished");
+        Ok(())
+    }
+}
@@ -46,7 +46,7 @@ mod translate;
 
 use self::compiler::Compiler;
 
-const TRAP_INTERNAL_ASSERT: TrapCode = TrapCode::unwrap_user(1);
+pub const TRAP_INTERNAL_ASSERT: TrapCode = TrapCode::unwrap_user(1);
 const TRAP_OFFSET: u8 = 2;
 pub const TRAP_ALWAYS: TrapCode =
     TrapCode::unwrap_user(Trap::AlwaysTrapAdapter as u8 + TRAP_OFFSET);
@@ -178,15 +178,15 @@ fn wasm_call_conv(isa: &dyn TargetIsa, tunables: &Tunables) -> CallConv {
     //
     // The Winch calling convention is used instead when generating trampolines
     // which call Winch-generated functions. The winch calling convention is
-    // only implemented for x64 and aarch64, so assert that here and panic on
-    // other architectures.
+    // only implemented for x64, aarch64 and riscv64, so assert that here and
+    // panic on other architectures.
     if tunables.winch_callable {
         assert!(
             matches!(
                 isa.triple().architecture,
-                Architecture::X86_64 | Architecture::Aarch64(_)
+                Architecture::X86_64 | Architecture::Aarch64(_) | Architecture::Riscv64(_)
             ),
-            "The Winch calling convention is only implemented for x86_64 and aarch64"
+            "The Winch calling convention is only implemented for x86_64, aarch64 and riscv64"
         );
         CallConv::Winch
     } else {
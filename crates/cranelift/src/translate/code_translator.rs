@@ -160,6 +160,7 @@ pub fn translate_operator(
             }
 
             builder.def_var(Variable::from_u32(*local_index), val);
+            environ.debug_def_local(builder, *local_index, val);
             let label = ValueLabel::from_u32(*local_index);
             builder.set_val_label(val, label);
         }
@@ -173,6 +174,7 @@ pub fn translate_operator(
             }
 
             builder.def_var(Variable::from_u32(*local_index), val);
+            environ.debug_def_local(builder, *local_index, val);
             let label = ValueLabel::from_u32(*local_index);
             builder.set_val_label(val, label);
         }
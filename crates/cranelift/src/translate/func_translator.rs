@@ -245,11 +245,39 @@ fn parse_function_body(
     let mut reader = OperatorsReader::new(reader);
     let mut operand_types = vec![];
 
+    // When guest debugging is enabled locals are written through to a frame
+    // on the stack, and the hook before each operator spills the types and
+    // values of the whole operand stack next to them.
+    let debug_guest = environ.tunables().debug_guest;
+    let mut debug_stack_types = vec![];
+    if debug_guest {
+        let mut local_types = vec![];
+        for i in 0..validator.len_locals() {
+            let ty = validator.get_local_type(i).unwrap();
+            local_types.push(environ.convert_valtype(ty)?);
+        }
+        environ.debug_init_frame(&local_types, builder)?;
+    }
+
     while !reader.eof() {
         let pos = reader.original_position();
         builder.set_srcloc(cur_srcloc(&reader.get_binary_reader()));
 
         let op = reader.read()?;
+        if debug_guest && state.reachable() {
+            debug_stack_types.clear();
+            for i in (0..validator.operand_stack_height()).rev() {
+                let i = usize::try_from(i).unwrap();
+                let ty = validator.get_operand_type(i).unwrap().unwrap();
+                debug_stack_types.push(environ.convert_valtype(ty)?);
+            }
+            environ.debug_before_op(
+                u32::try_from(pos).unwrap(),
+                &debug_stack_types,
+                builder,
+                state,
+            )?;
+        }
         let operand_types =
             validate_op_and_get_operand_types(validator, environ, &mut operand_types, &op, pos)?;
 
@@ -82,7 +82,7 @@ macro_rules! foreach_builtin_function {
 
             // Allocate a new, uninitialized GC object and return a reference to
             // it.
-            #[cfg(feature = "gc-drc")]
+            #[cfg(any(feature = "gc-drc", feature = "gc-generational"))]
             gc_alloc_raw(
                 vmctx: vmctx,
                 kind: u32,
@@ -189,6 +189,22 @@ macro_rules! foreach_builtin_function {
                 expected_engine_type: u32
             ) -> u32;
 
+            // Allocates a new exception object for the given tag, with its
+            // tag already set and its payload fields left uninitialized.
+            #[cfg(feature = "gc")]
+            alloc_exn(vmctx: vmctx, tag: u32) -> u32;
+
+            // Returns whether the given non-null exception object was thrown
+            // for the given tag.
+            #[cfg(feature = "gc")]
+            exn_tag_matches(vmctx: vmctx, exnref: u32, tag: u32) -> u32;
+
+            // Builtin implementation of the `throw_ref` instruction, also used
+            // to throw the exceptions created by `throw`. Unwinds to the
+            // innermost handler or traps if the exception is uncaught.
+            #[cfg(feature = "gc")]
+            throw_ref(vmctx: vmctx, exnref: u32) -> bool;
+
             // Returns an index for Wasm's `table.grow` instruction for GC references.
             #[cfg(feature = "gc")]
             table_grow_gc_ref(vmctx: vmctx, table: u32, delta: u64, init: u32) -> pointer;
@@ -239,6 +255,17 @@ macro_rules! foreach_builtin_function {
             // the Option<VMContObj>, as in previous libcall.
             #[cfg(feature = "stack-switching")]
             table_fill_cont_obj(vmctx: vmctx, table: u32, dst: u64, value_contref: pointer, value_revision: u64, len: u64) -> bool;
+
+            // Invoked before a wasm instruction when guest debugging is active
+            // for the store. `values` points to `len` spilled `ValRaw`s (locals
+            // first, then the operand stack) and `tags` to one type tag byte
+            // per value.
+            debug_hook(vmctx: vmctx, func: u32, offset: u32, values: pointer, tags: pointer, len: u32, locals: u32) -> bool;
+
+            // Invoked when the store's tier-up countdown expires in baseline
+            // code compiled for tiered compilation. `func` is the
+            // `DefinedFuncIndex` of the function that was executing.
+            tier_up(vmctx: vmctx, func: u32) -> bool;
         }
     };
 }
@@ -397,6 +424,7 @@ impl BuiltinFunctionIndex {
             (@get gc_alloc_raw u32) => (TrapSentinel::Falsy);
             (@get array_new_data u32) => (TrapSentinel::Falsy);
             (@get array_new_elem u32) => (TrapSentinel::Falsy);
+            (@get alloc_exn u32) => (TrapSentinel::Falsy);
 
             // The final epoch represents a trap
             (@get new_epoch u64) => (TrapSentinel::NegativeOne);
@@ -407,6 +435,7 @@ impl BuiltinFunctionIndex {
             (@get get_interned_func_ref pointer) => (return None);
             (@get intern_func_ref_for_gc_heap u64) => (return None);
             (@get is_subtype u32) => (return None);
+            (@get exn_tag_matches u32) => (return None);
             (@get ceil_f32 f32) => (return None);
             (@get ceil_f64 f64) => (return None);
             (@get floor_f32 f32) => (return None);
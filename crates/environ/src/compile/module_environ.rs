@@ -348,7 +348,13 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                     let sigindex = entry?;
                     let ty = TypeIndex::from_u32(sigindex);
                     let interned_index = self.result.module.types[ty];
-                    self.result.module.push_function(interned_index);
+                    let func_index = self.result.module.push_function(interned_index);
+                    // Code compiled for tiered compilation calls all defined
+                    // functions through their `VMFuncRef`, which is switched
+                    // over to optimized code once the function is tiered up.
+                    if self.tunables.tiered_compilation {
+                        self.flag_func_escaped(func_index);
+                    }
                 }
             }
 
@@ -0,0 +1,71 @@
+//! Layout of Wasm GC objects in the generational collector.
+
+use super::*;
+
+/// The size of the `VMGenerationalHeader` header for GC objects.
+pub const HEADER_SIZE: u32 = 16;
+
+/// The align of the `VMGenerationalHeader` header for GC objects.
+///
+/// Every object in the generational collector's heap is aligned to, and has a
+/// size that is a multiple of, this value. That means that the heap never
+/// contains any alignment padding between objects and can always be walked
+/// linearly, one object after another.
+pub const HEADER_ALIGN: u32 = 16;
+
+/// The offset of the object-size field in a `VMGenerationalHeader`.
+pub const HEADER_OBJECT_SIZE_OFFSET: u32 = VM_GC_HEADER_SIZE;
+
+/// The offset of the length field in a `VMGenerationalArrayHeader`.
+pub const ARRAY_LENGTH_OFFSET: u32 = HEADER_SIZE;
+
+/// The bit within a `VMGenerationalHeader`'s reserved bits that is the mark
+/// bit.
+pub const HEADER_MARK_BIT: u32 = 1 << 0;
+
+/// The log2 of the number of bytes of GC heap covered by a single card in the
+/// generational collector's card table.
+pub const CARD_SIZE_LOG2: u8 = 9;
+
+/// The number of bytes of GC heap covered by a single card in the generational
+/// collector's card table.
+pub const CARD_SIZE: u32 = 1 << CARD_SIZE_LOG2;
+
+/// The offset of the card table pointer within the heap data pointed to by
+/// `VMContext::gc_heap_data`.
+pub const HEAP_DATA_CARD_TABLE_OFFSET: u32 = 0;
+
+/// The offset of the bump-allocation finger within the heap data pointed to by
+/// `VMContext::gc_heap_data`.
+pub fn heap_data_next_offset<P: crate::PtrSize>(ptr: &P) -> u32 {
+    u32::from(ptr.size())
+}
+
+/// Round the given object size up to a multiple of `HEADER_ALIGN`.
+///
+/// Returns `None` on overflow.
+pub fn round_object_size(size: u32) -> Option<u32> {
+    size.checked_next_multiple_of(HEADER_ALIGN)
+}
+
+/// The layout of Wasm GC objects in the generational collector.
+#[derive(Default)]
+pub struct GenerationalTypeLayouts;
+
+impl GcTypeLayouts for GenerationalTypeLayouts {
+    fn array_length_field_offset(&self) -> u32 {
+        ARRAY_LENGTH_OFFSET
+    }
+
+    fn array_layout(&self, ty: &WasmArrayType) -> GcArrayLayout {
+        common_array_layout(ty, HEADER_SIZE, HEADER_ALIGN, ARRAY_LENGTH_OFFSET)
+    }
+
+    fn struct_layout(&self, ty: &WasmStructType) -> GcStructLayout {
+        common_struct_layout(ty, HEADER_SIZE, HEADER_ALIGN)
+    }
+
+    fn exn_layout(&self, ty: &WasmExnType) -> GcExceptionLayout {
+        common_exn_layout(ty, HEADER_SIZE, HEADER_ALIGN)
+    }
+}
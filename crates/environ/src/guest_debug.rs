@@ -0,0 +1,79 @@
+//! Definitions shared between compiled code and the runtime for the guest
+//! debugging instrumentation.
+//!
+//! When `Tunables::debug_guest` is enabled, compiled code checks the
+//! `VMStoreContext::debug_active` flag before each wasm instruction. If the
+//! flag is set then the function's operand stack is spilled to a frame on the
+//! native stack, next to its locals, and the `debug_hook` builtin is invoked.
+//! Locals are written through to the frame whenever they're set, so that only
+//! the operand stack needs to be spilled before each instruction. The frame
+//! contains one 16-byte `ValRaw` per value and, separately, one
+//! [`DebugValueKind`] byte per value describing how to interpret it.
+
+use crate::{WasmHeapTopType, WasmValType};
+
+/// The size, in bytes, of each value slot in a guest debugging buffer.
+pub const DEBUG_VALUE_SLOT_SIZE: u32 = 16;
+
+/// The type of a value spilled by the guest debugging instrumentation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DebugValueKind {
+    /// A 32-bit integer.
+    I32 = 0,
+    /// A 64-bit integer.
+    I64 = 1,
+    /// A 32-bit float.
+    F32 = 2,
+    /// A 64-bit float.
+    F64 = 3,
+    /// A 128-bit vector.
+    V128 = 4,
+    /// A reference within the `func` hierarchy.
+    FuncRef = 5,
+    /// A reference within the `extern` hierarchy.
+    ExternRef = 6,
+    /// A reference within the `any` hierarchy.
+    AnyRef = 7,
+    /// A reference within the `exn` hierarchy.
+    ExnRef = 8,
+}
+
+impl DebugValueKind {
+    /// Get the kind used to spill a value of the given Wasm type.
+    ///
+    /// Returns `None` for types which the guest debugging instrumentation
+    /// cannot spill, such as continuation references.
+    pub fn from_wasm_type(ty: &WasmValType) -> Option<Self> {
+        Some(match ty {
+            WasmValType::I32 => Self::I32,
+            WasmValType::I64 => Self::I64,
+            WasmValType::F32 => Self::F32,
+            WasmValType::F64 => Self::F64,
+            WasmValType::V128 => Self::V128,
+            WasmValType::Ref(r) => match r.heap_type.top() {
+                WasmHeapTopType::Func => Self::FuncRef,
+                WasmHeapTopType::Extern => Self::ExternRef,
+                WasmHeapTopType::Any => Self::AnyRef,
+                WasmHeapTopType::Exn => Self::ExnRef,
+                WasmHeapTopType::Cont => return None,
+            },
+        })
+    }
+
+    /// Decode a kind from the byte written by compiled code.
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => Self::I32,
+            1 => Self::I64,
+            2 => Self::F32,
+            3 => Self::F64,
+            4 => Self::V128,
+            5 => Self::FuncRef,
+            6 => Self::ExternRef,
+            7 => Self::AnyRef,
+            8 => Self::ExnRef,
+            _ => return None,
+        })
+    }
+}
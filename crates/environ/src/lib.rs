@@ -25,6 +25,7 @@ mod demangling;
 mod error;
 mod ext;
 mod gc;
+mod guest_debug;
 mod hostcall;
 mod module;
 mod module_artifacts;
@@ -45,6 +46,7 @@ pub use crate::builtin::*;
 pub use crate::demangling::*;
 pub use crate::error::*;
 pub use crate::gc::*;
+pub use crate::guest_debug::*;
 pub use crate::hostcall::*;
 pub use crate::module::*;
 pub use crate::module_artifacts::*;
@@ -98,6 +98,15 @@ pub const ELF_WASMTIME_STACK_MAP: &str = ".wasmtime.stackmap";
 /// to the 32-bit encodings for offsets this doesn't support images >=4gb.
 pub const ELF_WASMTIME_TRAPS: &str = ".wasmtime.traps";
 
+/// A custom section which contains the exception handler table of the
+/// compiled code: for each call site which may unwind to a handler in its own
+/// function, the offset of the handler.
+///
+/// This section is encoded with `wasmtime_unwinder::ExceptionTableBuilder`
+/// and decoded with `wasmtime_unwinder::ExceptionTable`, with all offsets
+/// relative to the start of the text section.
+pub const ELF_WASMTIME_EXCEPTIONS: &str = ".wasmtime.exceptions";
+
 /// A custom section which consists of just 1 byte which is either 0 or 1 as to
 /// whether BTI is enabled.
 pub const ELF_WASM_BTI: &str = ".wasmtime.bti";
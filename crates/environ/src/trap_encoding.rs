@@ -107,6 +107,9 @@ pub enum Trap {
     /// that all host tasks have completed and any/all host-owned stream/future
     /// handles have been dropped.
     AsyncDeadlock,
+
+    /// A Wasm exception was thrown and no handler for it was found.
+    UncaughtException,
     // if adding a variant here be sure to update the `check!` macro below
 }
 
@@ -148,6 +151,7 @@ impl Trap {
             ContinuationAlreadyConsumed
             DisabledOpcode
             AsyncDeadlock
+            UncaughtException
         }
 
         None
@@ -183,6 +187,7 @@ impl fmt::Display for Trap {
             ContinuationAlreadyConsumed => "continuation already consumed",
             DisabledOpcode => "pulley opcode disabled at compile time was executed",
             AsyncDeadlock => "deadlock detected: event loop cannot make further progress",
+            UncaughtException => "uncaught wasm exception",
         };
         write!(f, "wasm trap: {desc}")
     }
@@ -86,6 +86,16 @@ define_tunables! {
         /// Whether or not we use epoch-based interruption.
         pub epoch_interruption: bool,
 
+        /// Whether or not generated code is instrumented with hooks for the
+        /// guest debugging API, meaning that breakpoints and single-stepping
+        /// can pause execution before each wasm instruction.
+        pub debug_guest: bool,
+
+        /// Whether or not baseline code is instrumented to count function
+        /// entries and loop iterations so that hot functions can be recompiled
+        /// with an optimizing compiler at runtime.
+        pub tiered_compilation: bool,
+
         /// Whether or not linear memories are allowed to be reallocated after
         /// initial allocation at runtime.
         pub memory_may_move: bool,
@@ -182,6 +192,8 @@ impl Tunables {
             parse_wasm_debuginfo: true,
             consume_fuel: false,
             epoch_interruption: false,
+            debug_guest: false,
+            tiered_compilation: false,
             memory_may_move: true,
             guard_before_linear_memory: true,
             table_lazy_init: true,
@@ -257,6 +269,8 @@ pub enum Collector {
     DeferredReferenceCounting,
     /// The null collector.
     Null,
+    /// The generational collector.
+    Generational,
 }
 
 impl fmt::Display for Collector {
@@ -264,6 +278,7 @@ impl fmt::Display for Collector {
         match self {
             Collector::DeferredReferenceCounting => write!(f, "deferred reference-counting"),
             Collector::Null => write!(f, "null"),
+            Collector::Generational => write!(f, "generational"),
         }
     }
 }
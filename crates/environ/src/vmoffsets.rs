@@ -221,6 +221,16 @@ pub trait PtrSize {
         self.vmstore_context_last_wasm_entry_fp() + self.size()
     }
 
+    /// Return the offset of the `debug_active` field of `VMStoreContext`.
+    fn vmstore_context_debug_active(&self) -> u8 {
+        self.vmstore_context_stack_chain() + self.size_of_vmstack_chain()
+    }
+
+    /// Return the offset of the `tier_up_countdown` field of `VMStoreContext`.
+    fn vmstore_context_tier_up_countdown(&self) -> u8 {
+        self.vmstore_context_debug_active() + 4
+    }
+
     // Offsets within `VMMemoryDefinition`
 
     /// The offset of the `base` field.
@@ -241,6 +241,7 @@ impl Config {
                 Collector::DeferredReferenceCounting => {
                     wasmtime_test_util::wast::Collector::DeferredReferenceCounting
                 }
+                Collector::Generational => wasmtime_test_util::wast::Collector::Generational,
             },
             pooling: matches!(
                 self.wasmtime.strategy,
@@ -869,6 +870,7 @@ impl Arbitrary<'_> for CompilerStrategy {
 pub enum Collector {
     DeferredReferenceCounting,
     Null,
+    Generational,
 }
 
 impl Collector {
@@ -876,6 +878,7 @@ impl Collector {
         match self {
             Collector::DeferredReferenceCounting => wasmtime::Collector::DeferredReferenceCounting,
             Collector::Null => wasmtime::Collector::Null,
+            Collector::Generational => wasmtime::Collector::Generational,
         }
     }
 }
@@ -0,0 +1,24 @@
+use test_programs::p3::proxy::exports::wasi::http::handler::Guest;
+use test_programs::p3::proxy::wasi::http::types::{ErrorCode, Fields, Request, Response};
+use test_programs::p3::proxy::wit_future;
+
+struct Component;
+
+test_programs::p3::proxy::export!(Component);
+
+impl Guest for Component {
+    /// Return a response which echoes the request headers, body, and trailers.
+    async fn handle(request: Request) -> Result<Response, ErrorCode> {
+        let headers = request.get_headers().copy_all();
+        let (_, result_rx) = wit_future::new(|| Ok(()));
+        let (contents, trailers) = Request::consume_body(request, result_rx);
+        let (response, _result) = Response::new(
+            Fields::from_list(&headers).unwrap(),
+            Some(contents),
+            trailers,
+        );
+        Ok(response)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,65 @@
+use test_programs::p3::wasi::sockets::ip_name_lookup::{ErrorCode, resolve_addresses};
+use test_programs::p3::wasi::sockets::types::IpAddress;
+
+struct Component;
+
+test_programs::p3::export!(Component);
+
+impl test_programs::p3::exports::wasi::cli::run::Guest for Component {
+    async fn run() -> Result<(), ()> {
+        // Valid domains
+        resolve_addresses("localhost".into()).await.unwrap();
+
+        // Valid IP addresses
+        assert_eq!(
+            resolve_one("0.0.0.0").await.unwrap(),
+            IpAddress::Ipv4((0, 0, 0, 0))
+        );
+        assert_eq!(
+            resolve_one("127.0.0.1").await.unwrap(),
+            IpAddress::Ipv4((127, 0, 0, 1))
+        );
+        assert_eq!(
+            resolve_one("::").await.unwrap(),
+            IpAddress::Ipv6((0, 0, 0, 0, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            resolve_one("::1").await.unwrap(),
+            IpAddress::Ipv6((0, 0, 0, 0, 0, 0, 0, 1))
+        );
+        assert_eq!(
+            resolve_one("[::]").await.unwrap(),
+            IpAddress::Ipv6((0, 0, 0, 0, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            resolve_one("dead:beef::").await.unwrap(),
+            IpAddress::Ipv6((0xdead, 0xbeef, 0, 0, 0, 0, 0, 0))
+        );
+
+        // Invalid inputs
+        for name in [
+            "",
+            " ",
+            "a.b<&>",
+            "127.0.0.1:80",
+            "[::]:80",
+            "http://example.com/",
+        ] {
+            assert_eq!(
+                resolve_addresses(name.into()).await.unwrap_err(),
+                ErrorCode::InvalidArgument
+            );
+        }
+        Ok(())
+    }
+}
+
+async fn resolve_one(name: &str) -> Result<IpAddress, ErrorCode> {
+    Ok(resolve_addresses(name.into())
+        .await?
+        .first()
+        .unwrap()
+        .to_owned())
+}
+
+fn main() {}
@@ -0,0 +1,82 @@
+use test_programs::p3::wasi::sockets::types::{
+    IpAddressFamily, IpSocketAddress, Ipv4SocketAddress, Ipv6SocketAddress, UdpSocket,
+};
+
+struct Component;
+
+test_programs::p3::export!(Component);
+
+async fn test_udp_sample_application(family: IpAddressFamily, bind_address: IpSocketAddress) {
+    let first_message = &[];
+    let second_message = b"Hello, world!";
+    let third_message = b"Greetings, planet!";
+
+    let server = UdpSocket::new(family);
+    server.bind(bind_address).unwrap();
+    let addr = server.local_address().unwrap();
+
+    let client_addr = {
+        let client = UdpSocket::new(family);
+        client.connect(addr).unwrap();
+        let client_addr = client.local_address().unwrap();
+
+        client.send(first_message.to_vec(), None).await.unwrap();
+        client
+            .send(second_message.to_vec(), Some(addr))
+            .await
+            .unwrap();
+        client_addr
+    };
+
+    {
+        // Check that we've received our sent messages.
+        let (data, remote_address) = server.receive().await.unwrap();
+        assert_eq!(data, first_message);
+        assert_eq!(remote_address, client_addr);
+
+        let (data, remote_address) = server.receive().await.unwrap();
+        assert_eq!(data, second_message);
+        assert_eq!(remote_address, client_addr);
+    }
+
+    // Another client
+    {
+        let client = UdpSocket::new(family);
+        client
+            .send(third_message.to_vec(), Some(addr))
+            .await
+            .unwrap();
+    }
+
+    {
+        // Check that we sent and received our message!
+        let (data, _) = server.receive().await.unwrap();
+        assert_eq!(data, third_message);
+    }
+}
+
+impl test_programs::p3::exports::wasi::cli::run::Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_udp_sample_application(
+            IpAddressFamily::Ipv4,
+            IpSocketAddress::Ipv4(Ipv4SocketAddress {
+                port: 0,
+                address: (127, 0, 0, 1),
+            }),
+        )
+        .await;
+        test_udp_sample_application(
+            IpAddressFamily::Ipv6,
+            IpSocketAddress::Ipv6(Ipv6SocketAddress {
+                port: 0,
+                address: (0, 0, 0, 0, 0, 0, 0, 1),
+                flow_info: 0,
+                scope_id: 0,
+            }),
+        )
+        .await;
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -14,6 +14,79 @@ wit_bindgen::generate!({
     pub_export_macro: true,
     async: [
         "wasi:cli/run@0.3.0#run",
+        "wasi:sockets/ip-name-lookup@0.3.0#resolve-addresses",
+        "wasi:sockets/types@0.3.0#[method]tcp-socket.connect",
+        "wasi:sockets/types@0.3.0#[method]tcp-socket.send",
+        "wasi:sockets/types@0.3.0#[method]udp-socket.receive",
+        "wasi:sockets/types@0.3.0#[method]udp-socket.send",
     ],
     generate_all
 });
+
+pub mod proxy {
+    wit_bindgen::generate!({
+        inline: "
+            package wasmtime:test;
+
+            world testp3-proxy {
+                include wasi:http/proxy@0.3.0;
+            }
+        ",
+        path: "../wasi-http/src/p3/wit",
+        world: "wasmtime:test/testp3-proxy",
+        default_bindings_module: "test_programs::p3::proxy",
+        pub_export_macro: true,
+        async: [
+            "wasi:http/handler@0.3.0#handle",
+        ],
+        with: {
+            "wasi:cli/stdout@0.3.0": crate::p3::wasi::cli::stdout,
+            "wasi:cli/stderr@0.3.0": crate::p3::wasi::cli::stderr,
+            "wasi:cli/stdin@0.3.0": crate::p3::wasi::cli::stdin,
+            "wasi:clocks/monotonic-clock@0.3.0": crate::p3::wasi::clocks::monotonic_clock,
+            "wasi:clocks/wall-clock@0.3.0": crate::p3::wasi::clocks::wall_clock,
+            "wasi:random/random@0.3.0": crate::p3::wasi::random::random,
+            "wasi:http/types@0.3.0": generate,
+            "wasi:http/handler@0.3.0": generate,
+        },
+    });
+}
+
+use self::wasi::sockets::types::{
+    IpAddress, IpSocketAddress, Ipv4SocketAddress, Ipv6SocketAddress,
+};
+
+impl PartialEq for IpAddress {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Ipv4(left), Self::Ipv4(right)) => left == right,
+            (Self::Ipv6(left), Self::Ipv6(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for Ipv4SocketAddress {
+    fn eq(&self, other: &Self) -> bool {
+        self.port == other.port && self.address == other.address
+    }
+}
+
+impl PartialEq for Ipv6SocketAddress {
+    fn eq(&self, other: &Self) -> bool {
+        self.port == other.port
+            && self.flow_info == other.flow_info
+            && self.address == other.address
+            && self.scope_id == other.scope_id
+    }
+}
+
+impl PartialEq for IpSocketAddress {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Ipv4(l0), Self::Ipv4(r0)) => l0 == r0,
+            (Self::Ipv6(l0), Self::Ipv6(r0)) => l0 == r0,
+            _ => false,
+        }
+    }
+}
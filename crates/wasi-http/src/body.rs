@@ -85,7 +85,7 @@ enum IncomingBodyState {
 
 /// Small wrapper around [`HyperIncomingBody`] which adds a timeout to every frame.
 #[derive(Debug)]
-struct BodyWithTimeout {
+pub(crate) struct BodyWithTimeout {
     /// Underlying stream that frames are coming from.
     inner: HyperIncomingBody,
     /// Currently active timeout that's reset between frames.
@@ -99,7 +99,10 @@ struct BodyWithTimeout {
 }
 
 impl BodyWithTimeout {
-    fn new(inner: HyperIncomingBody, between_bytes_timeout: Duration) -> BodyWithTimeout {
+    pub(crate) fn new(
+        inner: HyperIncomingBody,
+        between_bytes_timeout: Duration,
+    ) -> BodyWithTimeout {
         BodyWithTimeout {
             inner,
             between_bytes_timeout,
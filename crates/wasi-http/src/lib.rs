@@ -229,6 +229,9 @@ pub mod types;
 
 pub mod bindings;
 
+#[cfg(feature = "p3")]
+pub mod p3;
+
 pub use crate::error::{
     HttpError, HttpResult, http_request_error, hyper_request_error, hyper_response_error,
 };
@@ -0,0 +1,88 @@
+//! Raw bindings to the `wasi:http` package.
+
+use crate::p3::{Request, Response, WasiHttpView};
+use anyhow::Context as _;
+use wasmtime::component::Accessor;
+
+#[expect(missing_docs, reason = "bindgen-generated code")]
+mod generated {
+    wasmtime::component::bindgen!({
+        path: "src/p3/wit",
+        world: "wasi:http/proxy",
+        tracing: true,
+        trappable_imports: true,
+        concurrent_exports: true,
+        concurrent_imports: true,
+        async: {
+            only_imports: [
+                "wasi:cli/stdin@0.3.0#get-stdin",
+                "wasi:cli/stdout@0.3.0#set-stdout",
+                "wasi:cli/stderr@0.3.0#set-stderr",
+                "wasi:clocks/monotonic-clock@0.3.0#[async]wait-for",
+                "wasi:clocks/monotonic-clock@0.3.0#[async]wait-until",
+                "wasi:http/handler@0.3.0#handle",
+                "wasi:http/types@0.3.0#[static]request.new",
+                "wasi:http/types@0.3.0#[static]request.consume-body",
+                "wasi:http/types@0.3.0#[static]response.new",
+                "wasi:http/types@0.3.0#[static]response.consume-body",
+            ],
+        },
+        with: {
+            // Upstream package dependencies
+            "wasi:cli": wasmtime_wasi::p3::bindings::cli,
+            "wasi:clocks": wasmtime_wasi::p3::bindings::clocks,
+            "wasi:random": wasmtime_wasi::p3::bindings::random,
+
+            // Configure all WIT http resources to be defined types in this
+            // crate to use the `ResourceTable` helper methods.
+            "wasi:http/types/fields": crate::p3::Fields,
+            "wasi:http/types/request": crate::p3::Request,
+            "wasi:http/types/request-options": crate::p3::RequestOptions,
+            "wasi:http/types/response": crate::p3::Response,
+        },
+    });
+}
+
+pub use self::generated::wasi::*;
+
+/// Raw bindings to the `wasi:http/proxy` exports.
+pub use self::generated::exports;
+
+/// Bindings to the `wasi:http/proxy` world.
+pub use self::generated::{Proxy, ProxyIndices, ProxyPre};
+
+impl Proxy {
+    /// Calls the `wasi:http/handler.handle` function exported by the guest
+    /// with `request`.
+    ///
+    /// The body of the returned [`Response`] can be converted to a
+    /// [`http::Response`] with [`Response::into_http`].
+    pub async fn handle<T>(
+        &self,
+        store: &Accessor<T>,
+        request: Request,
+    ) -> wasmtime::Result<Result<Response, http::types::ErrorCode>>
+    where
+        T: WasiHttpView + 'static,
+    {
+        let request = store.with(|mut view| {
+            view.get()
+                .http()
+                .table
+                .push(request)
+                .context("failed to push request resource to table")
+        })?;
+        let response = match self.wasi_http_handler().call_handle(store, request).await? {
+            Ok(response) => response,
+            Err(err) => return Ok(Err(err)),
+        };
+        let response = store.with(|mut view| {
+            view.get()
+                .http()
+                .table
+                .delete(response)
+                .context("failed to delete response resource from table")
+        })?;
+        Ok(Ok(response))
+    }
+}
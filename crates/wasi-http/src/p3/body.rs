@@ -0,0 +1,270 @@
+use crate::p3::bindings::http::types::ErrorCode;
+use crate::p3::{Fields, HttpBody, WasiHttp};
+use anyhow::Context as _;
+use bytes::{Bytes, BytesMut};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use http_body::Frame;
+use http_body_util::BodyExt as _;
+use std::io::Cursor;
+use tokio::sync::mpsc;
+use wasmtime::component::{
+    Access, Accessor, AccessorTask, FutureReader, FutureWriter, HostFuture, HostStream, Resource,
+    StreamReader, StreamWriter,
+};
+
+/// The type of the `trailers` future of a body.
+pub(crate) type TrailersFuture = HostFuture<Result<Option<Resource<Fields>>, ErrorCode>>;
+
+/// The body of a [`Request`](crate::p3::Request) or a
+/// [`Response`](crate::p3::Response).
+pub(crate) enum Body {
+    /// A body created by the guest through `request.new` or `response.new`.
+    Guest {
+        /// The contents of the body, `None` if the body is empty.
+        contents: Option<HostStream<u8>>,
+        /// The future resolving to the trailers once `contents` is closed.
+        trailers: TrailersFuture,
+        /// The write end of the future reporting the result of transmitting
+        /// this body back to the guest.
+        result: FutureWriter<Result<(), ErrorCode>>,
+    },
+    /// A body created by the host.
+    Host(HttpBody),
+}
+
+impl Body {
+    /// Converts this body into a [`HttpBody`].
+    ///
+    /// Guest bodies are forwarded by a task spawned on the instance, so the
+    /// returned body only makes progress while the instance's event loop is
+    /// running.
+    pub(crate) fn into_http<T: 'static>(self, view: &mut Access<'_, T, WasiHttp>) -> HttpBody {
+        match self {
+            Body::Guest {
+                contents,
+                trailers,
+                result,
+            } => {
+                let contents = contents.map(|contents| contents.into_reader(&mut *view));
+                let trailers = trailers.into_reader(&mut *view);
+                let (tx, rx) = mpsc::channel(1);
+                view.spawn(GuestBodyTask {
+                    contents,
+                    trailers,
+                    result,
+                    tx,
+                });
+                ChannelBody { rx }.boxed()
+            }
+            Body::Host(body) => body,
+        }
+    }
+
+    /// Implements `consume-body` of both requests and responses.
+    pub(crate) fn consume<T: 'static>(
+        self,
+        view: &mut Access<'_, T, WasiHttp>,
+        res: HostFuture<Result<(), ErrorCode>>,
+    ) -> wasmtime::Result<(HostStream<u8>, TrailersFuture)> {
+        let instance = view.instance();
+        match self {
+            Body::Guest {
+                contents,
+                trailers,
+                result,
+            } => {
+                let contents = match contents {
+                    Some(contents) => contents,
+                    None => {
+                        // An empty body is represented by a stream which is
+                        // closed right away.
+                        let (_, rx) = instance
+                            .stream::<u8, Cursor<Bytes>, BytesMut>(&mut *view)
+                            .context("failed to create stream")?;
+                        rx.into()
+                    }
+                };
+                // The guest consuming this body is the one to report the result
+                // of its transmission to the guest that created it.
+                let res = res.into_reader(&mut *view);
+                view.spawn(ResultTask { res, result });
+                Ok((contents, trailers))
+            }
+            Body::Host(body) => {
+                // Nobody is interested in the result of transmitting a body
+                // created by the host.
+                drop(res.into_reader(&mut *view));
+                let (contents_tx, contents_rx) = instance
+                    .stream::<_, _, BytesMut>(&mut *view)
+                    .context("failed to create stream")?;
+                let (trailers_tx, trailers_rx) = instance
+                    .future(|| Err(ErrorCode::InternalError(None)), &mut *view)
+                    .context("failed to create future")?;
+                view.spawn(HostBodyTask {
+                    body,
+                    contents: contents_tx,
+                    trailers: trailers_tx,
+                });
+                Ok((contents_rx.into(), trailers_rx.into()))
+            }
+        }
+    }
+}
+
+/// A [`http_body::Body`] receiving its frames from a [`GuestBodyTask`].
+struct ChannelBody {
+    rx: mpsc::Receiver<Result<Frame<Bytes>, ErrorCode>>,
+}
+
+impl http_body::Body for ChannelBody {
+    type Data = Bytes;
+    type Error = ErrorCode;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, ErrorCode>>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Forwards the contents and trailers of a guest body to a [`ChannelBody`].
+struct GuestBodyTask {
+    contents: Option<StreamReader<BytesMut>>,
+    trailers: FutureReader<Result<Option<Resource<Fields>>, ErrorCode>>,
+    result: FutureWriter<Result<(), ErrorCode>>,
+    tx: mpsc::Sender<Result<Frame<Bytes>, ErrorCode>>,
+}
+
+impl GuestBodyTask {
+    async fn forward<T>(
+        contents: Option<StreamReader<BytesMut>>,
+        trailers: FutureReader<Result<Option<Resource<Fields>>, ErrorCode>>,
+        tx: &mpsc::Sender<Result<Frame<Bytes>, ErrorCode>>,
+        store: &Accessor<T, WasiHttp>,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        if let Some(mut contents) = contents {
+            let mut buf = BytesMut::with_capacity(8192);
+            while !contents.is_closed() {
+                buf = contents.read(store, buf).await;
+                if buf.is_empty() {
+                    continue;
+                }
+                let frame = Frame::data(buf.split().freeze());
+                if tx.send(Ok(frame)).await.is_err() {
+                    return Ok(Err(ErrorCode::ConnectionTerminated));
+                }
+                buf.reserve(8192);
+            }
+        }
+        let trailers = match trailers.read(store).await {
+            Some(Ok(Some(trailers))) => store.with(|mut view| {
+                view.get()
+                    .table
+                    .delete(trailers)
+                    .context("failed to delete trailers resource from table")
+            })?,
+            // A trailers future which was dropped without a value is treated
+            // like the absence of trailers.
+            Some(Ok(None)) | None => return Ok(Ok(())),
+            Some(Err(err)) => {
+                _ = tx.send(Err(err.clone())).await;
+                return Ok(Err(err));
+            }
+        };
+        if tx.send(Ok(Frame::trailers(trailers.fields))).await.is_err() {
+            return Ok(Err(ErrorCode::ConnectionTerminated));
+        }
+        Ok(Ok(()))
+    }
+}
+
+impl<T> AccessorTask<T, WasiHttp, wasmtime::Result<()>> for GuestBodyTask {
+    async fn run(self, store: &Accessor<T, WasiHttp>) -> wasmtime::Result<()> {
+        let Self {
+            contents,
+            trailers,
+            result,
+            tx,
+        } = self;
+        let res = Self::forward(contents, trailers, &tx, store).await?;
+        drop(tx);
+        result.write(store, res).await;
+        Ok(())
+    }
+}
+
+/// Forwards the frames of a host body to the streams given to a guest.
+struct HostBodyTask {
+    body: HttpBody,
+    contents: StreamWriter<Cursor<Bytes>>,
+    trailers: FutureWriter<Result<Option<Resource<Fields>>, ErrorCode>>,
+}
+
+impl<T> AccessorTask<T, WasiHttp, wasmtime::Result<()>> for HostBodyTask {
+    async fn run(mut self, store: &Accessor<T, WasiHttp>) -> wasmtime::Result<()> {
+        let result = loop {
+            if self.contents.is_closed() {
+                // The guest is no longer interested in the contents.
+                break Ok(None);
+            }
+            let frame = match self.body.frame().await {
+                Some(Ok(frame)) => frame,
+                Some(Err(err)) => break Err(err),
+                None => break Ok(None),
+            };
+            match frame.into_data() {
+                Ok(data) => {
+                    self.contents.write_all(store, Cursor::new(data)).await;
+                }
+                Err(frame) => {
+                    let Ok(trailers) = frame.into_trailers() else {
+                        continue;
+                    };
+                    let trailers = store.with(|mut view| {
+                        view.get()
+                            .table
+                            .push(Fields::new_immutable(trailers))
+                            .context("failed to push trailers resource to table")
+                    })?;
+                    break Ok(Some(trailers));
+                }
+            }
+        };
+        drop(self.contents);
+        let trailers = match &result {
+            Ok(Some(trailers)) => Some(trailers.rep()),
+            _ => None,
+        };
+        if !self.trailers.write(store, result).await {
+            if let Some(rep) = trailers {
+                // The guest dropped the future, so the trailers were never
+                // handed over and are still owned by the host.
+                store.with(|mut view| {
+                    view.get()
+                        .table
+                        .delete(Resource::<Fields>::new_own(rep))
+                        .context("failed to delete trailers resource from table")
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Forwards the result reported by the consumer of a guest body to the guest
+/// which created it.
+struct ResultTask {
+    res: FutureReader<Result<(), ErrorCode>>,
+    result: FutureWriter<Result<(), ErrorCode>>,
+}
+
+impl<T> AccessorTask<T, WasiHttp, wasmtime::Result<()>> for ResultTask {
+    async fn run(self, store: &Accessor<T, WasiHttp>) -> wasmtime::Result<()> {
+        if let Some(res) = self.res.read(store).await {
+            self.result.write(store, res).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::body::BodyWithTimeout;
+use crate::p3::bindings::http::types::ErrorCode;
+use crate::p3::{HttpBody, RequestOptions};
+use crate::types::{OutgoingRequestConfig, default_send_request_handler};
+use http_body_util::BodyExt as _;
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The default implementation of how an outgoing request is sent.
+///
+/// This implementation is used by the default [`WasiHttpCtx::send_request`]
+/// and shares its connection handling with the `wasi:http/outgoing-handler`
+/// implementation of WASIp2.
+///
+/// [`WasiHttpCtx::send_request`]: crate::p3::WasiHttpCtx::send_request
+pub async fn default_send_request(
+    request: http::Request<HttpBody>,
+    options: Option<RequestOptions>,
+) -> Result<http::Response<HttpBody>, ErrorCode> {
+    let options = options.unwrap_or_default();
+    let config = OutgoingRequestConfig {
+        use_tls: request.uri().scheme() == Some(&http::uri::Scheme::HTTPS),
+        connect_timeout: options.connect_timeout.unwrap_or(DEFAULT_TIMEOUT),
+        first_byte_timeout: options.first_byte_timeout.unwrap_or(DEFAULT_TIMEOUT),
+        between_bytes_timeout: options.between_bytes_timeout.unwrap_or(DEFAULT_TIMEOUT),
+    };
+    let between_bytes_timeout = config.between_bytes_timeout;
+    let request = request.map(|body| body.map_err(Into::into).boxed());
+    let response = default_send_request_handler(request, config)
+        .await
+        .map_err(ErrorCode::from)?;
+    let worker = response.worker;
+    Ok(response.resp.map(|body| {
+        BodyWithTimeout::new(body, between_bytes_timeout)
+            .map_err(move |err| {
+                // The connection is driven by `worker`, so it must be kept
+                // alive for as long as the body may still be read.
+                let _ = &worker;
+                err.into()
+            })
+            .boxed()
+    }))
+}
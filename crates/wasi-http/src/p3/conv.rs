@@ -0,0 +1,161 @@
+use crate::p3::bindings::http::types::{Method, Scheme};
+use core::str::FromStr as _;
+
+impl From<http::Method> for Method {
+    fn from(method: http::Method) -> Self {
+        if method == http::Method::GET {
+            Self::Get
+        } else if method == http::Method::HEAD {
+            Self::Head
+        } else if method == http::Method::POST {
+            Self::Post
+        } else if method == http::Method::PUT {
+            Self::Put
+        } else if method == http::Method::DELETE {
+            Self::Delete
+        } else if method == http::Method::CONNECT {
+            Self::Connect
+        } else if method == http::Method::OPTIONS {
+            Self::Options
+        } else if method == http::Method::TRACE {
+            Self::Trace
+        } else if method == http::Method::PATCH {
+            Self::Patch
+        } else {
+            Self::Other(method.to_string())
+        }
+    }
+}
+
+impl TryFrom<Method> for http::Method {
+    type Error = http::method::InvalidMethod;
+
+    fn try_from(method: Method) -> Result<Self, Self::Error> {
+        match method {
+            Method::Get => Ok(Self::GET),
+            Method::Head => Ok(Self::HEAD),
+            Method::Post => Ok(Self::POST),
+            Method::Put => Ok(Self::PUT),
+            Method::Delete => Ok(Self::DELETE),
+            Method::Connect => Ok(Self::CONNECT),
+            Method::Options => Ok(Self::OPTIONS),
+            Method::Trace => Ok(Self::TRACE),
+            Method::Patch => Ok(Self::PATCH),
+            Method::Other(method) => Self::from_bytes(method.as_bytes()),
+        }
+    }
+}
+
+impl From<http::uri::Scheme> for Scheme {
+    fn from(scheme: http::uri::Scheme) -> Self {
+        if scheme == http::uri::Scheme::HTTP {
+            Self::Http
+        } else if scheme == http::uri::Scheme::HTTPS {
+            Self::Https
+        } else {
+            Self::Other(scheme.as_str().into())
+        }
+    }
+}
+
+impl TryFrom<Scheme> for http::uri::Scheme {
+    type Error = http::uri::InvalidUri;
+
+    fn try_from(scheme: Scheme) -> Result<Self, Self::Error> {
+        match scheme {
+            Scheme::Http => Ok(Self::HTTP),
+            Scheme::Https => Ok(Self::HTTPS),
+            Scheme::Other(scheme) => Self::from_str(&scheme),
+        }
+    }
+}
+
+/// Implements `From` between the `error-code` variants of two versions of
+/// `wasi:http`, which are structurally identical.
+macro_rules! convert_error_code {
+    ($($from:ident)::+ => $($to:ident)::+) => {
+        impl From<$($from)::+::ErrorCode> for $($to)::+::ErrorCode {
+            fn from(code: $($from)::+::ErrorCode) -> Self {
+                use $($from)::+ as from;
+                use $($to)::+ as to;
+
+                let field_size = |payload: from::FieldSizePayload| to::FieldSizePayload {
+                    field_name: payload.field_name,
+                    field_size: payload.field_size,
+                };
+                match code {
+                    from::ErrorCode::DnsTimeout => Self::DnsTimeout,
+                    from::ErrorCode::DnsError(payload) => Self::DnsError(to::DnsErrorPayload {
+                        rcode: payload.rcode,
+                        info_code: payload.info_code,
+                    }),
+                    from::ErrorCode::DestinationNotFound => Self::DestinationNotFound,
+                    from::ErrorCode::DestinationUnavailable => Self::DestinationUnavailable,
+                    from::ErrorCode::DestinationIpProhibited => Self::DestinationIpProhibited,
+                    from::ErrorCode::DestinationIpUnroutable => Self::DestinationIpUnroutable,
+                    from::ErrorCode::ConnectionRefused => Self::ConnectionRefused,
+                    from::ErrorCode::ConnectionTerminated => Self::ConnectionTerminated,
+                    from::ErrorCode::ConnectionTimeout => Self::ConnectionTimeout,
+                    from::ErrorCode::ConnectionReadTimeout => Self::ConnectionReadTimeout,
+                    from::ErrorCode::ConnectionWriteTimeout => Self::ConnectionWriteTimeout,
+                    from::ErrorCode::ConnectionLimitReached => Self::ConnectionLimitReached,
+                    from::ErrorCode::TlsProtocolError => Self::TlsProtocolError,
+                    from::ErrorCode::TlsCertificateError => Self::TlsCertificateError,
+                    from::ErrorCode::TlsAlertReceived(payload) => {
+                        Self::TlsAlertReceived(to::TlsAlertReceivedPayload {
+                            alert_id: payload.alert_id,
+                            alert_message: payload.alert_message,
+                        })
+                    }
+                    from::ErrorCode::HttpRequestDenied => Self::HttpRequestDenied,
+                    from::ErrorCode::HttpRequestLengthRequired => Self::HttpRequestLengthRequired,
+                    from::ErrorCode::HttpRequestBodySize(size) => Self::HttpRequestBodySize(size),
+                    from::ErrorCode::HttpRequestMethodInvalid => Self::HttpRequestMethodInvalid,
+                    from::ErrorCode::HttpRequestUriInvalid => Self::HttpRequestUriInvalid,
+                    from::ErrorCode::HttpRequestUriTooLong => Self::HttpRequestUriTooLong,
+                    from::ErrorCode::HttpRequestHeaderSectionSize(size) => {
+                        Self::HttpRequestHeaderSectionSize(size)
+                    }
+                    from::ErrorCode::HttpRequestHeaderSize(payload) => {
+                        Self::HttpRequestHeaderSize(payload.map(field_size))
+                    }
+                    from::ErrorCode::HttpRequestTrailerSectionSize(size) => {
+                        Self::HttpRequestTrailerSectionSize(size)
+                    }
+                    from::ErrorCode::HttpRequestTrailerSize(payload) => {
+                        Self::HttpRequestTrailerSize(field_size(payload))
+                    }
+                    from::ErrorCode::HttpResponseIncomplete => Self::HttpResponseIncomplete,
+                    from::ErrorCode::HttpResponseHeaderSectionSize(size) => {
+                        Self::HttpResponseHeaderSectionSize(size)
+                    }
+                    from::ErrorCode::HttpResponseHeaderSize(payload) => {
+                        Self::HttpResponseHeaderSize(field_size(payload))
+                    }
+                    from::ErrorCode::HttpResponseBodySize(size) => Self::HttpResponseBodySize(size),
+                    from::ErrorCode::HttpResponseTrailerSectionSize(size) => {
+                        Self::HttpResponseTrailerSectionSize(size)
+                    }
+                    from::ErrorCode::HttpResponseTrailerSize(payload) => {
+                        Self::HttpResponseTrailerSize(field_size(payload))
+                    }
+                    from::ErrorCode::HttpResponseTransferCoding(coding) => {
+                        Self::HttpResponseTransferCoding(coding)
+                    }
+                    from::ErrorCode::HttpResponseContentCoding(coding) => {
+                        Self::HttpResponseContentCoding(coding)
+                    }
+                    from::ErrorCode::HttpResponseTimeout => Self::HttpResponseTimeout,
+                    from::ErrorCode::HttpUpgradeFailed => Self::HttpUpgradeFailed,
+                    from::ErrorCode::HttpProtocolError => Self::HttpProtocolError,
+                    from::ErrorCode::LoopDetected => Self::LoopDetected,
+                    from::ErrorCode::ConfigurationError => Self::ConfigurationError,
+                    from::ErrorCode::InternalError(msg) => Self::InternalError(msg),
+                }
+            }
+        }
+    };
+}
+
+convert_error_code!(crate::bindings::http::types => crate::p3::bindings::http::types);
+convert_error_code!(crate::p3::bindings::http::types => crate::bindings::http::types);
@@ -0,0 +1,28 @@
+use http::HeaderMap;
+
+/// The concrete type behind a `wasi:http/types/fields` resource.
+#[derive(Clone, Debug, Default)]
+pub struct Fields {
+    /// The headers or trailers contained in this resource.
+    pub fields: HeaderMap,
+    /// Whether the guest is allowed to modify `fields`.
+    pub(crate) mutable: bool,
+}
+
+impl Fields {
+    /// Creates a new mutable set of fields.
+    pub fn new(fields: HeaderMap) -> Self {
+        Self {
+            fields,
+            mutable: true,
+        }
+    }
+
+    /// Creates a new set of fields which the guest is not allowed to modify.
+    pub fn new_immutable(fields: HeaderMap) -> Self {
+        Self {
+            fields,
+            mutable: false,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::p3::bindings::http::handler::{Host, HostConcurrent};
+use crate::p3::bindings::http::types::ErrorCode;
+use crate::p3::{Request, Response, WasiHttp, WasiHttpCtxView};
+use anyhow::Context as _;
+use wasmtime::component::{Accessor, Resource};
+
+impl Host for WasiHttpCtxView<'_> {}
+
+impl HostConcurrent for WasiHttp {
+    async fn handle<T: 'static>(
+        store: &Accessor<T, Self>,
+        request: Resource<Request>,
+    ) -> wasmtime::Result<Result<Resource<Response>, ErrorCode>> {
+        let fut = store.with(|mut view| -> wasmtime::Result<_> {
+            let request = view
+                .get()
+                .table
+                .delete(request)
+                .context("failed to delete request resource from table")?;
+            let (request, options) = match request.into_http(&mut view) {
+                Ok(request) => request,
+                Err(err) => return Ok(Err(err)),
+            };
+            Ok(Ok(view.get().ctx.send_request(request, options)))
+        })?;
+        let fut = match fut {
+            Ok(fut) => fut,
+            Err(err) => return Ok(Err(err)),
+        };
+        let response = match fut.await {
+            Ok(response) => Response::from_http(response),
+            Err(err) => return Ok(Err(err)),
+        };
+        store.with(|mut view| {
+            let response = view
+                .get()
+                .table
+                .push(response)
+                .context("failed to push response resource to table")?;
+            Ok(Ok(response))
+        })
+    }
+}
@@ -0,0 +1,621 @@
+use crate::p3::bindings::http::types::{
+    Duration, ErrorCode, FieldName, FieldValue, HeaderError, Host, HostConcurrent, HostFields,
+    HostRequest, HostRequestConcurrent, HostRequestOptions, HostResponse, HostResponseConcurrent,
+    Method, RequestOptionsError, Scheme, StatusCode,
+};
+use crate::p3::body::{Body, TrailersFuture};
+use crate::p3::{Fields, Request, RequestOptions, Response, WasiHttp, WasiHttpCtxView};
+use anyhow::Context as _;
+use core::str::FromStr as _;
+use http::header::{HeaderName, HeaderValue};
+use http::uri::{Authority, PathAndQuery};
+use wasmtime::component::{Accessor, HostFuture, HostStream, Resource, ResourceTable};
+
+fn get_fields<'a>(
+    table: &'a ResourceTable,
+    fields: &Resource<Fields>,
+) -> wasmtime::Result<&'a Fields> {
+    table
+        .get(fields)
+        .context("failed to get fields resource from table")
+}
+
+/// Returns the fields to modify, or `HeaderError::Immutable` if the guest
+/// isn't allowed to modify them.
+fn get_fields_mut<'a>(
+    table: &'a mut ResourceTable,
+    fields: &Resource<Fields>,
+) -> wasmtime::Result<Result<&'a mut Fields, HeaderError>> {
+    let fields = table
+        .get_mut(fields)
+        .context("failed to get fields resource from table")?;
+    if fields.mutable {
+        Ok(Ok(fields))
+    } else {
+        Ok(Err(HeaderError::Immutable))
+    }
+}
+
+fn get_request<'a>(
+    table: &'a mut ResourceTable,
+    request: &Resource<Request>,
+) -> wasmtime::Result<&'a mut Request> {
+    table
+        .get_mut(request)
+        .context("failed to get request resource from table")
+}
+
+fn get_request_options<'a>(
+    table: &'a mut ResourceTable,
+    options: &Resource<RequestOptions>,
+) -> wasmtime::Result<&'a mut RequestOptions> {
+    table
+        .get_mut(options)
+        .context("failed to get request options resource from table")
+}
+
+/// Returns the options to modify, or `RequestOptionsError::Immutable` if the
+/// guest isn't allowed to modify them.
+fn get_request_options_mut<'a>(
+    table: &'a mut ResourceTable,
+    options: &Resource<RequestOptions>,
+) -> wasmtime::Result<Result<&'a mut RequestOptions, RequestOptionsError>> {
+    let options = get_request_options(table, options)?;
+    if options.mutable {
+        Ok(Ok(options))
+    } else {
+        Ok(Err(RequestOptionsError::Immutable))
+    }
+}
+
+fn get_response<'a>(
+    table: &'a mut ResourceTable,
+    response: &Resource<Response>,
+) -> wasmtime::Result<&'a mut Response> {
+    table
+        .get_mut(response)
+        .context("failed to get response resource from table")
+}
+
+fn duration_from_nanos(duration: Option<Duration>) -> Option<std::time::Duration> {
+    duration.map(std::time::Duration::from_nanos)
+}
+
+fn duration_to_nanos(duration: Option<std::time::Duration>) -> Option<Duration> {
+    duration.map(|duration| duration.as_nanos().try_into().unwrap_or(Duration::MAX))
+}
+
+impl WasiHttpCtxView<'_> {
+    /// Parses a header name, checking that the guest is allowed to use it.
+    fn header_name(&mut self, name: &str) -> Result<HeaderName, HeaderError> {
+        let name = HeaderName::from_bytes(name.as_bytes()).or(Err(HeaderError::InvalidSyntax))?;
+        if self.ctx.is_forbidden_header(&name) {
+            return Err(HeaderError::Forbidden);
+        }
+        Ok(name)
+    }
+
+    /// Takes the headers passed by the guest to `request.new` or
+    /// `response.new` out of the table.
+    fn take_fields(&mut self, fields: Resource<Fields>) -> wasmtime::Result<http::HeaderMap> {
+        let fields = self
+            .table
+            .delete(fields)
+            .context("failed to delete fields resource from table")?;
+        Ok(fields.fields)
+    }
+}
+
+impl Host for WasiHttpCtxView<'_> {}
+
+impl HostConcurrent for WasiHttp {}
+
+impl HostFields for WasiHttpCtxView<'_> {
+    fn new(&mut self) -> wasmtime::Result<Resource<Fields>> {
+        self.table
+            .push(Fields::new(http::HeaderMap::new()))
+            .context("failed to push fields resource to table")
+    }
+
+    fn from_list(
+        &mut self,
+        entries: Vec<(FieldName, FieldValue)>,
+    ) -> wasmtime::Result<Result<Resource<Fields>, HeaderError>> {
+        let mut fields = http::HeaderMap::new();
+        for (name, value) in entries {
+            let name = match self.header_name(&name) {
+                Ok(name) => name,
+                Err(err) => return Ok(Err(err)),
+            };
+            let Ok(value) = HeaderValue::from_bytes(&value) else {
+                return Ok(Err(HeaderError::InvalidSyntax));
+            };
+            fields.append(name, value);
+        }
+        let fields = self
+            .table
+            .push(Fields::new(fields))
+            .context("failed to push fields resource to table")?;
+        Ok(Ok(fields))
+    }
+
+    fn get(
+        &mut self,
+        fields: Resource<Fields>,
+        name: FieldName,
+    ) -> wasmtime::Result<Vec<FieldValue>> {
+        let fields = get_fields(self.table, &fields)?;
+        let Ok(name) = HeaderName::from_bytes(name.as_bytes()) else {
+            return Ok(Vec::new());
+        };
+        Ok(fields
+            .fields
+            .get_all(name)
+            .into_iter()
+            .map(|value| value.as_bytes().to_vec())
+            .collect())
+    }
+
+    fn has(&mut self, fields: Resource<Fields>, name: FieldName) -> wasmtime::Result<bool> {
+        let fields = get_fields(self.table, &fields)?;
+        match HeaderName::from_bytes(name.as_bytes()) {
+            Ok(name) => Ok(fields.fields.contains_key(name)),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn set(
+        &mut self,
+        fields: Resource<Fields>,
+        name: FieldName,
+        value: Vec<FieldValue>,
+    ) -> wasmtime::Result<Result<(), HeaderError>> {
+        let name = match self.header_name(&name) {
+            Ok(name) => name,
+            Err(err) => return Ok(Err(err)),
+        };
+        let mut values = Vec::with_capacity(value.len());
+        for value in value {
+            let Ok(value) = HeaderValue::from_bytes(&value) else {
+                return Ok(Err(HeaderError::InvalidSyntax));
+            };
+            values.push(value);
+        }
+        Ok(get_fields_mut(self.table, &fields)?.map(|fields| {
+            fields.fields.remove(&name);
+            for value in values {
+                fields.fields.append(&name, value);
+            }
+        }))
+    }
+
+    fn delete(
+        &mut self,
+        fields: Resource<Fields>,
+        name: FieldName,
+    ) -> wasmtime::Result<Result<(), HeaderError>> {
+        let name = match self.header_name(&name) {
+            Ok(name) => name,
+            Err(err) => return Ok(Err(err)),
+        };
+        Ok(get_fields_mut(self.table, &fields)?.map(|fields| {
+            fields.fields.remove(name);
+        }))
+    }
+
+    fn get_and_delete(
+        &mut self,
+        fields: Resource<Fields>,
+        name: FieldName,
+    ) -> wasmtime::Result<Result<Vec<FieldValue>, HeaderError>> {
+        let name = match self.header_name(&name) {
+            Ok(name) => name,
+            Err(err) => return Ok(Err(err)),
+        };
+        Ok(get_fields_mut(self.table, &fields)?.map(|fields| {
+            let http::header::Entry::Occupied(entry) = fields.fields.entry(name) else {
+                return Vec::new();
+            };
+            let (_, values) = entry.remove_entry_mult();
+            values.map(|value| value.as_bytes().to_vec()).collect()
+        }))
+    }
+
+    fn append(
+        &mut self,
+        fields: Resource<Fields>,
+        name: FieldName,
+        value: FieldValue,
+    ) -> wasmtime::Result<Result<(), HeaderError>> {
+        let name = match self.header_name(&name) {
+            Ok(name) => name,
+            Err(err) => return Ok(Err(err)),
+        };
+        let Ok(value) = HeaderValue::from_bytes(&value) else {
+            return Ok(Err(HeaderError::InvalidSyntax));
+        };
+        Ok(get_fields_mut(self.table, &fields)?.map(|fields| {
+            fields.fields.append(name, value);
+        }))
+    }
+
+    fn copy_all(
+        &mut self,
+        fields: Resource<Fields>,
+    ) -> wasmtime::Result<Vec<(FieldName, FieldValue)>> {
+        let fields = get_fields(self.table, &fields)?;
+        Ok(fields
+            .fields
+            .iter()
+            .map(|(name, value)| (name.as_str().into(), value.as_bytes().to_vec()))
+            .collect())
+    }
+
+    fn clone(&mut self, fields: Resource<Fields>) -> wasmtime::Result<Resource<Fields>> {
+        let fields = get_fields(self.table, &fields)?.fields.clone();
+        self.table
+            .push(Fields::new(fields))
+            .context("failed to push fields resource to table")
+    }
+
+    fn drop(&mut self, fields: Resource<Fields>) -> wasmtime::Result<()> {
+        self.table
+            .delete(fields)
+            .context("failed to delete fields resource from table")?;
+        Ok(())
+    }
+}
+
+impl HostRequestConcurrent for WasiHttp {
+    async fn new<T: 'static>(
+        store: &Accessor<T, Self>,
+        headers: Resource<Fields>,
+        contents: Option<HostStream<u8>>,
+        trailers: TrailersFuture,
+        options: Option<Resource<RequestOptions>>,
+    ) -> wasmtime::Result<(Resource<Request>, HostFuture<Result<(), ErrorCode>>)> {
+        store.with(|mut view| {
+            let instance = view.instance();
+            let (result_tx, result_rx) = instance
+                .future(|| Err(ErrorCode::InternalError(None)), &mut view)
+                .context("failed to create future")?;
+            let mut view = view.get();
+            let headers = view.take_fields(headers)?;
+            let options = options
+                .map(|options| {
+                    view.table
+                        .delete(options)
+                        .context("failed to delete request options resource from table")
+                })
+                .transpose()?;
+            let request = Request {
+                method: http::Method::GET,
+                scheme: None,
+                authority: None,
+                path_with_query: None,
+                headers,
+                options,
+                body: Body::Guest {
+                    contents,
+                    trailers,
+                    result: result_tx,
+                },
+            };
+            let request = view
+                .table
+                .push(request)
+                .context("failed to push request resource to table")?;
+            Ok((request, result_rx.into()))
+        })
+    }
+
+    async fn consume_body<T: 'static>(
+        store: &Accessor<T, Self>,
+        request: Resource<Request>,
+        res: HostFuture<Result<(), ErrorCode>>,
+    ) -> wasmtime::Result<(HostStream<u8>, TrailersFuture)> {
+        store.with(|mut view| {
+            let request = view
+                .get()
+                .table
+                .delete(request)
+                .context("failed to delete request resource from table")?;
+            request.body.consume(&mut view, res)
+        })
+    }
+}
+
+impl HostRequest for WasiHttpCtxView<'_> {
+    fn get_method(&mut self, request: Resource<Request>) -> wasmtime::Result<Method> {
+        let request = get_request(self.table, &request)?;
+        Ok(request.method.clone().into())
+    }
+
+    fn set_method(
+        &mut self,
+        request: Resource<Request>,
+        method: Method,
+    ) -> wasmtime::Result<Result<(), ()>> {
+        let request = get_request(self.table, &request)?;
+        let Ok(method) = method.try_into() else {
+            return Ok(Err(()));
+        };
+        request.method = method;
+        Ok(Ok(()))
+    }
+
+    fn get_path_with_query(
+        &mut self,
+        request: Resource<Request>,
+    ) -> wasmtime::Result<Option<String>> {
+        let request = get_request(self.table, &request)?;
+        Ok(request
+            .path_with_query
+            .as_ref()
+            .map(|path_with_query| path_with_query.as_str().into()))
+    }
+
+    fn set_path_with_query(
+        &mut self,
+        request: Resource<Request>,
+        path_with_query: Option<String>,
+    ) -> wasmtime::Result<Result<(), ()>> {
+        let request = get_request(self.table, &request)?;
+        let path_with_query = match path_with_query.as_deref().map(PathAndQuery::from_str) {
+            Some(Ok(path_with_query)) => Some(path_with_query),
+            Some(Err(_)) => return Ok(Err(())),
+            None => None,
+        };
+        request.path_with_query = path_with_query;
+        Ok(Ok(()))
+    }
+
+    fn get_scheme(&mut self, request: Resource<Request>) -> wasmtime::Result<Option<Scheme>> {
+        let request = get_request(self.table, &request)?;
+        Ok(request.scheme.clone().map(Into::into))
+    }
+
+    fn set_scheme(
+        &mut self,
+        request: Resource<Request>,
+        scheme: Option<Scheme>,
+    ) -> wasmtime::Result<Result<(), ()>> {
+        let request = get_request(self.table, &request)?;
+        let scheme = match scheme.map(TryInto::try_into) {
+            Some(Ok(scheme)) => Some(scheme),
+            Some(Err(_)) => return Ok(Err(())),
+            None => None,
+        };
+        request.scheme = scheme;
+        Ok(Ok(()))
+    }
+
+    fn get_authority(&mut self, request: Resource<Request>) -> wasmtime::Result<Option<String>> {
+        let request = get_request(self.table, &request)?;
+        Ok(request
+            .authority
+            .as_ref()
+            .map(|authority| authority.as_str().into()))
+    }
+
+    fn set_authority(
+        &mut self,
+        request: Resource<Request>,
+        authority: Option<String>,
+    ) -> wasmtime::Result<Result<(), ()>> {
+        let request = get_request(self.table, &request)?;
+        let authority = match authority.as_deref().map(Authority::from_str) {
+            Some(Ok(authority)) => Some(authority),
+            Some(Err(_)) => return Ok(Err(())),
+            None => None,
+        };
+        request.authority = authority;
+        Ok(Ok(()))
+    }
+
+    fn get_options(
+        &mut self,
+        request: Resource<Request>,
+    ) -> wasmtime::Result<Option<Resource<RequestOptions>>> {
+        let request = get_request(self.table, &request)?;
+        let Some(options) = request.options.clone() else {
+            return Ok(None);
+        };
+        let options = self
+            .table
+            .push(RequestOptions {
+                mutable: false,
+                ..options
+            })
+            .context("failed to push request options resource to table")?;
+        Ok(Some(options))
+    }
+
+    fn get_headers(&mut self, request: Resource<Request>) -> wasmtime::Result<Resource<Fields>> {
+        let request = get_request(self.table, &request)?;
+        let headers = Fields::new_immutable(request.headers.clone());
+        self.table
+            .push(headers)
+            .context("failed to push fields resource to table")
+    }
+
+    fn drop(&mut self, request: Resource<Request>) -> wasmtime::Result<()> {
+        self.table
+            .delete(request)
+            .context("failed to delete request resource from table")?;
+        Ok(())
+    }
+}
+
+impl HostRequestOptions for WasiHttpCtxView<'_> {
+    fn new(&mut self) -> wasmtime::Result<Resource<RequestOptions>> {
+        self.table
+            .push(RequestOptions {
+                mutable: true,
+                ..RequestOptions::default()
+            })
+            .context("failed to push request options resource to table")
+    }
+
+    fn get_connect_timeout(
+        &mut self,
+        options: Resource<RequestOptions>,
+    ) -> wasmtime::Result<Option<Duration>> {
+        let options = get_request_options(self.table, &options)?;
+        Ok(duration_to_nanos(options.connect_timeout))
+    }
+
+    fn set_connect_timeout(
+        &mut self,
+        options: Resource<RequestOptions>,
+        duration: Option<Duration>,
+    ) -> wasmtime::Result<Result<(), RequestOptionsError>> {
+        Ok(
+            get_request_options_mut(self.table, &options)?.map(|options| {
+                options.connect_timeout = duration_from_nanos(duration);
+            }),
+        )
+    }
+
+    fn get_first_byte_timeout(
+        &mut self,
+        options: Resource<RequestOptions>,
+    ) -> wasmtime::Result<Option<Duration>> {
+        let options = get_request_options(self.table, &options)?;
+        Ok(duration_to_nanos(options.first_byte_timeout))
+    }
+
+    fn set_first_byte_timeout(
+        &mut self,
+        options: Resource<RequestOptions>,
+        duration: Option<Duration>,
+    ) -> wasmtime::Result<Result<(), RequestOptionsError>> {
+        Ok(
+            get_request_options_mut(self.table, &options)?.map(|options| {
+                options.first_byte_timeout = duration_from_nanos(duration);
+            }),
+        )
+    }
+
+    fn get_between_bytes_timeout(
+        &mut self,
+        options: Resource<RequestOptions>,
+    ) -> wasmtime::Result<Option<Duration>> {
+        let options = get_request_options(self.table, &options)?;
+        Ok(duration_to_nanos(options.between_bytes_timeout))
+    }
+
+    fn set_between_bytes_timeout(
+        &mut self,
+        options: Resource<RequestOptions>,
+        duration: Option<Duration>,
+    ) -> wasmtime::Result<Result<(), RequestOptionsError>> {
+        Ok(
+            get_request_options_mut(self.table, &options)?.map(|options| {
+                options.between_bytes_timeout = duration_from_nanos(duration);
+            }),
+        )
+    }
+
+    fn clone(
+        &mut self,
+        options: Resource<RequestOptions>,
+    ) -> wasmtime::Result<Resource<RequestOptions>> {
+        let options = get_request_options(self.table, &options)?.clone();
+        self.table
+            .push(RequestOptions {
+                mutable: true,
+                ..options
+            })
+            .context("failed to push request options resource to table")
+    }
+
+    fn drop(&mut self, options: Resource<RequestOptions>) -> wasmtime::Result<()> {
+        self.table
+            .delete(options)
+            .context("failed to delete request options resource from table")?;
+        Ok(())
+    }
+}
+
+impl HostResponseConcurrent for WasiHttp {
+    async fn new<T: 'static>(
+        store: &Accessor<T, Self>,
+        headers: Resource<Fields>,
+        contents: Option<HostStream<u8>>,
+        trailers: TrailersFuture,
+    ) -> wasmtime::Result<(Resource<Response>, HostFuture<Result<(), ErrorCode>>)> {
+        store.with(|mut view| {
+            let instance = view.instance();
+            let (result_tx, result_rx) = instance
+                .future(|| Err(ErrorCode::InternalError(None)), &mut view)
+                .context("failed to create future")?;
+            let mut view = view.get();
+            let headers = view.take_fields(headers)?;
+            let response = Response {
+                status: http::StatusCode::OK,
+                headers,
+                body: Body::Guest {
+                    contents,
+                    trailers,
+                    result: result_tx,
+                },
+            };
+            let response = view
+                .table
+                .push(response)
+                .context("failed to push response resource to table")?;
+            Ok((response, result_rx.into()))
+        })
+    }
+
+    async fn consume_body<T: 'static>(
+        store: &Accessor<T, Self>,
+        response: Resource<Response>,
+        res: HostFuture<Result<(), ErrorCode>>,
+    ) -> wasmtime::Result<(HostStream<u8>, TrailersFuture)> {
+        store.with(|mut view| {
+            let response = view
+                .get()
+                .table
+                .delete(response)
+                .context("failed to delete response resource from table")?;
+            response.body.consume(&mut view, res)
+        })
+    }
+}
+
+impl HostResponse for WasiHttpCtxView<'_> {
+    fn get_status_code(&mut self, response: Resource<Response>) -> wasmtime::Result<StatusCode> {
+        let response = get_response(self.table, &response)?;
+        Ok(response.status.as_u16())
+    }
+
+    fn set_status_code(
+        &mut self,
+        response: Resource<Response>,
+        status_code: StatusCode,
+    ) -> wasmtime::Result<Result<(), ()>> {
+        let response = get_response(self.table, &response)?;
+        let Ok(status) = http::StatusCode::from_u16(status_code) else {
+            return Ok(Err(()));
+        };
+        response.status = status;
+        Ok(Ok(()))
+    }
+
+    fn get_headers(&mut self, response: Resource<Response>) -> wasmtime::Result<Resource<Fields>> {
+        let response = get_response(self.table, &response)?;
+        let headers = Fields::new_immutable(response.headers.clone());
+        self.table
+            .push(headers)
+            .context("failed to push fields resource to table")
+    }
+
+    fn drop(&mut self, response: Resource<Response>) -> wasmtime::Result<()> {
+        self.table
+            .delete(response)
+            .context("failed to delete response resource from table")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! Experimental, unstable and incomplete implementation of wasip3 version of `wasi:http`.
+//!
+//! This module is under heavy development.
+//! It is not compliant with semver and is not ready
+//! for production use.
+//!
+//! Bug and security fixes limited to wasip3 will not be given patch releases.
+//!
+//! Documentation of this module may be incorrect or out-of-sync with the implementation.
+
+pub mod bindings;
+mod body;
+mod client;
+mod conv;
+mod fields;
+mod host;
+mod request;
+mod response;
+
+use crate::p3::bindings::http::types::ErrorCode;
+use bytes::Bytes;
+use core::future::Future;
+use core::pin::Pin;
+use http::HeaderName;
+use http_body_util::combinators::BoxBody;
+use wasmtime::component::{HasData, Linker, ResourceTable};
+
+pub use self::client::default_send_request;
+pub use self::fields::Fields;
+pub use self::request::{Request, RequestOptions};
+pub use self::response::Response;
+
+/// The body type of requests and responses exchanged with the host.
+pub type HttpBody = BoxBody<Bytes, ErrorCode>;
+
+/// The future returned by [`WasiHttpCtx::send_request`].
+pub type SendRequestFuture =
+    Pin<Box<dyn Future<Output = Result<http::Response<HttpBody>, ErrorCode>> + Send>>;
+
+/// Per-[`Store`] behavior of the `wasi:http` interfaces.
+///
+/// All methods have default implementations, [`DefaultWasiHttpCtx`] can be
+/// used by embedders which don't need to customize anything.
+///
+/// [`Store`]: wasmtime::Store
+pub trait WasiHttpCtx: Send {
+    /// Whether a given header should be considered forbidden and not allowed.
+    fn is_forbidden_header(&mut self, name: &HeaderName) -> bool {
+        crate::types::DEFAULT_FORBIDDEN_HEADERS.contains(name)
+    }
+
+    /// Send an outgoing request on behalf of a guest calling the
+    /// `wasi:http/handler.handle` import.
+    ///
+    /// The URI of `request` always contains a scheme and an authority.
+    fn send_request(
+        &mut self,
+        request: http::Request<HttpBody>,
+        options: Option<RequestOptions>,
+    ) -> SendRequestFuture {
+        Box::pin(default_send_request(request, options))
+    }
+}
+
+/// A [`WasiHttpCtx`] which uses the default behavior for everything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultWasiHttpCtx;
+
+impl WasiHttpCtx for DefaultWasiHttpCtx {}
+
+/// The state required by the `wasi:http` interfaces, as projected out of the
+/// embedder's `T` by [`WasiHttpView::http`].
+pub struct WasiHttpCtxView<'a> {
+    /// The embedder's customization of `wasi:http`.
+    pub ctx: &'a mut dyn WasiHttpCtx,
+    /// The table in which all `wasi:http` resources are stored.
+    pub table: &'a mut ResourceTable,
+}
+
+/// A trait which provides access to the [`WasiHttpCtx`] and [`ResourceTable`]
+/// inside the embedder's `T` of [`Store<T>`][`Store`].
+///
+/// [`Store`]: wasmtime::Store
+pub trait WasiHttpView: Send {
+    /// Yields mutable access to the `wasi:http` state of this store.
+    fn http(&mut self) -> WasiHttpCtxView<'_>;
+}
+
+impl<T: ?Sized + WasiHttpView> WasiHttpView for &mut T {
+    fn http(&mut self) -> WasiHttpCtxView<'_> {
+        T::http(self)
+    }
+}
+
+impl<T: ?Sized + WasiHttpView> WasiHttpView for Box<T> {
+    fn http(&mut self) -> WasiHttpCtxView<'_> {
+        T::http(self)
+    }
+}
+
+/// Add all `wasi:http` interfaces from this module into the `linker` provided.
+///
+/// This only adds the `wasi:http/types` and `wasi:http/handler` interfaces,
+/// [`wasmtime_wasi::p3::add_to_linker`] can be used to add the remaining
+/// interfaces of the `wasi:http/proxy` world.
+///
+/// # Example
+///
+/// ```
+/// use wasmtime::{Engine, Result, Store, Config};
+/// use wasmtime::component::{Linker, ResourceTable};
+/// use wasmtime_wasi_http::p3::{DefaultWasiHttpCtx, WasiHttpCtxView, WasiHttpView};
+///
+/// fn main() -> Result<()> {
+///     let mut config = Config::new();
+///     config.async_support(true);
+///     config.wasm_component_model_async(true);
+///     let engine = Engine::new(&config)?;
+///
+///     let mut linker = Linker::<MyState>::new(&engine);
+///     wasmtime_wasi_http::p3::add_to_linker(&mut linker)?;
+///     // ... add any further functionality to `linker` if desired ...
+///
+///     let mut store = Store::new(
+///         &engine,
+///         MyState::default(),
+///     );
+///
+///     // ... use `linker` to instantiate within `store` ...
+///
+///     Ok(())
+/// }
+///
+/// #[derive(Default)]
+/// struct MyState {
+///     http: DefaultWasiHttpCtx,
+///     table: ResourceTable,
+/// }
+///
+/// impl WasiHttpView for MyState {
+///     fn http(&mut self) -> WasiHttpCtxView<'_> {
+///         WasiHttpCtxView {
+///             ctx: &mut self.http,
+///             table: &mut self.table,
+///         }
+///     }
+/// }
+/// ```
+pub fn add_to_linker<T>(linker: &mut Linker<T>) -> wasmtime::Result<()>
+where
+    T: WasiHttpView + 'static,
+{
+    bindings::http::types::add_to_linker::<_, WasiHttp>(linker, T::http)?;
+    bindings::http::handler::add_to_linker::<_, WasiHttp>(linker, T::http)?;
+    Ok(())
+}
+
+pub(crate) struct WasiHttp;
+
+impl HasData for WasiHttp {
+    type Data<'a> = WasiHttpCtxView<'a>;
+}
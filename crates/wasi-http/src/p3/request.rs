@@ -0,0 +1,113 @@
+use crate::p3::bindings::http::types::ErrorCode;
+use crate::p3::body::Body;
+use crate::p3::{HttpBody, WasiHttp};
+use http::uri::{Authority, PathAndQuery, Scheme};
+use http::{HeaderMap, Method};
+use http_body_util::BodyExt as _;
+use std::time::Duration;
+use wasmtime::component::Access;
+
+/// The concrete type behind a `wasi:http/types/request` resource.
+pub struct Request {
+    /// The method of the request.
+    pub method: Method,
+    /// The scheme of the request, `None` if unspecified.
+    pub scheme: Option<Scheme>,
+    /// The authority of the request, `None` if unspecified.
+    pub authority: Option<Authority>,
+    /// The path and query of the request, `None` if unspecified.
+    pub path_with_query: Option<PathAndQuery>,
+    /// The headers of the request.
+    pub headers: HeaderMap,
+    /// The options to use when sending the request over the network.
+    pub options: Option<RequestOptions>,
+    pub(crate) body: Body,
+}
+
+impl Request {
+    /// Creates a new request with the given `body` which has been created by
+    /// the host.
+    pub fn new(
+        method: Method,
+        scheme: Option<Scheme>,
+        authority: Option<Authority>,
+        path_with_query: Option<PathAndQuery>,
+        headers: HeaderMap,
+        body: impl http_body::Body<Data = bytes::Bytes, Error = ErrorCode> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            method,
+            scheme,
+            authority,
+            path_with_query,
+            headers,
+            options: None,
+            body: Body::Host(body.boxed()),
+        }
+    }
+
+    /// Creates a new request from a [`http::Request`], for example to pass a
+    /// request received by the host to a guest exporting `wasi:http/handler`.
+    pub fn from_http<B>(request: http::Request<B>) -> Self
+    where
+        B: http_body::Body<Data = bytes::Bytes, Error = ErrorCode> + Send + Sync + 'static,
+    {
+        let (parts, body) = request.into_parts();
+        let uri = parts.uri.into_parts();
+        Self::new(
+            parts.method,
+            uri.scheme,
+            uri.authority,
+            uri.path_and_query,
+            parts.headers,
+            body,
+        )
+    }
+
+    /// Converts this request into a [`http::Request`] to be sent over the
+    /// network by [`WasiHttpCtx::send_request`](crate::p3::WasiHttpCtx::send_request).
+    pub(crate) fn into_http<T: 'static>(
+        self,
+        view: &mut Access<'_, T, WasiHttp>,
+    ) -> Result<(http::Request<HttpBody>, Option<RequestOptions>), ErrorCode> {
+        let scheme = self.scheme.unwrap_or(Scheme::HTTPS);
+        // We can only support http/https
+        if scheme != Scheme::HTTP && scheme != Scheme::HTTPS {
+            return Err(ErrorCode::HttpProtocolError);
+        }
+        let Some(authority) = self.authority else {
+            return Err(ErrorCode::HttpRequestUriInvalid);
+        };
+        let mut uri = http::Uri::builder()
+            .scheme(scheme)
+            .authority(authority.clone());
+        if let Some(path_with_query) = self.path_with_query {
+            uri = uri.path_and_query(path_with_query);
+        } else {
+            uri = uri.path_and_query("/");
+        }
+        let uri = uri.build().map_err(|_| ErrorCode::HttpRequestUriInvalid)?;
+        let mut request = http::Request::new(self.body.into_http(view));
+        *request.method_mut() = self.method;
+        *request.uri_mut() = uri;
+        *request.headers_mut() = self.headers;
+        let host = http::HeaderValue::from_str(authority.as_str())
+            .map_err(|_| ErrorCode::HttpRequestUriInvalid)?;
+        request.headers_mut().insert(http::header::HOST, host);
+        Ok((request, self.options))
+    }
+}
+
+/// The concrete type behind a `wasi:http/types/request-options` resource.
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    /// The timeout for the initial connect to the HTTP server.
+    pub connect_timeout: Option<Duration>,
+    /// The timeout for receiving the first byte of the response body.
+    pub first_byte_timeout: Option<Duration>,
+    /// The timeout for receiving subsequent chunks of bytes in the response
+    /// body stream.
+    pub between_bytes_timeout: Option<Duration>,
+    /// Whether the guest is allowed to modify these options.
+    pub(crate) mutable: bool,
+}
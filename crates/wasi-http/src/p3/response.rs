@@ -0,0 +1,50 @@
+use crate::p3::bindings::http::types::ErrorCode;
+use crate::p3::body::Body;
+use crate::p3::{HttpBody, WasiHttp, WasiHttpView};
+use http::{HeaderMap, StatusCode};
+use http_body_util::BodyExt as _;
+use wasmtime::component::Accessor;
+
+/// The concrete type behind a `wasi:http/types/response` resource.
+pub struct Response {
+    /// The status code of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+    pub(crate) body: Body,
+}
+
+impl Response {
+    /// Creates a new response from a [`http::Response`], for example one
+    /// received by [`WasiHttpCtx::send_request`](crate::p3::WasiHttpCtx::send_request).
+    pub fn from_http<B>(response: http::Response<B>) -> Self
+    where
+        B: http_body::Body<Data = bytes::Bytes, Error = ErrorCode> + Send + Sync + 'static,
+    {
+        let (parts, body) = response.into_parts();
+        Self {
+            status: parts.status,
+            headers: parts.headers,
+            body: Body::Host(body.boxed()),
+        }
+    }
+
+    /// Converts this response into a [`http::Response`], for example to send a
+    /// response returned by a guest exporting `wasi:http/handler` over the
+    /// network.
+    ///
+    /// The body of a response created by a guest is forwarded by a task
+    /// running on the component instance of `store`, so it only makes progress
+    /// while that instance's event loop is running.
+    pub fn into_http<T>(self, store: &Accessor<T>) -> http::Response<HttpBody>
+    where
+        T: WasiHttpView + 'static,
+    {
+        let store = store.with_data::<WasiHttp>(T::http);
+        let body = store.with(|mut view| self.body.into_http(&mut view));
+        let mut response = http::Response::new(body);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        response
+    }
+}
@@ -9,6 +9,8 @@ macro_rules! assert_test_exists {
 
 mod http_server;
 mod p2;
+#[cfg(feature = "p3")]
+mod p3;
 
 mod body {
     use http_body_util::{BodyExt, Empty, Full, combinators::BoxBody};
@@ -0,0 +1,123 @@
+use anyhow::Context as _;
+use bytes::Bytes;
+use http_body::Frame;
+use http_body_util::{BodyExt as _, StreamBody};
+use std::path::Path;
+use test_programs_artifacts::*;
+use wasmtime::Store;
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime_wasi::p3::{WasiCtx, WasiCtxView, WasiView};
+use wasmtime_wasi_http::p3::bindings::Proxy;
+use wasmtime_wasi_http::p3::bindings::http::types::ErrorCode;
+use wasmtime_wasi_http::p3::{DefaultWasiHttpCtx, Request, WasiHttpCtxView, WasiHttpView};
+
+foreach_p3_http!(assert_test_exists);
+
+struct Ctx {
+    table: ResourceTable,
+    p2: wasmtime_wasi::p2::WasiCtx,
+    p3: WasiCtx,
+    http: DefaultWasiHttpCtx,
+}
+
+impl WasiView for Ctx {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.p3,
+            table: &mut self.table,
+        }
+    }
+}
+
+impl WasiHttpView for Ctx {
+    fn http(&mut self) -> WasiHttpCtxView<'_> {
+        WasiHttpCtxView {
+            ctx: &mut self.http,
+            table: &mut self.table,
+        }
+    }
+}
+
+// TODO: Remove once test components are not built for `wasm32-wasip1`
+impl wasmtime_wasi::p2::WasiView for Ctx {
+    fn ctx(&mut self) -> &mut wasmtime_wasi::p2::WasiCtx {
+        &mut self.p2
+    }
+}
+
+// TODO: Remove once test components are not built for `wasm32-wasip1`
+impl wasmtime_wasi::p2::IoView for Ctx {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+/// Sends `request` to the `wasi:http/handler` exported by the component at
+/// `path`, returning the response with its body fully collected.
+async fn handle(
+    path: &str,
+    request: http::Request<wasmtime_wasi_http::p3::HttpBody>,
+) -> anyhow::Result<http::Response<http_body_util::Collected<Bytes>>> {
+    let engine = test_programs_artifacts::engine(|config| {
+        config.async_support(true);
+        config.wasm_component_model_async(true);
+    });
+    let component =
+        Component::from_file(&engine, Path::new(path)).context("failed to compile component")?;
+
+    let mut linker = Linker::new(&engine);
+    // TODO: Remove once test components are not built for `wasm32-wasip1`
+    wasmtime_wasi::p2::add_to_linker_async(&mut linker)
+        .context("failed to link `wasi:cli@0.2.x`")?;
+    wasmtime_wasi::p3::add_to_linker(&mut linker).context("failed to link `wasi:cli@0.3.x`")?;
+    wasmtime_wasi_http::p3::add_to_linker(&mut linker)
+        .context("failed to link `wasi:http@0.3.x`")?;
+
+    let ctx = Ctx {
+        table: ResourceTable::default(),
+        p2: wasmtime_wasi::p2::WasiCtx::builder()
+            .inherit_stdout()
+            .inherit_stderr()
+            .build(),
+        p3: WasiCtx::builder().inherit_stdout().inherit_stderr().build(),
+        http: DefaultWasiHttpCtx,
+    };
+    let mut store = Store::new(&engine, ctx);
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let proxy = Proxy::new(&mut store, &instance)?;
+    instance
+        .run_concurrent(&mut store, async move |store| {
+            let response = proxy
+                .handle(store, Request::from_http(request))
+                .await?
+                .context("`wasi:http/handler#handle` failed")?;
+            let (parts, body) = response.into_http(store).into_parts();
+            let body = body.collect().await.context("failed to collect body")?;
+            anyhow::Ok(http::Response::from_parts(parts, body))
+        })
+        .await?
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn p3_http_echo() -> anyhow::Result<()> {
+    let mut trailers = http::HeaderMap::new();
+    trailers.insert("x-trailer", "done".parse()?);
+    let frames = [
+        Ok::<_, ErrorCode>(Frame::data(Bytes::from_static(b"hello, "))),
+        Ok(Frame::data(Bytes::from_static(b"world"))),
+        Ok(Frame::trailers(trailers.clone())),
+    ];
+    let request = http::Request::builder()
+        .method(http::Method::POST)
+        .uri("http://localhost/echo")
+        .header("x-header", "value")
+        .body(StreamBody::new(futures::stream::iter(frames)).boxed())?;
+
+    let response = handle(P3_HTTP_ECHO_COMPONENT, request).await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()["x-header"], "value");
+    let body = response.into_body();
+    assert_eq!(body.trailers(), Some(&trailers));
+    assert_eq!(body.to_bytes(), "hello, world");
+    Ok(())
+}
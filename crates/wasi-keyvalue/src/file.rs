@@ -0,0 +1,283 @@
+use crate::{Error, KeyValueStore};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Bytes at the start of every file, followed by records.
+const MAGIC: &[u8; 8] = b"wasmkv\0\x01";
+
+/// Size of the header of a record: the length of its changes followed by
+/// their checksum.
+const RECORD_HEADER: usize = 4 + 8;
+
+/// Tags of changes within a record.
+const SET: u8 = 0;
+const DELETE: u8 = 1;
+
+/// Logs smaller than this are never compacted.
+const MIN_COMPACT_LEN: u64 = 1 << 20;
+
+/// A [`KeyValueStore`] which persists its contents to a file on the host.
+///
+/// The file is a log of changes. All changes made by one operation are
+/// appended as a single record which is synced to storage before any of them
+/// become visible, so an operation takes effect entirely or not at all even
+/// if the process is interrupted while writing it. Incomplete records at the
+/// end of the log are discarded when it's opened, and the log is compacted if
+/// most of it consists of changes which have since been overwritten.
+///
+/// The contents are also kept in memory, and a file may only be used by one
+/// store at a time, whether in this process or another.
+pub struct FileStore {
+    state: Mutex<State>,
+}
+
+struct State {
+    file: File,
+    /// Length of the valid records in `file`, where the next one is written.
+    len: u64,
+    data: BTreeMap<String, Vec<u8>>,
+}
+
+impl FileStore {
+    /// Opens the store persisted at `path`, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        if contents.is_empty() {
+            file.write_all(MAGIC)?;
+            file.sync_all()?;
+            contents.extend_from_slice(MAGIC);
+        }
+        if !contents.starts_with(MAGIC) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a key-value store file",
+            ));
+        }
+
+        let mut data = BTreeMap::new();
+        let mut len = MAGIC.len();
+        while let Some((changes, record_len)) = decode_record(&contents[len..])? {
+            apply(&mut data, changes);
+            len += record_len;
+        }
+        let mut len = len as u64;
+        if len < contents.len() as u64 {
+            // The last record was only partially written, so its changes
+            // never took effect.
+            file.set_len(len)?;
+            file.sync_all()?;
+        }
+
+        let compacted_len = (MAGIC.len() + RECORD_HEADER) as u64
+            + data
+                .iter()
+                .map(|(k, v)| (1 + 4 + k.len() + 4 + v.len()) as u64)
+                .sum::<u64>();
+        if len >= MIN_COMPACT_LEN && len > 2 * compacted_len {
+            drop(file);
+            len = compact(path, &data)?;
+            file = OpenOptions::new().read(true).write(true).open(path)?;
+        }
+        file.seek(SeekFrom::Start(len))?;
+
+        Ok(Self {
+            state: Mutex::new(State { file, len, data }),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl State {
+    /// Durably appends `changes` to the log and then applies them.
+    fn write(&mut self, changes: Vec<(String, Option<Vec<u8>>)>) -> io::Result<()> {
+        let record = encode_record(&changes)?;
+        let result = self
+            .file
+            .write_all(&record)
+            .and_then(|()| self.file.sync_data());
+        if let Err(e) = result {
+            // Discard whatever part of the record was written so that it
+            // doesn't take effect once the log is opened again.
+            let _ = self.file.set_len(self.len);
+            let _ = self.file.seek(SeekFrom::Start(self.len));
+            return Err(e);
+        }
+        self.len += record.len() as u64;
+        apply(&mut self.data, changes);
+        Ok(())
+    }
+}
+
+impl KeyValueStore for FileStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.state().data.get(key).cloned())
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        let state = self.state();
+        Ok(keys
+            .iter()
+            .map(|key| state.data.get(key).cloned())
+            .collect())
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.state().data.contains_key(key))
+    }
+
+    fn apply(&self, changes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error> {
+        Ok(self.state().write(changes)?)
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        new: Vec<u8>,
+    ) -> Result<bool, Error> {
+        let mut state = self.state();
+        if state.data.get(key).map(|v| &v[..]) != current {
+            return Ok(false);
+        }
+        state.write(vec![(key.to_string(), Some(new))])?;
+        Ok(true)
+    }
+
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error> {
+        let cursor = usize::try_from(cursor.unwrap_or(0)).unwrap_or(usize::MAX);
+        Ok((
+            self.state().data.keys().skip(cursor).cloned().collect(),
+            None,
+        ))
+    }
+}
+
+fn apply(data: &mut BTreeMap<String, Vec<u8>>, changes: Vec<(String, Option<Vec<u8>>)>) {
+    for (key, value) in changes {
+        match value {
+            Some(value) => data.insert(key, value),
+            None => data.remove(&key),
+        };
+    }
+}
+
+/// Replaces the log at `path` with a single record setting all of `data`,
+/// returning its length.
+fn compact(path: &Path, data: &BTreeMap<String, Vec<u8>>) -> io::Result<u64> {
+    let changes = data
+        .iter()
+        .map(|(k, v)| (k.clone(), Some(v.clone())))
+        .collect::<Vec<_>>();
+    let record = encode_record(&changes)?;
+
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(".tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(MAGIC)?;
+    file.write_all(&record)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp, path)?;
+    Ok((MAGIC.len() + record.len()) as u64)
+}
+
+fn encode_record(changes: &[(String, Option<Vec<u8>>)]) -> io::Result<Vec<u8>> {
+    fn encode_len(out: &mut Vec<u8>, len: usize) -> io::Result<()> {
+        let len = u32::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "change too large"))?;
+        out.extend_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+
+    let mut body = Vec::new();
+    for (key, value) in changes {
+        match value {
+            Some(value) => {
+                body.push(SET);
+                encode_len(&mut body, key.len())?;
+                body.extend_from_slice(key.as_bytes());
+                encode_len(&mut body, value.len())?;
+                body.extend_from_slice(value);
+            }
+            None => {
+                body.push(DELETE);
+                encode_len(&mut body, key.len())?;
+                body.extend_from_slice(key.as_bytes());
+            }
+        }
+    }
+
+    let mut record = Vec::with_capacity(RECORD_HEADER + body.len());
+    encode_len(&mut record, body.len())?;
+    record.extend_from_slice(&checksum(&body).to_le_bytes());
+    record.extend_from_slice(&body);
+    Ok(record)
+}
+
+/// Decodes the record at the start of `bytes`, returning its changes and
+/// length, or `None` if it's incomplete.
+fn decode_record(bytes: &[u8]) -> io::Result<Option<(Vec<(String, Option<Vec<u8>>)>, usize)>> {
+    let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "corrupt key-value store file");
+
+    let Some((header, rest)) = bytes.split_at_checked(RECORD_HEADER) else {
+        return Ok(None);
+    };
+    let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    let sum = u64::from_le_bytes(header[4..].try_into().unwrap());
+    let Some(mut body) = rest.get(..len) else {
+        return Ok(None);
+    };
+    if checksum(body) != sum {
+        return Err(corrupt());
+    }
+
+    fn take<'a>(body: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        let (bytes, rest) = body.split_at_checked(len)?;
+        *body = rest;
+        Some(bytes)
+    }
+    fn take_len(body: &mut &[u8]) -> Option<usize> {
+        Some(u32::from_le_bytes(take(body, 4)?.try_into().unwrap()) as usize)
+    }
+    fn take_str(body: &mut &[u8]) -> Option<String> {
+        let len = take_len(body)?;
+        String::from_utf8(take(body, len)?.to_vec()).ok()
+    }
+
+    let mut changes = Vec::new();
+    while let Some(&tag) = body.first() {
+        body = &body[1..];
+        let change = match tag {
+            SET => take_str(&mut body).and_then(|key| {
+                let len = take_len(&mut body)?;
+                Some((key, Some(take(&mut body, len)?.to_vec())))
+            }),
+            DELETE => take_str(&mut body).map(|key| (key, None)),
+            _ => None,
+        };
+        changes.push(change.ok_or_else(corrupt)?);
+    }
+    Ok(Some((changes, RECORD_HEADER + len)))
+}
+
+/// 64-bit FNV-1a, to detect records which were damaged after being written.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
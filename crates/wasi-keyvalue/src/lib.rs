@@ -4,8 +4,15 @@
 //! API. With this crate, the runtime can run components that call APIs in
 //! [wasi-keyvalue] and provide components with access to key-value storages.
 //!
-//! Currently supported storage backends:
-//! * In-Memory (empty identifier)
+//! Stores are opened by WebAssembly through an identifier which is mapped to a
+//! [`KeyValueStore`] when building the [`WasiKeyValueCtx`]. Built-in stores
+//! are:
+//!
+//! * [`MemoryStore`], whose contents are lost when it's dropped. Unless
+//!   configured otherwise the empty identifier refers to one of these.
+//! * [`FileStore`], which persists its contents to a file on the host.
+//!
+//! Embedders can provide other storage by implementing [`KeyValueStore`].
 //!
 //! # Examples
 //!
@@ -84,12 +91,23 @@ mod generated {
 use self::generated::wasi::keyvalue;
 use anyhow::Result;
 use std::collections::HashMap;
+use std::sync::Arc;
 use wasmtime::component::{HasData, Resource, ResourceTable, ResourceTableError};
 
-#[doc(hidden)]
+mod file;
+mod memory;
+
+pub use self::file::FileStore;
+pub use self::memory::MemoryStore;
+
+/// Errors reported by a [`KeyValueStore`].
+#[derive(Debug)]
 pub enum Error {
+    /// The identifier doesn't refer to a store.
     NoSuchStore,
+    /// Access to the store was denied.
     AccessDenied,
+    /// Any other error, described by its message.
     Other(String),
 }
 
@@ -99,15 +117,96 @@ impl From<ResourceTableError> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Other(err.to_string())
+    }
+}
+
+/// A key-value store which WebAssembly can open with `wasi:keyvalue/store.open`.
+///
+/// Stores are shared by all buckets opened with the same identifier and must
+/// support being used concurrently. Operations are blocking.
+pub trait KeyValueStore: Send + Sync {
+    /// Returns the value of `key`, if it has one.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Returns the values of all `keys`.
+    ///
+    /// The default implementation calls [`KeyValueStore::get`] for each key,
+    /// so the values may have been read at different points in time.
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
+    /// Returns whether `key` has a value.
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Applies all `changes`, where a value of `None` deletes the key.
+    ///
+    /// This must be atomic: either all of the changes take effect or none of
+    /// them do, and no other operation observes only some of them.
+    fn apply(&self, changes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error>;
+
+    /// Sets `key` to `new` only if its value is currently `current`, where
+    /// `None` means it has no value, returning whether it was set.
+    ///
+    /// This must be atomic with respect to all other operations.
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        new: Vec<u8>,
+    ) -> Result<bool, Error>;
+
+    /// Returns the keys of this store starting at `cursor`, along with the
+    /// cursor to pass to retrieve the next keys if there are more.
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error>;
+}
+
+impl<T: KeyValueStore + ?Sized> KeyValueStore for Arc<T> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        T::get(self, key)
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        T::get_many(self, keys)
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        T::exists(self, key)
+    }
+
+    fn apply(&self, changes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error> {
+        T::apply(self, changes)
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        new: Vec<u8>,
+    ) -> Result<bool, Error> {
+        T::compare_and_swap(self, key, current, new)
+    }
+
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error> {
+        T::list_keys(self, cursor)
+    }
+}
+
 #[doc(hidden)]
 pub struct Bucket {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    store: Arc<dyn KeyValueStore>,
 }
 
 /// Builder-style structure used to create a [`WasiKeyValueCtx`].
 #[derive(Default)]
 pub struct WasiKeyValueCtxBuilder {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    in_memory_data: Vec<(String, Vec<u8>)>,
+    stores: HashMap<String, Arc<dyn KeyValueStore>>,
 }
 
 impl WasiKeyValueCtxBuilder {
@@ -117,6 +216,9 @@ impl WasiKeyValueCtxBuilder {
     }
 
     /// Preset data for the In-Memory provider.
+    ///
+    /// This is the [`MemoryStore`] opened with the empty identifier, unless
+    /// another store was configured for it with [`WasiKeyValueCtxBuilder::store`].
     pub fn in_memory_data<I, K, V>(mut self, data: I) -> Self
     where
         I: IntoIterator<Item = (K, V)>,
@@ -130,17 +232,43 @@ impl WasiKeyValueCtxBuilder {
         self
     }
 
+    /// Makes `store` available to WebAssembly under `identifier`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wasmtime_wasi_keyvalue::{FileStore, WasiKeyValueCtxBuilder};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let ctx = WasiKeyValueCtxBuilder::new()
+    ///     .store("cache", FileStore::open("cache.kv")?)
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn store(
+        mut self,
+        identifier: impl Into<String>,
+        store: impl KeyValueStore + 'static,
+    ) -> Self {
+        self.stores.insert(identifier.into(), Arc::new(store));
+        self
+    }
+
     /// Uses the configured context so far to construct the final [`WasiKeyValueCtx`].
-    pub fn build(self) -> WasiKeyValueCtx {
+    pub fn build(mut self) -> WasiKeyValueCtx {
+        self.stores
+            .entry(String::new())
+            .or_insert_with(|| Arc::new(MemoryStore::from_iter(self.in_memory_data)));
         WasiKeyValueCtx {
-            in_memory_data: self.in_memory_data,
+            stores: self.stores,
         }
     }
 }
 
 /// Capture the state necessary for use in the `wasi-keyvalue` API implementation.
 pub struct WasiKeyValueCtx {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    stores: HashMap<String, Arc<dyn KeyValueStore>>,
 }
 
 impl WasiKeyValueCtx {
@@ -165,11 +293,11 @@ impl<'a> WasiKeyValue<'a> {
 
 impl keyvalue::store::Host for WasiKeyValue<'_> {
     fn open(&mut self, identifier: String) -> Result<Resource<Bucket>, Error> {
-        match identifier.as_str() {
-            "" => Ok(self.table.push(Bucket {
-                in_memory_data: self.ctx.in_memory_data.clone(),
+        match self.ctx.stores.get(&identifier) {
+            Some(store) => Ok(self.table.push(Bucket {
+                store: store.clone(),
             })?),
-            _ => Err(Error::NoSuchStore),
+            None => Err(Error::NoSuchStore),
         }
     }
 
@@ -184,25 +312,23 @@ impl keyvalue::store::Host for WasiKeyValue<'_> {
 
 impl keyvalue::store::HostBucket for WasiKeyValue<'_> {
     fn get(&mut self, bucket: Resource<Bucket>, key: String) -> Result<Option<Vec<u8>>, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(bucket.in_memory_data.get(&key).cloned())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.get(&key)
     }
 
     fn set(&mut self, bucket: Resource<Bucket>, key: String, value: Vec<u8>) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        bucket.in_memory_data.insert(key, value);
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.apply(vec![(key, Some(value))])
     }
 
     fn delete(&mut self, bucket: Resource<Bucket>, key: String) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        bucket.in_memory_data.remove(&key);
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.apply(vec![(key, None)])
     }
 
     fn exists(&mut self, bucket: Resource<Bucket>, key: String) -> Result<bool, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(bucket.in_memory_data.contains_key(&key))
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.exists(&key)
     }
 
     fn list_keys(
@@ -210,14 +336,9 @@ impl keyvalue::store::HostBucket for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         cursor: Option<u64>,
     ) -> Result<keyvalue::store::KeyResponse, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        let keys: Vec<String> = bucket.in_memory_data.keys().cloned().collect();
-        let cursor = cursor.unwrap_or(0) as usize;
-        let keys_slice = &keys[cursor..];
-        Ok(keyvalue::store::KeyResponse {
-            keys: keys_slice.to_vec(),
-            cursor: None,
-        })
+        let bucket = self.table.get(&bucket)?;
+        let (keys, cursor) = bucket.store.list_keys(cursor)?;
+        Ok(keyvalue::store::KeyResponse { keys, cursor })
     }
 
     fn drop(&mut self, bucket: Resource<Bucket>) -> Result<()> {
@@ -233,18 +354,29 @@ impl keyvalue::atomics::Host for WasiKeyValue<'_> {
         key: String,
         delta: u64,
     ) -> Result<u64, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        let value = bucket
-            .in_memory_data
-            .entry(key.clone())
-            .or_insert("0".to_string().into_bytes());
-        let current_value = String::from_utf8(value.clone())
-            .map_err(|e| Error::Other(e.to_string()))?
-            .parse::<u64>()
-            .map_err(|e| Error::Other(e.to_string()))?;
-        let new_value = current_value + delta;
-        *value = new_value.to_string().into_bytes();
-        Ok(new_value)
+        let bucket = self.table.get(&bucket)?;
+        // Retry until no other update to `key` happened between reading the
+        // current value and writing the new one.
+        loop {
+            let current = bucket.store.get(&key)?;
+            let current_value = match &current {
+                Some(value) => std::str::from_utf8(value)
+                    .map_err(|e| Error::Other(e.to_string()))?
+                    .parse::<u64>()
+                    .map_err(|e| Error::Other(e.to_string()))?,
+                None => 0,
+            };
+            let new_value = current_value
+                .checked_add(delta)
+                .ok_or_else(|| Error::Other("integer overflow".to_string()))?;
+            let new = new_value.to_string().into_bytes();
+            if bucket
+                .store
+                .compare_and_swap(&key, current.as_deref(), new)?
+            {
+                return Ok(new_value);
+            }
+        }
     }
 }
 
@@ -254,15 +386,12 @@ impl keyvalue::batch::Host for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         keys: Vec<String>,
     ) -> Result<Vec<Option<(String, Vec<u8>)>>, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
+        let bucket = self.table.get(&bucket)?;
+        let values = bucket.store.get_many(&keys)?;
         Ok(keys
             .into_iter()
-            .map(|key| {
-                bucket
-                    .in_memory_data
-                    .get(&key)
-                    .map(|value| (key.clone(), value.clone()))
-            })
+            .zip(values)
+            .map(|(key, value)| value.map(|value| (key, value)))
             .collect())
     }
 
@@ -271,19 +400,20 @@ impl keyvalue::batch::Host for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         key_values: Vec<(String, Vec<u8>)>,
     ) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        for (key, value) in key_values {
-            bucket.in_memory_data.insert(key, value);
-        }
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.apply(
+            key_values
+                .into_iter()
+                .map(|(key, value)| (key, Some(value)))
+                .collect(),
+        )
     }
 
     fn delete_many(&mut self, bucket: Resource<Bucket>, keys: Vec<String>) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        for key in keys {
-            bucket.in_memory_data.remove(&key);
-        }
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket
+            .store
+            .apply(keys.into_iter().map(|key| (key, None)).collect())
     }
 }
 
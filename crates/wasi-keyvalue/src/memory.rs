@@ -0,0 +1,84 @@
+use crate::{Error, KeyValueStore};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// A [`KeyValueStore`] which keeps its contents in memory.
+///
+/// All buckets opened for the same store share its contents, which are lost
+/// once it's dropped.
+#[derive(Default)]
+pub struct MemoryStore {
+    data: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    /// Creates a new, empty, store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> MutexGuard<'_, BTreeMap<String, Vec<u8>>> {
+        self.data.lock().unwrap()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MemoryStore
+where
+    K: Into<String>,
+    V: Into<Vec<u8>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(data: I) -> Self {
+        Self {
+            data: Mutex::new(
+                data.into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl KeyValueStore for MemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.data().get(key).cloned())
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        let data = self.data();
+        Ok(keys.iter().map(|key| data.get(key).cloned()).collect())
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.data().contains_key(key))
+    }
+
+    fn apply(&self, changes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error> {
+        let mut data = self.data();
+        for (key, value) in changes {
+            match value {
+                Some(value) => data.insert(key, value),
+                None => data.remove(&key),
+            };
+        }
+        Ok(())
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        new: Vec<u8>,
+    ) -> Result<bool, Error> {
+        let mut data = self.data();
+        if data.get(key).map(|v| &v[..]) != current {
+            return Ok(false);
+        }
+        data.insert(key.to_string(), new);
+        Ok(true)
+    }
+
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error> {
+        let cursor = usize::try_from(cursor.unwrap_or(0)).unwrap_or(usize::MAX);
+        Ok((self.data().keys().skip(cursor).cloned().collect(), None))
+    }
+}
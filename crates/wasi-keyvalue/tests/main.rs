@@ -1,11 +1,14 @@
 use anyhow::{Result, anyhow};
+use std::io::Write;
 use test_programs_artifacts::{KEYVALUE_MAIN_COMPONENT, foreach_keyvalue};
 use wasmtime::{
     Store,
     component::{Component, Linker, ResourceTable},
 };
 use wasmtime_wasi::p2::{IoView, WasiCtx, WasiCtxBuilder, WasiView, bindings::Command};
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{
+    FileStore, KeyValueStore, WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder,
+};
 
 struct Ctx {
     table: ResourceTable,
@@ -68,3 +71,79 @@ async fn keyvalue_main() -> Result<()> {
     )
     .await
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keyvalue_main_file_store() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.kv");
+
+    let store = FileStore::open(&path)?;
+    store
+        .apply(vec![("atomics_key".to_string(), Some(b"5".to_vec()))])
+        .unwrap();
+    run_wasi(
+        KEYVALUE_MAIN_COMPONENT,
+        Ctx {
+            table: ResourceTable::new(),
+            wasi_ctx: WasiCtxBuilder::new().inherit_stderr().build(),
+            wasi_keyvalue_ctx: WasiKeyValueCtxBuilder::new().store("", store).build(),
+        },
+    )
+    .await?;
+
+    let store = FileStore::open(&path)?;
+    let values = store
+        .get_many(&[
+            "atomics_key".into(),
+            "hello".into(),
+            "a1".into(),
+            "b1".into(),
+        ])
+        .unwrap();
+    assert_eq!(
+        values,
+        [Some(b"6".to_vec()), None, None, Some(b"v1".to_vec())]
+    );
+    Ok(())
+}
+
+#[test]
+fn file_store_discards_incomplete_records() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store.kv");
+
+    let store = FileStore::open(&path)?;
+    store
+        .apply(vec![
+            ("a".to_string(), Some(b"1".to_vec())),
+            ("b".to_string(), Some(b"2".to_vec())),
+        ])
+        .unwrap();
+    drop(store);
+    let len = std::fs::metadata(&path)?.len();
+
+    // Simulate a crash while writing a record, which must not take effect.
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path)?;
+    file.write_all(&[100, 0, 0, 0, 1, 2, 3])?;
+    drop(file);
+
+    let store = FileStore::open(&path)?;
+    assert_eq!(std::fs::metadata(&path)?.len(), len);
+    assert_eq!(store.get("a").unwrap(), Some(b"1".to_vec()));
+    assert!(
+        store
+            .compare_and_swap("b", Some(b"2"), b"3".to_vec())
+            .unwrap()
+    );
+    assert!(
+        !store
+            .compare_and_swap("b", Some(b"2"), b"4".to_vec())
+            .unwrap()
+    );
+    drop(store);
+
+    let store = FileStore::open(&path)?;
+    assert_eq!(store.get("b").unwrap(), Some(b"3".to_vec()));
+    assert_eq!(store.list_keys(None).unwrap().0, ["a", "b"]);
+    Ok(())
+}
@@ -0,0 +1,275 @@
+//! Implementation of [`WasiDir`] and [`WasiFile`] in terms of the host's
+//! filesystem through `cap-std`.
+
+use crate::SystemTimeSpec;
+use crate::fs::{
+    Advice, DirEntry, FileType, Metadata, OpenOptions, Opened, WasiDir, WasiFile,
+    cross_device_error,
+};
+use cap_fs_ext::{DirExt, MetadataExt};
+use std::any::Any;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A directory on the host's filesystem.
+pub(crate) struct HostDir(cap_std::fs::Dir);
+
+impl HostDir {
+    pub(crate) fn new(dir: cap_std::fs::Dir) -> Self {
+        Self(dir)
+    }
+
+    /// Returns the `cap_std` directory of `dir` if it's also a [`HostDir`],
+    /// operations between directories of different implementations are
+    /// rejected as crossing devices.
+    fn downcast(dir: &dyn WasiDir) -> io::Result<&cap_std::fs::Dir> {
+        let dir: &dyn Any = dir;
+        match dir.downcast_ref::<HostDir>() {
+            Some(dir) => Ok(&dir.0),
+            None => Err(cross_device_error()),
+        }
+    }
+}
+
+impl WasiDir for HostDir {
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened> {
+        use cap_fs_ext::{FollowSymlinks, OpenOptionsFollowExt, OpenOptionsMaybeDirExt};
+        use system_interface::fs::{FdFlags, GetSetFdFlags};
+
+        let mut opts = cap_std::fs::OpenOptions::new();
+        opts.maybe_dir(true);
+        opts.read(options.read)
+            .write(options.write)
+            .create(options.create)
+            .create_new(options.create_new)
+            .truncate(options.truncate);
+        if options.follow {
+            opts.follow(FollowSymlinks::Yes);
+        } else {
+            opts.follow(FollowSymlinks::No);
+        }
+
+        let mut opened = self.0.open_with(path, &opts)?;
+        if opened.metadata()?.is_dir() {
+            Ok(Opened::Dir(Arc::new(HostDir(
+                cap_std::fs::Dir::from_std_file(opened.into_std()),
+            ))))
+        } else if options.directory {
+            Err(io::ErrorKind::NotADirectory.into())
+        } else {
+            // FIXME cap-std needs a nonblocking open option so that files reads and writes
+            // are nonblocking. Instead we set it after opening here:
+            let set_fd_flags = opened.new_set_fd_flags(FdFlags::NONBLOCK)?;
+            opened.set_fd_flags(set_fd_flags)?;
+            Ok(Opened::File(Arc::new(HostFile::new(opened))))
+        }
+    }
+
+    fn create_dir_at(&self, path: &str) -> io::Result<()> {
+        self.0.create_dir(path)
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(metadata_from(self.0.dir_metadata()?))
+    }
+
+    fn metadata_at(&self, path: &str, follow: bool) -> io::Result<Metadata> {
+        let meta = if follow {
+            self.0.metadata(path)?
+        } else {
+            self.0.symlink_metadata(path)?
+        };
+        Ok(metadata_from(meta))
+    }
+
+    fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        fs_set_times::SetTimes::set_times(
+            &self.0,
+            atime.map(SystemTimeSpec::into_std),
+            mtime.map(SystemTimeSpec::into_std),
+        )
+    }
+
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow: bool,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        if follow {
+            DirExt::set_times(&self.0, path, atime, mtime)
+        } else {
+            self.0.set_symlink_times(path, atime, mtime)
+        }
+    }
+
+    fn read_dir(&self) -> io::Result<Vec<io::Result<DirEntry>>> {
+        // Both `entries` and `metadata` perform syscalls, which is why they are
+        // done here rather than delay calculating the metadata for entries
+        // when they're demanded later.
+        let entries = self.0.entries()?.map(|entry| {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            Ok(DirEntry {
+                name: entry.file_name(),
+                file_type: filetype_from(meta.file_type()),
+            })
+        });
+
+        // On windows, filter out files like `C:\DumpStack.log.tmp` which we
+        // can't get full metadata for.
+        #[cfg(windows)]
+        let entries = entries.filter(|entry| {
+            use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION};
+            if let Err(err) = entry {
+                if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32)
+                    || err.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32)
+                {
+                    return false;
+                }
+            }
+            true
+        });
+
+        Ok(entries.collect())
+    }
+
+    fn read_link_at(&self, path: &str) -> io::Result<PathBuf> {
+        self.0.read_link(path)
+    }
+
+    fn remove_dir_at(&self, path: &str) -> io::Result<()> {
+        self.0.remove_dir(path)
+    }
+
+    fn unlink_file_at(&self, path: &str) -> io::Result<()> {
+        self.0.remove_file_or_symlink(path)
+    }
+
+    fn symlink_at(&self, target: &str, path: &str) -> io::Result<()> {
+        self.0.symlink(target, path)
+    }
+
+    fn rename_at(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        self.0.rename(old_path, Self::downcast(new_dir)?, new_path)
+    }
+
+    fn hard_link_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn WasiDir,
+        new_path: &str,
+    ) -> io::Result<()> {
+        self.0
+            .hard_link(old_path, Self::downcast(new_dir)?, new_path)
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.0.open(std::path::Component::CurDir)?.sync_all()
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.0.open(std::path::Component::CurDir)?.sync_data()
+    }
+}
+
+/// A file on the host's filesystem.
+pub(crate) struct HostFile(cap_std::fs::File);
+
+impl HostFile {
+    pub(crate) fn new(file: cap_std::fs::File) -> Self {
+        Self(file)
+    }
+}
+
+impl WasiFile for HostFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        system_interface::fs::FileIoExt::read_at(&self.0, buf, offset)
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        system_interface::fs::FileIoExt::write_at(&self.0, buf, offset)
+    }
+
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        system_interface::fs::FileIoExt::append(&self.0, buf)
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        self.0.set_len(size)
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(metadata_from(self.0.metadata()?))
+    }
+
+    fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        fs_set_times::SetTimes::set_times(
+            &self.0,
+            atime.map(SystemTimeSpec::into_std),
+            mtime.map(SystemTimeSpec::into_std),
+        )
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.0.sync_all()
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.0.sync_data()
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        use system_interface::fs::{Advice as A, FileIoExt};
+
+        let advice = match advice {
+            Advice::Normal => A::Normal,
+            Advice::Sequential => A::Sequential,
+            Advice::Random => A::Random,
+            Advice::WillNeed => A::WillNeed,
+            Advice::DontNeed => A::DontNeed,
+            Advice::NoReuse => A::NoReuse,
+        };
+        self.0.advise(offset, len, advice)
+    }
+}
+
+fn filetype_from(ft: cap_std::fs::FileType) -> FileType {
+    use cap_fs_ext::FileTypeExt;
+    if ft.is_dir() {
+        FileType::Directory
+    } else if ft.is_symlink() {
+        FileType::SymbolicLink
+    } else if ft.is_block_device() {
+        FileType::BlockDevice
+    } else if ft.is_char_device() {
+        FileType::CharacterDevice
+    } else if ft.is_file() {
+        FileType::RegularFile
+    } else {
+        FileType::Unknown
+    }
+}
+
+fn metadata_from(meta: cap_std::fs::Metadata) -> Metadata {
+    Metadata {
+        file_type: filetype_from(meta.file_type()),
+        len: meta.len(),
+        nlink: meta.nlink(),
+        dev: meta.dev(),
+        ino: meta.ino(),
+        accessed: meta.accessed().map(|t| t.into_std()).ok(),
+        modified: meta.modified().map(|t| t.into_std()).ok(),
+        created: meta.created().map(|t| t.into_std()).ok(),
+    }
+}
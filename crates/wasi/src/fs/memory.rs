@@ -0,0 +1,1313 @@
+//! An in-memory filesystem, optionally layered on top of a read-only directory
+//! of the host.
+
+use crate::SystemTimeSpec;
+use crate::fs::{
+    DirEntry, FileType, Metadata, OpenOptions, Opened, WasiDir, WasiFile, bad_descriptor_error,
+    cross_device_error, escape_error, loop_error,
+};
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// Maximum number of symbolic links followed while resolving a single path.
+const MAX_SYMLINK_EXPANSIONS: usize = 40;
+
+/// A filesystem which lives entirely in memory.
+///
+/// A `MemoryFs` can be made available to WebAssembly through
+/// [`WasiCtxBuilder::preopened_memory_dir`], for example to give each guest
+/// its own scratch space without touching the host's disk. Clones of a
+/// `MemoryFs` share the same contents, so the embedder can keep a handle to
+/// inspect or seed the files seen by the guest.
+///
+/// With [`MemoryFs::overlay`] the filesystem is instead layered on top of a
+/// directory of the host, which is never modified. Its contents are loaded
+/// lazily when accessed and files are copied into memory the first time they
+/// are written to.
+///
+/// The amount of memory used can be limited with
+/// [`MemoryFs::with_size_limit`] and [`MemoryFs::with_inode_limit`], once a
+/// limit is reached operations fail with `insufficient-space`.
+///
+/// # Examples
+///
+/// ```
+/// use wasmtime_wasi::p2::WasiCtxBuilder;
+/// use wasmtime_wasi::{DirPerms, FilePerms, MemoryFs};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let fs = MemoryFs::new().with_size_limit(1 << 20);
+/// fs.create_dir_all("etc")?;
+/// fs.write("etc/motd", "hello")?;
+///
+/// let mut wasi = WasiCtxBuilder::new();
+/// wasi.preopened_memory_dir(fs.clone(), "/", DirPerms::all(), FilePerms::all());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`WasiCtxBuilder::preopened_memory_dir`]: crate::p2::WasiCtxBuilder::preopened_memory_dir
+#[derive(Clone)]
+pub struct MemoryFs {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// Device identifier reported for all objects of this filesystem.
+    dev: u64,
+    state: Mutex<State>,
+}
+
+struct State {
+    nodes: HashMap<u64, Node>,
+    next_ino: u64,
+    /// Number of bytes used by the contents of files and symbolic links
+    /// stored in memory.
+    size: u64,
+    size_limit: Option<u64>,
+    inode_limit: Option<u64>,
+}
+
+struct Node {
+    kind: Kind,
+    /// Number of directory entries referring to this node, the node is
+    /// deallocated once this and `handles` reach zero.
+    nlink: u64,
+    /// Number of open [`MemoryDir`] and [`MemoryFile`] handles to this node.
+    handles: usize,
+    accessed: SystemTime,
+    modified: SystemTime,
+    created: SystemTime,
+}
+
+enum Kind {
+    File(Content),
+    Dir(Entries),
+    Symlink(String),
+}
+
+enum Content {
+    Memory(Vec<u8>),
+    /// A file of the lower directory which hasn't been written to yet.
+    Lower(LowerPath, u64),
+}
+
+enum Entries {
+    Loaded(BTreeMap<String, u64>),
+    /// A directory of the lower directory whose entries haven't been loaded
+    /// yet.
+    Lower(LowerPath),
+}
+
+/// An object in the read-only lower directory of an overlay, identified by
+/// its parent directory and name.
+#[derive(Clone)]
+struct LowerPath {
+    dir: Arc<cap_std::fs::Dir>,
+    name: Option<String>,
+}
+
+impl LowerPath {
+    fn open_dir(&self) -> io::Result<cap_std::fs::Dir> {
+        match &self.name {
+            Some(name) => self.dir.open_dir(name),
+            None => self.dir.try_clone(),
+        }
+    }
+
+    fn open_file(&self) -> io::Result<cap_std::fs::File> {
+        match &self.name {
+            Some(name) => self.dir.open(name),
+            None => Err(io::ErrorKind::IsADirectory.into()),
+        }
+    }
+}
+
+/// A single component of a path.
+#[derive(Copy, Clone)]
+enum Component<'a> {
+    CurDir,
+    ParentDir,
+    Normal(&'a str),
+}
+
+impl<'a> Component<'a> {
+    fn new(s: &'a str) -> Self {
+        match s {
+            "." => Component::CurDir,
+            ".." => Component::ParentDir,
+            s => Component::Normal(s),
+        }
+    }
+}
+
+impl MemoryFs {
+    /// Creates a new, empty, filesystem.
+    pub fn new() -> Self {
+        Self::with_root(Entries::Loaded(BTreeMap::new()))
+    }
+
+    /// Creates a new filesystem layered on top of the host directory `lower`.
+    ///
+    /// The filesystem initially has the same contents as `lower`, but all
+    /// modifications are only made in memory and `lower` is never written to.
+    pub fn overlay(lower: cap_std::fs::Dir) -> Self {
+        Self::with_root(Entries::Lower(LowerPath {
+            dir: Arc::new(lower),
+            name: None,
+        }))
+    }
+
+    fn with_root(entries: Entries) -> Self {
+        // Start device identifiers at the top of the range to make it unlikely
+        // that they collide with the host's.
+        static NEXT_DEV: AtomicU64 = AtomicU64::new(u64::MAX);
+
+        let now = SystemTime::now();
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            ROOT,
+            Node {
+                kind: Kind::Dir(entries),
+                nlink: 1,
+                handles: 0,
+                accessed: now,
+                modified: now,
+                created: now,
+            },
+        );
+        Self {
+            inner: Arc::new(Inner {
+                dev: NEXT_DEV.fetch_sub(1, Ordering::Relaxed),
+                state: Mutex::new(State {
+                    nodes,
+                    next_ino: ROOT + 1,
+                    size: 0,
+                    size_limit: None,
+                    inode_limit: None,
+                }),
+            }),
+        }
+    }
+
+    /// Limits the number of bytes which the contents of files and symbolic
+    /// links stored in memory may use.
+    ///
+    /// Files of the lower directory of an overlay only count towards this limit
+    /// once they have been written to.
+    pub fn with_size_limit(self, bytes: u64) -> Self {
+        self.inner.lock().size_limit = Some(bytes);
+        self
+    }
+
+    /// Limits the number of files, directories and symbolic links of this
+    /// filesystem.
+    ///
+    /// This includes entries of the lower directory of an overlay once their
+    /// parent directory has been accessed.
+    pub fn with_inode_limit(self, inodes: u64) -> Self {
+        self.inner.lock().inode_limit = Some(inodes);
+        self
+    }
+
+    /// Returns the number of bytes currently used by the contents of files and
+    /// symbolic links stored in memory.
+    pub fn size(&self) -> u64 {
+        self.inner.lock().size
+    }
+
+    /// Recursively creates the directory `path` and all of its missing
+    /// parents.
+    pub fn create_dir_all(&self, path: &str) -> io::Result<()> {
+        let root = self.root();
+        let mut prefix = String::new();
+        for part in path.split('/').filter(|s| !s.is_empty()) {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(part);
+            match root.create_dir_at(&prefix) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `contents` to the file `path`, creating it if it doesn't exist
+    /// and replacing its contents if it does.
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        let options = OpenOptions {
+            write: true,
+            create: true,
+            truncate: true,
+            follow: true,
+            ..OpenOptions::default()
+        };
+        match self.root().open_at(path, &options)? {
+            Opened::File(file) => {
+                file.write_at(contents.as_ref(), 0)?;
+                Ok(())
+            }
+            Opened::Dir(_) => Err(io::ErrorKind::IsADirectory.into()),
+        }
+    }
+
+    /// Returns the contents of the file `path`.
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let options = OpenOptions {
+            read: true,
+            follow: true,
+            ..OpenOptions::default()
+        };
+        match self.root().open_at(path, &options)? {
+            Opened::File(file) => {
+                let mut contents = vec![0; usize::try_from(file.metadata()?.len).unwrap()];
+                let mut pos = 0;
+                while pos < contents.len() {
+                    match file.read_at(&mut contents[pos..], pos as u64)? {
+                        0 => break,
+                        n => pos += n,
+                    }
+                }
+                contents.truncate(pos);
+                Ok(contents)
+            }
+            Opened::Dir(_) => Err(io::ErrorKind::IsADirectory.into()),
+        }
+    }
+
+    /// Returns a new handle to the root directory of this filesystem.
+    pub(crate) fn root(&self) -> Arc<dyn WasiDir> {
+        let mut state = self.inner.lock();
+        Arc::new(MemoryDir(Handle::new(&self.inner, &mut state, ROOT)))
+    }
+}
+
+impl Default for MemoryFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const ROOT: u64 = 1;
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl State {
+    fn node(&self, ino: u64) -> &Node {
+        &self.nodes[&ino]
+    }
+
+    fn node_mut(&mut self, ino: u64) -> &mut Node {
+        self.nodes.get_mut(&ino).unwrap()
+    }
+
+    fn reserve_inodes(&self, n: usize) -> io::Result<()> {
+        match self.inode_limit {
+            Some(limit) if (self.nodes.len() + n) as u64 > limit => {
+                Err(io::ErrorKind::StorageFull.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn reserve_bytes(&mut self, n: u64) -> io::Result<()> {
+        let size = self
+            .size
+            .checked_add(n)
+            .ok_or(io::ErrorKind::FileTooLarge)?;
+        if let Some(limit) = self.size_limit {
+            if size > limit {
+                return Err(io::ErrorKind::StorageFull.into());
+            }
+        }
+        self.size = size;
+        Ok(())
+    }
+
+    fn alloc(&mut self, kind: Kind) -> io::Result<u64> {
+        self.reserve_inodes(1)?;
+        Ok(self.insert(kind, SystemTime::now()))
+    }
+
+    fn insert(&mut self, kind: Kind, now: SystemTime) -> u64 {
+        let ino = self.next_ino;
+        self.next_ino += 1;
+        self.nodes.insert(
+            ino,
+            Node {
+                kind,
+                nlink: 1,
+                handles: 0,
+                accessed: now,
+                modified: now,
+                created: now,
+            },
+        );
+        ino
+    }
+
+    /// Deallocates `ino` if it's no longer referenced.
+    fn collect(&mut self, ino: u64) {
+        let node = self.node(ino);
+        if node.nlink > 0 || node.handles > 0 {
+            return;
+        }
+        let node = self.nodes.remove(&ino).unwrap();
+        match node.kind {
+            Kind::File(Content::Memory(data)) => self.size -= data.len() as u64,
+            Kind::Symlink(target) => self.size -= target.len() as u64,
+            Kind::File(Content::Lower(..)) | Kind::Dir(_) => {}
+        }
+    }
+
+    fn unlink(&mut self, ino: u64) {
+        let node = self.node_mut(ino);
+        node.nlink -= 1;
+        node.created = SystemTime::now();
+        self.collect(ino);
+    }
+
+    fn touch(&mut self, ino: u64) {
+        let now = SystemTime::now();
+        let node = self.node_mut(ino);
+        node.modified = now;
+        node.created = now;
+    }
+
+    /// Returns the entries of the directory `ino`, loading them from the lower
+    /// directory first if necessary.
+    fn entries(&mut self, ino: u64) -> io::Result<&mut BTreeMap<String, u64>> {
+        let lower = match &self.node(ino).kind {
+            Kind::Dir(Entries::Loaded(_)) => None,
+            Kind::Dir(Entries::Lower(lower)) => Some(lower.clone()),
+            Kind::File(_) | Kind::Symlink(_) => return Err(io::ErrorKind::NotADirectory.into()),
+        };
+        if let Some(lower) = lower {
+            let entries = self.load(&lower)?;
+            self.node_mut(ino).kind = Kind::Dir(Entries::Loaded(entries));
+        }
+        match &mut self.node_mut(ino).kind {
+            Kind::Dir(Entries::Loaded(entries)) => Ok(entries),
+            _ => unreachable!(),
+        }
+    }
+
+    fn load(&mut self, lower: &LowerPath) -> io::Result<BTreeMap<String, u64>> {
+        let dir = Arc::new(lower.open_dir()?);
+        let mut loaded = Vec::new();
+        for entry in dir.entries()? {
+            let entry = entry?;
+            // Names which aren't valid UTF-8 can't be represented in WASI and
+            // are hidden.
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let meta = entry.metadata()?;
+            let lower = LowerPath {
+                dir: dir.clone(),
+                name: Some(name.clone()),
+            };
+            let kind = if meta.is_dir() {
+                Kind::Dir(Entries::Lower(lower))
+            } else if meta.is_symlink() {
+                let Some(target) = dir.read_link(&name)?.to_str().map(str::to_owned) else {
+                    continue;
+                };
+                Kind::Symlink(target)
+            } else if meta.is_file() {
+                Kind::File(Content::Lower(lower, meta.len()))
+            } else {
+                continue;
+            };
+            let modified = meta.modified().ok().map(|t| t.into_std());
+            let times = (
+                meta.accessed().ok().map(|t| t.into_std()).or(modified),
+                modified,
+                meta.created().ok().map(|t| t.into_std()).or(modified),
+            );
+            loaded.push((name, kind, times));
+        }
+
+        self.reserve_inodes(loaded.len())?;
+        let now = SystemTime::now();
+        let mut entries = BTreeMap::new();
+        for (name, kind, (accessed, modified, created)) in loaded {
+            if let Kind::Symlink(target) = &kind {
+                self.size += target.len() as u64;
+            }
+            let ino = self.insert(kind, now);
+            let node = self.node_mut(ino);
+            node.accessed = accessed.unwrap_or(now);
+            node.modified = modified.unwrap_or(now);
+            node.created = created.unwrap_or(now);
+            entries.insert(name, ino);
+        }
+        Ok(entries)
+    }
+
+    fn lookup(&mut self, dir: u64, name: &str) -> io::Result<Option<u64>> {
+        Ok(self.entries(dir)?.get(name).copied())
+    }
+
+    /// Splits `path` into its components, walking all but the last one
+    /// starting from the top of `stack`.
+    ///
+    /// The `stack` contains all directories walked through so far, its
+    /// bottom is the directory the path is relative to and `..` can't be used
+    /// to leave it. Returns the last component and whether the path ended with
+    /// a `/`.
+    fn walk<'a>(
+        &mut self,
+        stack: &mut Vec<u64>,
+        path: &'a str,
+        expansions: &mut usize,
+    ) -> io::Result<(Component<'a>, bool)> {
+        if path.is_empty() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        if path.starts_with('/') {
+            return Err(escape_error());
+        }
+        if path.contains('\0') {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        let mut parts = path.split('/').filter(|s| !s.is_empty());
+        let mut last = parts.next().unwrap();
+        for part in parts {
+            self.enter(stack, Component::new(last), expansions)?;
+            last = part;
+        }
+        Ok((Component::new(last), path.ends_with('/')))
+    }
+
+    /// Pushes the directory referred to by `component` onto `stack`.
+    fn enter(
+        &mut self,
+        stack: &mut Vec<u64>,
+        component: Component<'_>,
+        expansions: &mut usize,
+    ) -> io::Result<()> {
+        match component {
+            Component::CurDir => Ok(()),
+            Component::ParentDir => {
+                if stack.len() == 1 {
+                    return Err(escape_error());
+                }
+                stack.pop();
+                Ok(())
+            }
+            Component::Normal(name) => {
+                let dir = *stack.last().unwrap();
+                let ino = self.lookup(dir, name)?.ok_or(io::ErrorKind::NotFound)?;
+                match &self.node(ino).kind {
+                    Kind::Dir(_) => {
+                        stack.push(ino);
+                        Ok(())
+                    }
+                    Kind::Symlink(target) => {
+                        let target = target.clone();
+                        self.follow(stack, &target, expansions, true)?;
+                        Ok(())
+                    }
+                    Kind::File(_) => Err(io::ErrorKind::NotADirectory.into()),
+                }
+            }
+        }
+    }
+
+    /// Resolves the symbolic link `target` relative to the top of `stack`.
+    ///
+    /// If `enter` is set then the target must be a directory which is pushed
+    /// onto `stack`.
+    fn follow(
+        &mut self,
+        stack: &mut Vec<u64>,
+        target: &str,
+        expansions: &mut usize,
+        enter: bool,
+    ) -> io::Result<u64> {
+        *expansions += 1;
+        if *expansions > MAX_SYMLINK_EXPANSIONS {
+            return Err(loop_error());
+        }
+        let (last, trailing) = self.walk(stack, target, expansions)?;
+        if enter {
+            self.enter(stack, last, expansions)?;
+            Ok(*stack.last().unwrap())
+        } else {
+            self.resolve(stack, last, true, trailing, expansions)
+        }
+    }
+
+    /// Returns the node referred to by the last component of a path.
+    fn resolve(
+        &mut self,
+        stack: &mut Vec<u64>,
+        last: Component<'_>,
+        follow: bool,
+        trailing: bool,
+        expansions: &mut usize,
+    ) -> io::Result<u64> {
+        let ino = match last {
+            Component::CurDir => *stack.last().unwrap(),
+            Component::ParentDir => {
+                if stack.len() == 1 {
+                    return Err(escape_error());
+                }
+                stack[stack.len() - 2]
+            }
+            Component::Normal(name) => {
+                let dir = *stack.last().unwrap();
+                let ino = self.lookup(dir, name)?.ok_or(io::ErrorKind::NotFound)?;
+                match &self.node(ino).kind {
+                    Kind::Symlink(target) if follow || trailing => {
+                        let target = target.clone();
+                        self.follow(stack, &target, expansions, false)?
+                    }
+                    _ => ino,
+                }
+            }
+        };
+        if trailing && !matches!(self.node(ino).kind, Kind::Dir(_)) {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        Ok(ino)
+    }
+
+    /// Resolves `path` relative to `base`.
+    fn resolve_path(&mut self, base: u64, path: &str, follow: bool) -> io::Result<u64> {
+        let mut stack = vec![base];
+        let mut expansions = 0;
+        let (last, trailing) = self.walk(&mut stack, path, &mut expansions)?;
+        self.resolve(&mut stack, last, follow, trailing, &mut expansions)
+    }
+
+    /// Resolves all but the last component of `path` relative to `base`,
+    /// returning the parent directory and the name of the last component.
+    ///
+    /// Fails with `err` if the last component is `.` or `..`.
+    fn resolve_parent<'a>(
+        &mut self,
+        base: u64,
+        path: &'a str,
+        err: io::ErrorKind,
+    ) -> io::Result<(u64, &'a str, bool)> {
+        let mut stack = vec![base];
+        let (last, trailing) = self.walk(&mut stack, path, &mut 0)?;
+        match last {
+            Component::Normal(name) => Ok((*stack.last().unwrap(), name, trailing)),
+            Component::CurDir | Component::ParentDir => Err(err.into()),
+        }
+    }
+
+    fn is_dir(&self, ino: u64) -> bool {
+        matches!(self.node(ino).kind, Kind::Dir(_))
+    }
+
+    /// Returns whether `ino` is `dir` or a directory within it.
+    fn is_within(&self, ino: u64, dir: u64) -> bool {
+        if ino == dir {
+            return true;
+        }
+        match &self.node(dir).kind {
+            Kind::Dir(Entries::Loaded(entries)) => entries
+                .values()
+                .any(|&child| self.is_dir(child) && self.is_within(ino, child)),
+            _ => false,
+        }
+    }
+
+    fn metadata(&self, dev: u64, ino: u64) -> Metadata {
+        let node = self.node(ino);
+        let (file_type, len) = match &node.kind {
+            Kind::File(Content::Memory(data)) => (FileType::RegularFile, data.len() as u64),
+            Kind::File(Content::Lower(_, len)) => (FileType::RegularFile, *len),
+            Kind::Dir(_) => (FileType::Directory, 0),
+            Kind::Symlink(target) => (FileType::SymbolicLink, target.len() as u64),
+        };
+        Metadata {
+            file_type,
+            len,
+            nlink: node.nlink,
+            dev,
+            ino,
+            accessed: Some(node.accessed),
+            modified: Some(node.modified),
+            created: Some(node.created),
+        }
+    }
+
+    fn set_times(
+        &mut self,
+        ino: u64,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) {
+        let now = SystemTime::now();
+        let time = |spec| match spec {
+            SystemTimeSpec::SymbolicNow => now,
+            SystemTimeSpec::Absolute(time) => time.into_std(),
+        };
+        let node = self.node_mut(ino);
+        if let Some(atime) = atime {
+            node.accessed = time(atime);
+        }
+        if let Some(mtime) = mtime {
+            node.modified = time(mtime);
+        }
+        node.created = now;
+    }
+
+    /// Returns the in-memory contents of the file `ino`, copying them from the
+    /// lower directory first if necessary.
+    fn contents(&mut self, ino: u64) -> io::Result<&mut Vec<u8>> {
+        if let Kind::File(Content::Lower(lower, _)) = &self.node(ino).kind {
+            let lower = lower.clone();
+            let mut data = Vec::new();
+            io::Read::read_to_end(&mut lower.open_file()?, &mut data)?;
+            self.reserve_bytes(data.len() as u64)?;
+            self.node_mut(ino).kind = Kind::File(Content::Memory(data));
+        }
+        match &mut self.node_mut(ino).kind {
+            Kind::File(Content::Memory(data)) => Ok(data),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Resizes the file `ino` to `len` bytes, accounting for the change in
+    /// size.
+    fn resize(&mut self, ino: u64, len: u64) -> io::Result<()> {
+        let cur = self.contents(ino)?.len() as u64;
+        if len > cur {
+            let len = usize::try_from(len).map_err(|_| io::ErrorKind::FileTooLarge)?;
+            self.reserve_bytes(len as u64 - cur)?;
+            self.contents(ino)?.resize(len, 0);
+        } else {
+            self.size -= cur - len;
+            self.contents(ino)?.truncate(len as usize);
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, ino: u64, buf: &[u8], offset: u64) -> io::Result<usize> {
+        // Empty writes never extend the file, even past its end.
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let end = offset
+            .checked_add(buf.len() as u64)
+            .ok_or(io::ErrorKind::FileTooLarge)?;
+        if end > self.contents(ino)?.len() as u64 {
+            self.resize(ino, end)?;
+        }
+        let offset = offset as usize;
+        self.contents(ino)?[offset..offset + buf.len()].copy_from_slice(buf);
+        self.touch(ino);
+        Ok(buf.len())
+    }
+}
+
+/// An open handle to a node of a [`MemoryFs`].
+struct Handle {
+    fs: Arc<Inner>,
+    ino: u64,
+}
+
+impl Handle {
+    fn new(fs: &Arc<Inner>, state: &mut State, ino: u64) -> Self {
+        state.node_mut(ino).handles += 1;
+        Self {
+            fs: fs.clone(),
+            ino,
+        }
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let mut state = self.fs.lock();
+        state.node_mut(self.ino).handles -= 1;
+        state.collect(self.ino);
+    }
+}
+
+/// A directory of a [`MemoryFs`].
+struct MemoryDir(Handle);
+
+impl MemoryDir {
+    /// Returns the handle of `dir` if it's a directory of the same
+    /// [`MemoryFs`] as `self`.
+    fn same_fs<'a>(&self, dir: &'a dyn WasiDir) -> io::Result<&'a Handle> {
+        let dir: &dyn Any = dir;
+        match dir.downcast_ref::<MemoryDir>() {
+            Some(dir) if Arc::ptr_eq(&dir.0.fs, &self.0.fs) => Ok(&dir.0),
+            _ => Err(cross_device_error()),
+        }
+    }
+}
+
+impl WasiDir for MemoryDir {
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened> {
+        let fs = &self.0.fs;
+        let mut state = fs.lock();
+        let mut stack = vec![self.0.ino];
+        let mut expansions = 0;
+        let (last, trailing) = state.walk(&mut stack, path, &mut expansions)?;
+
+        let existing = match last {
+            Component::Normal(name) => {
+                let dir = *stack.last().unwrap();
+                state.lookup(dir, name)?
+            }
+            Component::CurDir | Component::ParentDir => None,
+        };
+        let ino = match (last, existing) {
+            (Component::Normal(name), None) => {
+                if !options.create && !options.create_new {
+                    return Err(io::ErrorKind::NotFound.into());
+                }
+                if trailing || options.directory {
+                    return Err(io::ErrorKind::IsADirectory.into());
+                }
+                let dir = *stack.last().unwrap();
+                if state.node(dir).nlink == 0 {
+                    return Err(io::ErrorKind::NotFound.into());
+                }
+                let ino = state.alloc(Kind::File(Content::Memory(Vec::new())))?;
+                state.entries(dir)?.insert(name.to_owned(), ino);
+                state.touch(dir);
+                return Ok(Opened::File(Arc::new(MemoryFile::new(
+                    Handle::new(fs, &mut state, ino),
+                    options,
+                ))));
+            }
+            _ if options.create_new => return Err(io::ErrorKind::AlreadyExists.into()),
+            (Component::Normal(_), Some(ino))
+                if !options.follow
+                    && !trailing
+                    && matches!(state.node(ino).kind, Kind::Symlink(_)) =>
+            {
+                return Err(loop_error());
+            }
+            _ => state.resolve(&mut stack, last, options.follow, trailing, &mut expansions)?,
+        };
+
+        match &state.node(ino).kind {
+            Kind::Dir(_) => {
+                if options.write || options.truncate {
+                    return Err(io::ErrorKind::IsADirectory.into());
+                }
+                Ok(Opened::Dir(Arc::new(MemoryDir(Handle::new(
+                    fs, &mut state, ino,
+                )))))
+            }
+            Kind::File(_) => {
+                if options.directory {
+                    return Err(io::ErrorKind::NotADirectory.into());
+                }
+                if options.truncate {
+                    state.resize(ino, 0)?;
+                    state.touch(ino);
+                }
+                Ok(Opened::File(Arc::new(MemoryFile::new(
+                    Handle::new(fs, &mut state, ino),
+                    options,
+                ))))
+            }
+            Kind::Symlink(_) => Err(loop_error()),
+        }
+    }
+
+    fn create_dir_at(&self, path: &str) -> io::Result<()> {
+        let mut state = self.0.fs.lock();
+        let (dir, name, _) =
+            state.resolve_parent(self.0.ino, path, io::ErrorKind::AlreadyExists)?;
+        if state.lookup(dir, name)?.is_some() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        if state.node(dir).nlink == 0 {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let ino = state.alloc(Kind::Dir(Entries::Loaded(BTreeMap::new())))?;
+        state.entries(dir)?.insert(name.to_owned(), ino);
+        state.touch(dir);
+        Ok(())
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(self.0.fs.lock().metadata(self.0.fs.dev, self.0.ino))
+    }
+
+    fn metadata_at(&self, path: &str, follow: bool) -> io::Result<Metadata> {
+        let mut state = self.0.fs.lock();
+        let ino = state.resolve_path(self.0.ino, path, follow)?;
+        Ok(state.metadata(self.0.fs.dev, ino))
+    }
+
+    fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.0.fs.lock().set_times(self.0.ino, atime, mtime);
+        Ok(())
+    }
+
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow: bool,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        let mut state = self.0.fs.lock();
+        let ino = state.resolve_path(self.0.ino, path, follow)?;
+        state.set_times(ino, atime, mtime);
+        Ok(())
+    }
+
+    fn read_dir(&self) -> io::Result<Vec<io::Result<DirEntry>>> {
+        let mut state = self.0.fs.lock();
+        let entries = state
+            .entries(self.0.ino)?
+            .iter()
+            .map(|(name, ino)| (name.clone(), *ino))
+            .collect::<Vec<_>>();
+        Ok(entries
+            .into_iter()
+            .map(|(name, ino)| {
+                Ok(DirEntry {
+                    name: name.into(),
+                    file_type: state.metadata(self.0.fs.dev, ino).file_type,
+                })
+            })
+            .collect())
+    }
+
+    fn read_link_at(&self, path: &str) -> io::Result<PathBuf> {
+        let mut state = self.0.fs.lock();
+        let ino = state.resolve_path(self.0.ino, path, false)?;
+        match &state.node(ino).kind {
+            Kind::Symlink(target) => Ok(target.into()),
+            _ => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    fn remove_dir_at(&self, path: &str) -> io::Result<()> {
+        let mut state = self.0.fs.lock();
+        let (dir, name, _) = state.resolve_parent(self.0.ino, path, io::ErrorKind::InvalidInput)?;
+        let ino = state.lookup(dir, name)?.ok_or(io::ErrorKind::NotFound)?;
+        if !state.is_dir(ino) {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        if !state.entries(ino)?.is_empty() {
+            return Err(io::ErrorKind::DirectoryNotEmpty.into());
+        }
+        state.entries(dir)?.remove(name);
+        state.touch(dir);
+        state.unlink(ino);
+        Ok(())
+    }
+
+    fn unlink_file_at(&self, path: &str) -> io::Result<()> {
+        let mut state = self.0.fs.lock();
+        let (dir, name, trailing) =
+            state.resolve_parent(self.0.ino, path, io::ErrorKind::IsADirectory)?;
+        let ino = state.lookup(dir, name)?.ok_or(io::ErrorKind::NotFound)?;
+        if state.is_dir(ino) {
+            return Err(io::ErrorKind::IsADirectory.into());
+        }
+        if trailing {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        state.entries(dir)?.remove(name);
+        state.touch(dir);
+        state.unlink(ino);
+        Ok(())
+    }
+
+    fn symlink_at(&self, target: &str, path: &str) -> io::Result<()> {
+        // Absolute symlinks could never be followed, so like `cap-std` refuse
+        // to create them in the first place.
+        if target.starts_with('/') {
+            return Err(escape_error());
+        }
+        let mut state = self.0.fs.lock();
+        let (dir, name, trailing) =
+            state.resolve_parent(self.0.ino, path, io::ErrorKind::AlreadyExists)?;
+        if let Some(ino) = state.lookup(dir, name)? {
+            if trailing && !matches!(state.node(ino).kind, Kind::Dir(_)) {
+                return Err(io::ErrorKind::NotADirectory.into());
+            }
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        if trailing || state.node(dir).nlink == 0 {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        state.reserve_bytes(target.len() as u64)?;
+        let ino = match state.alloc(Kind::Symlink(target.to_owned())) {
+            Ok(ino) => ino,
+            Err(e) => {
+                state.size -= target.len() as u64;
+                return Err(e);
+            }
+        };
+        state.entries(dir)?.insert(name.to_owned(), ino);
+        state.touch(dir);
+        Ok(())
+    }
+
+    fn rename_at(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let new_base = self.same_fs(new_dir)?.ino;
+        let mut state = self.0.fs.lock();
+        let (old_dir, old_name, old_trailing) =
+            state.resolve_parent(self.0.ino, old_path, io::ErrorKind::InvalidInput)?;
+        let (new_dir, new_name, new_trailing) =
+            state.resolve_parent(new_base, new_path, io::ErrorKind::InvalidInput)?;
+        let ino = state
+            .lookup(old_dir, old_name)?
+            .ok_or(io::ErrorKind::NotFound)?;
+        let is_dir = state.is_dir(ino);
+        if (old_trailing || new_trailing) && !is_dir {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+
+        if let Some(existing) = state.lookup(new_dir, new_name)? {
+            if existing == ino {
+                return Ok(());
+            }
+            match (is_dir, state.is_dir(existing)) {
+                (true, true) => {
+                    if !state.entries(existing)?.is_empty() {
+                        return Err(io::ErrorKind::DirectoryNotEmpty.into());
+                    }
+                }
+                (true, false) => return Err(io::ErrorKind::NotADirectory.into()),
+                (false, true) => return Err(io::ErrorKind::IsADirectory.into()),
+                (false, false) => {}
+            }
+        }
+        if is_dir && state.is_within(new_dir, ino) {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        if state.node(new_dir).nlink == 0 {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+
+        state.entries(old_dir)?.remove(old_name);
+        if let Some(existing) = state.entries(new_dir)?.insert(new_name.to_owned(), ino) {
+            state.unlink(existing);
+        }
+        state.touch(old_dir);
+        state.touch(new_dir);
+        Ok(())
+    }
+
+    fn hard_link_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn WasiDir,
+        new_path: &str,
+    ) -> io::Result<()> {
+        let new_base = self.same_fs(new_dir)?.ino;
+        let mut state = self.0.fs.lock();
+        let (old_dir, old_name, old_trailing) =
+            state.resolve_parent(self.0.ino, old_path, io::ErrorKind::PermissionDenied)?;
+        let (new_dir, new_name, new_trailing) =
+            state.resolve_parent(new_base, new_path, io::ErrorKind::AlreadyExists)?;
+        let ino = state
+            .lookup(old_dir, old_name)?
+            .ok_or(io::ErrorKind::NotFound)?;
+        if state.is_dir(ino) {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+        if old_trailing {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        if state.lookup(new_dir, new_name)?.is_some() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        if new_trailing || state.node(new_dir).nlink == 0 {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        state.entries(new_dir)?.insert(new_name.to_owned(), ino);
+        state.node_mut(ino).nlink += 1;
+        state.node_mut(ino).created = SystemTime::now();
+        state.touch(new_dir);
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A file of a [`MemoryFs`].
+///
+/// Unlike nodes, which are shared by all handles, whether the file may be read
+/// or written is a property of the handle it was opened as.
+struct MemoryFile {
+    handle: Handle,
+    read: bool,
+    write: bool,
+}
+
+impl MemoryFile {
+    fn new(handle: Handle, options: &OpenOptions) -> Self {
+        Self {
+            handle,
+            // Like hosts, files opened with neither mode are readable.
+            read: options.read || !options.write,
+            write: options.write,
+        }
+    }
+
+    fn check_read(&self) -> io::Result<()> {
+        if self.read {
+            Ok(())
+        } else {
+            Err(bad_descriptor_error())
+        }
+    }
+
+    fn check_write(&self) -> io::Result<()> {
+        if self.write {
+            Ok(())
+        } else {
+            Err(bad_descriptor_error())
+        }
+    }
+}
+
+impl WasiFile for MemoryFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.check_read()?;
+        let state = self.handle.fs.lock();
+        match &state.node(self.handle.ino).kind {
+            Kind::File(Content::Memory(data)) => {
+                let Ok(offset) = usize::try_from(offset) else {
+                    return Ok(0);
+                };
+                let data = data.get(offset..).unwrap_or(&[]);
+                let n = data.len().min(buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                Ok(n)
+            }
+            Kind::File(Content::Lower(lower, _)) => {
+                let lower = lower.clone();
+                // Don't block the rest of the filesystem while reading from
+                // the host.
+                drop(state);
+                system_interface::fs::FileIoExt::read_at(&lower.open_file()?, buf, offset)
+            }
+            Kind::Dir(_) | Kind::Symlink(_) => unreachable!(),
+        }
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.check_write()?;
+        self.handle.fs.lock().write_at(self.handle.ino, buf, offset)
+    }
+
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        self.check_write()?;
+        let mut state = self.handle.fs.lock();
+        let offset = state.contents(self.handle.ino)?.len() as u64;
+        state.write_at(self.handle.ino, buf, offset)
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        // As with `ftruncate`, this is an invalid argument rather than a bad
+        // descriptor.
+        if !self.write {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        let mut state = self.handle.fs.lock();
+        state.resize(self.handle.ino, size)?;
+        state.touch(self.handle.ino);
+        Ok(())
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(self
+            .handle
+            .fs
+            .lock()
+            .metadata(self.handle.fs.dev, self.handle.ino))
+    }
+
+    fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.handle
+            .fs
+            .lock()
+            .set_times(self.handle.ino, atime, mtime);
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create(dir: &dyn WasiDir, path: &str) -> io::Result<Arc<dyn WasiFile>> {
+        let options = OpenOptions {
+            write: true,
+            create: true,
+            ..OpenOptions::default()
+        };
+        match dir.open_at(path, &options)? {
+            Opened::File(file) => Ok(file),
+            Opened::Dir(_) => panic!("opened a directory"),
+        }
+    }
+
+    #[test]
+    fn size_limit() -> io::Result<()> {
+        let fs = MemoryFs::new().with_size_limit(10);
+        let root = fs.root();
+        let file = create(&*root, "a")?;
+        assert_eq!(file.write_at(b"0123456789", 0)?, 10);
+        assert_eq!(fs.size(), 10);
+        let err = file.append(b"x").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        let err = root.symlink_at("a", "b").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+
+        // Space is only released once the last handle to a file is closed.
+        root.unlink_file_at("a")?;
+        assert_eq!(fs.size(), 10);
+        drop(file);
+        assert_eq!(fs.size(), 0);
+        root.symlink_at("a", "b")?;
+        assert_eq!(fs.size(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn inode_limit() -> io::Result<()> {
+        let fs = MemoryFs::new().with_inode_limit(3);
+        let root = fs.root();
+        root.create_dir_at("a")?;
+        create(&*root, "a/b")?;
+        let err = root.create_dir_at("c").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        root.unlink_file_at("a/b")?;
+        root.create_dir_at("c")?;
+        Ok(())
+    }
+
+    #[test]
+    fn paths_stay_within_directory() -> io::Result<()> {
+        let fs = MemoryFs::new();
+        fs.create_dir_all("a/b")?;
+        fs.write("a/file", "contents")?;
+        let root = fs.root();
+        let Opened::Dir(a) = root.open_at("a", &OpenOptions::default())? else {
+            panic!("expected a directory");
+        };
+
+        assert_eq!(a.metadata_at("b/../file", true)?.len, 8);
+        for path in ["/a", "..", "b/../../a", "../a/file"] {
+            let err = a.metadata_at(path, true).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::PermissionDenied, "{path}");
+        }
+
+        a.symlink_at("..", "up")?;
+        a.symlink_at("b/..", "here")?;
+        assert_eq!(
+            a.metadata_at("up", false)?.file_type,
+            FileType::SymbolicLink
+        );
+        assert_eq!(a.metadata_at("here/file", true)?.len, 8);
+        let err = a.metadata_at("up/a", true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(root.metadata_at("a/up/a/file", true)?.len, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn symlink_loop() -> io::Result<()> {
+        let fs = MemoryFs::new();
+        let root = fs.root();
+        root.symlink_at("b", "a")?;
+        root.symlink_at("a", "b")?;
+        let err = root.metadata_at("a", true).unwrap_err();
+        assert_eq!(err.raw_os_error(), loop_error().raw_os_error());
+        Ok(())
+    }
+
+    #[test]
+    fn rename() -> io::Result<()> {
+        let fs = MemoryFs::new();
+        fs.create_dir_all("a/b")?;
+        fs.write("a/b/file", "contents")?;
+        let root = fs.root();
+
+        let err = root.rename_at("a", &*root, "a/b/c").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = root.rename_at("a/b/file", &*root, "a").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::IsADirectory);
+
+        root.rename_at("a/b", &*root, "c")?;
+        assert_eq!(fs.read("c/file")?, b"contents");
+        assert!(root.read_dir()?.len() == 2);
+
+        let other = MemoryFs::new().root();
+        let err = root.rename_at("c", &*other, "c").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::CrossesDevices);
+        Ok(())
+    }
+
+    #[test]
+    fn overlay() -> io::Result<()> {
+        let lower = tempfile::tempdir()?;
+        std::fs::create_dir(lower.path().join("dir"))?;
+        std::fs::write(lower.path().join("dir/file"), "lower")?;
+        let lower_dir =
+            cap_std::fs::Dir::open_ambient_dir(lower.path(), cap_std::ambient_authority())?;
+
+        let fs = MemoryFs::overlay(lower_dir).with_size_limit(100);
+        assert_eq!(fs.read("dir/file")?, b"lower");
+        assert_eq!(fs.size(), 0);
+
+        fs.write("dir/file", "upper")?;
+        fs.write("new", "new")?;
+        assert_eq!(fs.read("dir/file")?, b"upper");
+        assert_eq!(fs.size(), 8);
+        fs.root().unlink_file_at("dir/file")?;
+        fs.root().remove_dir_at("dir")?;
+        assert_eq!(fs.root().read_dir()?.len(), 1);
+
+        assert_eq!(std::fs::read(lower.path().join("dir/file"))?, b"lower");
+        assert!(!lower.path().join("new").exists());
+        Ok(())
+    }
+}
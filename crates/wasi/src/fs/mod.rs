@@ -0,0 +1,342 @@
+//! Filesystem implementations which can be made available to WebAssembly.
+//!
+//! Directories preopened with [`WasiCtxBuilder::preopened_dir`] are backed by
+//! the host's filesystem and those preopened with
+//! [`WasiCtxBuilder::preopened_memory_dir`] by a [`MemoryFs`]. Embedders can
+//! additionally provide their own storage, for example an object store, by
+//! implementing [`WasiDir`] and [`WasiFile`] and using
+//! [`WasiCtxBuilder::preopened_custom_dir`].
+//!
+//! Regardless of the implementation all descriptors behave the same from
+//! the guest's point of view: [`DirPerms`], [`FilePerms`] and the mode a file
+//! was opened with are enforced before any method of these traits is called,
+//! and errors are translated to WASI error codes the same way.
+//!
+//! # Errors
+//!
+//! Methods report failure with an [`io::Error`]. If it carries a raw OS error
+//! that is translated to the corresponding WASI error code, otherwise its
+//! [`io::ErrorKind`] is used, for example:
+//!
+//! | [`io::ErrorKind`] | WASI error code |
+//! |---|---|
+//! | [`NotFound`](io::ErrorKind::NotFound) | `no-entry` |
+//! | [`PermissionDenied`](io::ErrorKind::PermissionDenied) | `not-permitted` |
+//! | [`AlreadyExists`](io::ErrorKind::AlreadyExists) | `exist` |
+//! | [`InvalidInput`](io::ErrorKind::InvalidInput) | `invalid` |
+//! | [`NotADirectory`](io::ErrorKind::NotADirectory) | `not-directory` |
+//! | [`IsADirectory`](io::ErrorKind::IsADirectory) | `is-directory` |
+//! | [`DirectoryNotEmpty`](io::ErrorKind::DirectoryNotEmpty) | `not-empty` |
+//! | [`ReadOnlyFilesystem`](io::ErrorKind::ReadOnlyFilesystem) | `read-only` |
+//! | [`StorageFull`](io::ErrorKind::StorageFull) | `insufficient-space` |
+//! | [`QuotaExceeded`](io::ErrorKind::QuotaExceeded) | `quota` |
+//! | [`FileTooLarge`](io::ErrorKind::FileTooLarge) | `file-too-large` |
+//! | [`CrossesDevices`](io::ErrorKind::CrossesDevices) | `cross-device` |
+//! | [`Unsupported`](io::ErrorKind::Unsupported) | `unsupported` |
+//!
+//! Any other error is reported as `io`.
+//!
+//! [`WasiCtxBuilder::preopened_dir`]: crate::p2::WasiCtxBuilder::preopened_dir
+//! [`WasiCtxBuilder::preopened_memory_dir`]: crate::p2::WasiCtxBuilder::preopened_memory_dir
+//! [`WasiCtxBuilder::preopened_custom_dir`]: crate::p2::WasiCtxBuilder::preopened_custom_dir
+
+use crate::SystemTimeSpec;
+use std::any::Any;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+mod host;
+mod memory;
+
+pub(crate) use self::host::HostDir;
+pub use self::memory::MemoryFs;
+
+bitflags::bitflags! {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct FilePerms: usize {
+        const READ = 0b1;
+        const WRITE = 0b10;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct OpenMode: usize {
+        const READ = 0b1;
+        const WRITE = 0b10;
+    }
+}
+
+bitflags::bitflags! {
+    /// Permission bits for operating on a directory.
+    ///
+    /// Directories can be limited to being readonly. This will restrict what
+    /// can be done with them, for example preventing creation of new files.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct DirPerms: usize {
+        /// This directory can be read, for example its entries can be iterated
+        /// over and files can be opened.
+        const READ = 0b1;
+
+        /// This directory can be mutated, for example by creating new files
+        /// within it.
+        const MUTATE = 0b10;
+    }
+}
+
+/// Options used to open a path relative to a [`WasiDir`].
+///
+/// These mirror the subset of `open-flags` and `descriptor-flags` which is
+/// passed down to the filesystem implementation after all of the
+/// permissions of the descriptor have been checked.
+#[derive(Copy, Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct OpenOptions {
+    /// Open the file for reading.
+    pub read: bool,
+    /// Open the file for writing.
+    pub write: bool,
+    /// Create the file if it does not exist.
+    pub create: bool,
+    /// Create the file, failing if it already exists.
+    pub create_new: bool,
+    /// Truncate the file to zero length.
+    pub truncate: bool,
+    /// Fail if the path does not refer to a directory.
+    pub directory: bool,
+    /// Follow a symbolic link in the final component of the path.
+    pub follow: bool,
+}
+
+/// The result of [`WasiDir::open_at`].
+pub enum Opened {
+    /// The path referred to a directory.
+    Dir(Arc<dyn WasiDir>),
+    /// The path referred to anything else, typically a regular file.
+    File(Arc<dyn WasiFile>),
+}
+
+/// The type of a filesystem object.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FileType {
+    /// A directory.
+    Directory,
+    /// A regular file.
+    RegularFile,
+    /// A symbolic link.
+    SymbolicLink,
+    /// A block device.
+    BlockDevice,
+    /// A character device.
+    CharacterDevice,
+    /// Any other type of object.
+    Unknown,
+}
+
+/// Metadata about a filesystem object.
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    /// The type of the object.
+    pub file_type: FileType,
+    /// The size of the object in bytes.
+    pub len: u64,
+    /// The number of hard links to the object.
+    pub nlink: u64,
+    /// Identifier of the device containing this object, together with `ino`
+    /// this uniquely identifies an object.
+    pub dev: u64,
+    /// Identifier of the object within its device.
+    pub ino: u64,
+    /// The last time the object was accessed, if known.
+    pub accessed: Option<SystemTime>,
+    /// The last time the object was modified, if known.
+    pub modified: Option<SystemTime>,
+    /// The time the object was created, if known.
+    pub created: Option<SystemTime>,
+}
+
+/// An entry returned by [`WasiDir::read_dir`].
+#[derive(Clone, Debug)]
+pub struct DirEntry {
+    /// The name of the entry within its directory.
+    pub name: OsString,
+    /// The type of the object the entry refers to.
+    pub file_type: FileType,
+}
+
+/// Access pattern hints passed to [`WasiFile::advise`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Advice {
+    /// No particular access pattern.
+    Normal,
+    /// The data will be accessed sequentially.
+    Sequential,
+    /// The data will be accessed in a random order.
+    Random,
+    /// The data will be accessed in the near future.
+    WillNeed,
+    /// The data will not be accessed in the near future.
+    DontNeed,
+    /// The data will be accessed only once.
+    NoReuse,
+}
+
+/// A directory which can be exposed to WebAssembly as a descriptor.
+///
+/// All methods are blocking and are invoked from a thread which is allowed to
+/// block, see [`allow_blocking_current_thread`]. Paths are always relative to
+/// this directory and implementations must not allow them to resolve to
+/// anything outside of it, for example through `..` or symbolic links.
+///
+/// Permissions configured through [`DirPerms`] and [`FilePerms`] have already
+/// been checked by the time these methods are called.
+///
+/// Operations involving two directories, such as [`WasiDir::rename_at`], are
+/// given the other directory as a `&dyn WasiDir`. Implementations can upcast
+/// it to `&dyn Any` to recover their own type and should fail with
+/// [`io::ErrorKind::CrossesDevices`] if it's anything else.
+///
+/// [`allow_blocking_current_thread`]: crate::p2::WasiCtxBuilder::allow_blocking_current_thread
+pub trait WasiDir: Any + Send + Sync {
+    /// Opens the file or directory at `path`.
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened>;
+
+    /// Creates a new, empty, directory at `path`.
+    fn create_dir_at(&self, path: &str) -> io::Result<()>;
+
+    /// Returns the metadata of this directory.
+    fn metadata(&self) -> io::Result<Metadata>;
+
+    /// Returns the metadata of the object at `path`.
+    fn metadata_at(&self, path: &str, follow: bool) -> io::Result<Metadata>;
+
+    /// Updates the timestamps of this directory, `None` leaves a timestamp
+    /// unchanged.
+    fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()>;
+
+    /// Updates the timestamps of the object at `path`.
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow: bool,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()>;
+
+    /// Returns all entries of this directory, excluding `.` and `..`.
+    fn read_dir(&self) -> io::Result<Vec<io::Result<DirEntry>>>;
+
+    /// Returns the target of the symbolic link at `path`.
+    fn read_link_at(&self, path: &str) -> io::Result<PathBuf>;
+
+    /// Removes the empty directory at `path`.
+    fn remove_dir_at(&self, path: &str) -> io::Result<()>;
+
+    /// Removes the file or symbolic link at `path`.
+    fn unlink_file_at(&self, path: &str) -> io::Result<()>;
+
+    /// Creates a symbolic link at `path` pointing to `target`.
+    fn symlink_at(&self, target: &str, path: &str) -> io::Result<()>;
+
+    /// Moves the object at `old_path` to `new_path` within `new_dir`.
+    ///
+    /// `new_dir` may be a different implementation of this trait in which
+    /// case this should fail.
+    fn rename_at(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()>;
+
+    /// Creates a hard link at `new_path` within `new_dir` to the file at
+    /// `old_path`.
+    fn hard_link_at(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str)
+    -> io::Result<()>;
+
+    /// Flushes all data and metadata of this directory to storage.
+    fn sync(&self) -> io::Result<()>;
+
+    /// Flushes the data of this directory to storage.
+    fn sync_data(&self) -> io::Result<()>;
+}
+
+/// A file which can be exposed to WebAssembly as a descriptor.
+///
+/// Like [`WasiDir`] all methods here are blocking.
+///
+/// Files are opened with the [`OpenOptions`] given to [`WasiDir::open_at`]
+/// and, like a host file, should fail operations they weren't opened for.
+pub trait WasiFile: Send + Sync {
+    /// Reads bytes at `offset` into `buf`, returning how many were read.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    /// Writes bytes from `buf` at `offset`, returning how many were written.
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize>;
+
+    /// Writes bytes from `buf` at the end of the file, returning how many were
+    /// written.
+    fn append(&self, buf: &[u8]) -> io::Result<usize>;
+
+    /// Truncates or extends the file to `size` bytes.
+    fn set_len(&self, size: u64) -> io::Result<()>;
+
+    /// Returns the metadata of this file.
+    fn metadata(&self) -> io::Result<Metadata>;
+
+    /// Updates the timestamps of this file, `None` leaves a timestamp
+    /// unchanged.
+    fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> io::Result<()>;
+
+    /// Flushes all data and metadata of this file to storage.
+    fn sync(&self) -> io::Result<()>;
+
+    /// Flushes the data of this file to storage.
+    fn sync_data(&self) -> io::Result<()>;
+
+    /// Provides a hint about how a range of this file is going to be accessed.
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let _ = (offset, len, advice);
+        Ok(())
+    }
+}
+
+/// Returns the error for an operation involving two different filesystems.
+pub(crate) fn cross_device_error() -> io::Error {
+    io::ErrorKind::CrossesDevices.into()
+}
+
+/// Returns the error for an operation on a file which wasn't opened for it.
+pub(crate) fn bad_descriptor_error() -> io::Error {
+    #[cfg(unix)]
+    return io::Error::from_raw_os_error(rustix::io::Errno::BADF.raw_os_error());
+    #[cfg(windows)]
+    return io::Error::from_raw_os_error(
+        windows_sys::Win32::Foundation::ERROR_INVALID_HANDLE as i32,
+    );
+}
+
+/// Returns the error for too many levels of symbolic links.
+pub(crate) fn loop_error() -> io::Error {
+    #[cfg(unix)]
+    return io::Error::from_raw_os_error(rustix::io::Errno::LOOP.raw_os_error());
+    #[cfg(windows)]
+    return io::Error::from_raw_os_error(
+        windows_sys::Win32::Foundation::ERROR_STOPPED_ON_SYMLINK as i32,
+    );
+}
+
+/// Returns the error for a path which would resolve outside of the directory
+/// it's relative to.
+pub(crate) fn escape_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "a path led outside of the filesystem",
+    )
+}
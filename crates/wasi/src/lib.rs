@@ -16,7 +16,7 @@ pub mod cli;
 pub mod clocks;
 mod ctx;
 mod error;
-mod fs;
+pub mod fs;
 mod net;
 pub mod p2;
 #[cfg(feature = "p3")]
@@ -31,7 +31,7 @@ pub mod runtime;
 pub use self::clocks::{HostMonotonicClock, HostWallClock};
 pub(crate) use self::ctx::WasiCtxBuilder;
 pub use self::error::{I32Exit, TrappableError};
-pub use self::fs::{DirPerms, FilePerms, OpenMode};
+pub use self::fs::{DirPerms, FilePerms, MemoryFs, OpenMode};
 pub use self::net::{Network, SocketAddrUse};
 pub use self::random::{Deterministic, thread_rng};
 #[doc(no_inline)]
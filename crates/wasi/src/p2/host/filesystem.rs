@@ -1,3 +1,4 @@
+use crate::fs::{Advice, FileType, Metadata, OpenOptions, Opened};
 use crate::p2::bindings::clocks::wall_clock;
 use crate::p2::bindings::filesystem::preopens;
 use crate::p2::bindings::filesystem::types::{
@@ -7,7 +8,7 @@ use crate::p2::filesystem::{
     Descriptor, Dir, File, FileInputStream, FileOutputStream, ReaddirIterator,
 };
 use crate::p2::{FsError, FsResult, IoView, WasiImpl, WasiView};
-use crate::{DirPerms, FilePerms, OpenMode};
+use crate::{DirPerms, FilePerms, OpenMode, SystemTimeSpec};
 use anyhow::Context;
 use wasmtime::component::Resource;
 use wasmtime_wasi_io::streams::{DynInputStream, DynOutputStream};
@@ -68,16 +69,13 @@ where
         len: types::Filesize,
         advice: types::Advice,
     ) -> FsResult<()> {
-        use system_interface::fs::{Advice as A, FileIoExt};
-        use types::Advice;
-
         let advice = match advice {
-            Advice::Normal => A::Normal,
-            Advice::Sequential => A::Sequential,
-            Advice::Random => A::Random,
-            Advice::WillNeed => A::WillNeed,
-            Advice::DontNeed => A::DontNeed,
-            Advice::NoReuse => A::NoReuse,
+            types::Advice::Normal => Advice::Normal,
+            types::Advice::Sequential => Advice::Sequential,
+            types::Advice::Random => Advice::Random,
+            types::Advice::WillNeed => Advice::WillNeed,
+            types::Advice::DontNeed => Advice::DontNeed,
+            types::Advice::NoReuse => Advice::NoReuse,
         };
 
         let f = self.table().get(&fd)?.file()?;
@@ -106,10 +104,7 @@ where
                     Err(e) => Err(e.into()),
                 }
             }
-            Descriptor::Dir(d) => {
-                d.run_blocking(|d| Ok(d.open(std::path::Component::CurDir)?.sync_data()?))
-                    .await
-            }
+            Descriptor::Dir(d) => Ok(d.run_blocking(|d| d.sync_data()).await?),
         }
     }
 
@@ -117,28 +112,14 @@ where
         &mut self,
         fd: Resource<types::Descriptor>,
     ) -> FsResult<types::DescriptorFlags> {
-        use system_interface::fs::{FdFlags, GetSetFdFlags};
         use types::DescriptorFlags;
 
-        fn get_from_fdflags(flags: FdFlags) -> DescriptorFlags {
-            let mut out = DescriptorFlags::empty();
-            if flags.contains(FdFlags::DSYNC) {
-                out |= DescriptorFlags::REQUESTED_WRITE_SYNC;
-            }
-            if flags.contains(FdFlags::RSYNC) {
-                out |= DescriptorFlags::DATA_INTEGRITY_SYNC;
-            }
-            if flags.contains(FdFlags::SYNC) {
-                out |= DescriptorFlags::FILE_INTEGRITY_SYNC;
-            }
-            out
-        }
-
+        // Descriptors are never opened with any of the `*-sync` flags, see
+        // `open_at`, so only the open mode needs to be reflected here.
         let descriptor = self.table().get(&fd)?;
         match descriptor {
             Descriptor::File(f) => {
-                let flags = f.run_blocking(|f| f.get_fd_flags()).await?;
-                let mut flags = get_from_fdflags(flags);
+                let mut flags = DescriptorFlags::empty();
                 if f.open_mode.contains(OpenMode::READ) {
                     flags |= DescriptorFlags::READ;
                 }
@@ -148,8 +129,7 @@ where
                 Ok(flags)
             }
             Descriptor::Dir(d) => {
-                let flags = d.run_blocking(|d| d.get_fd_flags()).await?;
-                let mut flags = get_from_fdflags(flags);
+                let mut flags = DescriptorFlags::empty();
                 if d.open_mode.contains(OpenMode::READ) {
                     flags |= DescriptorFlags::READ;
                 }
@@ -170,7 +150,7 @@ where
         match descriptor {
             Descriptor::File(f) => {
                 let meta = f.run_blocking(|f| f.metadata()).await?;
-                Ok(descriptortype_from(meta.file_type()))
+                Ok(descriptortype_from(meta.file_type))
             }
             Descriptor::Dir(_) => Ok(types::DescriptorType::Directory),
         }
@@ -195,8 +175,6 @@ where
         atim: types::NewTimestamp,
         mtim: types::NewTimestamp,
     ) -> FsResult<()> {
-        use fs_set_times::SetTimes;
-
         let descriptor = self.table().get(&fd)?;
         match descriptor {
             Descriptor::File(f) => {
@@ -226,9 +204,6 @@ where
         len: types::Filesize,
         offset: types::Filesize,
     ) -> FsResult<(Vec<u8>, bool)> {
-        use std::io::IoSliceMut;
-        use system_interface::fs::FileIoExt;
-
         let table = self.table();
 
         let f = table.get(&fd)?.file()?;
@@ -239,7 +214,7 @@ where
         let (mut buffer, r) = f
             .run_blocking(move |f| {
                 let mut buffer = vec![0; len.try_into().unwrap_or(usize::MAX)];
-                let r = f.read_vectored_at(&mut [IoSliceMut::new(&mut buffer)], offset);
+                let r = f.read_at(&mut buffer, offset);
                 (buffer, r)
             })
             .await;
@@ -260,18 +235,13 @@ where
         buf: Vec<u8>,
         offset: types::Filesize,
     ) -> FsResult<types::Filesize> {
-        use std::io::IoSlice;
-        use system_interface::fs::FileIoExt;
-
         let table = self.table();
         let f = table.get(&fd)?.file()?;
         if !f.perms.contains(FilePerms::WRITE) {
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        let bytes_written = f
-            .run_blocking(move |f| f.write_vectored_at(&[IoSlice::new(&buf)], offset))
-            .await?;
+        let bytes_written = f.run_blocking(move |f| f.write_at(&buf, offset)).await?;
 
         Ok(types::Filesize::try_from(bytes_written).expect("usize fits in Filesize"))
     }
@@ -297,42 +267,18 @@ where
         }
 
         let entries = d
-            .run_blocking(|d| {
-                // Both `entries` and `metadata` perform syscalls, which is why they are done
-                // within this `block` call, rather than delay calculating the metadata
-                // for entries when they're demanded later in the iterator chain.
-                Ok::<_, std::io::Error>(
-                    d.entries()?
-                        .map(|entry| {
-                            let entry = entry?;
-                            let meta = entry.metadata()?;
-                            let type_ = descriptortype_from(meta.file_type());
-                            let name = entry
-                                .file_name()
-                                .into_string()
-                                .map_err(|_| ReaddirError::IllegalSequence)?;
-                            Ok(types::DirectoryEntry { type_, name })
-                        })
-                        .collect::<Vec<Result<types::DirectoryEntry, ReaddirError>>>(),
-                )
-            })
+            .run_blocking(|d| d.read_dir())
             .await?
-            .into_iter();
-
-        // On windows, filter out files like `C:\DumpStack.log.tmp` which we
-        // can't get full metadata for.
-        #[cfg(windows)]
-        let entries = entries.filter(|entry| {
-            use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION};
-            if let Err(ReaddirError::Io(err)) = entry {
-                if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32)
-                    || err.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32)
-                {
-                    return false;
-                }
-            }
-            true
-        });
+            .into_iter()
+            .map(|entry| {
+                let entry = entry?;
+                let type_ = descriptortype_from(entry.file_type);
+                let name = entry
+                    .name
+                    .into_string()
+                    .map_err(|_| ReaddirError::IllegalSequence)?;
+                Ok(types::DirectoryEntry { type_, name })
+            });
         let entries = entries.map(|r| match r {
             Ok(r) => Ok(r),
             Err(ReaddirError::Io(e)) => Err(e.into()),
@@ -346,7 +292,7 @@ where
 
         match descriptor {
             Descriptor::File(f) => {
-                match f.run_blocking(|f| f.sync_all()).await {
+                match f.run_blocking(|f| f.sync()).await {
                     Ok(()) => Ok(()),
                     // On windows, `sync_data` uses `FileFlushBuffers` which fails with
                     // `ERROR_ACCESS_DENIED` if the file is not upen for writing. Ignore
@@ -361,10 +307,7 @@ where
                     Err(e) => Err(e.into()),
                 }
             }
-            Descriptor::Dir(d) => {
-                d.run_blocking(|d| Ok(d.open(std::path::Component::CurDir)?.sync_all()?))
-                    .await
-            }
+            Descriptor::Dir(d) => Ok(d.run_blocking(|d| d.sync()).await?),
         }
     }
 
@@ -378,7 +321,7 @@ where
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        d.run_blocking(move |d| d.create_dir(&path)).await?;
+        d.run_blocking(move |d| d.create_dir_at(&path)).await?;
         Ok(())
     }
 
@@ -392,7 +335,7 @@ where
             }
             Descriptor::Dir(d) => {
                 // No permissions check on stat: if opened, allowed to stat it
-                let meta = d.run_blocking(|d| d.dir_metadata()).await?;
+                let meta = d.run_blocking(|d| d.metadata()).await?;
                 Ok(descriptorstat_from(meta))
             }
         }
@@ -410,11 +353,10 @@ where
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        let meta = if symlink_follow(path_flags) {
-            d.run_blocking(move |d| d.metadata(&path)).await?
-        } else {
-            d.run_blocking(move |d| d.symlink_metadata(&path)).await?
-        };
+        let follow = symlink_follow(path_flags);
+        let meta = d
+            .run_blocking(move |d| d.metadata_at(&path, follow))
+            .await?;
         Ok(descriptorstat_from(meta))
     }
 
@@ -426,8 +368,6 @@ where
         atim: types::NewTimestamp,
         mtim: types::NewTimestamp,
     ) -> FsResult<()> {
-        use cap_fs_ext::DirExt;
-
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::MUTATE) {
@@ -435,25 +375,9 @@ where
         }
         let atim = systemtimespec_from(atim)?;
         let mtim = systemtimespec_from(mtim)?;
-        if symlink_follow(path_flags) {
-            d.run_blocking(move |d| {
-                d.set_times(
-                    &path,
-                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                )
-            })
-            .await?;
-        } else {
-            d.run_blocking(move |d| {
-                d.set_symlink_times(
-                    &path,
-                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                )
-            })
+        let follow = symlink_follow(path_flags);
+        d.run_blocking(move |d| d.set_times_at(&path, follow, atim, mtim))
             .await?;
-        }
         Ok(())
     }
 
@@ -480,7 +404,7 @@ where
         }
         let new_dir_handle = std::sync::Arc::clone(&new_dir.dir);
         old_dir
-            .run_blocking(move |d| d.hard_link(&old_path, &new_dir_handle, &new_path))
+            .run_blocking(move |d| d.hard_link_at(&old_path, &*new_dir_handle, &new_path))
             .await?;
         Ok(())
     }
@@ -493,8 +417,6 @@ where
         oflags: types::OpenFlags,
         flags: types::DescriptorFlags,
     ) -> FsResult<Resource<types::Descriptor>> {
-        use cap_fs_ext::{FollowSymlinks, OpenOptionsFollowExt, OpenOptionsMaybeDirExt};
-        use system_interface::fs::{FdFlags, GetSetFdFlags};
         use types::{DescriptorFlags, OpenFlags};
 
         let allow_blocking_current_thread = self.ctx().allow_blocking_current_thread;
@@ -517,42 +439,39 @@ where
         let mut create = false;
         // Track open mode, for permission check and recording in created descriptor:
         let mut open_mode = OpenMode::empty();
-        // Construct the OpenOptions to give the OS:
-        let mut opts = cap_std::fs::OpenOptions::new();
-        opts.maybe_dir(true);
+        // Construct the OpenOptions to give the filesystem:
+        let mut opts = OpenOptions::default();
 
         if oflags.contains(OpenFlags::CREATE) {
             if oflags.contains(OpenFlags::EXCLUSIVE) {
-                opts.create_new(true);
+                opts.create_new = true;
             } else {
-                opts.create(true);
+                opts.create = true;
             }
             create = true;
-            opts.write(true);
+            opts.write = true;
             open_mode |= OpenMode::WRITE;
         }
 
         if oflags.contains(OpenFlags::TRUNCATE) {
-            opts.truncate(true).write(true);
+            opts.truncate = true;
+            opts.write = true;
         }
         if flags.contains(DescriptorFlags::READ) {
-            opts.read(true);
+            opts.read = true;
             open_mode |= OpenMode::READ;
         }
         if flags.contains(DescriptorFlags::WRITE) {
-            opts.write(true);
+            opts.write = true;
             open_mode |= OpenMode::WRITE;
         } else {
             // If not opened write, open read. This way the OS lets us open
             // the file, but we can use perms to reject use of the file later.
-            opts.read(true);
+            opts.read = true;
             open_mode |= OpenMode::READ;
         }
-        if symlink_follow(path_flags) {
-            opts.follow(FollowSymlinks::Yes);
-        } else {
-            opts.follow(FollowSymlinks::No);
-        }
+        opts.follow = symlink_follow(path_flags);
+        opts.directory = oflags.contains(OpenFlags::DIRECTORY);
 
         // These flags are not yet supported in cap-std:
         if flags.contains(DescriptorFlags::FILE_INTEGRITY_SYNC)
@@ -571,8 +490,8 @@ where
             }
         }
 
-        // Now enforce this WasiCtx's permissions before letting the OS have
-        // its shot:
+        // Now enforce this WasiCtx's permissions before letting the filesystem
+        // have its shot:
         if !d.perms.contains(DirPerms::MUTATE) && create {
             Err(ErrorCode::NotPermitted)?;
         }
@@ -580,36 +499,10 @@ where
             Err(ErrorCode::NotPermitted)?;
         }
 
-        // Represents each possible outcome from the spawn_blocking operation.
-        // This makes sure we don't have to give spawn_blocking any way to
-        // manipulate the table.
-        enum OpenResult {
-            Dir(cap_std::fs::Dir),
-            File(cap_std::fs::File),
-            NotDir,
-        }
-
-        let opened = d
-            .run_blocking::<_, std::io::Result<OpenResult>>(move |d| {
-                let mut opened = d.open_with(&path, &opts)?;
-                if opened.metadata()?.is_dir() {
-                    Ok(OpenResult::Dir(cap_std::fs::Dir::from_std_file(
-                        opened.into_std(),
-                    )))
-                } else if oflags.contains(OpenFlags::DIRECTORY) {
-                    Ok(OpenResult::NotDir)
-                } else {
-                    // FIXME cap-std needs a nonblocking open option so that files reads and writes
-                    // are nonblocking. Instead we set it after opening here:
-                    let set_fd_flags = opened.new_set_fd_flags(FdFlags::NONBLOCK)?;
-                    opened.set_fd_flags(set_fd_flags)?;
-                    Ok(OpenResult::File(opened))
-                }
-            })
-            .await?;
+        let opened = d.run_blocking(move |d| d.open_at(&path, &opts)).await?;
 
         match opened {
-            OpenResult::Dir(dir) => Ok(table.push(Descriptor::Dir(Dir::new(
+            Opened::Dir(dir) => Ok(table.push(Descriptor::Dir(Dir::new(
                 dir,
                 d.perms,
                 d.file_perms,
@@ -617,14 +510,12 @@ where
                 allow_blocking_current_thread,
             )))?),
 
-            OpenResult::File(file) => Ok(table.push(Descriptor::File(File::new(
+            Opened::File(file) => Ok(table.push(Descriptor::File(File::new(
                 file,
                 d.file_perms,
                 open_mode,
                 allow_blocking_current_thread,
             )))?),
-
-            OpenResult::NotDir => Err(ErrorCode::NotDirectory.into()),
         }
     }
 
@@ -651,7 +542,7 @@ where
         if !d.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        let link = d.run_blocking(move |d| d.read_link(&path)).await?;
+        let link = d.run_blocking(move |d| d.read_link_at(&path)).await?;
         Ok(link
             .into_os_string()
             .into_string()
@@ -668,7 +559,7 @@ where
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.run_blocking(move |d| d.remove_dir(&path)).await?)
+        Ok(d.run_blocking(move |d| d.remove_dir_at(&path)).await?)
     }
 
     async fn rename_at(
@@ -689,7 +580,7 @@ where
         }
         let new_dir_handle = std::sync::Arc::clone(&new_dir.dir);
         Ok(old_dir
-            .run_blocking(move |d| d.rename(&old_path, &new_dir_handle, &new_path))
+            .run_blocking(move |d| d.rename_at(&old_path, &*new_dir_handle, &new_path))
             .await?)
     }
 
@@ -699,16 +590,12 @@ where
         src_path: String,
         dest_path: String,
     ) -> FsResult<()> {
-        // On windows, Dir.symlink is provided by DirExt
-        #[cfg(windows)]
-        use cap_fs_ext::DirExt;
-
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.run_blocking(move |d| d.symlink(&src_path, &dest_path))
+        Ok(d.run_blocking(move |d| d.symlink_at(&src_path, &dest_path))
             .await?)
     }
 
@@ -717,15 +604,12 @@ where
         fd: Resource<types::Descriptor>,
         path: String,
     ) -> FsResult<()> {
-        use cap_fs_ext::DirExt;
-
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.run_blocking(move |d| d.remove_file_or_symlink(&path))
-            .await?)
+        Ok(d.run_blocking(move |d| d.unlink_file_at(&path)).await?)
     }
 
     fn read_via_stream(
@@ -797,12 +681,11 @@ where
         a: Resource<types::Descriptor>,
         b: Resource<types::Descriptor>,
     ) -> anyhow::Result<bool> {
-        use cap_fs_ext::MetadataExt;
         let descriptor_a = self.table().get(&a)?;
         let meta_a = get_descriptor_metadata(descriptor_a).await?;
         let descriptor_b = self.table().get(&b)?;
         let meta_b = get_descriptor_metadata(descriptor_b).await?;
-        if meta_a.dev() == meta_b.dev() && meta_a.ino() == meta_b.ino() {
+        if meta_a.dev == meta_b.dev && meta_a.ino == meta_b.ino {
             // MetadataHashValue does not derive eq, so use a pair of
             // comparisons to check equality:
             debug_assert_eq!(
@@ -836,14 +719,9 @@ where
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         // No permissions check on metadata: if dir opened, allowed to stat it
+        let follow = symlink_follow(path_flags);
         let meta = d
-            .run_blocking(move |d| {
-                if symlink_follow(path_flags) {
-                    d.metadata(path)
-                } else {
-                    d.symlink_metadata(path)
-                }
-            })
+            .run_blocking(move |d| d.metadata_at(&path, follow))
             .await?;
         Ok(calculate_metadata_hash(&meta))
     }
@@ -868,7 +746,7 @@ where
     }
 }
 
-async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<cap_std::fs::Metadata> {
+async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<Metadata> {
     match fd {
         Descriptor::File(f) => {
             // No permissions check on metadata: if opened, allowed to stat it
@@ -876,21 +754,20 @@ async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<cap_std::fs
         }
         Descriptor::Dir(d) => {
             // No permissions check on metadata: if opened, allowed to stat it
-            Ok(d.run_blocking(|d| d.dir_metadata()).await?)
+            Ok(d.run_blocking(|d| d.metadata()).await?)
         }
     }
 }
 
-fn calculate_metadata_hash(meta: &cap_std::fs::Metadata) -> types::MetadataHashValue {
-    use cap_fs_ext::MetadataExt;
+fn calculate_metadata_hash(meta: &Metadata) -> types::MetadataHashValue {
     // Without incurring any deps, std provides us with a 64 bit hash
     // function:
     use std::hash::Hasher;
     // Note that this means that the metadata hash (which becomes a preview1 ino) may
     // change when a different rustc release is used to build this host implementation:
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    hasher.write_u64(meta.dev());
-    hasher.write_u64(meta.ino());
+    hasher.write_u64(meta.dev);
+    hasher.write_u64(meta.ino);
     let lower = hasher.finish();
     // MetadataHashValue has a pair of 64-bit members for representing a
     // single 128-bit number. However, we only have 64 bits of entropy. To
@@ -937,6 +814,7 @@ fn from_raw_os_error(err: Option<i32>) -> Option<ErrorCode> {
         RustixErrno::ALREADY => ErrorCode::Already,
         RustixErrno::INPROGRESS => ErrorCode::InProgress,
         RustixErrno::INTR => ErrorCode::Interrupted,
+        RustixErrno::XDEV => ErrorCode::CrossDevice,
 
         #[allow(
             unreachable_patterns,
@@ -973,6 +851,7 @@ fn from_raw_os_error(raw_os_error: Option<i32>) -> Option<ErrorCode> {
         Some(Foundation::ERROR_ALREADY_EXISTS) => ErrorCode::Exist,
         Some(Foundation::ERROR_STOPPED_ON_SYMLINK) => ErrorCode::Loop,
         Some(Foundation::ERROR_DIRECTORY_NOT_SUPPORTED) => ErrorCode::IsDirectory,
+        Some(Foundation::ERROR_NOT_SAME_DEVICE) => ErrorCode::CrossDevice,
         _ => return None,
     })
 }
@@ -994,6 +873,15 @@ impl<'a> From<&'a std::io::Error> for ErrorCode {
                     std::io::ErrorKind::PermissionDenied => ErrorCode::NotPermitted,
                     std::io::ErrorKind::AlreadyExists => ErrorCode::Exist,
                     std::io::ErrorKind::InvalidInput => ErrorCode::Invalid,
+                    std::io::ErrorKind::NotADirectory => ErrorCode::NotDirectory,
+                    std::io::ErrorKind::IsADirectory => ErrorCode::IsDirectory,
+                    std::io::ErrorKind::DirectoryNotEmpty => ErrorCode::NotEmpty,
+                    std::io::ErrorKind::StorageFull => ErrorCode::InsufficientSpace,
+                    std::io::ErrorKind::FileTooLarge => ErrorCode::FileTooLarge,
+                    std::io::ErrorKind::ReadOnlyFilesystem => ErrorCode::ReadOnly,
+                    std::io::ErrorKind::QuotaExceeded => ErrorCode::Quota,
+                    std::io::ErrorKind::CrossesDevices => ErrorCode::CrossDevice,
+                    std::io::ErrorKind::Unsupported => ErrorCode::Unsupported,
                     _ => ErrorCode::Io,
                 }
             }
@@ -1014,31 +902,26 @@ impl From<std::num::TryFromIntError> for ErrorCode {
     }
 }
 
-fn descriptortype_from(ft: cap_std::fs::FileType) -> types::DescriptorType {
-    use cap_fs_ext::FileTypeExt;
+fn descriptortype_from(ft: FileType) -> types::DescriptorType {
     use types::DescriptorType;
-    if ft.is_dir() {
-        DescriptorType::Directory
-    } else if ft.is_symlink() {
-        DescriptorType::SymbolicLink
-    } else if ft.is_block_device() {
-        DescriptorType::BlockDevice
-    } else if ft.is_char_device() {
-        DescriptorType::CharacterDevice
-    } else if ft.is_file() {
-        DescriptorType::RegularFile
-    } else {
-        DescriptorType::Unknown
+    match ft {
+        FileType::Directory => DescriptorType::Directory,
+        FileType::SymbolicLink => DescriptorType::SymbolicLink,
+        FileType::BlockDevice => DescriptorType::BlockDevice,
+        FileType::CharacterDevice => DescriptorType::CharacterDevice,
+        FileType::RegularFile => DescriptorType::RegularFile,
+        FileType::Unknown => DescriptorType::Unknown,
     }
 }
 
-fn systemtimespec_from(t: types::NewTimestamp) -> FsResult<Option<fs_set_times::SystemTimeSpec>> {
-    use fs_set_times::SystemTimeSpec;
+fn systemtimespec_from(t: types::NewTimestamp) -> FsResult<Option<SystemTimeSpec>> {
     use types::NewTimestamp;
     match t {
         NewTimestamp::NoChange => Ok(None),
         NewTimestamp::Now => Ok(Some(SystemTimeSpec::SymbolicNow)),
-        NewTimestamp::Timestamp(st) => Ok(Some(SystemTimeSpec::Absolute(systemtime_from(st)?))),
+        NewTimestamp::Timestamp(st) => Ok(Some(SystemTimeSpec::Absolute(
+            cap_std::time::SystemTime::from_std(systemtime_from(st)?),
+        ))),
     }
 }
 
@@ -1054,15 +937,14 @@ fn datetime_from(t: std::time::SystemTime) -> wall_clock::Datetime {
     wall_clock::Datetime::try_from(cap_std::time::SystemTime::from_std(t)).unwrap()
 }
 
-fn descriptorstat_from(meta: cap_std::fs::Metadata) -> types::DescriptorStat {
-    use cap_fs_ext::MetadataExt;
+fn descriptorstat_from(meta: Metadata) -> types::DescriptorStat {
     types::DescriptorStat {
-        type_: descriptortype_from(meta.file_type()),
-        link_count: meta.nlink(),
-        size: meta.len(),
-        data_access_timestamp: meta.accessed().map(|t| datetime_from(t.into_std())).ok(),
-        data_modification_timestamp: meta.modified().map(|t| datetime_from(t.into_std())).ok(),
-        status_change_timestamp: meta.created().map(|t| datetime_from(t.into_std())).ok(),
+        type_: descriptortype_from(meta.file_type),
+        link_count: meta.nlink,
+        size: meta.len,
+        data_access_timestamp: meta.accessed.map(datetime_from),
+        data_modification_timestamp: meta.modified.map(datetime_from),
+        status_change_timestamp: meta.created.map(datetime_from),
     }
 }
 
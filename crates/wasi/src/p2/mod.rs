@@ -231,9 +231,9 @@ use wasmtime::component::{HasData, Linker};
 pub mod bindings;
 mod ctx;
 pub(crate) mod filesystem;
-mod host;
+pub(crate) mod host;
 mod ip_name_lookup;
-mod network;
+pub(crate) mod network;
 pub mod pipe;
 mod poll;
 mod stdio;
@@ -70,16 +70,16 @@
 //!             "wasi:filesystem/types@0.3.0#[async method]descriptor.is-same-object",
 //!             "wasi:filesystem/types@0.3.0#[async method]descriptor.metadata-hash",
 //!             "wasi:filesystem/types@0.3.0#[async method]descriptor.metadata-hash-at",
-//!             "wasi:sockets/ip-name-lookup@0.3.0#[async]resolve-addresses",
-//!             "wasi:sockets/types@0.3.0#[async method]tcp-socket.connect",
-//!             "wasi:sockets/types@0.3.0#[async method]tcp-socket.send",
-//!             "wasi:sockets/types@0.3.0#[async method]udp-socket.receive",
-//!             "wasi:sockets/types@0.3.0#[async method]udp-socket.send",
+//!             "wasi:sockets/ip-name-lookup@0.3.0#resolve-addresses",
 //!             "wasi:sockets/types@0.3.0#[method]tcp-socket.bind",
+//!             "wasi:sockets/types@0.3.0#[method]tcp-socket.connect",
 //!             "wasi:sockets/types@0.3.0#[method]tcp-socket.listen",
 //!             "wasi:sockets/types@0.3.0#[method]tcp-socket.receive",
+//!             "wasi:sockets/types@0.3.0#[method]tcp-socket.send",
 //!             "wasi:sockets/types@0.3.0#[method]udp-socket.bind",
 //!             "wasi:sockets/types@0.3.0#[method]udp-socket.connect",
+//!             "wasi:sockets/types@0.3.0#[method]udp-socket.receive",
+//!             "wasi:sockets/types@0.3.0#[method]udp-socket.send",
 //!         ],
 //!     },
 //! });
@@ -159,21 +159,23 @@ mod generated {
                 "wasi:filesystem/types@0.3.0#[async method]descriptor.is-same-object",
                 "wasi:filesystem/types@0.3.0#[async method]descriptor.metadata-hash",
                 "wasi:filesystem/types@0.3.0#[async method]descriptor.metadata-hash-at",
-                "wasi:sockets/ip-name-lookup@0.3.0#[async]resolve-addresses",
-                "wasi:sockets/types@0.3.0#[async method]tcp-socket.connect",
-                "wasi:sockets/types@0.3.0#[async method]tcp-socket.send",
-                "wasi:sockets/types@0.3.0#[async method]udp-socket.receive",
-                "wasi:sockets/types@0.3.0#[async method]udp-socket.send",
+                "wasi:sockets/ip-name-lookup@0.3.0#resolve-addresses",
                 "wasi:sockets/types@0.3.0#[method]tcp-socket.bind",
+                "wasi:sockets/types@0.3.0#[method]tcp-socket.connect",
                 "wasi:sockets/types@0.3.0#[method]tcp-socket.listen",
                 "wasi:sockets/types@0.3.0#[method]tcp-socket.receive",
+                "wasi:sockets/types@0.3.0#[method]tcp-socket.send",
                 "wasi:sockets/types@0.3.0#[method]udp-socket.bind",
                 "wasi:sockets/types@0.3.0#[method]udp-socket.connect",
+                "wasi:sockets/types@0.3.0#[method]udp-socket.receive",
+                "wasi:sockets/types@0.3.0#[method]udp-socket.send",
             ],
         },
         with: {
             "wasi:cli/terminal-input/terminal-input": crate::p3::cli::TerminalInput,
             "wasi:cli/terminal-output/terminal-output": crate::p3::cli::TerminalOutput,
+            "wasi:sockets/types/tcp-socket": crate::p3::sockets::TcpSocket,
+            "wasi:sockets/types/udp-socket": crate::p3::sockets::UdpSocket,
         }
     });
 }
@@ -1,10 +1,12 @@
 use crate::cli::WasiCliCtx;
 use crate::clocks::{HostMonotonicClock, HostWallClock, WasiClocksCtx};
+use crate::fs::{HostDir, WasiDir};
 use crate::net::SocketAddrUse;
 use crate::p3::cli::{InputStream, OutputStream};
 use crate::p3::filesystem::Dir;
+use crate::p3::sockets::WasiSocketsCtx;
 use crate::random::WasiRandomCtx;
-use crate::{DirPerms, FilePerms, OpenMode};
+use crate::{DirPerms, FilePerms, MemoryFs, OpenMode};
 use anyhow::Result;
 use cap_rand::RngCore;
 use cap_std::ambient_authority;
@@ -13,6 +15,7 @@ use std::mem;
 use std::net::SocketAddr;
 use std::path::Path;
 use std::pin::Pin;
+use std::sync::Arc;
 use tokio::io::{empty, stderr, stdin, stdout};
 
 /// Builder-style structure used to create a [`WasiCtx`].
@@ -291,6 +294,85 @@ impl WasiCtxBuilder {
         file_perms: FilePerms,
     ) -> Result<&mut Self> {
         let dir = cap_std::fs::Dir::open_ambient_dir(host_path.as_ref(), ambient_authority())?;
+        self.preopen(
+            Arc::new(HostDir::new(dir)),
+            guest_path.as_ref(),
+            dir_perms,
+            file_perms,
+        );
+        Ok(self)
+    }
+
+    /// Configures a "preopened directory" backed by the in-memory filesystem
+    /// `fs` to be available to WebAssembly.
+    ///
+    /// This is the same as [`preopened_dir`](WasiCtxBuilder::preopened_dir)
+    /// except that the root of `fs` is made available at `guest_path`
+    /// instead of a directory of the host. See [`MemoryFs`] for more
+    /// information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wasmtime_wasi::p3::WasiCtxBuilder;
+    /// use wasmtime_wasi::{DirPerms, FilePerms, MemoryFs};
+    ///
+    /// let mut wasi = WasiCtxBuilder::new();
+    ///
+    /// // Make an empty filesystem available in the guest as `/tmp`, with at
+    /// // most 1 MiB of file contents.
+    /// let tmp = MemoryFs::new().with_size_limit(1 << 20);
+    /// wasi.preopened_memory_dir(tmp, "/tmp", DirPerms::all(), FilePerms::all());
+    /// ```
+    pub fn preopened_memory_dir(
+        &mut self,
+        fs: MemoryFs,
+        guest_path: impl AsRef<str>,
+        dir_perms: DirPerms,
+        file_perms: FilePerms,
+    ) -> &mut Self {
+        self.preopen(fs.root(), guest_path.as_ref(), dir_perms, file_perms);
+        self
+    }
+
+    /// Configures a "preopened directory" backed by a custom filesystem
+    /// implementation to be available to WebAssembly.
+    ///
+    /// This is the same as [`preopened_dir`](WasiCtxBuilder::preopened_dir)
+    /// except that `dir` is made available at `guest_path`. This can be used
+    /// to back guest filesystems with storage other than the host's
+    /// filesystem, see the [`fs`](crate::fs) module for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wasmtime_wasi::fs::WasiDir;
+    /// use wasmtime_wasi::p3::WasiCtxBuilder;
+    /// use wasmtime_wasi::{DirPerms, FilePerms};
+    ///
+    /// fn configure(wasi: &mut WasiCtxBuilder, blobs: impl WasiDir) {
+    ///     // Make `blobs` available read-only in the guest as `/data`.
+    ///     wasi.preopened_custom_dir(blobs, "/data", DirPerms::READ, FilePerms::READ);
+    /// }
+    /// ```
+    pub fn preopened_custom_dir(
+        &mut self,
+        dir: impl WasiDir,
+        guest_path: impl AsRef<str>,
+        dir_perms: DirPerms,
+        file_perms: FilePerms,
+    ) -> &mut Self {
+        self.preopen(Arc::new(dir), guest_path.as_ref(), dir_perms, file_perms);
+        self
+    }
+
+    fn preopen(
+        &mut self,
+        dir: Arc<dyn WasiDir>,
+        guest_path: &str,
+        dir_perms: DirPerms,
+        file_perms: FilePerms,
+    ) {
         let mut open_mode = OpenMode::empty();
         if dir_perms.contains(DirPerms::READ) {
             open_mode |= OpenMode::READ;
@@ -306,9 +388,8 @@ impl WasiCtxBuilder {
                 open_mode,
                 self.common.allow_blocking_current_thread,
             ),
-            guest_path.as_ref().to_owned(),
+            guest_path.to_owned(),
         ));
-        Ok(self)
     }
 
     /// Set the generator for the `wasi:random/random` number generator to the
@@ -432,6 +513,8 @@ impl WasiCtxBuilder {
                     random,
                     clocks,
                     cli,
+                    socket_addr_check,
+                    allowed_network_uses,
                     ..
                 },
             built: _,
@@ -443,6 +526,10 @@ impl WasiCtxBuilder {
             random,
             clocks,
             cli,
+            sockets: WasiSocketsCtx {
+                socket_addr_check,
+                allowed_network_uses,
+            },
         }
     }
 }
@@ -499,6 +586,7 @@ pub struct WasiCtx {
     pub random: WasiRandomCtx,
     pub clocks: WasiClocksCtx,
     pub cli: WasiCliCtx<Box<dyn InputStream>, Box<dyn OutputStream>>,
+    pub sockets: WasiSocketsCtx,
 }
 
 impl WasiCtx {
@@ -1,12 +1,14 @@
+use crate::fs::WasiDir;
 use crate::{DirPerms, FilePerms, OpenMode};
+use std::sync::Arc;
 
 // TODO: implement
 pub struct Dir;
 
 impl Dir {
     #[expect(unused)]
-    pub fn new(
-        dir: cap_std::fs::Dir,
+    pub(crate) fn new(
+        dir: Arc<dyn WasiDir>,
         perms: DirPerms,
         file_perms: FilePerms,
         open_mode: OpenMode,
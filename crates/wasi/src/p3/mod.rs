@@ -14,12 +14,14 @@ pub mod clocks;
 mod ctx;
 pub mod filesystem;
 pub mod random;
+pub mod sockets;
 mod view;
 
 use wasmtime::component::Linker;
 
 use crate::p3::bindings::LinkOptions;
 use crate::p3::cli::WasiCliCtxView;
+use crate::p3::sockets::WasiSocketsCtxView;
 
 pub use self::ctx::{WasiCtx, WasiCtxBuilder};
 pub use self::view::{WasiCtxView, WasiView};
@@ -97,5 +99,12 @@ where
             table,
         }
     })?;
+    sockets::add_to_linker_impl(linker, |x| {
+        let WasiCtxView { ctx, table } = x.ctx();
+        WasiSocketsCtxView {
+            ctx: &mut ctx.sockets,
+            table,
+        }
+    })?;
     Ok(())
 }
@@ -0,0 +1,173 @@
+use crate::net::SocketAddressFamily;
+use crate::p2::network::{from_ipv4_addr, from_ipv6_addr, to_ipv4_addr, to_ipv6_addr};
+use crate::p3::bindings::sockets::types::{
+    ErrorCode, IpAddress, IpAddressFamily, IpSocketAddress, Ipv4SocketAddress, Ipv6SocketAddress,
+};
+use rustix::io::Errno;
+use std::io;
+use std::net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+impl From<io::Error> for ErrorCode {
+    fn from(value: io::Error) -> Self {
+        (&value).into()
+    }
+}
+
+impl From<&io::Error> for ErrorCode {
+    fn from(value: &io::Error) -> Self {
+        // Attempt the more detailed native error code first:
+        if let Some(errno) = Errno::from_io_error(value) {
+            return errno.into();
+        }
+
+        match value.kind() {
+            io::ErrorKind::AddrInUse => ErrorCode::AddressInUse,
+            io::ErrorKind::AddrNotAvailable => ErrorCode::AddressNotBindable,
+            io::ErrorKind::ConnectionAborted => ErrorCode::ConnectionAborted,
+            io::ErrorKind::ConnectionRefused => ErrorCode::ConnectionRefused,
+            io::ErrorKind::ConnectionReset => ErrorCode::ConnectionReset,
+            io::ErrorKind::InvalidInput => ErrorCode::InvalidArgument,
+            io::ErrorKind::NotConnected => ErrorCode::InvalidState,
+            io::ErrorKind::OutOfMemory => ErrorCode::OutOfMemory,
+            io::ErrorKind::PermissionDenied => ErrorCode::AccessDenied,
+            io::ErrorKind::TimedOut => ErrorCode::Timeout,
+            io::ErrorKind::Unsupported => ErrorCode::NotSupported,
+
+            _ => {
+                tracing::debug!("unknown I/O error: {value}");
+                ErrorCode::Unknown
+            }
+        }
+    }
+}
+
+impl From<Errno> for ErrorCode {
+    fn from(value: Errno) -> Self {
+        (&value).into()
+    }
+}
+
+impl From<&Errno> for ErrorCode {
+    fn from(value: &Errno) -> Self {
+        match *value {
+            #[cfg(not(windows))]
+            Errno::PERM => ErrorCode::AccessDenied,
+            Errno::ACCESS => ErrorCode::AccessDenied,
+            Errno::ADDRINUSE => ErrorCode::AddressInUse,
+            Errno::ADDRNOTAVAIL => ErrorCode::AddressNotBindable,
+            Errno::ALREADY => ErrorCode::InvalidState,
+            Errno::TIMEDOUT => ErrorCode::Timeout,
+            Errno::CONNREFUSED => ErrorCode::ConnectionRefused,
+            Errno::CONNRESET => ErrorCode::ConnectionReset,
+            Errno::CONNABORTED => ErrorCode::ConnectionAborted,
+            Errno::INVAL => ErrorCode::InvalidArgument,
+            Errno::HOSTUNREACH => ErrorCode::RemoteUnreachable,
+            Errno::HOSTDOWN => ErrorCode::RemoteUnreachable,
+            Errno::NETDOWN => ErrorCode::RemoteUnreachable,
+            Errno::NETUNREACH => ErrorCode::RemoteUnreachable,
+            #[cfg(target_os = "linux")]
+            Errno::NONET => ErrorCode::RemoteUnreachable,
+            Errno::ISCONN => ErrorCode::InvalidState,
+            Errno::NOTCONN => ErrorCode::InvalidState,
+            Errno::DESTADDRREQ => ErrorCode::InvalidState,
+            Errno::MSGSIZE => ErrorCode::DatagramTooLarge,
+            #[cfg(not(windows))]
+            Errno::NOMEM => ErrorCode::OutOfMemory,
+            Errno::NOBUFS => ErrorCode::OutOfMemory,
+            Errno::OPNOTSUPP => ErrorCode::NotSupported,
+            Errno::NOPROTOOPT => ErrorCode::NotSupported,
+            Errno::PFNOSUPPORT => ErrorCode::NotSupported,
+            Errno::PROTONOSUPPORT => ErrorCode::NotSupported,
+            Errno::PROTOTYPE => ErrorCode::NotSupported,
+            Errno::SOCKTNOSUPPORT => ErrorCode::NotSupported,
+            Errno::AFNOSUPPORT => ErrorCode::NotSupported,
+
+            _ => {
+                tracing::debug!("unknown I/O error: {value}");
+                ErrorCode::Unknown
+            }
+        }
+    }
+}
+
+impl From<IpAddr> for IpAddress {
+    fn from(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(v4) => Self::Ipv4(from_ipv4_addr(v4)),
+            IpAddr::V6(v6) => Self::Ipv6(from_ipv6_addr(v6)),
+        }
+    }
+}
+
+impl From<IpSocketAddress> for SocketAddr {
+    fn from(addr: IpSocketAddress) -> Self {
+        match addr {
+            IpSocketAddress::Ipv4(ipv4) => Self::V4(ipv4.into()),
+            IpSocketAddress::Ipv6(ipv6) => Self::V6(ipv6.into()),
+        }
+    }
+}
+
+impl From<SocketAddr> for IpSocketAddress {
+    fn from(addr: SocketAddr) -> Self {
+        match addr {
+            SocketAddr::V4(v4) => Self::Ipv4(v4.into()),
+            SocketAddr::V6(v6) => Self::Ipv6(v6.into()),
+        }
+    }
+}
+
+impl From<Ipv4SocketAddress> for SocketAddrV4 {
+    fn from(addr: Ipv4SocketAddress) -> Self {
+        Self::new(to_ipv4_addr(addr.address), addr.port)
+    }
+}
+
+impl From<SocketAddrV4> for Ipv4SocketAddress {
+    fn from(addr: SocketAddrV4) -> Self {
+        Self {
+            address: from_ipv4_addr(*addr.ip()),
+            port: addr.port(),
+        }
+    }
+}
+
+impl From<Ipv6SocketAddress> for SocketAddrV6 {
+    fn from(addr: Ipv6SocketAddress) -> Self {
+        Self::new(
+            to_ipv6_addr(addr.address),
+            addr.port,
+            addr.flow_info,
+            addr.scope_id,
+        )
+    }
+}
+
+impl From<SocketAddrV6> for Ipv6SocketAddress {
+    fn from(addr: SocketAddrV6) -> Self {
+        Self {
+            address: from_ipv6_addr(*addr.ip()),
+            port: addr.port(),
+            flow_info: addr.flowinfo(),
+            scope_id: addr.scope_id(),
+        }
+    }
+}
+
+impl From<IpAddressFamily> for cap_net_ext::AddressFamily {
+    fn from(family: IpAddressFamily) -> Self {
+        match family {
+            IpAddressFamily::Ipv4 => cap_net_ext::AddressFamily::Ipv4,
+            IpAddressFamily::Ipv6 => cap_net_ext::AddressFamily::Ipv6,
+        }
+    }
+}
+
+impl From<SocketAddressFamily> for IpAddressFamily {
+    fn from(family: SocketAddressFamily) -> Self {
+        match family {
+            SocketAddressFamily::Ipv4 => IpAddressFamily::Ipv4,
+            SocketAddressFamily::Ipv6 => IpAddressFamily::Ipv6,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::p2::host::network::util;
+use crate::p3::bindings::sockets::ip_name_lookup::{ErrorCode, Host, HostConcurrent};
+use crate::p3::bindings::sockets::types::IpAddress;
+use crate::p3::sockets::{WasiSockets, WasiSocketsCtxView};
+use crate::runtime::spawn_blocking;
+use std::net::{Ipv6Addr, ToSocketAddrs};
+use std::str::FromStr;
+use wasmtime::component::Accessor;
+
+impl Host for WasiSocketsCtxView<'_> {}
+
+impl HostConcurrent for WasiSockets {
+    async fn resolve_addresses<T: 'static>(
+        store: &Accessor<T, Self>,
+        name: String,
+    ) -> wasmtime::Result<Result<Vec<IpAddress>, ErrorCode>> {
+        let allowed = store.with(|mut view| view.get().ctx.allowed_network_uses.ip_name_lookup);
+        if !allowed {
+            return Ok(Err(ErrorCode::PermanentResolverFailure));
+        }
+        let host = match parse(&name) {
+            Ok(host) => host,
+            Err(err) => return Ok(Err(err)),
+        };
+        Ok(spawn_blocking(move || blocking_resolve(&host)).await)
+    }
+}
+
+fn parse(name: &str) -> Result<url::Host, ErrorCode> {
+    // `url::Host::parse` serves us two functions:
+    // 1. validate the input is a valid domain name or IP,
+    // 2. convert unicode domains to punycode.
+    match url::Host::parse(name) {
+        Ok(host) => Ok(host),
+
+        // `url::Host::parse` doesn't understand bare IPv6 addresses without [brackets]
+        Err(_) => {
+            if let Ok(addr) = Ipv6Addr::from_str(name) {
+                Ok(url::Host::Ipv6(addr))
+            } else {
+                Err(ErrorCode::InvalidArgument)
+            }
+        }
+    }
+}
+
+fn blocking_resolve(host: &url::Host) -> Result<Vec<IpAddress>, ErrorCode> {
+    match host {
+        url::Host::Ipv4(v4addr) => Ok(vec![IpAddress::from(std::net::IpAddr::V4(*v4addr))]),
+        url::Host::Ipv6(v6addr) => Ok(vec![IpAddress::from(std::net::IpAddr::V6(*v6addr))]),
+        url::Host::Domain(domain) => {
+            // For now use the standard library to perform actual resolution through
+            // the usage of the `ToSocketAddrs` trait. This is only
+            // resolving names, not ports, so force the port to be 0.
+            let addresses = (domain.as_str(), 0)
+                .to_socket_addrs()
+                .map_err(|_| ErrorCode::NameUnresolvable)? // If/when we use `getaddrinfo` directly, map the error properly.
+                .map(|addr| util::to_canonical(&addr.ip()).into())
+                .collect();
+
+            Ok(addresses)
+        }
+    }
+}
@@ -0,0 +1,48 @@
+mod tcp;
+mod udp;
+
+use crate::net::{SocketAddrCheck, SocketAddrUse, SocketAddressFamily};
+use crate::p3::bindings::sockets::types::{ErrorCode, Host, HostConcurrent};
+use crate::p3::sockets::{WasiSockets, WasiSocketsCtx, WasiSocketsCtxView};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+impl Host for WasiSocketsCtxView<'_> {}
+
+impl HostConcurrent for WasiSockets {}
+
+impl WasiSocketsCtx {
+    /// Returns the socket address check to run for `reason`, failing if the
+    /// protocol of `reason` was disabled altogether.
+    fn socket_addr_check(&self, reason: SocketAddrUse) -> Result<SocketAddrCheck, ErrorCode> {
+        match reason {
+            SocketAddrUse::TcpBind | SocketAddrUse::TcpConnect => {
+                self.allowed_network_uses.check_allowed_tcp()?
+            }
+            SocketAddrUse::UdpBind
+            | SocketAddrUse::UdpConnect
+            | SocketAddrUse::UdpOutgoingDatagram => {
+                self.allowed_network_uses.check_allowed_udp()?
+            }
+        }
+        Ok(self.socket_addr_check.clone())
+    }
+}
+
+/// Runs the embedder's socket address check, mapping a denial to
+/// `access-denied`.
+async fn check_socket_addr(
+    check: SocketAddrCheck,
+    addr: SocketAddr,
+    reason: SocketAddrUse,
+) -> Result<(), ErrorCode> {
+    check.check(addr, reason).await?;
+    Ok(())
+}
+
+/// The address a socket of `family` is implicitly bound to.
+fn unspecified_address(family: SocketAddressFamily) -> SocketAddr {
+    match family {
+        SocketAddressFamily::Ipv4 => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddressFamily::Ipv6 => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    }
+}
@@ -0,0 +1,515 @@
+use super::{check_socket_addr, unspecified_address};
+use crate::net::{SocketAddrUse, SocketAddressFamily};
+use crate::p3::bindings::sockets::types::{
+    Duration, ErrorCode, HostTcpSocket, HostTcpSocketConcurrent, IpAddressFamily, IpSocketAddress,
+};
+use crate::p3::sockets::tcp::{InheritedOptions, native_shutdown};
+use crate::p3::sockets::{TcpSocket, WasiSockets, WasiSocketsCtxView};
+use anyhow::Context as _;
+use bytes::BytesMut;
+use rustix::io::Errno;
+use std::io::{self, Cursor};
+use std::net::{Shutdown, SocketAddr};
+use std::sync::Arc;
+use wasmtime::component::{
+    Accessor, AccessorTask, FutureWriter, HostFuture, HostStream, Resource, ResourceTable,
+    StreamWriter,
+};
+
+fn get_socket<'a>(
+    table: &'a mut ResourceTable,
+    socket: &Resource<TcpSocket>,
+) -> wasmtime::Result<&'a mut TcpSocket> {
+    table
+        .get_mut(socket)
+        .context("failed to get TCP socket resource from table")
+}
+
+/// Returns whether an error returned by `accept` only concerns the connection
+/// being accepted, in which case the listener remains operational.
+fn is_transient_accept_error(err: &io::Error) -> bool {
+    match Errno::from_io_error(err) {
+        Some(Errno::CONNABORTED | Errno::INTR) => true,
+
+        // From: https://learn.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-accept#:~:text=WSAEINPROGRESS
+        // > WSAEINPROGRESS: A blocking Windows Sockets 1.1 call is in progress,
+        // > or the service provider is still processing a callback function.
+        #[cfg(windows)]
+        Some(Errno::INPROGRESS) => true,
+
+        // From https://man7.org/linux/man-pages/man2/accept.2.html:
+        // > Linux accept() passes already-pending network errors on the
+        // > new socket as an error code from accept(). This behavior
+        // > differs from other BSD socket implementations. (...)
+        #[cfg(target_os = "linux")]
+        Some(
+            Errno::CONNRESET
+            | Errno::NETRESET
+            | Errno::HOSTUNREACH
+            | Errno::HOSTDOWN
+            | Errno::NETDOWN
+            | Errno::NETUNREACH
+            | Errno::PROTO
+            | Errno::NOPROTOOPT
+            | Errno::NONET
+            | Errno::OPNOTSUPP,
+        ) => true,
+
+        _ => false,
+    }
+}
+
+async fn write_all(stream: &tokio::net::TcpStream, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        stream.writable().await?;
+        match stream.try_write(buf) {
+            Ok(n) => buf = &buf[n..],
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+struct ListenTask {
+    listener: Arc<tokio::net::TcpListener>,
+    family: SocketAddressFamily,
+    options: InheritedOptions,
+    tx: StreamWriter<Option<Resource<TcpSocket>>>,
+}
+
+impl<T> AccessorTask<T, WasiSockets, wasmtime::Result<()>> for ListenTask {
+    async fn run(mut self, store: &Accessor<T, WasiSockets>) -> wasmtime::Result<()> {
+        while !self.tx.is_closed() {
+            let client = match self.listener.accept().await {
+                Ok((client, _)) => client,
+                Err(err) if is_transient_accept_error(&err) => {
+                    tracing::debug!("skipping failed incoming connection: {err}");
+                    continue;
+                }
+                Err(err) => {
+                    tracing::debug!("failed to accept connection: {err}");
+                    break;
+                }
+            };
+            let socket = TcpSocket::accepted(client, self.family, &self.options);
+            let socket = store.with(|mut view| {
+                view.get()
+                    .table
+                    .push(socket)
+                    .context("failed to push TCP socket resource to table")
+            })?;
+            if let Some(socket) = self.tx.write_all(store, Some(socket)).await {
+                // The stream was closed before the socket could be delivered.
+                store.with(|mut view| {
+                    view.get()
+                        .table
+                        .delete(socket)
+                        .context("failed to delete TCP socket resource from table")
+                })?;
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct ReceiveTask {
+    stream: Arc<tokio::net::TcpStream>,
+    data: StreamWriter<Cursor<BytesMut>>,
+    result: FutureWriter<Result<(), ErrorCode>>,
+}
+
+impl<T> AccessorTask<T, WasiSockets, wasmtime::Result<()>> for ReceiveTask {
+    async fn run(mut self, store: &Accessor<T, WasiSockets>) -> wasmtime::Result<()> {
+        let mut buf = BytesMut::with_capacity(8192);
+        let result = loop {
+            if self.data.is_closed() {
+                // The guest is no longer interested in any data.
+                native_shutdown(&self.stream, Shutdown::Read);
+                break Ok(());
+            }
+            if let Err(err) = self.stream.readable().await {
+                break Err(err.into());
+            }
+            match self.stream.try_read_buf(&mut buf) {
+                Ok(0) => break Ok(()),
+                Ok(_) => {
+                    buf = self
+                        .data
+                        .write_all(store, Cursor::new(buf))
+                        .await
+                        .into_inner();
+                    buf.clear();
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) => break Err(err.into()),
+            }
+        };
+        drop(self.data);
+        self.result.write(store, result).await;
+        Ok(())
+    }
+}
+
+impl HostTcpSocketConcurrent for WasiSockets {
+    async fn bind<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<TcpSocket>,
+        local_address: IpSocketAddress,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let local_address = SocketAddr::from(local_address);
+        let check = store.with(|mut view| -> wasmtime::Result<_> {
+            let view = view.get();
+            let check = view.ctx.socket_addr_check(SocketAddrUse::TcpBind);
+            let socket = get_socket(view.table, &socket)?;
+            Ok(check.and_then(|check| {
+                socket.validate_bind(&local_address)?;
+                Ok(check)
+            }))
+        })?;
+        let check = match check {
+            Ok(check) => check,
+            Err(err) => return Ok(Err(err)),
+        };
+        if let Err(err) = check_socket_addr(check, local_address, SocketAddrUse::TcpBind).await {
+            return Ok(Err(err));
+        }
+        store.with(|mut view| {
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok(socket.bind(local_address))
+        })
+    }
+
+    async fn connect<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<TcpSocket>,
+        remote_address: IpSocketAddress,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let remote_address = SocketAddr::from(remote_address);
+        let check = store.with(|mut view| -> wasmtime::Result<_> {
+            let view = view.get();
+            let check = view.ctx.socket_addr_check(SocketAddrUse::TcpConnect);
+            let socket = get_socket(view.table, &socket)?;
+            Ok(check.and_then(|check| {
+                socket.validate_connect(&remote_address)?;
+                Ok(check)
+            }))
+        })?;
+        let check = match check {
+            Ok(check) => check,
+            Err(err) => return Ok(Err(err)),
+        };
+        if let Err(err) = check_socket_addr(check, remote_address, SocketAddrUse::TcpConnect).await
+        {
+            return Ok(Err(err));
+        }
+        let tokio_socket = store.with(|mut view| -> wasmtime::Result<_> {
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok(socket.start_connect(&remote_address))
+        })?;
+        let tokio_socket = match tokio_socket {
+            Ok(tokio_socket) => tokio_socket,
+            Err(err) => return Ok(Err(err)),
+        };
+        let result = tokio_socket.connect(remote_address).await;
+        store.with(|mut view| {
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok(socket.finish_connect(result))
+        })
+    }
+
+    async fn listen<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<HostStream<Resource<TcpSocket>>, ErrorCode>> {
+        // Listening on a socket which isn't bound yet implicitly binds it to
+        // an ephemeral port, which is subject to the same checks as an
+        // explicit `bind`.
+        let implicit_bind = store.with(|mut view| -> wasmtime::Result<_> {
+            let view = view.get();
+            let check = view.ctx.socket_addr_check(SocketAddrUse::TcpBind);
+            let socket = get_socket(view.table, &socket)?;
+            if !socket.is_unbound() {
+                return Ok(check.map(|_| None));
+            }
+            let addr = unspecified_address(socket.address_family());
+            Ok(check.map(|check| Some((check, addr))))
+        })?;
+        let implicit_bind = match implicit_bind {
+            Ok(Some((check, addr))) => {
+                if let Err(err) = check_socket_addr(check, addr, SocketAddrUse::TcpBind).await {
+                    return Ok(Err(err));
+                }
+                Some(addr)
+            }
+            Ok(None) => None,
+            Err(err) => return Ok(Err(err)),
+        };
+        store.with(|mut view| {
+            let instance = view.instance();
+            let socket = get_socket(view.get().table, &socket)?;
+            if let Some(addr) = implicit_bind {
+                if let Err(err) = socket.bind(addr) {
+                    return Ok(Err(err));
+                }
+            }
+            let listener = match socket.listen() {
+                Ok(listener) => listener,
+                Err(err) => return Ok(Err(err)),
+            };
+            let family = socket.address_family();
+            let options = socket.inherited_options();
+            let (tx, rx) = instance
+                .stream::<_, _, Option<_>>(&mut view)
+                .context("failed to create stream")?;
+            view.spawn(ListenTask {
+                listener,
+                family,
+                options,
+                tx,
+            });
+            Ok(Ok(rx.into()))
+        })
+    }
+
+    async fn send<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<TcpSocket>,
+        data: HostStream<u8>,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let (mut data, stream) = store.with(|mut view| -> wasmtime::Result<_> {
+            let data = data.into_reader::<BytesMut>(&mut view);
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok((data, socket.start_send()))
+        })?;
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => return Ok(Err(err)),
+        };
+        let mut buf = BytesMut::with_capacity(8192);
+        while !data.is_closed() {
+            buf = data.read(store, buf).await;
+            if let Err(err) = write_all(&stream, &buf).await {
+                return Ok(Err(err.into()));
+            }
+            buf.clear();
+        }
+        native_shutdown(&stream, Shutdown::Write);
+        Ok(Ok(()))
+    }
+
+    async fn receive<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<(HostStream<u8>, HostFuture<Result<(), ErrorCode>>)> {
+        store.with(|mut view| {
+            let instance = view.instance();
+            let (data_tx, data_rx) = instance
+                .stream::<_, _, BytesMut>(&mut view)
+                .context("failed to create stream")?;
+            let socket = get_socket(view.get().table, &socket)?;
+            match socket.start_receive() {
+                Ok(stream) => {
+                    let (result_tx, result_rx) = instance
+                        .future(|| Err(ErrorCode::Unknown), &mut view)
+                        .context("failed to create future")?;
+                    view.spawn(ReceiveTask {
+                        stream,
+                        data: data_tx,
+                        result: result_tx,
+                    });
+                    Ok((data_rx.into(), result_rx.into()))
+                }
+                Err(err) => {
+                    debug_assert_eq!(err, ErrorCode::InvalidState);
+                    // The stream is closed right away and the future resolves
+                    // to the error once its write end is dropped.
+                    drop(data_tx);
+                    let (_, result_rx) = instance
+                        .future(|| Err(ErrorCode::InvalidState), &mut view)
+                        .context("failed to create future")?;
+                    Ok((data_rx.into(), result_rx.into()))
+                }
+            }
+        })
+    }
+}
+
+impl HostTcpSocket for WasiSocketsCtxView<'_> {
+    fn new(&mut self, address_family: IpAddressFamily) -> wasmtime::Result<Resource<TcpSocket>> {
+        let socket = TcpSocket::new(address_family.into()).context("failed to create socket")?;
+        self.table
+            .push(socket)
+            .context("failed to push TCP socket resource to table")
+    }
+
+    fn local_address(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<IpSocketAddress, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.local_address().map(Into::into))
+    }
+
+    fn remote_address(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<IpSocketAddress, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.remote_address().map(Into::into))
+    }
+
+    fn is_listening(&mut self, socket: Resource<TcpSocket>) -> wasmtime::Result<bool> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.is_listening())
+    }
+
+    fn address_family(&mut self, socket: Resource<TcpSocket>) -> wasmtime::Result<IpAddressFamily> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.address_family().into())
+    }
+
+    fn set_listen_backlog_size(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: u64,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        // Silently clamp backlog size. This is OK for us to do, because operating systems do this too.
+        let value = value.try_into().unwrap_or(u32::MAX);
+        Ok(socket.set_listen_backlog_size(value))
+    }
+
+    fn keep_alive_enabled(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<bool, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.keep_alive_enabled())
+    }
+
+    fn set_keep_alive_enabled(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: bool,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.set_keep_alive_enabled(value))
+    }
+
+    fn keep_alive_idle_time(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<Duration, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.keep_alive_idle_time().map(duration_to_nanos))
+    }
+
+    fn set_keep_alive_idle_time(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: Duration,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.set_keep_alive_idle_time(std::time::Duration::from_nanos(value)))
+    }
+
+    fn keep_alive_interval(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<Duration, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.keep_alive_interval().map(duration_to_nanos))
+    }
+
+    fn set_keep_alive_interval(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: Duration,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.set_keep_alive_interval(std::time::Duration::from_nanos(value)))
+    }
+
+    fn keep_alive_count(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<u32, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.keep_alive_count())
+    }
+
+    fn set_keep_alive_count(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: u32,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.set_keep_alive_count(value))
+    }
+
+    fn hop_limit(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<u8, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.hop_limit())
+    }
+
+    fn set_hop_limit(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: u8,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.set_hop_limit(value))
+    }
+
+    fn receive_buffer_size(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<u64, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.receive_buffer_size().map(|size| size as u64))
+    }
+
+    fn set_receive_buffer_size(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: u64,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        let value = value.try_into().unwrap_or(usize::MAX);
+        Ok(socket.set_receive_buffer_size(value))
+    }
+
+    fn send_buffer_size(
+        &mut self,
+        socket: Resource<TcpSocket>,
+    ) -> wasmtime::Result<Result<u64, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.send_buffer_size().map(|size| size as u64))
+    }
+
+    fn set_send_buffer_size(
+        &mut self,
+        socket: Resource<TcpSocket>,
+        value: u64,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        let value = value.try_into().unwrap_or(usize::MAX);
+        Ok(socket.set_send_buffer_size(value))
+    }
+
+    fn drop(&mut self, rep: Resource<TcpSocket>) -> wasmtime::Result<()> {
+        self.table
+            .delete(rep)
+            .context("failed to delete TCP socket resource from table")?;
+        Ok(())
+    }
+}
+
+fn duration_to_nanos(duration: std::time::Duration) -> Duration {
+    duration.as_nanos().try_into().unwrap_or(Duration::MAX)
+}
@@ -0,0 +1,255 @@
+use super::check_socket_addr;
+use crate::net::SocketAddrUse;
+use crate::p3::bindings::sockets::types::{
+    ErrorCode, HostUdpSocket, HostUdpSocketConcurrent, IpAddressFamily, IpSocketAddress,
+};
+use crate::p3::sockets::udp::MAX_UDP_DATAGRAM_SIZE;
+use crate::p3::sockets::{UdpSocket, WasiSockets, WasiSocketsCtxView};
+use anyhow::Context as _;
+use std::net::SocketAddr;
+use wasmtime::component::{Accessor, Resource, ResourceTable};
+
+fn get_socket<'a>(
+    table: &'a mut ResourceTable,
+    socket: &Resource<UdpSocket>,
+) -> wasmtime::Result<&'a mut UdpSocket> {
+    table
+        .get_mut(socket)
+        .context("failed to get UDP socket resource from table")
+}
+
+impl HostUdpSocketConcurrent for WasiSockets {
+    async fn bind<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<UdpSocket>,
+        local_address: IpSocketAddress,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let local_address = SocketAddr::from(local_address);
+        let check = store.with(|mut view| -> wasmtime::Result<_> {
+            let view = view.get();
+            let check = view.ctx.socket_addr_check(SocketAddrUse::UdpBind);
+            let socket = get_socket(view.table, &socket)?;
+            Ok(check.and_then(|check| {
+                socket.validate_bind(&local_address)?;
+                Ok(check)
+            }))
+        })?;
+        let check = match check {
+            Ok(check) => check,
+            Err(err) => return Ok(Err(err)),
+        };
+        if let Err(err) = check_socket_addr(check, local_address, SocketAddrUse::UdpBind).await {
+            return Ok(Err(err));
+        }
+        store.with(|mut view| {
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok(socket.bind(local_address))
+        })
+    }
+
+    async fn connect<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<UdpSocket>,
+        remote_address: IpSocketAddress,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let remote_address = SocketAddr::from(remote_address);
+        let check = store.with(|mut view| -> wasmtime::Result<_> {
+            let view = view.get();
+            let check = view.ctx.socket_addr_check(SocketAddrUse::UdpConnect);
+            let socket = get_socket(view.table, &socket)?;
+            Ok(check.and_then(|check| {
+                socket.validate_connect(&remote_address)?;
+                Ok(check)
+            }))
+        })?;
+        let check = match check {
+            Ok(check) => check,
+            Err(err) => return Ok(Err(err)),
+        };
+        if let Err(err) = check_socket_addr(check, remote_address, SocketAddrUse::UdpConnect).await
+        {
+            return Ok(Err(err));
+        }
+        store.with(|mut view| {
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok(socket.connect(remote_address))
+        })
+    }
+
+    async fn send<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<UdpSocket>,
+        data: Vec<u8>,
+        remote_address: Option<IpSocketAddress>,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        if data.len() > MAX_UDP_DATAGRAM_SIZE {
+            return Ok(Err(ErrorCode::DatagramTooLarge));
+        }
+        let remote_address = remote_address.map(SocketAddr::from);
+        let destination = store.with(|mut view| -> wasmtime::Result<_> {
+            let view = view.get();
+            let socket = get_socket(view.table, &socket)?;
+            let inner = socket.socket().clone();
+            let destination = match socket.send_destination(remote_address) {
+                Ok(Some(addr)) => view
+                    .ctx
+                    .socket_addr_check(SocketAddrUse::UdpOutgoingDatagram)
+                    .map(|check| Some((check, addr))),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
+            };
+            Ok(destination.map(|destination| (inner, destination)))
+        })?;
+        let (inner, destination) = match destination {
+            Ok(destination) => destination,
+            Err(err) => return Ok(Err(err)),
+        };
+        let result = match destination {
+            Some((check, addr)) => {
+                if let Err(err) =
+                    check_socket_addr(check, addr, SocketAddrUse::UdpOutgoingDatagram).await
+                {
+                    return Ok(Err(err));
+                }
+                inner.send_to(&data, addr).await
+            }
+            None => inner.send(&data).await,
+        };
+        if let Err(err) = result {
+            return Ok(Err(err.into()));
+        }
+        store.with(|mut view| {
+            let socket = get_socket(view.get().table, &socket)?;
+            socket.finish_send();
+            Ok(Ok(()))
+        })
+    }
+
+    async fn receive<T: 'static>(
+        store: &Accessor<T, Self>,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<(Vec<u8>, IpSocketAddress), ErrorCode>> {
+        let filter = store.with(|mut view| -> wasmtime::Result<_> {
+            let socket = get_socket(view.get().table, &socket)?;
+            Ok(socket
+                .receive_filter()
+                .map(|filter| (socket.socket().clone(), filter)))
+        })?;
+        let (inner, filter) = match filter {
+            Ok(filter) => filter,
+            Err(err) => return Ok(Err(err)),
+        };
+        let mut buf = vec![0; MAX_UDP_DATAGRAM_SIZE];
+        loop {
+            let (n, addr) = match inner.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(err) => return Ok(Err(err.into())),
+            };
+            // A connected socket may still have datagrams from other peers
+            // queued up from before it was connected.
+            if filter.is_some_and(|filter| filter != addr) {
+                continue;
+            }
+            buf.truncate(n);
+            return Ok(Ok((buf, addr.into())));
+        }
+    }
+}
+
+impl HostUdpSocket for WasiSocketsCtxView<'_> {
+    fn new(&mut self, address_family: IpAddressFamily) -> wasmtime::Result<Resource<UdpSocket>> {
+        let socket = UdpSocket::new(address_family.into()).context("failed to create socket")?;
+        self.table
+            .push(socket)
+            .context("failed to push UDP socket resource to table")
+    }
+
+    fn disconnect(
+        &mut self,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.disconnect())
+    }
+
+    fn local_address(
+        &mut self,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<IpSocketAddress, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.local_address().map(Into::into))
+    }
+
+    fn remote_address(
+        &mut self,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<IpSocketAddress, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.remote_address().map(Into::into))
+    }
+
+    fn address_family(&mut self, socket: Resource<UdpSocket>) -> wasmtime::Result<IpAddressFamily> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.address_family().into())
+    }
+
+    fn unicast_hop_limit(
+        &mut self,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<u8, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.unicast_hop_limit())
+    }
+
+    fn set_unicast_hop_limit(
+        &mut self,
+        socket: Resource<UdpSocket>,
+        value: u8,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.set_unicast_hop_limit(value))
+    }
+
+    fn receive_buffer_size(
+        &mut self,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<u64, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.receive_buffer_size().map(|size| size as u64))
+    }
+
+    fn set_receive_buffer_size(
+        &mut self,
+        socket: Resource<UdpSocket>,
+        value: u64,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        let value = value.try_into().unwrap_or(usize::MAX);
+        Ok(socket.set_receive_buffer_size(value))
+    }
+
+    fn send_buffer_size(
+        &mut self,
+        socket: Resource<UdpSocket>,
+    ) -> wasmtime::Result<Result<u64, ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        Ok(socket.send_buffer_size().map(|size| size as u64))
+    }
+
+    fn set_send_buffer_size(
+        &mut self,
+        socket: Resource<UdpSocket>,
+        value: u64,
+    ) -> wasmtime::Result<Result<(), ErrorCode>> {
+        let socket = get_socket(self.table, &socket)?;
+        let value = value.try_into().unwrap_or(usize::MAX);
+        Ok(socket.set_send_buffer_size(value))
+    }
+
+    fn drop(&mut self, rep: Resource<UdpSocket>) -> wasmtime::Result<()> {
+        self.table
+            .delete(rep)
+            .context("failed to delete UDP socket resource from table")?;
+        Ok(())
+    }
+}
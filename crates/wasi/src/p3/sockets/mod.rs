@@ -0,0 +1,122 @@
+mod conv;
+mod host;
+pub(crate) mod tcp;
+pub(crate) mod udp;
+
+use crate::ctx::AllowedNetworkUses;
+use crate::net::SocketAddrCheck;
+use crate::p3::bindings::sockets;
+use wasmtime::component::{HasData, Linker, ResourceTable};
+
+pub use self::tcp::TcpSocket;
+pub use self::udp::UdpSocket;
+
+/// Per-[`Store`] state for the `wasi:sockets` interfaces.
+///
+/// This is created through [`WasiCtxBuilder`](crate::p3::WasiCtxBuilder),
+/// which carries over the configured
+/// [`socket_addr_check`](crate::p3::WasiCtxBuilder::socket_addr_check) and the
+/// `allow_tcp`/`allow_udp`/`allow_ip_name_lookup` settings. The default value
+/// denies all addresses and all name lookups.
+///
+/// [`Store`]: wasmtime::Store
+#[derive(Default)]
+pub struct WasiSocketsCtx {
+    pub(crate) socket_addr_check: SocketAddrCheck,
+    pub(crate) allowed_network_uses: AllowedNetworkUses,
+}
+
+pub struct WasiSocketsCtxView<'a> {
+    pub ctx: &'a mut WasiSocketsCtx,
+    pub table: &'a mut ResourceTable,
+}
+
+impl<T: WasiSocketsView> WasiSocketsView for &mut T {
+    fn sockets(&mut self) -> WasiSocketsCtxView<'_> {
+        T::sockets(self)
+    }
+}
+
+impl<T: WasiSocketsView> WasiSocketsView for Box<T> {
+    fn sockets(&mut self) -> WasiSocketsCtxView<'_> {
+        T::sockets(self)
+    }
+}
+
+pub trait WasiSocketsView: Send {
+    fn sockets(&mut self) -> WasiSocketsCtxView<'_>;
+}
+
+/// Add all WASI interfaces from this module into the `linker` provided.
+///
+/// This function will add all interfaces implemented by this module to the
+/// [`Linker`], which corresponds to the `wasi:sockets/imports` world supported by
+/// this crate.
+///
+/// This is low-level API for advanced use cases,
+/// [`wasmtime_wasi::p3::add_to_linker`](crate::p3::add_to_linker) can be used instead
+/// to add *all* wasip3 interfaces (including the ones from this module) to the `linker`.
+///
+/// # Example
+///
+/// ```
+/// use wasmtime::{Engine, Result, Store, Config};
+/// use wasmtime::component::{Linker, ResourceTable};
+/// use wasmtime_wasi::p3::sockets::{WasiSocketsCtx, WasiSocketsCtxView, WasiSocketsView};
+///
+/// fn main() -> Result<()> {
+///     let mut config = Config::new();
+///     config.async_support(true);
+///     config.wasm_component_model_async(true);
+///     let engine = Engine::new(&config)?;
+///
+///     let mut linker = Linker::<MyState>::new(&engine);
+///     wasmtime_wasi::p3::sockets::add_to_linker(&mut linker)?;
+///     // ... add any further functionality to `linker` if desired ...
+///
+///     let mut store = Store::new(
+///         &engine,
+///         MyState::default(),
+///     );
+///
+///     // ... use `linker` to instantiate within `store` ...
+///
+///     Ok(())
+/// }
+///
+/// #[derive(Default)]
+/// struct MyState {
+///     sockets: WasiSocketsCtx,
+///     table: ResourceTable,
+/// }
+///
+/// impl WasiSocketsView for MyState {
+///     fn sockets(&mut self) -> WasiSocketsCtxView<'_> {
+///         WasiSocketsCtxView {
+///             ctx: &mut self.sockets,
+///             table: &mut self.table,
+///         }
+///     }
+/// }
+/// ```
+pub fn add_to_linker<T>(linker: &mut Linker<T>) -> wasmtime::Result<()>
+where
+    T: WasiSocketsView + 'static,
+{
+    add_to_linker_impl(linker, T::sockets)
+}
+
+pub(crate) fn add_to_linker_impl<T: Send>(
+    linker: &mut Linker<T>,
+    host_getter: fn(&mut T) -> WasiSocketsCtxView<'_>,
+) -> wasmtime::Result<()> {
+    sockets::ip_name_lookup::add_to_linker::<_, WasiSockets>(linker, host_getter)?;
+    sockets::types::add_to_linker::<_, WasiSockets>(linker, host_getter)?;
+    Ok(())
+}
+
+struct WasiSockets;
+
+impl HasData for WasiSockets {
+    type Data<'a> = WasiSocketsCtxView<'a>;
+}
@@ -0,0 +1,528 @@
+use crate::net::{DEFAULT_TCP_BACKLOG, SocketAddressFamily};
+use crate::p2::host::network::util;
+use crate::p3::bindings::sockets::types::ErrorCode;
+use crate::runtime::with_ambient_tokio_runtime;
+use cap_net_ext::AddressFamily;
+use io_lifetimes::AsSocketlike;
+use io_lifetimes::views::SocketlikeView;
+use rustix::io::Errno;
+use rustix::net::sockopt;
+use std::io;
+use std::mem;
+use std::net::{Shutdown, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The state of a TCP socket.
+///
+/// This represents the various states a socket can be in during the
+/// activities of binding, listening, accepting, and connecting. Unlike the
+/// wasip2 implementation there are no intermediate "started" states: the
+/// asynchronous parts of these operations are awaited by the host functions
+/// themselves.
+enum TcpState {
+    /// The initial state for a newly-created socket.
+    Default(tokio::net::TcpSocket),
+
+    /// The socket has an address but is not yet listening for connections.
+    Bound(tokio::net::TcpSocket),
+
+    /// The socket is now listening. Incoming connections are accepted by the
+    /// task backing the stream returned from `listen`.
+    Listening(Arc<tokio::net::TcpListener>),
+
+    /// An outgoing connection is in progress.
+    Connecting,
+
+    /// An outgoing connection has been established or this socket was
+    /// accepted from a listener.
+    Connected {
+        stream: Arc<tokio::net::TcpStream>,
+        sending: bool,
+        receiving: bool,
+    },
+
+    Closed,
+}
+
+/// Socket options which are not automatically inherited from the listener on
+/// all platforms. We keep track of which options have been explicitly set and
+/// manually apply those values to newly accepted clients.
+#[cfg(target_os = "macos")]
+#[derive(Clone, Default)]
+pub(crate) struct InheritedOptions {
+    receive_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    hop_limit: Option<u8>,
+    keep_alive_idle_time: Option<Duration>,
+}
+
+#[cfg(target_os = "macos")]
+impl InheritedOptions {
+    pub(crate) fn apply(&self, family: SocketAddressFamily, client: &tokio::net::TcpStream) {
+        // Potential errors are ignored, as with the wasip2 implementation.
+        if let Some(size) = self.receive_buffer_size {
+            _ = util::set_socket_recv_buffer_size(client, size);
+        }
+        if let Some(size) = self.send_buffer_size {
+            _ = util::set_socket_send_buffer_size(client, size);
+        }
+        // For some reason, IP_TTL is inherited, but IPV6_UNICAST_HOPS isn't.
+        if let (SocketAddressFamily::Ipv6, Some(ttl)) = (family, self.hop_limit) {
+            _ = util::set_ipv6_unicast_hops(client, ttl);
+        }
+        if let Some(value) = self.keep_alive_idle_time {
+            _ = util::set_tcp_keepidle(client, value);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+#[derive(Clone, Default)]
+pub(crate) struct InheritedOptions;
+
+#[cfg(not(target_os = "macos"))]
+impl InheritedOptions {
+    pub(crate) fn apply(&self, _family: SocketAddressFamily, _client: &tokio::net::TcpStream) {}
+}
+
+/// A host TCP socket, plus associated bookkeeping.
+pub struct TcpSocket {
+    /// The current state in the bind/listen/accept/connect progression.
+    tcp_state: TcpState,
+
+    /// The desired listen queue size.
+    listen_backlog_size: u32,
+
+    family: SocketAddressFamily,
+
+    options: InheritedOptions,
+}
+
+impl TcpSocket {
+    /// Create a new socket in the given family.
+    pub(crate) fn new(family: AddressFamily) -> io::Result<Self> {
+        with_ambient_tokio_runtime(|| {
+            let (socket, family) = match family {
+                AddressFamily::Ipv4 => {
+                    let socket = tokio::net::TcpSocket::new_v4()?;
+                    (socket, SocketAddressFamily::Ipv4)
+                }
+                AddressFamily::Ipv6 => {
+                    let socket = tokio::net::TcpSocket::new_v6()?;
+                    sockopt::set_ipv6_v6only(&socket, true)?;
+                    (socket, SocketAddressFamily::Ipv6)
+                }
+            };
+
+            Ok(Self::from_state(TcpState::Default(socket), family))
+        })
+    }
+
+    /// Create a socket for a connection accepted from a listener.
+    pub(crate) fn accepted(
+        stream: tokio::net::TcpStream,
+        family: SocketAddressFamily,
+        options: &InheritedOptions,
+    ) -> Self {
+        options.apply(family, &stream);
+        Self::from_state(
+            TcpState::Connected {
+                stream: Arc::new(stream),
+                sending: false,
+                receiving: false,
+            },
+            family,
+        )
+    }
+
+    fn from_state(state: TcpState, family: SocketAddressFamily) -> Self {
+        Self {
+            tcp_state: state,
+            listen_backlog_size: DEFAULT_TCP_BACKLOG,
+            family,
+            options: InheritedOptions::default(),
+        }
+    }
+
+    fn as_std_view(&self) -> Result<SocketlikeView<'_, std::net::TcpStream>, ErrorCode> {
+        match &self.tcp_state {
+            TcpState::Default(socket) | TcpState::Bound(socket) => {
+                Ok(socket.as_socketlike_view::<std::net::TcpStream>())
+            }
+            TcpState::Connected { stream, .. } => {
+                Ok(stream.as_socketlike_view::<std::net::TcpStream>())
+            }
+            TcpState::Listening(listener) => {
+                Ok(listener.as_socketlike_view::<std::net::TcpStream>())
+            }
+            TcpState::Connecting | TcpState::Closed => Err(ErrorCode::InvalidState),
+        }
+    }
+
+    /// Returns whether this socket has not yet been bound, either explicitly
+    /// or implicitly.
+    pub(crate) fn is_unbound(&self) -> bool {
+        matches!(self.tcp_state, TcpState::Default(..))
+    }
+
+    /// Performs the validation of `bind` which can be done before the
+    /// embedder's socket address check runs.
+    pub(crate) fn validate_bind(&self, local_address: &SocketAddr) -> Result<(), ErrorCode> {
+        if !self.is_unbound() {
+            return Err(ErrorCode::InvalidState);
+        }
+        util::validate_unicast(local_address)?;
+        util::validate_address_family(local_address, &self.family)?;
+        Ok(())
+    }
+
+    pub(crate) fn bind(&mut self, local_address: SocketAddr) -> Result<(), ErrorCode> {
+        self.validate_bind(&local_address)?;
+        let TcpState::Default(tokio_socket) = &self.tcp_state else {
+            unreachable!();
+        };
+
+        // Automatically bypass the TIME_WAIT state when the user is trying
+        // to bind to a specific port:
+        let reuse_addr = local_address.port() > 0;
+
+        // Unconditionally (re)set SO_REUSEADDR, even when the value is false.
+        // This ensures we're not accidentally affected by any socket option
+        // state left behind by a previous failed call to this method.
+        util::set_tcp_reuseaddr(tokio_socket, reuse_addr)?;
+
+        // Perform the OS bind call.
+        tokio_socket
+            .bind(local_address)
+            .map_err(|error| match Errno::from_io_error(&error) {
+                // From https://pubs.opengroup.org/onlinepubs/9699919799/functions/bind.html:
+                // > [EAFNOSUPPORT] The specified address is not a valid address for the address family of the specified socket
+                //
+                // The most common reasons for this error should have already
+                // been handled by our own validation slightly higher up in this
+                // function. This error mapping is here just in case there is
+                // an edge case we didn't catch.
+                Some(Errno::AFNOSUPPORT) => ErrorCode::InvalidArgument,
+
+                // See: https://learn.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-bind#:~:text=WSAENOBUFS
+                // Windows returns WSAENOBUFS when the ephemeral ports have been exhausted.
+                #[cfg(windows)]
+                Some(Errno::NOBUFS) => ErrorCode::AddressInUse,
+
+                _ => ErrorCode::from(error),
+            })?;
+
+        self.tcp_state = match mem::replace(&mut self.tcp_state, TcpState::Closed) {
+            TcpState::Default(socket) => TcpState::Bound(socket),
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    /// Performs the validation of `connect` which can be done before the
+    /// embedder's socket address check runs.
+    pub(crate) fn validate_connect(&self, remote_address: &SocketAddr) -> Result<(), ErrorCode> {
+        match self.tcp_state {
+            TcpState::Default(..) | TcpState::Bound(..) => {}
+            _ => return Err(ErrorCode::InvalidState),
+        }
+
+        util::validate_unicast(remote_address)?;
+        util::validate_remote_address(remote_address)?;
+        util::validate_address_family(remote_address, &self.family)?;
+        Ok(())
+    }
+
+    /// Validates `remote_address` and transitions the socket into the
+    /// `connecting` state, returning the underlying socket to connect with.
+    pub(crate) fn start_connect(
+        &mut self,
+        remote_address: &SocketAddr,
+    ) -> Result<tokio::net::TcpSocket, ErrorCode> {
+        self.validate_connect(remote_address)?;
+
+        let (TcpState::Default(socket) | TcpState::Bound(socket)) =
+            mem::replace(&mut self.tcp_state, TcpState::Connecting)
+        else {
+            unreachable!();
+        };
+        Ok(socket)
+    }
+
+    /// Records the outcome of a connection attempt started with
+    /// [`TcpSocket::start_connect`].
+    pub(crate) fn finish_connect(
+        &mut self,
+        result: io::Result<tokio::net::TcpStream>,
+    ) -> Result<(), ErrorCode> {
+        debug_assert!(matches!(self.tcp_state, TcpState::Connecting));
+        match result {
+            Ok(stream) => {
+                self.tcp_state = TcpState::Connected {
+                    stream: Arc::new(stream),
+                    sending: false,
+                    receiving: false,
+                };
+                Ok(())
+            }
+            Err(err) => {
+                self.tcp_state = TcpState::Closed;
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Transitions a bound socket into the `listening` state, returning the
+    /// listener that incoming connections are accepted from.
+    pub(crate) fn listen(&mut self) -> Result<Arc<tokio::net::TcpListener>, ErrorCode> {
+        let tokio_socket = match mem::replace(&mut self.tcp_state, TcpState::Closed) {
+            TcpState::Bound(tokio_socket) => tokio_socket,
+            previous_state => {
+                self.tcp_state = previous_state;
+                return Err(ErrorCode::InvalidState);
+            }
+        };
+
+        match with_ambient_tokio_runtime(|| tokio_socket.listen(self.listen_backlog_size)) {
+            Ok(listener) => {
+                let listener = Arc::new(listener);
+                self.tcp_state = TcpState::Listening(listener.clone());
+                Ok(listener)
+            }
+            Err(err) => {
+                self.tcp_state = TcpState::Closed;
+
+                Err(match Errno::from_io_error(&err) {
+                    // See: https://learn.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-listen#:~:text=WSAEMFILE
+                    // According to the docs, `listen` can return EMFILE on Windows.
+                    // This is odd, because we're not trying to create a new socket
+                    // or file descriptor of any kind. So we rewrite it to less
+                    // surprising error code.
+                    #[cfg(windows)]
+                    Some(Errno::MFILE) => ErrorCode::OutOfMemory,
+
+                    _ => err.into(),
+                })
+            }
+        }
+    }
+
+    /// Options to apply to sockets accepted from this listener.
+    pub(crate) fn inherited_options(&self) -> InheritedOptions {
+        self.options.clone()
+    }
+
+    /// Marks the sending half of this connection as in use, returning the
+    /// stream to write to.
+    pub(crate) fn start_send(&mut self) -> Result<Arc<tokio::net::TcpStream>, ErrorCode> {
+        match &mut self.tcp_state {
+            TcpState::Connected {
+                stream,
+                sending: sending @ false,
+                ..
+            } => {
+                *sending = true;
+                Ok(stream.clone())
+            }
+            _ => Err(ErrorCode::InvalidState),
+        }
+    }
+
+    /// Marks the receiving half of this connection as in use, returning the
+    /// stream to read from.
+    pub(crate) fn start_receive(&mut self) -> Result<Arc<tokio::net::TcpStream>, ErrorCode> {
+        match &mut self.tcp_state {
+            TcpState::Connected {
+                stream,
+                receiving: receiving @ false,
+                ..
+            } => {
+                *receiving = true;
+                Ok(stream.clone())
+            }
+            _ => Err(ErrorCode::InvalidState),
+        }
+    }
+
+    pub(crate) fn local_address(&self) -> Result<SocketAddr, ErrorCode> {
+        let view = match self.tcp_state {
+            TcpState::Default(..) => return Err(ErrorCode::InvalidState),
+            _ => self.as_std_view()?,
+        };
+
+        Ok(view.local_addr()?)
+    }
+
+    pub(crate) fn remote_address(&self) -> Result<SocketAddr, ErrorCode> {
+        let view = match self.tcp_state {
+            TcpState::Connected { .. } => self.as_std_view()?,
+            _ => return Err(ErrorCode::InvalidState),
+        };
+
+        Ok(view.peer_addr()?)
+    }
+
+    pub(crate) fn is_listening(&self) -> bool {
+        matches!(self.tcp_state, TcpState::Listening(..))
+    }
+
+    pub(crate) fn address_family(&self) -> SocketAddressFamily {
+        self.family
+    }
+
+    pub(crate) fn set_listen_backlog_size(&mut self, value: u32) -> Result<(), ErrorCode> {
+        const MIN_BACKLOG: u32 = 1;
+        const MAX_BACKLOG: u32 = i32::MAX as u32; // OS'es will most likely limit it down even further.
+
+        if value == 0 {
+            return Err(ErrorCode::InvalidArgument);
+        }
+
+        // Silently clamp backlog size. This is OK for us to do, because operating systems do this too.
+        let value = value.clamp(MIN_BACKLOG, MAX_BACKLOG);
+
+        match &self.tcp_state {
+            TcpState::Default(..) | TcpState::Bound(..) => {
+                // Socket not listening yet. Stash value for first invocation to `listen`.
+            }
+            TcpState::Listening(listener) => {
+                // Try to update the backlog by calling `listen` again.
+                // Not all platforms support this. We'll only update our own value if the OS supports changing the backlog size after the fact.
+                rustix::net::listen(&**listener, value.try_into().unwrap())
+                    .map_err(|_| ErrorCode::NotSupported)?;
+            }
+            _ => return Err(ErrorCode::InvalidState),
+        }
+        self.listen_backlog_size = value;
+
+        Ok(())
+    }
+
+    pub(crate) fn keep_alive_enabled(&self) -> Result<bool, ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(sockopt::socket_keepalive(view)?)
+    }
+
+    pub(crate) fn set_keep_alive_enabled(&self, value: bool) -> Result<(), ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(sockopt::set_socket_keepalive(view, value)?)
+    }
+
+    pub(crate) fn keep_alive_idle_time(&self) -> Result<Duration, ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(sockopt::tcp_keepidle(view)?)
+    }
+
+    pub(crate) fn set_keep_alive_idle_time(&mut self, duration: Duration) -> Result<(), ErrorCode> {
+        {
+            let view = &*self.as_std_view()?;
+            util::set_tcp_keepidle(view, duration)?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            self.options.keep_alive_idle_time = Some(duration);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn keep_alive_interval(&self) -> Result<Duration, ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(sockopt::tcp_keepintvl(view)?)
+    }
+
+    pub(crate) fn set_keep_alive_interval(&self, duration: Duration) -> Result<(), ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(util::set_tcp_keepintvl(view, duration)?)
+    }
+
+    pub(crate) fn keep_alive_count(&self) -> Result<u32, ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(sockopt::tcp_keepcnt(view)?)
+    }
+
+    pub(crate) fn set_keep_alive_count(&self, value: u32) -> Result<(), ErrorCode> {
+        let view = &*self.as_std_view()?;
+        Ok(util::set_tcp_keepcnt(view, value)?)
+    }
+
+    pub(crate) fn hop_limit(&self) -> Result<u8, ErrorCode> {
+        let view = &*self.as_std_view()?;
+
+        let ttl = match self.family {
+            SocketAddressFamily::Ipv4 => util::get_ip_ttl(view)?,
+            SocketAddressFamily::Ipv6 => util::get_ipv6_unicast_hops(view)?,
+        };
+
+        Ok(ttl)
+    }
+
+    pub(crate) fn set_hop_limit(&mut self, value: u8) -> Result<(), ErrorCode> {
+        {
+            let view = &*self.as_std_view()?;
+
+            match self.family {
+                SocketAddressFamily::Ipv4 => util::set_ip_ttl(view, value)?,
+                SocketAddressFamily::Ipv6 => util::set_ipv6_unicast_hops(view, value)?,
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            self.options.hop_limit = Some(value);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn receive_buffer_size(&self) -> Result<usize, ErrorCode> {
+        let view = &*self.as_std_view()?;
+
+        Ok(util::get_socket_recv_buffer_size(view)?)
+    }
+
+    pub(crate) fn set_receive_buffer_size(&mut self, value: usize) -> Result<(), ErrorCode> {
+        {
+            let view = &*self.as_std_view()?;
+
+            util::set_socket_recv_buffer_size(view, value)?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            self.options.receive_buffer_size = Some(value);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn send_buffer_size(&self) -> Result<usize, ErrorCode> {
+        let view = &*self.as_std_view()?;
+
+        Ok(util::get_socket_send_buffer_size(view)?)
+    }
+
+    pub(crate) fn set_send_buffer_size(&mut self, value: usize) -> Result<(), ErrorCode> {
+        {
+            let view = &*self.as_std_view()?;
+
+            util::set_socket_send_buffer_size(view, value)?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            self.options.send_buffer_size = Some(value);
+        }
+
+        Ok(())
+    }
+}
+
+/// Shuts down one half of `stream`, ignoring any errors.
+pub(crate) fn native_shutdown(stream: &tokio::net::TcpStream, how: Shutdown) {
+    _ = stream
+        .as_socketlike_view::<std::net::TcpStream>()
+        .shutdown(how);
+}
@@ -0,0 +1,236 @@
+use crate::net::SocketAddressFamily;
+use crate::p2::host::network::util;
+use crate::p3::bindings::sockets::types::ErrorCode;
+use crate::runtime::with_ambient_tokio_runtime;
+use cap_net_ext::{AddressFamily, Blocking};
+use io_lifetimes::AsSocketlike;
+use io_lifetimes::raw::{FromRawSocketlike, IntoRawSocketlike};
+use rustix::io::Errno;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Theoretical maximum byte size of a UDP datagram, the real limit is lower,
+/// but we do not account for e.g. the transport layer here for simplicity.
+/// In practice, datagrams are typically less than 1500 bytes.
+pub(crate) const MAX_UDP_DATAGRAM_SIZE: usize = u16::MAX as usize;
+
+/// The state of a UDP socket.
+///
+/// This represents the various states a socket can be in during the
+/// activities of binding, and connecting.
+enum UdpState {
+    /// The initial state for a newly-created socket.
+    Default,
+
+    /// The socket has a local address, either through an explicit `bind` or
+    /// implicitly by sending a datagram.
+    Bound,
+
+    /// The socket is "connected" to a peer address.
+    Connected(SocketAddr),
+}
+
+/// A host UDP socket, plus associated bookkeeping.
+pub struct UdpSocket {
+    /// The underlying socket, which is reference-counted so that it can be
+    /// used by in-progress `send` and `receive` calls.
+    inner: Arc<tokio::net::UdpSocket>,
+
+    /// The current state in the bind/connect progression.
+    udp_state: UdpState,
+
+    /// Socket address family.
+    family: SocketAddressFamily,
+}
+
+impl UdpSocket {
+    /// Create a new socket in the given family.
+    pub(crate) fn new(family: AddressFamily) -> io::Result<Self> {
+        // Create a new host socket and set it to non-blocking, which is needed
+        // by our async implementation.
+        let fd = util::udp_socket(family, Blocking::No)?;
+
+        let socket_address_family = match family {
+            AddressFamily::Ipv4 => SocketAddressFamily::Ipv4,
+            AddressFamily::Ipv6 => {
+                rustix::net::sockopt::set_ipv6_v6only(&fd, true)?;
+                SocketAddressFamily::Ipv6
+            }
+        };
+
+        let std_socket =
+            unsafe { std::net::UdpSocket::from_raw_socketlike(fd.into_raw_socketlike()) };
+        let socket = with_ambient_tokio_runtime(|| tokio::net::UdpSocket::try_from(std_socket))?;
+
+        Ok(Self {
+            inner: Arc::new(socket),
+            udp_state: UdpState::Default,
+            family: socket_address_family,
+        })
+    }
+
+    pub(crate) fn socket(&self) -> &Arc<tokio::net::UdpSocket> {
+        &self.inner
+    }
+
+    /// Performs the validation of `bind` which can be done before the
+    /// embedder's socket address check runs.
+    pub(crate) fn validate_bind(&self, local_address: &SocketAddr) -> Result<(), ErrorCode> {
+        if !matches!(self.udp_state, UdpState::Default) {
+            return Err(ErrorCode::InvalidState);
+        }
+        util::validate_address_family(local_address, &self.family)?;
+        Ok(())
+    }
+
+    pub(crate) fn bind(&mut self, local_address: SocketAddr) -> Result<(), ErrorCode> {
+        self.validate_bind(&local_address)?;
+        util::udp_bind(&*self.inner, &local_address).map_err(|error| match error {
+            // See the TCP `bind` implementation.
+            Errno::AFNOSUPPORT => ErrorCode::InvalidArgument,
+            _ => ErrorCode::from(error),
+        })?;
+        self.udp_state = UdpState::Bound;
+        Ok(())
+    }
+
+    /// Performs the validation of `connect` which can be done before the
+    /// embedder's socket address check runs.
+    pub(crate) fn validate_connect(&self, remote_address: &SocketAddr) -> Result<(), ErrorCode> {
+        util::validate_remote_address(remote_address)?;
+        util::validate_address_family(remote_address, &self.family)?;
+        Ok(())
+    }
+
+    pub(crate) fn connect(&mut self, remote_address: SocketAddr) -> Result<(), ErrorCode> {
+        self.validate_connect(&remote_address)?;
+
+        // We disconnect & (re)connect in two distinct steps for two reasons:
+        // - To leave our socket instance in a consistent state in case the
+        //   connect fails.
+        // - When reconnecting to a different address, Linux sometimes fails
+        //   if there isn't a disconnect in between.
+        if let UdpState::Connected(..) = self.udp_state {
+            util::udp_disconnect(&*self.inner)?;
+            self.udp_state = UdpState::Bound;
+        }
+
+        rustix::net::connect(&*self.inner, &remote_address).map_err(|error| match error {
+            Errno::AFNOSUPPORT => ErrorCode::InvalidArgument,
+            Errno::INPROGRESS => {
+                tracing::debug!("UDP connect returned EINPROGRESS, which should never happen");
+                ErrorCode::Unknown
+            }
+            _ => ErrorCode::from(error),
+        })?;
+        self.udp_state = UdpState::Connected(remote_address);
+        Ok(())
+    }
+
+    pub(crate) fn disconnect(&mut self) -> Result<(), ErrorCode> {
+        if !matches!(self.udp_state, UdpState::Connected(..)) {
+            return Err(ErrorCode::InvalidState);
+        }
+        util::udp_disconnect(&*self.inner)?;
+        self.udp_state = UdpState::Bound;
+        Ok(())
+    }
+
+    /// Determines the destination of a datagram passed to `send`.
+    ///
+    /// Returns `Some` with the address to send to when the socket is not
+    /// connected, in which case the address is still subject to the embedder's
+    /// socket address check, and `None` when the datagram goes to the
+    /// connected peer.
+    pub(crate) fn send_destination(
+        &self,
+        remote_address: Option<SocketAddr>,
+    ) -> Result<Option<SocketAddr>, ErrorCode> {
+        match (&self.udp_state, remote_address) {
+            (UdpState::Default | UdpState::Bound, Some(addr)) => {
+                util::validate_remote_address(&addr)?;
+                util::validate_address_family(&addr, &self.family)?;
+                Ok(Some(addr))
+            }
+            (UdpState::Connected(_), None) => Ok(None),
+            (UdpState::Connected(connected), Some(addr)) if *connected == addr => Ok(None),
+            _ => Err(ErrorCode::InvalidArgument),
+        }
+    }
+
+    /// Records that a datagram was sent, which implicitly binds the socket.
+    pub(crate) fn finish_send(&mut self) {
+        if let UdpState::Default = self.udp_state {
+            self.udp_state = UdpState::Bound;
+        }
+    }
+
+    /// Returns the connected peer address, if any, failing if the socket
+    /// hasn't been bound yet.
+    pub(crate) fn receive_filter(&self) -> Result<Option<SocketAddr>, ErrorCode> {
+        match self.udp_state {
+            UdpState::Default => Err(ErrorCode::InvalidState),
+            UdpState::Bound => Ok(None),
+            UdpState::Connected(addr) => Ok(Some(addr)),
+        }
+    }
+
+    pub(crate) fn local_address(&self) -> Result<SocketAddr, ErrorCode> {
+        if let UdpState::Default = self.udp_state {
+            return Err(ErrorCode::InvalidState);
+        }
+        let addr = self
+            .inner
+            .as_socketlike_view::<std::net::UdpSocket>()
+            .local_addr()?;
+        Ok(addr)
+    }
+
+    pub(crate) fn remote_address(&self) -> Result<SocketAddr, ErrorCode> {
+        if !matches!(self.udp_state, UdpState::Connected(..)) {
+            return Err(ErrorCode::InvalidState);
+        }
+        let addr = self
+            .inner
+            .as_socketlike_view::<std::net::UdpSocket>()
+            .peer_addr()?;
+        Ok(addr)
+    }
+
+    pub(crate) fn address_family(&self) -> SocketAddressFamily {
+        self.family
+    }
+
+    pub(crate) fn unicast_hop_limit(&self) -> Result<u8, ErrorCode> {
+        let ttl = match self.family {
+            SocketAddressFamily::Ipv4 => util::get_ip_ttl(&*self.inner)?,
+            SocketAddressFamily::Ipv6 => util::get_ipv6_unicast_hops(&*self.inner)?,
+        };
+        Ok(ttl)
+    }
+
+    pub(crate) fn set_unicast_hop_limit(&self, value: u8) -> Result<(), ErrorCode> {
+        match self.family {
+            SocketAddressFamily::Ipv4 => util::set_ip_ttl(&*self.inner, value)?,
+            SocketAddressFamily::Ipv6 => util::set_ipv6_unicast_hops(&*self.inner, value)?,
+        }
+        Ok(())
+    }
+
+    pub(crate) fn receive_buffer_size(&self) -> Result<usize, ErrorCode> {
+        Ok(util::get_socket_recv_buffer_size(&*self.inner)?)
+    }
+
+    pub(crate) fn set_receive_buffer_size(&self, value: usize) -> Result<(), ErrorCode> {
+        Ok(util::set_socket_recv_buffer_size(&*self.inner, value)?)
+    }
+
+    pub(crate) fn send_buffer_size(&self) -> Result<usize, ErrorCode> {
+        Ok(util::get_socket_send_buffer_size(&*self.inner)?)
+    }
+
+    pub(crate) fn set_send_buffer_size(&self, value: usize) -> Result<(), ErrorCode> {
+        Ok(util::set_socket_send_buffer_size(&*self.inner, value)?)
+    }
+}
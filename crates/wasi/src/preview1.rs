@@ -64,6 +64,7 @@
 //! ```
 
 use crate::ResourceTable;
+use crate::fs::WasiFile;
 use crate::p2::bindings::{
     cli::{
         stderr::Host as _, stdin::Host as _, stdout::Host as _, terminal_input, terminal_output,
@@ -79,7 +80,7 @@ use std::mem::{self, size_of, size_of_val};
 use std::slice;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use system_interface::fs::FileIoExt;
+
 use wasmtime::component::Resource;
 use wasmtime_wasi_io::{
     IoImpl, IoView,
@@ -581,7 +582,7 @@ impl WasiP1Ctx {
                 let f = self.table().get(&fd)?.file()?;
                 let buf = first_non_empty_ciovec(memory, ciovs)?;
 
-                let do_write = move |f: &cap_std::fs::File, buf: &[u8]| match (append, write) {
+                let do_write = move |f: &dyn WasiFile, buf: &[u8]| match (append, write) {
                     // Note that this is implementing Linux semantics of
                     // `pwrite` where the offset is ignored if the file was
                     // opened in append mode.
@@ -1,15 +1,18 @@
 use anyhow::Result;
-use std::io::Write;
-use std::sync::Mutex;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use wasmtime::Store;
 use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime_wasi::fs::{DirEntry, FileType, Metadata, OpenOptions, Opened, WasiDir, WasiFile};
 use wasmtime_wasi::p2::bindings::Command;
 use wasmtime_wasi::p2::{
     IoView, WasiCtx, WasiCtxBuilder, WasiView, add_to_linker_async,
     bindings::{clocks::wall_clock, filesystem::types as filesystem},
 };
-use wasmtime_wasi::{DirPerms, FilePerms, HostMonotonicClock, HostWallClock};
+use wasmtime_wasi::{DirPerms, FilePerms, HostMonotonicClock, HostWallClock, SystemTimeSpec};
 
 struct CommandCtx {
     table: ResourceTable,
@@ -112,6 +115,180 @@ async fn api_read_only() -> Result<()> {
         .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
 }
 
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn api_custom_filesystem() -> Result<()> {
+    /// A flat, read-only, directory of blobs standing in for an object store.
+    #[derive(Clone)]
+    struct Blobs(Arc<BTreeMap<String, Arc<[u8]>>>);
+
+    struct Blob(u64, Arc<[u8]>);
+
+    fn metadata(file_type: FileType, ino: u64, len: usize) -> Metadata {
+        Metadata {
+            file_type,
+            len: len as u64,
+            nlink: 1,
+            dev: 42,
+            ino,
+            accessed: None,
+            modified: None,
+            created: None,
+        }
+    }
+
+    fn read_only() -> io::Result<()> {
+        Err(io::ErrorKind::ReadOnlyFilesystem.into())
+    }
+
+    impl Blobs {
+        fn lookup(&self, path: &str) -> io::Result<Blob> {
+            let (ino, (_, data)) = self
+                .0
+                .iter()
+                .enumerate()
+                .find(|(_, (name, _))| *name == path)
+                .ok_or(io::ErrorKind::NotFound)?;
+            Ok(Blob(ino as u64 + 1, data.clone()))
+        }
+    }
+
+    impl WasiDir for Blobs {
+        fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened> {
+            if options.write || options.create || options.truncate {
+                read_only()?;
+            }
+            if path == "." {
+                return Ok(Opened::Dir(Arc::new(self.clone())));
+            }
+            let blob = self.lookup(path)?;
+            if options.directory {
+                return Err(io::ErrorKind::NotADirectory.into());
+            }
+            Ok(Opened::File(Arc::new(blob)))
+        }
+        fn create_dir_at(&self, _path: &str) -> io::Result<()> {
+            read_only()
+        }
+        fn metadata(&self) -> io::Result<Metadata> {
+            Ok(metadata(FileType::Directory, 0, 0))
+        }
+        fn metadata_at(&self, path: &str, _follow: bool) -> io::Result<Metadata> {
+            if path == "." {
+                return self.metadata();
+            }
+            self.lookup(path)?.metadata()
+        }
+        fn set_times(
+            &self,
+            _: Option<SystemTimeSpec>,
+            _: Option<SystemTimeSpec>,
+        ) -> io::Result<()> {
+            read_only()
+        }
+        fn set_times_at(
+            &self,
+            _path: &str,
+            _follow: bool,
+            _: Option<SystemTimeSpec>,
+            _: Option<SystemTimeSpec>,
+        ) -> io::Result<()> {
+            read_only()
+        }
+        fn read_dir(&self) -> io::Result<Vec<io::Result<DirEntry>>> {
+            Ok(self
+                .0
+                .keys()
+                .map(|name| {
+                    Ok(DirEntry {
+                        name: name.into(),
+                        file_type: FileType::RegularFile,
+                    })
+                })
+                .collect())
+        }
+        fn read_link_at(&self, path: &str) -> io::Result<PathBuf> {
+            self.lookup(path)?;
+            Err(io::ErrorKind::InvalidInput.into())
+        }
+        fn remove_dir_at(&self, _path: &str) -> io::Result<()> {
+            read_only()
+        }
+        fn unlink_file_at(&self, _path: &str) -> io::Result<()> {
+            read_only()
+        }
+        fn symlink_at(&self, _target: &str, _path: &str) -> io::Result<()> {
+            read_only()
+        }
+        fn rename_at(&self, _old: &str, _new_dir: &dyn WasiDir, _new: &str) -> io::Result<()> {
+            read_only()
+        }
+        fn hard_link_at(&self, _old: &str, _new_dir: &dyn WasiDir, _new: &str) -> io::Result<()> {
+            read_only()
+        }
+        fn sync(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn sync_data(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl WasiFile for Blob {
+        fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            let data = self.1.get(offset as usize..).unwrap_or(&[]);
+            let n = data.len().min(buf.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            Ok(n)
+        }
+        fn write_at(&self, _buf: &[u8], _offset: u64) -> io::Result<usize> {
+            Err(io::ErrorKind::ReadOnlyFilesystem.into())
+        }
+        fn append(&self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::ReadOnlyFilesystem.into())
+        }
+        fn set_len(&self, _size: u64) -> io::Result<()> {
+            read_only()
+        }
+        fn metadata(&self) -> io::Result<Metadata> {
+            Ok(metadata(FileType::RegularFile, self.0, self.1.len()))
+        }
+        fn set_times(
+            &self,
+            _: Option<SystemTimeSpec>,
+            _: Option<SystemTimeSpec>,
+        ) -> io::Result<()> {
+            read_only()
+        }
+        fn sync(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn sync_data(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let blobs = Blobs(Arc::new(BTreeMap::from([(
+        "bar.txt".to_string(),
+        Arc::from(&b"And stood awhile in thought"[..]),
+    )])));
+
+    // Grant all permissions so that everything `api_read_only` expects to fail
+    // is rejected by the filesystem implementation itself.
+    let table = ResourceTable::new();
+    let wasi = WasiCtxBuilder::new()
+        .preopened_custom_dir(blobs, "/", DirPerms::all(), FilePerms::all())
+        .build();
+
+    let (mut store, command) =
+        instantiate(API_READ_ONLY_COMPONENT, CommandCtx { table, wasi }).await?;
+
+    command
+        .wasi_cli_run()
+        .call_run(&mut store)
+        .await?
+        .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
+}
+
 #[expect(
     dead_code,
     reason = "tested in the wasi-http crate, satisfying foreach_api! macro"
@@ -0,0 +1,179 @@
+//! Runs the filesystem tests of `preview1` against a [`MemoryFs`] preopen
+//! instead of a directory on the host.
+
+use super::*;
+use std::path::Path;
+use test_programs_artifacts::*;
+use wasmtime::{Linker, Module};
+use wasmtime_wasi::MemoryFs;
+use wasmtime_wasi::p2::pipe::MemoryOutputPipe;
+use wasmtime_wasi::preview1::add_to_linker_async;
+
+async fn run(path: &str) -> Result<()> {
+    let path = Path::new(path);
+    let name = path.file_stem().unwrap().to_str().unwrap();
+    let engine = test_programs_artifacts::engine(|config| {
+        config.async_support(true);
+    });
+    let mut linker = Linker::<Ctx>::new(&engine);
+    add_to_linker_async(&mut linker, |t| &mut t.wasi)?;
+
+    let stdout = MemoryOutputPipe::new(4096);
+    let stderr = MemoryOutputPipe::new(4096);
+    let mut builder = WasiCtxBuilder::new();
+    builder
+        .stdout(stdout.clone())
+        .stderr(stderr.clone())
+        .args(&[name, "."])
+        .preopened_memory_dir(MemoryFs::new(), ".", DirPerms::all(), FilePerms::all())
+        // The in-memory filesystem behaves the same on every host, following
+        // Linux semantics.
+        .env("ERRNO_MODE_UNIX", "1");
+    let ctx = Ctx {
+        wasi: builder.build_p1(),
+        stderr,
+        stdout,
+    };
+    let mut store = Store::new(&engine, ctx);
+
+    let module = Module::from_file(&engine, path)?;
+    let instance = linker.instantiate_async(&mut store, &module).await?;
+    let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+    start.call_async(&mut store, ()).await?;
+    Ok(())
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_dangling_fd() {
+    run(PREVIEW1_DANGLING_FD).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_dangling_symlink() {
+    run(PREVIEW1_DANGLING_SYMLINK).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_directory_seek() {
+    run(PREVIEW1_DIRECTORY_SEEK).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_dir_fd_op_failures() {
+    run(PREVIEW1_DIR_FD_OP_FAILURES).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_fd_filestat_get() {
+    run(PREVIEW1_FD_FILESTAT_GET).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_fd_filestat_set() {
+    run(PREVIEW1_FD_FILESTAT_SET).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_fd_readdir() {
+    run(PREVIEW1_FD_READDIR).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_allocate() {
+    run(PREVIEW1_FILE_ALLOCATE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_pread_pwrite() {
+    run(PREVIEW1_FILE_PREAD_PWRITE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_read_write() {
+    run(PREVIEW1_FILE_READ_WRITE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_seek_tell() {
+    run(PREVIEW1_FILE_SEEK_TELL).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_truncation() {
+    run(PREVIEW1_FILE_TRUNCATION).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_unbuffered_write() {
+    run(PREVIEW1_FILE_UNBUFFERED_WRITE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_file_write() {
+    run(PREVIEW1_FILE_WRITE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_interesting_paths() {
+    run(PREVIEW1_INTERESTING_PATHS).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_nofollow_errors() {
+    run(PREVIEW1_NOFOLLOW_ERRORS).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_exists() {
+    run(PREVIEW1_PATH_EXISTS).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_filestat() {
+    run(PREVIEW1_PATH_FILESTAT).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_link() {
+    run(PREVIEW1_PATH_LINK).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_open_create_existing() {
+    run(PREVIEW1_PATH_OPEN_CREATE_EXISTING).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_open_dirfd_not_dir() {
+    run(PREVIEW1_PATH_OPEN_DIRFD_NOT_DIR).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_open_missing() {
+    run(PREVIEW1_PATH_OPEN_MISSING).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_open_read_write() {
+    run(PREVIEW1_PATH_OPEN_READ_WRITE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_rename() {
+    run(PREVIEW1_PATH_RENAME).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_rename_dir_trailing_slashes() {
+    run(PREVIEW1_PATH_RENAME_DIR_TRAILING_SLASHES)
+        .await
+        .unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_path_symlink_trailing_slashes() {
+    run(PREVIEW1_PATH_SYMLINK_TRAILING_SLASHES).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_readlink() {
+    run(PREVIEW1_READLINK).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_remove_directory() {
+    run(PREVIEW1_REMOVE_DIRECTORY).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_remove_nonempty_directory() {
+    run(PREVIEW1_REMOVE_NONEMPTY_DIRECTORY).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_symlink_create() {
+    run(PREVIEW1_SYMLINK_CREATE).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_symlink_filestat() {
+    run(PREVIEW1_SYMLINK_FILESTAT).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_symlink_loop() {
+    run(PREVIEW1_SYMLINK_LOOP).await.unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview1_unlink_file_trailing_slashes() {
+    run(PREVIEW1_UNLINK_FILE_TRAILING_SLASHES).await.unwrap()
+}
@@ -88,5 +88,6 @@ macro_rules! assert_test_exists {
 
 mod api;
 mod async_;
+mod memory;
 mod preview1;
 mod sync;
@@ -111,3 +111,18 @@ async fn p3_random_imports() -> anyhow::Result<()> {
 async fn p3_cli() -> anyhow::Result<()> {
     run(P3_CLI_COMPONENT).await
 }
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn p3_sockets_ip_name_lookup() -> anyhow::Result<()> {
+    run(P3_SOCKETS_IP_NAME_LOOKUP_COMPONENT).await
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn p3_sockets_tcp_sample_application() -> anyhow::Result<()> {
+    run(P3_SOCKETS_TCP_SAMPLE_APPLICATION_COMPONENT).await
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn p3_sockets_udp_sample_application() -> anyhow::Result<()> {
+    run(P3_SOCKETS_UDP_SAMPLE_APPLICATION_COMPONENT).await
+}
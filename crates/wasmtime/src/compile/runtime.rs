@@ -95,7 +95,11 @@ impl<'a> CodeBuilder<'a> {
         let custom_alignment = self.custom_alignment();
         let (code, info_and_types) =
             self.compile_cached(super::build_artifacts, &custom_alignment)?;
-        Module::from_parts(self.engine, code, info_and_types)
+        let mut module = Module::from_parts(self.engine, code, info_and_types)?;
+        if self.engine.tunables().tiered_compilation {
+            module.enable_tiering(self.get_wasm()?);
+        }
+        Ok(module)
     }
 
     /// Same as [`CodeBuilder::compile_module`] except that it compiles a
@@ -109,16 +113,31 @@ impl<'a> CodeBuilder<'a> {
     }
 
     fn custom_alignment(&self) -> CustomAlignment {
-        CustomAlignment {
-            alignment: self
-                .engine
-                .custom_code_memory()
-                .map(|c| c.required_alignment())
-                .unwrap_or(1),
-        }
+        CustomAlignment::new(self.engine)
     }
 }
 
+/// Recompiles the module `wasm` with the engine's optimizing compiler as part
+/// of tiered compilation.
+///
+/// The returned module uses the same calling convention and `VMContext` layout
+/// as a module compiled from `wasm` by the engine's baseline compiler, so its
+/// functions can be used by instances of the baseline module.
+pub(crate) fn compile_tier_up(engine: &Engine, wasm: &[u8]) -> Result<Module> {
+    let compiler = engine
+        .tier_up_compiler()
+        .context("tiered compilation is not enabled")?;
+    let (mmap, info_and_types) = super::build_artifacts_with::<MmapVecWrapper>(
+        engine,
+        compiler,
+        wasm,
+        None,
+        &CustomAlignment::new(engine),
+    )?;
+    let code = publish_mmap(engine, mmap.0)?;
+    Module::from_parts(engine, code, info_and_types)
+}
+
 fn publish_mmap(engine: &Engine, mmap: MmapVec) -> Result<Arc<CodeMemory>> {
     let mut code = CodeMemory::new(engine, mmap)?;
     code.publish()?;
@@ -133,6 +152,17 @@ pub(crate) struct CustomAlignment {
     alignment: usize,
 }
 
+impl CustomAlignment {
+    fn new(engine: &Engine) -> CustomAlignment {
+        CustomAlignment {
+            alignment: engine
+                .custom_code_memory()
+                .map(|c| c.required_alignment())
+                .unwrap_or(1),
+        }
+    }
+}
+
 impl FinishedObject for MmapVecWrapper {
     type State = CustomAlignment;
     fn finish_object(obj: ObjectBuilder<'_>, align: &CustomAlignment) -> Result<Self> {
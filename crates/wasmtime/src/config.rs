@@ -442,6 +442,32 @@ impl Config {
         self
     }
 
+    /// Configures whether compiled code is instrumented for Wasmtime's
+    /// built-in guest debugging API.
+    ///
+    /// When enabled, execution of WebAssembly can be paused at breakpoints
+    /// added with [`Store::add_breakpoint`] or before every instruction with
+    /// [`Store::set_single_step`]. While paused, the handler configured with
+    /// [`Store::debug_handler`] can inspect the current function's locals and
+    /// operand stack as well as its instance's globals.
+    ///
+    /// The instrumentation checks a flag in the [`Store`] before every
+    /// WebAssembly instruction, so code compiled with this option runs slower
+    /// even when no breakpoints are set.
+    ///
+    /// By default this option is `false`.
+    ///
+    /// **Note** Enabling this option is not compatible with the Winch compiler.
+    ///
+    /// [`Store`]: crate::Store
+    /// [`Store::add_breakpoint`]: crate::Store::add_breakpoint
+    /// [`Store::set_single_step`]: crate::Store::set_single_step
+    /// [`Store::debug_handler`]: crate::Store::debug_handler
+    pub fn guest_debug(&mut self, enable: bool) -> &mut Self {
+        self.tunables.debug_guest = Some(enable);
+        self
+    }
+
     /// Configures whether [`WasmBacktrace`] will be present in the context of
     /// errors returned from Wasmtime.
     ///
@@ -1214,6 +1240,39 @@ impl Config {
         self
     }
 
+    /// Configures whether modules compiled with Winch are tiered up to
+    /// Cranelift at runtime.
+    ///
+    /// When enabled, [`Module::new`] and related APIs compile modules quickly
+    /// with Winch, whose code additionally counts function entries and loop
+    /// iterations. Once a function is found to be hot the module is recompiled
+    /// with Cranelift on a background thread, after which hot functions are
+    /// switched over to the optimized code as they continue to be executed.
+    ///
+    /// Optimized code is picked up by all subsequent calls of a hot function,
+    /// including direct calls from baseline code, calls from the host, and
+    /// calls through a table or `call_ref`. Activations of baseline code which
+    /// are already on the stack keep running the baseline code.
+    ///
+    /// Only modules compiled in-process are tiered: modules loaded with
+    /// [`Module::deserialize`] and modules within components always run the
+    /// code they were compiled with.
+    ///
+    /// This option requires that [`Config::strategy`] is
+    /// [`Strategy::Winch`] and that both the `winch` and `cranelift` features
+    /// of this crate are enabled. Winch serves as the baseline tier, so Pulley
+    /// targets, which Winch doesn't support, can't use tiered compilation.
+    ///
+    /// By default this option is `false`.
+    ///
+    /// [`Module::new`]: crate::Module::new
+    /// [`Module::deserialize`]: crate::Module::deserialize
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn tiered_compilation(&mut self, enable: bool) -> &mut Self {
+        self.tunables.tiered_compilation = Some(enable);
+        self
+    }
+
     /// Configures which garbage collector will be used for Wasm modules.
     ///
     /// This method can be used to configure which garbage collector
@@ -2052,19 +2111,29 @@ impl Config {
             None | Some(Strategy::Cranelift) => {
                 let mut unsupported = WasmFeatures::empty();
 
-                // Pulley at this time fundamentally doesn't support the
-                // `threads` proposal, notably shared memory, because Rust can't
-                // safely implement loads/stores in the face of shared memory.
-                // Stack switching is not implemented, either.
-                if self.compiler_target().is_pulley() {
-                    unsupported |= WasmFeatures::THREADS;
-                    unsupported |= WasmFeatures::STACK_SWITCHING;
-                }
-
                 use target_lexicon::*;
                 match self.compiler_target() {
+                    // Pulley implements stack switching in the interpreter,
+                    // but continuation stacks are still allocated by the host
+                    // runtime, which only supports a subset of platforms.
+                    target if target.is_pulley() => {
+                        if !cfg!(all(
+                            unix,
+                            any(
+                                target_arch = "x86_64",
+                                target_arch = "aarch64",
+                                target_arch = "riscv64"
+                            )
+                        )) {
+                            unsupported |= WasmFeatures::STACK_SWITCHING;
+                        }
+                    }
                     Triple {
-                        architecture: Architecture::X86_64 | Architecture::X86_64h,
+                        architecture:
+                            Architecture::X86_64
+                            | Architecture::X86_64h
+                            | Architecture::Aarch64(_)
+                            | Architecture::Riscv64(_),
                         operating_system:
                             OperatingSystem::Linux
                             | OperatingSystem::MacOSX(_)
@@ -2075,26 +2144,23 @@ impl Config {
                     }
 
                     _ => {
-                        // On platforms other than x64 Unix-like, we don't
-                        // support stack switching.
+                        // On platforms other than x64, aarch64, and riscv64
+                        // Unix-like, we don't support stack switching.
                         unsupported |= WasmFeatures::STACK_SWITCHING;
                     }
                 }
                 unsupported
             }
             Some(Strategy::Winch) => {
-                let mut unsupported = WasmFeatures::GC
-                    | WasmFeatures::FUNCTION_REFERENCES
-                    | WasmFeatures::RELAXED_SIMD
-                    | WasmFeatures::TAIL_CALL
-                    | WasmFeatures::GC_TYPES
-                    | WasmFeatures::EXCEPTIONS
+                let mut unsupported = WasmFeatures::RELAXED_SIMD
                     | WasmFeatures::LEGACY_EXCEPTIONS
                     | WasmFeatures::STACK_SWITCHING;
                 match self.compiler_target().architecture {
-                    target_lexicon::Architecture::Aarch64(_) => {
+                    target_lexicon::Architecture::Aarch64(_)
+                    | target_lexicon::Architecture::Riscv64(_) => {
                         unsupported |= WasmFeatures::THREADS;
                         unsupported |= WasmFeatures::WIDE_ARITHMETIC;
+                        unsupported |= WasmFeatures::EXCEPTIONS;
                     }
 
                     // Winch doesn't support other non-x64 architectures at this
@@ -2245,6 +2311,18 @@ impl Config {
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
             tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
+
+            if tunables.tiered_compilation {
+                if !tunables.winch_callable {
+                    bail!("tiered compilation requires the Winch compilation strategy");
+                }
+                if self.compiler_target().is_pulley() {
+                    bail!("tiered compilation is not supported on Pulley targets");
+                }
+                if !cfg!(feature = "cranelift") {
+                    bail!("tiered compilation requires the `cranelift` feature to be enabled");
+                }
+            }
         }
 
         tunables.collector = if features.gc_types() {
@@ -2254,6 +2332,7 @@ impl Config {
                 Some(match self.collector.try_not_auto()? {
                     Collector::DeferredReferenceCounting => EnvCollector::DeferredReferenceCounting,
                     Collector::Null => EnvCollector::Null,
+                    Collector::Generational => EnvCollector::Generational,
                     Collector::Auto => unreachable!(),
                 })
             }
@@ -2315,7 +2394,7 @@ impl Config {
 
         #[cfg(feature = "gc")]
         #[cfg_attr(
-            not(any(feature = "gc-null", feature = "gc-drc")),
+            not(any(feature = "gc-null", feature = "gc-drc", feature = "gc-generational")),
             expect(unreachable_code, reason = "definitions known to be dummy")
         )]
         {
@@ -2334,6 +2413,14 @@ impl Config {
                 #[cfg(not(feature = "gc-null"))]
                 Collector::Null => unreachable!(),
 
+                #[cfg(feature = "gc-generational")]
+                Collector::Generational => {
+                    Arc::new(crate::runtime::vm::GenerationalCollector::default())
+                        as Arc<dyn GcRuntime>
+                }
+                #[cfg(not(feature = "gc-generational"))]
+                Collector::Generational => unreachable!(),
+
                 Collector::Auto => unreachable!(),
             }))
         }
@@ -2350,6 +2437,27 @@ impl Config {
         })
     }
 
+    /// Builds the Cranelift compiler that hot functions are recompiled with
+    /// when tiered compilation is enabled.
+    ///
+    /// This shares `tunables` with the baseline compiler, notably including
+    /// `winch_callable`, so optimized code can be called from and call into
+    /// Winch-compiled code.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn build_tier_up_compiler(
+        &self,
+        tunables: &Tunables,
+        features: WasmFeatures,
+    ) -> Result<Option<Box<dyn wasmtime_environ::Compiler>>> {
+        if !tunables.tiered_compilation {
+            return Ok(None);
+        }
+        let mut config = self.clone();
+        config.compiler_config.strategy = Some(Strategy::Cranelift);
+        let (_, compiler) = config.build_compiler(tunables, features)?;
+        Ok(Some(compiler))
+    }
+
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     pub(crate) fn build_compiler(
         mut self,
@@ -2478,6 +2586,7 @@ impl Config {
         if features.contains(WasmFeatures::STACK_SWITCHING) {
             use target_lexicon::OperatingSystem;
             let model = match target.operating_system {
+                _ if target.is_pulley() => "basic",
                 OperatingSystem::Windows => "update_windows_tib",
                 OperatingSystem::Linux
                 | OperatingSystem::MacOSX(_)
@@ -2729,6 +2838,7 @@ impl Strategy {
 /// |-----------------------------|----------------------|-------------|----------------|----------------------|----------------------|
 /// | `DeferredReferenceCounting` | Yes, but not cycles  | 🙂         | 🙁             | 😐                   | 😐                  |
 /// | `Null`                      | No                   | 🙂         | 🙂             | 🙂                   | 🙂                  |
+/// | `Generational`              | Yes, including cycles | 😐         | 🙂             | 🙂                   | 😐                  |
 ///
 /// [^1]: Whether or not the collector is capable of collecting garbage and cyclic garbage.
 ///
@@ -2793,6 +2903,20 @@ pub enum Collector {
     /// collectors, as this collector imposes as close to zero throughput and
     /// latency overhead as possible.
     Null,
+
+    /// The generational collector.
+    ///
+    /// A tracing collector that bump-allocates new objects into a nursery. When
+    /// the GC heap fills up, surviving nursery objects are compacted and
+    /// promoted into the old generation, and the old generation is itself
+    /// occasionally marked and compacted as a whole. A card-marking write
+    /// barrier tracks references from old objects to young objects so that
+    /// collecting the nursery does not require tracing the whole heap.
+    ///
+    /// Unlike the deferred reference-counting collector, this collector can
+    /// collect cycles. Because it moves objects, it trades longer pauses during
+    /// collection for fast inline allocation and improved throughput.
+    Generational,
 }
 
 impl Default for Collector {
@@ -2810,6 +2934,8 @@ impl Collector {
                     Some(Collector::DeferredReferenceCounting)
                 } else if cfg!(feature = "gc-null") {
                     Some(Collector::Null)
+                } else if cfg!(feature = "gc-generational") {
+                    Some(Collector::Generational)
                 } else {
                     None
                 }
@@ -2837,12 +2963,21 @@ impl Collector {
                  the `gc-null` feature was not enabled at compile time",
             ),
 
+            #[cfg(feature = "gc-generational")]
+            Some(c @ Collector::Generational) => Ok(c),
+            #[cfg(not(feature = "gc-generational"))]
+            Some(Collector::Generational) => bail!(
+                "cannot create an engine using the generational collector \
+                 because the `gc-generational` feature was not enabled at \
+                 compile time",
+            ),
+
             Some(Collector::Auto) => unreachable!(),
 
             None => bail!(
                 "cannot create an engine with GC support when none of the \
                  collectors are available; enable one of the following \
-                 features: `gc-drc`, `gc-null`",
+                 features: `gc-drc`, `gc-null`, `gc-generational`",
             ),
         }
     }
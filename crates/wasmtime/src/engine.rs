@@ -52,6 +52,10 @@ struct EngineInner {
     tunables: Tunables,
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     compiler: Box<dyn wasmtime_environ::Compiler>,
+    /// The optimizing compiler that hot functions are recompiled with when
+    /// tiered compilation is enabled.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    tier_up_compiler: Option<Box<dyn wasmtime_environ::Compiler>>,
     #[cfg(feature = "runtime")]
     allocator: Box<dyn crate::runtime::vm::InstanceAllocator + Send + Sync>,
     #[cfg(feature = "runtime")]
@@ -114,6 +118,8 @@ impl Engine {
             }
         }
 
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        let tier_up_compiler = config.build_tier_up_compiler(&tunables, features)?;
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         let (config, compiler) = config.build_compiler(&tunables, features)?;
 
@@ -121,6 +127,8 @@ impl Engine {
             inner: Arc::new(EngineInner {
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 compiler,
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                tier_up_compiler,
                 #[cfg(feature = "runtime")]
                 allocator: {
                     let allocator = config.build_allocator(&tunables)?;
@@ -393,6 +401,7 @@ impl Engine {
                     use target_lexicon::OperatingSystem;
                     let expected =
                     match target.operating_system  {
+                        _ if target.is_pulley() => "basic",
                         OperatingSystem::Windows => "update_windows_tib",
                         OperatingSystem::Linux
                         | OperatingSystem::MacOSX(_)
@@ -425,6 +434,8 @@ impl Engine {
             | "tls_model" // wasmtime doesn't use tls right now
             | "opt_level" // opt level doesn't change semantics
             | "enable_alias_analysis" // alias analysis-based opts don't change semantics
+            | "enable_loop_opts" // loop opts don't change semantics
+            | "enable_slp_vectorizer" // vectorization doesn't change semantics
             | "probestack_size_log2" // probestack above asserted disabled
             | "regalloc" // shouldn't change semantics
             | "enable_incremental_compilation_cache_checks" // shouldn't change semantics
@@ -591,6 +602,12 @@ impl Engine {
         &*self.inner.compiler
     }
 
+    /// Returns the compiler used to recompile hot functions, or `None` if
+    /// tiered compilation is disabled.
+    pub(crate) fn tier_up_compiler(&self) -> Option<&dyn wasmtime_environ::Compiler> {
+        self.inner.tier_up_compiler.as_deref()
+    }
+
     /// Ahead-of-time (AOT) compiles a WebAssembly module.
     ///
     /// The `bytes` provided must be in one of two formats:
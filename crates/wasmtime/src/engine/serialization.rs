@@ -281,6 +281,8 @@ impl Metadata<'_> {
             parse_wasm_debuginfo,
             consume_fuel,
             epoch_interruption,
+            debug_guest,
+            tiered_compilation,
             memory_may_move,
             guard_before_linear_memory,
             table_lazy_init,
@@ -328,6 +330,12 @@ impl Metadata<'_> {
             other.epoch_interruption,
             "epoch interruption",
         )?;
+        Self::check_bool(debug_guest, other.debug_guest, "guest debugging")?;
+        Self::check_bool(
+            tiered_compilation,
+            other.tiered_compilation,
+            "tiered compilation",
+        )?;
         Self::check_bool(memory_may_move, other.memory_may_move, "memory may move")?;
         Self::check_bool(
             guard_before_linear_memory,
@@ -33,6 +33,7 @@ pub(crate) mod code;
 pub(crate) mod code_memory;
 #[cfg(feature = "debug-builtins")]
 pub(crate) mod debug;
+pub(crate) mod debugger;
 pub(crate) mod externals;
 #[cfg(feature = "async")]
 pub(crate) mod fiber;
@@ -72,6 +73,7 @@ cfg_if::cfg_if! {
 }
 
 pub use code_memory::CodeMemory;
+pub use debugger::{DebugAction, DebugFrame, DebugReason};
 pub use externals::*;
 pub use func::*;
 pub use gc::*;
@@ -107,11 +109,25 @@ pub(crate) mod stack;
 #[cfg(feature = "async")]
 pub use stack::*;
 
+#[cfg(feature = "checkpoint")]
+mod checkpoint;
+
 #[cfg(feature = "coredump")]
 mod coredump;
 #[cfg(feature = "coredump")]
 pub use coredump::*;
 
+#[cfg(all(
+    feature = "pre-initialize",
+    any(feature = "cranelift", feature = "winch")
+))]
+mod pre_initialize;
+
+#[cfg(feature = "record-replay")]
+pub(crate) mod record_replay;
+#[cfg(feature = "record-replay")]
+pub use record_replay::{HostCallRecorder, HostCallReplayer};
+
 #[cfg(feature = "wave")]
 mod wave;
 
@@ -0,0 +1,888 @@
+//! Checkpointing and restoring the state of running instances.
+//!
+//! [`Instance::checkpoint`] serializes the state of a core instance into a
+//! self-contained byte buffer and [`Instance::restore`] loads that state back
+//! into another instance of the same [`Module`], which is typically a fresh
+//! instance in a new [`Store`](crate::Store) and possibly in another process.
+//! This allows long-lived instances, such as plugin sessions, to be suspended
+//! and migrated between hosts and then resumed by calling their exports again.
+//!
+//! A checkpoint contains:
+//!
+//! * The size and contents of every linear memory that the instance defines.
+//!
+//! * The size and contents of every table that the instance defines, where
+//!   function references are encoded as indices into the module's function
+//!   index space.
+//!
+//! * The values of all mutable globals that the instance defines.
+//!
+//! * Which passive data and element segments have been dropped.
+//!
+//! * When the `gc` feature is enabled, the graph of GC objects (structs,
+//!   arrays and `i31ref`s) reachable from the instance's globals and tables.
+//!   Sharing and cycles between objects are preserved. Objects referenced by
+//!   immutable globals are not reallocated on restore, but are instead
+//!   identified with the objects that the restored instance's globals were
+//!   initialized with.
+//!
+//! Imported memories, tables and globals are not part of an instance's
+//! checkpoint, as they belong to the instance (or host) that defines them.
+//! When multiple instances are linked together each one must be checkpointed
+//! separately and restored in the same order that they were instantiated in.
+//!
+//! State that can't be described in terms of the module is rejected:
+//! references to host functions or functions of other instances, non-null
+//! `externref`s and `exnref`s, and shared memories all produce an error when
+//! checkpointing. The WebAssembly stack isn't captured either, so checkpoints
+//! should only be taken while the instance isn't executing.
+
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::{
+    AsContextMut, Extern, Func, Instance, Module, Mutability, Ref, StoreContextMut, Val, ValType,
+};
+use serde_derive::{Deserialize, Serialize};
+use wasmtime_environ::{DataIndex, ElemIndex, EntityIndex, FuncIndex};
+#[cfg(feature = "gc")]
+use {
+    crate::{
+        AnyRef, ArrayRef, ArrayRefPre, ArrayType, FieldType, I31, Rooted, RootedGcRefImpl,
+        StructRef, StructRefPre, StructType,
+    },
+    wasmtime_environ::{ModuleInternedTypeIndex, VMSharedTypeIndex},
+};
+
+/// Bytes at the start of every checkpoint, used to reject buffers which
+/// aren't checkpoints produced by this module.
+const MAGIC: &[u8; 16] = b"\0wasmtime-chkpt\0";
+
+/// Version of the checkpoint format, bumped whenever [`Checkpoint`] changes.
+const VERSION: u32 = 1;
+
+/// Runs of zero bytes in linear memory shorter than this are stored inline
+/// rather than splitting the surrounding data into two chunks.
+const MIN_ZERO_RUN: usize = 16;
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    shape: Shape,
+    /// One entry per defined memory.
+    memories: Vec<MemoryState>,
+    /// One entry per defined table, holding all of its elements.
+    tables: Vec<Vec<Value>>,
+    /// One entry per defined global. Immutable globals are `None` unless they
+    /// refer to a GC object.
+    globals: Vec<Option<Value>>,
+    /// GC objects, referred to by [`Value::Object`].
+    objects: Vec<Object>,
+    dropped_data: Vec<u32>,
+    dropped_elements: Vec<u32>,
+}
+
+/// A summary of the module that a checkpoint was taken from, used to reject
+/// restoring into an instance of an unrelated module.
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Shape {
+    types: u32,
+    functions: u32,
+    memories: u32,
+    tables: u32,
+    globals: u32,
+    data: u32,
+    elements: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MemoryState {
+    pages: u64,
+    /// Non-zero regions of the memory, as `(offset, bytes)`.
+    data: Vec<(u64, Vec<u8>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Object {
+    /// The module-interned index of the object's type.
+    ty: u32,
+    /// Fields of a struct or elements of an array.
+    fields: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+enum Value {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+    V128(u128),
+    Null,
+    Func(u32),
+    I31(u32),
+    Object(u32),
+}
+
+impl Instance {
+    /// Serializes the state of this instance so that it can be loaded into
+    /// another instance of the same module with [`Instance::restore`].
+    ///
+    /// The returned bytes include the instance's defined memories, tables,
+    /// mutable globals and reachable GC objects. See the documentation of
+    /// [`Instance::restore`] for what is required to load them again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the instance holds state which can't be
+    /// serialized, such as a shared memory, a reference to a host function or
+    /// a function of another instance, or a non-null `externref`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn checkpoint(&self, mut store: impl AsContextMut) -> Result<Vec<u8>> {
+        #[cfg(feature = "gc")]
+        let mut store = crate::RootScope::new(&mut store);
+        let mut store = store.as_context_mut();
+        let checkpoint = Capture::new(&mut store, *self).run(&mut store)?;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        postcard::to_extend(&checkpoint, bytes).context("failed to serialize checkpoint")
+    }
+
+    /// Loads a checkpoint produced by [`Instance::checkpoint`] into this
+    /// instance.
+    ///
+    /// This instance must be an instance of the same module that the
+    /// checkpoint was taken from, though it may live in a different store, in
+    /// a different [`Engine`](crate::Engine), or in a different process, as
+    /// long as the module was compiled by the same version of Wasmtime with
+    /// the same configuration (for example by using
+    /// [`Module::serialize`] and [`Module::deserialize`]). Typically this is a
+    /// freshly created instance: memories and tables are grown to their
+    /// checkpointed sizes and then overwritten, and mutable globals are set to
+    /// their checkpointed values. Imported items are left untouched, so they
+    /// must be restored separately and before this instance.
+    ///
+    /// Once restored the instance's exports can be called to resume
+    /// execution where it left off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `checkpoint` is malformed or was taken from a
+    /// different module, or if a memory or table can't be grown to its
+    /// checkpointed size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(
+    ///     &engine,
+    ///     r#"
+    ///         (module
+    ///             (global $count (mut i32) (i32.const 0))
+    ///             (func (export "next") (result i32)
+    ///                 (global.set $count (i32.add (global.get $count) (i32.const 1)))
+    ///                 (global.get $count))
+    ///         )
+    ///     "#,
+    /// )?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Instance::new(&mut store, &module, &[])?;
+    /// let next = instance.get_typed_func::<(), i32>(&mut store, "next")?;
+    /// next.call(&mut store, ())?;
+    /// next.call(&mut store, ())?;
+    /// let checkpoint = instance.checkpoint(&mut store)?;
+    ///
+    /// // Later, possibly in another process...
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Instance::new(&mut store, &module, &[])?;
+    /// instance.restore(&mut store, &checkpoint)?;
+    /// let next = instance.get_typed_func::<(), i32>(&mut store, "next")?;
+    /// assert_eq!(next.call(&mut store, ())?, 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn restore(&self, mut store: impl AsContextMut, checkpoint: &[u8]) -> Result<()> {
+        let checkpoint = Checkpoint::decode(checkpoint)?;
+        #[cfg(feature = "gc")]
+        let mut store = crate::RootScope::new(&mut store);
+        let mut store = store.as_context_mut();
+        Restore::new(&mut store, *self).run(&mut store, &checkpoint)
+    }
+}
+
+impl Checkpoint {
+    fn decode(bytes: &[u8]) -> Result<Checkpoint> {
+        let header_len = MAGIC.len() + 4;
+        ensure!(
+            bytes.len() >= header_len && bytes[..MAGIC.len()] == MAGIC[..],
+            "bytes are not a wasmtime instance checkpoint"
+        );
+        let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len].try_into().unwrap());
+        ensure!(
+            version == VERSION,
+            "unsupported checkpoint version {version}, expected {VERSION}"
+        );
+        postcard::from_bytes(&bytes[header_len..]).context("failed to deserialize checkpoint")
+    }
+}
+
+impl Shape {
+    fn of(module: &Module) -> Shape {
+        let env = module.env_module();
+        let len = |n: usize| u32::try_from(n).unwrap();
+        Shape {
+            types: len(module.signatures().as_module_map().len()),
+            functions: len(env.functions.len()),
+            memories: len(env.memories.len()),
+            tables: len(env.tables.len()),
+            globals: len(env.globals.len()),
+            data: len(env.passive_data_map.len()),
+            elements: len(env.passive_elements_map.len()),
+        }
+    }
+}
+
+/// Returns the module that `instance` is an instance of.
+fn instance_module<T>(store: &StoreContextMut<'_, T>, instance: Instance) -> Module {
+    store
+        .0
+        .instance(instance.id())
+        .runtime_module()
+        .expect("core instances always have a module")
+        .clone()
+}
+
+/// State used while capturing a checkpoint.
+struct Capture {
+    instance: Instance,
+    module: Module,
+    /// Lazily-built map from `VMFuncRef` addresses to function indices.
+    funcs: Option<HashMap<usize, u32>>,
+    objects: Vec<Object>,
+    /// Lazily-built map from engine-level types to module-level types.
+    #[cfg(feature = "gc")]
+    types: Option<HashMap<VMSharedTypeIndex, u32>>,
+    /// Map from raw GC references to indices in `objects`.
+    #[cfg(feature = "gc")]
+    object_ids: HashMap<u32, u32>,
+    /// Objects in `objects` whose fields have yet to be captured.
+    #[cfg(feature = "gc")]
+    pending: Vec<(u32, Rooted<AnyRef>)>,
+}
+
+impl Capture {
+    fn new<T>(store: &mut StoreContextMut<'_, T>, instance: Instance) -> Capture {
+        Capture {
+            instance,
+            module: instance_module(store, instance),
+            funcs: None,
+            objects: Vec::new(),
+            #[cfg(feature = "gc")]
+            types: None,
+            #[cfg(feature = "gc")]
+            object_ids: HashMap::new(),
+            #[cfg(feature = "gc")]
+            pending: Vec::new(),
+        }
+    }
+
+    fn run<T>(mut self, store: &mut StoreContextMut<'_, T>) -> Result<Checkpoint> {
+        let env = self.module.env_module().clone();
+        let instance = self.instance;
+
+        let mut memories = Vec::new();
+        for index in env.memories.keys() {
+            if env.defined_memory_index(index).is_none() {
+                continue;
+            }
+            let memory = match instance._get_export(store.0, EntityIndex::Memory(index)) {
+                Extern::Memory(memory) => memory,
+                Extern::SharedMemory(_) => {
+                    bail!("cannot checkpoint shared memory {}", index.as_u32())
+                }
+                _ => unreachable!(),
+            };
+            memories.push(MemoryState {
+                pages: memory.size(&*store),
+                data: nonzero_regions(memory.data(&*store)),
+            });
+        }
+
+        let mut tables = Vec::new();
+        for index in env.tables.keys() {
+            if env.defined_table_index(index).is_none() {
+                continue;
+            }
+            let Extern::Table(table) = instance._get_export(store.0, EntityIndex::Table(index))
+            else {
+                unreachable!()
+            };
+            let mut elements = Vec::new();
+            for i in 0..table.size(&*store) {
+                let element = table.get(&mut *store, i).unwrap();
+                let value = self.capture_ref(store, element).with_context(|| {
+                    format!("cannot checkpoint element {i} of table {}", index.as_u32())
+                })?;
+                elements.push(value);
+            }
+            tables.push(elements);
+        }
+
+        let mut globals = Vec::new();
+        for index in env.globals.keys() {
+            if env.defined_global_index(index).is_none() {
+                continue;
+            }
+            let Extern::Global(global) = instance._get_export(store.0, EntityIndex::Global(index))
+            else {
+                unreachable!()
+            };
+            let mutable = global.ty(&*store).mutability() == Mutability::Var;
+            let value = match global.get(&mut *store) {
+                // Immutable globals are recreated when the module is
+                // instantiated, so they only need to be recorded when they
+                // refer to GC objects whose identity must be preserved.
+                val @ Val::AnyRef(Some(_)) => Some(self.capture_val(store, val)),
+                val if mutable => Some(self.capture_val(store, val)),
+                _ => None,
+            };
+            let value = value
+                .transpose()
+                .with_context(|| format!("cannot checkpoint global {}", index.as_u32()))?;
+            globals.push(value);
+        }
+
+        #[cfg(feature = "gc")]
+        while let Some((id, object)) = self.pending.pop() {
+            let values: Vec<Val> = match object.as_struct(&*store)? {
+                Some(s) => s.fields(&mut *store)?.collect(),
+                None => object.unwrap_array(&*store)?.elems(&mut *store)?.collect(),
+            };
+            let mut fields = Vec::with_capacity(values.len());
+            for value in values {
+                fields.push(self.capture_val(store, value)?);
+            }
+            self.objects[id as usize].fields = fields;
+        }
+
+        let vm = store.0.instance(instance.id());
+        let dropped_data = env
+            .passive_data_map
+            .keys()
+            .filter(|i| vm.is_data_dropped(**i))
+            .map(|i| i.as_u32())
+            .collect();
+        let dropped_elements = env
+            .passive_elements_map
+            .keys()
+            .filter(|i| vm.is_elem_dropped(**i))
+            .map(|i| i.as_u32())
+            .collect();
+
+        Ok(Checkpoint {
+            shape: Shape::of(&self.module),
+            memories,
+            tables,
+            globals,
+            objects: self.objects,
+            dropped_data,
+            dropped_elements,
+        })
+    }
+
+    fn capture_val<T>(&mut self, store: &mut StoreContextMut<'_, T>, val: Val) -> Result<Value> {
+        Ok(match val {
+            Val::I32(x) => Value::I32(x),
+            Val::I64(x) => Value::I64(x),
+            Val::F32(x) => Value::F32(x),
+            Val::F64(x) => Value::F64(x),
+            Val::V128(x) => Value::V128(x.as_u128()),
+            val => self.capture_ref(store, val.ref_().unwrap())?,
+        })
+    }
+
+    fn capture_ref<T>(&mut self, store: &mut StoreContextMut<'_, T>, r: Ref) -> Result<Value> {
+        match r {
+            r if r.is_null() => Ok(Value::Null),
+            Ref::Func(Some(func)) => self.func_index(store, &func).map(Value::Func),
+            #[cfg(feature = "gc")]
+            Ref::Any(Some(any)) => self.capture_anyref(store, any),
+            _ => bail!("host references cannot be checkpointed"),
+        }
+    }
+
+    fn func_index<T>(&mut self, store: &mut StoreContextMut<'_, T>, func: &Func) -> Result<u32> {
+        let key = func.vm_func_ref(store.0).as_ptr().addr();
+        let map = match &mut self.funcs {
+            Some(map) => map,
+            None => {
+                let mut map = HashMap::new();
+                for index in self.module.env_module().functions.keys() {
+                    let Extern::Func(func) = self
+                        .instance
+                        ._get_export(store.0, EntityIndex::Function(index))
+                    else {
+                        unreachable!()
+                    };
+                    map.entry(func.vm_func_ref(store.0).as_ptr().addr())
+                        .or_insert(index.as_u32());
+                }
+                self.funcs.insert(map)
+            }
+        };
+        match map.get(&key) {
+            Some(index) => Ok(*index),
+            None => bail!("reference to a function which is not part of the instance"),
+        }
+    }
+
+    #[cfg(feature = "gc")]
+    fn capture_anyref<T>(
+        &mut self,
+        store: &mut StoreContextMut<'_, T>,
+        any: Rooted<AnyRef>,
+    ) -> Result<Value> {
+        if let Some(i31) = any.as_i31(&*store)? {
+            return Ok(Value::I31(i31.get_u32()));
+        }
+        let key = any.try_gc_ref(store.0)?.as_raw_u32();
+        if let Some(id) = self.object_ids.get(&key) {
+            return Ok(Value::Object(*id));
+        }
+        let ty = if let Some(s) = any.as_struct(&*store)? {
+            s.type_index(store.0)?
+        } else if let Some(a) = any.as_array(&*store)? {
+            a.type_index(store.0)?
+        } else {
+            bail!("host references cannot be checkpointed");
+        };
+        let module = &self.module;
+        let types = self.types.get_or_insert_with(|| {
+            module
+                .signatures()
+                .as_module_map()
+                .iter()
+                .map(|(module_ty, shared_ty)| (*shared_ty, module_ty.as_u32()))
+                .collect()
+        });
+        let Some(ty) = types.get(&ty).copied() else {
+            bail!("GC object has a type which is not defined by the module");
+        };
+
+        let id = u32::try_from(self.objects.len()).unwrap();
+        self.object_ids.insert(key, id);
+        self.objects.push(Object {
+            ty,
+            fields: Vec::new(),
+        });
+        self.pending.push((id, any));
+        Ok(Value::Object(id))
+    }
+}
+
+/// Splits the contents of a linear memory into the regions which contain
+/// non-zero bytes.
+fn nonzero_regions(data: &[u8]) -> Vec<(u64, Vec<u8>)> {
+    let mut regions = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = data[pos..].iter().position(|b| *b != 0) {
+        let start = pos + start;
+        let mut end = start;
+        loop {
+            end += data[end..]
+                .iter()
+                .position(|b| *b == 0)
+                .unwrap_or(data.len() - end);
+            match data[end..].iter().position(|b| *b != 0) {
+                Some(zeros) if zeros < MIN_ZERO_RUN => end += zeros,
+                _ => break,
+            }
+        }
+        regions.push((start as u64, data[start..end].to_vec()));
+        pos = end;
+    }
+    regions
+}
+
+/// State used while restoring a checkpoint.
+struct Restore {
+    instance: Instance,
+    module: Module,
+    /// Restored GC objects, indexed like [`Checkpoint::objects`].
+    #[cfg(feature = "gc")]
+    objects: Vec<Option<Rooted<AnyRef>>>,
+}
+
+impl Restore {
+    fn new<T>(store: &mut StoreContextMut<'_, T>, instance: Instance) -> Restore {
+        Restore {
+            instance,
+            module: instance_module(store, instance),
+            #[cfg(feature = "gc")]
+            objects: Vec::new(),
+        }
+    }
+
+    fn run<T>(mut self, store: &mut StoreContextMut<'_, T>, checkpoint: &Checkpoint) -> Result<()> {
+        ensure!(
+            checkpoint.shape == Shape::of(&self.module),
+            "checkpoint was taken from a different module"
+        );
+        let env = self.module.env_module().clone();
+        let instance = self.instance;
+
+        let defined_memories = env
+            .memories
+            .keys()
+            .filter(|i| env.defined_memory_index(*i).is_some());
+        for (index, state) in defined_memories.zip(&checkpoint.memories) {
+            let Extern::Memory(memory) = instance._get_export(store.0, EntityIndex::Memory(index))
+            else {
+                bail!("cannot restore shared memory {}", index.as_u32());
+            };
+            let size = memory.size(&*store);
+            ensure!(
+                state.pages >= size,
+                "memory {} is larger than its checkpointed size",
+                index.as_u32()
+            );
+            if state.pages > size {
+                memory
+                    .grow(&mut *store, state.pages - size)
+                    .with_context(|| format!("failed to grow memory {}", index.as_u32()))?;
+            }
+            let data = memory.data_mut(&mut *store);
+            data.fill(0);
+            for (offset, bytes) in &state.data {
+                let dst = usize::try_from(*offset)
+                    .ok()
+                    .and_then(|offset| data.get_mut(offset..)?.get_mut(..bytes.len()))
+                    .context("checkpointed memory data is out of bounds")?;
+                dst.copy_from_slice(bytes);
+            }
+        }
+
+        self.restore_objects(store, checkpoint)?;
+
+        let defined_tables = env
+            .tables
+            .keys()
+            .filter(|i| env.defined_table_index(*i).is_some());
+        for (index, elements) in defined_tables.zip(&checkpoint.tables) {
+            let Extern::Table(table) = instance._get_export(store.0, EntityIndex::Table(index))
+            else {
+                unreachable!()
+            };
+            let ty = ValType::Ref(table.ty(&*store).element().clone());
+            let mut refs = Vec::with_capacity(elements.len());
+            for element in elements {
+                refs.push(self.val(store, element, &ty)?.ref_().unwrap());
+            }
+            let size = table.size(&*store);
+            ensure!(
+                refs.len() as u64 >= size,
+                "table {} is larger than its checkpointed size",
+                index.as_u32()
+            );
+            if let Some(init) = refs.get(usize::try_from(size).unwrap()) {
+                table
+                    .grow(&mut *store, refs.len() as u64 - size, init.clone())
+                    .with_context(|| format!("failed to grow table {}", index.as_u32()))?;
+            }
+            for (i, r) in refs.into_iter().enumerate() {
+                table.set(&mut *store, i as u64, r)?;
+            }
+        }
+
+        let defined_globals = env
+            .globals
+            .keys()
+            .filter(|i| env.defined_global_index(*i).is_some());
+        for (index, value) in defined_globals.zip(&checkpoint.globals) {
+            let Extern::Global(global) = instance._get_export(store.0, EntityIndex::Global(index))
+            else {
+                unreachable!()
+            };
+            let ty = global.ty(&*store);
+            let Some(value) = value else { continue };
+            if ty.mutability() == Mutability::Var {
+                let val = self.val(store, value, ty.content())?;
+                global
+                    .set(&mut *store, val)
+                    .with_context(|| format!("failed to restore global {}", index.as_u32()))?;
+            }
+        }
+
+        let mut vm = store.0.instance_mut(instance.id());
+        for index in &checkpoint.dropped_data {
+            vm.as_mut().data_drop(DataIndex::from_u32(*index));
+        }
+        for index in &checkpoint.dropped_elements {
+            vm.as_mut().elem_drop(ElemIndex::from_u32(*index));
+        }
+        Ok(())
+    }
+
+    /// Converts a checkpointed value back into a value of type `ty`.
+    fn val<T>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        value: &Value,
+        ty: &ValType,
+    ) -> Result<Val> {
+        Ok(match *value {
+            Value::I32(x) => Val::I32(x),
+            Value::I64(x) => Val::I64(x),
+            Value::F32(x) => Val::F32(x),
+            Value::F64(x) => Val::F64(x),
+            Value::V128(x) => Val::V128(x.into()),
+            Value::Null => match ty {
+                ValType::Ref(r) => Val::null_ref(r.heap_type()),
+                _ => bail!("checkpoint does not match the module"),
+            },
+            Value::Func(index) => {
+                let index = FuncIndex::from_u32(index);
+                ensure!(
+                    self.module.env_module().functions.get(index).is_some(),
+                    "checkpoint refers to a function which does not exist"
+                );
+                let Extern::Func(func) = self
+                    .instance
+                    ._get_export(store.0, EntityIndex::Function(index))
+                else {
+                    unreachable!()
+                };
+                Val::FuncRef(Some(func))
+            }
+            #[cfg(feature = "gc")]
+            Value::I31(x) => {
+                let i31 = I31::new_u32(x).context("invalid i31ref in checkpoint")?;
+                Val::AnyRef(Some(AnyRef::from_i31(&mut *store, i31)))
+            }
+            #[cfg(feature = "gc")]
+            Value::Object(id) => Val::AnyRef(Some(
+                self.objects
+                    .get(id as usize)
+                    .copied()
+                    .flatten()
+                    .context("checkpoint refers to a GC object which does not exist")?,
+            )),
+            #[cfg(not(feature = "gc"))]
+            Value::I31(_) | Value::Object(_) => {
+                bail!("restoring GC references requires the `gc` feature")
+            }
+        })
+    }
+
+    #[cfg(not(feature = "gc"))]
+    fn restore_objects<T>(
+        &mut self,
+        _store: &mut StoreContextMut<'_, T>,
+        checkpoint: &Checkpoint,
+    ) -> Result<()> {
+        ensure!(
+            checkpoint.objects.is_empty(),
+            "restoring GC objects requires the `gc` feature"
+        );
+        Ok(())
+    }
+
+    /// Reallocates the GC objects of `checkpoint`.
+    ///
+    /// Objects are allocated such that the targets of immutable references
+    /// are allocated before the objects referring to them. Mutable references
+    /// to objects which haven't been allocated yet, which arise from cycles,
+    /// are filled in once all objects exist.
+    #[cfg(feature = "gc")]
+    fn restore_objects<T>(
+        &mut self,
+        store: &mut StoreContextMut<'_, T>,
+        checkpoint: &Checkpoint,
+    ) -> Result<()> {
+        let objects = &checkpoint.objects;
+        self.objects = vec![None; objects.len()];
+        let mut types = Vec::with_capacity(objects.len());
+        for object in objects {
+            let ty = self.object_type(store, object.ty)?;
+            if let ObjectType::Struct(ty) = &ty {
+                ensure!(
+                    ty.fields().len() == object.fields.len(),
+                    "checkpoint does not match the module"
+                );
+            }
+            types.push(ty);
+        }
+        let object_type = |id: usize| types.get(id).context("invalid GC object in checkpoint");
+
+        // Objects referenced by immutable globals already exist in the new
+        // instance, so only their mutable fields need to be restored.
+        let mut needs_update = vec![false; objects.len()];
+        let env = self.module.env_module().clone();
+        let defined_globals = env
+            .globals
+            .keys()
+            .filter(|i| env.defined_global_index(*i).is_some());
+        for (index, value) in defined_globals.zip(&checkpoint.globals) {
+            let Some(Value::Object(id)) = *value else {
+                continue;
+            };
+            let Extern::Global(global) = self
+                .instance
+                ._get_export(store.0, EntityIndex::Global(index))
+            else {
+                unreachable!()
+            };
+            if global.ty(&*store).mutability() == Mutability::Var {
+                continue;
+            }
+            let Val::AnyRef(Some(any)) = global.get(&mut *store) else {
+                bail!("checkpoint does not match the module");
+            };
+            let matches = match object_type(id as usize)? {
+                ObjectType::Struct(ty) => any.as_struct(&*store)?.is_some_and(|s| {
+                    s.type_index(store.0).ok() == Some(ty.registered_type().index())
+                }),
+                ObjectType::Array(ty) => any.as_array(&*store)?.is_some_and(|a| {
+                    a.type_index(store.0).ok() == Some(ty.registered_type().index())
+                }),
+            };
+            ensure!(matches, "checkpoint does not match the module");
+            self.objects[id as usize] = Some(any);
+            needs_update[id as usize] = true;
+        }
+
+        // Depth-first traversal over immutable references, allocating each
+        // object once everything it immutably refers to exists.
+        let mut in_progress = vec![false; objects.len()];
+        for root in 0..objects.len() {
+            let mut stack = vec![(root, 0)];
+            while let Some((id, field)) = stack.pop() {
+                if self.objects[id].is_some() {
+                    continue;
+                }
+                let ty = object_type(id)?;
+                let fields = &objects[id].fields;
+                in_progress[id] = true;
+                if let Some(next) = (field..fields.len()).find(|i| {
+                    ty.field(*i).mutability() == Mutability::Const
+                        && matches!(fields[*i], Value::Object(t)
+                            if self.objects.get(t as usize).is_some_and(|o| o.is_none()))
+                }) {
+                    let Value::Object(target) = fields[next] else {
+                        unreachable!()
+                    };
+                    ensure!(
+                        !in_progress[target as usize],
+                        "checkpoint contains a cycle of immutable references"
+                    );
+                    stack.push((id, next + 1));
+                    stack.push((target as usize, 0));
+                    continue;
+                }
+
+                let mut vals = Vec::with_capacity(fields.len());
+                for (i, value) in fields.iter().enumerate() {
+                    let field = ty.field(i);
+                    let val_ty = field.element_type().unpack();
+                    let val = match *value {
+                        Value::Object(t)
+                            if self.objects.get(t as usize).is_some_and(|o| o.is_none()) =>
+                        {
+                            needs_update[id] = true;
+                            match val_ty {
+                                ValType::Ref(r) if r.is_nullable() => Val::null_ref(r.heap_type()),
+                                _ => bail!("cannot restore a cycle of non-nullable GC references"),
+                            }
+                        }
+                        _ => self.val(store, value, val_ty)?,
+                    };
+                    vals.push(val);
+                }
+                let object = match ty {
+                    ObjectType::Struct(ty) => {
+                        let pre = StructRefPre::new(&mut *store, ty.clone());
+                        StructRef::new(&mut *store, &pre, &vals)?.to_anyref()
+                    }
+                    ObjectType::Array(ty) => {
+                        let pre = ArrayRefPre::new(&mut *store, ty.clone());
+                        ArrayRef::new_fixed(&mut *store, &pre, &vals)?.to_anyref()
+                    }
+                };
+                self.objects[id] = Some(object);
+                in_progress[id] = false;
+            }
+        }
+
+        for (id, object) in objects.iter().enumerate() {
+            if !needs_update[id] {
+                continue;
+            }
+            let ty = object_type(id)?;
+            let any = self.objects[id].unwrap();
+            for (i, value) in object.fields.iter().enumerate() {
+                let field = ty.field(i);
+                if field.mutability() == Mutability::Const {
+                    continue;
+                }
+                let val = self.val(store, value, field.element_type().unpack())?;
+                match ty {
+                    ObjectType::Struct(_) => {
+                        any.unwrap_struct(&*store)?.set_field(&mut *store, i, val)?
+                    }
+                    ObjectType::Array(_) => any.unwrap_array(&*store)?.set(
+                        &mut *store,
+                        u32::try_from(i).unwrap(),
+                        val,
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up the struct or array type with the module-interned index `ty`.
+    #[cfg(feature = "gc")]
+    fn object_type<T>(&self, store: &StoreContextMut<'_, T>, ty: u32) -> Result<ObjectType> {
+        let index = ModuleInternedTypeIndex::from_u32(ty);
+        let shared = self
+            .module
+            .signatures()
+            .shared_type(index)
+            .context("checkpoint refers to a type which does not exist")?;
+        let engine = store.engine();
+        let ty = &self.module.types()[index];
+        Ok(if ty.is_struct() {
+            ObjectType::Struct(StructType::from_shared_type_index(engine, shared))
+        } else if ty.is_array() {
+            ObjectType::Array(ArrayType::from_shared_type_index(engine, shared))
+        } else {
+            bail!("checkpoint does not match the module")
+        })
+    }
+}
+
+#[cfg(feature = "gc")]
+enum ObjectType {
+    Struct(StructType),
+    Array(ArrayType),
+}
+
+#[cfg(feature = "gc")]
+impl ObjectType {
+    fn field(&self, i: usize) -> FieldType {
+        match self {
+            ObjectType::Struct(ty) => ty.field(i).unwrap(),
+            ObjectType::Array(ty) => ty.field_type(),
+        }
+    }
+}
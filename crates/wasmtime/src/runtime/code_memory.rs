@@ -31,6 +31,7 @@ pub struct CodeMemory {
     text: Range<usize>,
     unwind: Range<usize>,
     trap_data: Range<usize>,
+    exception_data: Range<usize>,
     wasm_data: Range<usize>,
     address_map_data: Range<usize>,
     stack_map_data: Range<usize>,
@@ -119,6 +120,7 @@ impl CodeMemory {
         #[cfg(feature = "debug-builtins")]
         let mut has_native_debug_info = false;
         let mut trap_data = 0..0;
+        let mut exception_data = 0..0;
         let mut wasm_data = 0..0;
         let mut address_map_data = 0..0;
         let mut stack_map_data = 0..0;
@@ -168,6 +170,7 @@ impl CodeMemory {
                 obj::ELF_WASMTIME_ADDRMAP => address_map_data = range,
                 obj::ELF_WASMTIME_STACK_MAP => stack_map_data = range,
                 obj::ELF_WASMTIME_TRAPS => trap_data = range,
+                obj::ELF_WASMTIME_EXCEPTIONS => exception_data = range,
                 obj::ELF_NAME_DATA => func_name_data = range,
                 obj::ELF_WASMTIME_INFO => info_data = range,
                 obj::ELF_WASMTIME_DWARF => wasm_dwarf = range,
@@ -198,6 +201,7 @@ impl CodeMemory {
             text,
             unwind,
             trap_data,
+            exception_data,
             address_map_data,
             stack_map_data,
             func_name_data,
@@ -269,6 +273,13 @@ impl CodeMemory {
         &self.mmap[self.trap_data.clone()]
     }
 
+    /// Returns the contents of the `ELF_WASMTIME_EXCEPTIONS` section, or an
+    /// empty slice if it wasn't found.
+    #[inline]
+    pub fn exception_data(&self) -> &[u8] {
+        &self.mmap[self.exception_data.clone()]
+    }
+
     /// Publishes the internal ELF image to be ready for execution.
     ///
     /// This method can only be called once and will panic if called twice. This
@@ -272,6 +272,8 @@ where
     }
 
     let types = instance.id().get(store.0).component().types().clone();
+    #[cfg(feature = "record-replay")]
+    let ty_index = ty;
     let ty = &types[ty];
     let param_tys = InterfaceType::Tuple(ty.params);
     let result_tys = InterfaceType::Tuple(ty.results);
@@ -349,24 +351,61 @@ where
             );
         }
     } else {
-        let mut storage = Storage::<'_, Params, Return>::new_sync(storage);
+        let mut typed_storage = Storage::<'_, Params, Return>::new_sync(&mut *storage);
         let mut lift = LiftContext::new(store.0.store_opaque_mut(), &options, instance);
         lift.enter_call();
-        let params = storage.lift_params(&mut lift, param_tys)?;
+        let params = typed_storage.lift_params(&mut lift, param_tys)?;
+
+        #[cfg(feature = "record-replay")]
+        let mut recording = match crate::record_replay::begin_component_host_call(
+            store.0,
+            ty_index,
+            caller_instance,
+            async_,
+        )? {
+            crate::record_replay::ComponentHostCall::Call(recording) => recording,
+            crate::record_replay::ComponentHostCall::Replay(effects) => {
+                drop(params);
+                let flat_results = types[ty.results].abi.flat_count(MAX_FLAT_RESULTS);
+                flags.set_may_leave(false);
+                let mut lower = LowerContext::new(store, &options, &types, instance);
+                crate::record_replay::replay_lowering(
+                    &mut lower,
+                    effects,
+                    &mut storage[..flat_results.unwrap_or(0)],
+                )?;
+                flags.set_may_leave(true);
+                lower.exit_call()?;
+                return Ok(());
+            }
+        };
 
         let ret = match closure(store.as_context_mut(), instance, params) {
-            HostResult::Done(result) => result?,
+            HostResult::Done(result) => result,
             #[cfg(feature = "component-model-async")]
             HostResult::Future(future) => {
-                instance.poll_and_block(store.0.traitobj_mut(), future, caller_instance)?
+                instance.poll_and_block(store.0.traitobj_mut(), future, caller_instance)
             }
         };
+        #[cfg(feature = "record-replay")]
+        let ret = recording.returned(store.0, &options, ret);
+        let ret = ret?;
 
         flags.set_may_leave(false);
         let mut lower = LowerContext::new(store, &options, &types, instance);
-        storage.lower_results(&mut lower, result_tys, ret)?;
+        typed_storage.lower_results(&mut lower, result_tys, ret)?;
         flags.set_may_leave(true);
         lower.exit_call()?;
+
+        #[cfg(feature = "record-replay")]
+        {
+            let flat_results = types[ty.results].abi.flat_count(MAX_FLAT_RESULTS);
+            recording.lowered(
+                lower.store.0,
+                &options,
+                &storage[..flat_results.unwrap_or(0)],
+            )?;
+        }
     }
 
     return Ok(());
@@ -832,14 +871,40 @@ where
             );
         }
     } else {
+        #[cfg(feature = "record-replay")]
+        let mut recording = match crate::record_replay::begin_component_host_call(
+            store.0,
+            ty,
+            caller_instance,
+            async_,
+        )? {
+            crate::record_replay::ComponentHostCall::Call(recording) => recording,
+            crate::record_replay::ComponentHostCall::Replay(effects) => {
+                drop(params_and_results);
+                let flat_results = result_tys.abi.flat_count(MAX_FLAT_RESULTS);
+                flags.set_may_leave(false);
+                let mut cx = LowerContext::new(store, &options, &types, instance);
+                crate::record_replay::replay_lowering(
+                    &mut cx,
+                    effects,
+                    &mut storage[..flat_results.unwrap_or(0)],
+                )?;
+                flags.set_may_leave(true);
+                cx.exit_call()?;
+                return Ok(());
+            }
+        };
+
         let future = closure(
             store.as_context_mut(),
             instance,
             params_and_results,
             result_start,
         );
-        let result_vals =
-            instance.poll_and_block(store.0.traitobj_mut(), future, caller_instance)?;
+        let result_vals = instance.poll_and_block(store.0.traitobj_mut(), future, caller_instance);
+        #[cfg(feature = "record-replay")]
+        let result_vals = recording.returned(store.0, &options, result_vals);
+        let result_vals = result_vals?;
         let result_vals = &result_vals[result_start..];
 
         flags.set_may_leave(false);
@@ -863,6 +928,12 @@ where
         flags.set_may_leave(true);
 
         cx.exit_call()?;
+
+        #[cfg(feature = "record-replay")]
+        {
+            let flat_results = result_tys.abi.flat_count(MAX_FLAT_RESULTS);
+            recording.lowered(cx.store.0, &options, &storage[..flat_results.unwrap_or(0)])?;
+        }
     }
 
     Ok(())
@@ -129,6 +129,12 @@ impl Options {
         Ok((result_slice, result))
     }
 
+    /// Returns whether this function has an associated memory attached to it.
+    #[cfg(feature = "record-replay")]
+    pub(crate) fn has_memory(&self) -> bool {
+        self.memory.is_some()
+    }
+
     /// Asserts that this function has an associated memory attached to it and
     /// then returns the slice of memory tied to the lifetime of the provided
     /// store.
@@ -272,7 +278,8 @@ impl<'a, T: 'static> LowerContext<'a, T> {
         new_size: usize,
     ) -> Result<usize> {
         let realloc_func_ty = Arc::clone(self.instance().component().realloc_func_ty());
-        self.options
+        let ptr = self
+            .options
             .realloc(
                 &mut self.store,
                 &realloc_func_ty,
@@ -281,7 +288,18 @@ impl<'a, T: 'static> LowerContext<'a, T> {
                 old_align,
                 new_size,
             )
-            .map(|(_, ptr)| ptr)
+            .map(|(_, ptr)| ptr)?;
+        #[cfg(feature = "record-replay")]
+        self.store
+            .0
+            .record_lowering(|| crate::record_replay::LoweringEffect::Realloc {
+                old: old as u64,
+                old_size: old_size as u64,
+                old_align,
+                new_size: new_size as u64,
+                ptr: ptr as u64,
+            });
+        Ok(ptr)
     }
 
     /// Returns a fixed mutable slice of memory `N` bytes large starting at
@@ -317,7 +335,16 @@ impl<'a, T: 'static> LowerContext<'a, T> {
         ty: TypeResourceTableIndex,
         rep: u32,
     ) -> Result<u32> {
-        self.resource_tables().guest_resource_lower_own(rep, ty)
+        let index = self.resource_tables().guest_resource_lower_own(rep, ty)?;
+        #[cfg(feature = "record-replay")]
+        self.store
+            .0
+            .record_lowering(|| crate::record_replay::LoweringEffect::ResourceOwn {
+                ty: ty.as_u32(),
+                rep,
+                index,
+            });
+        Ok(index)
     }
 
     /// Lowers a `borrow` resource into the guest, converting the `rep` to a
@@ -8,6 +8,8 @@ use crate::component::{
 use crate::instance::OwnedImports;
 use crate::linker::DefinitionType;
 use crate::prelude::*;
+#[cfg(feature = "record-replay")]
+use crate::record_replay::HostCallLog;
 use crate::runtime::vm::component::{
     CallContexts, ComponentInstance, ResourceTables, TypedResource, TypedResourceIndex,
 };
@@ -927,6 +929,8 @@ pub struct InstancePre<T: 'static> {
     component: Component,
     imports: Arc<PrimaryMap<RuntimeImportIndex, RuntimeImport>>,
     resource_types: Arc<PrimaryMap<ResourceIndex, ResourceType>>,
+    #[cfg(feature = "record-replay")]
+    host_call_log: Option<HostCallLog>,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -937,6 +941,8 @@ impl<T: 'static> Clone for InstancePre<T> {
             component: self.component.clone(),
             imports: self.imports.clone(),
             resource_types: self.resource_types.clone(),
+            #[cfg(feature = "record-replay")]
+            host_call_log: self.host_call_log.clone(),
             _marker: self._marker,
         }
     }
@@ -958,10 +964,18 @@ impl<T: 'static> InstancePre<T> {
             component,
             imports,
             resource_types,
+            #[cfg(feature = "record-replay")]
+            host_call_log: None,
             _marker: marker::PhantomData,
         }
     }
 
+    #[cfg(feature = "record-replay")]
+    pub(crate) fn with_host_call_log(mut self, log: Option<HostCallLog>) -> InstancePre<T> {
+        self.host_call_log = log;
+        self
+    }
+
     /// Returns the underlying component that will be instantiated.
     pub fn component(&self) -> &Component {
         &self.component
@@ -1016,6 +1030,10 @@ impl<T: 'static> InstancePre<T> {
 
     fn instantiate_impl(&self, mut store: impl AsContextMut<Data = T>) -> Result<Instance> {
         let mut store = store.as_context_mut();
+        #[cfg(feature = "record-replay")]
+        if let Some(log) = &self.host_call_log {
+            store.0.set_host_call_log(log)?;
+        }
         store
             .engine()
             .allocator()
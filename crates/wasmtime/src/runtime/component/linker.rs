@@ -9,6 +9,8 @@ use crate::component::{
 };
 use crate::hash_map::HashMap;
 use crate::prelude::*;
+#[cfg(feature = "record-replay")]
+use crate::record_replay::{HostCallLog, HostCallRecorder, HostCallReplayer};
 use crate::{AsContextMut, Engine, Module, StoreContextMut};
 use alloc::sync::Arc;
 use core::marker;
@@ -64,6 +66,8 @@ pub struct Linker<T: 'static> {
     map: NameMap<usize, Definition>,
     path: Vec<usize>,
     allow_shadowing: bool,
+    #[cfg(feature = "record-replay")]
+    host_call_log: Option<HostCallLog>,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -75,6 +79,8 @@ impl<T: 'static> Clone for Linker<T> {
             map: self.map.clone(),
             path: self.path.clone(),
             allow_shadowing: self.allow_shadowing,
+            #[cfg(feature = "record-replay")]
+            host_call_log: self.host_call_log.clone(),
             _marker: self._marker,
         }
     }
@@ -119,6 +125,8 @@ impl<T: 'static> Linker<T> {
             map: NameMap::default(),
             allow_shadowing: false,
             path: Vec::new(),
+            #[cfg(feature = "record-replay")]
+            host_call_log: None,
             _marker: marker::PhantomData,
         }
     }
@@ -137,6 +145,39 @@ impl<T: 'static> Linker<T> {
         self
     }
 
+    /// Records the results of all host calls made by instances created with
+    /// this linker into the trace of `recorder`.
+    ///
+    /// Every call from a component to a host function defined in this linker
+    /// is logged along with the results lowered into the component. The trace
+    /// can later be fed back to the same component with
+    /// [`Linker::replay_host_calls`] to deterministically reproduce this
+    /// execution. See [`HostCallRecorder`] for more information.
+    ///
+    /// The recorder is attached to each [`Store`](crate::Store) that an
+    /// instance is created in, and a store can only use one trace. This
+    /// replaces any previous call to [`Linker::replay_host_calls`].
+    #[cfg(feature = "record-replay")]
+    pub fn record_host_calls(&mut self, recorder: HostCallRecorder) -> &mut Self {
+        self.host_call_log = Some(HostCallLog::Record(recorder));
+        self
+    }
+
+    /// Replays host calls from the trace of `replayer` instead of calling the
+    /// host for all instances created with this linker.
+    ///
+    /// Host functions defined in this linker are not invoked at all; instead
+    /// each call from a component receives the results recorded by
+    /// [`Linker::record_host_calls`]. Calls which diverge from the trace trap.
+    /// See [`HostCallReplayer`] for more information.
+    ///
+    /// This replaces any previous call to [`Linker::record_host_calls`].
+    #[cfg(feature = "record-replay")]
+    pub fn replay_host_calls(&mut self, replayer: HostCallReplayer) -> &mut Self {
+        self.host_call_log = Some(HostCallLog::Replay(replayer));
+        self
+    }
+
     /// Returns the "root instance" of this linker, used to define names into
     /// the root namespace.
     pub fn root(&mut self) -> LinkerInstance<'_, T> {
@@ -254,9 +295,12 @@ impl<T: 'static> Linker<T> {
             let i = imports.push(import);
             assert_eq!(i, idx);
         }
-        Ok(unsafe {
+        let pre = unsafe {
             InstancePre::new_unchecked(component.clone(), Arc::new(imports), imported_resources)
-        })
+        };
+        #[cfg(feature = "record-replay")]
+        let pre = pre.with_host_call_log(self.host_call_log.clone());
+        Ok(pre)
     }
 
     /// Instantiates the [`Component`] provided into the `store` specified.
@@ -0,0 +1,279 @@
+//! Native debugging of guest WebAssembly: breakpoints, single-stepping, and
+//! inspection of paused frames.
+//!
+//! Guest debugging requires code to be compiled with
+//! [`Config::guest_debug`](crate::Config::guest_debug). The compiled code
+//! checks a flag in the store before every instruction and calls into the
+//! runtime when the flag is set, which only happens while the store has a
+//! debug handler and at least one breakpoint or single-stepping enabled.
+
+use crate::hash_set::HashSet;
+use crate::prelude::*;
+use crate::runtime::vm::{CompiledModuleId, VMStoreContext};
+use crate::store::{AutoAssertNoGc, InstanceId, StoreOpaque};
+use crate::{AsContextMut, Extern, Global, Instance, Module, Val, ValRaw, ValType};
+use wasmtime_environ::{DebugValueKind, EntityIndex, FuncIndex, GlobalIndex};
+
+/// Why execution of WebAssembly paused and the store's debug handler was
+/// invoked.
+///
+/// See [`Store::debug_handler`](crate::Store::debug_handler).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugReason {
+    /// Execution reached a breakpoint registered with
+    /// [`Store::add_breakpoint`](crate::Store::add_breakpoint).
+    Breakpoint,
+    /// Execution reached the next instruction while single-stepping.
+    Step,
+}
+
+/// What a store's debug handler wants to happen once it returns.
+///
+/// See [`Store::debug_handler`](crate::Store::debug_handler).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Resume execution until the next breakpoint is reached.
+    ///
+    /// This disables single-stepping.
+    Continue,
+    /// Resume execution and pause again before the next WebAssembly
+    /// instruction, possibly in a different function.
+    ///
+    /// This enables single-stepping.
+    Step,
+}
+
+/// A WebAssembly frame that is paused at a breakpoint or single-step.
+///
+/// This is passed to the handler configured with
+/// [`Store::debug_handler`](crate::Store::debug_handler) and describes the
+/// instruction that is about to execute, along with the values of the
+/// function's locals and operand stack at that point.
+#[derive(Debug)]
+pub struct DebugFrame {
+    reason: DebugReason,
+    instance: Instance,
+    module: Module,
+    func_index: FuncIndex,
+    module_offset: u32,
+    func_offset: u32,
+    locals: Vec<Val>,
+    stack: Vec<Val>,
+}
+
+impl DebugFrame {
+    /// Create a frame from the values that compiled code spilled for the
+    /// `debug_hook` builtin.
+    ///
+    /// Any GC references are rooted in the store's current LIFO scope.
+    pub(crate) fn new(
+        store: &mut StoreOpaque,
+        reason: DebugReason,
+        instance: InstanceId,
+        func_index: FuncIndex,
+        module_offset: u32,
+        values: &[ValRaw],
+        kinds: &[u8],
+        num_locals: usize,
+    ) -> DebugFrame {
+        let instance = Instance::from_wasmtime(instance, store);
+        let module = instance._module(store).clone();
+        let func_start = module
+            .env_module()
+            .defined_func_index(func_index)
+            .and_then(|i| module.compiled_module().func_start_srcloc(i).file_offset())
+            .unwrap_or(0);
+
+        let mut store = AutoAssertNoGc::new(store);
+        let mut vals = values.iter().zip(kinds).map(|(raw, kind)| {
+            let ty = match DebugValueKind::from_u8(*kind).unwrap() {
+                DebugValueKind::I32 => ValType::I32,
+                DebugValueKind::I64 => ValType::I64,
+                DebugValueKind::F32 => ValType::F32,
+                DebugValueKind::F64 => ValType::F64,
+                DebugValueKind::V128 => ValType::V128,
+                DebugValueKind::FuncRef => ValType::FUNCREF,
+                DebugValueKind::ExternRef => ValType::EXTERNREF,
+                DebugValueKind::AnyRef => ValType::ANYREF,
+                DebugValueKind::ExnRef => ValType::EXNREF,
+            };
+            // SAFETY: compiled code spilled a valid value of the type
+            // described by its tag byte.
+            unsafe { Val::_from_raw(&mut store, *raw, &ty) }
+        });
+        let locals = vals.by_ref().take(num_locals).collect();
+        let stack = vals.collect();
+
+        DebugFrame {
+            reason,
+            instance,
+            module,
+            func_index,
+            module_offset,
+            func_offset: module_offset.saturating_sub(func_start),
+            locals,
+            stack,
+        }
+    }
+
+    /// Why execution paused at this frame.
+    pub fn reason(&self) -> DebugReason {
+        self.reason
+    }
+
+    /// The instance that this frame's function belongs to.
+    pub fn instance(&self) -> Instance {
+        self.instance
+    }
+
+    /// The module that this frame's function is defined in.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// The index of this frame's function within its module's function index
+    /// space.
+    pub fn func_index(&self) -> u32 {
+        self.func_index.as_u32()
+    }
+
+    /// The byte offset, within the original wasm module, of the instruction
+    /// that is about to execute.
+    ///
+    /// This is the same offset space as [`FrameInfo::module_offset`] and the
+    /// one used by [`Store::add_breakpoint`](crate::Store::add_breakpoint).
+    ///
+    /// [`FrameInfo::module_offset`]: crate::FrameInfo::module_offset
+    pub fn module_offset(&self) -> u32 {
+        self.module_offset
+    }
+
+    /// The byte offset, relative to the start of this frame's function, of
+    /// the instruction that is about to execute.
+    ///
+    /// This is the same offset space as
+    /// [`FrameInfo::func_offset`](crate::FrameInfo::func_offset).
+    pub fn func_offset(&self) -> u32 {
+        self.func_offset
+    }
+
+    /// The current values of this function's locals, including its
+    /// parameters.
+    pub fn locals(&self) -> &[Val] {
+        &self.locals
+    }
+
+    /// The current values on this function's operand stack, from the bottom
+    /// of the stack to the top.
+    pub fn stack(&self) -> &[Val] {
+        &self.stack
+    }
+
+    /// Get the global at `index` in the global index space of this frame's
+    /// instance.
+    ///
+    /// Unlike [`Instance::get_global`] this gives access to globals that are
+    /// not exported.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this frame's instance.
+    pub fn global(&self, mut store: impl AsContextMut, index: u32) -> Option<Global> {
+        let store = store.as_context_mut().0;
+        if index >= self.num_globals() {
+            return None;
+        }
+        let entity = EntityIndex::Global(GlobalIndex::from_u32(index));
+        match self.instance._get_export(store, entity) {
+            Extern::Global(g) => Some(g),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The number of globals in this frame's instance, including imported
+    /// globals.
+    pub fn num_globals(&self) -> u32 {
+        u32::try_from(self.module.env_module().globals.len()).unwrap()
+    }
+}
+
+/// Per-store guest debugging state: the registered breakpoints and whether
+/// single-stepping is enabled.
+#[derive(Default)]
+pub(crate) struct DebugState {
+    breakpoints: HashSet<(CompiledModuleId, FuncIndex, u32)>,
+    single_step: bool,
+}
+
+impl DebugState {
+    pub(crate) fn add_breakpoint(
+        &mut self,
+        module: &Module,
+        func_index: u32,
+        offset: u32,
+    ) -> Result<()> {
+        if !module.engine().tunables().debug_guest {
+            bail!("breakpoints require `Config::guest_debug` to be enabled");
+        }
+        let env_module = module.env_module();
+        let func = FuncIndex::from_u32(func_index);
+        if !env_module.functions.is_valid(func) {
+            bail!("function index {func_index} is out of bounds");
+        }
+        if env_module.is_imported_function(func) {
+            bail!("cannot set a breakpoint in imported function {func_index}");
+        }
+        self.breakpoints.insert((module.id(), func, offset));
+        Ok(())
+    }
+
+    pub(crate) fn remove_breakpoint(
+        &mut self,
+        module: &Module,
+        func_index: u32,
+        offset: u32,
+    ) -> bool {
+        self.breakpoints
+            .remove(&(module.id(), FuncIndex::from_u32(func_index), offset))
+    }
+
+    pub(crate) fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub(crate) fn set_single_step(&mut self, enable: bool) {
+        self.single_step = enable;
+    }
+
+    pub(crate) fn apply(&mut self, action: DebugAction) {
+        self.single_step = match action {
+            DebugAction::Continue => false,
+            DebugAction::Step => true,
+        };
+    }
+
+    /// Update the flag that compiled code checks before every instruction.
+    pub(crate) fn sync(&self, vm_store_context: &mut VMStoreContext, has_handler: bool) {
+        let active = has_handler && (self.single_step || !self.breakpoints.is_empty());
+        *vm_store_context.debug_active.get_mut() = u32::from(active);
+    }
+
+    /// Determine whether execution should pause before the instruction at
+    /// `offset` in `func_index`.
+    pub(crate) fn pause_reason(
+        &self,
+        module: CompiledModuleId,
+        func_index: FuncIndex,
+        offset: u32,
+    ) -> Option<DebugReason> {
+        if self.single_step {
+            Some(DebugReason::Step)
+        } else if self.breakpoints.contains(&(module, func_index, offset)) {
+            Some(DebugReason::Breakpoint)
+        } else {
+            None
+        }
+    }
+}
@@ -494,7 +494,7 @@ impl Table {
     /// `StoreData` multiple times and becomes multiple `wasmtime::Table`s,
     /// this hash key will be consistent across all of these tables.
     #[cfg_attr(
-        not(test),
+        not(any(test, feature = "pre-initialize")),
         expect(dead_code, reason = "Not used yet, but added for consistency")
     )]
     pub(crate) fn hash_key(&self, store: &StoreOpaque) -> impl core::hash::Hash + Eq + use<'_> {
@@ -2302,11 +2302,28 @@ impl HostContext {
             let state = &*(state as *const _ as *const HostFuncState<F>);
             let func = &state.func;
 
+            #[cfg(feature = "record-replay")]
+            let mut host_call = crate::record_replay::CoreHostCall::Call;
             let ret = 'ret: {
                 if let Err(trap) = caller.store.0.call_hook(CallHook::CallingHost) {
                     break 'ret R::fallible_from_error(trap);
                 }
 
+                #[cfg(feature = "record-replay")]
+                match crate::record_replay::begin_core_host_call(
+                    &mut caller.store,
+                    caller.caller,
+                    vmctx.as_ref().func_ref().type_index,
+                    args.as_mut(),
+                ) {
+                    Ok(crate::record_replay::CoreHostCall::Replayed) => {
+                        caller.store.0.call_hook(CallHook::ReturningFromHost)?;
+                        return Ok(());
+                    }
+                    Ok(call) => host_call = call,
+                    Err(trap) => break 'ret R::fallible_from_error(trap),
+                }
+
                 let mut store = if P::may_gc() {
                     AutoAssertNoGc::new(caller.store.0)
                 } else {
@@ -2324,17 +2341,22 @@ impl HostContext {
                 r.into_fallible()
             };
 
-            if !ret.compatible_with_store(caller.store.0) {
-                bail!("host function attempted to return cross-`Store` value to Wasm")
+            let result = if !ret.compatible_with_store(caller.store.0) {
+                Err(anyhow!(
+                    "host function attempted to return cross-`Store` value to Wasm"
+                ))
             } else {
                 let mut store = if R::may_gc() {
                     AutoAssertNoGc::new(caller.store.0)
                 } else {
                     unsafe { AutoAssertNoGc::disabled(caller.store.0) }
                 };
-                let ret = ret.store(&mut store, args.as_mut())?;
-                Ok(ret)
-            }
+                ret.store(&mut store, args.as_mut())
+            };
+
+            #[cfg(feature = "record-replay")]
+            host_call.finish(&mut caller.store, args.as_ref(), result.as_ref())?;
+            result
         };
 
         // With nothing else on the stack move `run` into this
@@ -2406,10 +2428,32 @@ impl HostFunc {
         T: 'static,
     {
         assert!(ty.comes_from_same_engine(engine));
+        #[cfg(feature = "record-replay")]
+        let type_index = ty.type_index();
         let func = move |caller_vmctx, values: &mut [ValRaw]| {
             Caller::<T>::with(caller_vmctx, |mut caller| {
                 caller.store.0.call_hook(CallHook::CallingHost)?;
-                let result = func(caller.sub_caller(), values)?;
+                #[cfg(feature = "record-replay")]
+                let host_call = match crate::record_replay::begin_core_host_call(
+                    &mut caller.store,
+                    caller.caller,
+                    type_index,
+                    crate::record_replay::as_uninit(values),
+                )? {
+                    crate::record_replay::CoreHostCall::Replayed => {
+                        caller.store.0.call_hook(CallHook::ReturningFromHost)?;
+                        return Ok(());
+                    }
+                    call => call,
+                };
+                let result = func(caller.sub_caller(), values);
+                #[cfg(feature = "record-replay")]
+                host_call.finish(
+                    &mut caller.store,
+                    crate::record_replay::as_uninit(values),
+                    result.as_ref(),
+                )?;
+                let result = result?;
                 caller.store.0.call_hook(CallHook::ReturningFromHost)?;
                 Ok(result)
             })
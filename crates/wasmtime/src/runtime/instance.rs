@@ -1,5 +1,7 @@
 use crate::linker::{Definition, DefinitionType};
 use crate::prelude::*;
+#[cfg(feature = "record-replay")]
+use crate::record_replay::HostCallLog;
 use crate::runtime::vm::{
     self, Imports, ModuleRuntimeInfo, VMFuncRef, VMFunctionImport, VMGlobalImport, VMMemoryImport,
     VMTableImport, VMTagImport,
@@ -346,7 +348,7 @@ impl Instance {
         self._module(store.into().0)
     }
 
-    fn _module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
+    pub(crate) fn _module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
         store.module_for_instance(self.id).unwrap()
     }
 
@@ -430,7 +432,7 @@ impl Instance {
         Some(self._get_export(store, export.entity))
     }
 
-    fn _get_export(&self, store: &mut StoreOpaque, entity: EntityIndex) -> Extern {
+    pub(crate) fn _get_export(&self, store: &mut StoreOpaque, entity: EntityIndex) -> Extern {
         let id = store.id();
         // SAFETY: the store `id` owns this instance and all exports contained
         // within.
@@ -574,7 +576,7 @@ impl Instance {
     /// Returns both exported and non-exported memories.
     ///
     /// Gives access to the full memories space.
-    #[cfg(feature = "coredump")]
+    #[cfg(any(feature = "coredump", feature = "record-replay"))]
     pub(crate) fn all_memories<'a>(
         &'a self,
         store: &'a StoreOpaque,
@@ -729,6 +731,11 @@ pub struct InstancePre<T> {
     /// This is an `Arc<[T]>` for the same reason as `items`.
     func_refs: Arc<[VMFuncRef]>,
 
+    /// The trace that instances' host calls are recorded to or replayed from,
+    /// as configured on the `Linker` that created this.
+    #[cfg(feature = "record-replay")]
+    host_call_log: Option<HostCallLog>,
+
     _marker: core::marker::PhantomData<fn() -> T>,
 }
 
@@ -740,6 +747,8 @@ impl<T> Clone for InstancePre<T> {
             items: self.items.clone(),
             host_funcs: self.host_funcs,
             func_refs: self.func_refs.clone(),
+            #[cfg(feature = "record-replay")]
+            host_call_log: self.host_call_log.clone(),
             _marker: self._marker,
         }
     }
@@ -784,10 +793,18 @@ impl<T: 'static> InstancePre<T> {
             items: items.into(),
             host_funcs,
             func_refs: func_refs.into(),
+            #[cfg(feature = "record-replay")]
+            host_call_log: None,
             _marker: core::marker::PhantomData,
         })
     }
 
+    #[cfg(feature = "record-replay")]
+    pub(crate) fn with_host_call_log(mut self, log: Option<HostCallLog>) -> InstancePre<T> {
+        self.host_call_log = log;
+        self
+    }
+
     /// Returns a reference to the module that this [`InstancePre`] will be
     /// instantiating.
     pub fn module(&self) -> &Module {
@@ -811,6 +828,10 @@ impl<T: 'static> InstancePre<T> {
     /// [`Engine`] than the [`InstancePre`] originally came from.
     pub fn instantiate(&self, mut store: impl AsContextMut<Data = T>) -> Result<Instance> {
         let mut store = store.as_context_mut();
+        #[cfg(feature = "record-replay")]
+        if let Some(log) = &self.host_call_log {
+            store.0.set_host_call_log(log)?;
+        }
         let imports = pre_instantiate_raw(
             &mut store.0,
             &self.module,
@@ -841,6 +862,10 @@ impl<T: 'static> InstancePre<T> {
         mut store: impl AsContextMut<Data: Send>,
     ) -> Result<Instance> {
         let mut store = store.as_context_mut();
+        #[cfg(feature = "record-replay")]
+        if let Some(log) = &self.host_call_log {
+            store.0.set_host_call_log(log)?;
+        }
         let imports = pre_instantiate_raw(
             &mut store.0,
             &self.module,
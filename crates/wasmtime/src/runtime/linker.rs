@@ -1,6 +1,8 @@
 use crate::func::HostFunc;
 use crate::hash_map::{Entry, HashMap};
 use crate::instance::InstancePre;
+#[cfg(feature = "record-replay")]
+use crate::record_replay::{HostCallLog, HostCallRecorder, HostCallReplayer};
 use crate::store::StoreOpaque;
 use crate::{
     AsContext, AsContextMut, Caller, Engine, Extern, ExternType, Func, FuncType, ImportType,
@@ -87,6 +89,8 @@ pub struct Linker<T> {
     map: HashMap<ImportKey, Definition>,
     allow_shadowing: bool,
     allow_unknown_exports: bool,
+    #[cfg(feature = "record-replay")]
+    host_call_log: Option<HostCallLog>,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -105,6 +109,8 @@ impl<T> Clone for Linker<T> {
             map: self.map.clone(),
             allow_shadowing: self.allow_shadowing,
             allow_unknown_exports: self.allow_unknown_exports,
+            #[cfg(feature = "record-replay")]
+            host_call_log: self.host_call_log.clone(),
             _marker: self._marker,
         }
     }
@@ -153,6 +159,8 @@ impl<T> Linker<T> {
             strings: Vec::new(),
             allow_shadowing: false,
             allow_unknown_exports: false,
+            #[cfg(feature = "record-replay")]
+            host_call_log: None,
             _marker: marker::PhantomData,
         }
     }
@@ -219,6 +227,40 @@ impl<T> Linker<T> {
         self
     }
 
+    /// Records the results of all host calls made by instances created with
+    /// this linker into the trace of `recorder`.
+    ///
+    /// Every call from WebAssembly to a host function, whether defined with
+    /// [`Linker::func_wrap`], [`Linker::func_new`], or otherwise, is logged
+    /// along with its results and the linear memory it wrote to. The trace can
+    /// later be fed back to the same WebAssembly with
+    /// [`Linker::replay_host_calls`] to deterministically reproduce this
+    /// execution. See [`HostCallRecorder`] for more information.
+    ///
+    /// The recorder is attached to each [`Store`](crate::Store) that an
+    /// instance is created in, and a store can only use one trace. This
+    /// replaces any previous call to [`Linker::replay_host_calls`].
+    #[cfg(feature = "record-replay")]
+    pub fn record_host_calls(&mut self, recorder: HostCallRecorder) -> &mut Self {
+        self.host_call_log = Some(HostCallLog::Record(recorder));
+        self
+    }
+
+    /// Replays host calls from the trace of `replayer` instead of calling the
+    /// host for all instances created with this linker.
+    ///
+    /// Host functions defined in this linker are not invoked at all; instead
+    /// each call from WebAssembly receives the results recorded by
+    /// [`Linker::record_host_calls`]. Calls which diverge from the trace trap.
+    /// See [`HostCallReplayer`] for more information.
+    ///
+    /// This replaces any previous call to [`Linker::record_host_calls`].
+    #[cfg(feature = "record-replay")]
+    pub fn replay_host_calls(&mut self, replayer: HostCallReplayer) -> &mut Self {
+        self.host_call_log = Some(HostCallLog::Replay(replayer));
+        self
+    }
+
     /// Implement any imports of the given [`Module`] with a function which traps.
     ///
     /// By default a [`Linker`] will error when unknown imports are encountered
@@ -1225,7 +1267,10 @@ impl<T> Linker<T> {
                 import.update_size(store);
             }
         }
-        unsafe { InstancePre::new(module, imports) }
+        let pre = unsafe { InstancePre::new(module, imports)? };
+        #[cfg(feature = "record-replay")]
+        let pre = pre.with_host_call_log(self.host_call_log.clone());
+        Ok(pre)
     }
 
     /// Returns an iterator over all items defined in this `Linker`, in
@@ -673,7 +673,7 @@ impl Memory {
     /// Even if the same underlying memory definition is added to the
     /// `StoreData` multiple times and becomes multiple `wasmtime::Memory`s,
     /// this hash key will be consistent across all of these memories.
-    #[cfg(feature = "coredump")]
+    #[cfg(any(feature = "coredump", feature = "pre-initialize"))]
     pub(crate) fn hash_key(&self, store: &StoreOpaque) -> impl core::hash::Hash + Eq + use<> {
         store[self.instance].memory_ptr(self.index).as_ptr().addr()
     }
@@ -23,8 +23,12 @@ use wasmtime_environ::{
     VMSharedTypeIndex,
 };
 mod registry;
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+mod tiering;
 
 pub use registry::*;
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+pub(crate) use tiering::tier_up;
 
 /// A compiled WebAssembly module, ready to be instantiated.
 ///
@@ -42,11 +46,13 @@ pub use registry::*;
 /// call to [`Module::deserialize`] will quickly load the module to execute and
 /// does not need to compile any code, representing a more AOT-style use case.
 ///
-/// Currently a `Module` does not implement any form of tiering or dynamic
+/// By default a `Module` does not implement any form of tiering or dynamic
 /// optimization of compiled code. Creation of a `Module` via [`Module::new`] or
 /// related APIs will perform the entire compilation step synchronously. When
 /// finished no further compilation will happen at runtime or later during
-/// execution of WebAssembly instances for example.
+/// execution of WebAssembly instances for example. The exception to this is
+/// when [`Config::tiered_compilation`] is enabled, in which case hot functions
+/// are recompiled in the background.
 ///
 /// Compilation of WebAssembly by default goes through Cranelift and is
 /// recommended to be done once-per-module. The same WebAssembly binary need not
@@ -124,6 +130,7 @@ pub use registry::*;
 /// ```
 ///
 /// [`Config`]: crate::Config
+/// [`Config::tiered_compilation`]: crate::Config::tiered_compilation
 #[derive(Clone)]
 pub struct Module {
     inner: Arc<ModuleInner>,
@@ -157,6 +164,11 @@ struct ModuleInner {
 
     /// Runtime offset information for `VMContext`.
     offsets: VMOffsets<HostPtr>,
+
+    /// State for recompiling this module's hot functions, present if this
+    /// module was compiled with tiered compilation enabled.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    tiering: Option<tiering::Tiering>,
 }
 
 impl fmt::Debug for Module {
@@ -540,6 +552,8 @@ impl Module {
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 serializable,
                 offsets,
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                tiering: None,
             }),
         })
     }
@@ -639,7 +653,12 @@ impl Module {
         self.inner.code.module_types()
     }
 
-    #[cfg(any(feature = "component-model", feature = "gc-drc"))]
+    #[cfg(any(
+        feature = "component-model",
+        feature = "checkpoint",
+        feature = "gc-drc",
+        feature = "gc-generational"
+    ))]
     pub(crate) fn signatures(&self) -> &crate::type_registry::TypeCollection {
         self.inner.code.signatures()
     }
@@ -874,6 +893,18 @@ impl Module {
         &self.inner.engine
     }
 
+    /// Returns whether `a` and `b` refer to the same compiled module.
+    ///
+    /// This is useful for matching a [`FrameInfo::module`] or
+    /// [`DebugFrame::module`] against a particular module.
+    ///
+    /// [`FrameInfo::module`]: crate::FrameInfo::module
+    /// [`DebugFrame::module`]: crate::DebugFrame::module
+    #[inline]
+    pub fn same(a: &Module, b: &Module) -> bool {
+        Arc::ptr_eq(&a.inner, &b.inner)
+    }
+
     /// Returns a summary of the resources required to instantiate this
     /// [`Module`].
     ///
@@ -0,0 +1,159 @@
+//! Runtime support for tiered compilation.
+//!
+//! Modules compiled with [`Config::tiered_compilation`] enabled start out
+//! running Winch-compiled baseline code. That code decrements a countdown in
+//! the store's `VMStoreContext` on every function entry and loop iteration and
+//! calls the `tier_up` libcall once it expires, which samples the function that
+//! was executing. Once a function has been sampled often enough the whole
+//! module is recompiled with Cranelift on a background thread. Later samples of
+//! hot functions then switch the instance over to the optimized code for that
+//! function.
+//!
+//! Baseline code calls all defined functions through their `VMFuncRef`, which
+//! is also what tables, `ref.func` and exported `Func`s refer to, so pointing
+//! that at the optimized code switches over every caller. The instance
+//! remembers which of its functions were switched over, so that the
+//! `VMFuncRef` keeps pointing at the optimized code whenever the runtime
+//! reinitializes it.
+//!
+//! Tiered compilation is only available on targets supported by Winch, so
+//! there is no baseline tier for Pulley.
+//!
+//! [`Config::tiered_compilation`]: crate::Config::tiered_compilation
+
+use super::ModuleInner;
+use crate::Module;
+use crate::prelude::*;
+use crate::runtime::vm::Instance;
+use crate::store::StoreOpaque;
+use alloc::sync::Arc;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicU32, Ordering::Relaxed};
+use std::sync::{Mutex, OnceLock};
+use wasmtime_environ::{DefinedFuncIndex, PrimaryMap};
+
+/// Number of function entries and loop iterations executed in baseline code
+/// between two samples.
+const SAMPLE_INTERVAL: i32 = 1000;
+
+/// Number of samples after which a function is considered hot.
+const HOT_SAMPLES: u32 = 16;
+
+/// Tiered compilation state of a baseline module.
+pub(super) struct Tiering {
+    /// Number of samples taken so far in each defined function, saturating at
+    /// `HOT_SAMPLES`.
+    samples: PrimaryMap<DefinedFuncIndex, AtomicU32>,
+
+    /// The original wasm binary, taken when recompilation starts.
+    wasm: Mutex<Option<Vec<u8>>>,
+
+    /// The module recompiled with the engine's optimizing compiler, filled in
+    /// by the background thread once it has finished.
+    optimized: Arc<OnceLock<Module>>,
+}
+
+impl Module {
+    /// Enables tiered compilation for this freshly compiled module, retaining
+    /// `wasm` for recompiling it later on.
+    pub(crate) fn enable_tiering(&mut self, wasm: &[u8]) {
+        let inner = Arc::get_mut(&mut self.inner).expect("module should not be shared yet");
+        let samples = inner
+            .module
+            .module()
+            .defined_func_indices()
+            .map(|_| AtomicU32::new(0))
+            .collect();
+        inner.tiering = Some(Tiering {
+            samples,
+            wasm: Mutex::new(Some(wasm.to_vec())),
+            optimized: Arc::new(OnceLock::new()),
+        });
+    }
+}
+
+impl Tiering {
+    /// Records a sample of the function `index` of `module`.
+    ///
+    /// Returns the optimized module if `index` is hot and recompilation has
+    /// finished. Otherwise recompilation is started in the background if this
+    /// is the first function to become hot.
+    fn sample(&self, module: &ModuleInner, index: DefinedFuncIndex) -> Option<&Module> {
+        // Count the sample, saturating at `HOT_SAMPLES`, and get the previous
+        // count.
+        let (Ok(n) | Err(n)) = self.samples[index]
+            .fetch_update(Relaxed, Relaxed, |n| (n < HOT_SAMPLES).then(|| n + 1));
+        if n + 1 < HOT_SAMPLES {
+            return None;
+        }
+        if let Some(optimized) = self.optimized.get() {
+            return Some(optimized);
+        }
+        self.recompile(module);
+        None
+    }
+
+    /// Spawns a thread recompiling `module` with the engine's optimizing
+    /// compiler, unless that has already happened.
+    fn recompile(&self, module: &ModuleInner) {
+        let Some(wasm) = self.wasm.lock().unwrap().take() else {
+            return;
+        };
+        let engine = module.engine.clone();
+        let optimized = self.optimized.clone();
+        let size_of_vmctx = module.offsets.size_of_vmctx();
+        let needs_gc_heap = module.module.module().needs_gc_heap;
+        let result = std::thread::Builder::new()
+            .name("wasmtime-tier-up".into())
+            .spawn(move || {
+                let result = crate::compile::compile_tier_up(&engine, &wasm).and_then(|module| {
+                    // The optimized code is run with the baseline module's
+                    // instances, so bail out if the two somehow disagree on
+                    // what those look like.
+                    ensure!(
+                        module.offsets().size_of_vmctx() == size_of_vmctx
+                            && module.env_module().needs_gc_heap == needs_gc_heap,
+                        "optimized module is incompatible with its baseline module"
+                    );
+                    Ok(module)
+                });
+                match result {
+                    Ok(module) => {
+                        let _ = optimized.set(module);
+                    }
+                    Err(e) => log::warn!("failed to recompile module for tier-up: {e:?}"),
+                }
+            });
+        if let Err(e) = result {
+            log::warn!("failed to spawn tier-up thread: {e}");
+        }
+    }
+}
+
+/// Implementation of the `tier_up` libcall, invoked from baseline code once the
+/// store's countdown expires while executing the defined function `index` of
+/// `instance`.
+pub(crate) fn tier_up(
+    store: &mut StoreOpaque,
+    instance: Pin<&mut Instance>,
+    index: DefinedFuncIndex,
+) {
+    *store.vm_store_context_mut().tier_up_countdown.get_mut() = SAMPLE_INTERVAL;
+
+    let Some(module) = instance.runtime_module() else {
+        return;
+    };
+    let Some(tiering) = &module.inner.tiering else {
+        return;
+    };
+    let Some(optimized) = tiering.sample(&module.inner, index) else {
+        return;
+    };
+
+    // Keep the optimized code alive, and make its frames known to the store for
+    // backtraces, GC stack maps and exception handling, before any of it can
+    // be executed in this store.
+    let optimized = optimized.clone();
+    store.modules_mut().register_module(&optimized);
+    instance.tier_up_func_ref(index, &optimized);
+}
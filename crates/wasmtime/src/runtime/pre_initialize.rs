@@ -0,0 +1,845 @@
+//! Pre-initialization of WebAssembly modules and components.
+//!
+//! Pre-initialization instantiates a module, runs an initialization function
+//! exported by it, and then snapshots the resulting state of the instance back
+//! into a new WebAssembly binary. Instantiating the new binary yields an
+//! instance which is already initialized, so expensive startup work such as
+//! parsing configuration or populating caches is paid once ahead of time
+//! rather than on every instantiation. This is the same technique that the
+//! [Wizer](https://github.com/bytecodealliance/wizer) tool implements.
+//!
+//! The snapshot contains:
+//!
+//! * The contents and size of every defined linear memory, encoded as active
+//!   data segments. The original active data segments are emptied, keeping
+//!   their indices so that `memory.init` and `data.drop` still refer to the
+//!   right passive segments.
+//!
+//! * The value of every defined mutable global, which replaces the global's
+//!   original initializer.
+//!
+//! * The contents and size of every defined table, encoded as active element
+//!   segments in the same manner as memories.
+//!
+//! Start functions are removed from the snapshot since their effects are
+//! already part of it. For core modules the initialization function's export
+//! is removed as well, so it can't be accidentally run a second time.
+//!
+//! Not all state can be captured. Shared memories, imported memories and
+//! tables of core modules, non-null GC references, and functions from other
+//! instances stored in tables or globals all cause pre-initialization to fail.
+//! For components, tables which hold functions of other core instances (such
+//! as the indirect-call tables generated for lowered imports) are left as they
+//! were defined originally instead.
+
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::{
+    AsContextMut, Extern, Instance, Linker, Memory, Module, Mutability, Ref, StoreContextMut,
+    Table, Val,
+};
+use alloc::borrow::Cow;
+use wasm_encoder::reencode::{Reencode, RoundtripReencoder};
+use wasm_encoder::{
+    ConstExpr, DataCountSection, DataSection, ElementSection, Elements, ExportSection,
+    GlobalSection, Ieee32, Ieee64, MemorySection, RawSection, RefType, TableSection, ValType,
+};
+use wasmparser::{DataKind, ElementItems, ElementKind, Parser, Payload, TableInit, TypeRef};
+use wasmtime_environ::EntityIndex;
+
+/// Runs of zero bytes shorter than this are included in a data segment rather
+/// than splitting it in two, since a new segment costs a few bytes itself.
+const MIN_DATA_GAP: usize = 8;
+
+/// If snapshotting a memory produces more data segments than this then runs
+/// of zeros are merged more aggressively, staying well below the limits that
+/// engines place on the number of segments in a module.
+const MAX_DATA_SEGMENTS: usize = 10_000;
+
+impl Module {
+    /// Pre-initializes the module in `wasm` by running its `init_func` export
+    /// and snapshotting the resulting state into a new module.
+    ///
+    /// The module is compiled, instantiated within `store` using `linker`, and
+    /// then its `init_func` export, which must have the type `[] -> []`, is
+    /// called. Afterwards the state of the instance's linear memories,
+    /// mutable globals, and tables is encoded into a copy of `wasm` which is
+    /// returned. Instantiating the returned module produces an instance in the
+    /// same state the original was in after `init_func` returned, without
+    /// running `init_func` or the module's start function again.
+    ///
+    /// The `wasm` provided may be in the text format if the `wat` feature is
+    /// enabled, but the returned module is always in the binary format.
+    ///
+    /// Only state owned by the instance is captured: any effects the
+    /// initializer had on the host, through imports, are not. See the
+    /// [Wizer](https://github.com/bytecodealliance/wizer) documentation for
+    /// more background on this technique.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `wasm` fails to compile or instantiate, if
+    /// `init_func` isn't an exported function of type `[] -> []` or traps, or
+    /// if the state of the instance can't be represented in a module. This
+    /// is the case for shared memories, imported memories or tables,
+    /// non-null GC references, and references to functions that aren't part
+    /// of the module itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` has async support enabled, or if `linker` belongs to
+    /// a different engine than `store`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let wasm = Module::pre_initialize(
+    ///     &mut Store::new(&engine, ()),
+    ///     &Linker::new(&engine),
+    ///     br#"
+    ///         (module
+    ///             (global $g (export "g") (mut i32) (i32.const 0))
+    ///             (func (export "init") (global.set $g (i32.const 42)))
+    ///         )
+    ///     "#,
+    ///     "init",
+    /// )?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let module = Module::new(&engine, &wasm)?;
+    /// let instance = Instance::new(&mut store, &module, &[])?;
+    /// let g = instance.get_global(&mut store, "g").unwrap();
+    /// assert_eq!(g.get(&mut store).unwrap_i32(), 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pre_initialize<T: 'static>(
+        mut store: impl AsContextMut<Data = T>,
+        linker: &Linker<T>,
+        wasm: &[u8],
+        init_func: &str,
+    ) -> Result<Vec<u8>> {
+        let mut store = store.as_context_mut();
+        #[cfg(feature = "wat")]
+        let wasm = &wat::parse_bytes(wasm)?[..];
+
+        let module = Module::new(store.engine(), wasm)?;
+        let instance = linker.instantiate(&mut store, &module)?;
+        instance
+            .get_typed_func::<(), ()>(&mut store, init_func)?
+            .call(&mut store, ())
+            .with_context(|| format!("failed to run initialization function `{init_func}`"))?;
+
+        let mut captured = Captured::default();
+        let mut snapshot = Snapshot::capture(&mut store, instance, true, &mut captured)?;
+        snapshot.resolve_imports(&mut store, instance, &captured);
+        rewrite_module(wasm, &snapshot, Some(init_func))
+    }
+}
+
+/// The state of a single core instance.
+#[derive(Default)]
+struct Snapshot {
+    /// Defined memories, in order.
+    memories: Vec<MemorySnapshot>,
+    /// Defined globals, in order, or `None` for immutable globals.
+    globals: Vec<Option<GlobalValue>>,
+    /// Defined tables, in order, or `None` if the table's contents can't be
+    /// represented in the module and it's left as originally defined.
+    tables: Vec<Option<TableSnapshot>>,
+    /// For every memory in the module, imported or not, whether its contents
+    /// are part of a snapshot, making active data segments for it redundant.
+    memories_captured: Vec<bool>,
+    /// Same as `memories_captured`, but for tables and element segments.
+    tables_captured: Vec<bool>,
+}
+
+struct MemorySnapshot {
+    pages: u64,
+    data: Vec<(u64, Vec<u8>)>,
+}
+
+struct TableSnapshot {
+    size: u64,
+    elements: Vec<(u64, Vec<u32>)>,
+}
+
+enum GlobalValue {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+    V128(u128),
+    RefNull,
+    RefFunc(u32),
+}
+
+/// Memories and tables whose contents have been snapshotted, so that
+/// instances importing them know that they're captured.
+#[derive(Default)]
+struct Captured {
+    memories: Vec<Memory>,
+    tables: Vec<Table>,
+}
+
+impl Snapshot {
+    /// Captures the defined state of `instance`.
+    ///
+    /// If `standalone` is false then `instance` is part of a component, where
+    /// memories and tables are imported from other instances which are
+    /// captured separately.
+    fn capture<T>(
+        store: &mut StoreContextMut<'_, T>,
+        instance: Instance,
+        standalone: bool,
+        captured: &mut Captured,
+    ) -> Result<Snapshot> {
+        let module = store.0.instance(instance.id()).env_module().clone();
+        let mut funcs = FuncIndices::default();
+        let mut snapshot = Snapshot::default();
+
+        for index in module.memories.keys() {
+            let memory = match instance._get_export(store.0, EntityIndex::Memory(index)) {
+                Extern::Memory(memory) => memory,
+                Extern::SharedMemory(_) => {
+                    bail!("cannot snapshot shared memory {}", index.as_u32())
+                }
+                _ => unreachable!(),
+            };
+            if module.defined_memory_index(index).is_none() {
+                ensure!(
+                    !standalone,
+                    "cannot snapshot imported memory {}",
+                    index.as_u32()
+                );
+                continue;
+            }
+            captured.memories.push(memory);
+            snapshot.memories.push(MemorySnapshot {
+                pages: memory.size(&*store),
+                data: data_segments(memory.data(&*store)),
+            });
+        }
+
+        for index in module.tables.keys() {
+            let table = match instance._get_export(store.0, EntityIndex::Table(index)) {
+                Extern::Table(table) => table,
+                _ => unreachable!(),
+            };
+            if module.defined_table_index(index).is_none() {
+                ensure!(
+                    !standalone,
+                    "cannot snapshot imported table {}",
+                    index.as_u32()
+                );
+                continue;
+            }
+            let size = table.size(&*store);
+            let mut elements = Vec::<(u64, Vec<u32>)>::new();
+            let mut representable = true;
+            for i in 0..size {
+                let element = table.get(&mut *store, i).unwrap();
+                let func = match funcs.index_of(store.0, instance, &element) {
+                    Ok(Some(func)) => func,
+                    Ok(None) => continue,
+                    Err(e) if standalone => {
+                        return Err(e.context(format!(
+                            "cannot snapshot element {i} of table {}",
+                            index.as_u32()
+                        )));
+                    }
+                    Err(_) => {
+                        representable = false;
+                        break;
+                    }
+                };
+                match elements.last_mut() {
+                    Some((start, funcs)) if *start + funcs.len() as u64 == i => funcs.push(func),
+                    _ => elements.push((i, vec![func])),
+                }
+            }
+            snapshot.tables.push(if representable {
+                captured.tables.push(table);
+                Some(TableSnapshot { size, elements })
+            } else {
+                None
+            });
+        }
+
+        for index in module.globals.keys() {
+            if module.defined_global_index(index).is_none() {
+                continue;
+            }
+            let global = match instance._get_export(store.0, EntityIndex::Global(index)) {
+                Extern::Global(global) => global,
+                _ => unreachable!(),
+            };
+            if global.ty(&*store).mutability() == Mutability::Const {
+                snapshot.globals.push(None);
+                continue;
+            }
+            let value = match global.get(&mut *store) {
+                Val::I32(x) => GlobalValue::I32(x),
+                Val::I64(x) => GlobalValue::I64(x),
+                Val::F32(x) => GlobalValue::F32(x),
+                Val::F64(x) => GlobalValue::F64(x),
+                Val::V128(x) => GlobalValue::V128(x.as_u128()),
+                val => {
+                    let r = val.ref_().unwrap();
+                    match funcs
+                        .index_of(store.0, instance, &r)
+                        .with_context(|| format!("cannot snapshot global {}", index.as_u32()))?
+                    {
+                        Some(func) => GlobalValue::RefFunc(func),
+                        None => GlobalValue::RefNull,
+                    }
+                }
+            };
+            snapshot.globals.push(Some(value));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Determines, for every memory and table of `instance`, whether its
+    /// contents were captured by this or another snapshot.
+    fn resolve_imports<T>(
+        &mut self,
+        store: &mut StoreContextMut<'_, T>,
+        instance: Instance,
+        captured: &Captured,
+    ) {
+        let store = &mut *store.0;
+        let module = store.instance(instance.id()).env_module().clone();
+        for index in module.memories.keys() {
+            let is_captured = match instance._get_export(store, EntityIndex::Memory(index)) {
+                Extern::Memory(memory) => {
+                    let key = memory.hash_key(store);
+                    captured.memories.iter().any(|m| m.hash_key(store) == key)
+                }
+                _ => false,
+            };
+            self.memories_captured.push(is_captured);
+        }
+        for index in module.tables.keys() {
+            let is_captured = match instance._get_export(store, EntityIndex::Table(index)) {
+                Extern::Table(table) => {
+                    let key = table.hash_key(store);
+                    captured.tables.iter().any(|t| t.hash_key(store) == key)
+                }
+                _ => false,
+            };
+            self.tables_captured.push(is_captured);
+        }
+    }
+}
+
+/// A lazily-built map from the functions of an instance back to their
+/// indices, used to encode references to them.
+#[derive(Default)]
+struct FuncIndices(Option<HashMap<usize, u32>>);
+
+impl FuncIndices {
+    /// Returns the index of the function that `r` refers to, or `None` if
+    /// `r` is null.
+    fn index_of(
+        &mut self,
+        store: &mut crate::store::StoreOpaque,
+        instance: Instance,
+        r: &Ref,
+    ) -> Result<Option<u32>> {
+        if r.is_null() {
+            return Ok(None);
+        }
+        let Some(Some(func)) = r.as_func() else {
+            bail!("GC references cannot be snapshotted");
+        };
+        let key = func.vm_func_ref(store).as_ptr().addr();
+        let map = match &mut self.0 {
+            Some(map) => map,
+            None => {
+                let module = store.instance(instance.id()).env_module().clone();
+                let mut map = HashMap::new();
+                for index in module.functions.keys() {
+                    let Extern::Func(func) =
+                        instance._get_export(store, EntityIndex::Function(index))
+                    else {
+                        unreachable!()
+                    };
+                    map.entry(func.vm_func_ref(store).as_ptr().addr())
+                        .or_insert(index.as_u32());
+                }
+                self.0.insert(map)
+            }
+        };
+        match map.get(&key) {
+            Some(index) => Ok(Some(*index)),
+            None => bail!("reference to a function which is not part of the instance"),
+        }
+    }
+}
+
+/// Splits the contents of a linear memory into data segments covering all of
+/// its non-zero bytes.
+fn data_segments(data: &[u8]) -> Vec<(u64, Vec<u8>)> {
+    let mut gap = MIN_DATA_GAP;
+    loop {
+        let mut segments = Vec::new();
+        let mut pos = 0;
+        while let Some(start) = data[pos..].iter().position(|b| *b != 0) {
+            let start = pos + start;
+            let mut end = start;
+            loop {
+                end += data[end..]
+                    .iter()
+                    .position(|b| *b == 0)
+                    .unwrap_or(data.len() - end);
+                match data[end..].iter().position(|b| *b != 0) {
+                    Some(zeros) if zeros < gap => end += zeros,
+                    _ => break,
+                }
+            }
+            segments.push((start as u64, data[start..end].to_vec()));
+            pos = end;
+        }
+        if segments.len() <= MAX_DATA_SEGMENTS {
+            return segments;
+        }
+        gap *= 2;
+    }
+}
+
+/// Returns a constant expression for `offset` within a 32-bit or 64-bit
+/// memory or table.
+fn offset_expr(index64: bool, offset: u64) -> ConstExpr {
+    if index64 {
+        ConstExpr::i64_const(offset as i64)
+    } else {
+        ConstExpr::i32_const(u32::try_from(offset).unwrap() as i32)
+    }
+}
+
+impl GlobalValue {
+    fn const_expr(&self, ty: ValType) -> ConstExpr {
+        match *self {
+            GlobalValue::I32(x) => ConstExpr::i32_const(x),
+            GlobalValue::I64(x) => ConstExpr::i64_const(x),
+            GlobalValue::F32(x) => ConstExpr::f32_const(Ieee32::from(f32::from_bits(x))),
+            GlobalValue::F64(x) => ConstExpr::f64_const(Ieee64::from(f64::from_bits(x))),
+            GlobalValue::V128(x) => ConstExpr::v128_const(x as i128),
+            GlobalValue::RefNull => match ty {
+                ValType::Ref(ty) => ConstExpr::ref_null(ty.heap_type),
+                _ => unreachable!(),
+            },
+            GlobalValue::RefFunc(func) => ConstExpr::ref_func(func),
+        }
+    }
+}
+
+/// Encodes `snapshot` into a copy of the core module `wasm`.
+///
+/// Sections which don't hold state are copied over verbatim. If `init_func`
+/// is provided then the export with that name is removed.
+fn rewrite_module(wasm: &[u8], snapshot: &Snapshot, init_func: Option<&str>) -> Result<Vec<u8>> {
+    let mut reencoder = RoundtripReencoder;
+    let mut module = wasm_encoder::Module::new();
+    let mut memory64 = Vec::new();
+    let mut table_types = Vec::new();
+    let mut elements_done = false;
+    let mut data_done = false;
+    let data_count = snapshot
+        .memories
+        .iter()
+        .map(|m| m.data.len())
+        .sum::<usize>();
+
+    let append_elements = |elements: &mut ElementSection, table_types: &[(bool, RefType)]| {
+        let defined = table_types.len() - snapshot.tables.len();
+        for (i, table) in snapshot.tables.iter().enumerate() {
+            let Some(table) = table else { continue };
+            let (table64, ty) = table_types[defined + i];
+            for (offset, funcs) in table.elements.iter() {
+                let funcs = if ty == RefType::FUNCREF {
+                    Elements::Functions(Cow::Borrowed(funcs))
+                } else {
+                    let exprs = funcs.iter().map(|f| ConstExpr::ref_func(*f)).collect();
+                    Elements::Expressions(ty, Cow::Owned(exprs))
+                };
+                let index = u32::try_from(defined + i).unwrap();
+                elements.active(Some(index), &offset_expr(table64, *offset), funcs);
+            }
+        }
+    };
+    let append_data = |data: &mut DataSection, memory64: &[bool]| {
+        let defined = memory64.len() - snapshot.memories.len();
+        for (i, memory) in snapshot.memories.iter().enumerate() {
+            for (offset, bytes) in memory.data.iter() {
+                let index = u32::try_from(defined + i).unwrap();
+                let offset = offset_expr(memory64[defined + i], *offset);
+                data.active(index, &offset, bytes.iter().copied());
+            }
+        }
+    };
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = payload?;
+
+        // Element segments for tables need to be added before any of the
+        // following sections even if the original module had no element
+        // section.
+        if !elements_done
+            && matches!(
+                payload,
+                Payload::DataCountSection { .. }
+                    | Payload::CodeSectionStart { .. }
+                    | Payload::DataSection(_)
+                    | Payload::End(_)
+            )
+        {
+            let mut elements = ElementSection::new();
+            append_elements(&mut elements, &table_types);
+            if !elements.is_empty() {
+                module.section(&elements);
+            }
+            elements_done = true;
+        }
+
+        match payload {
+            Payload::Version { .. } | Payload::CodeSectionEntry(_) => {}
+
+            Payload::ImportSection(imports) => {
+                for import in imports.clone() {
+                    match import?.ty {
+                        TypeRef::Memory(ty) => memory64.push(ty.memory64),
+                        TypeRef::Table(ty) => {
+                            table_types.push((ty.table64, reencoder.ref_type(ty.element_type)?))
+                        }
+                        _ => {}
+                    }
+                }
+                let range = imports.range();
+                module.section(&RawSection {
+                    id: wasm_encoder::SectionId::Import as u8,
+                    data: &wasm[range],
+                });
+            }
+
+            Payload::TableSection(tables) => {
+                let mut section = TableSection::new();
+                for (i, table) in tables.into_iter().enumerate() {
+                    let table = table?;
+                    let mut ty = reencoder.table_type(table.ty)?;
+                    table_types.push((ty.table64, ty.element_type));
+                    if let Some(snapshot) = &snapshot.tables[i] {
+                        ty.minimum = snapshot.size;
+                    }
+                    match table.init {
+                        TableInit::RefNull => section.table(ty),
+                        TableInit::Expr(expr) => {
+                            section.table_with_init(ty, &reencoder.const_expr(expr)?)
+                        }
+                    };
+                }
+                module.section(&section);
+            }
+
+            Payload::MemorySection(memories) => {
+                let mut section = MemorySection::new();
+                for (i, ty) in memories.into_iter().enumerate() {
+                    let mut ty = reencoder.memory_type(ty?)?;
+                    memory64.push(ty.memory64);
+                    ty.minimum = snapshot.memories[i].pages;
+                    section.memory(ty);
+                }
+                module.section(&section);
+            }
+
+            Payload::GlobalSection(globals) => {
+                let mut section = GlobalSection::new();
+                for (i, global) in globals.into_iter().enumerate() {
+                    let global = global?;
+                    let ty = reencoder.global_type(global.ty)?;
+                    let init = match &snapshot.globals[i] {
+                        Some(value) => value.const_expr(ty.val_type),
+                        None => reencoder.const_expr(global.init_expr)?,
+                    };
+                    section.global(ty, &init);
+                }
+                module.section(&section);
+            }
+
+            Payload::ExportSection(exports) => {
+                let mut section = ExportSection::new();
+                for export in exports {
+                    let export = export?;
+                    if Some(export.name) != init_func {
+                        let kind = reencoder.export_kind(export.kind)?;
+                        section.export(export.name, kind, export.index);
+                    }
+                }
+                module.section(&section);
+            }
+
+            // The start function already ran, and its effects are part of
+            // the snapshot.
+            Payload::StartSection { .. } => {}
+
+            Payload::ElementSection(elements) => {
+                let mut section = ElementSection::new();
+                for element in elements {
+                    let element = element?;
+                    match element.kind {
+                        // Segments initializing tables which were captured
+                        // are kept, but emptied, so the indices of the other
+                        // segments don't change.
+                        ElementKind::Active { table_index, .. }
+                            if snapshot.tables_captured[table_index.unwrap_or(0) as usize] =>
+                        {
+                            let (table64, _) = table_types[table_index.unwrap_or(0) as usize];
+                            let items = match element.items {
+                                ElementItems::Functions(_) => {
+                                    Elements::Functions(Cow::Borrowed(&[]))
+                                }
+                                ElementItems::Expressions(ty, _) => Elements::Expressions(
+                                    reencoder.ref_type(ty)?,
+                                    Cow::Borrowed(&[]),
+                                ),
+                            };
+                            section.active(table_index, &offset_expr(table64, 0), items);
+                        }
+                        _ => reencoder.parse_element(&mut section, element)?,
+                    }
+                }
+                append_elements(&mut section, &table_types);
+                module.section(&section);
+                elements_done = true;
+            }
+
+            Payload::DataCountSection { count, .. } => {
+                let count = count + u32::try_from(data_count)?;
+                module.section(&DataCountSection { count });
+            }
+
+            Payload::DataSection(data) => {
+                let mut section = DataSection::new();
+                for datum in data {
+                    let datum = datum?;
+                    match datum.kind {
+                        // Like element segments above, segments for captured
+                        // memories are emptied.
+                        DataKind::Active { memory_index, .. }
+                            if snapshot.memories_captured[memory_index as usize] =>
+                        {
+                            let offset = offset_expr(memory64[memory_index as usize], 0);
+                            section.active(memory_index, &offset, []);
+                        }
+                        _ => reencoder.parse_data(&mut section, datum)?,
+                    }
+                }
+                append_data(&mut section, &memory64);
+                module.section(&section);
+                data_done = true;
+            }
+
+            Payload::End(_) => {
+                if !data_done && data_count > 0 {
+                    let mut section = DataSection::new();
+                    append_data(&mut section, &memory64);
+                    module.section(&section);
+                }
+            }
+
+            other => match other.as_section() {
+                Some((id, range)) => {
+                    module.section(&RawSection {
+                        id,
+                        data: &wasm[range],
+                    });
+                }
+                None => bail!("unexpected payload in a core module"),
+            },
+        }
+    }
+
+    Ok(module.finish())
+}
+
+#[cfg(feature = "component-model")]
+mod component {
+    use super::{Captured, Snapshot, rewrite_module};
+    use crate::AsContextMut;
+    use crate::component::{Component, Linker};
+    use crate::hash_map::HashMap;
+    use crate::prelude::*;
+    use alloc::borrow::Cow;
+    use wasm_encoder::{ComponentSectionId, RawSection};
+    use wasmparser::{Chunk, Parser, Payload};
+    use wasmtime_environ::component::{GlobalInitializer, InstantiateModule, RuntimeInstanceIndex};
+
+    impl Component {
+        /// Pre-initializes the component in `wasm` by running its
+        /// `init_func` export and snapshotting the resulting state into a new
+        /// component.
+        ///
+        /// This is the component equivalent of
+        /// [`Module::pre_initialize`](crate::Module::pre_initialize). The
+        /// component is instantiated within `store` using `linker` and its
+        /// root-level `init_func` export, which must have the type
+        /// `func()`, is called. Afterwards the state of each of the
+        /// component's core instances is snapshotted into the core module it
+        /// was instantiated from, and the resulting component is returned.
+        ///
+        /// Unlike core modules, the `init_func` export is retained in the
+        /// returned component since other exports may refer to it.
+        ///
+        /// # Errors
+        ///
+        /// In addition to the errors described in
+        /// [`Module::pre_initialize`](crate::Module::pre_initialize), this
+        /// returns an error if a core module within the component is
+        /// instantiated more than once, since a single module can't hold the
+        /// state of multiple instances, or if the component instantiates a
+        /// core module that it imports.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `store` has async support enabled, or if `linker` belongs
+        /// to a different engine than `store`.
+        pub fn pre_initialize<T: 'static>(
+            mut store: impl AsContextMut<Data = T>,
+            linker: &Linker<T>,
+            wasm: &[u8],
+            init_func: &str,
+        ) -> Result<Vec<u8>> {
+            let mut store = store.as_context_mut();
+            #[cfg(feature = "wat")]
+            let wasm = &wat::parse_bytes(wasm)?[..];
+
+            let component = Component::new(store.engine(), wasm)?;
+            let instance = linker.instantiate(&mut store, &component)?;
+            let func = instance.get_typed_func::<(), ()>(&mut store, init_func)?;
+            func.call(&mut store, ())
+                .with_context(|| format!("failed to run initialization function `{init_func}`"))?;
+            func.post_return(&mut store)?;
+
+            // Find the core instance created from each of the component's
+            // module sections. Modules beyond those in the binary are
+            // adapters generated by Wasmtime, which hold no state.
+            let modules = Parser::new(0)
+                .parse_all(wasm)
+                .filter(|p| matches!(p, Ok(Payload::ModuleSection { .. })))
+                .count();
+            let mut instances = Vec::new();
+            let component_instance = instance.id().get(store.0);
+            let mut runtime_index = RuntimeInstanceIndex::from_u32(0);
+            for initializer in component_instance
+                .component()
+                .env_component()
+                .initializers
+                .iter()
+            {
+                let GlobalInitializer::InstantiateModule(m) = initializer else {
+                    continue;
+                };
+                let id = component_instance.instance(runtime_index);
+                runtime_index = RuntimeInstanceIndex::from_u32(runtime_index.as_u32() + 1);
+                match m {
+                    InstantiateModule::Static(index, _) if index.as_u32() as usize >= modules => {}
+                    InstantiateModule::Static(index, _) => {
+                        ensure!(
+                            instances.iter().all(|(i, _)| i != index),
+                            "cannot snapshot module {} since it is instantiated more than once",
+                            index.as_u32()
+                        );
+                        instances.push((*index, id));
+                    }
+                    InstantiateModule::Import(..) => {
+                        bail!("cannot snapshot a component which instantiates an imported module")
+                    }
+                }
+            }
+
+            let mut captured = Captured::default();
+            let mut snapshots = Vec::new();
+            for (index, id) in instances {
+                let instance = crate::Instance::from_wasmtime(id, store.0);
+                let snapshot = Snapshot::capture(&mut store, instance, false, &mut captured)
+                    .with_context(|| format!("failed to snapshot module {}", index.as_u32()))?;
+                snapshots.push((index, instance, snapshot));
+            }
+            let mut modules = HashMap::new();
+            for (index, instance, mut snapshot) in snapshots {
+                snapshot.resolve_imports(&mut store, instance, &captured);
+                modules.insert(index.as_u32(), snapshot);
+            }
+
+            let component = rewrite_component(wasm, &modules, &mut 0)?;
+            Ok(component.finish())
+        }
+    }
+
+    /// Re-encodes `wasm`, replacing the module sections which have a
+    /// snapshot in `modules`.
+    ///
+    /// Module sections are numbered in the order they're encountered,
+    /// including those within nested components, which matches how they're
+    /// numbered during compilation. `next` is the index of the first module
+    /// section in `wasm`.
+    fn rewrite_component(
+        wasm: &[u8],
+        modules: &HashMap<u32, Snapshot>,
+        next: &mut u32,
+    ) -> Result<wasm_encoder::Component> {
+        let mut component = wasm_encoder::Component::new();
+        let mut parser = Parser::new(0);
+        let mut remaining = wasm;
+        while !remaining.is_empty() {
+            let payload = match parser.parse(remaining, true)? {
+                Chunk::Parsed { consumed, payload } => {
+                    remaining = &remaining[consumed..];
+                    payload
+                }
+                Chunk::NeedMoreData(_) => unreachable!(),
+            };
+            match payload {
+                Payload::Version { .. } | Payload::End(_) => {}
+                Payload::ModuleSection {
+                    unchecked_range, ..
+                } => {
+                    remaining = &remaining[unchecked_range.len()..];
+                    let index = *next;
+                    *next += 1;
+                    let module = &wasm[unchecked_range];
+                    let module = match modules.get(&index) {
+                        Some(snapshot) => Cow::Owned(rewrite_module(module, snapshot, None)?),
+                        None => Cow::Borrowed(module),
+                    };
+                    component.section(&RawSection {
+                        id: ComponentSectionId::CoreModule as u8,
+                        data: &module,
+                    });
+                }
+                Payload::ComponentSection {
+                    unchecked_range, ..
+                } => {
+                    remaining = &remaining[unchecked_range.len()..];
+                    let nested = rewrite_component(&wasm[unchecked_range], modules, next)?;
+                    component.section(&wasm_encoder::NestedComponentSection(&nested));
+                }
+                other => match other.as_section() {
+                    Some((id, range)) => {
+                        component.section(&RawSection {
+                            id,
+                            data: &wasm[range],
+                        });
+                    }
+                    None => bail!("unexpected payload in a component"),
+                },
+            }
+        }
+        Ok(component)
+    }
+}
@@ -0,0 +1,857 @@
+//! Deterministic record and replay of host function calls.
+//!
+//! A [`Linker`](crate::Linker) or
+//! [`component::Linker`](crate::component::Linker) configured with a
+//! [`HostCallRecorder`] logs the outcome of every host import that WebAssembly
+//! calls into a trace. A linker configured with a [`HostCallReplayer`] later
+//! feeds those outcomes back to the guest instead of calling the host at all.
+//! As long as the guest itself is deterministic this reproduces the original
+//! execution exactly, which is useful for reproducing bugs that depend on
+//! clocks, randomness, the filesystem, or the network.
+//!
+//! The trace records, for each call, everything the call did to the guest:
+//!
+//! * For core host functions these are the results along with the bytes of
+//!   the calling instance's linear memories which changed during the call.
+//!
+//! * For component host functions these are the flat results, the calls to
+//!   the guest's `realloc` and the resources inserted into the guest's tables
+//!   while lowering results, and the bytes of linear memory that lowering
+//!   wrote. During replay `realloc` is invoked again, so the guest's allocator
+//!   evolves exactly as it did when the trace was recorded.
+//!
+//! When replaying, each call is checked against the trace: calling a
+//! function with a different signature or different core parameters than
+//! what was recorded is reported as an error rather than silently feeding the
+//! wrong results to the guest.
+//!
+//! Some effects of host functions are not captured. Host functions which call
+//! back into WebAssembly (other than through `realloc` during lowering),
+//! modify globals or tables, or write to shared memories will not have those
+//! effects replayed. Async-lowered component imports are not supported.
+
+use crate::prelude::*;
+use crate::store::StoreOpaque;
+use crate::{FuncType, HeapType, Instance, Memory, StoreContextMut, ValRaw, ValType};
+use alloc::sync::Arc;
+use core::fmt;
+use core::mem::MaybeUninit;
+use serde_derive::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+use wasmtime_environ::VMSharedTypeIndex;
+
+/// Bytes at the start of every trace, used to reject files which aren't
+/// traces produced by this module.
+const MAGIC: &[u8; 16] = b"\0wasmtime-hcall\0";
+
+/// Version of the trace format, bumped whenever [`Event`] changes.
+const VERSION: u32 = 1;
+
+/// Memory is compared in chunks of this many bytes when computing which parts
+/// of it a host call wrote to.
+const DIFF_CHUNK: usize = 64;
+
+/// Records the results of host calls into a trace.
+///
+/// A recorder is attached to a linker with
+/// [`Linker::record_host_calls`](crate::Linker::record_host_calls) or
+/// [`component::Linker::record_host_calls`](crate::component::Linker::record_host_calls),
+/// after which all stores that instances from that linker are placed into log
+/// their host calls to this recorder. The trace can later be replayed with a
+/// [`HostCallReplayer`].
+///
+/// Cloning a recorder is cheap and the clones all write to the same trace.
+/// Note that recording copies the calling instance's linear memory around
+/// every host call to find out what the call wrote, so recording is intended
+/// for debugging rather than for production use.
+#[derive(Clone)]
+pub struct HostCallRecorder {
+    inner: Arc<Mutex<Recorder>>,
+}
+
+struct Recorder {
+    writer: Box<dyn Write + Send>,
+    events: u64,
+}
+
+impl HostCallRecorder {
+    /// Creates a new recorder which writes its trace to `writer`.
+    ///
+    /// Each host call is written to `writer` as soon as it returns, so it's
+    /// recommended to use a buffered writer and to call
+    /// [`HostCallRecorder::flush`] once execution is finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trace's header fails to be written.
+    pub fn new(writer: impl Write + Send + 'static) -> Result<HostCallRecorder> {
+        let mut writer: Box<dyn Write + Send> = Box::new(writer);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(HostCallRecorder {
+            inner: Arc::new(Mutex::new(Recorder { writer, events: 0 })),
+        })
+    }
+
+    /// Returns the number of host calls recorded so far.
+    pub fn len(&self) -> u64 {
+        self.inner.lock().unwrap().events
+    }
+
+    /// Returns whether no host calls have been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Flushes the underlying writer of this trace.
+    pub fn flush(&self) -> Result<()> {
+        self.inner.lock().unwrap().writer.flush()?;
+        Ok(())
+    }
+
+    fn write(&self, event: &Event) -> Result<()> {
+        let bytes = postcard::to_allocvec(event)?;
+        let len = u32::try_from(bytes.len())?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.writer.write_all(&len.to_le_bytes())?;
+        inner.writer.write_all(&bytes)?;
+        inner.events += 1;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HostCallRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostCallRecorder")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Replays a trace produced by a [`HostCallRecorder`].
+///
+/// A replayer is attached to a linker with
+/// [`Linker::replay_host_calls`](crate::Linker::replay_host_calls) or
+/// [`component::Linker::replay_host_calls`](crate::component::Linker::replay_host_calls).
+/// Host imports of instances created from that linker are then no longer
+/// called; instead each call consumes the next call in the trace and its
+/// recorded effects are applied to the guest. If the guest makes a call that
+/// doesn't match the trace, or makes more calls than were recorded, the call
+/// traps with an error describing the divergence.
+///
+/// Cloning a replayer is cheap and the clones all read from the same trace.
+#[derive(Clone)]
+pub struct HostCallReplayer {
+    inner: Arc<Mutex<Replayer>>,
+}
+
+struct Replayer {
+    reader: Box<dyn Read + Send>,
+    events: u64,
+    peeked: Option<Event>,
+}
+
+impl HostCallReplayer {
+    /// Creates a new replayer which reads a trace from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `reader` doesn't start with a trace header produced
+    /// by a compatible version of [`HostCallRecorder`].
+    pub fn new(reader: impl Read + Send + 'static) -> Result<HostCallReplayer> {
+        let mut reader: Box<dyn Read + Send> = Box::new(reader);
+        let mut header = [0; MAGIC.len() + 4];
+        reader
+            .read_exact(&mut header)
+            .context("failed to read host call trace header")?;
+        let (magic, version) = header.split_at(MAGIC.len());
+        if magic != MAGIC {
+            bail!("not a host call trace");
+        }
+        let version = u32::from_le_bytes(version.try_into().unwrap());
+        if version != VERSION {
+            bail!("unsupported host call trace version {version}, expected {VERSION}");
+        }
+        Ok(HostCallReplayer {
+            inner: Arc::new(Mutex::new(Replayer {
+                reader,
+                events: 0,
+                peeked: None,
+            })),
+        })
+    }
+
+    /// Returns the number of host calls replayed so far.
+    pub fn len(&self) -> u64 {
+        self.inner.lock().unwrap().events
+    }
+
+    /// Returns whether no host calls have been replayed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether every host call in the trace has been replayed.
+    ///
+    /// This can be used after execution finishes to check that the guest made
+    /// all of the calls that were recorded.
+    pub fn is_finished(&self) -> Result<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.peeked.is_none() {
+            inner.peeked = inner.read()?;
+        }
+        Ok(inner.peeked.is_none())
+    }
+
+    /// Returns the next event in the trace along with its index.
+    fn next(&self) -> Result<(u64, Event)> {
+        let mut inner = self.inner.lock().unwrap();
+        let event = match inner.peeked.take() {
+            Some(event) => event,
+            None => match inner.read()? {
+                Some(event) => event,
+                None => bail!(
+                    "host call trace exhausted: guest made more than the {} recorded host calls",
+                    inner.events
+                ),
+            },
+        };
+        let index = inner.events;
+        inner.events += 1;
+        Ok((index, event))
+    }
+}
+
+impl Replayer {
+    fn read(&mut self) -> Result<Option<Event>> {
+        let mut len = [0; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let mut bytes = vec![0; usize::try_from(u32::from_le_bytes(len))?];
+        self.reader
+            .read_exact(&mut bytes)
+            .context("host call trace is truncated")?;
+        Ok(Some(postcard::from_bytes(&bytes)?))
+    }
+}
+
+impl fmt::Debug for HostCallReplayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostCallReplayer")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Either a recorder or a replayer, as configured on a linker.
+#[derive(Clone, Debug)]
+pub(crate) enum HostCallLog {
+    Record(HostCallRecorder),
+    Replay(HostCallReplayer),
+}
+
+impl HostCallLog {
+    fn same(&self, other: &HostCallLog) -> bool {
+        match (self, other) {
+            (HostCallLog::Record(a), HostCallLog::Record(b)) => Arc::ptr_eq(&a.inner, &b.inner),
+            (HostCallLog::Replay(a), HostCallLog::Replay(b)) => Arc::ptr_eq(&a.inner, &b.inner),
+            _ => false,
+        }
+    }
+}
+
+/// Per-store state of host call recording and replay.
+#[derive(Default)]
+pub(crate) struct HostCallState {
+    log: Option<HostCallLog>,
+    #[cfg(feature = "component-model")]
+    lowering: Option<Vec<LoweringEffect>>,
+}
+
+impl StoreOpaque {
+    /// Configures this store to record or replay host calls with `log`.
+    ///
+    /// This is done when instantiating from a linker that was configured with
+    /// a recorder or replayer, and fails if this store is already using a
+    /// different trace.
+    pub(crate) fn set_host_call_log(&mut self, log: &HostCallLog) -> Result<()> {
+        let state = self.host_calls_mut();
+        match &state.log {
+            Some(prev) if !prev.same(log) => {
+                bail!("store is already recording or replaying a different host call trace")
+            }
+            _ => state.log = Some(log.clone()),
+        }
+        Ok(())
+    }
+
+    fn host_call_log(&mut self) -> Option<HostCallLog> {
+        self.host_calls_mut().log.clone()
+    }
+}
+
+/// One host call in a trace.
+#[derive(Serialize, Deserialize, Debug)]
+enum Event {
+    Core {
+        signature: String,
+        params: Vec<Value>,
+        outcome: Outcome<CoreEffects>,
+    },
+    #[cfg_attr(not(feature = "component-model"), allow(dead_code))]
+    Component {
+        func: u32,
+        caller: u32,
+        outcome: Outcome<ComponentEffects>,
+    },
+}
+
+impl Event {
+    fn describe(&self) -> String {
+        match self {
+            Event::Core { signature, .. } => format!("core host function `{signature}`"),
+            Event::Component { func, caller, .. } => {
+                format!("component host function of type {func} from instance {caller}")
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Outcome<T> {
+    Returned(T),
+    Failed(String),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CoreEffects {
+    results: Vec<Value>,
+    memories: Vec<MemoryEffects>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MemoryEffects {
+    index: u32,
+    size: u64,
+    writes: Vec<MemoryWrite>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MemoryWrite {
+    offset: u64,
+    bytes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct ComponentEffects {
+    lowering: Vec<LoweringEffect>,
+    writes: Vec<MemoryWrite>,
+    results: Vec<u64>,
+}
+
+/// Something that happened to the guest while lowering a component host
+/// function's results, other than writes to linear memory.
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(not(feature = "component-model"), allow(dead_code))]
+pub(crate) enum LoweringEffect {
+    Realloc {
+        old: u64,
+        old_size: u64,
+        old_align: u32,
+        new_size: u64,
+        ptr: u64,
+    },
+    ResourceOwn {
+        ty: u32,
+        rep: u32,
+        index: u32,
+    },
+}
+
+/// A core WebAssembly value passed to or returned from a host function.
+///
+/// References are opaque across executions so only their nullness is
+/// recorded.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Value {
+    I32(u32),
+    I64(u64),
+    F32(u32),
+    F64(u64),
+    V128(u128),
+    Ref { null: bool },
+}
+
+impl Value {
+    fn from_raw(ty: &ValType, raw: &ValRaw) -> Value {
+        match ty {
+            ValType::I32 => Value::I32(raw.get_u32()),
+            ValType::I64 => Value::I64(raw.get_u64()),
+            ValType::F32 => Value::F32(raw.get_f32()),
+            ValType::F64 => Value::F64(raw.get_f64()),
+            ValType::V128 => Value::V128(raw.get_v128()),
+            ValType::Ref(r) => Value::Ref {
+                null: match r.heap_type().top() {
+                    HeapType::Func => raw.get_funcref().is_null(),
+                    HeapType::Extern => raw.get_externref() == 0,
+                    HeapType::Exn => raw.get_exnref() == 0,
+                    HeapType::Any => raw.get_anyref() == 0,
+                    _ => false,
+                },
+            },
+        }
+    }
+
+    fn to_raw(&self, ty: &ValType) -> Result<ValRaw> {
+        Ok(match (self, ty) {
+            (Value::I32(i), ValType::I32) => ValRaw::u32(*i),
+            (Value::I64(i), ValType::I64) => ValRaw::u64(*i),
+            (Value::F32(i), ValType::F32) => ValRaw::f32(*i),
+            (Value::F64(i), ValType::F64) => ValRaw::f64(*i),
+            (Value::V128(i), ValType::V128) => ValRaw::v128(*i),
+            (Value::Ref { null: true }, ValType::Ref(r)) => match r.heap_type().top() {
+                HeapType::Func => ValRaw::funcref(core::ptr::null_mut()),
+                HeapType::Extern => ValRaw::externref(0),
+                HeapType::Exn => ValRaw::exnref(0),
+                HeapType::Any => ValRaw::anyref(0),
+                _ => bail!("cannot replay a null `{ty}` result"),
+            },
+            _ => bail!("recorded result {self:?} does not have type `{ty}`"),
+        })
+    }
+}
+
+/// Reads the first `tys.len()` values of a host function's arguments.
+fn read_values(
+    tys: impl ExactSizeIterator<Item = ValType>,
+    values: &[MaybeUninit<ValRaw>],
+) -> Vec<Value> {
+    tys.zip(values)
+        .map(|(ty, raw)| Value::from_raw(&ty, unsafe { raw.assume_init_ref() }))
+        .collect()
+}
+
+/// Returns the chunks of `after` which differ from `before`, treating bytes
+/// beyond the end of `before` as zero.
+fn diff(before: &[u8], after: &[u8]) -> Vec<MemoryWrite> {
+    let mut writes: Vec<MemoryWrite> = Vec::new();
+    for (i, new) in after.chunks(DIFF_CHUNK).enumerate() {
+        let start = i * DIFF_CHUNK;
+        let old = before.get(start..).unwrap_or(&[]);
+        let old = &old[..old.len().min(new.len())];
+        let (overlap, grown) = new.split_at(old.len());
+        if overlap == old && grown.iter().all(|b| *b == 0) {
+            continue;
+        }
+        let offset = u64::try_from(start).unwrap();
+        match writes.last_mut() {
+            Some(w) if w.offset + u64::try_from(w.bytes.len()).unwrap() == offset => {
+                w.bytes.extend_from_slice(new)
+            }
+            _ => writes.push(MemoryWrite {
+                offset,
+                bytes: new.to_vec(),
+            }),
+        }
+    }
+    writes
+}
+
+/// Copies the recorded `writes` into `memory`.
+fn apply(memory: &mut [u8], writes: &[MemoryWrite]) -> Result<()> {
+    for write in writes {
+        let start = usize::try_from(write.offset)?;
+        let dst = start
+            .checked_add(write.bytes.len())
+            .and_then(|end| memory.get_mut(start..end))
+            .ok_or_else(|| anyhow!("recorded memory write at {start:#x} is out of bounds"))?;
+        dst.copy_from_slice(&write.bytes);
+    }
+    Ok(())
+}
+
+fn divergence(index: u64, event: &Event, actual: &str) -> Error {
+    anyhow!(
+        "host call #{index} diverged from the recorded trace: expected a call to {}, \
+         but the guest called {actual}",
+        event.describe()
+    )
+}
+
+/// How a core host function call should proceed, returned by
+/// [`begin_core_host_call`].
+pub(crate) enum CoreHostCall {
+    /// Call the host function as usual.
+    Call,
+    /// Call the host function and then record it with
+    /// [`CoreHostCall::finish`].
+    Record(Box<CoreRecording>),
+    /// The call was replayed from the trace and the host function must not be
+    /// called. Results have already been written.
+    Replayed,
+}
+
+pub(crate) struct CoreRecording {
+    recorder: HostCallRecorder,
+    ty: FuncType,
+    params: Vec<Value>,
+    memories: Vec<(u32, Memory, Vec<u8>)>,
+}
+
+/// Called before the host function behind a core import is invoked with the
+/// arguments in `values`.
+pub(crate) fn begin_core_host_call<T>(
+    store: &mut StoreContextMut<'_, T>,
+    caller: Instance,
+    ty: VMSharedTypeIndex,
+    values: &mut [MaybeUninit<ValRaw>],
+) -> Result<CoreHostCall> {
+    let Some(log) = store.0.host_call_log() else {
+        return Ok(CoreHostCall::Call);
+    };
+    let ty = FuncType::from_shared_type_index(store.engine(), ty);
+    let params = read_values(ty.params(), values);
+    let memories = caller_memories(store, caller);
+
+    let replayer = match log {
+        HostCallLog::Record(recorder) => {
+            let memories = memories
+                .into_iter()
+                .map(|(i, memory)| (i, memory, memory.data(&*store).to_vec()))
+                .collect();
+            return Ok(CoreHostCall::Record(Box::new(CoreRecording {
+                recorder,
+                ty,
+                params,
+                memories,
+            })));
+        }
+        HostCallLog::Replay(replayer) => replayer,
+    };
+
+    let signature = ty.to_string();
+    let (index, event) = replayer.next()?;
+    let outcome = match event {
+        Event::Core {
+            signature: ref recorded,
+            params: ref recorded_params,
+            outcome,
+        } if *recorded == signature => {
+            if *recorded_params != params {
+                bail!(
+                    "host call #{index} diverged from the recorded trace: `{signature}` was \
+                     called with {params:?} but {recorded_params:?} was recorded"
+                );
+            }
+            outcome
+        }
+        event => {
+            return Err(divergence(
+                index,
+                &event,
+                &format!("core host function `{signature}`"),
+            ));
+        }
+    };
+    let effects = match outcome {
+        Outcome::Returned(effects) => effects,
+        Outcome::Failed(message) => return Err(Error::msg(message)),
+    };
+
+    for recorded in &effects.memories {
+        let (_, memory) = memories
+            .iter()
+            .find(|(i, _)| *i == recorded.index)
+            .ok_or_else(|| anyhow!("recorded write to unknown memory {}", recorded.index))?;
+        let size = u64::try_from(memory.data_size(&*store))?;
+        if size < recorded.size {
+            let page_size = memory.page_size(&*store);
+            memory.grow(&mut *store, (recorded.size - size) / page_size)?;
+        }
+        apply(memory.data_mut(&mut *store), &recorded.writes)?;
+    }
+
+    if effects.results.len() != ty.results().len() {
+        bail!("host call #{index} recorded the wrong number of results");
+    }
+    for ((dst, ty), value) in values.iter_mut().zip(ty.results()).zip(&effects.results) {
+        *dst = MaybeUninit::new(value.to_raw(&ty)?);
+    }
+    Ok(CoreHostCall::Replayed)
+}
+
+impl CoreHostCall {
+    /// Called after the host function returned `result`, with results stored
+    /// in `values`.
+    pub(crate) fn finish<T, R>(
+        self,
+        store: &mut StoreContextMut<'_, T>,
+        values: &[MaybeUninit<ValRaw>],
+        result: Result<&R, &Error>,
+    ) -> Result<()> {
+        let CoreHostCall::Record(recording) = self else {
+            return Ok(());
+        };
+        let CoreRecording {
+            recorder,
+            ty,
+            params,
+            memories,
+        } = *recording;
+        let outcome = match result {
+            Ok(_) => {
+                let results = read_values(ty.results(), values);
+                if results.contains(&Value::Ref { null: false }) {
+                    bail!("cannot record host function `{ty}` returning non-null references");
+                }
+                let memories = memories
+                    .into_iter()
+                    .filter_map(|(index, memory, before)| {
+                        let after = memory.data(&*store);
+                        let writes = diff(&before, after);
+                        if writes.is_empty() {
+                            return None;
+                        }
+                        Some(MemoryEffects {
+                            index,
+                            size: u64::try_from(after.len()).unwrap(),
+                            writes,
+                        })
+                    })
+                    .collect();
+                Outcome::Returned(CoreEffects { results, memories })
+            }
+            Err(e) => Outcome::Failed(format!("{e:?}")),
+        };
+        recorder.write(&Event::Core {
+            signature: ty.to_string(),
+            params,
+            outcome,
+        })
+    }
+}
+
+/// Returns the non-shared memories, defined or imported, of `instance`.
+fn caller_memories<T>(store: &StoreContextMut<'_, T>, instance: Instance) -> Vec<(u32, Memory)> {
+    instance
+        .all_memories(store.0)
+        .map(|(i, memory)| (i.as_u32(), memory))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .filter(|(_, memory)| !memory.ty(store).is_shared())
+        .collect()
+}
+
+/// Views the arguments of a `Func::new_unchecked` host function in the same
+/// way as those of typed host functions.
+pub(crate) fn as_uninit(values: &mut [ValRaw]) -> &mut [MaybeUninit<ValRaw>] {
+    // SAFETY: `MaybeUninit<ValRaw>` has the same layout as `ValRaw` and only
+    // initialized values are ever written through the returned slice.
+    unsafe { &mut *(values as *mut [ValRaw] as *mut [MaybeUninit<ValRaw>]) }
+}
+
+#[cfg(feature = "component-model")]
+pub(crate) use self::component::*;
+
+#[cfg(feature = "component-model")]
+mod component {
+    use super::*;
+    use crate::component::__internal::{LowerContext, Options};
+    use wasmtime_environ::component::{
+        RuntimeComponentInstanceIndex, TypeFuncIndex, TypeResourceTableIndex,
+    };
+
+    impl StoreOpaque {
+        /// Notes that `effect` happened while lowering results, if they're
+        /// being recorded.
+        pub(crate) fn record_lowering(&mut self, effect: impl FnOnce() -> LoweringEffect) {
+            if let Some(effects) = &mut self.host_calls_mut().lowering {
+                effects.push(effect());
+            }
+        }
+    }
+
+    /// How a component host function call should proceed, returned by
+    /// [`begin_component_host_call`].
+    pub(crate) enum ComponentHostCall {
+        /// Call the host function, recording it if `Some`.
+        Call(ComponentRecording),
+        /// Don't call the host function but instead lower these recorded
+        /// results with [`replay_lowering`].
+        Replay(ComponentEffects),
+    }
+
+    pub(crate) struct ComponentRecording(Option<RecordingState>);
+
+    struct RecordingState {
+        recorder: HostCallRecorder,
+        func: u32,
+        caller: u32,
+        memory: Option<Vec<u8>>,
+    }
+
+    /// Called once a component host function's parameters have been lifted,
+    /// but before the host function is called.
+    pub(crate) fn begin_component_host_call(
+        store: &mut StoreOpaque,
+        func: TypeFuncIndex,
+        caller: RuntimeComponentInstanceIndex,
+        async_: bool,
+    ) -> Result<ComponentHostCall> {
+        let Some(log) = store.host_call_log() else {
+            return Ok(ComponentHostCall::Call(ComponentRecording(None)));
+        };
+        if async_ {
+            bail!("recording or replaying async-lowered host calls is not supported");
+        }
+        let func = func.as_u32();
+        let caller = caller.as_u32();
+        let replayer = match log {
+            HostCallLog::Record(recorder) => {
+                return Ok(ComponentHostCall::Call(ComponentRecording(Some(
+                    RecordingState {
+                        recorder,
+                        func,
+                        caller,
+                        memory: None,
+                    },
+                ))));
+            }
+            HostCallLog::Replay(replayer) => replayer,
+        };
+        let (index, event) = replayer.next()?;
+        match event {
+            Event::Component {
+                func: f,
+                caller: c,
+                outcome,
+            } if f == func && c == caller => match outcome {
+                Outcome::Returned(effects) => Ok(ComponentHostCall::Replay(effects)),
+                Outcome::Failed(message) => Err(Error::msg(message)),
+            },
+            event => Err(divergence(
+                index,
+                &event,
+                &format!("component host function of type {func} from instance {caller}"),
+            )),
+        }
+    }
+
+    impl ComponentRecording {
+        /// Called with the result of the host function before it's lowered.
+        ///
+        /// Failures are recorded immediately while for successful calls this
+        /// starts capturing the effects of lowering.
+        pub(crate) fn returned<R>(
+            &mut self,
+            store: &mut StoreOpaque,
+            options: &Options,
+            result: Result<R>,
+        ) -> Result<R> {
+            let Some(state) = &mut self.0 else {
+                return result;
+            };
+            match result {
+                Ok(ret) => {
+                    if options.has_memory() {
+                        state.memory = Some(options.memory(store).to_vec());
+                    }
+                    store.host_calls_mut().lowering = Some(Vec::new());
+                    Ok(ret)
+                }
+                Err(e) => {
+                    state.recorder.write(&Event::Component {
+                        func: state.func,
+                        caller: state.caller,
+                        outcome: Outcome::Failed(format!("{e:?}")),
+                    })?;
+                    Err(e)
+                }
+            }
+        }
+
+        /// Called after results have been lowered, with the flat results
+        /// stored in `results`.
+        pub(crate) fn lowered(
+            self,
+            store: &mut StoreOpaque,
+            options: &Options,
+            results: &[MaybeUninit<ValRaw>],
+        ) -> Result<()> {
+            let Some(state) = self.0 else {
+                return Ok(());
+            };
+            let lowering = store.host_calls_mut().lowering.take().unwrap_or_default();
+            let writes = match &state.memory {
+                Some(before) => diff(before, options.memory(store)),
+                None => Vec::new(),
+            };
+            let results = results
+                .iter()
+                .map(|raw| unsafe { raw.assume_init_ref() }.get_u64())
+                .collect();
+            state.recorder.write(&Event::Component {
+                func: state.func,
+                caller: state.caller,
+                outcome: Outcome::Returned(ComponentEffects {
+                    lowering,
+                    writes,
+                    results,
+                }),
+            })
+        }
+    }
+
+    /// Applies recorded `effects` in place of lowering a host function's
+    /// results, writing the flat results to `results`.
+    pub(crate) fn replay_lowering<T>(
+        cx: &mut LowerContext<'_, T>,
+        effects: ComponentEffects,
+        results: &mut [MaybeUninit<ValRaw>],
+    ) -> Result<()> {
+        for effect in effects.lowering {
+            match effect {
+                LoweringEffect::Realloc {
+                    old,
+                    old_size,
+                    old_align,
+                    new_size,
+                    ptr,
+                } => {
+                    let actual = cx.realloc(
+                        usize::try_from(old)?,
+                        usize::try_from(old_size)?,
+                        old_align,
+                        usize::try_from(new_size)?,
+                    )?;
+                    if u64::try_from(actual)? != ptr {
+                        bail!("replayed `realloc` returned {actual:#x} but {ptr:#x} was recorded");
+                    }
+                }
+                LoweringEffect::ResourceOwn { ty, rep, index } => {
+                    let ty = TypeResourceTableIndex::from_u32(ty);
+                    let actual = cx.guest_resource_lower_own(ty, rep)?;
+                    if actual != index {
+                        bail!(
+                            "replayed resource was given handle {actual} but {index} was recorded"
+                        );
+                    }
+                }
+            }
+        }
+        if !effects.writes.is_empty() {
+            apply(cx.as_slice_mut(), &effects.writes)?;
+        }
+        if effects.results.len() != results.len() {
+            bail!("recorded the wrong number of flat results");
+        }
+        for (dst, raw) in results.iter_mut().zip(effects.results) {
+            *dst = MaybeUninit::new(ValRaw::u64(raw));
+        }
+        Ok(())
+    }
+}
@@ -81,10 +81,13 @@ use crate::RootSet;
 use crate::component::ComponentStoreData;
 #[cfg(feature = "component-model-async")]
 use crate::component::concurrent;
+use crate::debugger::DebugState;
 #[cfg(feature = "async")]
 use crate::fiber;
 use crate::module::RegisteredModuleId;
 use crate::prelude::*;
+#[cfg(feature = "record-replay")]
+use crate::record_replay::HostCallState;
 #[cfg(feature = "gc")]
 use crate::runtime::vm::GcRootsList;
 #[cfg(feature = "stack-switching")]
@@ -96,8 +99,8 @@ use crate::runtime::vm::{
     SignalHandler, StoreBox, StorePtr, Unwind, VMContext, VMFuncRef, VMGcRef, VMStoreContext,
 };
 use crate::trampoline::VMHostGlobalContext;
+use crate::{DebugAction, DebugFrame, Global, Instance, Memory, Table, Uninhabited};
 use crate::{Engine, Module, Trap, Val, ValRaw, module::ModuleRegistry};
-use crate::{Global, Instance, Memory, Table, Uninhabited};
 use alloc::sync::Arc;
 use core::fmt;
 use core::marker;
@@ -106,7 +109,9 @@ use core::num::NonZeroU64;
 use core::ops::{Deref, DerefMut};
 use core::pin::Pin;
 use core::ptr::NonNull;
-use wasmtime_environ::{DefinedGlobalIndex, DefinedTableIndex, EntityRef, PrimaryMap, TripleExt};
+use wasmtime_environ::{
+    DefinedGlobalIndex, DefinedTableIndex, EntityRef, FuncIndex, PrimaryMap, TripleExt,
+};
 
 mod context;
 pub use self::context::*;
@@ -237,6 +242,9 @@ pub struct StoreInner<T: 'static> {
     #[cfg(target_has_atomic = "64")]
     epoch_deadline_behavior:
         Option<Box<dyn FnMut(StoreContextMut<T>) -> Result<UpdateDeadline> + Send + Sync>>,
+    debug_handler: Option<
+        Box<dyn FnMut(StoreContextMut<'_, T>, &DebugFrame) -> Result<DebugAction> + Send + Sync>,
+    >,
     // for comments about `ManuallyDrop`, see `Store::into_data`
     data: ManuallyDrop<T>,
 }
@@ -391,6 +399,13 @@ pub struct StoreOpaque {
     /// guest code.
     pkey: Option<ProtectionKey>,
 
+    /// Breakpoints and single-stepping state for guest debugging.
+    debug: DebugState,
+
+    /// The trace that host calls are recorded to or replayed from, if any.
+    #[cfg(feature = "record-replay")]
+    host_calls: HostCallState,
+
     /// Runtime state for components used in the handling of resources, borrow,
     /// and calls. These also interact with the `ResourceAny` type and its
     /// internal representation.
@@ -592,6 +607,9 @@ impl<T> Store<T> {
             hostcall_val_storage: Vec::new(),
             wasm_val_raw_storage: Vec::new(),
             pkey,
+            debug: DebugState::default(),
+            #[cfg(feature = "record-replay")]
+            host_calls: HostCallState::default(),
             #[cfg(feature = "component-model")]
             component_host_table: Default::default(),
             #[cfg(feature = "component-model")]
@@ -608,6 +626,7 @@ impl<T> Store<T> {
             call_hook: None,
             #[cfg(target_has_atomic = "64")]
             epoch_deadline_behavior: None,
+            debug_handler: None,
             data: ManuallyDrop::new(data),
         });
 
@@ -985,6 +1004,74 @@ impl<T> Store<T> {
     ) {
         self.inner.epoch_deadline_callback(Box::new(callback));
     }
+
+    /// Configures a handler that is invoked whenever WebAssembly execution
+    /// pauses at a breakpoint or while single-stepping.
+    ///
+    /// The handler is given a [`DebugFrame`] describing the instruction that
+    /// is about to execute along with the current values of the function's
+    /// locals and operand stack. The handler's [`DebugAction`] determines how
+    /// execution resumes: [`DebugAction::Continue`] runs until the next
+    /// breakpoint while [`DebugAction::Step`] pauses again before the next
+    /// instruction. If the handler returns an error then it is raised as a
+    /// trap.
+    ///
+    /// Execution only pauses in code compiled with
+    /// [`Config::guest_debug`](crate::Config::guest_debug) enabled. Use
+    /// [`Store::add_breakpoint`] and [`Store::set_single_step`] to choose
+    /// where execution pauses.
+    pub fn debug_handler(
+        &mut self,
+        handler: impl FnMut(StoreContextMut<'_, T>, &DebugFrame) -> Result<DebugAction>
+        + Send
+        + Sync
+        + 'static,
+    ) {
+        self.inner.debug_handler = Some(Box::new(handler));
+        self.inner.sync_debug_active();
+    }
+
+    /// Adds a breakpoint before the instruction at `offset` within the
+    /// function `func_index` of `module`.
+    ///
+    /// The `offset` is the byte offset of the instruction within the original
+    /// wasm module, the same as [`FrameInfo::module_offset`]. The breakpoint
+    /// applies to all instances of `module` within this store. When execution
+    /// reaches it the handler configured with [`Store::debug_handler`] is
+    /// invoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`Config::guest_debug`](crate::Config::guest_debug)
+    /// was not enabled or if `func_index` does not refer to a function defined
+    /// in `module`.
+    ///
+    /// [`FrameInfo::module_offset`]: crate::FrameInfo::module_offset
+    pub fn add_breakpoint(&mut self, module: &Module, func_index: u32, offset: u32) -> Result<()> {
+        self.inner.add_breakpoint(module, func_index, offset)
+    }
+
+    /// Removes a breakpoint previously added with [`Store::add_breakpoint`].
+    ///
+    /// Returns whether the breakpoint was present.
+    pub fn remove_breakpoint(&mut self, module: &Module, func_index: u32, offset: u32) -> bool {
+        self.inner.remove_breakpoint(module, func_index, offset)
+    }
+
+    /// Removes all breakpoints from this store.
+    pub fn clear_breakpoints(&mut self) {
+        self.inner.clear_breakpoints();
+    }
+
+    /// Configures whether WebAssembly execution pauses before every
+    /// instruction, invoking the handler configured with
+    /// [`Store::debug_handler`].
+    ///
+    /// This is also updated by the [`DebugAction`] returned from the debug
+    /// handler.
+    pub fn set_single_step(&mut self, enable: bool) {
+        self.inner.set_single_step(enable);
+    }
 }
 
 impl<'a, T> StoreContext<'a, T> {
@@ -1078,6 +1165,34 @@ impl<'a, T> StoreContextMut<'a, T> {
     pub fn epoch_deadline_trap(&mut self) {
         self.0.epoch_deadline_trap();
     }
+
+    /// Adds a guest debugging breakpoint.
+    ///
+    /// For more information see [`Store::add_breakpoint`].
+    pub fn add_breakpoint(&mut self, module: &Module, func_index: u32, offset: u32) -> Result<()> {
+        self.0.add_breakpoint(module, func_index, offset)
+    }
+
+    /// Removes a guest debugging breakpoint.
+    ///
+    /// For more information see [`Store::remove_breakpoint`].
+    pub fn remove_breakpoint(&mut self, module: &Module, func_index: u32, offset: u32) -> bool {
+        self.0.remove_breakpoint(module, func_index, offset)
+    }
+
+    /// Removes all guest debugging breakpoints.
+    ///
+    /// For more information see [`Store::clear_breakpoints`].
+    pub fn clear_breakpoints(&mut self) {
+        self.0.clear_breakpoints();
+    }
+
+    /// Configures single-stepping of WebAssembly execution.
+    ///
+    /// For more information see [`Store::set_single_step`].
+    pub fn set_single_step(&mut self, enable: bool) {
+        self.0.set_single_step(enable);
+    }
 }
 
 impl<T> StoreInner<T> {
@@ -2009,6 +2124,11 @@ at https://bytecodealliance.org/security.
         self.pkey.is_some()
     }
 
+    #[cfg(feature = "record-replay")]
+    pub(crate) fn host_calls_mut(&mut self) -> &mut HostCallState {
+        &mut self.host_calls
+    }
+
     pub(crate) fn executor(&mut self) -> ExecutorRef<'_> {
         match &mut self.executor {
             Executor::Interpreter(i) => ExecutorRef::Interpreter(i.as_interpreter_ref()),
@@ -2294,6 +2414,54 @@ unsafe impl<T> vm::VMStore for StoreInner<T> {
         delta_result
     }
 
+    fn debug_hook(
+        &mut self,
+        instance: InstanceId,
+        func_index: FuncIndex,
+        offset: u32,
+        values: &[ValRaw],
+        kinds: &[u8],
+        num_locals: usize,
+    ) -> Result<()> {
+        let module = self
+            .module_for_instance(StoreInstanceId::new(self.id(), instance))
+            .map(|m| m.id());
+        let reason = match module.and_then(|m| self.debug.pause_reason(m, func_index, offset)) {
+            Some(reason) => reason,
+            None => return Ok(()),
+        };
+
+        // Temporarily take the handler to avoid mutably borrowing the store
+        // multiple times, and keep any GC roots created for the frame's values
+        // scoped to this pause.
+        let Some(mut handler) = self.debug_handler.take() else {
+            return Ok(());
+        };
+        let scope = self.gc_roots().enter_lifo_scope();
+        let frame = DebugFrame::new(
+            &mut self.inner,
+            reason,
+            instance,
+            func_index,
+            offset,
+            values,
+            kinds,
+            num_locals,
+        );
+        let result = handler((&mut *self).as_context_mut(), &frame);
+        drop(frame);
+        self.exit_gc_lifo_scope(scope);
+
+        // Put back the handler unless it was replaced while running.
+        if self.debug_handler.is_none() {
+            self.debug_handler = Some(handler);
+        }
+        let action = result?;
+        self.inner.debug.apply(action);
+        self.sync_debug_active();
+        Ok(())
+    }
+
     #[cfg(feature = "gc")]
     unsafe fn maybe_async_grow_or_collect_gc_heap(
         &mut self,
@@ -2347,6 +2515,43 @@ impl<T> StoreInner<T> {
     fn get_epoch_deadline(&mut self) -> u64 {
         *self.vm_store_context.epoch_deadline.get_mut()
     }
+
+    fn add_breakpoint(&mut self, module: &Module, func_index: u32, offset: u32) -> Result<()> {
+        self.inner
+            .debug
+            .add_breakpoint(module, func_index, offset)?;
+        self.sync_debug_active();
+        Ok(())
+    }
+
+    fn remove_breakpoint(&mut self, module: &Module, func_index: u32, offset: u32) -> bool {
+        let removed = self
+            .inner
+            .debug
+            .remove_breakpoint(module, func_index, offset);
+        self.sync_debug_active();
+        removed
+    }
+
+    fn clear_breakpoints(&mut self) {
+        self.inner.debug.clear_breakpoints();
+        self.sync_debug_active();
+    }
+
+    fn set_single_step(&mut self, enable: bool) {
+        self.inner.debug.set_single_step(enable);
+        self.sync_debug_active();
+    }
+
+    fn sync_debug_active(&mut self) {
+        let has_handler = self.debug_handler.is_some();
+        let StoreOpaque {
+            debug,
+            vm_store_context,
+            ..
+        } = &mut self.inner;
+        debug.sync(vm_store_context, has_handler);
+    }
 }
 
 impl<T: Default> Default for Store<T> {
@@ -40,7 +40,7 @@ pub(crate) struct f64x2(crate::uninhabited::Uninhabited);
 use crate::StoreContextMut;
 use crate::prelude::*;
 use crate::store::StoreInner;
-use crate::store::StoreOpaque;
+use crate::store::{InstanceId, StoreOpaque};
 use crate::type_registry::RegisteredType;
 use alloc::sync::Arc;
 use core::fmt;
@@ -49,7 +49,7 @@ use core::ops::DerefMut;
 use core::ptr::NonNull;
 use core::sync::atomic::{AtomicUsize, Ordering};
 use wasmtime_environ::{
-    DefinedFuncIndex, DefinedMemoryIndex, HostPtr, VMOffsets, VMSharedTypeIndex,
+    DefinedFuncIndex, DefinedMemoryIndex, FuncIndex, HostPtr, VMOffsets, VMSharedTypeIndex,
 };
 
 #[cfg(feature = "gc")]
@@ -150,9 +150,9 @@ mod async_yield;
 #[cfg(feature = "async")]
 pub use crate::runtime::vm::async_yield::*;
 
-#[cfg(feature = "gc-null")]
+#[cfg(any(feature = "gc-null", feature = "gc-generational"))]
 mod send_sync_unsafe_cell;
-#[cfg(feature = "gc-null")]
+#[cfg(any(feature = "gc-null", feature = "gc-generational"))]
 pub use send_sync_unsafe_cell::SendSyncUnsafeCell;
 
 cfg_if::cfg_if! {
@@ -233,6 +233,23 @@ pub unsafe trait VMStore: 'static {
     #[cfg(target_has_atomic = "64")]
     fn new_epoch(&mut self) -> Result<u64, Error>;
 
+    /// Callback invoked by code compiled with guest debugging instrumentation
+    /// before the instruction at `offset` in `func_index` executes, while the
+    /// store's `debug_active` flag is set.
+    ///
+    /// `values` holds the function's locals followed by its operand stack and
+    /// `kinds` holds the `DebugValueKind` of each value. If an error is
+    /// returned it's raised as a trap.
+    fn debug_hook(
+        &mut self,
+        instance: InstanceId,
+        func_index: FuncIndex,
+        offset: u32,
+        values: &[ValRaw],
+        kinds: &[u8],
+        num_locals: usize,
+    ) -> Result<(), Error>;
+
     /// Callback invoked whenever an instance needs to grow-or-collect the GC
     /// heap.
     ///
@@ -413,6 +430,15 @@ impl ModuleRuntimeInfo {
         Some(ptr.cast())
     }
 
+    /// Returns whether this module was compiled for tiered compilation, in
+    /// which case its code calls defined functions through their `VMFuncRef`s.
+    fn tiered_compilation(&self) -> bool {
+        match self {
+            ModuleRuntimeInfo::Module(m) => m.engine().tunables().tiered_compilation,
+            ModuleRuntimeInfo::Bare(_) => false,
+        }
+    }
+
     /// Returns the `MemoryImage` structure used for copy-on-write
     /// initialization of the memory, if it's applicable.
     fn memory_image(
@@ -24,6 +24,11 @@ mod null;
 #[cfg(feature = "gc-null")]
 pub use null::*;
 
+#[cfg(feature = "gc-generational")]
+mod generational;
+#[cfg(feature = "gc-generational")]
+pub use generational::*;
+
 // Explicit methods to clearly indicate that truncation is desired when used.
 #[expect(
     clippy::cast_possible_truncation,
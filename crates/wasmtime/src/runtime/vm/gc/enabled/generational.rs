@@ -0,0 +1,1077 @@
+//! The generational collector.
+//!
+//! The generational collector bump allocates new objects into a nursery and
+//! periodically evacuates the nursery's survivors into the old generation.
+//!
+//! The GC heap is laid out as a single, contiguous sequence of objects with no
+//! padding between them:
+//!
+//! ```text
+//! 0       HEADER_ALIGN           old_end                 next          len
+//! +-------+----------------------+-----------------------+-------------+
+//! | null  | old generation       | nursery               | free space  |
+//! +-------+----------------------+-----------------------+-------------+
+//! ```
+//!
+//! Allocation (both from compiled Wasm code and from the runtime) bumps the
+//! `next` finger. When the heap is full and cannot be grown, we perform a
+//! *minor* collection that only considers objects in the nursery: we mark
+//! everything in the nursery that is reachable from the roots or from the old
+//! generation, and then slide the marked objects down so that they are
+//! contiguous with the old generation, at which point they become part of the
+//! old generation themselves. Everything left in the nursery is garbage.
+//!
+//! To find old-to-young edges without tracing the whole old generation, the
+//! heap is divided into fixed-size cards and writes of GC references into GC
+//! objects mark the card containing the written field as dirty. Compiled Wasm
+//! code does this via an inline write barrier, and the runtime does it whenever
+//! the host gets mutable access to an object's data. During a minor collection,
+//! the old objects overlapping dirty cards are treated as additional roots.
+//!
+//! When the old generation grows too large, a minor collection is followed by
+//! a *major* collection, which marks and compacts the whole heap.
+//!
+//! This is a moving collector, so compiled Wasm code must include every live GC
+//! reference in its stack maps, and every GC root is updated after its referent
+//! moves.
+
+use super::*;
+use crate::hash_map::HashMap;
+use crate::{
+    Engine, EngineWeak,
+    prelude::*,
+    vm::{
+        ExternRefHostDataId, ExternRefHostDataTable, GarbageCollection, GcHeap, GcHeapObject,
+        GcProgress, GcRoot, GcRootsIter, GcRuntime, SendSyncPtr, SendSyncUnsafeCell, VMGcHeader,
+        VMGcObjectData, VMGcRef, VMMemoryDefinition,
+    },
+};
+use core::ops::Range;
+use core::ptr::NonNull;
+use core::sync::atomic::AtomicUsize;
+use core::{alloc::Layout, any::Any, mem, num::NonZeroU32};
+use wasmtime_environ::generational::{
+    ARRAY_LENGTH_OFFSET, CARD_SIZE, CARD_SIZE_LOG2, GenerationalTypeLayouts, HEADER_ALIGN,
+    HEADER_MARK_BIT, round_object_size,
+};
+use wasmtime_environ::{
+    GcArrayLayout, GcExceptionLayout, GcLayout, GcStructLayout, GcTypeLayouts, VMGcKind,
+    VMSharedTypeIndex,
+};
+
+#[expect(clippy::cast_possible_truncation, reason = "known to not overflow")]
+const GC_REF_ARRAY_ELEMS_OFFSET: u32 = ARRAY_LENGTH_OFFSET + (mem::size_of::<u32>() as u32);
+
+/// The heap index of the first object in the heap.
+///
+/// Index zero is reserved for the null reference, and every object is aligned
+/// to `HEADER_ALIGN`.
+const HEAP_START: u32 = HEADER_ALIGN;
+
+/// The minimum size, in bytes, that the old generation must reach before we
+/// consider performing a major collection.
+const MIN_MAJOR_THRESHOLD: u32 = 1 << 16;
+
+/// A sentinel in `GenerationalHeap::object_starts` for cards that do not
+/// intersect any object in the old generation.
+const NO_OBJECT: u32 = u32::MAX;
+
+/// The generational collector.
+#[derive(Default)]
+pub struct GenerationalCollector {
+    layouts: GenerationalTypeLayouts,
+}
+
+unsafe impl GcRuntime for GenerationalCollector {
+    fn layouts(&self) -> &dyn GcTypeLayouts {
+        &self.layouts
+    }
+
+    fn new_gc_heap(&self, engine: &Engine) -> Result<Box<dyn GcHeap>> {
+        let heap = GenerationalHeap::new(engine)?;
+        Ok(Box::new(heap) as _)
+    }
+}
+
+/// How to trace a GC object.
+enum TraceInfo {
+    /// How to trace an array.
+    Array {
+        /// Whether this array type's elements are GC references, and need
+        /// tracing.
+        gc_ref_elems: bool,
+    },
+
+    /// How to trace a struct.
+    Struct {
+        /// The offsets of each GC reference field that needs tracing in
+        /// instances of this struct type.
+        gc_ref_offsets: Box<[u32]>,
+    },
+}
+
+/// The data shared between the generational heap and compiled Wasm code.
+///
+/// A pointer to this is stored in `VMContext::gc_heap_data`.
+#[repr(C)]
+struct VMGenerationalHeapData {
+    /// Pointer to the first entry in the card table, which has one byte per
+    /// `CARD_SIZE` bytes of GC heap.
+    ///
+    /// See `wasmtime_environ::generational::HEAP_DATA_CARD_TABLE_OFFSET`.
+    card_table: SendSyncPtr<u8>,
+
+    /// Bump-allocation finger.
+    ///
+    /// See `wasmtime_environ::generational::heap_data_next_offset`.
+    next: u32,
+}
+
+/// A GC heap for the generational collector.
+struct GenerationalHeap {
+    engine: EngineWeak,
+
+    /// For every type that we have allocated in this heap, how do we trace it?
+    ///
+    /// Compiled Wasm code allocates objects inline without informing us, so
+    /// entries are lazily inserted when we first trace an object of a given
+    /// type.
+    trace_infos: HashMap<VMSharedTypeIndex, TraceInfo>,
+
+    /// Count of how many no-gc scopes we are currently within.
+    no_gc_count: u64,
+
+    /// The card table pointer and bump-allocation finger.
+    ///
+    /// NB: this is boxed so that it has a stable address, since it is exposed
+    /// directly to compiled Wasm code through the vmctx, and is an
+    /// `UnsafeCell` because it is written to by compiled Wasm code.
+    heap_data: Box<SendSyncUnsafeCell<VMGenerationalHeapData>>,
+
+    /// The card table: one byte per `CARD_SIZE` bytes of GC heap, non-zero if
+    /// the card is dirty.
+    ///
+    /// `heap_data.card_table` points at this vector's storage and must be
+    /// updated whenever it is reallocated.
+    cards: Vec<u8>,
+
+    /// For each card, the heap index of the first old-generation object that
+    /// intersects the card, or `NO_OBJECT`.
+    object_starts: Vec<u32>,
+
+    /// The end of the old generation and start of the nursery.
+    old_end: u32,
+
+    /// Perform a major collection when the old generation grows larger than
+    /// this many bytes.
+    major_threshold: u32,
+
+    /// Reusable scratch space for tracing.
+    scratch: Vec<u32>,
+
+    /// The storage for the GC heap itself.
+    memory: Option<crate::vm::Memory>,
+
+    /// The cached `VMMemoryDefinition` for `self.memory` so that we don't have
+    /// to make indirect calls through a `dyn RuntimeLinearMemory` object.
+    ///
+    /// Must be updated and kept in sync with `self.memory`, cleared when the
+    /// memory is taken and updated when the memory is replaced.
+    vmmemory: Option<VMMemoryDefinition>,
+}
+
+/// The common header for all objects in the generational collector.
+#[repr(C)]
+struct VMGenerationalHeader {
+    header: VMGcHeader,
+
+    /// The size of this object, rounded up to a multiple of `HEADER_ALIGN`.
+    object_size: u32,
+
+    /// This object's new heap index, only valid during a collection.
+    forwarding: u32,
+}
+
+unsafe impl GcHeapObject for VMGenerationalHeader {
+    #[inline]
+    fn is(_header: &VMGcHeader) -> bool {
+        // All generational objects have a generational header.
+        true
+    }
+}
+
+impl VMGenerationalHeader {
+    /// Is this object marked?
+    #[inline]
+    fn is_marked(&self) -> bool {
+        self.header.reserved_u26() & HEADER_MARK_BIT != 0
+    }
+
+    /// Set or clear this object's mark bit.
+    #[inline]
+    fn set_marked(&mut self, marked: bool) {
+        let reserved = self.header.reserved_u26();
+        let new_reserved = if marked {
+            reserved | HEADER_MARK_BIT
+        } else {
+            reserved & !HEADER_MARK_BIT
+        };
+        self.header.set_reserved_u26(new_reserved);
+    }
+}
+
+/// The common header for all arrays in the generational collector.
+#[repr(C)]
+struct VMGenerationalArrayHeader {
+    header: VMGenerationalHeader,
+    length: u32,
+}
+
+unsafe impl GcHeapObject for VMGenerationalArrayHeader {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.kind() == VMGcKind::ArrayRef
+    }
+}
+
+/// The representation of an `externref` in the generational collector.
+#[repr(C)]
+struct VMGenerationalExternRef {
+    header: VMGenerationalHeader,
+    host_data: ExternRefHostDataId,
+}
+
+unsafe impl GcHeapObject for VMGenerationalExternRef {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.kind() == VMGcKind::ExternRef
+    }
+}
+
+/// Get the card containing the given heap index.
+#[inline]
+fn card_of(index: u32) -> usize {
+    usize::try_from(index >> CARD_SIZE_LOG2).unwrap()
+}
+
+/// Get the heap index of the object at `index`, as a `VMGcRef`.
+#[inline]
+fn gc_ref_at(index: u32) -> VMGcRef {
+    VMGcRef::from_heap_index(NonZeroU32::new(index).unwrap()).unwrap()
+}
+
+impl GenerationalHeap {
+    /// Construct a new, default generational heap.
+    fn new(engine: &Engine) -> Result<Self> {
+        log::trace!("allocating new generational heap");
+        Ok(Self {
+            engine: engine.weak(),
+            trace_infos: HashMap::with_capacity(1),
+            no_gc_count: 0,
+            heap_data: Box::new(SendSyncUnsafeCell::new(VMGenerationalHeapData {
+                card_table: SendSyncPtr::new(NonNull::dangling()),
+                next: u32::MAX,
+            })),
+            cards: Vec::new(),
+            object_starts: Vec::new(),
+            old_end: HEAP_START,
+            major_threshold: MIN_MAJOR_THRESHOLD,
+            scratch: Vec::new(),
+            memory: None,
+            vmmemory: None,
+        })
+    }
+
+    fn engine(&self) -> Engine {
+        self.engine.upgrade().unwrap()
+    }
+
+    /// Get the current bump-allocation finger.
+    #[inline]
+    fn next(&mut self) -> u32 {
+        self.heap_data.get_mut().next
+    }
+
+    /// Set the bump-allocation finger.
+    #[inline]
+    fn set_next(&mut self, next: u32) {
+        self.heap_data.get_mut().next = next;
+    }
+
+    /// The length of the GC heap, clamped to the range of heap indices.
+    fn heap_len(&self) -> u32 {
+        let len = self.vmmemory.as_ref().unwrap().current_length();
+        u32::try_from(len).unwrap_or(u32::MAX)
+    }
+
+    /// Resize the card and object-start tables to cover the whole GC heap, and
+    /// update the card table pointer exposed to compiled Wasm code.
+    fn resize_card_tables(&mut self) {
+        let num_cards = usize::try_from(self.heap_len().div_ceil(CARD_SIZE)).unwrap();
+        self.cards.resize(num_cards, 0);
+        self.object_starts.resize(num_cards, NO_OBJECT);
+        self.sync_card_table_ptr();
+    }
+
+    /// Update the card table pointer exposed to compiled Wasm code.
+    ///
+    /// Must be called after any mutable access to `self.cards`.
+    fn sync_card_table_ptr(&mut self) {
+        let ptr = NonNull::new(self.cards.as_mut_ptr()).unwrap();
+        self.heap_data.get_mut().card_table = SendSyncPtr::new(ptr);
+    }
+
+    /// Mark every card overlapping the given range of the heap as dirty.
+    fn mark_cards(&mut self, range: &Range<usize>) {
+        debug_assert!(range.start < range.end);
+        let first = range.start >> CARD_SIZE_LOG2;
+        let last = (range.end - 1) >> CARD_SIZE_LOG2;
+        self.cards[first..=last].fill(1);
+        self.sync_card_table_ptr();
+    }
+
+    /// Get the generational header of the object at the given heap index.
+    #[inline]
+    fn header_at(&self, index: u32) -> &VMGenerationalHeader {
+        self.index(gc_ref_at(index).as_typed_unchecked())
+    }
+
+    /// Get the generational header of the object at the given heap index.
+    #[inline]
+    fn header_at_mut(&mut self, index: u32) -> &mut VMGenerationalHeader {
+        self.index_mut(gc_ref_at(index).as_typed_unchecked())
+    }
+
+    #[inline]
+    fn read_u32(&self, index: u32) -> u32 {
+        let index = usize::try_from(index).unwrap();
+        let bytes = &self.heap_slice()[index..][..mem::size_of::<u32>()];
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    #[inline]
+    fn write_u32(&mut self, index: u32, value: u32) {
+        let index = usize::try_from(index).unwrap();
+        let bytes = &mut self.heap_slice_mut()[index..][..mem::size_of::<u32>()];
+        bytes.copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Attempt to bump-allocate an object with the given layout and header.
+    fn alloc(&mut self, header: VMGcHeader, layout: Layout) -> Result<Result<VMGcRef, u64>> {
+        debug_assert!(layout.size() >= mem::size_of::<VMGenerationalHeader>());
+        debug_assert_eq!(header.reserved_u26(), 0);
+
+        if layout.align() > usize::try_from(HEADER_ALIGN).unwrap() {
+            return Err(crate::Trap::AllocationTooLarge.into());
+        }
+
+        let size = match u32::try_from(layout.size())
+            .ok()
+            .and_then(round_object_size)
+        {
+            Some(size) => size,
+            None => return Err(crate::Trap::AllocationTooLarge.into()),
+        };
+
+        if let Some(ty) = header.ty() {
+            self.ensure_trace_info(ty);
+        } else {
+            debug_assert_eq!(header.kind(), VMGcKind::ExternRef);
+        }
+
+        let next = self.next();
+        let end_of_object = match next.checked_add(size) {
+            Some(end) => end,
+            None => return Err(crate::Trap::AllocationTooLarge.into()),
+        };
+        if end_of_object > self.heap_len() {
+            return Ok(Err(u64::from(size)));
+        }
+
+        self.set_next(end_of_object);
+        *self.header_at_mut(next) = VMGenerationalHeader {
+            header,
+            object_size: size,
+            forwarding: 0,
+        };
+        Ok(Ok(gc_ref_at(next)))
+    }
+
+    /// Deallocate the given uninitialized object.
+    ///
+    /// We can only reclaim its space if it was the most recent allocation;
+    /// otherwise it is unreachable garbage that will be swept up by the next
+    /// collection that covers it. Either way, the collector never traces it,
+    /// so its uninitialized fields are never observed.
+    fn dealloc_uninit(&mut self, gc_ref: VMGcRef) {
+        let index = gc_ref.as_heap_index().unwrap().get();
+        let size = self.header_at(index).object_size;
+        if index.checked_add(size) == Some(self.next()) {
+            self.set_next(index);
+        }
+    }
+
+    /// Ensure that we have tracing information for the given type.
+    fn ensure_trace_info(&mut self, ty: VMSharedTypeIndex) {
+        if self.trace_infos.contains_key(&ty) {
+            return;
+        }
+
+        let engine = self.engine();
+        let gc_layout = engine
+            .signatures()
+            .layout(ty)
+            .unwrap_or_else(|| panic!("should have a GC layout for {ty:?}"));
+
+        let info = match gc_layout {
+            GcLayout::Array(l) => {
+                if l.elems_are_gc_refs {
+                    debug_assert_eq!(l.elem_offset(0), GC_REF_ARRAY_ELEMS_OFFSET);
+                }
+                TraceInfo::Array {
+                    gc_ref_elems: l.elems_are_gc_refs,
+                }
+            }
+            GcLayout::Struct(l) => TraceInfo::Struct {
+                gc_ref_offsets: l
+                    .fields
+                    .iter()
+                    .filter_map(|f| if f.is_gc_ref { Some(f.offset) } else { None })
+                    .collect(),
+            },
+            GcLayout::Exception(e) => TraceInfo::Struct {
+                gc_ref_offsets: e
+                    .fields
+                    .iter()
+                    .filter_map(|f| if f.is_gc_ref { Some(f.offset) } else { None })
+                    .collect(),
+            },
+        };
+
+        let old_entry = self.trace_infos.insert(ty, info);
+        debug_assert!(old_entry.is_none());
+    }
+
+    /// Push the heap indices of every GC reference field in the object at
+    /// `index` onto `fields`.
+    fn gc_ref_fields(&mut self, index: u32, fields: &mut Vec<u32>) {
+        let Some(ty) = self.header_at(index).header.ty() else {
+            debug_assert!(
+                self.header_at(index)
+                    .header
+                    .kind()
+                    .matches(VMGcKind::ExternRef)
+            );
+            return;
+        };
+        self.ensure_trace_info(ty);
+        match &self.trace_infos[&ty] {
+            TraceInfo::Struct { gc_ref_offsets } => {
+                fields.extend(gc_ref_offsets.iter().map(|offset| index + offset));
+            }
+            TraceInfo::Array { gc_ref_elems } => {
+                if !*gc_ref_elems {
+                    return;
+                }
+                let len = self
+                    .index::<VMGenerationalArrayHeader>(gc_ref_at(index).as_typed_unchecked())
+                    .length;
+                let elem_size = u32::try_from(mem::size_of::<u32>()).unwrap();
+                let elems = index + GC_REF_ARRAY_ELEMS_OFFSET;
+                fields.extend((0..len).map(|i| elems + i * elem_size));
+            }
+        }
+    }
+
+    /// Get the heap indices of every old-generation object that overlaps a
+    /// dirty card.
+    fn dirty_old_objects(&self) -> Vec<u32> {
+        let mut objects = vec![];
+        if self.old_end == HEAP_START {
+            return objects;
+        }
+
+        let mut scanned_end = HEAP_START;
+        for card in 0..=card_of(self.old_end - 1) {
+            if self.cards[card] == 0 || self.object_starts[card] == NO_OBJECT {
+                continue;
+            }
+            let card_end = u32::try_from(card + 1).unwrap().saturating_mul(CARD_SIZE);
+            let mut index = self.object_starts[card].max(scanned_end);
+            while index < self.old_end && index < card_end {
+                objects.push(index);
+                index += self.header_at(index).object_size;
+            }
+            scanned_end = index;
+        }
+
+        log::trace!("found {} dirty old-generation objects", objects.len());
+        objects
+    }
+
+    /// Collect garbage in `from..next`, treating everything before `from` as
+    /// live.
+    ///
+    /// When `from` is the end of the old generation, this is a minor
+    /// collection, and when it is `HEAP_START` it is a major collection.
+    fn collect(
+        &mut self,
+        roots: &mut [GcRoot<'_>],
+        host_data_table: &mut ExternRefHostDataTable,
+        from: u32,
+    ) {
+        let next = self.next();
+        debug_assert!(from <= next);
+        let in_range = |gc_ref: &VMGcRef| {
+            gc_ref
+                .as_heap_index()
+                .is_some_and(|i| from <= i.get() && i.get() < next)
+        };
+
+        let mut fields = mem::take(&mut self.scratch);
+        debug_assert!(fields.is_empty());
+
+        // When doing a minor collection, the old objects on dirty cards may
+        // contain references into the nursery and are additional roots.
+        let remembered = if from == HEAP_START {
+            vec![]
+        } else {
+            self.dirty_old_objects()
+        };
+        for index in &remembered {
+            self.gc_ref_fields(*index, &mut fields);
+        }
+
+        // Mark every object in the collected range that is reachable from the
+        // roots.
+        let mut stack: Vec<VMGcRef> = roots.iter().map(|r| r.get()).collect();
+        stack.extend(
+            fields
+                .drain(..)
+                .filter_map(|field| VMGcRef::from_raw_u32(self.read_u32(field))),
+        );
+        while let Some(gc_ref) = stack.pop() {
+            if !in_range(&gc_ref) {
+                continue;
+            }
+            let index = gc_ref.as_heap_index().unwrap().get();
+            let header = self.header_at_mut(index);
+            if header.is_marked() {
+                continue;
+            }
+            header.set_marked(true);
+            self.gc_ref_fields(index, &mut fields);
+            stack.extend(
+                fields
+                    .drain(..)
+                    .filter_map(|field| VMGcRef::from_raw_u32(self.read_u32(field))),
+            );
+        }
+
+        // Compute the new location of every marked object, and reclaim the
+        // host data of every unmarked `externref`.
+        let mut live = Vec::new();
+        let mut new_next = from;
+        let mut index = from;
+        while index < next {
+            let header = self.header_at_mut(index);
+            let size = header.object_size;
+            if header.is_marked() {
+                header.forwarding = new_next;
+                live.push(index);
+                new_next += size;
+            } else if header.header.kind().matches(VMGcKind::ExternRef) {
+                let externref = gc_ref_at(index);
+                let host_data_id = self
+                    .index::<VMGenerationalExternRef>(externref.as_typed_unchecked())
+                    .host_data;
+                host_data_table.dealloc(host_data_id);
+            }
+            index += size;
+        }
+        log::trace!(
+            "{} of {} bytes in {from:#x}..{next:#x} survived",
+            new_next - from,
+            next - from,
+        );
+
+        // Update every reference into the collected range to point to its
+        // referent's new location.
+        let forward = |heap: &Self, gc_ref: VMGcRef| -> Option<VMGcRef> {
+            if !in_range(&gc_ref) {
+                return None;
+            }
+            let index = gc_ref.as_heap_index().unwrap().get();
+            let header = heap.header_at(index);
+            debug_assert!(header.is_marked());
+            Some(gc_ref_at(header.forwarding))
+        };
+
+        // Note that the same root slot may appear multiple times in the roots
+        // list, so compute every root's new value before updating any of them.
+        let new_roots: Vec<_> = roots.iter().map(|r| forward(self, r.get())).collect();
+        for (root, new_ref) in roots.iter_mut().zip(new_roots) {
+            if let Some(new_ref) = new_ref {
+                root.set(new_ref);
+            }
+        }
+        for index in remembered.iter().chain(&live) {
+            self.gc_ref_fields(*index, &mut fields);
+        }
+        for field in fields.drain(..) {
+            let Some(gc_ref) = VMGcRef::from_raw_u32(self.read_u32(field)) else {
+                continue;
+            };
+            if let Some(new_ref) = forward(self, gc_ref) {
+                self.write_u32(field, new_ref.as_raw_u32());
+            }
+        }
+
+        // Slide every marked object down to its new location. Objects only
+        // ever move to lower addresses, and we process them in address order,
+        // so we never clobber an object that we have yet to move.
+        for index in live {
+            let header = self.header_at_mut(index);
+            header.set_marked(false);
+            let size = usize::try_from(header.object_size).unwrap();
+            let dest = usize::try_from(header.forwarding).unwrap();
+            let src = usize::try_from(index).unwrap();
+            if src != dest {
+                self.heap_slice_mut().copy_within(src..src + size, dest);
+            }
+        }
+
+        // Zero out the newly-freed space so that stale object data does not
+        // linger around in the heap.
+        let freed = usize::try_from(new_next).unwrap()..usize::try_from(next).unwrap();
+        self.heap_slice_mut()[freed].fill(0);
+
+        // Everything that survived is now in the old generation.
+        self.set_next(new_next);
+        self.old_end = new_next;
+        self.update_object_starts(from);
+
+        // There are no objects in the nursery anymore, so there cannot be any
+        // old-to-young edges, and all cards are clean.
+        self.cards.fill(0);
+        self.sync_card_table_ptr();
+
+        debug_assert!(fields.is_empty());
+        self.scratch = fields;
+    }
+
+    /// Update the object-start table for the part of the old generation that
+    /// begins at `from`.
+    fn update_object_starts(&mut self, from: u32) {
+        for start in &mut self.object_starts[card_of(from)..] {
+            if *start != NO_OBJECT && *start >= from {
+                *start = NO_OBJECT;
+            }
+        }
+
+        let mut index = from;
+        while index < self.old_end {
+            let size = self.header_at(index).object_size;
+            for card in card_of(index)..=card_of(index + size - 1) {
+                if self.object_starts[card] == NO_OBJECT {
+                    self.object_starts[card] = index;
+                }
+            }
+            index += size;
+        }
+    }
+
+    /// Collect garbage in the nursery, promoting its survivors to the old
+    /// generation.
+    fn minor_collection(
+        &mut self,
+        roots: &mut [GcRoot<'_>],
+        host_data_table: &mut ExternRefHostDataTable,
+    ) {
+        log::trace!("Begin generational minor collection");
+        self.collect(roots, host_data_table, self.old_end);
+        log::trace!("End generational minor collection");
+    }
+
+    /// Collect garbage in the whole heap.
+    fn major_collection(
+        &mut self,
+        roots: &mut [GcRoot<'_>],
+        host_data_table: &mut ExternRefHostDataTable,
+    ) {
+        log::trace!("Begin generational major collection");
+        self.collect(roots, host_data_table, HEAP_START);
+        let live = self.old_end - HEAP_START;
+        self.major_threshold = live.saturating_mul(2).max(MIN_MAJOR_THRESHOLD);
+        log::trace!("End generational major collection");
+    }
+
+    /// Should we follow up the minor collection we just did with a major
+    /// collection?
+    ///
+    /// If the nursery was already empty, then the minor collection could not
+    /// have reclaimed anything, and only a major collection can make progress.
+    fn should_do_major_collection(&self, nursery_was_empty: bool) -> bool {
+        let old_size = self.old_end - HEAP_START;
+        let free = self.heap_len().saturating_sub(self.old_end);
+        nursery_was_empty || old_size > self.major_threshold || free < self.heap_len() / 2
+    }
+}
+
+unsafe impl GcHeap for GenerationalHeap {
+    fn is_attached(&self) -> bool {
+        debug_assert_eq!(self.memory.is_some(), self.vmmemory.is_some());
+        self.memory.is_some()
+    }
+
+    fn attach(&mut self, memory: crate::vm::Memory) {
+        assert!(!self.is_attached());
+        assert!(!memory.is_shared_memory());
+        self.vmmemory = Some(memory.vmmemory());
+        self.memory = Some(memory);
+        self.set_next(HEAP_START);
+        self.old_end = HEAP_START;
+        self.major_threshold = MIN_MAJOR_THRESHOLD;
+        self.resize_card_tables();
+    }
+
+    fn detach(&mut self) -> crate::vm::Memory {
+        assert!(self.is_attached());
+
+        let GenerationalHeap {
+            engine: _,
+            no_gc_count,
+            heap_data: _,
+            cards,
+            object_starts,
+            old_end,
+            major_threshold,
+            scratch,
+            memory,
+            vmmemory,
+
+            // NB: we will only ever be reused with the same engine, so no need
+            // to clear out our tracing info just to fill it back in with the
+            // same exact stuff.
+            trace_infos: _,
+        } = self;
+
+        *no_gc_count = 0;
+        cards.clear();
+        object_starts.clear();
+        *old_end = HEAP_START;
+        *major_threshold = MIN_MAJOR_THRESHOLD;
+        debug_assert!(scratch.is_empty());
+        *vmmemory = None;
+        let memory = memory.take().unwrap();
+
+        self.set_next(u32::MAX);
+        self.sync_card_table_ptr();
+        memory
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self as _
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self as _
+    }
+
+    fn enter_no_gc_scope(&mut self) {
+        self.no_gc_count += 1;
+    }
+
+    fn exit_no_gc_scope(&mut self) {
+        self.no_gc_count -= 1;
+    }
+
+    fn clone_gc_ref(&mut self, gc_ref: &VMGcRef) -> VMGcRef {
+        gc_ref.unchecked_copy()
+    }
+
+    fn write_gc_ref(
+        &mut self,
+        _host_data_table: &mut ExternRefHostDataTable,
+        destination: &mut Option<VMGcRef>,
+        source: Option<&VMGcRef>,
+    ) {
+        // Writes into GC objects go through `gc_object_data_mut`, which marks
+        // the object's cards, so there is nothing else to do here.
+        *destination = source.map(|s| s.unchecked_copy());
+    }
+
+    fn expose_gc_ref_to_wasm(&mut self, _gc_ref: VMGcRef) {
+        // Don't need to do anything special here.
+    }
+
+    fn alloc_externref(
+        &mut self,
+        host_data: ExternRefHostDataId,
+    ) -> Result<Result<VMExternRef, u64>> {
+        let gc_ref = match self.alloc(
+            VMGcHeader::externref(),
+            Layout::new::<VMGenerationalExternRef>(),
+        )? {
+            Ok(r) => r,
+            Err(bytes_needed) => return Ok(Err(bytes_needed)),
+        };
+        self.index_mut::<VMGenerationalExternRef>(gc_ref.as_typed_unchecked())
+            .host_data = host_data;
+        Ok(Ok(gc_ref.into_externref_unchecked()))
+    }
+
+    fn externref_host_data(&self, externref: &VMExternRef) -> ExternRefHostDataId {
+        let gc_ref = externref.as_gc_ref();
+        debug_assert!(gc_ref.is_typed::<VMGenerationalExternRef>(self));
+        self.index::<VMGenerationalExternRef>(gc_ref.as_typed_unchecked())
+            .host_data
+    }
+
+    fn header(&self, gc_ref: &VMGcRef) -> &VMGcHeader {
+        self.index(gc_ref.as_typed_unchecked())
+    }
+
+    fn header_mut(&mut self, gc_ref: &VMGcRef) -> &mut VMGcHeader {
+        self.index_mut(gc_ref.as_typed_unchecked())
+    }
+
+    fn object_size(&self, gc_ref: &VMGcRef) -> usize {
+        let header = self.index::<VMGenerationalHeader>(gc_ref.as_typed_unchecked());
+        usize::try_from(header.object_size).unwrap()
+    }
+
+    fn alloc_raw(&mut self, header: VMGcHeader, layout: Layout) -> Result<Result<VMGcRef, u64>> {
+        self.alloc(header, layout)
+    }
+
+    fn alloc_uninit_struct(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        layout: &GcStructLayout,
+    ) -> Result<Result<VMStructRef, u64>> {
+        self.alloc(
+            VMGcHeader::from_kind_and_index(VMGcKind::StructRef, ty),
+            layout.layout(),
+        )
+        .map(|r| r.map(|r| r.into_structref_unchecked()))
+    }
+
+    fn dealloc_uninit_struct(&mut self, structref: VMStructRef) {
+        self.dealloc_uninit(structref.into());
+    }
+
+    fn alloc_uninit_array(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        length: u32,
+        layout: &GcArrayLayout,
+    ) -> Result<Result<VMArrayRef, u64>> {
+        self.alloc(
+            VMGcHeader::from_kind_and_index(VMGcKind::ArrayRef, ty),
+            layout.layout(length),
+        )
+        .map(|r| {
+            r.map(|r| {
+                self.index_mut::<VMGenerationalArrayHeader>(r.as_typed_unchecked())
+                    .length = length;
+                r.into_arrayref_unchecked()
+            })
+        })
+    }
+
+    fn dealloc_uninit_array(&mut self, arrayref: VMArrayRef) {
+        self.dealloc_uninit(arrayref.into());
+    }
+
+    fn array_len(&self, arrayref: &VMArrayRef) -> u32 {
+        debug_assert!(
+            arrayref
+                .as_gc_ref()
+                .is_typed::<VMGenerationalArrayHeader>(self)
+        );
+        self.index::<VMGenerationalArrayHeader>(arrayref.as_gc_ref().as_typed_unchecked())
+            .length
+    }
+
+    fn alloc_uninit_exn(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        layout: &GcExceptionLayout,
+    ) -> Result<Result<VMExnRef, u64>> {
+        self.alloc(
+            VMGcHeader::from_kind_and_index(VMGcKind::ExnRef, ty),
+            layout.layout(),
+        )
+        .map(|r| r.map(|r| r.into_exnref_unchecked()))
+    }
+
+    fn dealloc_uninit_exn(&mut self, exnref: VMExnRef) {
+        self.dealloc_uninit(exnref.into());
+    }
+
+    fn gc<'a>(
+        &'a mut self,
+        roots: GcRootsIter<'a>,
+        host_data_table: &'a mut ExternRefHostDataTable,
+    ) -> Box<dyn GarbageCollection<'a> + 'a> {
+        assert_eq!(self.no_gc_count, 0, "Cannot GC inside a no-GC scope!");
+        Box::new(GenerationalCollection {
+            roots: roots.collect(),
+            host_data_table,
+            heap: self,
+            phase: GenerationalCollectionPhase::Minor,
+        })
+    }
+
+    unsafe fn vmctx_gc_heap_data(&self) -> NonNull<u8> {
+        let ptr: *mut VMGenerationalHeapData = unsafe { self.heap_data.get() };
+        NonNull::new(ptr).unwrap().cast()
+    }
+
+    unsafe fn take_memory(&mut self) -> crate::vm::Memory {
+        debug_assert!(self.is_attached());
+        self.vmmemory.take();
+        self.memory.take().unwrap()
+    }
+
+    unsafe fn replace_memory(&mut self, memory: crate::vm::Memory, _delta_bytes_grown: u64) {
+        debug_assert!(self.memory.is_none());
+        debug_assert!(!memory.is_shared_memory());
+        self.vmmemory = Some(memory.vmmemory());
+        self.memory = Some(memory);
+        self.resize_card_tables();
+    }
+
+    #[inline]
+    fn vmmemory(&self) -> VMMemoryDefinition {
+        debug_assert!(self.is_attached());
+        debug_assert!(!self.memory.as_ref().unwrap().is_shared_memory());
+        let vmmemory = self.vmmemory.as_ref().unwrap();
+        VMMemoryDefinition {
+            base: vmmemory.base,
+            current_length: AtomicUsize::new(vmmemory.current_length()),
+        }
+    }
+
+    fn gc_object_data_mut(&mut self, gc_ref: &VMGcRef) -> &mut VMGcObjectData {
+        // This is the runtime's write barrier: conservatively assume that the
+        // caller will write GC references into the object.
+        let range = self.object_range(gc_ref);
+        self.mark_cards(&range);
+        let data = &mut self.heap_slice_mut()[range];
+        data.into()
+    }
+
+    fn gc_object_data_pair(
+        &mut self,
+        a: &VMGcRef,
+        b: &VMGcRef,
+    ) -> (&mut VMGcObjectData, &mut VMGcObjectData) {
+        assert_ne!(a, b);
+
+        let a_range = self.object_range(a);
+        let b_range = self.object_range(b);
+
+        // Assert that the two objects do not overlap.
+        assert!(a_range.start <= a_range.end);
+        assert!(b_range.start <= b_range.end);
+        assert!(a_range.end <= b_range.start || b_range.end <= a_range.start);
+
+        // See `gc_object_data_mut` above.
+        self.mark_cards(&a_range);
+        self.mark_cards(&b_range);
+
+        let (a_data, b_data) = if a_range.start < b_range.start {
+            let (a_half, b_half) = self.heap_slice_mut().split_at_mut(b_range.start);
+            let b_len = b_range.end - b_range.start;
+            (&mut a_half[a_range], &mut b_half[..b_len])
+        } else {
+            let (b_half, a_half) = self.heap_slice_mut().split_at_mut(a_range.start);
+            let a_len = a_range.end - a_range.start;
+            (&mut a_half[..a_len], &mut b_half[b_range])
+        };
+
+        (a_data.into(), b_data.into())
+    }
+}
+
+struct GenerationalCollection<'a> {
+    roots: Vec<GcRoot<'a>>,
+    host_data_table: &'a mut ExternRefHostDataTable,
+    heap: &'a mut GenerationalHeap,
+    phase: GenerationalCollectionPhase,
+}
+
+enum GenerationalCollectionPhase {
+    Minor,
+    Major,
+    Done,
+}
+
+impl<'a> GarbageCollection<'a> for GenerationalCollection<'a> {
+    fn collect_increment(&mut self) -> GcProgress {
+        match self.phase {
+            GenerationalCollectionPhase::Minor => {
+                let nursery_was_empty = self.heap.next() == self.heap.old_end;
+                self.heap
+                    .minor_collection(&mut self.roots, self.host_data_table);
+                if self.heap.should_do_major_collection(nursery_was_empty) {
+                    self.phase = GenerationalCollectionPhase::Major;
+                    GcProgress::Continue
+                } else {
+                    self.phase = GenerationalCollectionPhase::Done;
+                    GcProgress::Complete
+                }
+            }
+            GenerationalCollectionPhase::Major => {
+                self.heap
+                    .major_collection(&mut self.roots, self.host_data_table);
+                self.phase = GenerationalCollectionPhase::Done;
+                GcProgress::Complete
+            }
+            GenerationalCollectionPhase::Done => GcProgress::Complete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vm_generational_header_size() {
+        assert_eq!(
+            (wasmtime_environ::generational::HEADER_SIZE as usize),
+            core::mem::size_of::<VMGenerationalHeader>()
+        );
+    }
+
+    #[test]
+    fn vm_generational_header_object_size_offset() {
+        assert_eq!(
+            wasmtime_environ::generational::HEADER_OBJECT_SIZE_OFFSET,
+            u32::try_from(core::mem::offset_of!(VMGenerationalHeader, object_size)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn vm_generational_array_header_length_offset() {
+        assert_eq!(
+            wasmtime_environ::generational::ARRAY_LENGTH_OFFSET,
+            u32::try_from(core::mem::offset_of!(VMGenerationalArrayHeader, length)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn vm_generational_heap_data_offsets() {
+        assert_eq!(
+            wasmtime_environ::generational::HEAP_DATA_CARD_TABLE_OFFSET,
+            u32::try_from(core::mem::offset_of!(VMGenerationalHeapData, card_table)).unwrap(),
+        );
+        assert_eq!(
+            wasmtime_environ::generational::heap_data_next_offset(&wasmtime_environ::HostPtr),
+            u32::try_from(core::mem::offset_of!(VMGenerationalHeapData, next)).unwrap(),
+        );
+    }
+}
@@ -29,10 +29,10 @@ use core::{mem, ptr};
 #[cfg(feature = "gc")]
 use wasmtime_environ::ModuleInternedTypeIndex;
 use wasmtime_environ::{
-    DataIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex,
-    ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex, HostPtr, MemoryIndex,
-    Module, PrimaryMap, PtrSize, TableIndex, TableInitialValue, TableSegmentElements, TagIndex,
-    Trap, VMCONTEXT_MAGIC, VMOffsets, VMSharedTypeIndex, WasmHeapTopType,
+    DataIndex, DefinedFuncIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex,
+    DefinedTagIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex, HostPtr,
+    MemoryIndex, Module, PrimaryMap, PtrSize, TableIndex, TableInitialValue, TableSegmentElements,
+    TagIndex, Trap, VMCONTEXT_MAGIC, VMOffsets, VMSharedTypeIndex, WasmHeapTopType,
     packed_option::ReservedValue,
 };
 #[cfg(feature = "wmemcheck")]
@@ -253,6 +253,10 @@ pub struct Instance {
     #[cfg(feature = "wmemcheck")]
     pub(crate) wmemcheck_state: Option<Wmemcheck>,
 
+    /// The recompilation of this instance's module for tiered compilation,
+    /// along with the defined functions which have been switched over to it.
+    tiered_up: Option<(ModuleRuntimeInfo, EntitySet<DefinedFuncIndex>)>,
+
     /// Self-pointer back to `Store<T>` and its functions. Not present for
     /// the brief time that `Store<T>` is itself being created. Also not
     /// present for some niche uses that are disconnected from stores (e.g.
@@ -305,6 +309,7 @@ impl Instance {
                     None
                 }
             },
+            tiered_up: None,
             store: None,
             vmctx: OwnedVMContext::new(),
         });
@@ -379,7 +384,12 @@ impl Instance {
         self.runtime_info.env_module()
     }
 
-    #[cfg(feature = "gc")]
+    #[cfg(any(
+        feature = "gc",
+        feature = "checkpoint",
+        feature = "cranelift",
+        feature = "winch"
+    ))]
     pub(crate) fn runtime_module(&self) -> Option<&crate::Module> {
         match &self.runtime_info {
             ModuleRuntimeInfo::Module(m) => Some(m),
@@ -823,13 +833,17 @@ impl Instance {
         into: *mut VMFuncRef,
     ) {
         let func_ref = if let Some(def_index) = self.env_module().defined_func_index(index) {
+            // Functions which were tiered up keep running the optimized code.
+            let code = match &self.tiered_up {
+                Some((optimized, funcs)) if funcs.contains(def_index) => optimized,
+                _ => &self.runtime_info,
+            };
             VMFuncRef {
-                array_call: self
-                    .runtime_info
+                array_call: code
                     .array_to_wasm_trampoline(def_index)
                     .expect("should have array-to-Wasm trampoline for escaping function")
                     .into(),
-                wasm_call: Some(self.runtime_info.function(def_index).into()),
+                wasm_call: Some(code.function(def_index).into()),
                 vmctx: VMOpaqueContext::from_vmcontext(self.vmctx()).into(),
                 type_index,
             }
@@ -906,6 +920,34 @@ impl Instance {
         Some(func_ref)
     }
 
+    /// Switches the defined function `index` over to its code in `optimized`,
+    /// a recompilation of this instance's module for tiered compilation.
+    ///
+    /// This points the function's `VMFuncRef`, through which code compiled for
+    /// tiered compilation calls it, at the optimized code, and keeps it there
+    /// whenever the `VMFuncRef` is reinitialized.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn tier_up_func_ref(
+        mut self: Pin<&mut Self>,
+        index: DefinedFuncIndex,
+        optimized: &crate::Module,
+    ) {
+        let func_index = self.env_module().func_index(index);
+        debug_assert!(self.env_module().functions[func_index].is_escaping());
+
+        // Note that `optimized` was compiled from the same wasm binary as this
+        // instance's module with the same calling convention and `VMContext`
+        // layout, so its code can run with this instance.
+        let (_, funcs) = self.as_mut().tiered_up_mut().get_or_insert_with(|| {
+            (
+                ModuleRuntimeInfo::Module(optimized.clone()),
+                EntitySet::new(),
+            )
+        });
+        funcs.insert(index);
+        self.get_func_ref(func_index);
+    }
+
     /// Get the passive elements segment at the given index.
     ///
     /// Returns an empty segment if the index is out of bounds or if the segment
@@ -1100,6 +1142,18 @@ impl Instance {
         // dropping a non-passive segment is a no-op (not a trap).
     }
 
+    /// Returns whether the given element segment has been dropped.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn is_elem_dropped(&self, elem_index: ElemIndex) -> bool {
+        self.dropped_elements.contains(elem_index)
+    }
+
+    /// Returns whether the given data segment has been dropped.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn is_data_dropped(&self, data_index: DataIndex) -> bool {
+        self.dropped_data.contains(data_index)
+    }
+
     /// Get a locally-defined memory.
     pub fn get_defined_memory_mut(self: Pin<&mut Self>, index: DefinedMemoryIndex) -> &mut Memory {
         &mut self.memories_mut()[index].1
@@ -1455,6 +1509,15 @@ impl Instance {
         // eagerly construct each element in it whenever asked for a reference
         // to that element. In other words, there is no state needed to track
         // the lazy-init, so we don't need to initialize any state now.
+        //
+        // The exception is code compiled for tiered compilation, which calls
+        // defined functions through their funcrefs without going through the
+        // runtime.
+        if self.runtime_info.tiered_compilation() {
+            for def_index in module.defined_func_indices() {
+                self.as_mut().get_func_ref(module.func_index(def_index));
+            }
+        }
 
         // Initialize the defined tables
         //
@@ -1643,6 +1706,14 @@ impl Instance {
         unsafe { &mut self.get_unchecked_mut().dropped_data }
     }
 
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    fn tiered_up_mut(
+        self: Pin<&mut Self>,
+    ) -> &mut Option<(ModuleRuntimeInfo, EntitySet<DefinedFuncIndex>)> {
+        // SAFETY: see `store_mut` above.
+        unsafe { &mut self.get_unchecked_mut().tiered_up }
+    }
+
     fn memories_mut(
         self: Pin<&mut Self>,
     ) -> &mut PrimaryMap<DefinedMemoryIndex, (MemoryAllocationIndex, Memory)> {
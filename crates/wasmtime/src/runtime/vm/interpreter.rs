@@ -472,6 +472,7 @@ fn trap(vm: &mut Vm, pc: NonNull<u8>, kind: Option<TrapKind>, setjmp: Setjmp) {
                     TrapKind::MemoryOutOfBounds => Trap::MemoryOutOfBounds,
                     TrapKind::DisabledOpcode => Trap::DisabledOpcode,
                     TrapKind::StackOverflow => Trap::StackOverflow,
+                    TrapKind::HeapMisaligned => Trap::HeapMisaligned,
                 };
                 s.set_jit_trap(regs, None, trap);
             }
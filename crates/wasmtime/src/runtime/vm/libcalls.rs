@@ -56,6 +56,7 @@
 
 #[cfg(feature = "stack-switching")]
 use super::stack_switching::VMContObj;
+use crate::ValRaw;
 use crate::prelude::*;
 use crate::runtime::store::StoreInstanceId;
 #[cfg(feature = "gc")]
@@ -558,7 +559,7 @@ unsafe fn grow_gc_heap(
 /// Allocate a raw, unininitialized GC object for Wasm code.
 ///
 /// The Wasm code is responsible for initializing the object.
-#[cfg(feature = "gc-drc")]
+#[cfg(any(feature = "gc-drc", feature = "gc-generational"))]
 unsafe fn gc_alloc_raw(
     store: &mut dyn VMStore,
     instance: Pin<&mut Instance>,
@@ -1074,6 +1075,138 @@ unsafe fn is_subtype(
     is_subtype as u32
 }
 
+/// Allocate a new exception object for the given tag, for Wasm's `throw`.
+///
+/// The tag of the exception object is initialized, but its payload fields are
+/// left for the Wasm code to initialize.
+#[cfg(feature = "gc")]
+unsafe fn alloc_exn(
+    store: &mut dyn VMStore,
+    instance: Pin<&mut Instance>,
+    tag: u32,
+) -> Result<core::num::NonZeroU32> {
+    use crate::{ExnRefPre, ExnType, store::AutoAssertNoGc};
+    use wasmtime_environ::TagIndex;
+
+    let store = store.store_opaque_mut();
+    let tag = instance.get_exported_tag(store.id(), TagIndex::from_u32(tag));
+    let ty = ExnType::from_tag_type(&tag._ty(store))?;
+    let allocator = ExnRefPre::_new(store, ty);
+    log::trace!("alloc_exn(tag={tag:?})");
+
+    let exnref = unsafe {
+        store.retry_after_gc_maybe_async((), |store, ()| {
+            store
+                .unwrap_gc_store_mut()
+                .alloc_uninit_exn(allocator.type_index(), allocator.layout())?
+                .map_err(|bytes_needed| crate::GcHeapOutOfMemory::new((), bytes_needed).into())
+        })?
+    };
+
+    let mut store = AutoAssertNoGc::new(store);
+    let (instance, index) = tag.to_raw_indices();
+    exnref.initialize_tag(&mut store, allocator.layout(), instance, index)?;
+    Ok(store
+        .unwrap_gc_store_mut()
+        .expose_gc_ref_to_wasm(exnref.into()))
+}
+
+/// Returns whether the given non-null exception object was thrown for the
+/// given tag, for the `catch` clauses of Wasm's `try_table`.
+#[cfg(feature = "gc")]
+unsafe fn exn_tag_matches(
+    store: &mut dyn VMStore,
+    instance: Pin<&mut Instance>,
+    exnref: u32,
+    tag: u32,
+) -> u32 {
+    use crate::store::AutoAssertNoGc;
+    use wasmtime_environ::TagIndex;
+
+    let mut store = AutoAssertNoGc::new(store.store_opaque_mut());
+    let tag = instance.get_exported_tag(store.id(), TagIndex::from_u32(tag));
+    let exnref = VMGcRef::from_raw_u32(exnref)
+        .expect("caught exception objects are never null")
+        .into_exnref_unchecked();
+    let ty = store
+        .unwrap_gc_store()
+        .header(exnref.as_gc_ref())
+        .ty()
+        .expect("exception objects have concrete types");
+    let layout = store
+        .engine()
+        .signatures()
+        .layout(ty)
+        .expect("exception types have GC layouts");
+    let thrown = exnref
+        .tag(&mut store, layout.unwrap_exception())
+        .expect("the GC heap is allocated");
+
+    let matches = thrown == tag.to_raw_indices();
+    log::trace!("exn_tag_matches(tag={tag:?}) -> {matches}");
+    matches as u32
+}
+
+/// Implementation of Wasm's `throw_ref`.
+///
+/// Looks for a handler for the given exception object in the Wasm frames of
+/// the current activation, in which case execution resumes at it. Exceptions
+/// without handlers trap, as they aren't propagated to the host.
+#[cfg(feature = "gc")]
+unsafe fn throw_ref(
+    store: &mut dyn VMStore,
+    _instance: Pin<&mut Instance>,
+    exnref: u32,
+) -> crate::runtime::vm::UnwindReason {
+    use crate::runtime::vm::{ExceptionHandler, UnwindReason};
+    use wasmtime_unwinder::{ExceptionTable, ThrowAction};
+
+    if exnref == 0 {
+        return UnwindReason::Trap(Trap::NullReference.into());
+    }
+
+    let store = store.store_opaque_mut();
+    let vm_store_context = store.vm_store_context();
+    let lookup = |pc: usize| {
+        let code = store
+            .modules()
+            .lookup_module_by_pc(pc)?
+            .code_object()
+            .code_memory();
+        let table = ExceptionTable::parse(code.exception_data()).ok()?;
+        Some((code.text().as_ptr() as usize, table))
+    };
+
+    // SAFETY: the libcall was entered through the Wasm-to-libcall trampoline,
+    // which saved the last Wasm PC and FP, and the entry FP delimits the Wasm
+    // frames of the current activation.
+    //
+    // Every handler is registered as a catch-all handler, with the tag
+    // matching left to the handler itself, so the tag passed here is
+    // irrelevant.
+    let action = unsafe {
+        wasmtime_unwinder::compute_throw_action(
+            store.unwinder(),
+            lookup,
+            *vm_store_context.last_wasm_exit_pc.get(),
+            *vm_store_context.last_wasm_exit_fp.get(),
+            *vm_store_context.last_wasm_entry_fp.get(),
+            0,
+        )
+    };
+    log::trace!("throw_ref(exnref={exnref:#x}) -> {action:?}");
+
+    match action {
+        ThrowAction::Handler { pc, sp, fp } => UnwindReason::Exception(ExceptionHandler {
+            pc,
+            sp,
+            fp,
+            payload: exnref,
+        }),
+        ThrowAction::None => UnwindReason::Trap(Trap::UncaughtException.into()),
+    }
+}
+
 // Implementation of `memory.atomic.notify` for locally defined memories.
 #[cfg(feature = "threads")]
 fn memory_atomic_notify(
@@ -1136,6 +1269,53 @@ fn new_epoch(store: &mut dyn VMStore, _instance: Pin<&mut Instance>) -> Result<N
 
 struct NextEpoch(u64);
 
+// Hook for guest debugging instrumentation, invoked before an instruction
+// while breakpoints or single-stepping are active.
+unsafe fn debug_hook(
+    store: &mut dyn VMStore,
+    instance: Pin<&mut Instance>,
+    func_index: u32,
+    offset: u32,
+    values: *mut u8,
+    tags: *mut u8,
+    len: u32,
+    num_locals: u32,
+) -> Result<()> {
+    let len = usize::try_from(len).unwrap();
+    // SAFETY: compiled code spills `len` values and `len` tag bytes into
+    // buffers that live for the duration of this call.
+    let (values, kinds) = unsafe {
+        (
+            core::slice::from_raw_parts(values.cast::<ValRaw>(), len),
+            core::slice::from_raw_parts(tags, len),
+        )
+    };
+    store.debug_hook(
+        instance.id(),
+        FuncIndex::from_u32(func_index),
+        offset,
+        values,
+        kinds,
+        usize::try_from(num_locals).unwrap(),
+    )
+}
+
+// Hook for tiered compilation, invoked periodically from baseline code to
+// sample which of its functions are hot.
+fn tier_up(store: &mut dyn VMStore, instance: Pin<&mut Instance>, func_index: u32) -> Result<()> {
+    // Only engines with a compiler can produce code instrumented for tiered
+    // compilation, and otherwise this libcall is never invoked.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    crate::module::tier_up(
+        store.store_opaque_mut(),
+        instance,
+        wasmtime_environ::DefinedFuncIndex::from_u32(func_index),
+    );
+    #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+    let _ = (store, instance, func_index);
+    Ok(())
+}
+
 unsafe impl HostResultHasUnwindSentinel for NextEpoch {
     type Abi = u64;
     const SENTINEL: u64 = u64::MAX;
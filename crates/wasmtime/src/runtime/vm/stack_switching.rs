@@ -330,6 +330,7 @@ pub fn cont_new(
         contref_args_ptr,
         param_count,
         result_count,
+        store.engine().is_pulley(),
     );
 
     // Now that the initial stack pointer was set by the initialization
@@ -8,7 +8,11 @@ use crate::runtime::vm::stack_switching::VMHostArray;
 use crate::runtime::vm::{VMContext, VMFuncRef, ValRaw};
 
 cfg_if::cfg_if! {
-    if #[cfg(all(feature = "stack-switching", unix, target_arch = "x86_64"))] {
+    if #[cfg(all(
+        feature = "stack-switching",
+        unix,
+        any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "riscv64"),
+    ))] {
         mod unix;
         use unix as imp;
     } else {
@@ -98,6 +102,10 @@ impl VMContinuationStack {
     ///
     /// It will be updated by this function to correctly describe
     /// the buffer used by this function for its arguments and return values.
+    ///
+    /// If `pulley` is true, the stack is prepared to be entered from Pulley
+    /// bytecode executing a `stack_switch` instruction rather than from
+    /// native code.
     pub fn initialize(
         &self,
         func_ref: *const VMFuncRef,
@@ -105,6 +113,7 @@ impl VMContinuationStack {
         args: *mut VMHostArray<ValRaw>,
         parameter_count: u32,
         return_value_count: u32,
+        pulley: bool,
     ) {
         self.0.initialize(
             func_ref,
@@ -112,6 +121,7 @@ impl VMContinuationStack {
             args,
             parameter_count,
             return_value_count,
+            pulley,
         )
     }
 }
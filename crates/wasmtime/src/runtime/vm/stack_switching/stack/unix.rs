@@ -3,11 +3,11 @@
 //!
 //! ```text
 //! 0xB000 +-----------------------+   <- top of stack (TOS)
-//!        | saved RIP             |
+//!        | saved PC              |
 //! 0xAff8 +-----------------------+
-//!        | saved RBP             |
+//!        | saved FP              |
 //! 0xAff0 +-----------------------+
-//!        | saved RSP             |
+//!        | saved SP              |
 //! 0xAfe8 +-----------------------+   <- beginning of "control context",
 //!        | args_capacity         |
 //! 0xAfe0 +-----------------------+
@@ -30,17 +30,19 @@
 //!
 //! 1.
 //! If the continuation is currently active (i.e., running directly, or ancestor
-//! of the running continuation), it stores the PC, RSP, and RBP of the *parent*
+//! of the running continuation), it stores the PC, SP, and FP of the *parent*
 //! of the running continuation.
 //!
 //! 2.
-//! If the picture shows a suspended computation, the fields store the PC, RSP,
-//! and RBP at the time of the suspension.
+//! If the picture shows a suspended computation, the fields store the PC, SP,
+//! and FP at the time of the suspension.
 //!
 //! Note that this design ensures that external tools can construct backtraces
 //! in the presence of stack switching by using frame pointers only: The
-//! wasmtime_continuation_start trampoline uses the address of the RBP field in the
-//! control context (0xAff0 above) as its frame pointer. This means that when
+//! wasmtime_continuation_start trampoline uses the address of the FP field in the
+//! control context (0xAff0 above) as its frame pointer. Since the saved PC is
+//! stored right above it, the FP and PC fields look like a regular frame record
+//! on all supported architectures. This means that when
 //! passing the wasmtime_continuation_start frame while doing frame pointer walking,
 //! the parent of that frame is the last frame in the parent of this
 //! continuation.
@@ -159,7 +161,7 @@ impl VMContinuationStack {
 
     pub fn control_context_instruction_pointer(&self) -> usize {
         // See picture at top of this file:
-        // RIP is stored 8 bytes below top of stack.
+        // PC is stored 8 bytes below top of stack.
         unsafe {
             let ptr = self.top.sub(8).cast::<usize>();
             *ptr
@@ -168,7 +170,7 @@ impl VMContinuationStack {
 
     pub fn control_context_frame_pointer(&self) -> usize {
         // See picture at top of this file:
-        // RBP is stored 16 bytes below top of stack.
+        // FP is stored 16 bytes below top of stack.
         unsafe {
             let ptr = self.top.sub(16).cast::<usize>();
             *ptr
@@ -177,7 +179,7 @@ impl VMContinuationStack {
 
     pub fn control_context_stack_pointer(&self) -> usize {
         // See picture at top of this file:
-        // RSP is stored 24 bytes below top of stack.
+        // SP is stored 24 bytes below top of stack.
         unsafe {
             let ptr = self.top.sub(24).cast::<usize>();
             *ptr
@@ -193,6 +195,11 @@ impl VMContinuationStack {
     /// calls `fiber_start` with  the following arguments:
     /// TOS, func_ref, caller_vmctx, args_ptr, args_capacity
     ///
+    /// If `pulley` is true, the future PC is instead the address of a Pulley
+    /// bytecode version of `wasmtime_continuation_start`, which expects the
+    /// same stack layout but invokes the function directly in the
+    /// interpreter (see the `pulley` module).
+    ///
     /// Note that at this point we also allocate the args buffer
     /// (see picture at the top of this file).
     /// We define `args_capacity` as the max of parameter and return value count.
@@ -209,8 +216,8 @@ impl VMContinuationStack {
     ///       TOS       | Contents
     ///  ---------------|-------------------------------------------------------
     ///       -0x08     | address of wasmtime_continuation_start function (future PC)
-    ///       -0x10     | TOS - 0x10 (future RBP)
-    ///       -0x18     | TOS - 0x40 - s (future RSP)
+    ///       -0x10     | TOS - 0x10 (future FP)
+    ///       -0x18     | TOS - 0x40 - s (future SP)
     ///       -0x20     | args_capacity
     ///
     ///
@@ -230,8 +237,14 @@ impl VMContinuationStack {
         args: *mut VMHostArray<ValRaw>,
         parameter_count: u32,
         return_value_count: u32,
+        pulley: bool,
     ) {
         let tos = self.top;
+        let start = if pulley {
+            pulley::wasmtime_continuation_start(func_ref).addr()
+        } else {
+            wasmtime_continuation_start as *const () as usize
+        };
 
         unsafe {
             let store = |tos_neg_offset, value| {
@@ -258,7 +271,7 @@ impl VMContinuationStack {
 
             let to_store = [
                 // Data near top of stack:
-                (0x08, wasmtime_continuation_start as usize),
+                (0x08, start),
                 (0x10, tos.sub(0x10).addr()),
                 (0x18, tos.sub(0x40 + args_data_size).addr()),
                 (0x20, usize::try_from(args_capacity).unwrap()),
@@ -298,7 +311,8 @@ unsafe extern "C" {
 }
 
 /// This function is responsible for actually running a wasm function inside a
-/// continuation. It is only ever called from `wasmtime_continuation_start`.
+/// continuation. It is only ever called from the native
+/// `wasmtime_continuation_start`.
 unsafe extern "C" fn fiber_start(
     func_ref: *mut VMFuncRef,
     caller_vmctx: *mut VMContext,
@@ -341,13 +355,19 @@ unsafe extern "C" fn fiber_start(
     }
 }
 
+mod pulley;
+
 cfg_if::cfg_if! {
     if #[cfg(target_arch = "x86_64")] {
         mod x86_64;
+    } else if #[cfg(target_arch = "aarch64")] {
+        mod aarch64;
+    } else if #[cfg(target_arch = "riscv64")] {
+        mod riscv64;
     } else {
         // Note that this should be unreachable: In stack.rs, we currently select
         // the module defined in the current file only if we are on unix AND
-        // x86_64.
+        // one of the architectures above.
         compile_error!("the stack switching feature is not supported on this CPU architecture");
     }
 }
@@ -0,0 +1,71 @@
+// A WORD OF CAUTION
+//
+// This entire file basically needs to be kept in sync with itself. It's not
+// really possible to modify just one bit of this file without understanding
+// all the other bits. Documentation tries to reference various bits here and
+// there but try to make sure to read over everything before tweaking things!
+
+use wasmtime_asm_macros::asm_func;
+
+// This is a pretty special function that has no real signature. Its use is to
+// be the "base" function of all fibers. This entrypoint is used in
+// `wasmtime_continuation_init` to bootstrap the execution of a new fiber.
+//
+// See the x86_64 version of this file for more commentary. The only notable
+// difference is that the `stack_switch` instruction enters this function with
+// an indirect branch, so it has to start with a `bti j` landing pad.
+//
+// Executing `stack_switch` on a stack prepared by `FiberStack::initialize` as
+// described in the comment on `FiberStack::initialize` leads to the following
+// values in various registers when execution of wasmtime_continuation_start begins:
+//
+// SP:  TOS - 0x40 - (16 * `args_capacity`)
+// X29: TOS - 0x10
+asm_func!(
+    "wasmtime_continuation_start",
+    "
+        hint #36 // bti j
+
+        //
+        // Note that the next 2 instructions amount to calling fiber_start
+        // with the following arguments:
+        // 1. func_ref
+        // 2. caller_vmctx
+        // 3. args (of type *mut ArrayRef<ValRaw>)
+        // 4. return_value_count
+        //
+        // SP stays 16-byte aligned after each of these pops.
+
+        ldp x3, x2, [sp], #16 // return_value_count, args
+        ldp x1, x0, [sp], #16 // caller_vmctx, func_ref
+        // Note that X29 already contains the right frame pointer to build a
+        // frame pointer chain including the parent continuation:
+        // The current value of X29 is where we store the parent X29 in the
+        // control context, directly followed by the parent PC!
+        bl {fiber_start}
+
+        // Return to the parent continuation.
+        // X29 is callee-saved, so its value is still TOS - 0x10.
+        // Use that fact to obtain saved parent FP, SP, and PC from control
+        // context near TOS.
+        ldr x1, [x29, #8] // putting new PC in temp register
+        ldr x2, [x29, #-8]
+        mov sp, x2
+        ldr x29, [x29]
+
+        // The stack_switch instruction uses register X0 for the payload.
+        // Here, the payload indicates that we are returning (value 0).
+        // See the test case below to keep this in sync with
+        // ControlEffect::return_()
+        mov x0, #0
+
+        br x1
+    ",
+    fiber_start = sym super::fiber_start,
+);
+
+#[test]
+fn test_return_payload() {
+    // The following assumption is baked into `wasmtime_continuation_start`.
+    assert_eq!(wasmtime_environ::CONTROL_EFFECT_RETURN_DISCRIMINANT, 0);
+}
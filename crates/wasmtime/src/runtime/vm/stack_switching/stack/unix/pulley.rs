@@ -0,0 +1,117 @@
+//! Pulley version of `wasmtime_continuation_start`.
+//!
+//! When Wasm is executed by the Pulley interpreter, the `stack_switch`
+//! instruction switches the interpreter's own `sp`, `fp`, and `pc` registers.
+//! The PC stored in the control context of a fresh continuation must thus be
+//! the address of Pulley bytecode rather than native code. This module
+//! assembles that bytecode once at runtime.
+//!
+//! The bytecode expects exactly the same stack layout and register values as
+//! its native counterpart (see `VMContinuationStack::initialize`):
+//!
+//! sp: TOS - 0x40 - (16 * `args_capacity`)
+//! fp: TOS - 0x10
+//!
+//! Instead of calling `fiber_start`, it calls the `array_call` function of the
+//! `func_ref` directly from within the interpreter, such that the Wasm
+//! function runs on the continuation stack. Afterwards it updates the length
+//! of the `args` buffer and switches back to the parent, with a payload of 0
+//! in `x0` (the payload register of Pulley's `stack_switch`).
+
+use crate::prelude::*;
+use crate::runtime::vm::VMFuncRef;
+use pulley_interpreter::encode as enc;
+use pulley_interpreter::regs::{AddrO32, XReg};
+use std::sync::OnceLock;
+use wasmtime_environ::{HostCall, HostPtr, PtrSize, VM_ARRAY_CALL_HOST_FUNC_MAGIC};
+
+/// Returns the address of the Pulley bytecode to use as the initial PC of a
+/// continuation running `func_ref`.
+///
+/// Functions defined by the host have a native `array_call` function pointer
+/// which must be invoked through `call_indirect_host`, so they use a different
+/// trampoline than Wasm functions, whose `array_call` is Pulley bytecode.
+pub fn wasmtime_continuation_start(func_ref: *const VMFuncRef) -> *const u8 {
+    static WASM: OnceLock<Box<[u8]>> = OnceLock::new();
+    static HOST: OnceLock<Box<[u8]>> = OnceLock::new();
+
+    let is_host =
+        unsafe { (*func_ref).vmctx.as_non_null().as_ref().magic == VM_ARRAY_CALL_HOST_FUNC_MAGIC };
+    let code = if is_host {
+        HOST.get_or_init(|| assemble(true))
+    } else {
+        WASM.get_or_init(|| assemble(false))
+    };
+    code.as_ptr()
+}
+
+fn addr(base: XReg, offset: u8) -> AddrO32 {
+    AddrO32 {
+        addr: base,
+        offset: offset.into(),
+    }
+}
+
+fn assemble(host: bool) -> Box<[u8]> {
+    use XReg::*;
+
+    let ptr = HostPtr;
+    let mut code = Vec::new();
+
+    // Stack layout, starting at `sp`:
+    // 0x00: return_value_count
+    // 0x08: args (of type *mut VMHostArray<ValRaw>)
+    // 0x10: caller_vmctx
+    // 0x18: func_ref
+    enc::xload64le_o32(&mut code, x5, addr(sp, 0x18));
+    enc::xload64le_o32(&mut code, x6, addr(sp, 0x08));
+    enc::xload64le_o32(&mut code, x7, addr(x5, ptr.vm_func_ref_array_call()));
+
+    // Arguments of the array call: callee vmctx, caller vmctx, pointer to
+    // the args buffer, and its capacity.
+    let [callee, caller, data, capacity] = if host {
+        [x1, x2, x3, x4]
+    } else {
+        [x0, x1, x2, x3]
+    };
+    enc::xload64le_o32(&mut code, callee, addr(x5, ptr.vm_func_ref_vmctx()));
+    enc::xload64le_o32(&mut code, caller, addr(sp, 0x10));
+    enc::xload64le_o32(&mut code, data, addr(x6, ptr.vmhostarray_data()));
+    enc::xload32le_o32(&mut code, capacity, addr(x6, ptr.vmhostarray_capacity()));
+    enc::zext32(&mut code, capacity, capacity);
+
+    if host {
+        // The host expects a non-null pointer even for an empty args buffer,
+        // any address will do for that case.
+        enc::xmov(&mut code, x8, sp);
+        enc::xselect64(&mut code, data, data, data, x8);
+        enc::xmov(&mut code, x0, x7);
+        enc::call_indirect_host(
+            &mut code,
+            u8::try_from(HostCall::ArrayCall.index()).unwrap(),
+        );
+    } else {
+        enc::call_indirect(&mut code, x7);
+    }
+
+    // TODO(dhil): like `fiber_start`, we are ignoring the boolean return
+    // value here.
+
+    // The array call has written `return_value_count` values to the `args`
+    // buffer; reflect that in its length field.
+    enc::xload64le_o32(&mut code, x6, addr(sp, 0x08));
+    enc::xload64le_o32(&mut code, x7, addr(sp, 0x00));
+    enc::xstore32le_o32(&mut code, addr(x6, ptr.vmhostarray_length()), x7);
+
+    // Return to the parent continuation. `fp` still points to TOS - 0x10, so
+    // the control context starts at `fp - 8`. The state of this stack is
+    // never resumed, so store it in scratch space below `sp`.
+    enc::xmov_fp(&mut code, x1);
+    enc::xsub64_u8(&mut code, x1, x1, 8);
+    enc::xmov(&mut code, x2, sp);
+    enc::xsub64_u8(&mut code, x2, x2, 32);
+    enc::xconst8(&mut code, x0, 0);
+    enc::stack_switch(&mut code, x2, x1);
+
+    code.into_boxed_slice()
+}
@@ -0,0 +1,70 @@
+// A WORD OF CAUTION
+//
+// This entire file basically needs to be kept in sync with itself. It's not
+// really possible to modify just one bit of this file without understanding
+// all the other bits. Documentation tries to reference various bits here and
+// there but try to make sure to read over everything before tweaking things!
+
+use wasmtime_asm_macros::asm_func;
+
+// This is a pretty special function that has no real signature. Its use is to
+// be the "base" function of all fibers. This entrypoint is used in
+// `wasmtime_continuation_init` to bootstrap the execution of a new fiber.
+//
+// See the x86_64 version of this file for more commentary.
+//
+// Executing `stack_switch` on a stack prepared by `FiberStack::initialize` as
+// described in the comment on `FiberStack::initialize` leads to the following
+// values in various registers when execution of wasmtime_continuation_start begins:
+//
+// SP: TOS - 0x40 - (16 * `args_capacity`)
+// S0: TOS - 0x10
+asm_func!(
+    "wasmtime_continuation_start",
+    "
+        //
+        // Note that the next 5 instructions amount to calling fiber_start
+        // with the following arguments:
+        // 1. func_ref
+        // 2. caller_vmctx
+        // 3. args (of type *mut ArrayRef<ValRaw>)
+        // 4. return_value_count
+        //
+        // SP stays 16-byte aligned afterwards.
+
+        ld a3, 0(sp) // return_value_count
+        ld a2, 8(sp) // args
+        ld a1, 16(sp) // caller_vmctx
+        ld a0, 24(sp) // func_ref
+        addi sp, sp, 32
+        // Note that S0 already contains the right frame pointer to build a
+        // frame pointer chain including the parent continuation, using the
+        // same frame record layout as Cranelift: The current value of S0 is
+        // where we store the parent S0 in the control context, directly
+        // followed by the parent PC!
+        call {fiber_start}
+
+        // Return to the parent continuation.
+        // S0 is callee-saved, so its value is still TOS - 0x10.
+        // Use that fact to obtain saved parent FP, SP, and PC from control
+        // context near TOS.
+        ld t0, 8(s0) // putting new PC in temp register
+        ld sp, -8(s0)
+        ld s0, 0(s0)
+
+        // The stack_switch instruction uses register A0 for the payload.
+        // Here, the payload indicates that we are returning (value 0).
+        // See the test case below to keep this in sync with
+        // ControlEffect::return_()
+        li a0, 0
+
+        jr t0
+    ",
+    fiber_start = sym super::fiber_start,
+);
+
+#[test]
+fn test_return_payload() {
+    // The following assumption is baked into `wasmtime_continuation_start`.
+    assert_eq!(wasmtime_environ::CONTROL_EFFECT_RETURN_DISCRIMINANT, 0);
+}
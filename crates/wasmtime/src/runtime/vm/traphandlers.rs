@@ -185,6 +185,27 @@ host_result_no_catch! {
     f64x2,
 }
 
+/// Implementation of `HostResult` for libcalls which always unwind, such as
+/// the one throwing Wasm exceptions: the libcall computes the unwind reason
+/// itself and the ABI value is always the `false` sentinel.
+impl HostResult for UnwindReason {
+    type Abi = bool;
+    fn maybe_catch_unwind(f: impl FnOnce() -> UnwindReason) -> (bool, Option<UnwindReason>) {
+        #[cfg(all(feature = "std", panic = "unwind"))]
+        {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+                Ok(reason) => (false, Some(reason)),
+                Err(err) => (false, Some(UnwindReason::Panic(err))),
+            }
+        }
+
+        #[cfg(not(all(feature = "std", panic = "unwind")))]
+        {
+            (false, Some(f()))
+        }
+    }
+}
+
 impl HostResult for NonNull<u8> {
     type Abi = *mut u8;
     fn maybe_catch_unwind(f: impl FnOnce() -> Self) -> (*mut u8, Option<UnwindReason>) {
@@ -437,6 +458,9 @@ where
         })),
         #[cfg(all(feature = "std", panic = "unwind"))]
         Err((UnwindReason::Panic(panic), _, _)) => std::panic::resume_unwind(panic),
+        Err((UnwindReason::Exception(_), _, _)) => {
+            unreachable!("exceptions resume at their handler instead of unwinding")
+        }
     };
 }
 
@@ -622,6 +646,24 @@ pub enum UnwindReason {
     #[cfg(all(feature = "std", panic = "unwind"))]
     Panic(Box<dyn std::any::Any + Send>),
     Trap(TrapReason),
+    /// A Wasm exception was thrown and a handler for it was found within the
+    /// current activation, where execution resumes instead of returning to
+    /// the host.
+    Exception(ExceptionHandler),
+}
+
+/// The handler of a thrown Wasm exception and the state to resume it with.
+#[derive(Clone, Copy, Debug)]
+pub struct ExceptionHandler {
+    /// The address of the handler.
+    pub pc: usize,
+    /// The stack pointer to restore before jumping to the handler.
+    pub sp: usize,
+    /// The frame pointer of the function containing the handler.
+    pub fp: usize,
+    /// The raw exception object, passed to the handler in the first payload
+    /// register.
+    pub payload: u32,
 }
 
 impl CallThreadState {
@@ -669,6 +711,8 @@ impl CallThreadState {
             // Wasm problem.
             #[cfg(all(feature = "std", panic = "unwind"))]
             UnwindReason::Panic(_) => (None, None),
+            // Caught exceptions never make it to the host.
+            UnwindReason::Exception(_) => (None, None),
             // And if we are just propagating an existing trap that already has
             // a backtrace attached to it, then there is no need to capture a
             // new backtrace either.
@@ -691,7 +735,9 @@ impl CallThreadState {
     /// Helper function to perform an actual unwinding operation.
     ///
     /// This must be preceded by a `record_unwind` operation above to be
-    /// processed correctly on the other side.
+    /// processed correctly on the other side. Exceptions with a handler
+    /// resume execution at that handler, everything else unwinds back to the
+    /// original `setjmp`.
     ///
     /// # Unsafety
     ///
@@ -702,6 +748,18 @@ impl CallThreadState {
     unsafe fn unwind(&self) -> ! {
         debug_assert!(!self.jmp_buf.get().is_null());
         debug_assert!(self.jmp_buf.get() != CallThreadState::JMP_BUF_INTERPRETER_SENTINEL);
+        match self.unwind.take() {
+            Some((UnwindReason::Exception(handler), _, _)) => unsafe {
+                wasmtime_unwinder::resume_to_exception_handler(
+                    handler.pc,
+                    handler.sp,
+                    handler.fp,
+                    usize::try_from(handler.payload).unwrap(),
+                    0,
+                )
+            },
+            unwind => self.unwind.set(unwind),
+        }
         unsafe {
             traphandlers::wasmtime_longjmp(self.jmp_buf.get());
         }
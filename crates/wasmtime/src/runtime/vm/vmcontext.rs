@@ -1153,6 +1153,21 @@ pub struct VMStoreContext {
     /// on `VMStackChain` for details.
     pub stack_chain: UnsafeCell<VMStackChain>,
 
+    /// Whether guest debugging hooks should call into the runtime.
+    ///
+    /// Code compiled with guest debugging instrumentation checks this flag
+    /// before every instruction. It is nonzero only while the store has a
+    /// debug handler along with breakpoints or single-stepping enabled.
+    pub debug_active: UnsafeCell<u32>,
+
+    /// Number of function entries and loop iterations left to execute in
+    /// baseline code before the next tier-up sample is taken.
+    ///
+    /// Only used by code compiled with tiered compilation enabled, which
+    /// decrements this and calls the `tier_up` libcall once it is no longer
+    /// positive.
+    pub tier_up_countdown: UnsafeCell<i32>,
+
     /// The range, in addresses, of the guard page that is currently in use.
     ///
     /// This field is used when signal handlers are run to determine whether a
@@ -1190,6 +1205,8 @@ impl Default for VMStoreContext {
             last_wasm_exit_pc: UnsafeCell::new(0),
             last_wasm_entry_fp: UnsafeCell::new(0),
             stack_chain: UnsafeCell::new(VMStackChain::Absent),
+            debug_active: UnsafeCell::new(0),
+            tier_up_countdown: UnsafeCell::new(0),
             async_guard_range: ptr::null_mut()..ptr::null_mut(),
         }
     }
@@ -1244,6 +1261,14 @@ mod test_vmstore_context {
         assert_eq!(
             offset_of!(VMStoreContext, stack_chain),
             usize::from(offsets.ptr.vmstore_context_stack_chain())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, debug_active),
+            usize::from(offsets.ptr.vmstore_context_debug_active())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, tier_up_countdown),
+            usize::from(offsets.ptr.vmstore_context_tier_up_countdown())
         )
     }
 }
@@ -458,6 +458,17 @@ where
         bail!("expected '{}', got '{}'", expected, actual)
     }
 
+    fn assert_exception(&self, result: Outcome) -> Result<()> {
+        let trap = match result {
+            Outcome::Ok(values) => bail!("expected exception, got {:?}", values),
+            Outcome::Trap(t) => t,
+        };
+        match trap.downcast_ref::<Trap>() {
+            Some(Trap::UncaughtException) => Ok(()),
+            _ => bail!("expected uncaught exception, got '{trap:?}'"),
+        }
+    }
+
     /// Run a wast script from a byte buffer.
     pub fn run_buffer(&mut self, filename: &str, wast: &[u8]) -> Result<()> {
         let wast = str::from_utf8(wast)?;
@@ -627,7 +638,10 @@ where
                     )
                 }
             }
-            AssertException { .. } => bail!("unimplemented assert_exception"),
+            AssertException { span: _, exec } => {
+                let result = self.perform_execute(exec, filename, wast)?;
+                self.assert_exception(result)?;
+            }
 
             Thread(thread) => {
                 let mut core_linker = Linker::new(self.store.engine());
@@ -11,7 +11,7 @@ use wasmtime_environ::{
     FunctionLoc, ModuleTranslation, ModuleTypesBuilder, PrimaryMap, RelocationTarget,
     StaticModuleIndex, Tunables, VMOffsets,
 };
-use winch_codegen::{BuiltinFunctions, CallingConvention, TargetIsa};
+use winch_codegen::{BuiltinFunctions, CallingConvention, CompiledFunctionOutput, TargetIsa};
 
 /// Function compilation context.
 /// This struct holds information that can be shared globally across
@@ -97,8 +97,8 @@ impl wasmtime_environ::Compiler for Compiler {
         types: &ModuleTypesBuilder,
         _symbol: &str,
     ) -> Result<CompiledFunctionBody, CompileError> {
-        let index = translation.module.func_index(index);
-        let sig = translation.module.functions[index]
+        let func_index = translation.module.func_index(index);
+        let sig = translation.module.functions[func_index]
             .signature
             .unwrap_module_type_index();
         let ty = types[sig].unwrap_func();
@@ -110,6 +110,7 @@ impl wasmtime_environ::Compiler for Compiler {
         let func = self
             .isa
             .compile_function(
+                index,
                 ty,
                 &body,
                 translation,
@@ -120,7 +121,10 @@ impl wasmtime_environ::Compiler for Compiler {
             )
             .map_err(|e| CompileError::Codegen(format!("{e:?}")));
         self.save_context(context, validator.into_allocations());
-        let mut func = func?;
+        let CompiledFunctionOutput {
+            function: mut func,
+            needs_gc_heap,
+        } = func?;
 
         let reader = body.get_binary_reader();
         func.set_address_map(
@@ -135,8 +139,7 @@ impl wasmtime_environ::Compiler for Compiler {
 
         Ok(CompiledFunctionBody {
             code: Box::new(func),
-            // TODO: Winch doesn't support GC objects and stack maps and all that yet.
-            needs_gc_heap: false,
+            needs_gc_heap,
         })
     }
 
@@ -0,0 +1,197 @@
+//! Example program of finding candidate superinstructions in pulley profiles.
+//!
+//! To use this program first record a profile as described in the
+//! `profiler-html.rs` example, producing a `pulley-$pid.data` file. That file
+//! is then fed to this program:
+//!
+//! ```text
+//! $ cargo run -p pulley-interpreter --example superinstructions --all-features \
+//!     ./pulley-$pid.data > pulley/macros/superinstructions.txt
+//! ```
+//!
+//! This will print the most frequently executed pairs of adjacent instructions
+//! in the format of `superinstructions.txt`, which lists the superinstructions
+//! that are built into Pulley. Each pair is weighted by the number of samples
+//! that fell on its first instruction. Pairs that can't be fused, for example
+//! because they contain control flow, are skipped. Superinstructions in the
+//! profile are counted as their components, such that the output stays stable
+//! when regenerating the list from a profile of a build which already uses it.
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use pulley_interpreter::decode::{Decoder, OpVisitor};
+use pulley_interpreter::disas::Disassembler;
+use pulley_interpreter::opcode::Opcode;
+use pulley_interpreter::profile::{Event, decode};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Superinstructions {
+    /// The profile data to load which was generated by a `--profile pulley` run
+    /// of Wasmtime previously.
+    profile: PathBuf,
+
+    /// The maximum number of superinstructions to print.
+    #[clap(long, default_value = "32")]
+    count: usize,
+}
+
+struct Function<'a> {
+    addr: u64,
+    body: &'a [u8],
+    instructions: HashMap<u32, u64>,
+}
+
+macro_rules! define_can_fuse {
+    (
+        $(
+            $( #[$attr:meta] )*
+                $snake_name:ident = $name:ident $( {
+                $(
+                    $( #[$field_attr:meta] )*
+                    $field:ident : $field_ty:ty
+                ),*
+            } )? ;
+        )*
+    ) => {
+        /// Returns the names of the ops executed by `opcode`, or an empty
+        /// list if it can't be part of a superinstruction.
+        ///
+        /// Control flow can't be fused, and superinstructions are expanded to
+        /// the ops they are made of.
+        fn fusible_ops(opcode: Opcode) -> &'static [&'static str] {
+            match opcode {
+                $(
+                    Opcode::$name => {
+                        let name = stringify!($snake_name);
+                        if let Some((_, components)) =
+                            SUPER_OPS.iter().find(|(s, _)| *s == name)
+                        {
+                            return components;
+                        }
+                        let control_flow = matches!(
+                            name,
+                            "ret" | "call_indirect" | "xjump" | "br_table32"
+                        ) $( $( || stringify!($field_ty) == "PcRelOffset" )* )?;
+                        if control_flow {
+                            &[]
+                        } else {
+                            &[stringify!($snake_name)]
+                        }
+                    }
+                )*
+                Opcode::ExtendedOp => &[],
+            }
+        }
+    };
+}
+pulley_interpreter::for_each_op!(define_can_fuse);
+
+macro_rules! define_super_ops {
+    (
+        $(
+            $snake_name:ident = $name:ident {
+                $( $component:ident = $component_name:ident { $($fields:tt)* } ; )*
+            } ;
+        )*
+    ) => {
+        &[ $( (stringify!($snake_name), &[ $( stringify!($component) ),* ]) ),* ]
+    };
+}
+const SUPER_OPS: &[(&str, &[&str])] = pulley_interpreter::for_each_super_op!(define_super_ops);
+
+const HEADER: &str = "\
+# Sequences of Pulley ops which are fused into a single superinstruction.
+#
+# Each line lists the ops of one superinstruction, in execution order. This
+# file is generated from execution profiles with:
+#
+#   cargo run -p pulley-interpreter --example superinstructions --all-features \\
+#       ./pulley-$pid.data > pulley/macros/superinstructions.txt
+#
+# See `for_each_super_op!` in `pulley/src/lib.rs` for more information.
+";
+
+fn main() -> Result<()> {
+    let args = Superinstructions::parse();
+    let profile = std::fs::read(&args.profile)
+        .with_context(|| format!("failed to read {:?}", args.profile))?;
+
+    let mut functions = BTreeMap::new();
+    let mut found_samples = false;
+    for event in decode(&profile) {
+        match event? {
+            Event::Function(addr, _name, body) => {
+                let prev = functions.insert(
+                    addr,
+                    Function {
+                        addr,
+                        body,
+                        instructions: HashMap::new(),
+                    },
+                );
+                assert!(prev.is_none());
+            }
+            Event::Samples(samples) => {
+                found_samples = true;
+                for sample in samples {
+                    let addr = sample.0;
+                    let (_, function) = functions.range_mut(..=addr).next_back().unwrap();
+                    assert!(addr >= function.addr);
+                    assert!(addr < function.addr + (function.body.len() as u64));
+                    *function
+                        .instructions
+                        .entry(u32::try_from(addr - function.addr).unwrap())
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    if functions.is_empty() {
+        bail!("no functions found in profile");
+    }
+    if !found_samples {
+        bail!("no samples found in profile");
+    }
+
+    // Weight each pair of adjacent instructions by the hits of the first one.
+    let mut pairs = HashMap::<(&str, &str), u64>::new();
+    for func in functions.values() {
+        let mut disas = Disassembler::new(func.body);
+        let mut decoder = Decoder::new();
+        let mut prev = None;
+        while !disas.bytecode().as_slice().is_empty() {
+            let offset = disas.bytecode().position();
+            let ops = Opcode::new(func.body[offset]).map_or(&[][..], fusible_ops);
+            decoder.decode_one(&mut disas)?;
+            let hits = func
+                .instructions
+                .get(&u32::try_from(offset).unwrap())
+                .copied()
+                .unwrap_or(0);
+            if ops.is_empty() {
+                prev = None;
+            }
+            for &name in ops {
+                if let Some((prev, prev_hits)) = prev {
+                    if prev_hits > 0 {
+                        *pairs.entry((prev, name)).or_insert(0) += prev_hits;
+                    }
+                }
+                prev = Some((name, hits));
+            }
+        }
+    }
+
+    let mut pairs = pairs.into_iter().collect::<Vec<_>>();
+    pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    print!("{HEADER}");
+    for ((first, second), _) in pairs.into_iter().take(args.count) {
+        println!("{first} {second}");
+    }
+
+    Ok(())
+}
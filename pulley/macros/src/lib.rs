@@ -1,8 +1,14 @@
 use proc_macro::TokenStream;
 
 mod interp_disable_if_cfg;
+mod superinstructions;
 
 #[proc_macro_attribute]
 pub fn interp_disable_if_cfg(attrs: TokenStream, item: TokenStream) -> TokenStream {
     interp_disable_if_cfg::run(attrs, item)
 }
+
+#[proc_macro]
+pub fn superinstructions(input: TokenStream) -> TokenStream {
+    superinstructions::run(input)
+}
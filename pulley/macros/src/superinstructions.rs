@@ -0,0 +1,261 @@
+//! Build-time generation of Pulley superinstructions.
+//!
+//! A superinstruction is a single opcode which executes a sequence of two
+//! other opcodes, saving one dispatch in the interpreter. The sequences are
+//! listed in `superinstructions.txt`, typically generated from execution
+//! profiles with the `superinstructions` example of `pulley-interpreter`.
+//!
+//! The operands of a superinstruction are the concatenated operands of its
+//! components, so its encoding is exactly the encoding of the sequence minus
+//! the opcode of the second instruction. Operand names get the 1-based index
+//! of their component appended, e.g. `dst_1`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Error, Ident, Result, Token, Type, braced,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+};
+
+/// The sequences of opcodes to fuse, one sequence per line.
+const SPEC: &str = include_str!("../superinstructions.txt");
+
+/// Opcodes which read or write the program counter and thus can't be part of
+/// a superinstruction (in addition to all opcodes with a `PcRelOffset`
+/// operand).
+const CONTROL_FLOW: &[&str] = &["ret", "call_indirect", "xjump", "br_table32"];
+
+pub fn run(input: TokenStream) -> TokenStream {
+    match expand(parse_macro_input!(input as Input)) {
+        Ok(tok) => tok.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Input to the macro: `mode macro; ops...` where `ops` uses the syntax of
+/// `for_each_op!`.
+struct Input {
+    mode: Ident,
+    macro_name: Ident,
+    ops: Vec<Op>,
+    tokens: TokenStream2,
+}
+
+struct Op {
+    snake_name: Ident,
+    name: Ident,
+    fields: Vec<(Ident, Type)>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mode = input.parse()?;
+        let macro_name = input.parse()?;
+        input.parse::<Token![;]>()?;
+        let tokens: TokenStream2 = input.fork().parse()?;
+        let mut ops = Vec::new();
+        while !input.is_empty() {
+            ops.push(input.parse()?);
+        }
+        Ok(Input {
+            mode,
+            macro_name,
+            ops,
+            tokens,
+        })
+    }
+}
+
+impl Parse for Op {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.call(Attribute::parse_outer)?;
+        let snake_name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let name = input.parse()?;
+        let mut fields = Vec::new();
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            let parsed = Punctuated::<Field, Token![,]>::parse_terminated(&content)?;
+            fields.extend(parsed.into_iter().map(|f| (f.name, f.ty)));
+        }
+        input.parse::<Token![;]>()?;
+        Ok(Op {
+            snake_name,
+            name,
+            fields,
+        })
+    }
+}
+
+struct Field {
+    name: Ident,
+    ty: Type,
+}
+
+impl Parse for Field {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.call(Attribute::parse_outer)?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Field { name, ty })
+    }
+}
+
+/// A superinstruction, along with the base opcodes it is made of.
+struct Super<'a> {
+    snake_name: Ident,
+    name: Ident,
+    components: Vec<&'a Op>,
+}
+
+impl Super<'_> {
+    /// The operands of each component, renamed to be unique within the
+    /// superinstruction.
+    fn fields(&self) -> impl Iterator<Item = Vec<(Ident, &Type)>> + '_ {
+        self.components.iter().enumerate().map(|(i, op)| {
+            op.fields
+                .iter()
+                .map(|(name, ty)| (format_ident!("{name}_{}", i + 1), ty))
+                .collect()
+        })
+    }
+}
+
+fn parse_spec(ops: &[Op]) -> Result<Vec<Super<'_>>> {
+    let err = |msg: String| Error::new(Span::call_site(), msg);
+    let mut supers = Vec::new();
+    for (i, line) in SPEC.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut components = Vec::new();
+        for op in line.split_whitespace() {
+            let component = ops.iter().find(|o| o.snake_name == op).ok_or_else(|| {
+                err(format!(
+                    "superinstructions.txt:{}: unknown op `{op}`",
+                    i + 1
+                ))
+            })?;
+            let is_control_flow = CONTROL_FLOW.contains(&op)
+                || component
+                    .fields
+                    .iter()
+                    .any(|(_, ty)| quote!(#ty).to_string() == "PcRelOffset");
+            if is_control_flow {
+                return Err(err(format!(
+                    "superinstructions.txt:{}: control flow op `{op}` can't be fused",
+                    i + 1
+                )));
+            }
+            components.push(component);
+        }
+        if components.len() != 2 {
+            return Err(err(format!(
+                "superinstructions.txt:{}: superinstructions must consist of exactly two ops",
+                i + 1
+            )));
+        }
+        let snake_name = format_ident!(
+            "{}",
+            components
+                .iter()
+                .map(|o| o.snake_name.to_string())
+                .collect::<Vec<_>>()
+                .join("_")
+        );
+        let name = format_ident!(
+            "{}",
+            components
+                .iter()
+                .map(|o| o.name.to_string())
+                .collect::<String>()
+        );
+        if ops.iter().any(|o| o.snake_name == snake_name)
+            || supers
+                .iter()
+                .any(|s: &Super<'_>| s.snake_name == snake_name)
+        {
+            return Err(err(format!(
+                "superinstructions.txt:{}: duplicate op `{snake_name}`",
+                i + 1
+            )));
+        }
+        supers.push(Super {
+            snake_name,
+            name,
+            components,
+        });
+    }
+
+    // All opcodes, plus the extended-op opcode, must fit in a byte.
+    if ops.len() + supers.len() + 1 > 256 {
+        return Err(err(format!(
+            "too many superinstructions: only {} opcodes are available",
+            255 - ops.len()
+        )));
+    }
+    Ok(supers)
+}
+
+fn expand(input: Input) -> Result<TokenStream2> {
+    let supers = parse_spec(&input.ops)?;
+    let macro_name = &input.macro_name;
+
+    if input.mode == "ops" {
+        // Append a regular op definition for each superinstruction.
+        let tokens = &input.tokens;
+        let defs = supers.iter().map(|s| {
+            let snake_name = &s.snake_name;
+            let name = &s.name;
+            let doc = format!(
+                "Superinstruction: {}.",
+                s.components
+                    .iter()
+                    .map(|o| format!("`{}`", o.snake_name))
+                    .collect::<Vec<_>>()
+                    .join(" followed by ")
+            );
+            let fields = s.fields().flatten().map(|(name, ty)| quote!(#name: #ty));
+            quote! {
+                #[doc = #doc]
+                #snake_name = #name { #(#fields),* };
+            }
+        });
+        Ok(quote! {
+            #macro_name! {
+                #tokens
+                #(#defs)*
+            }
+        })
+    } else if input.mode == "super_ops" {
+        // List each superinstruction along with its components, where the
+        // fields of each component are given their names in the
+        // superinstruction.
+        let defs = supers.iter().map(|s| {
+            let snake_name = &s.snake_name;
+            let name = &s.name;
+            let components = s.components.iter().zip(s.fields()).map(|(op, fields)| {
+                let snake_name = &op.snake_name;
+                let name = &op.name;
+                let fields = fields.iter().map(|(name, ty)| quote!(#name: #ty));
+                quote!(#snake_name = #name { #(#fields),* };)
+            });
+            quote! {
+                #snake_name = #name { #(#components)* };
+            }
+        });
+        Ok(quote! {
+            #macro_name! {
+                #(#defs)*
+            }
+        })
+    } else {
+        Err(Error::new(input.mode.span(), "unknown mode"))
+    }
+}
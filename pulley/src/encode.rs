@@ -290,6 +290,35 @@ macro_rules! impl_encoders {
 }
 for_each_op!(impl_encoders);
 
+macro_rules! impl_opcode_width {
+    (
+        $(
+            $( #[$attr:meta] )*
+                $snake_name:ident = $name:ident $( {
+                $(
+                    $( #[$field_attr:meta] )*
+                    $field:ident : $field_ty:ty
+                ),*
+            } )? ;
+        )*
+    ) => {
+        impl Opcode {
+            /// Returns the encoded width of an instruction with this opcode,
+            /// including the opcode itself.
+            ///
+            /// Trailing data, such as the table of `br_table32`, isn't
+            /// included. Returns `None` for `Opcode::ExtendedOp`.
+            pub fn width(self) -> Option<u8> {
+                match self {
+                    $( Opcode::$name => Some(<crate::op::$name as Encode>::WIDTH), )*
+                    Opcode::ExtendedOp => None,
+                }
+            }
+        }
+    };
+}
+for_each_op!(impl_opcode_width);
+
 macro_rules! impl_extended_encoders {
     (
         $(
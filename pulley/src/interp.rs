@@ -12,6 +12,7 @@ use core::mem;
 use core::ops::ControlFlow;
 use core::ops::{Index, IndexMut};
 use core::ptr::NonNull;
+use core::sync::atomic::{self, Ordering::SeqCst};
 use pulley_macros::interp_disable_if_cfg;
 use wasmtime_math::{WasmFloat, f32_cvt_to_int_bounds, f64_cvt_to_int_bounds};
 
@@ -959,6 +960,7 @@ mod done {
         MemoryOutOfBounds,
         DisabledOpcode,
         StackOverflow,
+        HeapMisaligned,
     }
 
     impl MachineState {
@@ -1071,11 +1073,16 @@ impl Interpreter<'_> {
     /// The `I` parameter here is the instruction that is setting the stack
     /// pointer and is used to calculate this instruction's own `pc` if this
     /// instruction traps.
+    ///
+    /// Note that this check is only performed while executing on the stack of
+    /// `self.state`. After a `stack_switch` to some other stack the bounds of
+    /// that stack aren't known, and it's up to the bytecode to check for stack
+    /// overflow itself.
     #[must_use]
     fn set_sp<I: Encode>(&mut self, sp: *mut u8) -> ControlFlow<Done> {
         let sp_raw = sp as usize;
         let base_raw = self.state.stack.base() as usize;
-        if sp_raw < base_raw {
+        if sp_raw < base_raw && self.on_vm_stack() {
             return self.done_trap_kind::<I>(Some(TrapKind::StackOverflow));
         }
         self.set_sp_unchecked(sp);
@@ -1085,7 +1092,7 @@ impl Interpreter<'_> {
     /// Same as `set_sp` but does not check to see if `sp` is in-bounds. Should
     /// only be used with stack increment operations such as `pop`.
     fn set_sp_unchecked<T>(&mut self, sp: *mut T) {
-        if cfg!(debug_assertions) {
+        if cfg!(debug_assertions) && self.on_vm_stack() {
             let sp_raw = sp as usize;
             let base = self.state.stack.base() as usize;
             let end = base + self.state.stack.len();
@@ -1094,6 +1101,14 @@ impl Interpreter<'_> {
         self.state[XReg::sp].set_ptr(sp);
     }
 
+    /// Returns whether the current stack pointer is within the stack of
+    /// `self.state`, as opposed to a stack switched to with `stack_switch`.
+    fn on_vm_stack(&mut self) -> bool {
+        let sp_raw = self.state[XReg::sp].get_ptr::<u8>() as usize;
+        let base_raw = self.state.stack.base() as usize;
+        base_raw <= sp_raw && sp_raw <= base_raw + self.state.stack.len()
+    }
+
     /// Loads a value of `T` using native-endian byte ordering from the `addr`
     /// specified.
     ///
@@ -1133,6 +1148,34 @@ impl Interpreter<'_> {
         unsafe { addr.store_ne::<T, I>(self, val) }
     }
 
+    /// Returns the atomic `A` located at the host address in `addr`.
+    ///
+    /// The `I` type parameter is the instruction issuing this access which is
+    /// used in case of traps to calculate the trapping pc.
+    ///
+    /// Returns `ControlFlow::Break` if a trap happens, which is the case if
+    /// `addr` is null or not suitably aligned for `A`.
+    ///
+    /// # Unsafety
+    ///
+    /// Safety of this method relies on the safety of the original bytecode
+    /// itself and correctly annotating both `A` and `I`.
+    #[must_use]
+    unsafe fn atomic<'a, A, I: Encode>(&mut self, addr: XReg) -> ControlFlow<Done, &'a A> {
+        // Like the `AddrZ` addressing mode accessing the null address is
+        // defined as a trap.
+        let addr = self.state[addr].get_ptr::<A>();
+        if addr.is_null() {
+            self.done_trap_kind::<I>(Some(TrapKind::MemoryOutOfBounds))?;
+            unreachable!();
+        }
+        if !addr.is_aligned() {
+            self.done_trap_kind::<I>(Some(TrapKind::HeapMisaligned))?;
+            unreachable!();
+        }
+        ControlFlow::Continue(unsafe { &*addr })
+    }
+
     fn check_xnn_from_f32<I: Encode>(
         &mut self,
         val: f32,
@@ -1329,6 +1372,32 @@ macro_rules! br_if_imm {
     )*};
 }
 
+/// Implements each superinstruction by executing its components in order.
+///
+/// Before executing a component, `pc` is set to the end of where the
+/// component is encoded within the superinstruction. For the first component
+/// this is exactly where `pc` would be if it was executed on its own, so a
+/// trap in it is reported at the start of the superinstruction.
+macro_rules! superinstruction_handlers {
+    ($(
+        $snake:ident = $camel:ident {
+            $( $csnake:ident = $ccamel:ident { $( $field:ident : $field_ty:ty ),* }; )*
+        };
+    )*) => {$(
+        fn $snake(&mut self $( $( , $field: $field_ty )* )*) -> ControlFlow<Done> {
+            // Skip the opcode of the superinstruction; each component's
+            // operands follow the previous component's operands.
+            let mut pc = unsafe { self.current_pc::<crate::$camel>().add(1) };
+            $(
+                pc = unsafe { pc.add(usize::from(crate::$ccamel::WIDTH) - 1) };
+                self.pc = unsafe { UnsafeBytecodeStream::new(pc) };
+                self.$csnake($($field),*)?;
+            )*
+            ControlFlow::Continue(())
+        }
+    )*};
+}
+
 impl OpVisitor for Interpreter<'_> {
     type BytecodeStream = UnsafeBytecodeStream;
     type Return = ControlFlow<Done>;
@@ -2818,6 +2887,152 @@ impl OpVisitor for Interpreter<'_> {
         }
         ControlFlow::Continue(())
     }
+
+    for_each_super_op!(superinstruction_handlers);
+}
+
+/// Values operated on by atomic instructions, which are taken from and
+/// zero-extended into the low 32 bits of registers unless they're 64 bits wide.
+trait XAtomicVal {
+    fn get_xreg(reg: &XRegVal) -> Self;
+    fn set_xreg(self, reg: &mut XRegVal);
+}
+
+macro_rules! xatomic_val {
+    ($($ty:ident => |$r:ident| $get:expr, |$v:ident| $set:expr;)*) => {$(
+        impl XAtomicVal for $ty {
+            #[inline]
+            fn get_xreg($r: &XRegVal) -> Self {
+                $get
+            }
+
+            #[inline]
+            fn set_xreg(self, $r: &mut XRegVal) {
+                let $v = self;
+                $set
+            }
+        }
+    )*};
+}
+
+xatomic_val! {
+    u8 => |r| r.get_u32() as u8, |v| r.set_u32(v.into());
+    i8 => |r| r.get_u32() as i8, |v| r.set_u32((v as u8).into());
+    u16 => |r| r.get_u32() as u16, |v| r.set_u32(v.into());
+    i16 => |r| r.get_u32() as i16, |v| r.set_u32((v as u16).into());
+    u32 => |r| r.get_u32(), |v| r.set_u32(v);
+    i32 => |r| r.get_i32(), |v| r.set_i32(v);
+    u64 => |r| r.get_u64(), |v| r.set_u64(v);
+    i64 => |r| r.get_i64(), |v| r.set_i64(v);
+}
+
+// Helpers for atomic instructions. All of these are sequentially consistent
+// and operate on little-endian values in memory. Hosts without native atomics
+// of the requisite width trap with `TrapKind::DisabledOpcode` instead.
+
+macro_rules! xatomic_load {
+    ($(
+        fn $snake:ident = $camel:ident / $width:literal / $atomic:ident : $ty:ident;
+    )*) => {$(
+        #[cfg(target_has_atomic = $width)]
+        fn $snake(&mut self, dst: XReg, addr: XReg) -> ControlFlow<Done> {
+            let atomic = unsafe { self.atomic::<atomic::$atomic, crate::$camel>(addr)? };
+            $ty::from_le(atomic.load(SeqCst)).set_xreg(&mut self.state[dst]);
+            ControlFlow::Continue(())
+        }
+
+        #[cfg(not(target_has_atomic = $width))]
+        fn $snake(&mut self, _dst: XReg, _addr: XReg) -> ControlFlow<Done> {
+            self.done_trap_kind::<crate::$camel>(Some(TrapKind::DisabledOpcode))
+        }
+    )*};
+}
+
+macro_rules! xatomic_store {
+    ($(
+        fn $snake:ident = $camel:ident / $width:literal / $atomic:ident : $ty:ident;
+    )*) => {$(
+        #[cfg(target_has_atomic = $width)]
+        fn $snake(&mut self, addr: XReg, src: XReg) -> ControlFlow<Done> {
+            let atomic = unsafe { self.atomic::<atomic::$atomic, crate::$camel>(addr)? };
+            let val = $ty::get_xreg(&self.state[src]);
+            atomic.store(val.to_le(), SeqCst);
+            ControlFlow::Continue(())
+        }
+
+        #[cfg(not(target_has_atomic = $width))]
+        fn $snake(&mut self, _addr: XReg, _src: XReg) -> ControlFlow<Done> {
+            self.done_trap_kind::<crate::$camel>(Some(TrapKind::DisabledOpcode))
+        }
+    )*};
+}
+
+macro_rules! xatomic_rmw {
+    ($(
+        fn $snake:ident = $camel:ident / $width:literal / $atomic:ident : $ty:ident
+            / $fetch:ident / |$a:tt, $b:tt| $update:expr;
+    )*) => {$(
+        #[cfg(target_has_atomic = $width)]
+        fn $snake(&mut self, dst: XReg, addr: XReg, src: XReg) -> ControlFlow<Done> {
+            let atomic = unsafe { self.atomic::<atomic::$atomic, crate::$camel>(addr)? };
+            let src = $ty::get_xreg(&self.state[src]);
+            let old = if cfg!(target_endian = "little") {
+                atomic.$fetch(src, SeqCst)
+            } else {
+                // Values in memory need to be byte-swapped on big-endian
+                // hosts, so the update can't be delegated to the native
+                // operation there.
+                let update = |$a: $ty, $b: $ty| $update;
+                let old = atomic
+                    .fetch_update(SeqCst, SeqCst, |old| {
+                        Some(update($ty::from_le(old), src).to_le())
+                    })
+                    .unwrap();
+                $ty::from_le(old)
+            };
+            old.set_xreg(&mut self.state[dst]);
+            ControlFlow::Continue(())
+        }
+
+        #[cfg(not(target_has_atomic = $width))]
+        fn $snake(&mut self, _dst: XReg, _addr: XReg, _src: XReg) -> ControlFlow<Done> {
+            self.done_trap_kind::<crate::$camel>(Some(TrapKind::DisabledOpcode))
+        }
+    )*};
+}
+
+macro_rules! xatomic_cas {
+    ($(
+        fn $snake:ident = $camel:ident / $width:literal / $atomic:ident : $ty:ident;
+    )*) => {$(
+        #[cfg(target_has_atomic = $width)]
+        fn $snake(
+            &mut self,
+            dst: XReg,
+            addr: XReg,
+            expected: XReg,
+            replacement: XReg,
+        ) -> ControlFlow<Done> {
+            let atomic = unsafe { self.atomic::<atomic::$atomic, crate::$camel>(addr)? };
+            let expected = $ty::get_xreg(&self.state[expected]);
+            let replacement = $ty::get_xreg(&self.state[replacement]);
+            let (Ok(old) | Err(old)) =
+                atomic.compare_exchange(expected.to_le(), replacement.to_le(), SeqCst, SeqCst);
+            $ty::from_le(old).set_xreg(&mut self.state[dst]);
+            ControlFlow::Continue(())
+        }
+
+        #[cfg(not(target_has_atomic = $width))]
+        fn $snake(
+            &mut self,
+            _dst: XReg,
+            _addr: XReg,
+            _expected: XReg,
+            _replacement: XReg,
+        ) -> ControlFlow<Done> {
+            self.done_trap_kind::<crate::$camel>(Some(TrapKind::DisabledOpcode))
+        }
+    )*};
 }
 
 impl ExtendedOpVisitor for Interpreter<'_> {
@@ -3902,6 +4117,20 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vaddpairwisei8x16_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let a = self.state[operands.src1].get_i8x16();
+        let b = self.state[operands.src2].get_i8x16();
+        let mut result = [0i8; 16];
+        let half = result.len() / 2;
+        for i in 0..half {
+            result[i] = a[2 * i].wrapping_add(a[2 * i + 1]);
+            result[i + half] = b[2 * i].wrapping_add(b[2 * i + 1]);
+        }
+        self.state[operands.dst].set_i8x16(result);
+        ControlFlow::Continue(())
+    }
+
     #[interp_disable_if_cfg(pulley_disable_interp_simd)]
     fn vaddpairwisei16x8_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
         let a = self.state[operands.src1].get_i16x8();
@@ -4491,6 +4720,30 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vunarrow16x8_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let a = self.state[operands.src1].get_u16x8();
+        let b = self.state[operands.src2].get_u16x8();
+        let mut result = [0; 16];
+        for (i, d) in a.iter().chain(&b).zip(&mut result) {
+            *d = (*i).try_into().unwrap_or(u8::MAX);
+        }
+        self.state[operands.dst].set_u8x16(result);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vunarrow32x4_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let a = self.state[operands.src1].get_u32x4();
+        let b = self.state[operands.src2].get_u32x4();
+        let mut result = [0; 8];
+        for (i, d) in a.iter().chain(&b).zip(&mut result) {
+            *d = (*i).try_into().unwrap_or(u16::MAX);
+        }
+        self.state[operands.dst].set_u16x8(result);
+        ControlFlow::Continue(())
+    }
+
     #[interp_disable_if_cfg(pulley_disable_interp_simd)]
     fn vunarrow64x2_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
         let a = self.state[operands.src1].get_u64x2();
@@ -4685,6 +4938,94 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi8x16_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_i8x16();
+        let b = self.state[operands.src2].get_i8x16();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((i16::from(*a) * i16::from(b)) >> 8) as i8;
+        }
+        self.state[operands.dst].set_i8x16(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi8x16_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_u8x16();
+        let b = self.state[operands.src2].get_u8x16();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((u16::from(*a) * u16::from(b)) >> 8) as u8;
+        }
+        self.state[operands.dst].set_u8x16(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi16x8_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_i16x8();
+        let b = self.state[operands.src2].get_i16x8();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((i32::from(*a) * i32::from(b)) >> 16) as i16;
+        }
+        self.state[operands.dst].set_i16x8(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi16x8_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_u16x8();
+        let b = self.state[operands.src2].get_u16x8();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((u32::from(*a) * u32::from(b)) >> 16) as u16;
+        }
+        self.state[operands.dst].set_u16x8(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi32x4_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_i32x4();
+        let b = self.state[operands.src2].get_i32x4();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((i64::from(*a) * i64::from(b)) >> 32) as i32;
+        }
+        self.state[operands.dst].set_i32x4(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi32x4_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_u32x4();
+        let b = self.state[operands.src2].get_u32x4();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((u64::from(*a) * u64::from(b)) >> 32) as u32;
+        }
+        self.state[operands.dst].set_u32x4(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi64x2_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_i64x2();
+        let b = self.state[operands.src2].get_i64x2();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((i128::from(*a) * i128::from(b)) >> 64) as i64;
+        }
+        self.state[operands.dst].set_i64x2(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmulhi64x2_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_u64x2();
+        let b = self.state[operands.src2].get_u64x2();
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = ((u128::from(*a) * u128::from(b)) >> 64) as u64;
+        }
+        self.state[operands.dst].set_u64x2(a);
+        ControlFlow::Continue(())
+    }
+
     #[interp_disable_if_cfg(pulley_disable_interp_simd)]
     fn vpopcnt8x16(&mut self, dst: VReg, src: VReg) -> ControlFlow<Done> {
         let a = self.state[src].get_u8x16();
@@ -4692,6 +5033,27 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vpopcnt16x8(&mut self, dst: VReg, src: VReg) -> ControlFlow<Done> {
+        let a = self.state[src].get_u16x8();
+        self.state[dst].set_u16x8(a.map(|i| i.count_ones() as u16));
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vpopcnt32x4(&mut self, dst: VReg, src: VReg) -> ControlFlow<Done> {
+        let a = self.state[src].get_u32x4();
+        self.state[dst].set_u32x4(a.map(|i| i.count_ones()));
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vpopcnt64x2(&mut self, dst: VReg, src: VReg) -> ControlFlow<Done> {
+        let a = self.state[src].get_u64x2();
+        self.state[dst].set_u64x2(a.map(|i| i.count_ones() as u64));
+        ControlFlow::Continue(())
+    }
+
     #[interp_disable_if_cfg(pulley_disable_interp_simd)]
     fn xextractv8x16(&mut self, dst: XReg, src: VReg, lane: u8) -> ControlFlow<Done> {
         let a = unsafe { *self.state[src].get_u8x16().get_unchecked(usize::from(lane)) };
@@ -5279,6 +5641,50 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmin64x2_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_i64x2();
+        let b = self.state[operands.src2].get_i64x2();
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a = (*a).min(*b);
+        }
+        self.state[operands.dst].set_i64x2(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmin64x2_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_u64x2();
+        let b = self.state[operands.src2].get_u64x2();
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a = (*a).min(*b);
+        }
+        self.state[operands.dst].set_u64x2(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmax64x2_s(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_i64x2();
+        let b = self.state[operands.src2].get_i64x2();
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a = (*a).max(*b);
+        }
+        self.state[operands.dst].set_i64x2(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vmax64x2_u(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_u64x2();
+        let b = self.state[operands.src2].get_u64x2();
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a = (*a).max(*b);
+        }
+        self.state[operands.dst].set_u64x2(a);
+        ControlFlow::Continue(())
+    }
+
     #[interp_disable_if_cfg(pulley_disable_interp_simd)]
     fn vabs8x16(&mut self, dst: VReg, src: VReg) -> ControlFlow<Done> {
         let a = self.state[src].get_i8x16();
@@ -5321,6 +5727,28 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vcopysignf32x4(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_f32x4();
+        let b = self.state[operands.src2].get_f32x4();
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a = a.wasm_copysign(*b);
+        }
+        self.state[operands.dst].set_f32x4(a);
+        ControlFlow::Continue(())
+    }
+
+    #[interp_disable_if_cfg(pulley_disable_interp_simd)]
+    fn vcopysignf64x2(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
+        let mut a = self.state[operands.src1].get_f64x2();
+        let b = self.state[operands.src2].get_f64x2();
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a = a.wasm_copysign(*b);
+        }
+        self.state[operands.dst].set_f64x2(a);
+        ControlFlow::Continue(())
+    }
+
     #[interp_disable_if_cfg(pulley_disable_interp_simd)]
     fn vmaximumf32x4(&mut self, operands: BinaryOperands<VReg>) -> ControlFlow<Done> {
         let mut a = self.state[operands.src1].get_f32x4();
@@ -5620,4 +6048,149 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         self.set_i128(dst_lo, dst_hi, result as i128);
         ControlFlow::Continue(())
     }
+
+    fn fence(&mut self) -> ControlFlow<Done> {
+        atomic::fence(SeqCst);
+        ControlFlow::Continue(())
+    }
+
+    fn stack_switch(
+        &mut self,
+        store_context_ptr: XReg,
+        load_context_ptr: XReg,
+    ) -> ControlFlow<Done> {
+        let load = self.state[load_context_ptr].get_ptr::<*mut u8>();
+        let store = self.state[store_context_ptr].get_ptr::<*mut u8>();
+        // SAFETY: part of the unsafe contract of the interpreter is that only
+        // valid bytecode is interpreted, so both control contexts are valid
+        // and the loaded `pc` points to more bytecode.
+        unsafe {
+            // Read everything before writing anything as the two contexts are
+            // allowed to be the same.
+            let sp = load.read();
+            let fp = load.add(1).read();
+            let pc = load.add(2).read();
+
+            store.write(self.state[XReg::sp].get_ptr());
+            store.add(1).write(self.state.fp);
+            store.add(2).write(self.pc.as_ptr().as_ptr());
+
+            self.state[XReg::sp].set_ptr(sp);
+            self.state.fp = fp;
+            self.pc = UnsafeBytecodeStream::new(NonNull::new_unchecked(pc));
+        }
+        ControlFlow::Continue(())
+    }
+
+    xatomic_load! {
+        fn xatomic_load8_u32 = XAtomicLoad8U32 / "8" / AtomicU8 : u8;
+        fn xatomic_load16le_u32 = XAtomicLoad16LeU32 / "16" / AtomicU16 : u16;
+        fn xatomic_load32le = XAtomicLoad32Le / "32" / AtomicU32 : u32;
+        fn xatomic_load64le = XAtomicLoad64Le / "64" / AtomicU64 : u64;
+    }
+
+    xatomic_store! {
+        fn xatomic_store8 = XAtomicStore8 / "8" / AtomicU8 : u8;
+        fn xatomic_store16le = XAtomicStore16Le / "16" / AtomicU16 : u16;
+        fn xatomic_store32le = XAtomicStore32Le / "32" / AtomicU32 : u32;
+        fn xatomic_store64le = XAtomicStore64Le / "64" / AtomicU64 : u64;
+    }
+
+    xatomic_rmw! {
+        fn xatomic_rmw_add8 = XAtomicRmwAdd8 / "8" / AtomicU8 : u8
+            / fetch_add / |a, b| a.wrapping_add(b);
+        fn xatomic_rmw_add16le = XAtomicRmwAdd16Le / "16" / AtomicU16 : u16
+            / fetch_add / |a, b| a.wrapping_add(b);
+        fn xatomic_rmw_add32le = XAtomicRmwAdd32Le / "32" / AtomicU32 : u32
+            / fetch_add / |a, b| a.wrapping_add(b);
+        fn xatomic_rmw_add64le = XAtomicRmwAdd64Le / "64" / AtomicU64 : u64
+            / fetch_add / |a, b| a.wrapping_add(b);
+        fn xatomic_rmw_sub8 = XAtomicRmwSub8 / "8" / AtomicU8 : u8
+            / fetch_sub / |a, b| a.wrapping_sub(b);
+        fn xatomic_rmw_sub16le = XAtomicRmwSub16Le / "16" / AtomicU16 : u16
+            / fetch_sub / |a, b| a.wrapping_sub(b);
+        fn xatomic_rmw_sub32le = XAtomicRmwSub32Le / "32" / AtomicU32 : u32
+            / fetch_sub / |a, b| a.wrapping_sub(b);
+        fn xatomic_rmw_sub64le = XAtomicRmwSub64Le / "64" / AtomicU64 : u64
+            / fetch_sub / |a, b| a.wrapping_sub(b);
+        fn xatomic_rmw_and8 = XAtomicRmwAnd8 / "8" / AtomicU8 : u8
+            / fetch_and / |a, b| a & b;
+        fn xatomic_rmw_and16le = XAtomicRmwAnd16Le / "16" / AtomicU16 : u16
+            / fetch_and / |a, b| a & b;
+        fn xatomic_rmw_and32le = XAtomicRmwAnd32Le / "32" / AtomicU32 : u32
+            / fetch_and / |a, b| a & b;
+        fn xatomic_rmw_and64le = XAtomicRmwAnd64Le / "64" / AtomicU64 : u64
+            / fetch_and / |a, b| a & b;
+        fn xatomic_rmw_nand8 = XAtomicRmwNand8 / "8" / AtomicU8 : u8
+            / fetch_nand / |a, b| !(a & b);
+        fn xatomic_rmw_nand16le = XAtomicRmwNand16Le / "16" / AtomicU16 : u16
+            / fetch_nand / |a, b| !(a & b);
+        fn xatomic_rmw_nand32le = XAtomicRmwNand32Le / "32" / AtomicU32 : u32
+            / fetch_nand / |a, b| !(a & b);
+        fn xatomic_rmw_nand64le = XAtomicRmwNand64Le / "64" / AtomicU64 : u64
+            / fetch_nand / |a, b| !(a & b);
+        fn xatomic_rmw_or8 = XAtomicRmwOr8 / "8" / AtomicU8 : u8
+            / fetch_or / |a, b| a | b;
+        fn xatomic_rmw_or16le = XAtomicRmwOr16Le / "16" / AtomicU16 : u16
+            / fetch_or / |a, b| a | b;
+        fn xatomic_rmw_or32le = XAtomicRmwOr32Le / "32" / AtomicU32 : u32
+            / fetch_or / |a, b| a | b;
+        fn xatomic_rmw_or64le = XAtomicRmwOr64Le / "64" / AtomicU64 : u64
+            / fetch_or / |a, b| a | b;
+        fn xatomic_rmw_xor8 = XAtomicRmwXor8 / "8" / AtomicU8 : u8
+            / fetch_xor / |a, b| a ^ b;
+        fn xatomic_rmw_xor16le = XAtomicRmwXor16Le / "16" / AtomicU16 : u16
+            / fetch_xor / |a, b| a ^ b;
+        fn xatomic_rmw_xor32le = XAtomicRmwXor32Le / "32" / AtomicU32 : u32
+            / fetch_xor / |a, b| a ^ b;
+        fn xatomic_rmw_xor64le = XAtomicRmwXor64Le / "64" / AtomicU64 : u64
+            / fetch_xor / |a, b| a ^ b;
+        fn xatomic_rmw_xchg8 = XAtomicRmwXchg8 / "8" / AtomicU8 : u8
+            / swap / |_, b| b;
+        fn xatomic_rmw_xchg16le = XAtomicRmwXchg16Le / "16" / AtomicU16 : u16
+            / swap / |_, b| b;
+        fn xatomic_rmw_xchg32le = XAtomicRmwXchg32Le / "32" / AtomicU32 : u32
+            / swap / |_, b| b;
+        fn xatomic_rmw_xchg64le = XAtomicRmwXchg64Le / "64" / AtomicU64 : u64
+            / swap / |_, b| b;
+        fn xatomic_rmw_umin8 = XAtomicRmwUmin8 / "8" / AtomicU8 : u8
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_umin16le = XAtomicRmwUmin16Le / "16" / AtomicU16 : u16
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_umin32le = XAtomicRmwUmin32Le / "32" / AtomicU32 : u32
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_umin64le = XAtomicRmwUmin64Le / "64" / AtomicU64 : u64
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_umax8 = XAtomicRmwUmax8 / "8" / AtomicU8 : u8
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_umax16le = XAtomicRmwUmax16Le / "16" / AtomicU16 : u16
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_umax32le = XAtomicRmwUmax32Le / "32" / AtomicU32 : u32
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_umax64le = XAtomicRmwUmax64Le / "64" / AtomicU64 : u64
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_smin8 = XAtomicRmwSmin8 / "8" / AtomicI8 : i8
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_smin16le = XAtomicRmwSmin16Le / "16" / AtomicI16 : i16
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_smin32le = XAtomicRmwSmin32Le / "32" / AtomicI32 : i32
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_smin64le = XAtomicRmwSmin64Le / "64" / AtomicI64 : i64
+            / fetch_min / |a, b| a.min(b);
+        fn xatomic_rmw_smax8 = XAtomicRmwSmax8 / "8" / AtomicI8 : i8
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_smax16le = XAtomicRmwSmax16Le / "16" / AtomicI16 : i16
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_smax32le = XAtomicRmwSmax32Le / "32" / AtomicI32 : i32
+            / fetch_max / |a, b| a.max(b);
+        fn xatomic_rmw_smax64le = XAtomicRmwSmax64Le / "64" / AtomicI64 : i64
+            / fetch_max / |a, b| a.max(b);
+    }
+
+    xatomic_cas! {
+        fn xatomic_cas8 = XAtomicCas8 / "8" / AtomicU8 : u8;
+        fn xatomic_cas16le = XAtomicCas16Le / "16" / AtomicU16 : u16;
+        fn xatomic_cas32le = XAtomicCas32Le / "32" / AtomicU32 : u32;
+        fn xatomic_cas64le = XAtomicCas64Le / "64" / AtomicU64 : u64;
+    }
 }
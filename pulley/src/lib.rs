@@ -82,10 +82,18 @@ extern crate alloc;
 ///
 /// More guidelines might get added here over time, and if you have any
 /// questions feel free to raise them and we can try to add them here as well!
+///
+/// Superinstructions listed in `pulley/macros/superinstructions.txt` are
+/// appended to the ops defined here, see [`for_each_super_op`] for more
+/// information.
 #[macro_export]
 macro_rules! for_each_op {
     ( $macro:ident ) => {
-        $macro! {
+        $crate::for_each_op!(@ops $macro);
+    };
+    ( @$mode:ident $macro:ident ) => {
+        $crate::__superinstructions! {
+            $mode $macro;
             /// Transfer control the address in the `lr` register.
             ret = Ret;
 
@@ -615,6 +623,39 @@ macro_rules! for_each_op {
     };
 }
 
+/// Calls the given macro with each superinstruction.
+///
+/// Superinstructions are regular ops (also listed by [`for_each_op`]) which
+/// execute a sequence of two other ops, saving a dispatch in the interpreter.
+/// The sequences are listed in `pulley/macros/superinstructions.txt`, which
+/// can be generated from execution profiles with the `superinstructions`
+/// example.
+///
+/// The operands of a superinstruction are the operands of its components in
+/// order, so its encoding is the encoding of the sequence with the opcode of
+/// the second op removed. Operands are renamed by appending the 1-based
+/// index of their component, e.g. `dst_1`.
+///
+/// The given macro is invoked with a list of
+///
+/// ```text
+/// snake_name = Name {
+///     component_snake_name = ComponentName { field: Type, ... };
+///     ...
+/// };
+/// ```
+///
+/// where `field` is the name of the operand in the superinstruction.
+#[macro_export]
+macro_rules! for_each_super_op {
+    ( $macro:ident ) => {
+        $crate::for_each_op!(@super_ops $macro);
+    };
+}
+
+#[doc(hidden)]
+pub use pulley_macros::superinstructions as __superinstructions;
+
 /// Calls the given macro with each extended opcode.
 #[macro_export]
 macro_rules! for_each_extended_op {
@@ -961,6 +1002,8 @@ macro_rules! for_each_extended_op {
             /// `dst = satruating_add(src1, src2)`
             vaddu16x8_sat = VAddU16x8Sat { operands: BinaryOperands<VReg> };
 
+            /// `dst = [src1[0] + src1[1], ..., src2[14] + src2[15]]`
+            vaddpairwisei8x16_s = VAddpairwiseI8x16S { operands: BinaryOperands<VReg> };
             /// `dst = [src1[0] + src1[1], ..., src2[6] + src2[7]]`
             vaddpairwisei16x8_s = VAddpairwiseI16x8S { operands: BinaryOperands<VReg> };
             /// `dst = [src1[0] + src1[1], ..., src2[2] + src2[3]]`
@@ -1128,6 +1171,12 @@ macro_rules! for_each_extended_op {
             /// Narrows the two 64x2 vectors, assuming all input lanes are
             /// signed, to half the width. Narrowing is unsigned and saturating.
             vnarrow64x2_u = Vnarrow64x2U { operands: BinaryOperands<VReg> };
+            /// Narrows the two 16x8 vectors, assuming all input lanes are
+            /// unsigned, to half the width. Narrowing is unsigned and saturating.
+            vunarrow16x8_u = Vunarrow16x8U { operands: BinaryOperands<VReg> };
+            /// Narrows the two 32x4 vectors, assuming all input lanes are
+            /// unsigned, to half the width. Narrowing is unsigned and saturating.
+            vunarrow32x4_u = Vunarrow32x4U { operands: BinaryOperands<VReg> };
             /// Narrows the two 64x2 vectors, assuming all input lanes are
             /// unsigned, to half the width. Narrowing is unsigned and saturating.
             vunarrow64x2_u = Vunarrow64x2U { operands: BinaryOperands<VReg> };
@@ -1171,8 +1220,31 @@ macro_rules! for_each_extended_op {
             /// `dst = signed_saturate(src1 * src2 + (1 << (Q - 1)) >> Q)`
             vqmulrsi16x8 = VQmulrsI16x8 { operands: BinaryOperands<VReg> };
 
+            /// `dst = high half of src1 * src2` (signed)
+            vmulhi8x16_s = VMulHi8x16S { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (unsigned)
+            vmulhi8x16_u = VMulHi8x16U { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (signed)
+            vmulhi16x8_s = VMulHi16x8S { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (unsigned)
+            vmulhi16x8_u = VMulHi16x8U { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (signed)
+            vmulhi32x4_s = VMulHi32x4S { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (unsigned)
+            vmulhi32x4_u = VMulHi32x4U { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (signed)
+            vmulhi64x2_s = VMulHi64x2S { operands: BinaryOperands<VReg> };
+            /// `dst = high half of src1 * src2` (unsigned)
+            vmulhi64x2_u = VMulHi64x2U { operands: BinaryOperands<VReg> };
+
             /// `dst = count_ones(src)`
             vpopcnt8x16 = VPopcnt8x16 { dst: VReg, src: VReg };
+            /// `dst = count_ones(src)`
+            vpopcnt16x8 = VPopcnt16x8 { dst: VReg, src: VReg };
+            /// `dst = count_ones(src)`
+            vpopcnt32x4 = VPopcnt32x4 { dst: VReg, src: VReg };
+            /// `dst = count_ones(src)`
+            vpopcnt64x2 = VPopcnt64x2 { dst: VReg, src: VReg };
 
             /// `low32(dst) = zext(src[lane])`
             xextractv8x16 = XExtractV8x16 { dst: XReg, src: VReg, lane: u8 };
@@ -1286,6 +1358,15 @@ macro_rules! for_each_extended_op {
             /// `dst = max(src1, src2)` (unsigned)
             vmax32x4_u = Vmax32x4U { operands: BinaryOperands<VReg> };
 
+            /// `dst = min(src1, src2)` (signed)
+            vmin64x2_s = Vmin64x2S { operands: BinaryOperands<VReg> };
+            /// `dst = min(src1, src2)` (unsigned)
+            vmin64x2_u = Vmin64x2U { operands: BinaryOperands<VReg> };
+            /// `dst = max(src1, src2)` (signed)
+            vmax64x2_s = Vmax64x2S { operands: BinaryOperands<VReg> };
+            /// `dst = max(src1, src2)` (unsigned)
+            vmax64x2_u = Vmax64x2U { operands: BinaryOperands<VReg> };
+
             /// `dst = |src|`
             vabs8x16 = Vabs8x16 { dst: VReg, src: VReg };
             /// `dst = |src|`
@@ -1299,6 +1380,10 @@ macro_rules! for_each_extended_op {
             vabsf32x4 = Vabsf32x4 { dst: VReg, src: VReg };
             /// `dst = |src|`
             vabsf64x2 = Vabsf64x2 { dst: VReg, src: VReg };
+            /// `dst = copysign(src1, src2)`
+            vcopysignf32x4 = VCopysignF32x4 { operands: BinaryOperands<VReg> };
+            /// `dst = copysign(src1, src2)`
+            vcopysignf64x2 = VCopysignF64x2 { operands: BinaryOperands<VReg> };
             /// `dst = ieee_maximum(src1, src2)`
             vmaximumf32x4 = Vmaximumf32x4 { operands: BinaryOperands<VReg> };
             /// `dst = ieee_maximum(src1, src2)`
@@ -1376,6 +1461,155 @@ macro_rules! for_each_extended_op {
                 lhs: XReg,
                 rhs: XReg
             };
+
+            // Atomic memory operations on X-registers. These are all
+            // sequentially consistent, take the address to operate on in a
+            // register, and trap if that address is null or not naturally
+            // aligned.
+
+            /// Sequentially consistent memory fence.
+            fence = Fence;
+
+            /// `low32(dst) = zext_8_32(atomic_load(*addr))`
+            xatomic_load8_u32 = XAtomicLoad8U32 { dst: XReg, addr: XReg };
+            /// `low32(dst) = zext_16_32(atomic_load(*addr))`
+            xatomic_load16le_u32 = XAtomicLoad16LeU32 { dst: XReg, addr: XReg };
+            /// `low32(dst) = atomic_load(*addr)`
+            xatomic_load32le = XAtomicLoad32Le { dst: XReg, addr: XReg };
+            /// `dst = atomic_load(*addr)`
+            xatomic_load64le = XAtomicLoad64Le { dst: XReg, addr: XReg };
+            /// `atomic_store(*addr, low8(src))`
+            xatomic_store8 = XAtomicStore8 { addr: XReg, src: XReg };
+            /// `atomic_store(*addr, low16(src))`
+            xatomic_store16le = XAtomicStore16Le { addr: XReg, src: XReg };
+            /// `atomic_store(*addr, low32(src))`
+            xatomic_store32le = XAtomicStore32Le { addr: XReg, src: XReg };
+            /// `atomic_store(*addr, src)`
+            xatomic_store64le = XAtomicStore64Le { addr: XReg, src: XReg };
+
+            // Atomic read-modify-write operations. The previous value in
+            // memory is written to `dst` and the update happens atomically.
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = *addr + low8(src)`
+            xatomic_rmw_add8 = XAtomicRmwAdd8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = *addr + low16(src)`
+            xatomic_rmw_add16le = XAtomicRmwAdd16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = *addr + low32(src)`
+            xatomic_rmw_add32le = XAtomicRmwAdd32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = *addr + src`
+            xatomic_rmw_add64le = XAtomicRmwAdd64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = *addr - low8(src)`
+            xatomic_rmw_sub8 = XAtomicRmwSub8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = *addr - low16(src)`
+            xatomic_rmw_sub16le = XAtomicRmwSub16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = *addr - low32(src)`
+            xatomic_rmw_sub32le = XAtomicRmwSub32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = *addr - src`
+            xatomic_rmw_sub64le = XAtomicRmwSub64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = *addr & low8(src)`
+            xatomic_rmw_and8 = XAtomicRmwAnd8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = *addr & low16(src)`
+            xatomic_rmw_and16le = XAtomicRmwAnd16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = *addr & low32(src)`
+            xatomic_rmw_and32le = XAtomicRmwAnd32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = *addr & src`
+            xatomic_rmw_and64le = XAtomicRmwAnd64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = !(*addr & low8(src))`
+            xatomic_rmw_nand8 = XAtomicRmwNand8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = !(*addr & low16(src))`
+            xatomic_rmw_nand16le = XAtomicRmwNand16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = !(*addr & low32(src))`
+            xatomic_rmw_nand32le = XAtomicRmwNand32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = !(*addr & src)`
+            xatomic_rmw_nand64le = XAtomicRmwNand64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = *addr | low8(src)`
+            xatomic_rmw_or8 = XAtomicRmwOr8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = *addr | low16(src)`
+            xatomic_rmw_or16le = XAtomicRmwOr16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = *addr | low32(src)`
+            xatomic_rmw_or32le = XAtomicRmwOr32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = *addr | src`
+            xatomic_rmw_or64le = XAtomicRmwOr64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = *addr ^ low8(src)`
+            xatomic_rmw_xor8 = XAtomicRmwXor8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = *addr ^ low16(src)`
+            xatomic_rmw_xor16le = XAtomicRmwXor16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = *addr ^ low32(src)`
+            xatomic_rmw_xor32le = XAtomicRmwXor32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = *addr ^ src`
+            xatomic_rmw_xor64le = XAtomicRmwXor64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = low8(src)`
+            xatomic_rmw_xchg8 = XAtomicRmwXchg8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = low16(src)`
+            xatomic_rmw_xchg16le = XAtomicRmwXchg16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = low32(src)`
+            xatomic_rmw_xchg32le = XAtomicRmwXchg32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = src`
+            xatomic_rmw_xchg64le = XAtomicRmwXchg64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = min(*addr, low8(src))` (unsigned)
+            xatomic_rmw_umin8 = XAtomicRmwUmin8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = min(*addr, low16(src))` (unsigned)
+            xatomic_rmw_umin16le = XAtomicRmwUmin16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = min(*addr, low32(src))` (unsigned)
+            xatomic_rmw_umin32le = XAtomicRmwUmin32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = min(*addr, src)` (unsigned)
+            xatomic_rmw_umin64le = XAtomicRmwUmin64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = max(*addr, low8(src))` (unsigned)
+            xatomic_rmw_umax8 = XAtomicRmwUmax8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = max(*addr, low16(src))` (unsigned)
+            xatomic_rmw_umax16le = XAtomicRmwUmax16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = max(*addr, low32(src))` (unsigned)
+            xatomic_rmw_umax32le = XAtomicRmwUmax32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = max(*addr, src)` (unsigned)
+            xatomic_rmw_umax64le = XAtomicRmwUmax64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = min(*addr, low8(src))` (signed)
+            xatomic_rmw_smin8 = XAtomicRmwSmin8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = min(*addr, low16(src))` (signed)
+            xatomic_rmw_smin16le = XAtomicRmwSmin16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = min(*addr, low32(src))` (signed)
+            xatomic_rmw_smin32le = XAtomicRmwSmin32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = min(*addr, src)` (signed)
+            xatomic_rmw_smin64le = XAtomicRmwSmin64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); *addr = max(*addr, low8(src))` (signed)
+            xatomic_rmw_smax8 = XAtomicRmwSmax8 { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = zext_16_32(*addr); *addr = max(*addr, low16(src))` (signed)
+            xatomic_rmw_smax16le = XAtomicRmwSmax16Le { dst: XReg, addr: XReg, src: XReg };
+            /// `low32(dst) = *addr; *addr = max(*addr, low32(src))` (signed)
+            xatomic_rmw_smax32le = XAtomicRmwSmax32Le { dst: XReg, addr: XReg, src: XReg };
+            /// `dst = *addr; *addr = max(*addr, src)` (signed)
+            xatomic_rmw_smax64le = XAtomicRmwSmax64Le { dst: XReg, addr: XReg, src: XReg };
+
+            /// `low32(dst) = zext_8_32(*addr); if low32(dst) == zext_8_32(expected) { *addr = low8(replacement) }`
+            xatomic_cas8 = XAtomicCas8 { dst: XReg, addr: XReg, expected: XReg, replacement: XReg };
+            /// `low32(dst) = zext_16_32(*addr); if low32(dst) == zext_16_32(expected) { *addr = low16(replacement) }`
+            xatomic_cas16le = XAtomicCas16Le { dst: XReg, addr: XReg, expected: XReg, replacement: XReg };
+            /// `low32(dst) = *addr; if low32(dst) == low32(expected) { *addr = low32(replacement) }`
+            xatomic_cas32le = XAtomicCas32Le { dst: XReg, addr: XReg, expected: XReg, replacement: XReg };
+            /// `dst = *addr; if dst == expected { *addr = replacement }`
+            xatomic_cas64le = XAtomicCas64Le { dst: XReg, addr: XReg, expected: XReg, replacement: XReg };
+
+            /// Switches execution to another stack.
+            ///
+            /// Both operands point to a "control context" made up of three
+            /// pointers: a stack pointer, a frame pointer, and a program
+            /// counter, in that order. The `sp`, `fp`, and `pc` to switch to
+            /// are loaded from `load_context_ptr`, and then the current `sp`,
+            /// `fp`, and the `pc` of the next instruction are stored to
+            /// `store_context_ptr`. The two pointers may be equal.
+            ///
+            /// No other registers are modified, so by convention the payload of
+            /// the switch is handed over in `x0`.
+            stack_switch = StackSwitch { store_context_ptr: XReg, load_context_ptr: XReg };
         }
     };
 }
@@ -55,6 +55,31 @@ impl Opcode {
     }
 }
 
+macro_rules! define_superinstruction_lookup {
+    (
+        $(
+            $snake_name:ident = $name:ident {
+                $( $component_snake_name:ident = $component:ident { $( $field:ident : $field_ty:ty ),* }; )*
+            };
+        )*
+    ) => {
+        impl Opcode {
+            /// Returns the opcode of the superinstruction which executes
+            /// `ops` in sequence, if there is one.
+            ///
+            /// See [`for_each_super_op`](crate::for_each_super_op) for more
+            /// information on superinstructions.
+            pub fn superinstruction(ops: &[Opcode]) -> Option<Opcode> {
+                match ops {
+                    $( [$(Opcode::$component),*] => Some(Opcode::$name), )*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+for_each_super_op!(define_superinstruction_lookup);
+
 macro_rules! define_extended_opcode {
     (
         $(
@@ -1,6 +1,6 @@
 //! Interpreter tests.
 
-use interp::Val;
+use interp::{Val, XRegVal};
 use pulley_interpreter::{
     interp::{DoneReason, Vm},
     *,
@@ -816,3 +816,226 @@ fn trap() {
     // `dst` should not have been written to the second time.
     assert_eq!(vm.state()[dst].get_u32(), 1);
 }
+
+#[test]
+fn superinstruction_matches_components() {
+    let first = Xadd32 {
+        operands: BinaryOperands {
+            dst: x(0),
+            src1: x(1),
+            src2: x(2),
+        },
+    };
+    let second = Xadd32 {
+        operands: BinaryOperands {
+            dst: x(0),
+            src1: x(0),
+            src2: x(1),
+        },
+    };
+    let fused = Xadd32Xadd32 {
+        operands_1: first.operands,
+        operands_2: second.operands,
+    };
+    assert_eq!(
+        opcode::Opcode::superinstruction(&[opcode::Opcode::Xadd32, opcode::Opcode::Xadd32]),
+        Some(opcode::Opcode::Xadd32Xadd32),
+    );
+
+    // The encoding of a superinstruction is that of its components without
+    // the opcode of the second one.
+    let mut expected = encoded(&[first.into(), second.into()]);
+    expected.remove(<Xadd32 as encode::Encode>::WIDTH.into());
+    expected[0] = opcode::Opcode::Xadd32Xadd32 as u8;
+    assert_eq!(encoded(&[fused.into()]), expected);
+
+    for ops in [vec![first.into(), second.into()], vec![fused.into()]] {
+        let mut vm = Vm::new();
+        vm.state_mut()[x(1)] = XRegVal::new_u32(10);
+        vm.state_mut()[x(2)] = XRegVal::new_u32(u32::MAX);
+        unsafe {
+            run(&mut vm, &[ops, vec![Op::Ret(Ret {})]].concat()).expect("should not trap");
+        }
+        assert_eq!(vm.state()[x(0)].get_u32(), 19);
+    }
+}
+
+#[test]
+fn superinstruction_trap() {
+    let mut vm = Vm::new();
+    let heap = [0u8; 4];
+    vm.state_mut()[x(1)] = XRegVal::new_ptr(heap.as_ptr().cast_mut());
+    vm.state_mut()[x(2)] = XRegVal::new_u64(heap.len() as u64);
+    vm.state_mut()[x(3)] = XRegVal::new_u32(4);
+    vm.state_mut()[x(4)] = XRegVal::new_u32(1);
+
+    let ops = [
+        Op::Xmov(Xmov {
+            dst: x(4),
+            src: x(3),
+        }),
+        Op::XStore8G32Xadd32(XStore8G32Xadd32 {
+            addr_1: AddrG32 {
+                host_heap_base: x(1),
+                host_heap_bound: x(2),
+                wasm_addr: x(3),
+                offset: 0,
+            },
+            src_1: x(0),
+            operands_2: BinaryOperands {
+                dst: x(4),
+                src1: x(4),
+                src2: x(4),
+            },
+        }),
+        Op::Ret(Ret {}),
+    ];
+    let code = encoded(&ops);
+    let pc = unsafe {
+        match vm.call(NonNull::from(&code[..]).cast(), &[], []) {
+            DoneReason::Trap { pc, .. } => pc,
+            _ => panic!("should trap"),
+        }
+    };
+
+    // The trap is reported at the start of the superinstruction, and its
+    // second component has not executed.
+    let offset = <Xmov as encode::Encode>::WIDTH;
+    assert_eq!(
+        pc.as_ptr().cast_const(),
+        code[usize::from(offset)..].as_ptr()
+    );
+    assert_eq!(vm.state()[x(4)].get_u32(), 4);
+}
+
+#[test]
+fn xatomic_rmw_add32le() {
+    let a = UnsafeCell::new([11u32.to_le(), 22u32.to_le()]);
+
+    for (expected, addr, src) in [
+        (11, a.get().cast::<u32>(), 1),
+        (12, a.get().cast::<u32>(), u32::MAX),
+        (22, unsafe { a.get().cast::<u32>().add(1) }, 2),
+    ] {
+        let init = 0x1234567812345678u64;
+        let expected = init & !u64::from(u32::MAX) | expected;
+        unsafe {
+            assert_one(
+                [
+                    (x(0), Val::from(init)),
+                    (x(1), Val::from(addr)),
+                    (x(2), Val::from(u64::from(src))),
+                ],
+                XAtomicRmwAdd32Le {
+                    dst: x(0),
+                    addr: x(1),
+                    src: x(2),
+                },
+                x(0),
+                expected,
+            );
+        }
+    }
+
+    let [a, b] = a.into_inner().map(u32::from_le);
+    assert_eq!(a, 11);
+    assert_eq!(b, 24);
+}
+
+#[test]
+fn xatomic_cas64le() {
+    let a = UnsafeCell::new(11u64.to_le());
+
+    for (expected, replacement, old) in [(12, 33, 11), (11, 44, 11), (11, 55, 44)] {
+        unsafe {
+            assert_one(
+                [
+                    (x(0), Val::from(a.get())),
+                    (x(1), Val::from(expected)),
+                    (x(2), Val::from(replacement)),
+                ],
+                XAtomicCas64Le {
+                    dst: x(3),
+                    addr: x(0),
+                    expected: x(1),
+                    replacement: x(2),
+                },
+                x(3),
+                old,
+            );
+        }
+    }
+
+    assert_eq!(u64::from_le(a.into_inner()), 44);
+}
+
+#[test]
+fn xatomic_misaligned_traps() {
+    let mut vm = Vm::new();
+    let a = UnsafeCell::new([0u64; 2]);
+    vm.state_mut()[x(0)] = interp::XRegVal::new_ptr(unsafe { a.get().cast::<u8>().add(4) });
+
+    unsafe {
+        run(
+            &mut vm,
+            &[
+                Op::ExtendedOp(ExtendedOp::XAtomicLoad64Le(XAtomicLoad64Le {
+                    dst: x(1),
+                    addr: x(0),
+                })),
+                Op::Ret(Ret {}),
+            ],
+        )
+        .unwrap_err();
+    }
+}
+
+#[test]
+fn stack_switch_round_trip() {
+    // A small foreign stack to switch to, along with bytecode to run on it
+    // which pushes a frame and then switches back with a payload in `x0`.
+    let mut stack = [0u128; 8];
+    let stack_top = stack.as_mut_ptr_range().end.cast::<u8>();
+    let other = encoded(&[
+        Op::Xconst8(Xconst8 { dst: x(0), imm: 42 }),
+        Op::Xmov(Xmov {
+            dst: x(3),
+            src: XReg::sp,
+        }),
+        Op::PushFrame(PushFrame {}),
+        Op::PopFrame(PopFrame {}),
+        Op::ExtendedOp(ExtendedOp::StackSwitch(StackSwitch {
+            store_context_ptr: x(2),
+            load_context_ptr: x(1),
+        })),
+    ]);
+
+    let main_context = UnsafeCell::new([0usize; 3]);
+    let other_context = UnsafeCell::new([stack_top as usize, 0, other.as_ptr() as usize]);
+
+    let mut vm = Vm::new();
+    vm.state_mut()[x(1)] = interp::XRegVal::new_ptr(main_context.get());
+    vm.state_mut()[x(2)] = interp::XRegVal::new_ptr(other_context.get());
+
+    unsafe {
+        run(
+            &mut vm,
+            &[
+                Op::ExtendedOp(ExtendedOp::StackSwitch(StackSwitch {
+                    store_context_ptr: x(1),
+                    load_context_ptr: x(2),
+                })),
+                Op::Ret(Ret {}),
+            ],
+        )
+        .expect("should not trap");
+    }
+
+    assert_eq!(vm.state_mut()[x(0)].get_u64(), 42);
+    assert_eq!(vm.state_mut()[x(3)].get_ptr::<u8>(), stack_top);
+    // The foreign stack's context was updated to resume just after the
+    // switch back to the main stack.
+    let [sp, _fp, pc] = other_context.into_inner();
+    assert_eq!(sp, stack_top as usize);
+    assert_eq!(pc, other.as_ptr() as usize + other.len());
+}
@@ -44,7 +44,10 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasmtime-internal-jit-icache-coherence",
     // Wasmtime unwinder, used by both `cranelift-jit` (optionally) and filetests, and by Wasmtime.
     "wasmtime-internal-unwinder",
-    // Cranelift crates that use Wasmtime unwinder.
+    // Wasmtime JIT debug interface, used by both `cranelift-jit` (optionally) and Wasmtime.
+    "wasmtime-internal-versioned-export-macros",
+    "wasmtime-internal-jit-debug",
+    // Cranelift crates that use Wasmtime unwinder and JIT debug interface.
     "cranelift-jit",
     "cranelift",
     // wiggle
@@ -54,12 +57,10 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "winch",
     // wasmtime
     "wasmtime-internal-asm-macros",
-    "wasmtime-internal-versioned-export-macros",
     "wasmtime-internal-slab",
     "wasmtime-internal-component-util",
     "wasmtime-internal-wit-bindgen",
     "wasmtime-internal-component-macro",
-    "wasmtime-internal-jit-debug",
     "wasmtime-internal-fiber",
     "wasmtime-environ",
     "wasmtime-internal-wmemcheck",
@@ -70,6 +70,10 @@ enum Subcommand {
     #[cfg(feature = "explore")]
     Explore(wasmtime_cli::commands::ExploreCommand),
 
+    /// Runs WebAssembly programs under a Debug Adapter Protocol server.
+    #[cfg(feature = "debug")]
+    Debug(wasmtime_cli::commands::DebugCommand),
+
     /// Serves requests from a wasi-http proxy component.
     #[cfg(feature = "serve")]
     Serve(wasmtime_cli::commands::ServeCommand),
@@ -112,6 +116,9 @@ impl Wasmtime {
             #[cfg(feature = "explore")]
             Subcommand::Explore(c) => c.execute(),
 
+            #[cfg(feature = "debug")]
+            Subcommand::Debug(c) => c.execute(),
+
             #[cfg(feature = "serve")]
             Subcommand::Serve(c) => c.execute(),
 
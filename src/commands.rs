@@ -5,6 +5,11 @@ mod run;
 #[cfg(feature = "run")]
 pub use self::run::*;
 
+#[cfg(feature = "debug")]
+mod debug;
+#[cfg(feature = "debug")]
+pub use self::debug::*;
+
 #[cfg(feature = "serve")]
 mod serve;
 #[cfg(feature = "serve")]
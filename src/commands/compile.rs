@@ -5,6 +5,8 @@ use clap::Parser;
 use std::fs;
 use std::path::PathBuf;
 use wasmtime::{CodeBuilder, CodeHint, Engine};
+#[cfg(feature = "run")]
+use wasmtime::{Linker, Module, Store};
 use wasmtime_cli_flags::CommonOptions;
 
 const AFTER_HELP: &str =
@@ -22,7 +24,11 @@ const AFTER_HELP: &str =
         \n\
         Compiling for a specific platform (Linux) and CPU preset (Skylake):\n\
         \n  \
-        wasmtime compile --target x86_64-unknown-linux -Ccranelift-skylake foo.wasm\n";
+        wasmtime compile --target x86_64-unknown-linux -Ccranelift-skylake foo.wasm\n\
+        \n\
+        Running an initialization function and compiling the resulting snapshot:\n\
+        \n  \
+        wasmtime compile --init-func wizer.initialize foo.wasm\n";
 
 /// Compiles a WebAssembly module.
 #[derive(Parser)]
@@ -43,6 +49,11 @@ pub struct CompileCommand {
     #[arg(long = "emit-clif", value_name = "PATH")]
     pub emit_clif: Option<PathBuf>,
 
+    /// Run the named export with WASI before compiling, and compile a
+    /// snapshot of the state it leaves behind instead of the original.
+    #[arg(long = "init-func", value_name = "NAME")]
+    pub init_func: Option<String>,
+
     /// The path of the WebAssembly to compile
     #[arg(index = 1, value_name = "MODULE")]
     pub module: PathBuf,
@@ -55,7 +66,7 @@ impl CompileCommand {
 
         let mut config = self.common.config(None)?;
 
-        if let Some(path) = self.emit_clif {
+        if let Some(path) = self.emit_clif.take() {
             if !path.exists() {
                 std::fs::create_dir(&path)?;
             }
@@ -80,7 +91,10 @@ impl CompileCommand {
         }
 
         let mut code = CodeBuilder::new(&engine);
-        code.wasm_binary_or_text_file(&self.module)?;
+        match self.pre_initialize()? {
+            Some(wasm) => code.wasm_binary(wasm, Some(&self.module))?,
+            None => code.wasm_binary_or_text_file(&self.module)?,
+        };
 
         let output = self.output.take().unwrap_or_else(|| {
             let mut output: PathBuf = self.module.file_name().unwrap().into();
@@ -102,6 +116,62 @@ impl CompileCommand {
 
         Ok(())
     }
+
+    /// Runs the `--init-func` export, if any, and returns the pre-initialized
+    /// WebAssembly to compile in place of the input.
+    #[cfg(feature = "run")]
+    fn pre_initialize(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some(init_func) = &self.init_func else {
+            return Ok(None);
+        };
+
+        // The initializer runs on this host, even when compiling for another
+        // target.
+        let target = self.common.target.take();
+        let config = self.common.config(None);
+        self.common.target = target;
+        let engine = Engine::new(&config?)?;
+
+        let wasm = fs::read(&self.module)
+            .with_context(|| format!("failed to read input: {}", self.module.display()))?;
+        let mut code = CodeBuilder::new(&engine);
+        code.wasm_binary_or_text(&wasm, Some(&self.module))?;
+        let hint = code.hint();
+
+        let mut builder = wasmtime_wasi::p2::WasiCtxBuilder::new();
+        builder
+            .inherit_stdio()
+            .arg(self.module.display().to_string());
+        let mut store = Store::new(&engine, builder.build_p1());
+        let wasm = match hint {
+            #[cfg(feature = "component-model")]
+            Some(CodeHint::Component) => {
+                let mut linker = wasmtime::component::Linker::new(&engine);
+                wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
+                wasmtime::component::Component::pre_initialize(
+                    &mut store, &linker, &wasm, init_func,
+                )?
+            }
+            #[cfg(not(feature = "component-model"))]
+            Some(CodeHint::Component) => {
+                bail!("component model support was disabled at compile time")
+            }
+            Some(CodeHint::Module) | None => {
+                let mut linker = Linker::new(&engine);
+                wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |cx| cx)?;
+                Module::pre_initialize(&mut store, &linker, &wasm, init_func)?
+            }
+        };
+        Ok(Some(wasm))
+    }
+
+    #[cfg(not(feature = "run"))]
+    fn pre_initialize(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.init_func.is_some() {
+            bail!("pre-initialization support was disabled at compile time");
+        }
+        Ok(None)
+    }
 }
 
 #[cfg(all(test, not(miri)))]
@@ -142,6 +212,60 @@ mod test {
         Ok(())
     }
 
+    #[cfg(feature = "run")]
+    #[test]
+    fn test_init_func_compile() -> Result<()> {
+        let (mut input, input_path) = NamedTempFile::new()?.into_parts();
+        input.write_all(
+            r#"
+                (module
+                    (import "wasi_snapshot_preview1" "random_get"
+                        (func $random_get (param i32 i32) (result i32)))
+                    (memory (export "memory") 1)
+                    (global $ready (export "ready") (mut i32) (i32.const 0))
+                    (func (export "wizer.initialize")
+                        (drop (call $random_get (i32.const 0) (i32.const 16)))
+                        (global.set $ready (i32.const 1)))
+                )
+            "#
+            .as_bytes(),
+        )?;
+        drop(input);
+
+        let output_path = NamedTempFile::new()?.into_temp_path();
+
+        let command = CompileCommand::try_parse_from(vec![
+            "compile",
+            "-Dlogging=n",
+            "--init-func",
+            "wizer.initialize",
+            "-o",
+            output_path.to_str().unwrap(),
+            input_path.to_str().unwrap(),
+        ])?;
+
+        command.execute()?;
+
+        let engine = Engine::default();
+        let contents = std::fs::read(output_path)?;
+        let module = unsafe { Module::deserialize(&engine, contents)? };
+        assert!(module.get_export("wizer.initialize").is_none());
+        let mut linker = wasmtime::Linker::new(&engine);
+        linker.func_wrap(
+            "wasi_snapshot_preview1",
+            "random_get",
+            |_: i32, _: i32| -> i32 { unreachable!() },
+        )?;
+        let mut store = Store::new(&engine, ());
+        let instance = linker.instantiate(&mut store, &module)?;
+        let ready = instance.get_global(&mut store, "ready").unwrap();
+        assert_eq!(ready.get(&mut store).unwrap_i32(), 1);
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+        assert!(memory.data(&store)[..16].iter().any(|b| *b != 0));
+
+        Ok(())
+    }
+
     #[cfg(target_arch = "x86_64")]
     #[test]
     fn test_x64_flags_compile() -> Result<()> {
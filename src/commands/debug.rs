@@ -0,0 +1,981 @@
+//! The module that implements the `wasmtime debug` command.
+//!
+//! This is a [Debug Adapter Protocol] server which runs programs the same way
+//! that `wasmtime run` does, but with guest debugging enabled so that editors
+//! can pause, step, and inspect the program at the WebAssembly level.
+//!
+//! Protocol requests are read on the main thread while the program runs on a
+//! thread of its own. Whenever the program is paused that thread blocks within
+//! the store's debug handler, and requests which need access to the paused
+//! program (such as `stackTrace` and `variables`) are forwarded to it. Requests
+//! which must affect a running program, such as `pause` and setting
+//! breakpoints, are delivered through the store's epoch callback.
+//!
+//! [Debug Adapter Protocol]: https://microsoft.github.io/debug-adapter-protocol/
+
+use crate::commands::RunCommand;
+use crate::common::RunTarget;
+use anyhow::{Context as _, Result, anyhow, bail};
+use clap::Parser;
+use serde_derive::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use wasmparser::{KnownCustom, Naming, Parser as WasmParser, Payload, TypeRef};
+use wasmtime::{
+    AsContextMut, DebugAction, DebugFrame, DebugReason, Engine, Module, StoreContextMut,
+    UpdateDeadline, Val, WasmBacktrace,
+};
+
+/// The only thread reported to the client; programs run on a single thread.
+const THREAD_ID: u64 = 1;
+
+/// `variablesReference`s for the scopes of the paused frame.
+const LOCALS_REF: u64 = 1;
+const STACK_REF: u64 = 2;
+const GLOBALS_REF: u64 = 3;
+
+/// Runs WebAssembly programs under a Debug Adapter Protocol server
+#[derive(Parser)]
+pub struct DebugCommand {
+    /// Accept a single debugger connection on this TCP address instead of
+    /// using stdin and stdout.
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<SocketAddr>,
+}
+
+impl DebugCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        let (reader, writer): (Box<dyn Read>, Box<dyn Write + Send>) = match self.listen {
+            Some(addr) => {
+                let listener = TcpListener::bind(addr)
+                    .with_context(|| format!("failed to listen on {addr}"))?;
+                eprintln!("Listening for a debugger on {}", listener.local_addr()?);
+                let (stream, _) = listener.accept()?;
+                (Box::new(stream.try_clone()?), Box::new(stream))
+            }
+            None => (Box::new(std::io::stdin()), take_stdout()?),
+        };
+
+        let mut server = Server {
+            session: Arc::new(Session {
+                writer: Mutex::new(writer),
+                seq: AtomicU64::new(1),
+                paused: AtomicBool::new(false),
+                pause_requested: AtomicBool::new(false),
+                breakpoints: Mutex::new(Breakpoints::default()),
+                engine: Mutex::new(None),
+            }),
+            launch: None,
+            symbols: Arc::new(Symbols::default()),
+            program: None,
+        };
+        let mut reader = BufReader::new(reader);
+        while let Some(request) = read_request(&mut reader)? {
+            if let Err(e) = server.handle(&request) {
+                server.session.fail(&request, &e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Takes over stdout for protocol messages, pointing the original stdout at
+/// stderr so that output from the program can't corrupt the protocol stream.
+#[cfg(unix)]
+fn take_stdout() -> Result<Box<dyn Write + Send>> {
+    use std::os::fd::AsFd;
+
+    let protocol = rustix::io::dup(std::io::stdout().as_fd())?;
+    rustix::stdio::dup2_stdout(std::io::stderr().as_fd())?;
+    Ok(Box::new(std::fs::File::from(protocol)))
+}
+
+#[cfg(not(unix))]
+fn take_stdout() -> Result<Box<dyn Write + Send>> {
+    Ok(Box::new(std::io::stdout()))
+}
+
+#[derive(Clone, Deserialize)]
+struct Request {
+    seq: u64,
+    command: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// Reads the next `Content-Length`-framed request, returning `None` once the
+/// client has disconnected.
+fn read_request(reader: &mut impl BufRead) -> Result<Option<Request>> {
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                len = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let len = len.ok_or_else(|| anyhow!("message is missing a `Content-Length` header"))?;
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(Some(
+        serde_json::from_slice(&body).context("invalid request")?,
+    ))
+}
+
+/// State shared between the thread serving requests and the thread running
+/// the program.
+struct Session {
+    writer: Mutex<Box<dyn Write + Send>>,
+    seq: AtomicU64,
+    /// Whether the program is paused and handling forwarded requests.
+    paused: AtomicBool,
+    /// Set by a `pause` request and cleared once the program has paused.
+    pause_requested: AtomicBool,
+    breakpoints: Mutex<Breakpoints>,
+    /// The engine running the program, once it has been created.
+    engine: Mutex<Option<Engine>>,
+}
+
+impl Session {
+    fn send(&self, mut message: Value) {
+        message["seq"] = self.seq.fetch_add(1, Ordering::SeqCst).into();
+        let body = message.to_string();
+        let mut writer = self.writer.lock().unwrap();
+        let result = write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())
+            .and_then(|()| writer.flush());
+        if let Err(e) = result {
+            log::warn!("failed to send message to the debugger: {e}");
+        }
+    }
+
+    fn respond(&self, request: &Request, body: Value) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "success": true,
+            "command": request.command,
+            "body": body,
+        }));
+    }
+
+    fn fail(&self, request: &Request, error: &anyhow::Error) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "success": false,
+            "command": request.command,
+            "message": format!("{error:#}"),
+        }));
+    }
+
+    fn event(&self, event: &str, body: Value) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    /// Interrupts the running program so that its epoch callback runs.
+    fn interrupt(&self) {
+        if let Some(engine) = &*self.engine.lock().unwrap() {
+            engine.increment_epoch();
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LaunchArguments {
+    program: PathBuf,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    wasmtime_args: Vec<String>,
+    #[serde(default)]
+    stop_on_entry: bool,
+}
+
+/// Serves requests on the main thread.
+struct Server {
+    session: Arc<Session>,
+    launch: Option<(RunCommand, bool)>,
+    symbols: Arc<Symbols>,
+    /// Forwards requests to the program while it is paused.
+    program: Option<mpsc::Sender<Request>>,
+}
+
+impl Server {
+    fn handle(&mut self, request: &Request) -> Result<()> {
+        let session = &*self.session;
+        match request.command.as_str() {
+            "initialize" => session.respond(
+                request,
+                json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsFunctionBreakpoints": true,
+                    "supportsInstructionBreakpoints": true,
+                }),
+            ),
+            "launch" => {
+                if self.launch.is_some() || self.program.is_some() {
+                    bail!("a program has already been launched");
+                }
+                let args: LaunchArguments = serde_json::from_value(request.arguments.clone())
+                    .context("invalid launch arguments")?;
+                let argv = ["run".into()]
+                    .into_iter()
+                    .chain(args.wasmtime_args.into_iter().map(OsString::from))
+                    .chain(["--".into(), args.program.clone().into()])
+                    .chain(args.args.into_iter().map(OsString::from));
+                let mut run = RunCommand::try_parse_from(argv)?;
+                if run.run.profile.is_some() {
+                    bail!("profiling is not supported while debugging");
+                }
+                if run.run.common.wasm.timeout.is_some() {
+                    bail!("timeouts are not supported while debugging");
+                }
+                run.run.common.init_logging()?;
+                self.symbols = Arc::new(Symbols::load(&args.program));
+                self.launch = Some((run, args.stop_on_entry));
+                session.respond(request, json!({}));
+                session.event("initialized", json!({}));
+            }
+            "setBreakpoints" => {
+                let breakpoints = request.arguments["breakpoints"]
+                    .as_array()
+                    .map_or(0, |b| b.len());
+                let unverified = json!({
+                    "verified": false,
+                    "message": "source breakpoints are not supported; \
+                                use function or instruction breakpoints",
+                });
+                session.respond(
+                    request,
+                    json!({ "breakpoints": vec![unverified; breakpoints] }),
+                );
+            }
+            "setFunctionBreakpoints" => {
+                let results = self.set_breakpoints(request, |symbols, bp| {
+                    let name = bp["name"].as_str().unwrap_or("");
+                    symbols.function_entry(name)
+                });
+                session.respond(request, json!({ "breakpoints": results }));
+            }
+            "setInstructionBreakpoints" => {
+                let results = self.set_breakpoints(request, |symbols, bp| {
+                    let reference = bp["instructionReference"].as_str().unwrap_or("");
+                    let offset = bp["offset"].as_i64().unwrap_or(0);
+                    symbols.instruction(reference, offset)
+                });
+                session.respond(request, json!({ "breakpoints": results }));
+            }
+            "setExceptionBreakpoints" => session.respond(request, json!({})),
+            "configurationDone" => {
+                let (run, stop_on_entry) = self
+                    .launch
+                    .take()
+                    .ok_or_else(|| anyhow!("no program has been launched"))?;
+                let (sender, receiver) = mpsc::channel();
+                let program = Program {
+                    session: self.session.clone(),
+                    symbols: self.symbols.clone(),
+                    requests: Arc::new(Mutex::new(receiver)),
+                    stop_on_entry,
+                };
+                self.program = Some(sender);
+                session.respond(request, json!({}));
+                thread::spawn(move || program.run(run));
+            }
+            "threads" => session.respond(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "pause" => {
+                if !session.paused.load(Ordering::SeqCst) {
+                    session.pause_requested.store(true, Ordering::SeqCst);
+                    session.interrupt();
+                }
+                session.respond(request, json!({}));
+            }
+            "disconnect" => {
+                session.respond(request, json!({}));
+                std::process::exit(0);
+            }
+            command => match &self.program {
+                Some(program) if session.paused.load(Ordering::SeqCst) => {
+                    program.send(request.clone())?;
+                }
+                _ => match command {
+                    "continue" | "next" | "stepIn" | "stepOut" | "stackTrace" | "scopes"
+                    | "variables" => bail!("the program is not paused"),
+                    _ => bail!("unsupported request `{command}`"),
+                },
+            },
+        }
+        Ok(())
+    }
+
+    /// Replaces all function or instruction breakpoints, depending on the
+    /// request, returning the protocol's description of each one.
+    fn set_breakpoints(
+        &self,
+        request: &Request,
+        resolve: impl Fn(&Symbols, &Value) -> Result<(u32, u32)>,
+    ) -> Vec<Value> {
+        let mut breakpoints = self.session.breakpoints.lock().unwrap();
+        let functions = request.command == "setFunctionBreakpoints";
+        let mut resolved = Vec::new();
+        let mut results = Vec::new();
+        let requested = request.arguments["breakpoints"].as_array();
+        for bp in requested.into_iter().flatten() {
+            match resolve(&self.symbols, bp) {
+                Ok((func_index, offset)) => {
+                    breakpoints.next_id += 1;
+                    let id = breakpoints.next_id;
+                    resolved.push(Breakpoint {
+                        id,
+                        func_index,
+                        offset,
+                    });
+                    results.push(json!({
+                        "id": id,
+                        "verified": true,
+                        "instructionReference": format!("0x{offset:x}"),
+                    }));
+                }
+                Err(e) => results.push(json!({
+                    "verified": false,
+                    "message": format!("{e:#}"),
+                })),
+            }
+        }
+        if functions {
+            breakpoints.functions = resolved;
+        } else {
+            breakpoints.instructions = resolved;
+        }
+        breakpoints.dirty = true;
+        drop(breakpoints);
+        self.session.interrupt();
+        results
+    }
+}
+
+struct Breakpoint {
+    id: u64,
+    func_index: u32,
+    offset: u32,
+}
+
+/// The breakpoints requested by the client, all within the main module.
+#[derive(Default)]
+struct Breakpoints {
+    functions: Vec<Breakpoint>,
+    instructions: Vec<Breakpoint>,
+    next_id: u64,
+    /// Whether the breakpoints have changed since they were last installed
+    /// in the store.
+    dirty: bool,
+}
+
+impl Breakpoints {
+    fn install<T: 'static>(&mut self, mut store: StoreContextMut<'_, T>, main: Option<&Module>) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        store.clear_breakpoints();
+        let Some(main) = main else { return };
+        for bp in self.functions.iter().chain(&self.instructions) {
+            if let Err(e) = store.add_breakpoint(main, bp.func_index, bp.offset) {
+                log::warn!("failed to add breakpoint: {e:#}");
+            }
+        }
+    }
+
+    /// Finds the breakpoint at the given location, returning the reason to
+    /// report for it along with its id.
+    fn find(&self, func_index: u32, offset: u32) -> Option<(&'static str, u64)> {
+        let at = |bp: &&Breakpoint| bp.func_index == func_index && bp.offset == offset;
+        if let Some(bp) = self.functions.iter().find(at) {
+            Some(("function breakpoint", bp.id))
+        } else {
+            let bp = self.instructions.iter().find(at)?;
+            Some(("instruction breakpoint", bp.id))
+        }
+    }
+}
+
+/// Names and instruction offsets from the program's core wasm module, used to
+/// resolve breakpoints and to name variables.
+///
+/// This is empty if the program is a component or precompiled.
+#[derive(Default)]
+struct Symbols {
+    func_names: HashMap<String, u32>,
+    local_names: HashMap<(u32, u32), String>,
+    global_names: HashMap<u32, String>,
+    /// The offset of every instruction in each defined function.
+    instructions: BTreeMap<u32, Vec<u32>>,
+}
+
+impl Symbols {
+    fn load(path: &Path) -> Symbols {
+        let parse = || -> Result<Symbols> {
+            let wasm = std::fs::read(path)?;
+            #[cfg(feature = "wat")]
+            let wasm = wat::parse_bytes(&wasm)?;
+            Symbols::parse(&wasm)
+        };
+        parse().unwrap_or_else(|e| {
+            log::warn!("failed to read symbols from {path:?}: {e:#}");
+            Symbols::default()
+        })
+    }
+
+    fn parse(wasm: &[u8]) -> Result<Symbols> {
+        let mut symbols = Symbols::default();
+        let mut func_index = 0;
+        for payload in WasmParser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::Version {
+                    encoding: wasmparser::Encoding::Component,
+                    ..
+                } => bail!("symbols are not supported for components"),
+                Payload::ImportSection(imports) => {
+                    for import in imports {
+                        if let TypeRef::Func(_) = import?.ty {
+                            func_index += 1;
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut offsets = Vec::new();
+                    let mut operators = body.get_operators_reader()?;
+                    while !operators.eof() {
+                        let (_, offset) = operators.read_with_offset()?;
+                        offsets.push(u32::try_from(offset)?);
+                    }
+                    symbols.instructions.insert(func_index, offsets);
+                    func_index += 1;
+                }
+                Payload::CustomSection(section) => {
+                    if let KnownCustom::Name(names) = section.as_known() {
+                        symbols.name_section(names)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(symbols)
+    }
+
+    fn name_section(&mut self, names: wasmparser::NameSectionReader<'_>) -> Result<()> {
+        for subsection in names {
+            match subsection? {
+                wasmparser::Name::Function(names) => {
+                    for name in names {
+                        let Naming { index, name } = name?;
+                        self.func_names.insert(name.to_string(), index);
+                    }
+                }
+                wasmparser::Name::Local(reader) => {
+                    for f in reader {
+                        let f = f?;
+                        for name in f.names {
+                            let Naming { index, name } = name?;
+                            self.local_names.insert((f.index, index), name.to_string());
+                        }
+                    }
+                }
+                wasmparser::Name::Global(names) => {
+                    for name in names {
+                        let Naming { index, name } = name?;
+                        self.global_names.insert(index, name.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves a function, by name or index, to the location of its first
+    /// instruction.
+    fn function_entry(&self, name: &str) -> Result<(u32, u32)> {
+        let index = match self.func_names.get(name) {
+            Some(index) => *index,
+            None => name
+                .parse()
+                .map_err(|_| anyhow!("no function named `{name}`"))?,
+        };
+        let offset = self
+            .instructions
+            .get(&index)
+            .and_then(|offsets| offsets.first())
+            .ok_or_else(|| anyhow!("function {index} is not defined in the main module"))?;
+        Ok((index, *offset))
+    }
+
+    /// Resolves an instruction reference, plus a byte offset, to the location
+    /// of an instruction.
+    fn instruction(&self, reference: &str, offset: i64) -> Result<(u32, u32)> {
+        let base = match reference.strip_prefix("0x") {
+            Some(hex) => i64::from_str_radix(hex, 16),
+            None => reference.parse(),
+        }
+        .map_err(|_| anyhow!("invalid instruction reference `{reference}`"))?;
+        let target = u32::try_from(base + offset)
+            .map_err(|_| anyhow!("instruction reference is out of bounds"))?;
+        self.instructions
+            .iter()
+            .find(|(_, offsets)| offsets.binary_search(&target).is_ok())
+            .map(|(func_index, _)| (*func_index, target))
+            .ok_or_else(|| anyhow!("there is no instruction at 0x{target:x}"))
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+/// An in-progress step, along with the number of frames on the stack when it
+/// started.
+#[derive(Copy, Clone)]
+struct Step {
+    resume: Resume,
+    depth: usize,
+}
+
+/// Runs the program on its own thread.
+#[derive(Clone)]
+struct Program {
+    session: Arc<Session>,
+    symbols: Arc<Symbols>,
+    requests: Arc<Mutex<mpsc::Receiver<Request>>>,
+    stop_on_entry: bool,
+}
+
+impl Program {
+    fn run(self, mut run: RunCommand) {
+        let result = run.run_with_hooks(
+            |config| {
+                config.guest_debug(true);
+                config.epoch_interruption(true);
+                Ok(())
+            },
+            |store, main| {
+                let main = match main {
+                    RunTarget::Core(module) => Some(module.clone()),
+                    #[cfg(feature = "component-model")]
+                    RunTarget::Component(_) => None,
+                };
+                *self.session.engine.lock().unwrap() = Some(store.engine().clone());
+                self.session
+                    .breakpoints
+                    .lock()
+                    .unwrap()
+                    .install(store.as_context_mut(), main.as_ref());
+
+                let session = self.session.clone();
+                let module = main.clone();
+                store.set_epoch_deadline(1);
+                store.epoch_deadline_callback(move |mut store| {
+                    if session.pause_requested.load(Ordering::SeqCst) {
+                        store.set_single_step(true);
+                    }
+                    let mut breakpoints = session.breakpoints.lock().unwrap();
+                    breakpoints.install(store, module.as_ref());
+                    Ok(UpdateDeadline::Continue(1))
+                });
+
+                let mut stepper = Stepper {
+                    program: self.clone(),
+                    main,
+                    step: None,
+                    entry: self.stop_on_entry,
+                };
+                store.debug_handler(move |store, frame| Ok(stepper.pause(store, frame)));
+                store.set_single_step(self.stop_on_entry);
+                Ok(())
+            },
+        );
+
+        let (code, error) = match result.and_then(|(_store, result)| result) {
+            Ok(()) => (0, None),
+            Err(e) => {
+                if let Some(exit) = e.downcast_ref::<wasmtime_wasi::I32Exit>() {
+                    (exit.0, None)
+                } else if let Some(exit) = e.downcast_ref::<wasi_common::I32Exit>() {
+                    (exit.0, None)
+                } else {
+                    if let Some(backtrace) = e.downcast_ref::<WasmBacktrace>() {
+                        let text = match e.downcast_ref::<wasmtime::Trap>() {
+                            Some(trap) => trap.to_string(),
+                            None => e.root_cause().to_string(),
+                        };
+                        let stop = Stop {
+                            reason: "exception",
+                            text: Some(text),
+                            hit: None,
+                        };
+                        self.wait::<()>(stop, backtrace, None);
+                    }
+                    (1, Some(e))
+                }
+            }
+        };
+        if let Some(e) = error {
+            self.session.event(
+                "output",
+                json!({ "category": "stderr", "output": format!("Error: {e:?}\n") }),
+            );
+        }
+        self.session.event("exited", json!({ "exitCode": code }));
+        self.session.event("terminated", json!({}));
+    }
+
+    /// Reports that the program stopped and then serves forwarded requests
+    /// until the client resumes it.
+    ///
+    /// `paused` is `None` if the program trapped and can't be inspected any
+    /// further.
+    fn wait<T: 'static>(
+        &self,
+        stop: Stop,
+        backtrace: &WasmBacktrace,
+        mut paused: Option<&mut Paused<'_, '_, T>>,
+    ) -> Resume {
+        let session = &*self.session;
+        session.paused.store(true, Ordering::SeqCst);
+        let mut body = json!({
+            "reason": stop.reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        if let Some(text) = stop.text {
+            body["text"] = text.into();
+        }
+        if let Some(id) = stop.hit {
+            body["hitBreakpointIds"] = json!([id]);
+        }
+        session.event("stopped", body);
+
+        let requests = self.requests.lock().unwrap();
+        loop {
+            // If the server has gone away then there's nobody left to resume
+            // the program, so let it run to completion.
+            let Ok(request) = requests.recv() else {
+                session.paused.store(false, Ordering::SeqCst);
+                return Resume::Continue;
+            };
+            let resume = match request.command.as_str() {
+                "continue" => Some(Resume::Continue),
+                "next" => Some(Resume::StepOver),
+                "stepIn" => Some(Resume::StepIn),
+                "stepOut" => Some(Resume::StepOut),
+                _ => None,
+            };
+            if let Some(resume) = resume {
+                session.paused.store(false, Ordering::SeqCst);
+                let body = match resume {
+                    Resume::Continue => json!({ "allThreadsContinued": true }),
+                    _ => json!({}),
+                };
+                session.respond(&request, body);
+                return resume;
+            }
+            let result = match request.command.as_str() {
+                "stackTrace" => Ok(self.stack_trace(&request, backtrace, paused.as_deref())),
+                "scopes" => Ok(scopes(&request, paused.is_some())),
+                "variables" => match &mut paused {
+                    Some(paused) => self.variables(&request, paused),
+                    None => Err(anyhow!("the program can no longer be inspected")),
+                },
+                command => Err(anyhow!("unsupported request `{command}`")),
+            };
+            match result {
+                Ok(body) => session.respond(&request, body),
+                Err(e) => session.fail(&request, &e),
+            }
+        }
+    }
+
+    fn stack_trace<T: 'static>(
+        &self,
+        request: &Request,
+        backtrace: &WasmBacktrace,
+        paused: Option<&Paused<'_, '_, T>>,
+    ) -> Value {
+        let start = request.arguments["startFrame"].as_u64().unwrap_or(0) as usize;
+        let levels = match request.arguments["levels"].as_u64() {
+            Some(levels) if levels > 0 => levels as usize,
+            _ => usize::MAX,
+        };
+        let frames = backtrace.frames();
+        let stack_frames = frames
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(levels)
+            .map(|(id, frame)| {
+                // The youngest frame's recorded offset is that of the call
+                // into the debug handler, so prefer the paused instruction.
+                let offset = match paused {
+                    Some(paused) if id == 0 => Some(paused.frame.module_offset()),
+                    _ => frame.module_offset().map(|o| o as u32),
+                };
+                let mut name = String::new();
+                if let Some(module) = frame.module().name() {
+                    name.push_str(module);
+                    name.push('!');
+                }
+                wasmtime_environ::demangle_function_name_or_index(
+                    &mut name,
+                    frame.func_name(),
+                    frame.func_index() as usize,
+                )
+                .unwrap();
+                let mut stack_frame = json!({
+                    "id": id,
+                    "name": name,
+                    "line": 0,
+                    "column": 0,
+                });
+                if let Some(offset) = offset {
+                    stack_frame["instructionPointerReference"] = format!("0x{offset:x}").into();
+                }
+                let symbol = frame.symbols().iter().find(|s| s.file().is_some());
+                if let Some(symbol) = symbol {
+                    let path = symbol.file().unwrap();
+                    let file_name = Path::new(path).file_name().map(|n| n.to_string_lossy());
+                    stack_frame["source"] = json!({ "name": file_name, "path": path });
+                    stack_frame["line"] = symbol.line().unwrap_or(0).into();
+                    stack_frame["column"] = symbol.column().unwrap_or(0).into();
+                }
+                stack_frame
+            })
+            .collect::<Vec<_>>();
+        json!({ "stackFrames": stack_frames, "totalFrames": frames.len() })
+    }
+
+    fn variables<T: 'static>(
+        &self,
+        request: &Request,
+        paused: &mut Paused<'_, '_, T>,
+    ) -> Result<Value> {
+        let Paused {
+            store,
+            frame,
+            in_main,
+        } = paused;
+        // Names only apply to the module that the symbols were read from.
+        let empty = Symbols::default();
+        let symbols = if *in_main { &*self.symbols } else { &empty };
+        let func_index = frame.func_index();
+        let variables = match request.arguments["variablesReference"].as_u64() {
+            Some(LOCALS_REF) => frame
+                .locals()
+                .iter()
+                .enumerate()
+                .map(|(i, val)| {
+                    let name = symbols
+                        .local_names
+                        .get(&(func_index, i as u32))
+                        .cloned()
+                        .unwrap_or_else(|| format!("local{i}"));
+                    variable(name, val)
+                })
+                .collect(),
+            Some(STACK_REF) => frame
+                .stack()
+                .iter()
+                .enumerate()
+                .map(|(i, val)| variable(format!("[{i}]"), val))
+                .collect(),
+            Some(GLOBALS_REF) => (0..frame.num_globals())
+                .map(|i| {
+                    let name = symbols
+                        .global_names
+                        .get(&i)
+                        .cloned()
+                        .unwrap_or_else(|| format!("global{i}"));
+                    let global = frame.global(&mut *store, i).unwrap();
+                    let val = global.get(&mut *store);
+                    variable(name, &val)
+                })
+                .collect::<Vec<_>>(),
+            _ => bail!("unknown variables reference"),
+        };
+        Ok(json!({ "variables": variables }))
+    }
+}
+
+/// The store and frame that the program is paused at.
+struct Paused<'a, 'b, T: 'static> {
+    store: StoreContextMut<'a, T>,
+    frame: &'b DebugFrame,
+    /// Whether the frame belongs to the main module.
+    in_main: bool,
+}
+
+/// Why the program stopped, as reported to the client.
+struct Stop {
+    reason: &'static str,
+    text: Option<String>,
+    hit: Option<u64>,
+}
+
+fn scopes(request: &Request, inspectable: bool) -> Value {
+    // Values are only available for the frame that is paused.
+    if !inspectable || request.arguments["frameId"].as_u64() != Some(0) {
+        return json!({ "scopes": [] });
+    }
+    json!({
+        "scopes": [
+            {
+                "name": "Locals",
+                "presentationHint": "locals",
+                "variablesReference": LOCALS_REF,
+                "expensive": false,
+            },
+            {
+                "name": "Operand Stack",
+                "variablesReference": STACK_REF,
+                "expensive": false,
+            },
+            {
+                "name": "Globals",
+                "variablesReference": GLOBALS_REF,
+                "expensive": false,
+            },
+        ]
+    })
+}
+
+fn variable(name: String, val: &Val) -> Value {
+    let (value, ty) = match val {
+        Val::I32(i) => (i.to_string(), "i32"),
+        Val::I64(i) => (i.to_string(), "i64"),
+        Val::F32(f) => (f32::from_bits(*f).to_string(), "f32"),
+        Val::F64(f) => (f64::from_bits(*f).to_string(), "f64"),
+        Val::V128(v) => (format!("0x{:032x}", v.as_u128()), "v128"),
+        Val::FuncRef(f) => (ref_value(f.is_some(), "funcref"), "funcref"),
+        Val::ExternRef(r) => (ref_value(r.is_some(), "externref"), "externref"),
+        Val::AnyRef(r) => (ref_value(r.is_some(), "anyref"), "anyref"),
+        Val::ExnRef(r) => (ref_value(r.is_some(), "exnref"), "exnref"),
+    };
+    json!({
+        "name": name,
+        "value": value,
+        "type": ty,
+        "variablesReference": 0,
+    })
+}
+
+fn ref_value(non_null: bool, ty: &str) -> String {
+    if non_null {
+        format!("<{ty}>")
+    } else {
+        format!("<null {ty}>")
+    }
+}
+
+/// The store's debug handler, which decides whether to stop at each pause.
+struct Stepper {
+    program: Program,
+    main: Option<Module>,
+    step: Option<Step>,
+    entry: bool,
+}
+
+impl Stepper {
+    fn pause<T: 'static>(
+        &mut self,
+        store: StoreContextMut<'_, T>,
+        frame: &DebugFrame,
+    ) -> DebugAction {
+        let session = &*self.program.session;
+        let backtrace = WasmBacktrace::capture(&store);
+        let depth = backtrace.frames().len();
+
+        let in_main = self
+            .main
+            .as_ref()
+            .is_some_and(|main| Module::same(main, frame.module()));
+        let hit = if in_main {
+            let breakpoints = session.breakpoints.lock().unwrap();
+            breakpoints.find(frame.func_index(), frame.module_offset())
+        } else {
+            None
+        };
+        let stop = if session.pause_requested.swap(false, Ordering::SeqCst) {
+            Some(("pause", None))
+        } else if let Some((reason, id)) = hit {
+            Some((reason, Some(id)))
+        } else if frame.reason() == DebugReason::Breakpoint {
+            Some(("breakpoint", None))
+        } else if std::mem::take(&mut self.entry) {
+            Some(("entry", None))
+        } else {
+            match self.step {
+                Some(Step {
+                    resume: Resume::StepOver,
+                    depth: start,
+                }) if depth > start => None,
+                Some(Step {
+                    resume: Resume::StepOut,
+                    depth: start,
+                }) if depth >= start => None,
+                _ => Some(("step", None)),
+            }
+        };
+        let Some((reason, hit)) = stop else {
+            return DebugAction::Step;
+        };
+
+        let stop = Stop {
+            reason,
+            text: None,
+            hit,
+        };
+        let mut paused = Paused {
+            store,
+            frame,
+            in_main,
+        };
+        let resume = self.program.wait(stop, &backtrace, Some(&mut paused));
+        session
+            .breakpoints
+            .lock()
+            .unwrap()
+            .install(paused.store, self.main.as_ref());
+        match resume {
+            Resume::Continue => {
+                self.step = None;
+                DebugAction::Continue
+            }
+            resume => {
+                self.step = Some(Step { resume, depth });
+                DebugAction::Step
+            }
+        }
+    }
+}
@@ -14,7 +14,7 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use wasi_common::sync::{Dir, TcpListener, WasiCtxBuilder, ambient_authority};
-use wasmtime::{Engine, Func, Module, Store, StoreLimits, Val, ValType};
+use wasmtime::{Config, Engine, Func, Module, Store, StoreLimits, Val, ValType};
 use wasmtime_wasi::p2::{IoView, WasiView};
 
 #[cfg(feature = "wasi-nn")]
@@ -92,6 +92,50 @@ impl RunCommand {
     pub fn execute(mut self) -> Result<()> {
         self.run.common.init_logging()?;
 
+        let (store, result) = self.run_with_hooks(|_config| Ok(()), |_store, _main| Ok(()))?;
+
+        match result {
+            Ok(()) => (),
+            Err(e) => {
+                // Exit the process if Wasmtime understands the error;
+                // otherwise, fall back on Rust's default error printing/return
+                // code.
+                if store.data().preview1_ctx.is_some() {
+                    return Err(wasi_common::maybe_exit_on_error(e));
+                } else if store.data().preview2_ctx.is_some() {
+                    if let Some(exit) = e.downcast_ref::<wasmtime_wasi::I32Exit>() {
+                        std::process::exit(exit.0);
+                    }
+                }
+                if e.is::<wasmtime::Trap>() {
+                    eprintln!("Error: {e:?}");
+                    cfg_if::cfg_if! {
+                        if #[cfg(unix)] {
+                            std::process::exit(rustix::process::EXIT_SIGNALED_SIGABRT);
+                        } else if #[cfg(windows)] {
+                            // https://docs.microsoft.com/en-us/cpp/c-runtime-library/reference/abort?view=vs-2019
+                            std::process::exit(3);
+                        }
+                    }
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles, instantiates, and runs the main module, returning the store
+    /// it ran within along with the result of running it.
+    ///
+    /// The `configure` hook can adjust the engine's configuration before it's
+    /// created and the `prepare` hook can adjust the store before anything is
+    /// instantiated within it. These are used by `wasmtime debug`.
+    pub(crate) fn run_with_hooks(
+        &mut self,
+        configure: impl FnOnce(&mut Config) -> Result<()>,
+        prepare: impl FnOnce(&mut Store<Host>, &RunTarget) -> Result<()>,
+    ) -> Result<(Store<Host>, Result<()>)> {
         let mut config = self.run.common.config(None)?;
         config.async_support(true);
 
@@ -108,6 +152,7 @@ impl RunCommand {
             }
             None => {}
         }
+        configure(&mut config)?;
 
         let engine = Engine::new(&config)?;
 
@@ -166,6 +211,8 @@ impl RunCommand {
             store.set_fuel(fuel)?;
         }
 
+        prepare(&mut store, &main)?;
+
         // Always run the module asynchronously to ensure that the module can be
         // interrupted, even if it is blocking on I/O or a timeout or something.
         let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -235,39 +282,11 @@ impl RunCommand {
             .await
         });
 
-        // Load the main wasm module.
-        match result.unwrap_or_else(|elapsed| {
+        let result = result.unwrap_or_else(|elapsed| {
             Err(anyhow::Error::from(wasmtime::Trap::Interrupt))
                 .with_context(|| format!("timed out after {elapsed}"))
-        }) {
-            Ok(()) => (),
-            Err(e) => {
-                // Exit the process if Wasmtime understands the error;
-                // otherwise, fall back on Rust's default error printing/return
-                // code.
-                if store.data().preview1_ctx.is_some() {
-                    return Err(wasi_common::maybe_exit_on_error(e));
-                } else if store.data().preview2_ctx.is_some() {
-                    if let Some(exit) = e.downcast_ref::<wasmtime_wasi::I32Exit>() {
-                        std::process::exit(exit.0);
-                    }
-                }
-                if e.is::<wasmtime::Trap>() {
-                    eprintln!("Error: {e:?}");
-                    cfg_if::cfg_if! {
-                        if #[cfg(unix)] {
-                            std::process::exit(rustix::process::EXIT_SIGNALED_SIGABRT);
-                        } else if #[cfg(windows)] {
-                            // https://docs.microsoft.com/en-us/cpp/c-runtime-library/reference/abort?view=vs-2019
-                            std::process::exit(3);
-                        }
-                    }
-                }
-                return Err(e);
-            }
-        }
-
-        Ok(())
+        });
+        Ok((store, result))
     }
 
     fn compute_argv(&self) -> Result<Vec<String>> {
@@ -901,16 +920,18 @@ impl RunCommand {
                         bail!("Cannot enable wasi-keyvalue for core wasm modules");
                     }
                     CliLinker::Component(linker) => {
-                        let ctx = WasiKeyValueCtxBuilder::new()
-                            .in_memory_data(
-                                self.run
-                                    .common
-                                    .wasi
-                                    .keyvalue_in_memory_data
-                                    .iter()
-                                    .map(|v| (v.key.clone(), v.value.clone())),
-                            )
-                            .build();
+                        let mut builder = WasiKeyValueCtxBuilder::new().in_memory_data(
+                            self.run
+                                .common
+                                .wasi
+                                .keyvalue_in_memory_data
+                                .iter()
+                                .map(|v| (v.key.clone(), v.value.clone())),
+                        );
+                        for (identifier, store) in self.run.open_wasi_keyvalue_stores()? {
+                            builder = builder.store(identifier, store);
+                        }
+                        let ctx = builder.build();
 
                         wasmtime_wasi_keyvalue::add_to_linker(linker, |h| {
                             let preview2_ctx =
@@ -1084,7 +1105,7 @@ impl RunCommand {
 }
 
 #[derive(Default, Clone)]
-struct Host {
+pub(crate) struct Host {
     preview1_ctx: Option<wasi_common::WasiCtx>,
 
     // The Mutex is only needed to satisfy the Sync constraint but we never
@@ -111,6 +111,12 @@ pub struct ServeCommand {
     /// The WebAssembly component to run.
     #[arg(value_name = "WASM", required = true)]
     component: PathBuf,
+
+    /// Key-value stores configured with `-S keyvalue-store`, opened once and
+    /// shared by all requests.
+    #[cfg(feature = "wasi-keyvalue")]
+    #[arg(skip)]
+    keyvalue_stores: Vec<(String, Arc<dyn wasmtime_wasi_keyvalue::KeyValueStore>)>,
 }
 
 impl ServeCommand {
@@ -132,6 +138,13 @@ impl ServeCommand {
             bail!("wasi-threads does not support components yet")
         }
 
+        if self.run.common.wasi.keyvalue == Some(true) {
+            #[cfg(feature = "wasi-keyvalue")]
+            {
+                self.keyvalue_stores = self.run.open_wasi_keyvalue_stores()?;
+            }
+        }
+
         // The serve command requires both wasi-http and the component model, so
         // we enable those by default here.
         if self.run.common.wasi.http.replace(true) == Some(false) {
@@ -222,16 +235,18 @@ impl ServeCommand {
         if self.run.common.wasi.keyvalue == Some(true) {
             #[cfg(feature = "wasi-keyvalue")]
             {
-                let ctx = WasiKeyValueCtxBuilder::new()
-                    .in_memory_data(
-                        self.run
-                            .common
-                            .wasi
-                            .keyvalue_in_memory_data
-                            .iter()
-                            .map(|v| (v.key.clone(), v.value.clone())),
-                    )
-                    .build();
+                let mut builder = WasiKeyValueCtxBuilder::new().in_memory_data(
+                    self.run
+                        .common
+                        .wasi
+                        .keyvalue_in_memory_data
+                        .iter()
+                        .map(|v| (v.key.clone(), v.value.clone())),
+                );
+                for (identifier, store) in &self.keyvalue_stores {
+                    builder = builder.store(identifier, store.clone());
+                }
+                let ctx = builder.build();
                 host.wasi_keyvalue.replace(ctx);
             }
         }
@@ -3,6 +3,8 @@
 use anyhow::{Context, Result, anyhow, bail};
 use clap::Parser;
 use std::net::TcpListener;
+#[cfg(feature = "wasi-keyvalue")]
+use std::sync::Arc;
 use std::{fs::File, path::Path, time::Duration};
 use wasmtime::{Engine, Module, Precompiled, StoreLimits, StoreLimitsBuilder};
 use wasmtime_cli_flags::{CommonOptions, opt::WasmtimeOptionValue};
@@ -322,6 +324,32 @@ impl RunCommon {
         Ok(())
     }
 
+    /// Opens the stores configured with `-S keyvalue-store`.
+    #[cfg(feature = "wasi-keyvalue")]
+    pub fn open_wasi_keyvalue_stores(
+        &self,
+    ) -> Result<Vec<(String, Arc<dyn wasmtime_wasi_keyvalue::KeyValueStore>)>> {
+        use wasmtime_cli_flags::WasiKeyValueBackend;
+        use wasmtime_wasi_keyvalue::{FileStore, KeyValueStore, MemoryStore};
+
+        self.common
+            .wasi
+            .keyvalue_store
+            .iter()
+            .map(|store| {
+                let backend: Arc<dyn KeyValueStore> = match &store.backend {
+                    WasiKeyValueBackend::Memory => Arc::new(MemoryStore::new()),
+                    WasiKeyValueBackend::File(path) => {
+                        Arc::new(FileStore::open(path).with_context(|| {
+                            format!("failed to open key-value store `{}`", path.display())
+                        })?)
+                    }
+                };
+                Ok((store.identifier.clone(), backend))
+            })
+            .collect()
+    }
+
     pub fn compute_preopen_sockets(&self) -> Result<Vec<TcpListener>> {
         let mut listeners = vec![];
 
@@ -0,0 +1,214 @@
+#![cfg(not(miri))]
+
+use wasmtime::*;
+
+/// Serializes `module` and loads it into a new engine, as if it were
+/// restored in another process.
+fn reload(config: &Config, module: &Module) -> Result<(Engine, Module)> {
+    let bytes = module.serialize()?;
+    let engine = Engine::new(config)?;
+    let module = unsafe { Module::deserialize(&engine, &bytes)? };
+    Ok((engine, module))
+}
+
+#[test]
+fn core_checkpoint_restore() -> Result<()> {
+    let config = Config::new();
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "host" "log" (func $log (param i32)))
+                (memory (export "memory") 1)
+                (table $t (export "table") 1 funcref)
+                (global $count (mut i32) (i32.const 0))
+                (global $f (mut funcref) (ref.null func))
+
+                (data $passive "hello")
+                (data (i32.const 0) "init")
+                (elem (i32.const 0) $a)
+
+                (func $a (result i32) i32.const 1)
+                (func $b (result i32) i32.const 2)
+                (elem declare func $b $log)
+
+                (func (export "run")
+                    (global.set $count (i32.add (global.get $count) (i32.const 1)))
+                    (memory.init $passive (i32.const 100) (i32.const 0) (i32.const 5))
+                    (data.drop $passive)
+                    (memory.fill (i32.const 0) (i32.const 0) (i32.const 4))
+                    (drop (memory.grow (i32.const 1)))
+                    (i32.store (i32.const 70000) (i32.const 0xabcdef))
+                    (drop (table.grow $t (ref.func $b) (i32.const 2)))
+                    (table.set $t (i32.const 0) (ref.null func))
+                    (global.set $f (ref.func $log)))
+                (func (export "count") (result i32) (global.get $count))
+                (func (export "call") (param i32) (result i32)
+                    (call_indirect $t (result i32) (local.get 0)))
+                (func (export "f-is-null") (result i32) (ref.is_null (global.get $f)))
+                (func (export "init-again")
+                    (memory.init $passive (i32.const 0) (i32.const 0) (i32.const 5)))
+            )
+        "#,
+    )?;
+
+    let checkpoint = {
+        let mut linker = Linker::new(&engine);
+        linker.func_wrap("host", "log", |_: i32| {})?;
+        let mut store = Store::new(&engine, ());
+        let instance = linker.instantiate(&mut store, &module)?;
+        let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+        run.call(&mut store, ())?;
+        instance.checkpoint(&mut store)?
+    };
+
+    let (engine, module) = reload(&config, &module)?;
+    let mut linker = Linker::new(&engine);
+    linker.func_wrap("host", "log", |_: i32| {})?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    instance.restore(&mut store, &checkpoint)?;
+
+    let count = instance.get_typed_func::<(), i32>(&mut store, "count")?;
+    assert_eq!(count.call(&mut store, ())?, 1);
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.size(&store), 2);
+    let data = memory.data(&store);
+    assert_eq!(&data[..4], &[0; 4]);
+    assert_eq!(&data[100..105], b"hello");
+    assert_eq!(&data[70000..70004], &0xabcdefu32.to_le_bytes());
+
+    let table = instance.get_table(&mut store, "table").unwrap();
+    assert_eq!(table.size(&store), 3);
+    let call = instance.get_typed_func::<u32, i32>(&mut store, "call")?;
+    assert!(call.call(&mut store, 0).is_err());
+    assert_eq!(call.call(&mut store, 1)?, 2);
+    assert_eq!(call.call(&mut store, 2)?, 2);
+
+    let f_is_null = instance.get_typed_func::<(), i32>(&mut store, "f-is-null")?;
+    assert_eq!(f_is_null.call(&mut store, ())?, 0);
+
+    // The passive segment was dropped before the checkpoint was taken.
+    let init_again = instance.get_typed_func::<(), ()>(&mut store, "init-again")?;
+    assert!(init_again.call(&mut store, ()).is_err());
+    Ok(())
+}
+
+#[test]
+fn gc_checkpoint_restore() -> Result<()> {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $node (struct (field $val (mut i32)) (field $next (mut (ref null $node)))))
+                (type $bytes (array (mut i8)))
+                (type $pair (struct (field (ref $node)) (field (ref $bytes))))
+
+                (global $root (ref $node) (struct.new $node (i32.const 1) (ref.null $node)))
+                (global $cur (mut (ref null $pair)) (ref.null $pair))
+                (table $t 2 anyref)
+
+                (func (export "build")
+                    (struct.set $node $val (global.get $root) (i32.const 10))
+                    (struct.set $node $next
+                        (global.get $root)
+                        (struct.new $node (i32.const 2) (global.get $root)))
+                    (global.set $cur
+                        (struct.new $pair
+                            (ref.as_non_null (struct.get $node $next (global.get $root)))
+                            (array.new $bytes (i32.const 7) (i32.const 3))))
+                    (table.set $t (i32.const 0) (ref.i31 (i32.const 42)))
+                    (table.set $t (i32.const 1) (global.get $cur)))
+
+                (func (export "sum") (result i32)
+                    (i32.add
+                        (i32.add
+                            (i32.add
+                                (struct.get $node $val (global.get $root))
+                                (struct.get $node $val
+                                    (struct.get $node $next (global.get $root))))
+                            (i32.add
+                                (struct.get $node $val
+                                    (struct.get $pair 0 (global.get $cur)))
+                                (array.get_u $bytes
+                                    (struct.get $pair 1 (global.get $cur))
+                                    (i32.const 2))))
+                        (i32.add
+                            (i32.add
+                                (i31.get_s (ref.cast (ref i31) (table.get $t (i32.const 0))))
+                                (ref.eq
+                                    (struct.get $node $next
+                                        (struct.get $node $next (global.get $root)))
+                                    (global.get $root)))
+                            (ref.eq
+                                (ref.cast (ref null $pair) (table.get $t (i32.const 1)))
+                                (global.get $cur)))))
+            )
+        "#,
+    )?;
+
+    let checkpoint = {
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let build = instance.get_typed_func::<(), ()>(&mut store, "build")?;
+        build.call(&mut store, ())?;
+        let sum = instance.get_typed_func::<(), i32>(&mut store, "sum")?;
+        assert_eq!(sum.call(&mut store, ())?, 65);
+        instance.checkpoint(&mut store)?
+    };
+
+    let (engine, module) = reload(&config, &module)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    instance.restore(&mut store, &checkpoint)?;
+    store.gc(None);
+    let sum = instance.get_typed_func::<(), i32>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, ())?, 65);
+    Ok(())
+}
+
+#[test]
+fn checkpoint_errors() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"(module (table (export "table") 1 funcref) (global (mut i32) (i32.const 0)))"#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let table = instance.get_table(&mut store, "table").unwrap();
+    let func = Func::wrap(&mut store, || {});
+    table.set(&mut store, 0, func.into())?;
+    let err = instance.checkpoint(&mut store).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("cannot checkpoint element 0 of table 0"),
+        "{err:?}"
+    );
+
+    table.set(&mut store, 0, Ref::Func(None))?;
+    let checkpoint = instance.checkpoint(&mut store)?;
+
+    let other = Module::new(&engine, r#"(module (memory 1))"#)?;
+    let other = Instance::new(&mut store, &other, &[])?;
+    let err = other.restore(&mut store, &checkpoint).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("checkpoint was taken from a different module"),
+        "{err:?}"
+    );
+
+    let err = instance
+        .restore(&mut store, b"not a checkpoint")
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("not a wasmtime instance checkpoint"),
+        "{err:?}"
+    );
+    Ok(())
+}
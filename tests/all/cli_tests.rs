@@ -2456,3 +2456,194 @@ fn big_table_in_pooling_allocator() -> Result<()> {
     ])?;
     Ok(())
 }
+
+/// A minimal Debug Adapter Protocol client driving `wasmtime debug` over its
+/// stdin and stdout.
+struct DapClient {
+    child: std::process::Child,
+    stdout: std::io::BufReader<std::process::ChildStdout>,
+    seq: u64,
+}
+
+impl DapClient {
+    fn new() -> Result<DapClient> {
+        let mut child = get_wasmtime_command()?
+            .args(&["debug"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        Ok(DapClient {
+            child,
+            stdout,
+            seq: 0,
+        })
+    }
+
+    fn send(&mut self, command: &str, arguments: serde_json::Value) -> Result<()> {
+        self.seq += 1;
+        let body = serde_json::json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        let stdin = self.child.stdin.as_mut().unwrap();
+        write!(stdin, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<serde_json::Value> {
+        use std::io::{BufRead, Read};
+
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                bail!("debugger exited unexpectedly");
+            }
+            match line.trim_end().split_once(": ") {
+                Some(("Content-Length", n)) => len = n.parse()?,
+                _ => break,
+            }
+        }
+        let mut body = vec![0; len];
+        self.stdout.read_exact(&mut body)?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Sends a request and returns the body of its successful response,
+    /// skipping over any events.
+    fn request(
+        &mut self,
+        command: &str,
+        arguments: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.send(command, arguments)?;
+        loop {
+            let message = self.recv()?;
+            if message["type"] == "response" && message["command"] == command {
+                if message["success"] != true {
+                    bail!("`{command}` failed: {message}");
+                }
+                return Ok(message["body"].clone());
+            }
+        }
+    }
+
+    /// Waits for the given event and returns its body.
+    fn event(&mut self, event: &str) -> Result<serde_json::Value> {
+        loop {
+            let message = self.recv()?;
+            if message["type"] == "event" && message["event"] == event {
+                return Ok(message["body"].clone());
+            }
+        }
+    }
+
+    fn launch(&mut self, arguments: serde_json::Value) -> Result<()> {
+        self.request("initialize", serde_json::json!({ "adapterID": "wasmtime" }))?;
+        self.request("launch", arguments)?;
+        self.event("initialized")?;
+        Ok(())
+    }
+
+    fn stack(&mut self) -> Result<Vec<String>> {
+        let body = self.request("stackTrace", serde_json::json!({ "threadId": 1 }))?;
+        Ok(body["stackFrames"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|frame| frame["name"].as_str().unwrap().to_string())
+            .collect())
+    }
+
+    fn variables(&mut self, reference: u64) -> Result<Vec<(String, String)>> {
+        let body = self.request(
+            "variables",
+            serde_json::json!({ "variablesReference": reference }),
+        )?;
+        Ok(body["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| {
+                let name = v["name"].as_str().unwrap().to_string();
+                (name, v["value"].as_str().unwrap().to_string())
+            })
+            .collect())
+    }
+
+    fn finish(mut self, exit_code: i64) -> Result<()> {
+        assert_eq!(self.event("exited")?["exitCode"], exit_code);
+        self.event("terminated")?;
+        self.request("disconnect", serde_json::json!({}))?;
+        assert!(self.child.wait()?.success());
+        Ok(())
+    }
+}
+
+fn pair(name: &str, value: &str) -> (String, String) {
+    (name.to_string(), value.to_string())
+}
+
+#[test]
+fn debug_breakpoint_and_step() -> Result<()> {
+    let mut client = DapClient::new()?;
+    client.launch(serde_json::json!({ "program": "tests/all/cli_tests/debug.wat" }))?;
+    let body = client.request(
+        "setFunctionBreakpoints",
+        serde_json::json!({ "breakpoints": [{ "name": "add" }, { "name": "nope" }] }),
+    )?;
+    assert_eq!(body["breakpoints"][0]["verified"], true);
+    assert_eq!(body["breakpoints"][1]["verified"], false);
+    client.request("configurationDone", serde_json::json!({}))?;
+
+    let stopped = client.event("stopped")?;
+    assert_eq!(stopped["reason"], "function breakpoint");
+    assert_eq!(client.stack()?, ["add", "start"]);
+    let scopes = client.request("scopes", serde_json::json!({ "frameId": 0 }))?;
+    assert_eq!(scopes["scopes"].as_array().unwrap().len(), 3);
+    assert_eq!(client.variables(1)?, [pair("a", "1"), pair("b", "2")]);
+    assert_eq!(client.variables(2)?, []);
+
+    client.request("next", serde_json::json!({ "threadId": 1 }))?;
+    assert_eq!(client.event("stopped")?["reason"], "step");
+    client.request("next", serde_json::json!({ "threadId": 1 }))?;
+    assert_eq!(client.event("stopped")?["reason"], "step");
+    assert_eq!(client.variables(2)?, [pair("[0]", "1"), pair("[1]", "2")]);
+
+    client.request("stepOut", serde_json::json!({ "threadId": 1 }))?;
+    assert_eq!(client.event("stopped")?["reason"], "step");
+    assert_eq!(client.stack()?, ["start"]);
+    assert_eq!(client.variables(2)?, [pair("[0]", "3")]);
+
+    client.request("continue", serde_json::json!({ "threadId": 1 }))?;
+    client.finish(0)
+}
+
+#[test]
+fn debug_stops_on_trap() -> Result<()> {
+    let mut client = DapClient::new()?;
+    client.launch(serde_json::json!({
+        "program": "tests/all/cli_tests/debug.wat",
+        "wasmtimeArgs": ["--invoke", "trap"],
+    }))?;
+    client.request("configurationDone", serde_json::json!({}))?;
+
+    let stopped = client.event("stopped")?;
+    assert_eq!(stopped["reason"], "exception");
+    assert!(
+        stopped["text"].as_str().unwrap().contains("unreachable"),
+        "bad stop: {stopped}"
+    );
+    assert_eq!(client.stack()?, ["fail", "trap"]);
+    let scopes = client.request("scopes", serde_json::json!({ "frameId": 0 }))?;
+    assert_eq!(scopes["scopes"], serde_json::json!([]));
+
+    client.request("continue", serde_json::json!({ "threadId": 1 }))?;
+    client.finish(1)
+}
@@ -0,0 +1,15 @@
+(module
+  (func $add (param $a i32) (param $b i32) (result i32)
+    local.get $a
+    local.get $b
+    i32.add)
+  (func $fail
+    unreachable)
+  (func $start (export "_start")
+    i32.const 1
+    i32.const 2
+    call $add
+    drop)
+  (func $trap (export "trap")
+    call $fail)
+)
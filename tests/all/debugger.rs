@@ -0,0 +1,228 @@
+#![cfg(not(miri))]
+
+use std::sync::{Arc, Mutex};
+use wasmtime::*;
+
+fn debug_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.guest_debug(true);
+    Engine::new(&config)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Pause {
+    reason: DebugReason,
+    func_index: u32,
+    module_offset: u32,
+    locals: Vec<i32>,
+    stack: Vec<i32>,
+}
+
+fn i32s(vals: &[Val]) -> Vec<i32> {
+    vals.iter().map(|v| v.unwrap_i32()).collect()
+}
+
+fn record_pauses(store: &mut Store<()>, action: DebugAction) -> Arc<Mutex<Vec<Pause>>> {
+    let pauses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = pauses.clone();
+    store.debug_handler(move |_store, frame| {
+        recorded.lock().unwrap().push(Pause {
+            reason: frame.reason(),
+            func_index: frame.func_index(),
+            module_offset: frame.module_offset(),
+            locals: i32s(frame.locals()),
+            stack: i32s(frame.stack()),
+        });
+        Ok(action)
+    });
+    pauses
+}
+
+const ADD_ONE: &str = r#"
+    (module
+        (func (export "f") (param i32) (result i32)
+            (local i32)
+            local.get 0
+            i32.const 1
+            i32.add
+            local.tee 1
+        )
+    )
+"#;
+
+#[test]
+fn single_step_observes_locals_and_stack() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, ADD_ONE)?;
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::Step);
+    store.set_single_step(true);
+
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+    assert_eq!(f.call(&mut store, 41)?, 42);
+
+    let pauses = pauses.lock().unwrap();
+    let states: Vec<_> = pauses
+        .iter()
+        .map(|p| (p.locals.clone(), p.stack.clone()))
+        .collect();
+    assert_eq!(
+        states,
+        [
+            (vec![41, 0], vec![]),
+            (vec![41, 0], vec![41]),
+            (vec![41, 0], vec![41, 1]),
+            (vec![41, 0], vec![42]),
+            (vec![41, 42], vec![42]),
+        ]
+    );
+    assert!(pauses.iter().all(|p| p.reason == DebugReason::Step));
+    assert!(pauses.iter().all(|p| p.func_index == 0));
+    assert!(pauses.windows(2).all(|w| w[0].module_offset < w[1].module_offset));
+    Ok(())
+}
+
+#[test]
+fn breakpoint_pauses_once_and_continues() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, ADD_ONE)?;
+
+    // Find the offset of the `i32.add` instruction by stepping through once.
+    let add_offset = {
+        let mut store = Store::new(&engine, ());
+        let pauses = record_pauses(&mut store, DebugAction::Step);
+        store.set_single_step(true);
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+        f.call(&mut store, 0)?;
+        let pauses = pauses.lock().unwrap();
+        pauses[2].module_offset
+    };
+
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::Continue);
+    store.add_breakpoint(&module, 0, add_offset)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+    assert_eq!(f.call(&mut store, 9)?, 10);
+    assert_eq!(f.call(&mut store, 19)?, 20);
+
+    assert_eq!(
+        *pauses.lock().unwrap(),
+        [
+            Pause {
+                reason: DebugReason::Breakpoint,
+                func_index: 0,
+                module_offset: add_offset,
+                locals: vec![9, 0],
+                stack: vec![9, 1],
+            },
+            Pause {
+                reason: DebugReason::Breakpoint,
+                func_index: 0,
+                module_offset: add_offset,
+                locals: vec![19, 0],
+                stack: vec![19, 1],
+            },
+        ]
+    );
+
+    assert!(store.remove_breakpoint(&module, 0, add_offset));
+    assert!(!store.remove_breakpoint(&module, 0, add_offset));
+    f.call(&mut store, 0)?;
+    assert_eq!(pauses.lock().unwrap().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn step_into_callee_and_read_globals() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (global $g (mut i32) (i32.const 100))
+                (func $callee (param i32) (result i32)
+                    local.get 0
+                    global.get $g
+                    i32.add
+                )
+                (func (export "f") (result i32)
+                    i32.const 7
+                    global.set $g
+                    i32.const 1
+                    call $callee
+                )
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    store.debug_handler(move |mut store, frame| {
+        assert_eq!(frame.num_globals(), 1);
+        let global = frame.global(&mut store, 0).unwrap();
+        let value = global.get(&mut store).unwrap_i32();
+        seen2.lock().unwrap().push((frame.func_index(), value));
+        assert!(frame.global(&mut store, 1).is_none());
+        Ok(DebugAction::Step)
+    });
+    store.set_single_step(true);
+
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<(), i32>(&mut store, "f")?;
+    assert_eq!(f.call(&mut store, ())?, 8);
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(
+        *seen,
+        [
+            (1, 100),
+            (1, 100),
+            (1, 7),
+            (1, 7),
+            (0, 7),
+            (0, 7),
+            (0, 7),
+            (0, 7),
+            (1, 7),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn handler_error_traps() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, ADD_ONE)?;
+    let mut store = Store::new(&engine, ());
+    store.debug_handler(|_, _| anyhow::bail!("stopped by the debugger"));
+    store.set_single_step(true);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+    let err = f.call(&mut store, 0).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("stopped by the debugger"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn breakpoints_require_guest_debug() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, ADD_ONE)?;
+    let mut store = Store::new(&engine, ());
+    let err = store.add_breakpoint(&module, 0, 0).unwrap_err();
+    assert!(
+        err.to_string().contains("Config::guest_debug"),
+        "bad error: {err}"
+    );
+
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, ADD_ONE)?;
+    let mut store = Store::new(&engine, ());
+    assert!(store.add_breakpoint(&module, 1, 0).is_err());
+    Ok(())
+}
@@ -1394,3 +1394,185 @@ fn drc_gc_inbetween_host_calls() -> Result<()> {
 
     Ok(())
 }
+
+/// Create an engine using the generational collector with a small, fixed-size
+/// GC heap, so that tests exercise many collections.
+fn small_heap_generational_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    config.collector(Collector::Generational);
+    config.memory_reservation(1 << 16);
+    config.memory_reservation_for_growth(0);
+    config.memory_guard_size(0);
+    config.memory_may_move(false);
+    Engine::new(&config)
+}
+
+#[test]
+#[cfg_attr(any(miri, not(target_pointer_width = "64")), ignore)]
+fn generational_preserves_live_objects_across_collections() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let engine = small_heap_generational_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $node (struct (field i32) (field (ref null $node))))
+                (type $holder (struct (field (mut (ref null $node)))))
+
+                (global $head (mut (ref null $node)) (ref.null $node))
+                (global $holder (mut (ref null $holder)) (ref.null $holder))
+
+                (func (export "run") (param $n i32)
+                    (local $i i32)
+                    (global.set $holder (struct.new $holder (ref.null $node)))
+                    (loop $loop
+                        ;; Periodically drop the whole list, turning it into
+                        ;; garbage.
+                        (if (i32.eqz (i32.rem_u (local.get $i) (i32.const 500)))
+                            (then (global.set $head (ref.null $node))))
+
+                        ;; Push a new node onto the list.
+                        (global.set $head
+                            (struct.new $node (local.get $i) (global.get $head)))
+
+                        ;; Store the new node into the (eventually old)
+                        ;; holder, creating an old-to-young edge.
+                        (struct.set $holder 0 (global.get $holder) (global.get $head))
+
+                        ;; And allocate some short-lived garbage.
+                        (drop (array.new_default $garbage (i32.const 4)))
+
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br_if $loop (i32.lt_u (local.get $i) (local.get $n)))
+                    )
+                )
+
+                (type $garbage (array (mut i64)))
+
+                (func (export "sum") (result i32)
+                    (local $sum i32)
+                    (local $node (ref null $node))
+                    (local.set $node (global.get $head))
+                    (block $done
+                        (loop $loop
+                            (br_if $done (ref.is_null (local.get $node)))
+                            (local.set $sum
+                                (i32.add (local.get $sum)
+                                         (struct.get $node 0 (local.get $node))))
+                            (local.set $node (struct.get $node 1 (local.get $node)))
+                            (br $loop)
+                        )
+                    )
+                    (local.get $sum)
+                )
+
+                (func (export "last") (result i32)
+                    (struct.get $node 0
+                        (struct.get $holder 0 (global.get $holder)))
+                )
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<u32, ()>(&mut store, "run")?;
+    let sum = instance.get_typed_func::<(), u32>(&mut store, "sum")?;
+    let last = instance.get_typed_func::<(), u32>(&mut store, "last")?;
+
+    let n = 10_000;
+    run.call(&mut store, n)?;
+
+    let expected: u32 = (9_500..n).sum();
+    assert_eq!(sum.call(&mut store, ())?, expected);
+    assert_eq!(last.call(&mut store, ())?, n - 1);
+
+    // Explicit collections must also preserve everything reachable.
+    store.gc(None);
+    assert_eq!(sum.call(&mut store, ())?, expected);
+    assert_eq!(last.call(&mut store, ())?, n - 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(any(miri, not(target_pointer_width = "64")), ignore)]
+fn generational_collects_cycles() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let engine = small_heap_generational_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $pair (struct (field (mut (ref null $pair)))))
+
+                (func (export "run") (param $n i32)
+                    (local $a (ref null $pair))
+                    (loop $loop
+                        ;; Create a two-object cycle and immediately drop it.
+                        (local.set $a (struct.new $pair (ref.null $pair)))
+                        (struct.set $pair 0
+                            (local.get $a)
+                            (struct.new $pair (local.get $a)))
+
+                        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+                        (br_if $loop (local.get $n))
+                    )
+                )
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<u32, ()>(&mut store, "run")?;
+
+    // Far more cyclic garbage than fits in the GC heap at once.
+    run.call(&mut store, 100_000)?;
+
+    Ok(())
+}
+
+#[test]
+fn generational_drops_unreachable_externrefs() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    config.collector(Collector::Generational);
+
+    let engine = Engine::new(&config)?;
+    let mut store = Store::new(&engine, ());
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let kept_dropped = Arc::new(AtomicBool::new(false));
+    let kept = {
+        let mut scope = RootScope::new(&mut store);
+        let _ = ExternRef::new(&mut scope, SetFlagOnDrop(dropped.clone()))?;
+        let kept = ExternRef::new(&mut scope, SetFlagOnDrop(kept_dropped.clone()))?;
+        kept.to_manually_rooted(&mut scope)?
+    };
+
+    store.gc(None);
+    assert!(dropped.load(SeqCst));
+    assert!(!kept_dropped.load(SeqCst));
+
+    // The surviving `externref` may have moved, but its host data is intact.
+    assert!(
+        kept.data(&store)?
+            .unwrap()
+            .downcast_ref::<SetFlagOnDrop>()
+            .is_some()
+    );
+
+    kept.unroot(&mut store);
+    store.gc(None);
+    assert!(kept_dropped.load(SeqCst));
+
+    Ok(())
+}
@@ -5,12 +5,14 @@ use wasmtime::Result;
 mod arrays;
 mod async_functions;
 mod call_hook;
+mod checkpoint;
 mod cli_tests;
 mod code_too_large;
 mod component_model;
 mod coredump;
 mod custom_code_memory;
 mod debug;
+mod debugger;
 mod defaults;
 mod epoch_interruption;
 mod exnrefs;
@@ -37,16 +39,27 @@ mod name;
 mod noextern;
 mod piped_tests;
 mod pooling_allocator;
+mod pre_initialize;
 mod pulley;
+mod record_replay;
 mod relocs;
 mod stack_creator;
 mod stack_overflow;
 mod store;
 mod structs;
 mod table;
-#[cfg(all(feature = "stack-switching", unix, target_arch = "x86_64"))]
+#[cfg(all(
+    feature = "stack-switching",
+    unix,
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64"
+    )
+))]
 mod tags;
 mod threads;
+mod tiered_compilation;
 mod traps;
 mod types;
 mod wait_notify;
@@ -0,0 +1,215 @@
+#![cfg(not(miri))]
+
+use wasmtime::*;
+
+#[test]
+fn core_snapshot() -> Result<()> {
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    linker.func_wrap("host", "value", || 7)?;
+
+    let wasm = Module::pre_initialize(
+        &mut Store::new(&engine, ()),
+        &linker,
+        br#"
+            (module
+                (import "host" "value" (func $value (result i32)))
+                (memory (export "memory") 1)
+                (table $t (export "table") 2 funcref)
+                (global $count (export "count") (mut i32) (i32.const 0))
+                (global $f (mut funcref) (ref.null func))
+                (global $k (export "k") i64 (i64.const 3))
+
+                (data (i32.const 0) "original")
+                (data $passive "passive")
+                (elem (i32.const 0) $a)
+
+                (func $a (result i32) i32.const 100)
+                (func $b (result i32) i32.const 200)
+                (elem declare func $b)
+
+                (func $start
+                    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+                (start $start)
+
+                (func (export "init")
+                    (global.set $count (i32.add (global.get $count) (call $value)))
+                    (memory.fill (i32.const 0) (i32.const 0) (i32.const 8))
+                    (i32.store (i32.const 100) (i32.const 0x11223344))
+                    (drop (memory.grow (i32.const 2)))
+                    (i32.store8 (i32.const 0x2ffff) (i32.const 9))
+                    (drop (table.grow $t (ref.func $b) (i32.const 3)))
+                    (table.set $t (i32.const 0) (ref.null func))
+                    (global.set $f (ref.func $b)))
+
+                (func (export "call") (param i32) (result i32)
+                    (call_indirect $t (result i32) (local.get 0)))
+                (func (export "call-f") (result i32)
+                    (table.set $t (i32.const 1) (global.get $f))
+                    (call_indirect $t (result i32) (i32.const 1)))
+                (func (export "passive") (result i32)
+                    (memory.init $passive (i32.const 200) (i32.const 0) (i32.const 7))
+                    (i32.load8_u (i32.const 200)))
+            )
+        "#,
+        "init",
+    )?;
+
+    // Neither the start function nor the initializer run again when the
+    // snapshot is instantiated, so `count` stays at `1 + 7`.
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(&engine, &wasm)?;
+    assert!(module.get_export("init").is_none());
+    let instance = linker.instantiate(&mut store, &module)?;
+
+    let count = instance.get_global(&mut store, "count").unwrap();
+    assert_eq!(count.get(&mut store).unwrap_i32(), 8);
+    let k = instance.get_global(&mut store, "k").unwrap();
+    assert_eq!(k.get(&mut store).unwrap_i64(), 3);
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.size(&store), 3);
+    let data = memory.data(&store);
+    assert_eq!(&data[..8], &[0; 8]);
+    assert_eq!(&data[100..104], &0x11223344u32.to_le_bytes());
+    assert_eq!(data[0x2ffff], 9);
+
+    let table = instance.get_table(&mut store, "table").unwrap();
+    assert_eq!(table.size(&store), 5);
+    assert!(table.get(&mut store, 0).unwrap().is_null());
+    let call = instance.get_typed_func::<u32, i32>(&mut store, "call")?;
+    for i in 2..5 {
+        assert_eq!(call.call(&mut store, i)?, 200);
+    }
+    assert!(call.call(&mut store, 0).is_err());
+
+    let call_f = instance.get_typed_func::<(), i32>(&mut store, "call-f")?;
+    assert_eq!(call_f.call(&mut store, ())?, 200);
+    let passive = instance.get_typed_func::<(), i32>(&mut store, "passive")?;
+    assert_eq!(passive.call(&mut store, ())?, i32::from(b'p'));
+    Ok(())
+}
+
+#[test]
+fn core_snapshot_errors() -> Result<()> {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    let mut store = Store::new(&engine, ());
+    let memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    linker.define(&mut store, "host", "memory", memory)?;
+
+    let err = Module::pre_initialize(
+        &mut store,
+        &linker,
+        br#"
+            (module
+                (import "host" "memory" (memory 1))
+                (func (export "init"))
+            )
+        "#,
+        "init",
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("cannot snapshot imported memory"),
+        "{err:?}"
+    );
+
+    let err = Module::pre_initialize(
+        &mut store,
+        &linker,
+        r#"(module (func (export "init") unreachable))"#.as_bytes(),
+        "init",
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("failed to run initialization function"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn component_snapshot() -> Result<()> {
+    use wasmtime::component::{Component, Linker};
+
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap("seed", |_, (): ()| Ok((5u32,)))?;
+
+    let wasm = Component::pre_initialize(
+        &mut Store::new(&engine, ()),
+        &linker,
+        br#"
+            (component
+                (import "seed" (func $seed (result u32)))
+                (core module $libc
+                    (memory (export "memory") 1)
+                    (table (export "table") 1 funcref)
+                )
+                (core instance $libc (instantiate $libc))
+                (core func $seed (canon lower (func $seed)))
+
+                (core module $m
+                    (import "libc" "memory" (memory 1))
+                    (import "libc" "table" (table 1 funcref))
+                    (import "host" "seed" (func $seed (result i32)))
+                    (global $g (mut i32) (i32.const 0))
+                    (data (i32.const 16) "hello")
+                    (elem (table 0) (i32.const 0) func $seed)
+                    (func (export "init")
+                        (global.set $g (call $seed))
+                        (i32.store8 (i32.const 16) (i32.const 106)))
+                    (func (export "get") (result i32)
+                        (i32.add
+                            (global.get $g)
+                            (i32.load8_u (i32.const 16))))
+                )
+                (core instance $m (instantiate $m
+                    (with "libc" (instance $libc))
+                    (with "host" (instance (export "seed" (func $seed))))
+                ))
+
+                (func (export "init") (canon lift (core func $m "init")))
+                (func (export "get") (result u32) (canon lift (core func $m "get")))
+            )
+        "#,
+        "init",
+    )?;
+
+    // The host import is still in the table, so `$libc`'s table keeps its
+    // original definition, but the memory of `$libc` and the global of `$m`
+    // are captured.
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, &wasm)?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    assert_eq!(get.call(&mut store, ())?.0, 5 + u32::from(b'j'));
+    Ok(())
+}
+
+#[test]
+fn component_module_instantiated_twice() -> Result<()> {
+    use wasmtime::component::{Component, Linker};
+
+    let engine = Engine::default();
+    let err = Component::pre_initialize(
+        &mut Store::new(&engine, ()),
+        &Linker::new(&engine),
+        br#"
+            (component
+                (core module $m (func (export "init")))
+                (core instance $a (instantiate $m))
+                (core instance $b (instantiate $m))
+                (func (export "init") (canon lift (core func $a "init")))
+            )
+        "#,
+        "init",
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("instantiated more than once"),
+        "{err:?}"
+    );
+    Ok(())
+}
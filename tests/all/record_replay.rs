@@ -0,0 +1,299 @@
+#![cfg(not(miri))]
+
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+use wasmtime::*;
+
+/// An in-memory trace which can be read back after being recorded.
+#[derive(Clone, Default)]
+struct Trace(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Trace {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Trace {
+    fn replayer(&self) -> Result<HostCallReplayer> {
+        HostCallReplayer::new(Cursor::new(self.0.lock().unwrap().clone()))
+    }
+}
+
+const CORE: &str = r#"
+    (module
+        (import "host" "random" (func $random (result i64)))
+        (import "host" "read" (func $read (param i32 i32) (result i32)))
+        (import "host" "fail" (func $fail))
+        (memory (export "memory") 1)
+        (func (export "run") (param $ptr i32) (result i64)
+            call $random
+            (call $read (local.get $ptr) (i32.const 8))
+            i64.extend_i32_u
+            i64.add
+            (i64.load (local.get $ptr))
+            i64.add)
+        (func (export "fail")
+            call $fail)
+    )
+"#;
+
+/// Defines the imports of `CORE`, which return different results on every
+/// call, or panic if `live` is false.
+fn core_linker(engine: &Engine, live: bool) -> Result<Linker<u64>> {
+    let mut linker = Linker::new(engine);
+    linker.func_wrap("host", "random", move |mut caller: Caller<'_, u64>| {
+        assert!(live, "host function called during replay");
+        *caller.data_mut() += 1;
+        Ok(*caller.data() * 0x1234_5678)
+    })?;
+    let ty = FuncType::new(engine, [ValType::I32, ValType::I32], [ValType::I32]);
+    linker.func_new("host", "read", ty, move |mut caller, params, results| {
+        assert!(live, "host function called during replay");
+        let ptr = params[0].unwrap_i32() as usize;
+        let len = params[1].unwrap_i32() as usize;
+        let byte = *caller.data() as u8;
+        let memory = caller.get_export("memory").unwrap().into_memory().unwrap();
+        memory.data_mut(&mut caller)[ptr..][..len].fill(byte);
+        results[0] = Val::I32(len as i32 - 1);
+        Ok(())
+    })?;
+    linker.func_wrap("host", "fail", move || -> Result<()> {
+        assert!(live, "host function called during replay");
+        anyhow::bail!("host function failed")
+    })?;
+    Ok(linker)
+}
+
+#[test]
+fn core_record_and_replay() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, CORE)?;
+    let trace = Trace::default();
+
+    let recorder = HostCallRecorder::new(trace.clone())?;
+    let mut linker = core_linker(&engine, true)?;
+    linker.record_host_calls(recorder.clone());
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let run = instance.get_typed_func::<u32, u64>(&mut store, "run")?;
+    let recorded = [run.call(&mut store, 100)?, run.call(&mut store, 200)?];
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    let recorded_memory = memory.data(&store).to_vec();
+    let err = instance
+        .get_typed_func::<(), ()>(&mut store, "fail")?
+        .call(&mut store, ())
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("host function failed"),
+        "{err:?}"
+    );
+    assert_eq!(recorder.len(), 5);
+
+    let replayer = trace.replayer()?;
+    let mut linker = core_linker(&engine, false)?;
+    linker.replay_host_calls(replayer.clone());
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let run = instance.get_typed_func::<u32, u64>(&mut store, "run")?;
+    let replayed = [run.call(&mut store, 100)?, run.call(&mut store, 200)?];
+    assert_eq!(recorded, replayed);
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(recorded_memory, memory.data(&store));
+    assert!(!replayer.is_finished()?);
+    let err = instance
+        .get_typed_func::<(), ()>(&mut store, "fail")?
+        .call(&mut store, ())
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("host function failed"),
+        "{err:?}"
+    );
+    assert!(replayer.is_finished()?);
+
+    // Making more calls than were recorded is an error.
+    let err = run.call(&mut store, 100).unwrap_err();
+    assert!(format!("{err:?}").contains("trace exhausted"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn core_replay_divergence() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, CORE)?;
+    let trace = Trace::default();
+
+    let mut linker = core_linker(&engine, true)?;
+    linker.record_host_calls(HostCallRecorder::new(trace.clone())?);
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let run = instance.get_typed_func::<u32, u64>(&mut store, "run")?;
+    run.call(&mut store, 100)?;
+
+    // Different parameters to the same function.
+    let mut linker = core_linker(&engine, false)?;
+    linker.replay_host_calls(trace.replayer()?);
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let run = instance.get_typed_func::<u32, u64>(&mut store, "run")?;
+    let err = run.call(&mut store, 104).unwrap_err();
+    assert!(format!("{err:?}").contains("diverged"), "{err:?}");
+
+    // A different function than what was recorded.
+    let mut linker = core_linker(&engine, false)?;
+    linker.replay_host_calls(trace.replayer()?);
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let err = instance
+        .get_typed_func::<(), ()>(&mut store, "fail")?
+        .call(&mut store, ())
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("diverged"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn trace_header_is_checked() {
+    let err = HostCallReplayer::new(Cursor::new(
+        b"this is certainly not a host call trace".to_vec(),
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("not a host call trace"), "{err:?}");
+}
+
+#[test]
+fn component_record_and_replay() -> Result<()> {
+    use wasmtime::component::{Component, Linker, Resource, ResourceType};
+
+    struct Res;
+
+    let engine = Engine::default();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "t" (type $t (sub resource)))
+                (import "now" (func $now (result u64)))
+                (import "name" (func $name (result string)))
+                (import "new" (func $new (result (own $t))))
+                (import "rep" (func $rep (param "x" (borrow $t)) (result u32)))
+
+                (core module $libc
+                    (memory (export "memory") 1)
+                    (global $next (mut i32) (i32.const 1000))
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                        (local $ret i32)
+                        (local.set $ret (global.get $next))
+                        (global.set $next (i32.add (global.get $next) (local.get 3)))
+                        (local.get $ret))
+                )
+                (core instance $libc (instantiate $libc))
+
+                (core func $now (canon lower (func $now)))
+                (core func $name (canon lower (func $name)
+                    (memory $libc "memory") (realloc (func $libc "realloc"))))
+                (core func $new (canon lower (func $new)))
+                (core func $rep (canon lower (func $rep)))
+                (core func $drop (canon resource.drop $t))
+
+                (core module $m
+                    (import "libc" "memory" (memory 1))
+                    (import "host" "now" (func $now (result i64)))
+                    (import "host" "name" (func $name (param i32)))
+                    (import "host" "new" (func $new (result i32)))
+                    (import "host" "rep" (func $rep (param i32) (result i32)))
+                    (import "host" "drop" (func $drop (param i32)))
+                    (func (export "run") (result i64)
+                        (local $h i32)
+                        (local $ret i64)
+                        (local.set $h (call $new))
+                        (call $name (i32.const 8))
+                        (local.set $ret (call $now))
+                        (local.set $ret (i64.add (local.get $ret)
+                            (i64.extend_i32_u (call $rep (local.get $h)))))
+                        (local.set $ret (i64.add (local.get $ret)
+                            (i64.shl (i64.load32_u (i32.const 8)) (i64.const 16))))
+                        (local.set $ret (i64.add (local.get $ret)
+                            (i64.shl (i64.load32_u (i32.const 12)) (i64.const 32))))
+                        (local.set $ret (i64.add (local.get $ret)
+                            (i64.shl
+                                (i64.load8_u (i32.load (i32.const 8)))
+                                (i64.const 48))))
+                        (call $drop (local.get $h))
+                        (local.get $ret))
+                )
+                (core instance $m (instantiate $m
+                    (with "libc" (instance $libc))
+                    (with "host" (instance
+                        (export "now" (func $now))
+                        (export "name" (func $name))
+                        (export "new" (func $new))
+                        (export "rep" (func $rep))
+                        (export "drop" (func $drop))
+                    ))
+                ))
+
+                (func (export "run") (result u64) (canon lift (core func $m "run")))
+            )
+        "#,
+    )?;
+
+    let linker = |live: bool| -> Result<Linker<u32>> {
+        let check = move || assert!(live, "host function called during replay");
+        let mut linker = Linker::new(&engine);
+        let mut root = linker.root();
+        root.resource("t", ResourceType::host::<Res>(), move |_, _| {
+            check();
+            Ok(())
+        })?;
+        root.func_wrap("now", move |mut store, (): ()| {
+            check();
+            *store.data_mut() += 1;
+            Ok((u64::from(*store.data()) * 7,))
+        })?;
+        root.func_wrap("name", move |store, (): ()| {
+            check();
+            Ok((format!("name{}", store.data()),))
+        })?;
+        root.func_wrap("new", move |store, (): ()| {
+            check();
+            Ok((Resource::<Res>::new_own(*store.data() + 40),))
+        })?;
+        root.func_wrap("rep", move |_, (r,): (Resource<Res>,)| {
+            check();
+            Ok((r.rep(),))
+        })?;
+        Ok(linker)
+    };
+
+    let trace = Trace::default();
+    let mut record = linker(true)?;
+    record.record_host_calls(HostCallRecorder::new(trace.clone())?);
+    let mut store = Store::new(&engine, 0);
+    let instance = record.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), (u64,)>(&mut store, "run")?;
+    let mut recorded = Vec::new();
+    for _ in 0..3 {
+        recorded.push(run.call(&mut store, ())?.0);
+        run.post_return(&mut store)?;
+    }
+
+    let replayer = trace.replayer()?;
+    let mut replay = linker(false)?;
+    replay.replay_host_calls(replayer.clone());
+    let mut store = Store::new(&engine, 0);
+    let instance = replay.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), (u64,)>(&mut store, "run")?;
+    let mut replayed = Vec::new();
+    for _ in 0..3 {
+        replayed.push(run.call(&mut store, ())?.0);
+        run.post_return(&mut store)?;
+    }
+    assert_eq!(recorded, replayed);
+    assert!(replayer.is_finished()?);
+    Ok(())
+}
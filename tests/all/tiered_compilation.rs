@@ -0,0 +1,231 @@
+// Winch is only available on some architectures.
+#![cfg(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "riscv64"
+))]
+
+use std::time::{Duration, Instant};
+use wasmtime::*;
+
+fn tiered_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch).tiered_compilation(true);
+    Engine::new(&config)
+}
+
+/// Calls `func` with `0`, which must trap, and returns whether the trapping
+/// frame was running optimized code, which belongs to a module other than the
+/// one that was instantiated.
+fn traps_in_optimized_code(
+    store: &mut Store<()>,
+    module: &Module,
+    func: &TypedFunc<i32, i32>,
+    name: &str,
+) -> Result<bool> {
+    let err = func.call(&mut *store, 0).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::UnreachableCodeReached)
+    );
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    let frame = &trace.frames()[0];
+    assert_eq!(frame.func_name(), Some(name));
+    Ok(!Module::same(frame.module(), module))
+}
+
+/// Repeatedly calls `func`, which adds one to non-zero arguments, until its
+/// callee `name` is switched over to optimized code.
+fn call_until_tiered_up(
+    store: &mut Store<()>,
+    module: &Module,
+    func: &TypedFunc<i32, i32>,
+    name: &str,
+) -> Result<()> {
+    let start = Instant::now();
+    while !traps_in_optimized_code(store, module, func, name)? {
+        assert!(
+            start.elapsed() < Duration::from_secs(60),
+            "`{name}` was never tiered up"
+        );
+        for i in 1..1000 {
+            assert_eq!(func.call(&mut *store, i)?, i + 1);
+        }
+    }
+
+    // Results are unchanged when running optimized code.
+    for i in 1..1000 {
+        assert_eq!(func.call(&mut *store, i)?, i + 1);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tiered_compilation_requires_winch() -> Result<()> {
+    let mut config = Config::new();
+    config
+        .strategy(Strategy::Cranelift)
+        .tiered_compilation(true);
+    let err = Engine::new(&config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "tiered compilation requires the Winch compilation strategy"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn hot_export_is_tiered_up() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $inc (export "inc") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.add))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let inc = instance.get_typed_func::<i32, i32>(&mut store, "inc")?;
+    call_until_tiered_up(&mut store, &module, &inc, "inc")
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn hot_loop_in_table_callee_is_tiered_up() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (table 1 funcref)
+                (elem (i32.const 0) $inc)
+
+                ;; Adds one to its argument, one loop iteration at a time.
+                (func $inc (param i32) (result i32)
+                    (local $i i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    loop $l
+                        local.get $i
+                        i32.const 1
+                        i32.add
+                        local.tee $i
+                        i32.const 100
+                        i32.lt_u
+                        br_if $l
+                    end
+                    local.get 0
+                    local.get $i
+                    i32.add
+                    i32.const 99
+                    i32.sub)
+
+                (func (export "call") (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    call_indirect (type $t)))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    call_until_tiered_up(&mut store, &module, &call, "inc")?;
+
+    // Instances in other stores are switched over as well.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    call_until_tiered_up(&mut store, &module, &call, "inc")
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn hot_direct_callee_is_tiered_up() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $inc (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.add)
+
+                (func (export "call") (param i32) (result i32)
+                    local.get 0
+                    call $inc))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    call_until_tiered_up(&mut store, &module, &call, "inc")
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tiered_up_func_stays_optimized_when_refetched() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (table 1 funcref)
+                (elem declare func $inc)
+
+                (func $inc (export "inc") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.add)
+
+                ;; Stores a fresh `ref.func` of `$inc` in the table and calls
+                ;; it through the table.
+                (func (export "call") (param i32) (result i32)
+                    i32.const 0
+                    ref.func $inc
+                    table.set
+                    local.get 0
+                    i32.const 0
+                    call_indirect (type $t)))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let inc = instance.get_typed_func::<i32, i32>(&mut store, "inc")?;
+    call_until_tiered_up(&mut store, &module, &inc, "inc")?;
+
+    // Fetching the function again, whether through the export or through
+    // `ref.func`, immediately runs the optimized code.
+    let inc = instance.get_typed_func::<i32, i32>(&mut store, "inc")?;
+    assert!(traps_in_optimized_code(&mut store, &module, &inc, "inc")?);
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    assert!(traps_in_optimized_code(&mut store, &module, &call, "inc")?);
+    assert_eq!(call.call(&mut store, 1)?, 2);
+    Ok(())
+}
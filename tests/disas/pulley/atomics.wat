@@ -0,0 +1,102 @@
+;;! target = "pulley64"
+;;! test = "compile"
+
+(module
+  (memory 1 1 shared)
+
+  (func $load (param i32) (result i64) (i64.atomic.load8_u (local.get 0)))
+  (func $store (param i32 i32) (i32.atomic.store (local.get 0) (local.get 1)))
+  (func $rmw_add (param i32 i64) (result i64) (i64.atomic.rmw.add (local.get 0) (local.get 1)))
+  (func $cmpxchg (param i32 i32 i32) (result i32)
+    (i32.atomic.rmw16.cmpxchg_u (local.get 0) (local.get 1) (local.get 2)))
+  (func $fence (atomic.fence))
+  (func $wait (param i32 i32 i64) (result i32)
+    (memory.atomic.wait32 (local.get 0) (local.get 1) (local.get 2)))
+  (func $notify (param i32 i32) (result i32)
+    (memory.atomic.notify (local.get 0) (local.get 1)))
+)
+;; wasm[0]::function[0]::load:
+;;       push_frame
+;;       zext32 x12, x2
+;;       xzero x13
+;;       xload64le_o32_xload64le_o32 x14, x0, 48, x14, x14, 0
+;;       xadd64 x14, x14, x12
+;;       xconst32 x10, 65535
+;;       xult64 x12, x10, x12
+;;       xselect64 x14, x12, x13, x14
+;;       xatomic_load8_u32 x12, x14
+;;       zext8 x0, x12
+;;       pop_frame
+;;       ret
+;;
+;; wasm[0]::function[1]::store:
+;;       push_frame
+;;       xband32_s8 x12, x2, 3
+;;       br_if_xneq32_i8 x12, 0, 0x31    // target = 0x64
+;;   3a: zext32 x13, x2
+;;       xzero x14
+;;       xload64le_o32_xload64le_o32 x15, x0, 48, x15, x15, 0
+;;       xadd64 x15, x15, x13
+;;       xconst32 x11, 65532
+;;       xult64 x13, x11, x13
+;;       xselect64 x15, x13, x14, x15
+;;       xatomic_store32le x15, x3
+;;       pop_frame
+;;       ret
+;;   64: trap
+;;
+;; wasm[0]::function[2]::rmw_add:
+;;       push_frame
+;;       xband32_s8 x13, x2, 7
+;;       br_if_xneq32_i8 x13, 0, 0x32    // target = 0x9e
+;;   73: zext32 x14, x2
+;;       xzero x15
+;;       xload64le_o32_xload64le_o32 x0, x0, 48, x0, x0, 0
+;;       xadd64 x0, x0, x14
+;;       xconst32 x12, 65528
+;;       xult64 x14, x12, x14
+;;       xselect64 x0, x14, x15, x0
+;;       xatomic_rmw_add64le x0, x0, x3
+;;       pop_frame
+;;       ret
+;;   9e: trap
+;;
+;; wasm[0]::function[3]::cmpxchg:
+;;       push_frame
+;;       xmov x5, x0
+;;       xband32_s8 x15, x2, 1
+;;       br_if_xneq32_i8 x15, 0, 0x36    // target = 0xdf
+;;   b0: zext32 x0, x2
+;;       xzero x1
+;;       xload64le_o32_xload64le_o32 x2, x5, 48, x2, x2, 0
+;;       xadd64 x2, x2, x0
+;;       xconst32 x14, 65534
+;;       xult64 x0, x14, x0
+;;       xselect64 x2, x0, x1, x2
+;;       xatomic_cas16le x0, x2, x3, x4
+;;       zext16 x0, x0
+;;       pop_frame
+;;       ret
+;;   df: trap
+;;
+;; wasm[0]::function[4]::fence:
+;;       push_frame
+;;       fence
+;;       pop_frame
+;;       ret
+;;
+;; wasm[0]::function[5]::wait:
+;;       push_frame
+;;       xzero x7
+;;       zext32 x8, x2
+;;       call3 x0, x7, x8, 0x427    // target = 0x515
+;;       pop_frame
+;;       ret
+;;
+;; wasm[0]::function[6]::notify:
+;;       push_frame
+;;       xzero x6
+;;       zext32 x7, x2
+;;       call3 x0, x6, x7, 0x49b    // target = 0x599
+;;       pop_frame
+;;       ret
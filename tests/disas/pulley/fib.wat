@@ -50,22 +50,20 @@
 )
 ;; wasm[0]::function[0]::fib:
 ;;       push_frame_save 32, x17, x24, x29
-;;       br_if_xeq32_i8 x2, 0, 0x47    // target = 0x4c
-;;       br_if_xeq32_i8 x2, 1, 0x39    // target = 0x45
+;;       br_if_xeq32_i8 x2, 0, 0x45    // target = 0x4a
+;;       br_if_xeq32_i8 x2, 1, 0x37    // target = 0x43
 ;;   13: xsub32_u8 x14, x2, 1
-;;       xmov x24, x0
-;;       xmov x29, x2
-;;       call3 x24, x24, x14, -0x1d    // target = 0x0
-;;       xmov x17, x0
-;;       xmov x2, x29
+;;       xmov_xmov x24, x0, x29, x2
+;;       call3 x24, x24, x14, -0x1c    // target = 0x0
+;;       xmov_xmov x17, x0, x2, x29
 ;;       xmov x0, x24
 ;;       xsub32_u8 x14, x2, 2
-;;       call3 x0, x0, x14, -0x32    // target = 0x0
+;;       call3 x0, x0, x14, -0x30    // target = 0x0
 ;;       xmov x5, x17
 ;;       xadd32 x0, x5, x0
-;;       jump 0xe    // target = 0x4e
-;;   45: xone x0
-;;       jump 0x7    // target = 0x4e
-;;   4c: xone x0
+;;       jump 0xe    // target = 0x4c
+;;   43: xone x0
+;;       jump 0x7    // target = 0x4c
+;;   4a: xone x0
 ;;       pop_frame_restore 32, x17, x24, x29
 ;;       ret
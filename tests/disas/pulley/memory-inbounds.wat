@@ -50,8 +50,7 @@
 ;;       push_frame
 ;;       xload64le_o32 x5, x0, 64
 ;;       xconst32 x6, 65533
-;;       xload64le_o32 x7, x0, 56
-;;       xload32le_g32 x0, x7, x5, x6, 0
+;;       xload64le_o32_xload32le_g32 x7, x0, 56, x0, x7, x5, x6, 0
 ;;       pop_frame
 ;;       ret
 ;;
@@ -79,8 +78,7 @@
 ;;       push_frame
 ;;       xload64le_o32 x5, x0, 64
 ;;       xconst32 x6, 131068
-;;       xload64le_o32 x7, x0, 56
-;;       xload32le_g32 x0, x7, x5, x6, 0
+;;       xload64le_o32_xload32le_g32 x7, x0, 56, x0, x7, x5, x6, 0
 ;;       pop_frame
 ;;       ret
 ;;
@@ -101,8 +99,7 @@
 ;;       push_frame
 ;;       xload64le_o32 x5, x0, 64
 ;;       xconst32 x6, 131069
-;;       xload64le_o32 x7, x0, 56
-;;       xload32le_g32 x0, x7, x5, x6, 0
+;;       xload64le_o32_xload32le_g32 x7, x0, 56, x0, x7, x5, x6, 0
 ;;       pop_frame
 ;;       ret
 ;;
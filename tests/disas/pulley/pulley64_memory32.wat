@@ -59,9 +59,8 @@
 )
 ;; wasm[0]::function[0]::load8:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x47
-;;       xload64le_o32 x6, x0, 56
 ;;       xload8_u32_g32 x0, x6, x5, x2, 0
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x4a
@@ -69,9 +68,8 @@
 ;;
 ;; wasm[0]::function[1]::load16:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x4f
-;;       xload64le_o32 x6, x0, 56
 ;;       xload16le_u32_g32 x0, x6, x5, x2, 0
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x52
@@ -79,9 +77,8 @@
 ;;
 ;; wasm[0]::function[2]::load32:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x57
-;;       xload64le_o32 x6, x0, 56
 ;;       xload32le_g32 x0, x6, x5, x2, 0
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x5a
@@ -89,9 +86,8 @@
 ;;
 ;; wasm[0]::function[3]::load64:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x5f
-;;       xload64le_o32 x6, x0, 56
 ;;       xload64le_g32 x0, x6, x5, x2, 0
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x62
@@ -99,9 +95,8 @@
 ;;
 ;; wasm[0]::function[4]::store8:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x69
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore8_g32 x6, x5, x2, 0, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x6c
@@ -109,9 +104,8 @@
 ;;
 ;; wasm[0]::function[5]::store16:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x73
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore16le_g32 x6, x5, x2, 0, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x76
@@ -119,9 +113,8 @@
 ;;
 ;; wasm[0]::function[6]::store32:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x7d
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore32le_g32 x6, x5, x2, 0, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x80
@@ -129,9 +122,8 @@
 ;;
 ;; wasm[0]::function[7]::store64:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x87
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore64le_g32 x6, x5, x2, 0, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x8a
@@ -139,9 +131,8 @@
 ;;
 ;; wasm[0]::function[8]::load8_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x8f
-;;       xload64le_o32 x6, x0, 56
 ;;       xload8_u32_g32 x0, x6, x5, x2, 32
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x92
@@ -149,9 +140,8 @@
 ;;
 ;; wasm[0]::function[9]::load16_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x97
-;;       xload64le_o32 x6, x0, 56
 ;;       xload16le_u32_g32 x0, x6, x5, x2, 32
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0x9a
@@ -159,9 +149,8 @@
 ;;
 ;; wasm[0]::function[10]::load32_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0x9f
-;;       xload64le_o32 x6, x0, 56
 ;;       xload32le_g32 x0, x6, x5, x2, 32
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0xa2
@@ -169,9 +158,8 @@
 ;;
 ;; wasm[0]::function[11]::load64_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0xa7
-;;       xload64le_o32 x6, x0, 56
 ;;       xload64le_g32 x0, x6, x5, x2, 32
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0xaa
@@ -179,9 +167,8 @@
 ;;
 ;; wasm[0]::function[12]::store8_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0xb1
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore8_g32 x6, x5, x2, 8, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0xb4
@@ -189,9 +176,8 @@
 ;;
 ;; wasm[0]::function[13]::store16_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0xbb
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore16le_g32 x6, x5, x2, 8, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0xbe
@@ -199,9 +185,8 @@
 ;;
 ;; wasm[0]::function[14]::store32_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0xc5
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore32le_g32 x6, x5, x2, 8, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0xc8
@@ -209,9 +194,8 @@
 ;;
 ;; wasm[0]::function[15]::store64_offset:
 ;;       push_frame
-;;       xload64le_o32 x5, x0, 64
+;;       xload64le_o32_xload64le_o32 x5, x0, 64, x6, x0, 56
 ;;       ╰─╼ addrmap: 0xcf
-;;       xload64le_o32 x6, x0, 56
 ;;       xstore64le_g32 x6, x5, x2, 8, x3
 ;;       pop_frame
 ;;       ╰─╼ addrmap: 0xd2
@@ -219,9 +203,8 @@
 ;;
 ;; wasm[0]::function[16]::load16_two:
 ;;       push_frame
-;;       xload64le_o32 x7, x0, 64
+;;       xload64le_o32_xload64le_o32 x7, x0, 64, x8, x0, 56
 ;;       ╰─╼ addrmap: 0xd7
-;;       xload64le_o32 x8, x0, 56
 ;;       xload16le_u32_g32 x0, x8, x7, x2, 0
 ;;       xload16le_u32_g32 x1, x8, x7, x3, 0
 ;;       ╰─╼ addrmap: 0xdc
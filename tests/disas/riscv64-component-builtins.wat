@@ -35,7 +35,7 @@
 ;;
 ;; block1 cold:
 ;;     v15 = load.i64 notrap aligned readonly v1+16
-;;     v16 = load.i64 notrap aligned readonly v15+408
+;;     v16 = load.i64 notrap aligned readonly v15+432
 ;;     call_indirect sig1, v16(v1)
 ;;     trap user1
 ;;
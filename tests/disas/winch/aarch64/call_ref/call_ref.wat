@@ -0,0 +1,154 @@
+;;! target = "aarch64"
+;;! test = "winch"
+;;! flags = "-W function-references"
+
+(module
+  (type $t (func (param i32) (result i32)))
+
+  (func $callee (type $t)
+    local.get 0)
+  (elem declare func $callee)
+
+  (func (export "call") (param i32) (result i32)
+    local.get 0
+    ref.func $callee
+    call_ref $t)
+
+  (func (export "return_call") (param (ref null $t)) (result i32)
+    i32.const 1
+    local.get 0
+    return_call_ref $t)
+)
+;; wasm[0]::function[0]::callee:
+;;       stp     x29, x30, [sp, #-0x10]!
+;;       mov     x29, sp
+;;       str     x28, [sp, #-0x10]!
+;;       mov     x28, sp
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16, #0x10]
+;;       mov     x17, #0
+;;       movk    x17, #0x18
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x60
+;;   2c: mov     x9, x0
+;;       sub     x28, x28, #0x18
+;;       mov     sp, x28
+;;       stur    x0, [x28, #0x10]
+;;       stur    x1, [x28, #8]
+;;       stur    w2, [x28, #4]
+;;       ldur    w0, [x28, #4]
+;;       add     x28, x28, #0x18
+;;       mov     sp, x28
+;;       mov     sp, x28
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       ret
+;;   60: .byte   0x1f, 0xc1, 0x00, 0x00
+;;
+;; wasm[0]::function[1]:
+;;       stp     x29, x30, [sp, #-0x10]!
+;;       mov     x29, sp
+;;       str     x28, [sp, #-0x10]!
+;;       mov     x28, sp
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16, #0x10]
+;;       mov     x17, #0
+;;       movk    x17, #0x24
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x164
+;;   ac: mov     x9, x0
+;;       sub     x28, x28, #0x18
+;;       mov     sp, x28
+;;       stur    x0, [x28, #0x10]
+;;       stur    x1, [x28, #8]
+;;       stur    w2, [x28, #4]
+;;       ldur    w16, [x28, #4]
+;;       sub     x28, x28, #4
+;;       mov     sp, x28
+;;       stur    w16, [x28]
+;;       sub     x28, x28, #4
+;;       mov     sp, x28
+;;       mov     x0, x9
+;;       mov     x1, #0
+;;       bl      #0x584
+;;   e8: add     x28, x28, #4
+;;       mov     sp, x28
+;;       ldur    x9, [x28, #0x14]
+;;       sub     sp, x28, #4
+;;       cbz     x0, #0x168
+;;   fc: mov     sp, x28
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x0, [x28]
+;;       ldur    x3, [x28]
+;;       add     x28, x28, #8
+;;       mov     sp, x28
+;;       ldur    x5, [x3, #0x18]
+;;       ldur    x4, [x3, #8]
+;;       sub     x28, x28, #4
+;;       mov     sp, x28
+;;       mov     x0, x5
+;;       mov     x1, x9
+;;       ldur    w2, [x28, #4]
+;;       blr     x4
+;;  138: add     x28, x28, #4
+;;       mov     sp, x28
+;;       add     x28, x28, #4
+;;       mov     sp, x28
+;;       ldur    x9, [x28, #0x10]
+;;       add     x28, x28, #0x18
+;;       mov     sp, x28
+;;       mov     sp, x28
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       ret
+;;  164: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  168: .byte   0x1f, 0xc1, 0x00, 0x00
+;;
+;; wasm[0]::function[2]:
+;;       stp     x29, x30, [sp, #-0x10]!
+;;       mov     x29, sp
+;;       str     x28, [sp, #-0x10]!
+;;       mov     x28, sp
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16, #0x10]
+;;       mov     x17, #0
+;;       movk    x17, #0x20
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x228
+;;  1ac: mov     x9, x0
+;;       sub     x28, x28, #0x18
+;;       mov     sp, x28
+;;       stur    x0, [x28, #0x10]
+;;       stur    x1, [x28, #8]
+;;       stur    x2, [x28]
+;;       ldur    x0, [x28]
+;;       sub     sp, x28, #8
+;;       cbz     x0, #0x22c
+;;  1d0: mov     sp, x28
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x0, [x28]
+;;       ldur    x3, [x28]
+;;       add     x28, x28, #8
+;;       mov     sp, x28
+;;       ldur    x5, [x3, #0x18]
+;;       ldur    x4, [x3, #8]
+;;       mov     x0, x5
+;;       mov     x1, x9
+;;       mov     x2, #1
+;;       sub     sp, x29, #0x10
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       br      x4
+;;  210: add     x28, x28, #0x18
+;;       mov     sp, x28
+;;       mov     sp, x28
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       ret
+;;  228: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  22c: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,180 @@
+;;! target = "aarch64"
+;;! test = "winch"
+
+(module
+  (func $callee (param i64 i64 i64 i64 i64 i64 i64) (result i64)
+    local.get 6)
+
+  ;; The callee's stack arguments fit in the caller's incoming argument area,
+  ;; so they are moved there before jumping to the callee.
+  (func (export "fits") (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+    local.get 7
+    local.get 6
+    local.get 5
+    local.get 4
+    local.get 3
+    local.get 2
+    local.get 1
+    return_call $callee)
+
+  ;; The callee's stack arguments don't fit in the caller's incoming argument
+  ;; area, so a regular call followed by a return is emitted instead.
+  (func (export "doesnt_fit") (result i64)
+    i64.const 1
+    i64.const 2
+    i64.const 3
+    i64.const 4
+    i64.const 5
+    i64.const 6
+    i64.const 7
+    return_call $callee)
+)
+;; wasm[0]::function[0]::callee:
+;;       stp     x29, x30, [sp, #-0x10]!
+;;       mov     x29, sp
+;;       str     x28, [sp, #-0x10]!
+;;       mov     x28, sp
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16, #0x10]
+;;       mov     x17, #0
+;;       movk    x17, #0x40
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x74
+;;   2c: mov     x9, x0
+;;       sub     x28, x28, #0x40
+;;       mov     sp, x28
+;;       stur    x0, [x28, #0x38]
+;;       stur    x1, [x28, #0x30]
+;;       stur    x2, [x28, #0x28]
+;;       stur    x3, [x28, #0x20]
+;;       stur    x4, [x28, #0x18]
+;;       stur    x5, [x28, #0x10]
+;;       stur    x6, [x28, #8]
+;;       stur    x7, [x28]
+;;       ldur    x0, [x29, #0x10]
+;;       add     x28, x28, #0x40
+;;       mov     sp, x28
+;;       mov     sp, x28
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       ret
+;;   74: .byte   0x1f, 0xc1, 0x00, 0x00
+;;
+;; wasm[0]::function[1]:
+;;       stp     x29, x30, [sp, #-0x10]!
+;;       mov     x29, sp
+;;       str     x28, [sp, #-0x10]!
+;;       mov     x28, sp
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16, #0x10]
+;;       mov     x17, #0
+;;       movk    x17, #0x80
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x1a8
+;;   ac: mov     x9, x0
+;;       sub     x28, x28, #0x40
+;;       mov     sp, x28
+;;       stur    x0, [x28, #0x38]
+;;       stur    x1, [x28, #0x30]
+;;       stur    x2, [x28, #0x28]
+;;       stur    x3, [x28, #0x20]
+;;       stur    x4, [x28, #0x18]
+;;       stur    x5, [x28, #0x10]
+;;       stur    x6, [x28, #8]
+;;       stur    x7, [x28]
+;;       ldur    x16, [x29, #0x18]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       ldur    x16, [x29, #0x10]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       ldur    x16, [x28, #0x10]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       ldur    x16, [x28, #0x20]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       ldur    x16, [x28, #0x30]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       ldur    x16, [x28, #0x40]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       ldur    x16, [x28, #0x50]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       stur    x16, [x28]
+;;       sub     x28, x28, #8
+;;       mov     sp, x28
+;;       mov     x0, x9
+;;       mov     x1, x9
+;;       ldur    x2, [x28, #0x38]
+;;       ldur    x3, [x28, #0x30]
+;;       ldur    x4, [x28, #0x28]
+;;       ldur    x5, [x28, #0x20]
+;;       ldur    x6, [x28, #0x18]
+;;       ldur    x7, [x28, #0x10]
+;;       ldur    x16, [x28, #8]
+;;       stur    x16, [x28]
+;;       ldur    x16, [x28]
+;;       stur    x16, [x29, #0x10]
+;;       sub     sp, x29, #0x10
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       b       #0
+;;  190: add     x28, x28, #0x40
+;;       mov     sp, x28
+;;       mov     sp, x28
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       ret
+;;  1a8: .byte   0x1f, 0xc1, 0x00, 0x00
+;;
+;; wasm[0]::function[2]:
+;;       stp     x29, x30, [sp, #-0x10]!
+;;       mov     x29, sp
+;;       str     x28, [sp, #-0x10]!
+;;       mov     x28, sp
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16, #0x10]
+;;       mov     x17, #0
+;;       movk    x17, #0x20
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x258
+;;  1ec: mov     x9, x0
+;;       sub     x28, x28, #0x10
+;;       mov     sp, x28
+;;       stur    x0, [x28, #8]
+;;       stur    x1, [x28]
+;;       sub     x28, x28, #0x10
+;;       mov     sp, x28
+;;       mov     x0, x9
+;;       mov     x1, x9
+;;       mov     x2, #1
+;;       mov     x3, #2
+;;       mov     x4, #3
+;;       mov     x5, #4
+;;       mov     x6, #5
+;;       mov     x7, #6
+;;       mov     x16, #7
+;;       stur    x16, [x28]
+;;       bl      #0
+;;  234: add     x28, x28, #0x10
+;;       mov     sp, x28
+;;       ldur    x9, [x28, #8]
+;;       add     x28, x28, #0x10
+;;       mov     sp, x28
+;;       mov     sp, x28
+;;       ldr     x28, [sp], #0x10
+;;       ldp     x29, x30, [sp], #0x10
+;;       ret
+;;  258: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,29 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+  (func (export "as-br-value") (result i32)
+    (block (result i32) (br 0 (br 0 (i32.const 9))))
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x10
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       addi    a0, zero, 9
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
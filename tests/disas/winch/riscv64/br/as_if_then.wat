@@ -0,0 +1,40 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+  (func (export "as-if-then") (param i32 i32) (result i32)
+    (block (result i32)
+      (if (result i32) (local.get 0)
+        (then (br 1 (i32.const 3)))
+        (else (local.get 1))
+      )
+    )
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       sw      a3, 0(sp)
+;;       lwu     t0, 4(sp)
+;;       beqz    t0, 0xc
+;;       addi    a0, zero, 3
+;;       j       8
+;;       lwu     a0, 0(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,43 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+  (func (export "as-local-set-value") (param i32) (result i32)
+    (local i32)
+    (block (result i32)
+      (local.set 0 (br_if 0 (i32.const 17) (local.get 0)))
+      (i32.const -1)
+    )
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       mv      t3, zero
+;;       sw      t3, 0(sp)
+;;       mv      t3, zero
+;;       lwu     t0, 4(sp)
+;;       addi    a0, zero, 0x11
+;;       bnez    t0, 0x14
+;;       sw      a0, 4(sp)
+;;       addi    a0, zero, -1
+;;       slli    a0, a0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,76 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+  (func (export "nested-br_table-loop-block") (param i32) (result i32)
+    (local.set 0
+      (loop (result i32)
+        (block
+          (br_table 1 0 0 (local.get 0))
+        )
+        (i32.const 0)
+      )
+    )
+    (loop (result i32)
+      (block
+        (br_table 0 1 1 (local.get 0))
+      )
+      (i32.const 3)
+    )
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       lwu     t0, 4(sp)
+;;       slli    t6, t0, 0x20
+;;       srli    t6, t6, 0x20
+;;       addi    t1, zero, 2
+;;       bltu    t6, t1, 0xc
+;;       auipc   t1, 0
+;;       jalr    zero, t1, 0x2c
+;;       auipc   t3, 0
+;;       slli    t1, t6, 3
+;;       add     t3, t3, t1
+;;       jalr    zero, t3, 0x10
+;;       auipc   t1, 0
+;;       jalr    zero, t1, -0x2c
+;;       auipc   t1, 0
+;;       jalr    zero, t1, 0xc
+;;       j       -0x3c
+;;       mv      t0, zero
+;;       sw      t0, 4(sp)
+;;       lwu     t0, 4(sp)
+;;       slli    t6, t0, 0x20
+;;       srli    t6, t6, 0x20
+;;       addi    t1, zero, 2
+;;       bltu    t6, t1, 0xc
+;;       auipc   t1, 0
+;;       jalr    zero, t1, -0x14
+;;       auipc   t3, 0
+;;       slli    t1, t6, 3
+;;       add     t3, t3, t1
+;;       jalr    zero, t3, 0x10
+;;       auipc   t1, 0
+;;       jalr    zero, t1, 0x10
+;;       auipc   t1, 0
+;;       jalr    zero, t1, -0x34
+;;       addi    a0, zero, 3
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
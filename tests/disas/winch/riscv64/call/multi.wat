@@ -0,0 +1,74 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+  (func $multi (result i32 i32)
+        i32.const 1
+        i32.const 2)
+
+  (func $start
+        call $multi
+        drop
+        drop)
+)
+;; wasm[0]::function[0]::multi:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a1)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x1c
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a1
+;;       addi    sp, sp, -0x18
+;;       sd      a1, 0x10(sp)
+;;       sd      a2, 8(sp)
+;;       sd      a0, 0(sp)
+;;       addi    a0, zero, 2
+;;       addi    sp, sp, -4
+;;       addi    t3, zero, 1
+;;       sw      t3, 0(sp)
+;;       ld      t0, 4(sp)
+;;       lwu     t3, 0(sp)
+;;       addi    sp, sp, 4
+;;       sw      t3, 0(t0)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[1]::start:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       addi    sp, sp, -4
+;;       addi    sp, sp, -0xc
+;;       mv      a1, s1
+;;       mv      a2, s1
+;;       addi    a0, sp, 0xc
+;;       auipc   ra, 0
+;;       jalr    ra, ra, -0xc4
+;;       addi    sp, sp, 0xc
+;;       ld      s1, 0xc(sp)
+;;       addi    sp, sp, 4
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,180 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+  (func (export "main") (param i32) (param i32) (result i32)
+    (local.get 1)
+    (local.get 0)
+    (i32.add)
+
+    (call $add (i32.const 1) (i32.const 2) (i32.const 3) (i32.const 4) (i32.const 5) (i32.const 6) (i32.const 7) (i32.const 8))
+
+    (local.get 1)
+    (local.get 0)
+    (i32.add)
+
+    (call $add (i32.const 2) (i32.const 3) (i32.const 4) (i32.const 5) (i32.const 6) (i32.const 7) (i32.const 8))
+  )
+
+  (func $add (param i32 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)
+    (local.get 0)
+    (local.get 1)
+    (i32.add)
+    (local.get 2)
+    (i32.add)
+    (local.get 3)
+    (i32.add)
+    (local.get 4)
+    (i32.add)
+    (local.get 5)
+    (i32.add)
+    (local.get 6)
+    (i32.add)
+    (local.get 7)
+    (i32.add)
+    (local.get 8)
+    (i32.add)
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x40
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       sw      a3, 0(sp)
+;;       lwu     t0, 4(sp)
+;;       lwu     t1, 0(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       addi    sp, sp, -4
+;;       sw      t1, 0(sp)
+;;       addi    sp, sp, -0x24
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       lwu     a2, 0x24(sp)
+;;       addi    a3, zero, 1
+;;       addi    a4, zero, 2
+;;       addi    a5, zero, 3
+;;       addi    a6, zero, 4
+;;       addi    a7, zero, 5
+;;       addi    t3, zero, 6
+;;       sw      t3, 0(sp)
+;;       addi    t3, zero, 7
+;;       sw      t3, 8(sp)
+;;       addi    t3, zero, 8
+;;       sw      t3, 0x10(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, 0x9c
+;;       addi    sp, sp, 0x24
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x10(sp)
+;;       lwu     t0, 4(sp)
+;;       lwu     t1, 0(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       addi    sp, sp, -4
+;;       sw      a0, 0(sp)
+;;       addi    sp, sp, -4
+;;       sw      t1, 0(sp)
+;;       addi    sp, sp, -0x20
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       lwu     a2, 0x24(sp)
+;;       lwu     a3, 0x20(sp)
+;;       addi    a4, zero, 2
+;;       addi    a5, zero, 3
+;;       addi    a6, zero, 4
+;;       addi    a7, zero, 5
+;;       addi    t3, zero, 6
+;;       sw      t3, 0(sp)
+;;       addi    t3, zero, 7
+;;       sw      t3, 8(sp)
+;;       addi    t3, zero, 8
+;;       sw      t3, 0x10(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, 0x28
+;;       addi    sp, sp, 0x20
+;;       addi    sp, sp, 8
+;;       ld      s1, 0x10(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[1]::add:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x28
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x28
+;;       sd      a0, 0x20(sp)
+;;       sd      a1, 0x18(sp)
+;;       sw      a2, 0x14(sp)
+;;       sw      a3, 0x10(sp)
+;;       sw      a4, 0xc(sp)
+;;       sw      a5, 8(sp)
+;;       sw      a6, 4(sp)
+;;       sw      a7, 0(sp)
+;;       lwu     t0, 0x10(sp)
+;;       lwu     t1, 0x14(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 0xc(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 8(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 4(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 0(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 0x10(s0)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 0x18(s0)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       lwu     t0, 0x20(s0)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x28
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,92 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+  (func $fibonacci8 (param $n i32) (result i32)
+    (if (result i32) (i32.le_s (local.get $n) (i32.const 1))
+      (then
+        ;; If n <= 1, return n (base case)
+        (local.get $n)
+      )
+      (else
+        ;; Else, return fibonacci(n - 1) + fibonacci(n - 2)
+        (i32.add
+          (call $fibonacci8
+            (i32.sub (local.get $n) (i32.const 1)) ;; Calculate n - 1
+          )
+          (call $fibonacci8
+            (i32.sub (local.get $n) (i32.const 2)) ;; Calculate n - 2
+          )
+        )
+      )
+    )
+  )
+  (export "fib" (func $fibonacci8))
+)
+;; wasm[0]::function[0]::fibonacci8:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       lwu     t0, 4(sp)
+;;       sext.w  t3, t0
+;;       addi    t4, zero, 1
+;;       slt     t0, t4, t3
+;;       xori    t0, t0, 1
+;;       beqz    t0, 0xc
+;;       lwu     a0, 4(sp)
+;;       j       0x98
+;;       lwu     t0, 4(sp)
+;;       addi    t0, t0, -1
+;;       slli    t0, t0, 0x20
+;;       srli    t0, t0, 0x20
+;;       addi    sp, sp, -4
+;;       sw      t0, 0(sp)
+;;       addi    sp, sp, -4
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       lwu     a2, 4(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, -0x88
+;;       addi    sp, sp, 4
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x10(sp)
+;;       lwu     t0, 4(sp)
+;;       addi    t0, t0, -2
+;;       slli    t0, t0, 0x20
+;;       srli    t0, t0, 0x20
+;;       addi    sp, sp, -4
+;;       sw      a0, 0(sp)
+;;       addi    sp, sp, -4
+;;       sw      t0, 0(sp)
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       lwu     a2, 0(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, -0xc8
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x14(sp)
+;;       lwu     t0, 0(sp)
+;;       addi    sp, sp, 4
+;;       add     t0, t0, a0
+;;       slli    t0, t0, 0x20
+;;       srli    t0, t0, 0x20
+;;       slli    a0, t0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
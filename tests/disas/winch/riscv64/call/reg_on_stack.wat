@@ -0,0 +1,65 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+  (func (export "") (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    call 0
+    i32.const 1
+    call 0
+    br_if 0 (;@0;)
+    unreachable
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x24
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       lwu     t3, 4(sp)
+;;       addi    sp, sp, -4
+;;       sw      t3, 0(sp)
+;;       addi    sp, sp, -4
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       addi    a2, zero, 1
+;;       auipc   ra, 0
+;;       jalr    ra, ra, -0x5c
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x14(sp)
+;;       addi    sp, sp, -4
+;;       sw      a0, 0(sp)
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       addi    a2, zero, 1
+;;       auipc   ra, 0
+;;       jalr    ra, ra, -0x80
+;;       ld      s1, 0x18(sp)
+;;       addi    sp, sp, -4
+;;       sw      a0, 0(sp)
+;;       lwu     t0, 0(sp)
+;;       addi    sp, sp, 4
+;;       lwu     a0, 0(sp)
+;;       addi    sp, sp, 4
+;;       beqz    t0, 0xc
+;;       addi    sp, sp, 4
+;;       j       8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,85 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+  (func $main (result i32)
+    (local $var i32)
+    (call $add (i32.const 20) (i32.const 80))
+    (local.set $var (i32.const 2))
+    (local.get $var)
+    (i32.add))
+
+  (func $add (param i32 i32) (result i32)
+    (local.get 0)
+    (local.get 1)
+    (i32.add))
+)
+;; wasm[0]::function[0]::main:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       mv      t3, zero
+;;       sd      t3, 0(sp)
+;;       addi    sp, sp, -8
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       addi    a2, zero, 0x14
+;;       addi    a3, zero, 0x50
+;;       auipc   ra, 0
+;;       jalr    ra, ra, 0x3c
+;;       addi    sp, sp, 8
+;;       ld      s1, 0x10(sp)
+;;       addi    t0, zero, 2
+;;       sw      t0, 4(sp)
+;;       lwu     t0, 4(sp)
+;;       add     a0, a0, t0
+;;       slli    a0, a0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[1]::add:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       sw      a3, 0(sp)
+;;       lwu     t0, 0(sp)
+;;       lwu     t1, 4(sp)
+;;       add     t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,114 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (type $param-i32 (func (param i32)))
+
+    (func $param-i32 (type $param-i32))
+    (func (export "")
+        (local i32)
+        local.get 0
+        (call_indirect (type $param-i32) (i32.const 0))
+    )
+
+    (table funcref
+      (elem
+        $param-i32)
+    )
+)
+;; wasm[0]::function[0]::param-i32:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[1]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x24
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       mv      t3, zero
+;;       sd      t3, 0(sp)
+;;       lwu     t3, 4(sp)
+;;       addi    sp, sp, -4
+;;       sw      t3, 0(sp)
+;;       mv      t0, zero
+;;       mv      t1, s1
+;;       ld      t2, 0x38(t1)
+;;       bltu    t0, t2, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      t3, t0
+;;       addi    t4, zero, 8
+;;       mul     t3, t3, t4
+;;       ld      t1, 0x30(t1)
+;;       mv      a1, t1
+;;       add     t1, t1, t3
+;;       bltu    t0, t2, 8
+;;       mv      t1, a1
+;;       ld      a0, 0(t1)
+;;       bnez    a0, 0x2c
+;;       addi    sp, sp, -4
+;;       sw      t0, 0(sp)
+;;       mv      a0, s1
+;;       mv      a1, zero
+;;       lwu     a2, 0(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, 0x3d4
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x14(sp)
+;;       j       8
+;;       andi    a0, a0, -2
+;;       bnez    a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      t3, 0x28(s1)
+;;       lwu     t0, 0(t3)
+;;       lwu     t1, 0x10(a0)
+;;       beq     t0, t1, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       addi    sp, sp, -8
+;;       sd      a0, 0(sp)
+;;       ld      t0, 0(sp)
+;;       addi    sp, sp, 8
+;;       ld      t2, 0x18(t0)
+;;       ld      t1, 8(t0)
+;;       addi    sp, sp, -4
+;;       mv      a0, t2
+;;       mv      a1, s1
+;;       lwu     a2, 4(sp)
+;;       jalr    t1
+;;       addi    sp, sp, 4
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x10(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
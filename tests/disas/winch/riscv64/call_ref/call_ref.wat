@@ -0,0 +1,129 @@
+;;! target = "riscv64"
+;;! test = "winch"
+;;! flags = "-W function-references"
+
+(module
+  (type $t (func (param i32) (result i32)))
+
+  (func $callee (type $t)
+    local.get 0)
+  (elem declare func $callee)
+
+  (func (export "call") (param i32) (result i32)
+    local.get 0
+    ref.func $callee
+    call_ref $t)
+
+  (func (export "return_call") (param (ref null $t)) (result i32)
+    i32.const 1
+    local.get 0
+    return_call_ref $t)
+)
+;; wasm[0]::function[0]::callee:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       lwu     a0, 4(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[1]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x24
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sw      a2, 4(sp)
+;;       lwu     t3, 4(sp)
+;;       addi    sp, sp, -4
+;;       sw      t3, 0(sp)
+;;       addi    sp, sp, -4
+;;       mv      a0, s1
+;;       mv      a1, zero
+;;       auipc   ra, 0
+;;       jalr    ra, ra, 0x5c8
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x14(sp)
+;;       bnez    a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       addi    sp, sp, -8
+;;       sd      a0, 0(sp)
+;;       ld      t0, 0(sp)
+;;       addi    sp, sp, 8
+;;       ld      t2, 0x18(t0)
+;;       ld      t1, 8(t0)
+;;       addi    sp, sp, -4
+;;       mv      a0, t2
+;;       mv      a1, s1
+;;       lwu     a2, 4(sp)
+;;       jalr    t1
+;;       addi    sp, sp, 4
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x10(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[2]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sd      a2, 0(sp)
+;;       ld      t0, 0(sp)
+;;       bnez    t0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      t2, 0x18(t0)
+;;       ld      t1, 8(t0)
+;;       mv      a0, t2
+;;       mv      a1, s1
+;;       addi    a2, zero, 1
+;;       mv      sp, s0
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       jr      t1
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
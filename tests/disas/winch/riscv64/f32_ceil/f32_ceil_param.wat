@@ -0,0 +1,42 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (param f32) (result f32)
+        (local.get 0)
+        (f32.ceil)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       fsw     fa0, 4(sp)
+;;       flw     ft11, 4(sp)
+;;       addi    sp, sp, -4
+;;       fsw     ft11, 0(sp)
+;;       addi    sp, sp, -4
+;;       mv      a0, s1
+;;       flw     fa0, 4(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, 0xc0
+;;       addi    sp, sp, 4
+;;       addi    sp, sp, 4
+;;       ld      s1, 0x10(sp)
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
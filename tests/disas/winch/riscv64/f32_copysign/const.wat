@@ -0,0 +1,42 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result f32)
+        (f32.const -1.1)
+        (f32.const 2.2)
+        (f32.copysign)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x10
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       lui     t6, 0x400cd
+;;       addi    t6, t6, -0x333
+;;       fmv.w.x ft0, t6
+;;       auipc   t6, 0
+;;       ld      t6, 0xc(t6)
+;;       j       0xc
+;;       .byte   0xcd, 0xcc, 0x8c, 0xbf
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       fmv.w.x ft1, t6
+;;       fsgnj.s ft1, ft1, ft0
+;;       fmv.s   fa0, ft1
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
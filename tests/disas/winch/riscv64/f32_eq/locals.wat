@@ -0,0 +1,52 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result i32)
+        (local $foo f32)
+        (local $bar f32)
+
+        (f32.const 2)
+        (local.set $foo)
+        (f32.const 3)
+        (local.set $bar)
+
+        (local.get $foo)
+        (local.get $bar)
+        (f32.eq)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       mv      t3, zero
+;;       sd      t3, 0(sp)
+;;       lui     t6, 0x40000
+;;       fmv.w.x ft0, t6
+;;       fsw     ft0, 4(sp)
+;;       lui     t6, 0x40400
+;;       fmv.w.x ft0, t6
+;;       fsw     ft0, 0(sp)
+;;       flw     ft0, 0(sp)
+;;       flw     ft1, 4(sp)
+;;       feq.s   t0, ft1, ft0
+;;       slli    a0, t0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,61 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result f64)
+        (local $foo f64)  
+        (local $bar f64)
+
+        (f64.const -1.1)
+        (local.set $foo)
+
+        (f64.const 2.2)
+        (local.set $bar)
+
+        (local.get $foo)
+        (local.get $bar)
+        f64.copysign
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x20
+;;       sd      a0, 0x18(sp)
+;;       sd      a1, 0x10(sp)
+;;       mv      t3, zero
+;;       sd      t3, 8(sp)
+;;       sd      t3, 0(sp)
+;;       auipc   t6, 0
+;;       ld      t6, 0xc(t6)
+;;       j       0xc
+;;       .byte   0x9a, 0x99, 0x99, 0x99
+;;       .byte   0x99, 0x99, 0xf1, 0xbf
+;;       fmv.d.x ft0, t6
+;;       fsd     ft0, 8(sp)
+;;       auipc   t6, 0
+;;       ld      t6, 0xc(t6)
+;;       j       0xc
+;;       .byte   0x9a, 0x99, 0x99, 0x99
+;;       .byte   0x99, 0x99, 0x01, 0x40
+;;       fmv.d.x ft0, t6
+;;       fsd     ft0, 0(sp)
+;;       fld     ft0, 0(sp)
+;;       fld     ft1, 8(sp)
+;;       fsgnj.d ft1, ft1, ft0
+;;       fmv.d   fa0, ft1
+;;       addi    sp, sp, 0x20
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
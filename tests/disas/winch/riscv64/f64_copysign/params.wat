@@ -0,0 +1,37 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (param f64) (param f64) (result f64)
+        (local.get 0)
+        (local.get 1)
+        (f64.copysign)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x20
+;;       sd      a0, 0x18(sp)
+;;       sd      a1, 0x10(sp)
+;;       fsd     fa0, 8(sp)
+;;       fsd     fa1, 0(sp)
+;;       fld     ft0, 0(sp)
+;;       fld     ft1, 8(sp)
+;;       fsgnj.d ft1, ft1, ft0
+;;       fmv.d   fa0, ft1
+;;       addi    sp, sp, 0x20
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,45 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result i32)
+	(i32.const 10)
+	(i32.clz)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x10
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       addi    t0, zero, 0xa
+;;       mv      t3, zero
+;;       addi    t4, zero, 0x20
+;;       addi    t6, zero, 1
+;;       slli    t6, t6, 0x1f
+;;       blez    t4, 0x1c
+;;       and     t5, t6, t0
+;;       bne     zero, t5, 0x14
+;;       addi    t3, t3, 1
+;;       addi    t4, t4, -1
+;;       srli    t6, t6, 1
+;;       j       -0x18
+;;       mv      t0, t3
+;;       slli    a0, t0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
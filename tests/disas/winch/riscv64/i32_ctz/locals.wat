@@ -0,0 +1,49 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result i32)
+        (local $foo i32)  
+        (i32.const 10)
+        (local.tee $foo)
+        i32.ctz
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       mv      t3, zero
+;;       sd      t3, 0(sp)
+;;       addi    t0, zero, 0xa
+;;       sw      t0, 4(sp)
+;;       mv      t3, zero
+;;       addi    t4, zero, 0x20
+;;       addi    t6, zero, 1
+;;       blez    t4, 0x1c
+;;       and     t5, t6, t0
+;;       bne     zero, t5, 0x14
+;;       addi    t3, t3, 1
+;;       addi    t4, t4, -1
+;;       slli    t6, t6, 1
+;;       j       -0x18
+;;       mv      t0, t3
+;;       slli    a0, t0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,50 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result i32)
+	(i32.const 0x80000000)
+	(i32.const -1)
+	(i32.div_s)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x10
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       addi    t0, zero, -1
+;;       slli    t0, t0, 0x20
+;;       srli    t0, t0, 0x20
+;;       lui     t1, 0x80000
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       bnez    t0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       addiw   t3, t0, 1
+;;       bnez    t3, 0x14
+;;       lui     t3, 0x80000
+;;       sext.w  t4, t1
+;;       bne     t4, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       divw    t1, t1, t0
+;;       slli    t1, t1, 0x20
+;;       srli    t1, t1, 0x20
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,54 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (result i32)
+        (f32.const 1.0)
+        (i32.trunc_f32_s)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x10
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       lui     t6, 0x3f800
+;;       fmv.w.x ft0, t6
+;;       feq.s   t3, ft0, ft0
+;;       bnez    t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       auipc   t6, 0
+;;       ld      t6, 0xc(t6)
+;;       j       0xc
+;;       .byte   0x01, 0x00, 0x00, 0xcf
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       fmv.w.x ft11, t6
+;;       fle.s   t3, ft0, ft11
+;;       beqz    t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       lui     t6, 0x4f000
+;;       fmv.w.x ft11, t6
+;;       fle.s   t3, ft11, ft0
+;;       beqz    t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       fcvt.w.s t0, ft0, rtz
+;;       slli    t0, t0, 0x20
+;;       srli    t0, t0, 0x20
+;;       slli    a0, t0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
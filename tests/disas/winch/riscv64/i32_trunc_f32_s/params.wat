@@ -0,0 +1,54 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+    (func (param f32) (result i32)
+        (local.get 0)
+        (i32.trunc_f32_s)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       fsw     fa0, 4(sp)
+;;       flw     ft0, 4(sp)
+;;       feq.s   t3, ft0, ft0
+;;       bnez    t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       auipc   t6, 0
+;;       ld      t6, 0xc(t6)
+;;       j       0xc
+;;       .byte   0x01, 0x00, 0x00, 0xcf
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       fmv.w.x ft11, t6
+;;       fle.s   t3, ft0, ft11
+;;       beqz    t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       lui     t6, 0x4f000
+;;       fmv.w.x ft11, t6
+;;       fle.s   t3, ft11, ft0
+;;       beqz    t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       fcvt.w.s t0, ft0, rtz
+;;       slli    t0, t0, 0x20
+;;       srli    t0, t0, 0x20
+;;       slli    a0, t0, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,38 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+    (func (result i64)
+	(i64.const 1)
+	(i64.const 0x7fffffffffffffff)
+	(i64.add)
+    )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x10
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       addi    t0, zero, 1
+;;       auipc   t3, 0
+;;       ld      t3, 0xc(t3)
+;;       j       0xc
+;;       .byte   0xff, 0xff, 0xff, 0xff
+;;       .byte   0xff, 0xff, 0xff, 0x7f
+;;       add     t0, t0, t3
+;;       mv      a0, t0
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
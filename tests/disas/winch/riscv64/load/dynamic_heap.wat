@@ -0,0 +1,111 @@
+;;! target = "riscv64"
+;;! test = "winch"
+;;! flags = "-O static-memory-maximum-size=100 -O dynamic-memory-guard-size=0xffff"
+
+(module
+  (memory (export "memory") 17)
+  (func (export "run") (param i32) (result i32 i32 i32)
+    ;; Within the guard region.
+    local.get 0
+    i32.load offset=0
+    ;; Also within the guard region, bounds check should GVN with previous.
+    local.get 0
+    i32.load offset=4
+
+    ;; Outside the guard region, needs additional bounds checks.
+    local.get 0
+    i32.load offset=0x000fffff
+  )
+  (data (i32.const 0) "\45\00\00\00\a4\01\00\00")
+  (data (i32.const 0x000fffff) "\39\05\00\00")
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a1)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x28
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a1
+;;       addi    sp, sp, -0x20
+;;       sd      a1, 0x18(sp)
+;;       sd      a2, 0x10(sp)
+;;       sw      a3, 0xc(sp)
+;;       sd      a0, 0(sp)
+;;       lwu     t0, 0xc(sp)
+;;       ld      t1, 0x40(s1)
+;;       slli    t2, t0, 0x20
+;;       srli    t2, t2, 0x20
+;;       addi    t3, t2, 4
+;;       bgeu    t3, t2, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      t2, t3
+;;       bgeu    t1, t2, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      a0, 0x38(s1)
+;;       add     a0, a0, t0
+;;       mv      a1, zero
+;;       bgeu    t1, t2, 8
+;;       mv      a0, a1
+;;       lwu     t0, 0(a0)
+;;       lwu     t1, 0xc(sp)
+;;       ld      t2, 0x40(s1)
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    t3, a0, 8
+;;       bgeu    t3, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      a0, t3
+;;       bgeu    t2, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      a1, 0x38(s1)
+;;       add     a1, a1, t1
+;;       addi    a1, a1, 4
+;;       mv      a2, zero
+;;       bgeu    t2, a0, 8
+;;       mv      a1, a2
+;;       lwu     t1, 0(a1)
+;;       lwu     t2, 0xc(sp)
+;;       ld      a0, 0x40(s1)
+;;       slli    a1, t2, 0x20
+;;       srli    a1, a1, 0x20
+;;       lui     t4, 0x100
+;;       addi    t4, t4, 3
+;;       add     t3, a1, t4
+;;       bgeu    t3, a1, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      a1, t3
+;;       bgeu    a0, a1, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      a2, 0x38(s1)
+;;       add     a2, a2, t2
+;;       lui     t3, 0x100
+;;       addi    t3, t3, -1
+;;       add     a2, a2, t3
+;;       mv      a3, zero
+;;       bgeu    a0, a1, 8
+;;       mv      a2, a3
+;;       lwu     t2, 0(a2)
+;;       slli    a0, t2, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    sp, sp, -4
+;;       sw      t0, 0(sp)
+;;       addi    sp, sp, -4
+;;       sw      t1, 0(sp)
+;;       ld      t0, 8(sp)
+;;       lwu     t3, 0(sp)
+;;       addi    sp, sp, 4
+;;       sw      t3, 0(t0)
+;;       lwu     t3, 0(sp)
+;;       addi    sp, sp, 4
+;;       sw      t3, 4(t0)
+;;       addi    sp, sp, 0x20
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,41 @@
+;;! target = "riscv64"
+;;! test = "winch"
+(module
+  (memory 1)
+  (func (export "i64_load8_s") (param $i i64) (result i64)
+   (i64.store8 (i32.const 8) (local.get $i))
+   (i64.load8_s (i32.const 8))
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x18
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x18
+;;       sd      a0, 0x10(sp)
+;;       sd      a1, 8(sp)
+;;       sd      a2, 0(sp)
+;;       ld      t0, 0(sp)
+;;       addi    t1, zero, 8
+;;       ld      t2, 0x38(s1)
+;;       add     t2, t2, t1
+;;       sb      t0, 0(t2)
+;;       addi    t0, zero, 8
+;;       ld      t1, 0x38(s1)
+;;       add     t1, t1, t0
+;;       lb      t0, 0(t1)
+;;       mv      a0, t0
+;;       addi    sp, sp, 0x18
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,57 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+	(func (export "run") (param i32 i32 f32 f32) (result i32 i32 f32 f32)
+		local.get 0
+		local.get 1
+		local.get 2
+		local.get 3
+	)
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a1)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x34
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a1
+;;       addi    sp, sp, -0x28
+;;       sd      a1, 0x20(sp)
+;;       sd      a2, 0x18(sp)
+;;       sw      a3, 0x14(sp)
+;;       sw      a4, 0x10(sp)
+;;       fsw     fa0, 0xc(sp)
+;;       fsw     fa1, 8(sp)
+;;       sd      a0, 0(sp)
+;;       flw     fa0, 8(sp)
+;;       lwu     t3, 0x14(sp)
+;;       addi    sp, sp, -4
+;;       sw      t3, 0(sp)
+;;       lwu     t3, 0x14(sp)
+;;       addi    sp, sp, -4
+;;       sw      t3, 0(sp)
+;;       flw     ft11, 0x14(sp)
+;;       addi    sp, sp, -4
+;;       fsw     ft11, 0(sp)
+;;       ld      t0, 0xc(sp)
+;;       flw     ft11, 0(sp)
+;;       addi    sp, sp, 4
+;;       fsw     ft11, 0(t0)
+;;       lwu     t3, 0(sp)
+;;       addi    sp, sp, 4
+;;       sw      t3, 4(t0)
+;;       lwu     t3, 0(sp)
+;;       addi    sp, sp, 4
+;;       sw      t3, 8(t0)
+;;       addi    sp, sp, 0x28
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
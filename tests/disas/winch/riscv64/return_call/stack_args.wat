@@ -0,0 +1,164 @@
+;;! target = "riscv64"
+;;! test = "winch"
+
+(module
+  (func $callee (param i64 i64 i64 i64 i64 i64 i64) (result i64)
+    local.get 6)
+
+  ;; The callee's stack arguments fit in the caller's incoming argument area,
+  ;; so they are moved there before jumping to the callee.
+  (func (export "fits") (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+    local.get 7
+    local.get 6
+    local.get 5
+    local.get 4
+    local.get 3
+    local.get 2
+    local.get 1
+    return_call $callee)
+
+  ;; The callee's stack arguments don't fit in the caller's incoming argument
+  ;; area, so a regular call followed by a return is emitted instead.
+  (func (export "doesnt_fit") (result i64)
+    i64.const 1
+    i64.const 2
+    i64.const 3
+    i64.const 4
+    i64.const 5
+    i64.const 6
+    i64.const 7
+    return_call $callee)
+)
+;; wasm[0]::function[0]::callee:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x40
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x40
+;;       sd      a0, 0x38(sp)
+;;       sd      a1, 0x30(sp)
+;;       sd      a2, 0x28(sp)
+;;       sd      a3, 0x20(sp)
+;;       sd      a4, 0x18(sp)
+;;       sd      a5, 0x10(sp)
+;;       sd      a6, 8(sp)
+;;       sd      a7, 0(sp)
+;;       ld      a0, 0x10(s0)
+;;       addi    sp, sp, 0x40
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[1]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x80
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x40
+;;       sd      a0, 0x38(sp)
+;;       sd      a1, 0x30(sp)
+;;       sd      a2, 0x28(sp)
+;;       sd      a3, 0x20(sp)
+;;       sd      a4, 0x18(sp)
+;;       sd      a5, 0x10(sp)
+;;       sd      a6, 8(sp)
+;;       sd      a7, 0(sp)
+;;       ld      t3, 0x18(s0)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0x10(s0)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0x10(sp)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0x20(sp)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0x30(sp)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0x40(sp)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0x50(sp)
+;;       addi    sp, sp, -8
+;;       sd      t3, 0(sp)
+;;       addi    sp, sp, -8
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       ld      a2, 0x38(sp)
+;;       ld      a3, 0x30(sp)
+;;       ld      a4, 0x28(sp)
+;;       ld      a5, 0x20(sp)
+;;       ld      a6, 0x18(sp)
+;;       ld      a7, 0x10(sp)
+;;       ld      t3, 8(sp)
+;;       sd      t3, 0(sp)
+;;       ld      t3, 0(sp)
+;;       sd      t3, 0x10(s0)
+;;       mv      sp, s0
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       auipc   t6, 0
+;;       jalr    zero, t6, -0x158
+;;       addi    sp, sp, 0x40
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
+;;
+;; wasm[0]::function[2]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x10
+;;       sd      a0, 8(sp)
+;;       sd      a1, 0(sp)
+;;       addi    sp, sp, -0x10
+;;       mv      a0, s1
+;;       mv      a1, s1
+;;       addi    a2, zero, 1
+;;       addi    a3, zero, 2
+;;       addi    a4, zero, 3
+;;       addi    a5, zero, 4
+;;       addi    a6, zero, 5
+;;       addi    a7, zero, 6
+;;       addi    t3, zero, 7
+;;       sd      t3, 0(sp)
+;;       auipc   ra, 0
+;;       jalr    ra, ra, -0x1dc
+;;       addi    sp, sp, 0x10
+;;       ld      s1, 8(sp)
+;;       addi    sp, sp, 0x10
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
@@ -0,0 +1,103 @@
+;;! target = "riscv64"
+;;! test = "winch"
+;;! flags = "-O static-memory-maximum-size=0 -O dynamic-memory-guard-size=0xffff"
+
+(module
+  (memory (export "memory") 1)
+  (func (export "run") (param i32 i32 i32 i32)
+    ;; Within the guard region.
+    local.get 0
+    local.get 1
+    i32.store offset=0
+    ;; Also within the guard region, bounds check should GVN with previous.
+    local.get 0
+    local.get 2
+    i32.store offset=4
+    ;; Outside the guard region, needs additional bounds checks.
+    local.get 0
+    local.get 3
+    i32.store offset=0x000fffff
+  )
+)
+;; wasm[0]::function[0]:
+;;       addi    sp, sp, -0x10
+;;       sd      ra, 8(sp)
+;;       sd      s0, 0(sp)
+;;       mv      s0, sp
+;;       ld      t3, 8(a0)
+;;       ld      t3, 0x10(t3)
+;;       lui     t4, 0
+;;       addi    t4, t4, 0x20
+;;       add     t3, t3, t4
+;;       bgeu    sp, t3, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      s1, a0
+;;       addi    sp, sp, -0x20
+;;       sd      a0, 0x18(sp)
+;;       sd      a1, 0x10(sp)
+;;       sw      a2, 0xc(sp)
+;;       sw      a3, 8(sp)
+;;       sw      a4, 4(sp)
+;;       sw      a5, 0(sp)
+;;       lwu     t0, 8(sp)
+;;       lwu     t1, 0xc(sp)
+;;       ld      t2, 0x40(s1)
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    t3, a0, 4
+;;       bgeu    t3, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      a0, t3
+;;       bgeu    t2, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      a1, 0x38(s1)
+;;       add     a1, a1, t1
+;;       mv      a2, zero
+;;       bgeu    t2, a0, 8
+;;       mv      a1, a2
+;;       sw      t0, 0(a1)
+;;       lwu     t0, 4(sp)
+;;       lwu     t1, 0xc(sp)
+;;       ld      t2, 0x40(s1)
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       addi    t3, a0, 8
+;;       bgeu    t3, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      a0, t3
+;;       bgeu    t2, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      a1, 0x38(s1)
+;;       add     a1, a1, t1
+;;       addi    a1, a1, 4
+;;       mv      a2, zero
+;;       bgeu    t2, a0, 8
+;;       mv      a1, a2
+;;       sw      t0, 0(a1)
+;;       lwu     t0, 0(sp)
+;;       lwu     t1, 0xc(sp)
+;;       ld      t2, 0x40(s1)
+;;       slli    a0, t1, 0x20
+;;       srli    a0, a0, 0x20
+;;       lui     t4, 0x100
+;;       addi    t4, t4, 3
+;;       add     t3, a0, t4
+;;       bgeu    t3, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       mv      a0, t3
+;;       bgeu    t2, a0, 8
+;;       .byte   0x00, 0x00, 0x00, 0x00
+;;       ld      a1, 0x38(s1)
+;;       add     a1, a1, t1
+;;       lui     t3, 0x100
+;;       addi    t3, t3, -1
+;;       add     a1, a1, t3
+;;       mv      a2, zero
+;;       bgeu    t2, a0, 8
+;;       mv      a1, a2
+;;       sw      t0, 0(a1)
+;;       addi    sp, sp, 0x20
+;;       ld      ra, 8(sp)
+;;       ld      s0, 0(sp)
+;;       addi    sp, sp, 0x10
+;;       ret
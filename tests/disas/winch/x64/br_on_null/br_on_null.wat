@@ -0,0 +1,131 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references"
+
+(module
+  (type $t (func (result i32)))
+
+  (func (export "br_on_null") (param (ref null $t)) (result i32)
+    (block
+      local.get 0
+      br_on_null 0
+      call_ref $t
+      return)
+    i32.const -1)
+
+  (func (export "br_on_non_null") (param (ref null $t)) (result i32)
+    (block (result (ref $t))
+      local.get 0
+      br_on_non_null 0
+      i32.const -1
+      return)
+    call_ref $t)
+
+  (func (export "as_non_null") (param (ref null $t)) (result i32)
+    local.get 0
+    ref.as_non_null
+    call_ref $t)
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x28, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x76
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movq    %rdx, 8(%rsp)
+;;       movq    8(%rsp), %rax
+;;       testq   %rax, %rax
+;;       je      0x68
+;;   43: testq   %rax, %rax
+;;       je      0x78
+;;   4c: pushq   %rax
+;;       popq    %rcx
+;;       movq    0x18(%rcx), %rbx
+;;       movq    8(%rcx), %rdx
+;;       movq    %rbx, %rdi
+;;       movq    %r14, %rsi
+;;       callq   *%rdx
+;;       movq    0x18(%rsp), %r14
+;;       jmp     0x6d
+;;   68: movl    $0xffffffff, %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   76: ud2
+;;   78: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x28, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xfb
+;;   9c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movq    %rdx, 8(%rsp)
+;;       movq    8(%rsp), %rcx
+;;       movq    %rcx, %rdx
+;;       pushq   %rcx
+;;       popq    %rax
+;;       testq   %rdx, %rdx
+;;       jne     0xd2
+;;   c8: movl    $0xffffffff, %eax
+;;       jmp     0xf2
+;;   d2: testq   %rax, %rax
+;;       je      0xfd
+;;   db: pushq   %rax
+;;       popq    %rcx
+;;       movq    0x18(%rcx), %rbx
+;;       movq    8(%rcx), %rdx
+;;       movq    %rbx, %rdi
+;;       movq    %r14, %rsi
+;;       callq   *%rdx
+;;       movq    0x18(%rsp), %r14
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   fb: ud2
+;;   fd: ud2
+;;
+;; wasm[0]::function[2]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x28, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x16c
+;;  11c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movq    %rdx, 8(%rsp)
+;;       movq    8(%rsp), %rax
+;;       testq   %rax, %rax
+;;       je      0x16e
+;;  143: testq   %rax, %rax
+;;       je      0x170
+;;  14c: pushq   %rax
+;;       popq    %rcx
+;;       movq    0x18(%rcx), %rbx
+;;       movq    8(%rcx), %rdx
+;;       movq    %rbx, %rdi
+;;       movq    %r14, %rsi
+;;       callq   *%rdx
+;;       movq    0x18(%rsp), %r14
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  16c: ud2
+;;  16e: ud2
+;;  170: ud2
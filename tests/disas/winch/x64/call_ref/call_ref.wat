@@ -0,0 +1,113 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references"
+
+(module
+  (type $t (func (param i32) (result i32)))
+
+  (func $callee (type $t)
+    local.get 0)
+  (elem declare func $callee)
+
+  (func (export "call") (param i32) (result i32)
+    local.get 0
+    ref.func $callee
+    call_ref $t)
+
+  (func (export "return_call") (param (ref null $t)) (result i32)
+    i32.const 1
+    local.get 0
+    return_call_ref $t)
+)
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x41
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   41: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xf6
+;;   6c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       subq    $0xc, %rsp
+;;       movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x3e1
+;;       addq    $0xc, %rsp
+;;       movq    0x1c(%rsp), %r14
+;;       testq   %rax, %rax
+;;       je      0xf8
+;;   bd: pushq   %rax
+;;       popq    %rcx
+;;       movq    0x18(%rcx), %r8
+;;       movq    8(%rcx), %rbx
+;;       subq    $0xc, %rsp
+;;       movq    %r8, %rdi
+;;       movq    %r14, %rsi
+;;       movl    0xc(%rsp), %edx
+;;       callq   *%rbx
+;;       addq    $0xc, %rsp
+;;       addq    $4, %rsp
+;;       movq    0x18(%rsp), %r14
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   f6: ud2
+;;   f8: ud2
+;;
+;; wasm[0]::function[2]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x28, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x167
+;;  11c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movq    %rdx, 8(%rsp)
+;;       movq    8(%rsp), %rax
+;;       testq   %rax, %rax
+;;       je      0x169
+;;  143: pushq   %rax
+;;       popq    %rcx
+;;       movq    0x18(%rcx), %r8
+;;       movq    8(%rcx), %rbx
+;;       movq    %r8, %rdi
+;;       movq    %r14, %rsi
+;;       movl    $1, %edx
+;;       movq    %rbp, %rsp
+;;       popq    %rbp
+;;       jmpq    *%rbx
+;;  15e: addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  167: ud2
+;;  169: ud2
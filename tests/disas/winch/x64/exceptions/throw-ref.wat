@@ -0,0 +1,38 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W exceptions,gc -C collector=drc"
+
+(module
+  (func (param exnref)
+    (throw_ref (local.get 0))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x75
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       subq    $0xc, %rsp
+;;       movq    %r14, %rdi
+;;       movl    0xc(%rsp), %esi
+;;       callq   0xec
+;;       addq    $0xc, %rsp
+;;       ╰─╼ stack_map: frame_size=48, frame_offsets=[12, 28]
+;;       addq    $4, %rsp
+;;       movq    0x18(%rsp), %r14
+;;       ud2
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   75: ud2
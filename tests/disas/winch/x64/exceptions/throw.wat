@@ -0,0 +1,61 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W exceptions,gc -C collector=drc"
+
+(module
+  (tag $e (param i32 i64))
+
+  (func (param i32 i64)
+    (throw $e (local.get 0) (local.get 1))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xd4
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movq    %rcx, (%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movq    4(%rsp), %r11
+;;       pushq   %r11
+;;       subq    $4, %rsp
+;;       movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x150
+;;       addq    $4, %rsp
+;;       movq    0x24(%rsp), %r14
+;;       movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movl    8(%rsp), %edx
+;;       movl    %edx, 0x20(%rcx)
+;;       movq    (%rsp), %rdx
+;;       movq    %rdx, 0x28(%rcx)
+;;       addq    $8, %rsp
+;;       addq    $4, %rsp
+;;       subq    $4, %rsp
+;;       movl    %eax, (%rsp)
+;;       subq    $0xc, %rsp
+;;       movq    %r14, %rdi
+;;       movl    0xc(%rsp), %esi
+;;       callq   0x1b0
+;;       addq    $0xc, %rsp
+;;       ╰─╼ stack_map: frame_size=48, frame_offsets=[12]
+;;       addq    $4, %rsp
+;;       movq    0x18(%rsp), %r14
+;;       ud2
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   d4: ud2
@@ -0,0 +1,101 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W exceptions,gc -C collector=drc"
+
+(module
+  (tag $e (param i32))
+
+  (func $f)
+
+  (func (result i32)
+    (block $h (result i32)
+      (try_table (result i32) (catch $e $h)
+        (call $f)
+        (i32.const 0)
+      )
+    )
+  )
+)
+;; wasm[0]::function[0]::f:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x10, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x38
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;   38: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x14e
+;;   5c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       movq    %r14, %rdi
+;;       movq    %r14, %rsi
+;;       callq   0
+;;       movq    8(%rsp), %r14
+;;       movl    $0, %eax
+;;       jmp     0x145
+;;   89: movq    %rbp, %rsp
+;;       subq    $0x10, %rsp
+;;       movl    %eax, %eax
+;;       movq    8(%rsp), %r14
+;;       subq    $4, %rsp
+;;       movl    %eax, (%rsp)
+;;       movl    (%rsp), %eax
+;;       subq    $4, %rsp
+;;       movl    %eax, (%rsp)
+;;       subq    $8, %rsp
+;;       movq    %r14, %rdi
+;;       movl    8(%rsp), %esi
+;;       movl    $0, %edx
+;;       callq   0x2a2
+;;       addq    $8, %rsp
+;;       ╰─╼ stack_map: frame_size=32, frame_offsets=[8, 12]
+;;       addq    $4, %rsp
+;;       movq    0xc(%rsp), %r14
+;;       testl   %eax, %eax
+;;       je      0x11d
+;;   e4: movl    (%rsp), %eax
+;;       movl    %eax, %ecx
+;;       addq    $0x24, %rcx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rcx
+;;       ja      0x150
+;;  101: movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movl    0x20(%rcx), %eax
+;;       addq    $4, %rsp
+;;       jmp     0x145
+;;  11d: subq    $0xc, %rsp
+;;       movq    %r14, %rdi
+;;       movl    0xc(%rsp), %esi
+;;       callq   0x2e7
+;;       addq    $0xc, %rsp
+;;       ╰─╼ stack_map: frame_size=32, frame_offsets=[12]
+;;       addq    $4, %rsp
+;;       movq    8(%rsp), %r14
+;;       ud2
+;;       addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;  14e: ud2
+;;  150: ud2
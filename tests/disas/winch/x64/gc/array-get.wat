@@ -0,0 +1,66 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (type $ty (array (mut i64)))
+
+  (func (param (ref $ty) i32) (result i64)
+    (array.get $ty (local.get 0) (local.get 1))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xc7
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    %ecx, 8(%rsp)
+;;       movl    8(%rsp), %eax
+;;       movl    0xc(%rsp), %ecx
+;;       testq   %rcx, %rcx
+;;       je      0xc9
+;;   49: movl    %ecx, %edx
+;;       addq    $0x1c, %rdx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rdx
+;;       ja      0xcb
+;;   63: movl    %ecx, %edx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rdx
+;;       movl    0x18(%rdx), %edx
+;;       cmpl    %edx, %eax
+;;       jae     0xcd
+;;   7b: movl    %eax, %eax
+;;       imulq   $8, %rax, %rax
+;;       addq    $0x20, %rax
+;;       movl    %ecx, %r11d
+;;       addq    %r11, %rax
+;;       addq    $8, %rax
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rax
+;;       ja      0xcf
+;;   a7: subq    $8, %rax
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rax
+;;       movq    (%rax), %rcx
+;;       movq    %rcx, %rax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   c7: ud2
+;;   c9: ud2
+;;   cb: ud2
+;;   cd: ud2
+;;   cf: ud2
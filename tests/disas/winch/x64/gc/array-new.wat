@@ -0,0 +1,73 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (type $ty (array (mut i64)))
+
+  (func (param i64 i32) (result (ref $ty))
+    (array.new $ty (local.get 0) (local.get 1))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x104
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movq    %rdx, 8(%rsp)
+;;       movl    %ecx, 4(%rsp)
+;;       movl    4(%rsp), %eax
+;;       movl    %eax, %eax
+;;       imulq   $8, %rax, %rax
+;;       movq    %rax, %rcx
+;;       shrq    $0x20, %rcx
+;;       cmpq    $0, %rcx
+;;       jne     0x106
+;;   57: addl    $0x20, %eax
+;;       jb      0x108
+;;   62: movq    8(%rsp), %r11
+;;       pushq   %r11
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       subq    $4, %rsp
+;;       movl    %eax, (%rsp)
+;;       movq    %r14, %rdi
+;;       movl    $0xa8000000, %esi
+;;       movl    $0, %edx
+;;       movl    (%rsp), %ecx
+;;       movl    $8, %r8d
+;;       callq   0x18a
+;;       addq    $4, %rsp
+;;       movq    0x24(%rsp), %r14
+;;       movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movl    (%rsp), %edx
+;;       movl    %edx, 0x18(%rcx)
+;;       movl    %edx, %edx
+;;       imulq   $8, %rdx, %rdx
+;;       addq    $0x20, %rcx
+;;       addq    %rcx, %rdx
+;;       movq    4(%rsp), %rbx
+;;       cmpq    %rdx, %rcx
+;;       je      0xed
+;;   de: movq    %rbx, (%rcx)
+;;       addq    $8, %rcx
+;;       jmp     0xd5
+;;   ed: addq    $4, %rsp
+;;       addq    $8, %rsp
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  104: ud2
+;;  106: ud2
+;;  108: ud2
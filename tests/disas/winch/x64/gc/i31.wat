@@ -0,0 +1,60 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (func (param i32) (result i32)
+    (i31.get_s (ref.i31 (local.get 0)))
+  )
+
+  (func (param i31ref) (result i32)
+    (i31.get_u (local.get 0))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x55
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       shll    $1, %eax
+;;       orl     $1, %eax
+;;       testq   %rax, %rax
+;;       je      0x57
+;;   49: sarl    $1, %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   55: ud2
+;;   57: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xad
+;;   7c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       testq   %rax, %rax
+;;       je      0xaf
+;;   a1: shrl    $1, %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   ad: ud2
+;;   af: ud2
@@ -0,0 +1,70 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (type $s (struct))
+  (func (param anyref) (result i32)
+    (ref.test (ref $s) (local.get 0))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xf4
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movl    (%rsp), %ecx
+;;       movl    $0, %eax
+;;       testl   %ecx, %ecx
+;;       je      0xe4
+;;   54: movl    $0, %eax
+;;       movl    %ecx, %edx
+;;       andl    $1, %edx
+;;       testl   %edx, %edx
+;;       jne     0xe4
+;;   69: movl    %ecx, %edx
+;;       addq    $8, %rdx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rdx
+;;       ja      0xf6
+;;   83: movl    %ecx, %edx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rdx
+;;       movl    4(%rdx), %edx
+;;       movq    0x28(%r14), %rcx
+;;       movl    (%rcx), %ecx
+;;       movl    $1, %eax
+;;       cmpl    %ecx, %edx
+;;       je      0xe4
+;;   a6: subq    $4, %rsp
+;;       movl    %edx, (%rsp)
+;;       subq    $4, %rsp
+;;       movl    %ecx, (%rsp)
+;;       subq    $4, %rsp
+;;       movq    %r14, %rdi
+;;       movl    8(%rsp), %esi
+;;       movl    4(%rsp), %edx
+;;       callq   0x16f
+;;       addq    $4, %rsp
+;;       ╰─╼ stack_map: frame_size=48, frame_offsets=[12, 28]
+;;       addq    $8, %rsp
+;;       movq    0x1c(%rsp), %r14
+;;       addq    $4, %rsp
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   f4: ud2
+;;   f6: ud2
@@ -0,0 +1,192 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (type $ty (struct (field (mut f32))
+                    (field (mut i8))
+                    (field (mut anyref))))
+
+  (func (param (ref null $ty)) (result f32)
+    (struct.get $ty 0 (local.get 0))
+  )
+
+  (func (param (ref null $ty)) (result i32)
+    (struct.get_s $ty 1 (local.get 0))
+  )
+
+  (func (param (ref null $ty)) (result i32)
+    (struct.get_u $ty 1 (local.get 0))
+  )
+
+  (func (param (ref null $ty)) (result anyref)
+    (struct.get $ty 2 (local.get 0))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x76
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       testq   %rax, %rax
+;;       je      0x78
+;;   41: movl    %eax, %ecx
+;;       addq    $0x1c, %rcx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rcx
+;;       ja      0x7a
+;;   5b: movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movss   0x18(%rcx), %xmm0
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   76: ud2
+;;   78: ud2
+;;   7a: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xf9
+;;   9c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       testq   %rax, %rax
+;;       je      0xfb
+;;   c1: movl    %eax, %ecx
+;;       addq    $0x1d, %rcx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rcx
+;;       ja      0xfd
+;;   db: movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movzbq  0x1c(%rcx), %rax
+;;       movsbl  %al, %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   f9: ud2
+;;   fb: ud2
+;;   fd: ud2
+;;
+;; wasm[0]::function[2]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x176
+;;  11c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       testq   %rax, %rax
+;;       je      0x178
+;;  141: movl    %eax, %ecx
+;;       addq    $0x1d, %rcx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rcx
+;;       ja      0x17a
+;;  15b: movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movzbq  0x1c(%rcx), %rax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  176: ud2
+;;  178: ud2
+;;  17a: ud2
+;;
+;; wasm[0]::function[3]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x267
+;;  19c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       testq   %rax, %rax
+;;       je      0x269
+;;  1c1: movl    %eax, %ecx
+;;       addq    $0x24, %rcx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rcx
+;;       ja      0x26b
+;;  1db: movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movl    0x20(%rcx), %eax
+;;       movl    %eax, %ebx
+;;       andl    $1, %ebx
+;;       testl   %ebx, %ebx
+;;       jne     0x25e
+;;  1fb: testl   %eax, %eax
+;;       je      0x25e
+;;  203: movl    %eax, %edx
+;;       addq    $0x18, %rdx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rdx
+;;       ja      0x26d
+;;  21d: movl    %eax, %edx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rdx
+;;       movl    (%rdx), %ebx
+;;       movl    %ebx, %esi
+;;       andl    $2, %esi
+;;       testl   %esi, %esi
+;;       jne     0x25e
+;;  23c: orl     $2, %ebx
+;;       movl    %ebx, (%rdx)
+;;       movq    0x20(%r14), %rbx
+;;       movl    (%rbx), %esi
+;;       movl    %esi, 0x10(%rdx)
+;;       movl    %eax, (%rbx)
+;;       movq    8(%rdx), %rbx
+;;       addq    $1, %rbx
+;;       movq    %rbx, 8(%rdx)
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  267: ud2
+;;  269: ud2
+;;  26b: ud2
+;;  26d: ud2
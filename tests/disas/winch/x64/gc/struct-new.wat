@@ -0,0 +1,84 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (type $ty (struct (field (mut f32))
+                    (field (mut i8))
+                    (field (mut anyref))))
+
+  (func (param f32 i32 anyref) (result (ref $ty))
+    (struct.new $ty (local.get 0) (local.get 1) (local.get 2))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x133
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movss   %xmm0, 0xc(%rsp)
+;;       movl    %edx, 8(%rsp)
+;;       movl    %ecx, 4(%rsp)
+;;       movss   0xc(%rsp), %xmm15
+;;       subq    $4, %rsp
+;;       movss   %xmm15, (%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       subq    $4, %rsp
+;;       movq    %r14, %rdi
+;;       movl    $0xb0000000, %esi
+;;       movl    $0, %edx
+;;       movl    $0x28, %ecx
+;;       movl    $8, %r8d
+;;       callq   0x1b9
+;;       addq    $4, %rsp
+;;       ╰─╼ stack_map: frame_size=48, frame_offsets=[4, 20]
+;;       movq    0x24(%rsp), %r14
+;;       movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movss   8(%rsp), %xmm0
+;;       movss   %xmm0, 0x18(%rcx)
+;;       movl    4(%rsp), %edx
+;;       movb    %dl, 0x1c(%rcx)
+;;       movl    (%rsp), %edx
+;;       movl    %edx, %ebx
+;;       andl    $1, %ebx
+;;       testl   %ebx, %ebx
+;;       jne     0x112
+;;   d4: testl   %edx, %edx
+;;       je      0x112
+;;   dc: movl    %edx, %ebx
+;;       addq    $0x10, %rbx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rbx
+;;       ja      0x135
+;;   f6: movl    %edx, %ebx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rbx
+;;       movq    8(%rbx), %rsi
+;;       addq    $1, %rsi
+;;       movq    %rsi, 8(%rbx)
+;;       movl    %edx, 0x20(%rcx)
+;;       addq    $4, %rsp
+;;       addq    $4, %rsp
+;;       addq    $4, %rsp
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  133: ud2
+;;  135: ud2
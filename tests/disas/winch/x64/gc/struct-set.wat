@@ -0,0 +1,190 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-W function-references,gc -C collector=drc"
+
+(module
+  (type $ty (struct (field (mut f32))
+                    (field (mut i8))
+                    (field (mut anyref))))
+
+  (func (param (ref null $ty) f32)
+    (struct.set $ty 0 (local.get 0) (local.get 1))
+  )
+
+  (func (param (ref null $ty) i32)
+    (struct.set $ty 1 (local.get 0) (local.get 1))
+  )
+
+  (func (param (ref null $ty) anyref)
+    (struct.set $ty 2 (local.get 0) (local.get 1))
+  )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x82
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movss   %xmm0, 8(%rsp)
+;;       movss   8(%rsp), %xmm0
+;;       movl    0xc(%rsp), %eax
+;;       testq   %rax, %rax
+;;       je      0x84
+;;   4d: movl    %eax, %ecx
+;;       addq    $0x1c, %rcx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rcx
+;;       ja      0x86
+;;   67: movl    %eax, %ecx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rcx
+;;       movss   %xmm0, 0x18(%rcx)
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   82: ud2
+;;   84: ud2
+;;   86: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x10c
+;;   ac: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    %ecx, 8(%rsp)
+;;       movl    8(%rsp), %eax
+;;       movl    0xc(%rsp), %ecx
+;;       testq   %rcx, %rcx
+;;       je      0x10e
+;;   d9: movl    %ecx, %edx
+;;       addq    $0x1d, %rdx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rdx
+;;       ja      0x110
+;;   f3: movl    %ecx, %edx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rdx
+;;       movb    %al, 0x1c(%rdx)
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  10c: ud2
+;;  10e: ud2
+;;  110: ud2
+;;
+;; wasm[0]::function[2]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x2a5
+;;  13c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    %ecx, 8(%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movl    (%rsp), %eax
+;;       addq    $4, %rsp
+;;       movl    (%rsp), %ecx
+;;       addq    $4, %rsp
+;;       testq   %rcx, %rcx
+;;       je      0x2a7
+;;  195: movl    %ecx, %edx
+;;       addq    $0x24, %rdx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rdx
+;;       ja      0x2a9
+;;  1af: movl    %ecx, %edx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rdx
+;;       movl    0x20(%rdx), %ecx
+;;       movl    %eax, %ebx
+;;       andl    $1, %ebx
+;;       testl   %ebx, %ebx
+;;       jne     0x20d
+;;  1cf: testl   %eax, %eax
+;;       je      0x20d
+;;  1d7: movl    %eax, %ebx
+;;       addq    $0x10, %rbx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rbx
+;;       ja      0x2ab
+;;  1f1: movl    %eax, %ebx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rbx
+;;       movq    8(%rbx), %rsi
+;;       addq    $1, %rsi
+;;       movq    %rsi, 8(%rbx)
+;;       movl    %eax, 0x20(%rdx)
+;;       movl    %ecx, %ebx
+;;       andl    $1, %ebx
+;;       testl   %ebx, %ebx
+;;       jne     0x29c
+;;  220: testl   %ecx, %ecx
+;;       je      0x29c
+;;  228: movl    %ecx, %ebx
+;;       addq    $0x10, %rbx
+;;       movq    8(%r14), %r11
+;;       movq    0x20(%r11), %r11
+;;       cmpq    %r11, %rbx
+;;       ja      0x2ad
+;;  242: movl    %ecx, %ebx
+;;       movq    8(%r14), %r11
+;;       movq    0x18(%r11), %r11
+;;       addq    %r11, %rbx
+;;       movq    8(%rbx), %rsi
+;;       subq    $1, %rsi
+;;       testq   %rsi, %rsi
+;;       jne     0x298
+;;  263: subq    $4, %rsp
+;;       movl    %ecx, (%rsp)
+;;       subq    $0xc, %rsp
+;;       movq    %r14, %rdi
+;;       movl    0xc(%rsp), %esi
+;;       callq   0x41c
+;;       addq    $0xc, %rsp
+;;       ╰─╼ stack_map: frame_size=48, frame_offsets=[24, 28]
+;;       addq    $4, %rsp
+;;       movq    0x18(%rsp), %r14
+;;       jmp     0x29c
+;;  298: movq    %rsi, 8(%rbx)
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  2a5: ud2
+;;  2a7: ud2
+;;  2a9: ud2
+;;  2ab: ud2
+;;  2ad: ud2
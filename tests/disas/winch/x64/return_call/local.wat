@@ -0,0 +1,64 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+  (func $callee (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.add)
+
+  (func (export "main") (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    return_call $callee)
+)
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x4d
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    %ecx, 8(%rsp)
+;;       movl    8(%rsp), %eax
+;;       movl    0xc(%rsp), %ecx
+;;       addl    %eax, %ecx
+;;       movl    %ecx, %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   4d: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x24, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xb4
+;;   6c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movq    %r14, %rdi
+;;       movq    %r14, %rsi
+;;       movl    (%rsp), %edx
+;;       movl    $1, %ecx
+;;       movq    %rbp, %rsp
+;;       popq    %rbp
+;;       jmp     0
+;;   ab: addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   b4: ud2
@@ -0,0 +1,75 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+  (func $callee (param i64) (result i64 i64 i64)
+    local.get 0
+    local.get 0
+    local.get 0)
+
+  ;; The pointer to the caller's return area is forwarded to the callee.
+  (func (export "main") (param i64) (result i64 i64 i64)
+    local.get 0
+    return_call $callee)
+)
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rsi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x40, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x66
+;;   1c: movq    %rsi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rsi, 0x28(%rsp)
+;;       movq    %rdx, 0x20(%rsp)
+;;       movq    %rcx, 0x18(%rsp)
+;;       movq    %rdi, 8(%rsp)
+;;       movq    0x18(%rsp), %rax
+;;       movq    0x18(%rsp), %r11
+;;       pushq   %r11
+;;       movq    0x20(%rsp), %r11
+;;       pushq   %r11
+;;       movq    0x18(%rsp), %rcx
+;;       popq    %r11
+;;       movq    %r11, (%rcx)
+;;       popq    %r11
+;;       movq    %r11, 8(%rcx)
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   66: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rsi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x38, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xe2
+;;   8c: movq    %rsi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rsi, 0x28(%rsp)
+;;       movq    %rdx, 0x20(%rsp)
+;;       movq    %rcx, 0x18(%rsp)
+;;       movq    %rdi, 8(%rsp)
+;;       movq    0x18(%rsp), %r11
+;;       pushq   %r11
+;;       movq    %r14, %rsi
+;;       movq    %r14, %rdx
+;;       movq    (%rsp), %rcx
+;;       movq    0x10(%rsp), %rdi
+;;       movq    %rbp, %rsp
+;;       popq    %rbp
+;;       jmp     0
+;;   c9: movq    0x18(%rsp), %rcx
+;;       popq    %r11
+;;       movq    %r11, (%rcx)
+;;       popq    %r11
+;;       movq    %r11, 8(%rcx)
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   e2: ud2
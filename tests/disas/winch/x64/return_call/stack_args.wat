@@ -0,0 +1,142 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+  (func $callee (param i64 i64 i64 i64 i64 i64 i64) (result i64)
+    local.get 6)
+
+  ;; The callee's stack arguments fit in the caller's incoming argument area,
+  ;; so they are moved there before jumping to the callee.
+  (func (export "fits") (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+    local.get 7
+    local.get 6
+    local.get 5
+    local.get 4
+    local.get 3
+    local.get 2
+    local.get 1
+    return_call $callee)
+
+  ;; The callee's stack arguments don't fit in the caller's incoming argument
+  ;; area, so a regular call followed by a return is emitted instead.
+  (func (export "doesnt_fit") (result i64)
+    i64.const 1
+    i64.const 2
+    i64.const 3
+    i64.const 4
+    i64.const 5
+    i64.const 6
+    i64.const 7
+    return_call $callee)
+)
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x50
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rdi, 0x28(%rsp)
+;;       movq    %rsi, 0x20(%rsp)
+;;       movq    %rdx, 0x18(%rsp)
+;;       movq    %rcx, 0x10(%rsp)
+;;       movq    %r8, 8(%rsp)
+;;       movq    %r9, (%rsp)
+;;       movq    0x20(%rbp), %rax
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   50: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x80, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x13a
+;;   7c: movq    %rdi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rdi, 0x28(%rsp)
+;;       movq    %rsi, 0x20(%rsp)
+;;       movq    %rdx, 0x18(%rsp)
+;;       movq    %rcx, 0x10(%rsp)
+;;       movq    %r8, 8(%rsp)
+;;       movq    %r9, (%rsp)
+;;       movq    0x28(%rbp), %r11
+;;       pushq   %r11
+;;       movq    0x20(%rbp), %r11
+;;       pushq   %r11
+;;       movq    0x18(%rbp), %r11
+;;       pushq   %r11
+;;       movq    0x10(%rbp), %r11
+;;       pushq   %r11
+;;       movq    0x20(%rsp), %r11
+;;       pushq   %r11
+;;       movq    0x30(%rsp), %r11
+;;       pushq   %r11
+;;       movq    0x40(%rsp), %r11
+;;       pushq   %r11
+;;       subq    $0x18, %rsp
+;;       movq    %r14, %rdi
+;;       movq    %r14, %rsi
+;;       movq    0x48(%rsp), %rdx
+;;       movq    0x40(%rsp), %rcx
+;;       movq    0x38(%rsp), %r8
+;;       movq    0x30(%rsp), %r9
+;;       movq    0x28(%rsp), %r11
+;;       movq    %r11, (%rsp)
+;;       movq    0x20(%rsp), %r11
+;;       movq    %r11, 8(%rsp)
+;;       movq    0x18(%rsp), %r11
+;;       movq    %r11, 0x10(%rsp)
+;;       movq    (%rsp), %r11
+;;       movq    %r11, 0x10(%rbp)
+;;       movq    8(%rsp), %r11
+;;       movq    %r11, 0x18(%rbp)
+;;       movq    0x10(%rsp), %r11
+;;       movq    %r11, 0x20(%rbp)
+;;       movq    %rbp, %rsp
+;;       popq    %rbp
+;;       jmp     0
+;;  131: addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;  13a: ud2
+;;
+;; wasm[0]::function[2]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x1cc
+;;  15c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       subq    $0x20, %rsp
+;;       movq    %r14, %rdi
+;;       movq    %r14, %rsi
+;;       movl    $1, %edx
+;;       movl    $2, %ecx
+;;       movl    $3, %r8d
+;;       movl    $4, %r9d
+;;       movl    $5, %r11d
+;;       movq    %r11, (%rsp)
+;;       movl    $6, %r11d
+;;       movq    %r11, 8(%rsp)
+;;       movl    $7, %r11d
+;;       movq    %r11, 0x10(%rsp)
+;;       callq   0
+;;       addq    $0x20, %rsp
+;;       movq    8(%rsp), %r14
+;;       addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;  1cc: ud2
@@ -0,0 +1,102 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+  (type $t (func (param i32) (result i32)))
+
+  (func $callee (type $t)
+    local.get 0)
+
+  (func (export "main") (param i32) (result i32)
+    local.get 0
+    i32.const 0
+    return_call_indirect (type $t))
+
+  (table funcref (elem $callee))
+)
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x41
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   41: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x14c
+;;   6c: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       movl    $0, %ecx
+;;       movq    %r14, %rdx
+;;       movq    0x38(%rdx), %rbx
+;;       cmpq    %rbx, %rcx
+;;       jae     0x14e
+;;   a9: movq    %rcx, %r11
+;;       imulq   $8, %r11, %r11
+;;       movq    0x30(%rdx), %rdx
+;;       movq    %rdx, %rsi
+;;       addq    %r11, %rdx
+;;       cmpl    %ebx, %ecx
+;;       cmovaeq %rsi, %rdx
+;;       movq    (%rdx), %rax
+;;       testq   %rax, %rax
+;;       jne     0x109
+;;   cf: subq    $4, %rsp
+;;       movl    %ecx, (%rsp)
+;;       subq    $8, %rsp
+;;       movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       movl    8(%rsp), %edx
+;;       callq   0x2cf
+;;       addq    $8, %rsp
+;;       addq    $4, %rsp
+;;       movq    0x1c(%rsp), %r14
+;;       jmp     0x10f
+;;  109: andq    $0xfffffffffffffffe, %rax
+;;       testq   %rax, %rax
+;;       je      0x150
+;;  118: movq    0x28(%r14), %r11
+;;       movl    (%r11), %ecx
+;;       movl    0x10(%rax), %edx
+;;       cmpl    %edx, %ecx
+;;       jne     0x152
+;;  12a: pushq   %rax
+;;       popq    %rcx
+;;       movq    0x18(%rcx), %r8
+;;       movq    8(%rcx), %rbx
+;;       movq    %r8, %rdi
+;;       movq    %r14, %rsi
+;;       movl    (%rsp), %edx
+;;       movq    %rbp, %rsp
+;;       popq    %rbp
+;;       jmpq    *%rbx
+;;  143: addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  14c: ud2
+;;  14e: ud2
+;;  150: ud2
+;;  152: ud2
@@ -0,0 +1,65 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-Ctiered-compilation"
+(module
+  (func $callee)
+  (func (export "run")
+    call $callee))
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x10, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x63
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       movq    8(%r14), %rcx
+;;       movl    0x54(%rcx), %edx
+;;       subl    $1, %edx
+;;       movl    %edx, 0x54(%rcx)
+;;       cmpl    $0, %edx
+;;       jg      0x5a
+;;   48: movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x21c
+;;       movq    8(%rsp), %r14
+;;       addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;   63: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x10, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0xe4
+;;   8c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       movq    8(%r14), %rcx
+;;       movl    0x54(%rcx), %edx
+;;       subl    $1, %edx
+;;       movl    %edx, 0x54(%rcx)
+;;       cmpl    $0, %edx
+;;       jg      0xca
+;;   b8: movq    %r14, %rdi
+;;       movl    $1, %esi
+;;       callq   0x21c
+;;       movq    8(%rsp), %r14
+;;       movq    0x38(%r14), %rax
+;;       movq    %r14, %rdi
+;;       movq    %r14, %rsi
+;;       callq   *%rax
+;;       movq    8(%rsp), %r14
+;;       addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;   e4: ud2
@@ -0,0 +1,31 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-Ctiered-compilation"
+(module
+  (func (export "run")))
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x10, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x63
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       movq    8(%r14), %rcx
+;;       movl    0x54(%rcx), %edx
+;;       subl    $1, %edx
+;;       movl    %edx, 0x54(%rcx)
+;;       cmpl    $0, %edx
+;;       jg      0x5a
+;;   48: movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x133
+;;       movq    8(%rsp), %r14
+;;       addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;   63: ud2
@@ -0,0 +1,44 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = "-Ctiered-compilation"
+(module
+  (func (export "run")
+        (loop $l
+              (br $l))))
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    0x10(%r11), %r11
+;;       addq    $0x10, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x93
+;;   1c: movq    %rdi, %r14
+;;       subq    $0x10, %rsp
+;;       movq    %rdi, 8(%rsp)
+;;       movq    %rsi, (%rsp)
+;;       movq    8(%r14), %rcx
+;;       movl    0x54(%rcx), %edx
+;;       subl    $1, %edx
+;;       movl    %edx, 0x54(%rcx)
+;;       cmpl    $0, %edx
+;;       jg      0x5a
+;;   48: movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x163
+;;       movq    8(%rsp), %r14
+;;       movq    8(%r14), %rcx
+;;       movl    0x54(%rcx), %edx
+;;       subl    $1, %edx
+;;       movl    %edx, 0x54(%rcx)
+;;       cmpl    $0, %edx
+;;       jg      0x85
+;;   73: movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x163
+;;       movq    8(%rsp), %r14
+;;       jmp     0x5a
+;;   8a: addq    $0x10, %rsp
+;;       popq    %rbp
+;;       retq
+;;   93: ud2
@@ -0,0 +1,132 @@
+;;! exceptions = true
+;;! gc = true
+
+(module
+  (tag $e0)
+  (tag $e1 (param i32))
+  (tag $e2 (param i32 i64 f32 f64))
+
+  (func $throw-if (param i32)
+    (if (local.get 0) (then (throw $e1 (local.get 0)))))
+
+  (func (export "catch-none") (result i32)
+    (block $h (result i32)
+      (try_table (result i32) (catch $e1 $h)
+        (call $throw-if (i32.const 0))
+        (i32.const 42))))
+
+  (func (export "catch-payload") (param i32) (result i32)
+    (block $h (result i32)
+      (try_table (result i32) (catch $e1 $h)
+        (call $throw-if (local.get 0))
+        (i32.const 0))))
+
+  (func (export "catch-multi") (result i32 i64 f32 f64)
+    (block $h (result i32 i64 f32 f64)
+      (try_table (catch $e2 $h)
+        (throw $e2 (i32.const 1) (i64.const 2) (f32.const 3) (f64.const 4)))
+      (unreachable)))
+
+  (func (export "catch-second") (result i32)
+    (block $h0
+      (block $h1 (result i32)
+        (try_table (catch $e0 $h0) (catch $e1 $h1)
+          (throw $e1 (i32.const 7)))
+        (unreachable))
+      (return))
+    (i32.const -1))
+
+  (func (export "catch-all") (result i32)
+    (block $h
+      (try_table (catch_all $h)
+        (throw $e2 (i32.const 1) (i64.const 2) (f32.const 3) (f64.const 4))))
+    (i32.const 1))
+
+  (func (export "uncaught")
+    (block $h
+      (try_table (catch $e0 $h)
+        (throw $e1 (i32.const 1)))))
+
+  (func (export "nested") (param i32) (result i32)
+    (block $outer (result i32)
+      (block $inner
+        (try_table (catch $e1 $outer)
+          (try_table (catch $e0 $inner)
+            (if (local.get 0)
+              (then (throw $e1 (i32.const 100)))
+              (else (throw $e0)))))
+        (unreachable))
+      (i32.const 200)))
+
+  (func (export "keeps-stack") (param i32) (result i32)
+    (i32.const 10)
+    (local.get 0)
+    (block $h (param i32) (result i32)
+      (try_table (param i32) (result i32) (catch $e1 $h)
+        (call $throw-if)
+        (i32.const 1)))
+    (i32.add))
+
+  (func (export "loop-catch") (result i32)
+    (local $i i32)
+    (local $sum i32)
+    (loop $l
+      (block $h (result i32)
+        (try_table (result i32) (catch $e1 $h)
+          (throw $e1 (local.get $i))))
+      (local.set $sum (i32.add (local.get $sum)))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $l (i32.lt_u (local.get $i) (i32.const 10))))
+    (local.get $sum))
+
+  (func $deep (param i32)
+    (if (i32.eqz (local.get 0)) (then (throw $e1 (i32.const 99))))
+    (call $deep (i32.sub (local.get 0) (i32.const 1))))
+
+  (func (export "deep") (result i32)
+    (block $h (result i32)
+      (try_table (result i32) (catch $e1 $h)
+        (call $deep (i32.const 100))
+        (i32.const 0))))
+
+  (func (export "live-values") (param i64 f64) (result i64 f64 i32)
+    (local.get 0)
+    (local.get 1)
+    (block $h (result i32)
+      (try_table (result i32) (catch $e1 $h)
+        (call $throw-if (i32.const 3))
+        (i32.const 0))))
+)
+
+(assert_return (invoke "catch-none") (i32.const 42))
+(assert_return (invoke "catch-payload" (i32.const 0)) (i32.const 0))
+(assert_return (invoke "catch-payload" (i32.const 13)) (i32.const 13))
+(assert_return (invoke "catch-multi") (i32.const 1) (i64.const 2) (f32.const 3) (f64.const 4))
+(assert_return (invoke "catch-second") (i32.const 7))
+(assert_return (invoke "catch-all") (i32.const 1))
+(assert_exception (invoke "uncaught"))
+(assert_return (invoke "nested" (i32.const 0)) (i32.const 200))
+(assert_return (invoke "nested" (i32.const 1)) (i32.const 100))
+(assert_return (invoke "keeps-stack" (i32.const 0)) (i32.const 11))
+(assert_return (invoke "keeps-stack" (i32.const 5)) (i32.const 15))
+(assert_return (invoke "loop-catch") (i32.const 45))
+(assert_return (invoke "deep") (i32.const 99))
+(assert_return (invoke "live-values" (i64.const 1) (f64.const 2)) (i64.const 1) (f64.const 2) (i32.const 3))
+
+(module
+  (type $f (func (result i32)))
+  (tag $e (param funcref))
+
+  (func $forty-two (result i32) (i32.const 42))
+  (elem declare func $forty-two)
+
+  (func (export "funcref-payload") (result i32)
+    (block $h (result funcref)
+      (try_table (catch $e $h)
+        (throw $e (ref.func $forty-two)))
+      (unreachable))
+    (ref.cast (ref $f))
+    (call_ref $f))
+)
+
+(assert_return (invoke "funcref-payload") (i32.const 42))
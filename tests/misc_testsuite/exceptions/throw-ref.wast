@@ -0,0 +1,74 @@
+;;! exceptions = true
+;;! gc = true
+
+(module
+  (tag $e (param i32))
+  (tag $f (param (ref null $s)))
+  (type $s (struct (field i32)))
+
+  (func (export "rethrow") (result i32)
+    (block $outer (result i32)
+      (try_table (catch $e $outer)
+        (block $h (result i32 exnref)
+          (try_table (catch_ref $e $h)
+            (throw $e (i32.const 3)))
+          (unreachable))
+        (throw_ref))
+      (unreachable)))
+
+  (func (export "catch-all-ref") (result i32)
+    (block $outer (result i32)
+      (try_table (catch $e $outer)
+        (block $h (result exnref)
+          (try_table (catch_all_ref $h)
+            (throw $e (i32.const 5)))
+          (unreachable))
+        (throw_ref))
+      (unreachable)))
+
+  (func (export "null") (result i32)
+    (block $h (result i32)
+      (try_table (catch $e $h)
+        (throw_ref (ref.null exn)))
+      (unreachable)))
+
+  (func (export "gc-payload") (result i32)
+    (block $h (result (ref null $s))
+      (try_table (catch $f $h)
+        (throw $f (struct.new $s (i32.const 9))))
+      (unreachable))
+    (struct.get $s 0))
+)
+
+(assert_return (invoke "rethrow") (i32.const 3))
+(assert_return (invoke "catch-all-ref") (i32.const 5))
+(assert_trap (invoke "null") "null reference")
+(assert_return (invoke "gc-payload") (i32.const 9))
+
+;; Tags are matched by identity, across instances.
+(module $a
+  (tag $e (export "e") (param i32))
+  (func (export "throw") (param i32)
+    (throw $e (local.get 0))))
+(register "a" $a)
+
+(module
+  (import "a" "e" (tag $imported (param i32)))
+  (tag $local (param i32))
+  (import "a" "throw" (func $throw (param i32)))
+
+  (func (export "imported") (result i32)
+    (block $h (result i32)
+      (try_table (catch $local $h) (catch $imported $h)
+        (call $throw (i32.const 11)))
+      (unreachable)))
+
+  (func (export "local") (result i32)
+    (block $h (result i32)
+      (try_table (catch $local $h)
+        (call $throw (i32.const 11)))
+      (unreachable)))
+)
+
+(assert_return (invoke "imported") (i32.const 11))
+(assert_exception (invoke "local"))
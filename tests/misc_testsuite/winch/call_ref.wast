@@ -0,0 +1,69 @@
+;;! function_references = true
+;;! tail_call = true
+
+(module
+  (type $i2i (func (param i32) (result i32)))
+
+  (func $double (type $i2i) (i32.mul (local.get 0) (i32.const 2)))
+  (func $inc (type $i2i) (i32.add (local.get 0) (i32.const 1)))
+  (elem declare func $double $inc)
+
+  (table $t 2 (ref null $i2i))
+  (elem (table $t) (i32.const 0) (ref null $i2i) (ref.func $double) (ref.func $inc))
+
+  (func (export "call") (param i32 i32) (result i32)
+    (call_ref $i2i (local.get 0) (table.get $t (local.get 1))))
+
+  (func (export "return-call") (param i32 i32) (result i32)
+    (return_call_ref $i2i (local.get 0) (table.get $t (local.get 1))))
+
+  (func (export "call-null") (result i32)
+    (call_ref $i2i (i32.const 0) (ref.null $i2i)))
+
+  (func (export "return-call-null") (result i32)
+    (return_call_ref $i2i (i32.const 0) (ref.null $i2i)))
+
+  (func (export "is-null") (param i32) (result i32)
+    (ref.is_null (table.get $t (local.get 0))))
+
+  (func (export "as-non-null") (param i32) (result i32)
+    (call_ref $i2i (i32.const 10) (ref.as_non_null (table.get $t (local.get 0)))))
+
+  (func (export "br-on-null") (param i32) (result i32)
+    (block $null
+      (return
+        (call_ref $i2i (i32.const 20) (br_on_null $null (table.get $t (local.get 0))))))
+    (i32.const -1))
+
+  (func (export "br-on-non-null") (param i32) (result i32)
+    (call_ref $i2i
+      (i32.const 30)
+      (block $non-null (result (ref $i2i))
+        (br_on_non_null $non-null (table.get $t (local.get 0)))
+        (return (i32.const -1)))))
+
+  ;; Keeps a value below the reference in the value stack, which must be
+  ;; preserved on both paths.
+  (func (export "br-on-non-null-keep") (param i32) (result i32 i32)
+    (local $f (ref null $i2i))
+    (block $non-null (result i32 (ref $i2i))
+      (i32.const 7)
+      (br_on_non_null $non-null (table.get $t (local.get 0)))
+      (return (i32.const -1)))
+    (local.set $f)
+    (call_ref $i2i (i32.const 40) (local.get $f)))
+)
+
+(assert_return (invoke "call" (i32.const 5) (i32.const 0)) (i32.const 10))
+(assert_return (invoke "call" (i32.const 5) (i32.const 1)) (i32.const 6))
+(assert_return (invoke "return-call" (i32.const 5) (i32.const 0)) (i32.const 10))
+(assert_return (invoke "return-call" (i32.const 5) (i32.const 1)) (i32.const 6))
+(assert_trap (invoke "call-null") "null reference")
+(assert_trap (invoke "return-call-null") "null reference")
+
+(invoke "call" (i32.const 0) (i32.const 0))
+(assert_return (invoke "is-null" (i32.const 0)) (i32.const 0))
+(assert_return (invoke "as-non-null" (i32.const 0)) (i32.const 20))
+(assert_return (invoke "br-on-null" (i32.const 1)) (i32.const 21))
+(assert_return (invoke "br-on-non-null" (i32.const 0)) (i32.const 60))
+(assert_return (invoke "br-on-non-null-keep" (i32.const 1)) (i32.const 7) (i32.const 41))
@@ -0,0 +1,89 @@
+;;! tail_call = true
+
+(module
+  (type $t (func (param i64 i64) (result i64)))
+
+  (table funcref (elem $count $even $odd))
+
+  ;; Loops far enough to overflow the stack if tail calls didn't reuse the
+  ;; caller's frame.
+  (func $count (export "count") (param i64 i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (local.get 1))
+      (else
+        (return_call $count
+          (i64.sub (local.get 0) (i64.const 1))
+          (i64.add (local.get 1) (i64.const 1))))))
+
+  (func $even (param i64 i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (i64.const 1))
+      (else
+        (return_call_indirect (type $t)
+          (i64.sub (local.get 0) (i64.const 1))
+          (local.get 1)
+          (i32.const 2)))))
+
+  (func $odd (param i64 i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (i64.const 0))
+      (else
+        (return_call_indirect (type $t)
+          (i64.sub (local.get 0) (i64.const 1))
+          (local.get 1)
+          (i32.const 1)))))
+
+  (func (export "even") (param i64) (result i64)
+    (return_call_indirect (type $t) (local.get 0) (i64.const 0) (i32.const 1)))
+
+  (func (export "bad-type") (result i32)
+    (return_call_indirect (param i32) (result i32) (i32.const 0) (i32.const 0)))
+
+  ;; Stack arguments, both with fewer and more stack arguments than the caller.
+  (func $sum (param i64 i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+    (i64.add
+      (i64.add
+        (i64.add (i64.add (local.get 0) (local.get 1)) (i64.add (local.get 2) (local.get 3)))
+        (i64.add (i64.add (local.get 4) (local.get 5)) (i64.add (local.get 6) (local.get 7))))
+      (local.get 8)))
+
+  (func $many (export "many") (param i64 i64 i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+    (return_call $sum
+      (local.get 9) (local.get 8) (local.get 7) (local.get 6) (local.get 5)
+      (local.get 4) (local.get 3) (local.get 2) (local.get 1)))
+
+  (func (export "few") (param i64) (result i64)
+    (return_call $sum
+      (local.get 0) (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+      (i64.const 5) (i64.const 6) (i64.const 7) (i64.const 8)))
+
+  ;; Multiple results, forwarding the caller's return area.
+  (func $triple (param i64) (result i64 i64 i64)
+    (local.get 0)
+    (i64.add (local.get 0) (i64.const 1))
+    (i64.add (local.get 0) (i64.const 2)))
+
+  (func $triple-loop (export "triple") (param i64 i64) (result i64 i64 i64)
+    (if (result i64 i64 i64) (i64.eqz (local.get 0))
+      (then (return_call $triple (local.get 1)))
+      (else
+        (return_call $triple-loop
+          (i64.sub (local.get 0) (i64.const 1))
+          (i64.add (local.get 1) (i64.const 1))))))
+)
+
+(assert_return (invoke "count" (i64.const 0) (i64.const 0)) (i64.const 0))
+(assert_return (invoke "count" (i64.const 1000000) (i64.const 0)) (i64.const 1000000))
+(assert_return (invoke "even" (i64.const 0)) (i64.const 1))
+(assert_return (invoke "even" (i64.const 1)) (i64.const 0))
+(assert_return (invoke "even" (i64.const 1000001)) (i64.const 0))
+(assert_return (invoke "even" (i64.const 1000000)) (i64.const 1))
+(assert_trap (invoke "bad-type") "indirect call type mismatch")
+(assert_return
+  (invoke "many"
+    (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4) (i64.const 5)
+    (i64.const 6) (i64.const 7) (i64.const 8) (i64.const 9) (i64.const 10))
+  (i64.const 54))
+(assert_return (invoke "few" (i64.const 100)) (i64.const 136))
+(assert_return (invoke "triple" (i64.const 100000) (i64.const 1))
+  (i64.const 100001) (i64.const 100002) (i64.const 100003))
@@ -1,5 +1,9 @@
 fn main() {
-    if cfg!(feature = "x64") || cfg!(feature = "arm64") || cfg!(feature = "all-arch") {
+    if cfg!(feature = "x64")
+        || cfg!(feature = "arm64")
+        || cfg!(feature = "riscv64")
+        || cfg!(feature = "all-arch")
+    {
         return;
     }
 
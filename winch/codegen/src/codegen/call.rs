@@ -60,7 +60,7 @@
 
 use crate::{
     FuncEnv,
-    abi::{ABIOperand, ABISig, RetArea, vmctx},
+    abi::{ABI, ABIOperand, ABISig, LocalSlot, RetArea, vmctx},
     codegen::{BuiltinFunction, BuiltinType, Callee, CodeGenContext, CodeGenError, Emission},
     masm::{
         CalleeKind, ContextArgs, IntScratch, MacroAssembler, MemMoveDirection, OperandSize,
@@ -70,7 +70,8 @@ use crate::{
     stack::Val,
 };
 use anyhow::{Result, ensure};
-use wasmtime_environ::{FuncIndex, PtrSize, VMOffsets};
+use cranelift_codegen::binemit::CodeOffset;
+use wasmtime_environ::{FuncIndex, FuncRefIndex, PtrSize, VMOffsets, WasmValType};
 
 /// All the information needed to emit a function call.
 #[derive(Copy, Clone)]
@@ -96,10 +97,12 @@ impl FnCall {
         context.spill(masm)?;
         let ret_area = Self::make_ret_area(&sig, masm)?;
         let arg_stack_space = sig.params_stack_size();
-        let reserved_stack = masm.call(arg_stack_space, |masm| {
+        let exception_handler = context.exception_handler;
+        let reserved_stack = masm.call(arg_stack_space, exception_handler, |masm| {
             Self::assign(sig, &callee_context, ret_area.as_ref(), context, masm)?;
             Ok((kind, sig.call_conv))
         })?;
+        let stack_map = Self::stack_map(masm, context)?;
 
         Self::cleanup(
             sig,
@@ -109,7 +112,102 @@ impl FnCall {
             ret_area,
             masm,
             context,
-        )
+        )?;
+
+        if let Some((return_addr, frame_size, offsets)) = stack_map {
+            env.push_stack_map(return_addr, frame_size, offsets);
+        }
+        Ok(())
+    }
+
+    /// Orchestrates the emission of a tail call, in which the frame of the
+    /// current function is replaced by the callee's:
+    /// 1. Lowers the [`Callee`] to a ([`CalleeKind`], [ContextArgs]) pair.
+    /// 2. Spills the value stack.
+    /// 3. Assigns the arguments, placing stack arguments in temporary stack
+    ///    space and forwarding the current function's return area, if any.
+    /// 4. Moves the stack arguments to the current function's incoming
+    ///    argument area.
+    /// 5. Tears down the current frame and jumps to the callee.
+    ///
+    /// Since the caller of the current function is responsible for freeing
+    /// its incoming argument area, the callee's stack arguments must fit in
+    /// it. When that's not the case, no code is emitted and `false` is
+    /// returned, so that a regular call followed by a return is emitted
+    /// instead.
+    pub fn emit_return_call<M: MacroAssembler>(
+        env: &mut FuncEnv<M::Ptr>,
+        masm: &mut M,
+        context: &mut CodeGenContext<Emission>,
+        callee: Callee,
+        caller_sig: &ABISig,
+    ) -> Result<bool> {
+        let arg_stack_space = env.callee_sig::<M::ABI>(&callee)?.params_stack_size();
+        if arg_stack_space > caller_sig.params_stack_size() {
+            return Ok(false);
+        }
+
+        let (kind, callee_context) = Self::lower(env, context.vmoffsets, &callee, context, masm)?;
+        let sig = env.callee_sig::<M::ABI>(&callee)?;
+        context.spill(masm)?;
+        let ret_area = context.frame.results_base_slot.map(RetArea::slot);
+        let base = masm.sp_offset()?;
+        masm.reserve_stack(arg_stack_space)?;
+        Self::assign(sig, &callee_context, ret_area.as_ref(), context, masm)?;
+
+        // The incoming arguments of the current function are no longer
+        // needed, as every value in the value stack has been spilled to the
+        // current frame.
+        let arg_base_offset: u32 = <M::ABI as ABI>::arg_base_offset().into();
+        let word_bytes = <M::ABI as ABI>::word_bytes();
+        for offset in (0..arg_stack_space).step_by(word_bytes as usize) {
+            let src = masm.address_at_sp(SPOffset::from_u32(offset))?;
+            let dst = masm.local_address(&LocalSlot::stack_arg(
+                WasmValType::I64,
+                arg_base_offset + offset,
+            ))?;
+            masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+                masm.load(src, scratch.writable(), OperandSize::S64)?;
+                masm.store(scratch.inner().into(), dst, OperandSize::S64)
+            })?;
+        }
+        masm.return_call(kind)?;
+
+        // Code after the tail call is unreachable. The arguments are left in
+        // the value stack, which is truncated when handling unreachable code,
+        // but the registers holding the callee must be released.
+        masm.reset_stack_pointer(base)?;
+        match kind {
+            CalleeKind::Indirect(r) => context.free_reg(r),
+            _ => {}
+        }
+        for loc in callee_context.as_slice() {
+            match loc {
+                VMContextLoc::Reg(r) => context.free_reg(*r),
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+
+    /// Computes the stack map for the call that was just emitted, describing
+    /// the GC references that are live across it, if any.
+    ///
+    /// The value stack is spilled before the call, so the live references
+    /// are either in locals or in the spilled value stack entries.
+    fn stack_map<M: MacroAssembler>(
+        masm: &M,
+        context: &CodeGenContext<Emission>,
+    ) -> Result<Option<(CodeOffset, u32, Vec<u32>)>> {
+        let offsets = context.gc_ref_stack_offsets(masm)?;
+        if offsets.is_empty() {
+            return Ok(None);
+        }
+        // The frame size is the distance between the frame pointer and the
+        // stack pointer at the return address.
+        let frame_base = <M::ABI as ABI>::initial_frame_size() - <M::ABI as ABI>::arg_base_offset();
+        let frame_size = masm.sp_offset()?.as_u32() + u32::from(frame_base);
+        Ok(Some((masm.current_code_offset()?, frame_size, offsets)))
     }
 
     /// Calculates the return area for the callee, if any.
@@ -149,6 +247,15 @@ impl FnCall {
                 Self::lower_funcref(env.callee_sig::<M::ABI>(callee)?, ptr, context, masm)
             }
             Callee::Local(i) => Ok(Self::lower_local(env, *i)),
+            Callee::TieredLocal(i) => {
+                // Tiered compilation makes every defined function escape, so
+                // that they all have a `VMFuncRef`.
+                let func = &env.translation.module.functions[*i];
+                ensure!(func.is_escaping(), CodeGenError::unexpected_function_call());
+                let func_ref = func.func_ref;
+                let sig = env.callee_sig::<M::ABI>(callee)?;
+                Self::lower_tiered_local(func_ref, sig, context, masm, vmoffsets)
+            }
             Callee::Import(i) => {
                 let sig = env.callee_sig::<M::ABI>(callee)?;
                 Self::lower_import(*i, sig, context, masm, vmoffsets)
@@ -185,6 +292,31 @@ impl FnCall {
         )
     }
 
+    /// Lowers a local function in code compiled for tiered compilation by
+    /// loading the address of its code from its `VMFuncRef` to the next
+    /// available register.
+    fn lower_tiered_local<M: MacroAssembler, P: PtrSize>(
+        func_ref: FuncRefIndex,
+        sig: &ABISig,
+        context: &mut CodeGenContext<Emission>,
+        masm: &mut M,
+        vmoffsets: &VMOffsets<P>,
+    ) -> Result<(CalleeKind, ContextArgs)> {
+        let callee =
+            context.without::<Result<Reg>, M, _>(&sig.regs, masm, |context, masm| {
+                context.any_gpr(masm)
+            })??;
+        let callee_body_offset =
+            vmoffsets.vmctx_func_ref(func_ref) + u32::from(vmoffsets.ptr.vm_func_ref_wasm_call());
+        let callee_addr = masm.address_at_vmctx(callee_body_offset)?;
+        masm.load_ptr(callee_addr, writable!(callee))?;
+
+        Ok((
+            CalleeKind::indirect(callee),
+            ContextArgs::pinned_callee_and_caller_vmctx(),
+        ))
+    }
+
     /// Lowers a function import by loading its address to the next available
     /// register.
     fn lower_import<M: MacroAssembler, P: PtrSize>(
@@ -338,22 +470,44 @@ impl FnCall {
 
         if sig.has_stack_results() {
             let operand = sig.params.unwrap_results_area_operand();
-            let base = ret_area.unwrap().unwrap_sp();
-            let addr = masm.address_from_sp(base)?;
-
-            match operand {
-                &ABIOperand::Reg { ty, reg, .. } => {
-                    masm.compute_addr(addr, writable!(reg), ty.try_into()?)?;
+            match ret_area.unwrap() {
+                RetArea::SP(base) => {
+                    let addr = masm.address_from_sp(*base)?;
+
+                    match operand {
+                        &ABIOperand::Reg { ty, reg, .. } => {
+                            masm.compute_addr(addr, writable!(reg), ty.try_into()?)?;
+                        }
+                        &ABIOperand::Stack { ty, offset, .. } => {
+                            let slot = masm.address_at_sp(SPOffset::from_u32(offset))?;
+                            // Don't rely on `ABI::scratch_for` as we always use
+                            // an int register as the return pointer.
+                            masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+                                masm.compute_addr(addr, scratch.writable(), ty.try_into()?)?;
+                                masm.store(scratch.inner().into(), slot, ty.try_into()?)
+                            })?;
+                        }
+                    }
                 }
-                &ABIOperand::Stack { ty, offset, .. } => {
-                    let slot = masm.address_at_sp(SPOffset::from_u32(offset))?;
-                    // Don't rely on `ABI::scratch_for` as we always use
-                    // an int register as the return pointer.
-                    masm.with_scratch::<IntScratch, _>(|masm, scratch| {
-                        masm.compute_addr(addr, scratch.writable(), ty.try_into()?)?;
-                        masm.store(scratch.inner().into(), slot, ty.try_into()?)
-                    })?;
+                // The return area of a tail call is the one of the current
+                // function, so the pointer to it is forwarded as is.
+                RetArea::Slot(local) => {
+                    let addr = masm.local_address(local)?;
+
+                    match operand {
+                        &ABIOperand::Reg { reg, .. } => {
+                            masm.load_ptr(addr, writable!(reg))?;
+                        }
+                        &ABIOperand::Stack { ty, offset, .. } => {
+                            let slot = masm.address_at_sp(SPOffset::from_u32(offset))?;
+                            masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+                                masm.load_ptr(addr, scratch.writable())?;
+                                masm.store(scratch.inner().into(), slot, ty.try_into()?)
+                            })?;
+                        }
+                    }
                 }
+                RetArea::Uninit => unreachable!(),
             }
         }
         Ok(())
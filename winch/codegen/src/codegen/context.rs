@@ -1,6 +1,7 @@
-use anyhow::{Result, bail, ensure};
+use anyhow::{Result, anyhow, bail, ensure};
+use cranelift_codegen::MachLabel;
 use wasmparser::{Ieee32, Ieee64};
-use wasmtime_environ::{VMOffsets, WasmHeapType, WasmValType};
+use wasmtime_environ::{VMOffsets, WasmHeapTopType, WasmValType};
 
 use super::ControlStackFrame;
 use crate::{
@@ -43,6 +44,11 @@ pub(crate) struct CodeGenContext<'a, P: CodeGenPhase> {
     pub reachable: bool,
     /// A reference to the VMOffsets.
     pub vmoffsets: &'a VMOffsets<u8>,
+    /// The landing pad of the innermost enclosing `try_table`, if any.
+    ///
+    /// Every call emitted while this is set is registered as a try-call
+    /// unwinding to this label.
+    pub exception_handler: Option<MachLabel>,
 }
 
 impl<'a> CodeGenContext<'a, Emission> {
@@ -121,6 +127,7 @@ impl<'a> CodeGenContext<'a, Prologue> {
             frame,
             reachable: true,
             vmoffsets,
+            exception_handler: None,
         }
     }
 
@@ -132,6 +139,7 @@ impl<'a> CodeGenContext<'a, Prologue> {
             reachable: self.reachable,
             vmoffsets: self.vmoffsets,
             frame: self.frame.for_emission(),
+            exception_handler: self.exception_handler,
         }
     }
 }
@@ -157,10 +165,11 @@ impl<'a> CodeGenContext<'a, Emission> {
             F32 | F64 => self.reg_for_class(RegClass::Float, masm),
             // All of our supported architectures use the float registers for vector operations.
             V128 => self.reg_for_class(RegClass::Float, masm),
-            Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func | WasmHeapType::Extern => {
-                    self.reg_for_class(RegClass::Int, masm)
-                }
+            Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func
+                | WasmHeapTopType::Any
+                | WasmHeapTopType::Extern
+                | WasmHeapTopType::Exn => self.reg_for_class(RegClass::Int, masm),
                 _ => bail!(CodeGenError::unsupported_wasm_type()),
             },
         }
@@ -816,6 +825,35 @@ impl<'a> CodeGenContext<'a, Emission> {
         }
     }
 
+    /// Returns the offsets, relative to the stack pointer, of all the GC
+    /// references held in the current frame: the ones stored in locals and
+    /// the ones spilled from the value stack.
+    ///
+    /// This function assumes that the value stack has been spilled, which is
+    /// the case at every call site.
+    pub fn gc_ref_stack_offsets<M: MacroAssembler>(&self, masm: &M) -> Result<Vec<u32>> {
+        let sp_offset = masm.sp_offset()?.as_u32();
+        let locals = self
+            .frame
+            .wasm_locals()
+            .filter(|slot| slot.addressed_from_sp() && slot.ty.is_vmgcref_type())
+            .map(|slot| slot.offset);
+        let spilled = self.stack.inner().iter().filter_map(|v| match v {
+            Val::Memory(mem) if mem.ty.is_vmgcref_type() => Some(mem.slot.offset.as_u32()),
+            _ => None,
+        });
+        let mut offsets = locals
+            .chain(spilled)
+            .map(|offset| {
+                sp_offset
+                    .checked_sub(offset)
+                    .ok_or_else(|| anyhow!(CodeGenError::invalid_local_offset()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        offsets.sort_unstable();
+        Ok(offsets)
+    }
+
     /// Load the [VMContext] pointer into the designated pinned register.
     pub fn load_vmctx<M>(&mut self, masm: &mut M) -> Result<()>
     where
@@ -4,7 +4,10 @@ use crate::{
     isa::TargetIsa,
 };
 use anyhow::Result;
-use cranelift_codegen::ir::{UserExternalName, UserExternalNameRef};
+use cranelift_codegen::{
+    binemit::CodeOffset,
+    ir::{UserExternalName, UserExternalNameRef},
+};
 use std::collections::{
     HashMap,
     hash_map::Entry::{Occupied, Vacant},
@@ -12,9 +15,9 @@ use std::collections::{
 use std::mem;
 use wasmparser::BlockType;
 use wasmtime_environ::{
-    BuiltinFunctionIndex, FuncIndex, GlobalIndex, IndexType, Memory, MemoryIndex,
-    ModuleTranslation, ModuleTypesBuilder, PrimaryMap, PtrSize, Table, TableIndex, TypeConvert,
-    TypeIndex, VMOffsets, WasmHeapType, WasmValType,
+    BuiltinFunctionIndex, DefinedFuncIndex, FuncIndex, GlobalIndex, IndexType, Memory, MemoryIndex,
+    ModuleTranslation, ModuleTypesBuilder, PrimaryMap, PtrSize, Table, TableIndex, Tunables,
+    TypeConvert, TypeIndex, VMOffsets, WasmHeapType, WasmRefType, WasmValType,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -78,6 +81,10 @@ impl HeapData {
 pub(crate) enum Callee {
     /// Locally defined function.
     Local(FuncIndex),
+    /// Locally defined function in code compiled for tiered compilation,
+    /// which is called through its `VMFuncRef` so that calls pick up the
+    /// optimized code once the callee is tiered up.
+    TieredLocal(FuncIndex),
     /// Imported function.
     Import(FuncIndex),
     /// Function reference.
@@ -96,6 +103,8 @@ pub(crate) enum Callee {
 pub struct FuncEnv<'a, 'translation: 'a, 'data: 'translation, P: PtrSize> {
     /// Offsets to the fields within the `VMContext` ptr.
     pub vmoffsets: &'a VMOffsets<P>,
+    /// The index of the function being compiled.
+    pub func_index: DefinedFuncIndex,
     /// Metadata about the translation process of a WebAssembly module.
     pub translation: &'translation ModuleTranslation<'data>,
     /// The module's function types.
@@ -124,6 +133,11 @@ pub struct FuncEnv<'a, 'translation: 'a, 'data: 'translation, P: PtrSize> {
     pub page_size_log2: u8,
     name_map: PrimaryMap<UserExternalNameRef, UserExternalName>,
     name_intern: HashMap<UserExternalName, UserExternalNameRef>,
+    /// Whether or not the function being compiled accesses the GC heap.
+    pub needs_gc_heap: bool,
+    /// The stack maps recorded at each call site that has live GC references,
+    /// as `(return address, frame size, offsets from SP)` triples.
+    stack_maps: Vec<(CodeOffset, u32, Vec<u32>)>,
 }
 
 pub fn ptr_type_from_ptr_size(size: u8) -> WasmValType {
@@ -136,6 +150,7 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
     /// Create a new function environment.
     pub fn new(
         vmoffsets: &'a VMOffsets<P>,
+        func_index: DefinedFuncIndex,
         translation: &'translation ModuleTranslation<'data>,
         types: &'translation ModuleTypesBuilder,
         builtins: &'translation mut BuiltinFunctions,
@@ -144,6 +159,7 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
     ) -> Self {
         Self {
             vmoffsets,
+            func_index,
             translation,
             types,
             resolved_tables: HashMap::new(),
@@ -158,6 +174,8 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
             builtins,
             name_map: Default::default(),
             name_intern: Default::default(),
+            needs_gc_heap: false,
+            stack_maps: Default::default(),
         }
     }
 
@@ -172,10 +190,12 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
     }
 
     /// Resolves a function [`Callee`] from an index.
-    pub(crate) fn callee_from_index(&mut self, idx: FuncIndex) -> Callee {
+    pub(crate) fn callee_from_index(&mut self, idx: FuncIndex, tunables: &Tunables) -> Callee {
         let import = self.translation.module.is_imported_function(idx);
         if import {
             Callee::Import(idx)
+        } else if tunables.tiered_compilation {
+            Callee::TieredLocal(idx)
         } else {
             Callee::Local(idx)
         }
@@ -242,7 +262,15 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
                     import_from: from_offset,
                     offset: base_offset,
                     current_elems_offset,
-                    element_size: OperandSize::from_bytes(self.vmoffsets.ptr.size()),
+                    element_size: if self.translation.module.tables[index]
+                        .ref_type
+                        .is_vmgcref_type()
+                    {
+                        // GC references are stored as 32-bit indices.
+                        OperandSize::S32
+                    } else {
+                        OperandSize::from_bytes(self.vmoffsets.ptr.size())
+                    },
                     current_elements_size: OperandSize::from_bytes(
                         self.vmoffsets.size_of_vmtable_definition_current_elements(),
                     ),
@@ -318,12 +346,21 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
         self.table_access_spectre_mitigation
     }
 
+    /// Converts a [wasmparser::HeapType] into a [WasmHeapType].
+    pub(crate) fn convert_heap_type(&self, ty: wasmparser::HeapType) -> Result<WasmHeapType> {
+        Ok(TypeConverter::new(self.translation, self.types).convert_heap_type(ty)?)
+    }
+
+    pub(crate) fn convert_ref_type(&self, ty: wasmparser::RefType) -> Result<WasmRefType> {
+        Ok(TypeConverter::new(self.translation, self.types).convert_ref_type(ty)?)
+    }
+
     pub(crate) fn callee_sig<'b, A>(&'b mut self, callee: &'b Callee) -> Result<&'b ABISig>
     where
         A: ABI,
     {
         match callee {
-            Callee::Local(idx) | Callee::Import(idx) => {
+            Callee::Local(idx) | Callee::TieredLocal(idx) | Callee::Import(idx) => {
                 if self.resolved_callees.contains_key(idx) {
                     Ok(self.resolved_callees.get(idx).unwrap())
                 } else {
@@ -382,6 +419,23 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
         self.name_intern.clear();
         mem::take(&mut self.name_map)
     }
+
+    /// Records the GC references that are live in the current frame across
+    /// the call whose return address is `return_addr`.
+    pub(crate) fn push_stack_map(
+        &mut self,
+        return_addr: CodeOffset,
+        frame_size: u32,
+        offsets: Vec<u32>,
+    ) {
+        self.stack_maps.push((return_addr, frame_size, offsets));
+    }
+
+    /// Extracts the stack maps that were recorded while translating this
+    /// function.
+    pub fn take_stack_maps(&mut self) -> Vec<(CodeOffset, u32, Vec<u32>)> {
+        mem::take(&mut self.stack_maps)
+    }
 }
 
 /// A wrapper struct over a reference to a [ModuleTranslation] and
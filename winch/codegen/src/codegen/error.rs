@@ -35,6 +35,10 @@ pub(crate) enum CodeGenError {
     /// Unsupported eager initialization of tables.
     #[error("Unsupported eager initialization of tables")]
     UnsupportedTableEagerInit,
+    /// Unsupported garbage collector.
+    #[cfg(feature = "gc")]
+    #[error("Unsupported GC collector; enable a GC collector Cargo feature supported by Winch")]
+    UnsupportedGcCollector,
     /// An internal error.
     ///
     /// This error means that an internal invariant was not met and usually
@@ -111,6 +115,11 @@ impl CodeGenError {
         Self::UnsupportedTableEagerInit
     }
 
+    #[cfg(feature = "gc")]
+    pub(crate) const fn unsupported_gc_collector() -> Self {
+        Self::UnsupportedGcCollector
+    }
+
     pub(crate) const fn unimplemented_wasm_instruction() -> Self {
         Self::UnimplementedWasmInstruction
     }
@@ -0,0 +1,200 @@
+//! Support for the exception handling proposal.
+//!
+//! A `try_table` is compiled as a regular block whose calls are registered as
+//! try-calls, unwinding to a landing pad emitted right after the block's
+//! body. The runtime's unwinder only knows about the landing pad: every
+//! handler is registered as a catch-all, and the landing pad matches the tag
+//! of the exception against the `try_table`'s catch clauses, rethrowing it if
+//! none of them applies.
+//!
+//! The unwinder resumes execution at the landing pad with the frame pointer
+//! of the function and the exception reference as the only live values;
+//! since the value stack is spilled at the start of the block and before each
+//! call, the landing pad only needs to restore the stack pointer to the
+//! block's base offset.
+
+use crate::{
+    codegen::{
+        CodeGen, CodeGenError, ControlStackFrame, Emission, UnconditionalBranch, control_index,
+    },
+    masm::{IntCmpKind, MacroAssembler, OperandSize},
+    reg::writable,
+    stack::TypedReg,
+};
+use anyhow::{Result, ensure};
+use cranelift_codegen::MachLabel;
+use wasmparser::{Catch, VisitOperator};
+use wasmtime_environ::{TagIndex, WasmHeapType, WasmRefType, WasmValType};
+
+/// The metadata of a `try_table` being compiled.
+pub(crate) struct TryTable {
+    /// The index of the `try_table`'s block in the control frames.
+    frame: usize,
+    /// The landing pad of the `try_table`.
+    landing_pad: MachLabel,
+    /// The catch clauses, in the order in which they must be matched.
+    catches: Vec<Catch>,
+    /// The landing pad of the enclosing `try_table`, if any, to restore
+    /// when exiting this one.
+    enclosing_handler: Option<MachLabel>,
+    /// Reachability state when entering the `try_table`. The landing pad is
+    /// only emitted if the body is reachable.
+    reachable: bool,
+}
+
+impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M, Emission>
+where
+    M: MacroAssembler,
+{
+    /// Emits the start of a `try_table`.
+    pub fn emit_try_table(&mut self, try_table: wasmparser::TryTable) -> Result<()> {
+        let frame = ControlStackFrame::block(
+            self.env.resolve_block_sig(try_table.ty)?,
+            self.masm,
+            &mut self.context,
+        )?;
+        let landing_pad = self.masm.get_label()?;
+        self.try_tables.push(TryTable {
+            frame: self.control_frames.len(),
+            landing_pad,
+            catches: try_table.catches,
+            enclosing_handler: self.context.exception_handler,
+            reachable: self.context.reachable,
+        });
+        self.control_frames.push(frame);
+        if self.context.reachable {
+            self.context.exception_handler = Some(landing_pad);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the innermost control frame is a `try_table`.
+    pub fn is_try_table_end(&self) -> bool {
+        self.try_tables
+            .last()
+            .is_some_and(|t| t.frame + 1 == self.control_frames.len())
+    }
+
+    /// Emits the end of the innermost `try_table`, followed by its landing
+    /// pad.
+    pub fn emit_try_table_end(&mut self) -> Result<()> {
+        let try_table = self
+            .try_tables
+            .pop()
+            .ok_or_else(|| CodeGenError::control_frame_expected())?;
+        self.context.exception_handler = try_table.enclosing_handler;
+        let mut frame = self.pop_control_frame()?;
+
+        // Jump over the landing pad when exiting the body normally.
+        if self.context.reachable {
+            ensure!(
+                frame.stack_state().target_len == self.context.stack.len(),
+                CodeGenError::control_frame_state_mismatch()
+            );
+            self.context.br::<_, _, UnconditionalBranch>(
+                &mut frame,
+                self.masm,
+                |masm, cx, frame| {
+                    frame.pop_abi_results::<M, _>(cx, masm, |results, _, _| {
+                        Ok(results.ret_area().copied())
+                    })
+                },
+            )?;
+        }
+
+        if try_table.reachable {
+            self.emit_landing_pad(&try_table, &frame)?;
+        }
+
+        if frame.is_next_sequence_reachable() {
+            self.context.reachable = true;
+            frame.ensure_stack_state(self.masm, &mut self.context)?;
+            frame.bind_end(self.masm, &mut self.context)?;
+        }
+        Ok(())
+    }
+
+    /// Emits the landing pad of the given `try_table`, which branches to the
+    /// label of the first catch clause matching the exception, or rethrows
+    /// it.
+    fn emit_landing_pad(&mut self, try_table: &TryTable, frame: &ControlStackFrame) -> Result<()> {
+        let state = *frame.stack_state();
+        self.masm.bind(try_table.landing_pad)?;
+        self.context.reachable = true;
+        self.masm.reset_stack_pointer(state.base_offset)?;
+        self.context.truncate_stack_to(state.base_len)?;
+
+        let exn = self.context.any_gpr(self.masm)?;
+        self.masm.landing_pad(state.base_offset, writable!(exn))?;
+        // The pinned `VMContext` register is not restored by the unwinder.
+        self.context.load_vmctx(self.masm)?;
+        let exn_ty = WasmValType::Ref(WasmRefType {
+            nullable: false,
+            heap_type: WasmHeapType::Exn,
+        });
+        self.context.stack.push(TypedReg::new(exn_ty, exn).into());
+        // Keep the exception reference in memory, so that it survives the
+        // calls to match the catch clauses.
+        self.context.spill(self.masm)?;
+        let exn_index = self.context.stack.len() - 1;
+
+        for catch in &try_table.catches {
+            let (tag, label, is_ref) = match *catch {
+                Catch::One { tag, label } => (Some(TagIndex::from_u32(tag)), label, false),
+                Catch::OneRef { tag, label } => (Some(TagIndex::from_u32(tag)), label, true),
+                Catch::All { label } => (None, label, false),
+                Catch::AllRef { label } => (None, label, true),
+            };
+
+            let next = self.masm.get_label()?;
+            // The state in which the next clause is matched.
+            let mut next_sp_offset = self.masm.sp_offset()?;
+            if let Some(tag) = tag {
+                let exn = self.load_stack_val(exn_index)?;
+                self.context.stack.push(exn.into());
+                self.emit_exn_tag_matches(tag)?;
+                let matches = self.context.pop_to_reg(self.masm, None)?;
+                self.masm.branch(
+                    IntCmpKind::Eq,
+                    matches.reg,
+                    matches.reg.into(),
+                    next,
+                    OperandSize::S32,
+                )?;
+                self.context.free_reg(matches);
+                next_sp_offset = self.masm.sp_offset()?;
+                self.emit_exn_payload(tag, exn_index)?;
+            }
+            if is_ref {
+                let exn = self.load_stack_val(exn_index)?;
+                self.context.stack.push(exn.into());
+            }
+
+            let index = control_index(label, self.control_frames.len())?;
+            let target = &mut self.control_frames[index];
+            self.context.br::<_, _, UnconditionalBranch>(
+                target,
+                self.masm,
+                |masm, cx, frame| {
+                    frame.pop_abi_results::<M, _>(cx, masm, |results, _, _| {
+                        Ok(results.ret_area().copied())
+                    })
+                },
+            )?;
+
+            // Any clauses after a catch-all are never matched.
+            if tag.is_none() {
+                return Ok(());
+            }
+
+            self.masm.bind(next)?;
+            self.context.reachable = true;
+            self.masm.reset_stack_pointer(next_sp_offset)?;
+            self.context.truncate_stack_to(exn_index + 1)?;
+        }
+
+        // No catch clause matched: rethrow the exception.
+        self.emit_throw_ref()?;
+        self.visit_unreachable()
+    }
+}
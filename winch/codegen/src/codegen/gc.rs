@@ -0,0 +1,56 @@
+//! Interface to compiling GC-related instructions.
+//!
+//! This module and its interface are implemented twice: once when the `gc`
+//! cargo feature is enabled and once when the feature is disabled, in the same
+//! way as Cranelift's GC support. Both implementations expose the same set of
+//! `emit_*` methods on [`CodeGen`], so that the visitor doesn't need to be
+//! aware of the feature.
+//!
+//! The lowering of the GC instructions mirrors the one in
+//! `wasmtime_cranelift::func_environ::gc`: object allocation and the complex
+//! array operations are performed through libcalls, while field accesses and
+//! the collector-specific barriers are emitted inline.
+
+use crate::{
+    codegen::{CodeGen, CodeGenError, Emission},
+    masm::MacroAssembler,
+    stack::TypedReg,
+};
+use anyhow::Result;
+use wasmtime_environ::WasmValType;
+
+#[cfg(feature = "gc")]
+mod enabled;
+
+#[cfg(not(feature = "gc"))]
+mod disabled;
+
+impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M, Emission>
+where
+    M: MacroAssembler,
+{
+    /// Pops the value at the top of the value stack and pushes it back with
+    /// the given type.
+    ///
+    /// Used by instructions that only change the static type of a
+    /// reference, like `any.convert_extern` or `ref.cast`.
+    pub fn emit_retype_top(&mut self, ty: WasmValType) -> Result<()> {
+        let top = self.context.pop_to_reg(self.masm, None)?;
+        self.context.stack.push(TypedReg::new(ty, top.reg).into());
+        Ok(())
+    }
+
+    /// Moves a copy of the value at the given value stack index to a new
+    /// register, without popping it.
+    pub fn load_stack_val(&mut self, index: usize) -> Result<TypedReg> {
+        let val = *self
+            .context
+            .stack
+            .inner()
+            .get(index)
+            .ok_or_else(|| CodeGenError::unexpected_value_stack_index())?;
+        let reg = self.context.reg_for_type(val.ty(), self.masm)?;
+        self.context.move_val_to_reg(&val, reg, self.masm)?;
+        Ok(TypedReg::new(val.ty(), reg))
+    }
+}
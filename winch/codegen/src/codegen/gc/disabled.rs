@@ -0,0 +1,132 @@
+//! GC support when the `gc` cargo feature is disabled.
+//!
+//! Every GC instruction is rejected at compile time.
+
+use crate::{
+    codegen::{BuiltinFunction, CodeGen, CodeGenError, Emission},
+    masm::MacroAssembler,
+};
+use anyhow::Result;
+use wasmtime_environ::{GlobalIndex, TableIndex, TagIndex, TypeIndex, WasmRefType};
+
+fn disabled<T>() -> Result<T> {
+    Err(CodeGenError::unimplemented_wasm_instruction().into())
+}
+
+impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M, Emission>
+where
+    M: MacroAssembler,
+{
+    pub fn emit_struct_new(&mut self, _type_index: TypeIndex, _default: bool) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_struct_get(
+        &mut self,
+        _type_index: TypeIndex,
+        _field_index: u32,
+        _signed: bool,
+    ) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_struct_set(&mut self, _type_index: TypeIndex, _field_index: u32) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_new(&mut self, _type_index: TypeIndex, _default: bool) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_new_fixed(&mut self, _type_index: TypeIndex, _len: u32) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_new_data(&mut self, _type_index: TypeIndex, _data_index: u32) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_new_elem(&mut self, _type_index: TypeIndex, _elem_index: u32) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_get(&mut self, _type_index: TypeIndex, _signed: bool) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_set(&mut self, _type_index: TypeIndex) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_len(&mut self) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_fill(&mut self, _type_index: TypeIndex) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_copy(&mut self) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_init_data(&mut self, _type_index: TypeIndex, _data_index: u32) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_array_init_elem(&mut self, _type_index: TypeIndex, _elem_index: u32) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_ref_test(&mut self, _ty: WasmRefType) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_ref_cast(&mut self, _ty: WasmRefType) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_br_on_cast_cond(&mut self, _ty: WasmRefType, _fail: bool) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_gc_table_get(&mut self, _table_index: TableIndex, _ty: WasmRefType) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_gc_table_set(&mut self, _table_index: TableIndex, _ty: WasmRefType) -> Result<()> {
+        disabled()
+    }
+
+    pub fn gc_table_grow_builtin(&mut self) -> Result<BuiltinFunction> {
+        disabled()
+    }
+
+    pub fn gc_table_fill_builtin(&mut self) -> Result<BuiltinFunction> {
+        disabled()
+    }
+
+    pub fn emit_gc_global_get(&mut self, _index: GlobalIndex, _ty: WasmRefType) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_gc_global_set(&mut self, _index: GlobalIndex, _ty: WasmRefType) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_throw(&mut self, _tag: TagIndex) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_throw_ref(&mut self) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_exn_tag_matches(&mut self, _tag: TagIndex) -> Result<()> {
+        disabled()
+    }
+
+    pub fn emit_exn_payload(&mut self, _tag: TagIndex, _exn_index: usize) -> Result<()> {
+        disabled()
+    }
+}
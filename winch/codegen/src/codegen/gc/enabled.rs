@@ -0,0 +1,1941 @@
+//! GC support when the `gc` cargo feature is enabled.
+//!
+//! GC references are 32-bit indices into the GC heap; the null reference is
+//! `0` and `i31ref`s are tagged with [`I31_DISCRIMINANT`]. Objects are only
+//! allocated through the `gc_alloc_raw` libcall, which makes the new object
+//! visible to the collector, and the GC references that are live across calls
+//! are found by the collector through the stack maps derived from the value
+//! stack at each call site.
+
+use crate::{
+    codegen::{BuiltinFunction, Callee, CodeGen, CodeGenError, Emission, FnCall},
+    isa::reg::{Reg, writable},
+    masm::{
+        Extend, Imm, IntCmpKind, IntScratch, MacroAssembler, OperandSize, RegImm, ShiftKind, Signed,
+    },
+    stack::{TypedReg, Val},
+};
+use anyhow::{Result, bail, ensure};
+#[cfg(feature = "gc-drc")]
+use cranelift_codegen::MachLabel;
+use wasmparser::{Ieee32, Ieee64};
+use wasmtime_cranelift::{
+    TRAP_ALLOCATION_TOO_LARGE, TRAP_ARRAY_OUT_OF_BOUNDS, TRAP_CAST_FAILURE, TRAP_INTERNAL_ASSERT,
+    TRAP_NULL_REFERENCE,
+};
+use wasmtime_environ::{
+    Collector, GcArrayLayout, GcExceptionLayout, GcStructLayout, GcTypeLayouts, GlobalIndex,
+    I31_DISCRIMINANT, ModuleInternedTypeIndex, PtrSize, TableIndex, TagIndex, TypeIndex,
+    VM_GC_HEADER_KIND_OFFSET, VM_GC_HEADER_TYPE_INDEX_OFFSET, VM_GC_KIND_SIZE, VMGcKind,
+    WasmExnType, WasmFieldType, WasmHeapTopType, WasmHeapType, WasmRefType, WasmStorageType,
+    WasmValType, packed_option::ReservedValue,
+};
+
+impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M, Emission>
+where
+    M: MacroAssembler,
+{
+    /// Returns the collector targeted by the current compilation, erroring if
+    /// Winch doesn't support it.
+    ///
+    /// Every instruction that accesses the GC heap goes through this method,
+    /// so it also flags the function as needing the GC heap.
+    fn gc_collector(&mut self) -> Result<Collector> {
+        self.env.needs_gc_heap = true;
+        match self.tunables.collector {
+            #[cfg(feature = "gc-drc")]
+            Some(Collector::DeferredReferenceCounting) => Ok(Collector::DeferredReferenceCounting),
+            #[cfg(feature = "gc-generational")]
+            Some(Collector::Generational) => Ok(Collector::Generational),
+            _ => bail!(CodeGenError::unsupported_gc_collector()),
+        }
+    }
+
+    /// Returns the object layouts used by the given collector.
+    fn gc_layouts(collector: Collector) -> &'static dyn GcTypeLayouts {
+        match collector {
+            #[cfg(feature = "gc-drc")]
+            Collector::DeferredReferenceCounting => &wasmtime_environ::drc::DrcTypeLayouts,
+            #[cfg(feature = "gc-generational")]
+            Collector::Generational => &wasmtime_environ::generational::GenerationalTypeLayouts,
+            #[allow(unreachable_patterns, reason = "depends on the enabled collectors")]
+            _ => unreachable!("unsupported collectors are rejected by `gc_collector`"),
+        }
+    }
+
+    /// Returns the alignment to request when allocating an object with the
+    /// given layout alignment.
+    fn gc_alloc_align(collector: Collector, layout_align: u32) -> u32 {
+        match collector {
+            #[cfg(feature = "gc-generational")]
+            Collector::Generational => wasmtime_environ::generational::HEADER_ALIGN,
+            _ => layout_align,
+        }
+    }
+
+    /// Resolves the module-interned index of the given type index.
+    fn interned_type_index(&self, index: TypeIndex) -> ModuleInternedTypeIndex {
+        self.env.translation.module.types[index].unwrap_module_type_index()
+    }
+
+    /// Returns the layout of the given struct type.
+    fn struct_layout(
+        &self,
+        collector: Collector,
+        index: ModuleInternedTypeIndex,
+    ) -> Result<GcStructLayout> {
+        let ty = self.env.types.unwrap_struct(index)?;
+        Ok(Self::gc_layouts(collector).struct_layout(ty))
+    }
+
+    /// Returns the layout and the element type of the given array type.
+    fn array_layout(
+        &self,
+        collector: Collector,
+        index: ModuleInternedTypeIndex,
+    ) -> Result<(GcArrayLayout, WasmStorageType)> {
+        let ty = self.env.types.unwrap_array(index)?;
+        Ok((
+            Self::gc_layouts(collector).array_layout(ty),
+            ty.0.element_type,
+        ))
+    }
+
+    /// Traps if the given offset, relative to the start of the GC heap, is
+    /// past the end of the GC heap.
+    fn emit_gc_heap_bounds_check(&mut self, end: Reg) -> Result<()> {
+        let store_context = self.env.vmoffsets.ptr.vmctx_store_context();
+        let heap_length = self
+            .env
+            .vmoffsets
+            .ptr
+            .vmstore_context_gc_heap_current_length();
+        self.masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+            masm.load_ptr(
+                masm.address_at_vmctx(store_context.into())?,
+                scratch.writable(),
+            )?;
+            masm.load_ptr(
+                masm.address_at_reg(scratch.inner(), heap_length.into())?,
+                scratch.writable(),
+            )?;
+            masm.cmp(end, scratch.inner().into(), OperandSize::S64)
+        })?;
+        self.masm.trapif(IntCmpKind::GtU, TRAP_INTERNAL_ASSERT)
+    }
+
+    /// Computes the raw address of the object referenced by `gc_ref` into
+    /// `dst`, after checking that the `size` bytes at `offset` are within
+    /// the GC heap.
+    ///
+    /// `gc_ref` must be a non-null, non-i31 reference.
+    fn emit_gc_object_addr(&mut self, gc_ref: Reg, offset: u32, size: u32, dst: Reg) -> Result<()> {
+        let end = offset
+            .checked_add(size)
+            .ok_or_else(|| CodeGenError::unexpected_operand_size())?;
+        self.masm
+            .mov(writable!(dst), gc_ref.into(), OperandSize::S32)?;
+        self.masm.add(
+            writable!(dst),
+            dst,
+            RegImm::i64(end.into()),
+            OperandSize::S64,
+        )?;
+        self.emit_gc_heap_bounds_check(dst)?;
+        self.masm
+            .mov(writable!(dst), gc_ref.into(), OperandSize::S32)?;
+        self.emit_add_gc_heap_base(dst)
+    }
+
+    /// Computes the raw address of a freshly allocated object into `dst`.
+    ///
+    /// The result of an allocation is trusted, so no bounds checks are
+    /// emitted.
+    fn emit_new_object_addr(&mut self, gc_ref: Reg, dst: Reg) -> Result<()> {
+        self.masm
+            .mov(writable!(dst), gc_ref.into(), OperandSize::S32)?;
+        self.emit_add_gc_heap_base(dst)
+    }
+
+    /// Adds the base address of the GC heap to `dst`.
+    fn emit_add_gc_heap_base(&mut self, dst: Reg) -> Result<()> {
+        let store_context = self.env.vmoffsets.ptr.vmctx_store_context();
+        let heap_base = self.env.vmoffsets.ptr.vmstore_context_gc_heap_base();
+        self.masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+            masm.load_ptr(
+                masm.address_at_vmctx(store_context.into())?,
+                scratch.writable(),
+            )?;
+            masm.load_ptr(
+                masm.address_at_reg(scratch.inner(), heap_base.into())?,
+                scratch.writable(),
+            )?;
+            masm.add(
+                writable!(dst),
+                dst,
+                scratch.inner().into(),
+                OperandSize::S64,
+            )
+        })
+    }
+
+    /// Turns `offset`, a 64-bit offset from the start of the object
+    /// referenced by `gc_ref`, into the raw address of that location, after
+    /// checking that the `size` bytes at that location are within the GC
+    /// heap.
+    fn emit_gc_dynamic_addr(&mut self, gc_ref: Reg, offset: Reg, size: u32) -> Result<()> {
+        self.masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+            masm.mov(scratch.writable(), gc_ref.into(), OperandSize::S32)?;
+            masm.add(
+                writable!(offset),
+                offset,
+                scratch.inner().into(),
+                OperandSize::S64,
+            )
+        })?;
+        if size > 0 {
+            self.masm.add(
+                writable!(offset),
+                offset,
+                RegImm::i64(size.into()),
+                OperandSize::S64,
+            )?;
+            self.emit_gc_heap_bounds_check(offset)?;
+            self.masm.sub(
+                writable!(offset),
+                offset,
+                RegImm::i64(size.into()),
+                OperandSize::S64,
+            )?;
+        } else {
+            self.emit_gc_heap_bounds_check(offset)?;
+        }
+        self.emit_add_gc_heap_base(offset)
+    }
+
+    /// Turns the 32-bit array index in `index` into the 64-bit offset of the
+    /// corresponding element from the start of the array.
+    fn emit_array_elem_offset(&mut self, index: Reg, layout: &GcArrayLayout) -> Result<()> {
+        self.masm
+            .mov(writable!(index), index.into(), OperandSize::S32)?;
+        self.masm.mul(
+            writable!(index),
+            index,
+            RegImm::i64(layout.elem_size.into()),
+            OperandSize::S64,
+        )?;
+        self.masm.add(
+            writable!(index),
+            index,
+            RegImm::i64(layout.base_size.into()),
+            OperandSize::S64,
+        )
+    }
+
+    /// Loads the length of the array referenced by `array` into `dst`,
+    /// trapping if the reference is null.
+    fn emit_load_array_len(&mut self, collector: Collector, array: Reg, dst: Reg) -> Result<()> {
+        let len_offset = Self::gc_layouts(collector).array_length_field_offset();
+        self.masm.trapz(array, TRAP_NULL_REFERENCE)?;
+        self.emit_gc_object_addr(array, len_offset, 4, dst)?;
+        self.masm.load(
+            self.masm.address_at_reg(dst, len_offset)?,
+            writable!(dst),
+            OperandSize::S32,
+        )
+    }
+
+    /// Turns `index` into the raw address of the array element at that index,
+    /// trapping if the array reference is null or if the index is out of
+    /// bounds.
+    fn emit_array_elem_addr(
+        &mut self,
+        collector: Collector,
+        array: Reg,
+        index: Reg,
+        layout: &GcArrayLayout,
+    ) -> Result<()> {
+        let len = self.context.any_gpr(self.masm)?;
+        self.emit_load_array_len(collector, array, len)?;
+        self.masm.cmp(index, len.into(), OperandSize::S32)?;
+        self.masm
+            .trapif(IntCmpKind::GeU, TRAP_ARRAY_OUT_OF_BOUNDS)?;
+        self.context.free_reg(len);
+
+        self.emit_array_elem_offset(index, layout)?;
+        self.emit_gc_dynamic_addr(array, index, layout.elem_size)
+    }
+
+    /// Computes the size in bytes of an array with the given layout and the
+    /// length in `len`, in place, trapping if the size doesn't fit in 32
+    /// bits.
+    fn emit_array_size(&mut self, len: Reg, layout: &GcArrayLayout) -> Result<()> {
+        self.masm
+            .mov(writable!(len), len.into(), OperandSize::S32)?;
+        self.masm.mul(
+            writable!(len),
+            len,
+            RegImm::i64(layout.elem_size.into()),
+            OperandSize::S64,
+        )?;
+        let high_bits = self.context.any_gpr(self.masm)?;
+        self.masm
+            .mov(writable!(high_bits), len.into(), OperandSize::S64)?;
+        self.masm.shift_ir(
+            writable!(high_bits),
+            Imm::i32(32),
+            high_bits,
+            ShiftKind::ShrU,
+            OperandSize::S64,
+        )?;
+        self.masm.cmp(high_bits, RegImm::i64(0), OperandSize::S64)?;
+        self.masm
+            .trapif(IntCmpKind::Ne, TRAP_ALLOCATION_TOO_LARGE)?;
+        self.context.free_reg(high_bits);
+        self.masm.checked_uadd(
+            writable!(len),
+            len,
+            RegImm::i32(layout.base_size as i32),
+            OperandSize::S32,
+            TRAP_ALLOCATION_TOO_LARGE,
+        )
+    }
+
+    /// Jumps to `label` if the given reference, of type `ty`, is null or an
+    /// `i31ref`.
+    #[cfg(feature = "gc-drc")]
+    fn emit_branch_if_null_or_i31(
+        &mut self,
+        ty: WasmRefType,
+        gc_ref: Reg,
+        tmp: Reg,
+        label: MachLabel,
+    ) -> Result<()> {
+        let might_be_i31 = matches!(
+            ty.heap_type,
+            WasmHeapType::Any | WasmHeapType::Eq | WasmHeapType::Extern
+        );
+        if might_be_i31 {
+            self.masm
+                .mov(writable!(tmp), gc_ref.into(), OperandSize::S32)?;
+            self.masm.and(
+                writable!(tmp),
+                tmp,
+                RegImm::i32(I31_DISCRIMINANT as i32),
+                OperandSize::S32,
+            )?;
+            self.masm
+                .branch(IntCmpKind::Ne, tmp, tmp.into(), label, OperandSize::S32)?;
+        }
+        self.masm.branch(
+            IntCmpKind::Eq,
+            gc_ref,
+            gc_ref.into(),
+            label,
+            OperandSize::S32,
+        )
+    }
+
+    /// Increments the reference count of the object referenced by `gc_ref`.
+    ///
+    /// `gc_ref` must be a non-null, non-i31 reference.
+    #[cfg(feature = "gc-drc")]
+    fn emit_drc_inc_ref(&mut self, gc_ref: Reg, addr: Reg, count: Reg) -> Result<()> {
+        let offset = self.env.vmoffsets.vm_drc_header_ref_count();
+        self.emit_gc_object_addr(gc_ref, offset, 8, addr)?;
+        let count_addr = self.masm.address_at_reg(addr, offset)?;
+        self.masm
+            .load(count_addr, writable!(count), OperandSize::S64)?;
+        self.masm
+            .add(writable!(count), count, RegImm::i64(1), OperandSize::S64)?;
+        self.masm.store(count.into(), count_addr, OperandSize::S64)
+    }
+
+    /// DRC read barrier: loads the reference at `base + offset` into a new
+    /// register and, if it's not already there, pushes the referenced object
+    /// onto the over-approximated-stack-roots list, so that the collector
+    /// keeps it alive while it is on the stack.
+    #[cfg(feature = "gc-drc")]
+    fn emit_drc_read_barrier(&mut self, ty: WasmRefType, base: Reg, offset: u32) -> Result<Reg> {
+        use wasmtime_environ::drc::{HEADER_IN_OVER_APPROX_LIST_BIT, HEADER_SIZE};
+
+        let dst = self.context.any_gpr(self.masm)?;
+        let obj = self.context.any_gpr(self.masm)?;
+        let tmp = self.context.any_gpr(self.masm)?;
+        let tmp2 = self.context.any_gpr(self.masm)?;
+        let done = self.masm.get_label()?;
+
+        self.masm.load(
+            self.masm.address_at_reg(base, offset)?,
+            writable!(dst),
+            OperandSize::S32,
+        )?;
+        self.emit_branch_if_null_or_i31(ty, dst, tmp, done)?;
+
+        // Check whether the object is already in the list.
+        let reserved_offset = self.env.vmoffsets.vm_gc_header_reserved_bits();
+        self.emit_gc_object_addr(dst, 0, HEADER_SIZE, obj)?;
+        let reserved_addr = self.masm.address_at_reg(obj, reserved_offset)?;
+        self.masm
+            .load(reserved_addr, writable!(tmp), OperandSize::S32)?;
+        self.masm
+            .mov(writable!(tmp2), tmp.into(), OperandSize::S32)?;
+        self.masm.and(
+            writable!(tmp2),
+            tmp2,
+            RegImm::i32(HEADER_IN_OVER_APPROX_LIST_BIT as i32),
+            OperandSize::S32,
+        )?;
+        self.masm
+            .branch(IntCmpKind::Ne, tmp2, tmp2.into(), done, OperandSize::S32)?;
+
+        // Mark the object as being in the list.
+        self.masm.or(
+            writable!(tmp),
+            tmp,
+            RegImm::i32(HEADER_IN_OVER_APPROX_LIST_BIT as i32),
+            OperandSize::S32,
+        )?;
+        self.masm
+            .store(tmp.into(), reserved_addr, OperandSize::S32)?;
+
+        // Link the object to the current head of the list and make it the
+        // new head.
+        let heap_data = self.env.vmoffsets.ptr.vmctx_gc_heap_data();
+        let next_offset = self
+            .env
+            .vmoffsets
+            .vm_drc_header_next_over_approximated_stack_root();
+        self.masm.load_ptr(
+            self.masm.address_at_vmctx(heap_data.into())?,
+            writable!(tmp),
+        )?;
+        self.masm.load(
+            self.masm.address_at_reg(tmp, 0)?,
+            writable!(tmp2),
+            OperandSize::S32,
+        )?;
+        self.masm.store(
+            tmp2.into(),
+            self.masm.address_at_reg(obj, next_offset)?,
+            OperandSize::S32,
+        )?;
+        self.masm.store(
+            dst.into(),
+            self.masm.address_at_reg(tmp, 0)?,
+            OperandSize::S32,
+        )?;
+
+        // The list holds a strong reference to the object.
+        let count_offset = self.env.vmoffsets.vm_drc_header_ref_count();
+        let count_addr = self.masm.address_at_reg(obj, count_offset)?;
+        self.masm
+            .load(count_addr, writable!(tmp), OperandSize::S64)?;
+        self.masm
+            .add(writable!(tmp), tmp, RegImm::i64(1), OperandSize::S64)?;
+        self.masm.store(tmp.into(), count_addr, OperandSize::S64)?;
+
+        self.masm.bind(done)?;
+        self.context.free_reg(obj);
+        self.context.free_reg(tmp);
+        self.context.free_reg(tmp2);
+        Ok(dst)
+    }
+
+    /// DRC initialization barrier: stores `val` at `base + offset`, a
+    /// location in a freshly allocated object, incrementing the reference
+    /// count of the referenced object.
+    #[cfg(feature = "gc-drc")]
+    fn emit_drc_init_barrier(
+        &mut self,
+        ty: WasmRefType,
+        base: Reg,
+        offset: u32,
+        val: Reg,
+    ) -> Result<()> {
+        let addr = self.context.any_gpr(self.masm)?;
+        let count = self.context.any_gpr(self.masm)?;
+        let done = self.masm.get_label()?;
+
+        self.emit_branch_if_null_or_i31(ty, val, addr, done)?;
+        self.emit_drc_inc_ref(val, addr, count)?;
+        self.masm.bind(done)?;
+
+        self.masm.store(
+            val.into(),
+            self.masm.address_at_reg(base, offset)?,
+            OperandSize::S32,
+        )?;
+        self.context.free_reg(addr);
+        self.context.free_reg(count);
+        Ok(())
+    }
+
+    /// DRC write barrier: stores `val` at `base + offset`, incrementing the
+    /// reference count of the new referenced object and decrementing the
+    /// one of the overwritten object. When the latter drops to zero, the
+    /// object is handed to the `drop_gc_ref` libcall.
+    ///
+    /// The value stack must be spilled before calling this method. The
+    /// registers in `live` are preserved across the libcall.
+    #[cfg(feature = "gc-drc")]
+    fn emit_drc_write_barrier(
+        &mut self,
+        ty: WasmRefType,
+        base: Reg,
+        offset: u32,
+        val: Reg,
+        live: &[TypedReg],
+    ) -> Result<()> {
+        let old = self.context.any_gpr(self.masm)?;
+        let addr = self.context.any_gpr(self.masm)?;
+        let count = self.context.any_gpr(self.masm)?;
+        let inc_done = self.masm.get_label()?;
+        let dec_done = self.masm.get_label()?;
+        let no_drop = self.masm.get_label()?;
+        let builtin = self.env.builtins.drop_gc_ref::<M::ABI, M::Ptr>()?;
+
+        let dst = self.masm.address_at_reg(base, offset)?;
+        self.masm.load(dst, writable!(old), OperandSize::S32)?;
+
+        self.emit_branch_if_null_or_i31(ty, val, addr, inc_done)?;
+        self.emit_drc_inc_ref(val, addr, count)?;
+        self.masm.bind(inc_done)?;
+
+        self.masm.store(val.into(), dst, OperandSize::S32)?;
+
+        self.emit_branch_if_null_or_i31(ty, old, addr, dec_done)?;
+        let count_offset = self.env.vmoffsets.vm_drc_header_ref_count();
+        self.emit_gc_object_addr(old, count_offset, 8, addr)?;
+        let count_addr = self.masm.address_at_reg(addr, count_offset)?;
+        self.masm
+            .load(count_addr, writable!(count), OperandSize::S64)?;
+        self.masm
+            .sub(writable!(count), count, RegImm::i64(1), OperandSize::S64)?;
+        self.masm.branch(
+            IntCmpKind::Ne,
+            count,
+            count.into(),
+            no_drop,
+            OperandSize::S64,
+        )?;
+
+        // The reference count dropped to zero: the libcall takes care of
+        // decrementing it and of deallocating the object.
+        for reg in live {
+            self.context.stack.push((*reg).into());
+        }
+        self.context.stack.push(TypedReg::i32(old).into());
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        for reg in live.iter().rev() {
+            self.context.pop_to_reg(self.masm, Some(reg.reg))?;
+        }
+        // The argument register was released by the call, reserve it again
+        // so that the register state is the same on both paths.
+        self.context.reg(old, self.masm)?;
+        self.masm.jmp(dec_done)?;
+
+        self.masm.bind(no_drop)?;
+        self.masm
+            .store(count.into(), count_addr, OperandSize::S64)?;
+        self.masm.bind(dec_done)?;
+
+        self.context.free_reg(old);
+        self.context.free_reg(addr);
+        self.context.free_reg(count);
+        Ok(())
+    }
+
+    /// Generational write barrier: stores `val` at `base + offset` and, if
+    /// that location is inside the GC heap, marks its card as dirty.
+    #[cfg(feature = "gc-generational")]
+    fn emit_generational_write_barrier(
+        &mut self,
+        ty: WasmRefType,
+        base: Reg,
+        offset: u32,
+        val: Reg,
+    ) -> Result<()> {
+        use wasmtime_environ::generational::{CARD_SIZE_LOG2, HEAP_DATA_CARD_TABLE_OFFSET};
+
+        let dst = self.masm.address_at_reg(base, offset)?;
+        self.masm.store(val.into(), dst, OperandSize::S32)?;
+
+        // Writes of `i31ref`s can never create an old-to-young edge.
+        if ty.heap_type == WasmHeapType::I31 {
+            return Ok(());
+        }
+
+        let card = self.context.any_gpr(self.masm)?;
+        let done = self.masm.get_label()?;
+        let store_context = self.env.vmoffsets.ptr.vmctx_store_context();
+        let heap_base = self.env.vmoffsets.ptr.vmstore_context_gc_heap_base();
+        let heap_length = self
+            .env
+            .vmoffsets
+            .ptr
+            .vmstore_context_gc_heap_current_length();
+        let heap_data = self.env.vmoffsets.ptr.vmctx_gc_heap_data();
+
+        self.masm
+            .mov(writable!(card), base.into(), OperandSize::S64)?;
+        self.masm.add(
+            writable!(card),
+            card,
+            RegImm::i64(offset.into()),
+            OperandSize::S64,
+        )?;
+        // Writes into globals and tables, which are always treated as roots,
+        // fail this bounds check and don't mark any card.
+        self.masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+            masm.load_ptr(
+                masm.address_at_vmctx(store_context.into())?,
+                scratch.writable(),
+            )?;
+            masm.load_ptr(
+                masm.address_at_reg(scratch.inner(), heap_base.into())?,
+                scratch.writable(),
+            )?;
+            masm.sub(
+                writable!(card),
+                card,
+                scratch.inner().into(),
+                OperandSize::S64,
+            )?;
+            masm.load_ptr(
+                masm.address_at_vmctx(store_context.into())?,
+                scratch.writable(),
+            )?;
+            masm.load_ptr(
+                masm.address_at_reg(scratch.inner(), heap_length.into())?,
+                scratch.writable(),
+            )?;
+            masm.branch(
+                IntCmpKind::GeU,
+                card,
+                scratch.inner().into(),
+                done,
+                OperandSize::S64,
+            )
+        })?;
+
+        self.masm.shift_ir(
+            writable!(card),
+            Imm::i32(CARD_SIZE_LOG2.into()),
+            card,
+            ShiftKind::ShrU,
+            OperandSize::S64,
+        )?;
+        self.masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+            masm.load_ptr(masm.address_at_vmctx(heap_data.into())?, scratch.writable())?;
+            masm.load_ptr(
+                masm.address_at_reg(scratch.inner(), HEAP_DATA_CARD_TABLE_OFFSET)?,
+                scratch.writable(),
+            )?;
+            masm.add(
+                writable!(card),
+                card,
+                scratch.inner().into(),
+                OperandSize::S64,
+            )
+        })?;
+        self.masm.store(
+            RegImm::i32(1),
+            self.masm.address_at_reg(card, 0)?,
+            OperandSize::S8,
+        )?;
+        self.masm.bind(done)?;
+        self.context.free_reg(card);
+        Ok(())
+    }
+
+    /// Reads the GC reference of type `ty` at `base + offset` into a new
+    /// register, emitting the collector's read barrier.
+    fn emit_gc_ref_read(
+        &mut self,
+        collector: Collector,
+        ty: WasmRefType,
+        base: Reg,
+        offset: u32,
+    ) -> Result<Reg> {
+        match ty.heap_type {
+            // The only value of uninhabited types is null.
+            WasmHeapType::None | WasmHeapType::NoExtern => {
+                let dst = self.context.any_gpr(self.masm)?;
+                self.masm
+                    .mov(writable!(dst), RegImm::i32(0), OperandSize::S32)?;
+                Ok(dst)
+            }
+            _ => match collector {
+                #[cfg(feature = "gc-drc")]
+                Collector::DeferredReferenceCounting if ty.heap_type != WasmHeapType::I31 => {
+                    self.emit_drc_read_barrier(ty, base, offset)
+                }
+                // Moving collectors don't need read barriers.
+                _ => self.emit_unbarriered_gc_ref_read(base, offset),
+            },
+        }
+    }
+
+    /// Reads the GC reference at `base + offset` into a new register.
+    fn emit_unbarriered_gc_ref_read(&mut self, base: Reg, offset: u32) -> Result<Reg> {
+        let dst = self.context.any_gpr(self.masm)?;
+        self.masm.load(
+            self.masm.address_at_reg(base, offset)?,
+            writable!(dst),
+            OperandSize::S32,
+        )?;
+        Ok(dst)
+    }
+
+    /// Initializes the location `base + offset` of a freshly allocated
+    /// object with the GC reference in `val`.
+    fn emit_gc_ref_init(
+        &mut self,
+        collector: Collector,
+        ty: WasmRefType,
+        base: Reg,
+        offset: u32,
+        val: Reg,
+    ) -> Result<()> {
+        match collector {
+            #[cfg(feature = "gc-drc")]
+            Collector::DeferredReferenceCounting if ty.heap_type != WasmHeapType::I31 => {
+                self.emit_drc_init_barrier(ty, base, offset, val)
+            }
+            // Freshly allocated objects are always in the nursery, so
+            // initializing writes never need the generational write barrier.
+            _ => {
+                let _ = ty;
+                self.masm.store(
+                    val.into(),
+                    self.masm.address_at_reg(base, offset)?,
+                    OperandSize::S32,
+                )
+            }
+        }
+    }
+
+    /// Writes the GC reference in `val` to `base + offset`, emitting the
+    /// collector's write barrier.
+    ///
+    /// The value stack must be spilled before calling this method. The
+    /// registers in `live` are preserved if the barrier needs to call into
+    /// the runtime.
+    fn emit_gc_ref_write(
+        &mut self,
+        collector: Collector,
+        ty: WasmRefType,
+        base: Reg,
+        offset: u32,
+        val: Reg,
+        live: &[TypedReg],
+    ) -> Result<()> {
+        match collector {
+            #[cfg(feature = "gc-drc")]
+            Collector::DeferredReferenceCounting if ty.heap_type != WasmHeapType::I31 => {
+                self.emit_drc_write_barrier(ty, base, offset, val, live)
+            }
+            #[cfg(feature = "gc-generational")]
+            Collector::Generational => self.emit_generational_write_barrier(ty, base, offset, val),
+            _ => {
+                let _ = (ty, live);
+                self.masm.store(
+                    val.into(),
+                    self.masm.address_at_reg(base, offset)?,
+                    OperandSize::S32,
+                )
+            }
+        }
+    }
+
+    /// Returns the operand size used to store a value of the given type in
+    /// the GC heap.
+    fn gc_storage_size(ty: WasmStorageType) -> Result<OperandSize> {
+        match ty {
+            WasmStorageType::I8 => Ok(OperandSize::S8),
+            WasmStorageType::I16 => Ok(OperandSize::S16),
+            // GC references and function references are stored as 32-bit
+            // values: the latter as their `FuncRefTableId`.
+            WasmStorageType::Val(WasmValType::Ref(_)) => Ok(OperandSize::S32),
+            WasmStorageType::Val(ty) => ty.try_into(),
+        }
+    }
+
+    /// Reads the field of type `ty` at `base + offset` and pushes it to the
+    /// value stack. Packed fields are sign-extended if `signed` is set and
+    /// zero-extended otherwise.
+    ///
+    /// Frees `base`.
+    fn emit_gc_read(
+        &mut self,
+        collector: Collector,
+        base: Reg,
+        offset: u32,
+        ty: WasmStorageType,
+        signed: bool,
+    ) -> Result<()> {
+        let addr = self.masm.address_at_reg(base, offset)?;
+        match ty {
+            WasmStorageType::I8 | WasmStorageType::I16 => {
+                let dst = self.context.any_gpr(self.masm)?;
+                let size = Self::gc_storage_size(ty)?;
+                self.masm.load(addr, writable!(dst), size)?;
+                if signed {
+                    let kind = if size == OperandSize::S8 {
+                        Extend::<Signed>::I32Extend8
+                    } else {
+                        Extend::<Signed>::I32Extend16
+                    };
+                    self.masm.extend(writable!(dst), dst, kind.into())?;
+                }
+                self.context.free_reg(base);
+                self.context.stack.push(TypedReg::i32(dst).into());
+            }
+            WasmStorageType::Val(WasmValType::Ref(rt))
+                if rt.heap_type.top() == WasmHeapTopType::Func =>
+            {
+                let id = self.context.any_gpr(self.masm)?;
+                self.masm.load(addr, writable!(id), OperandSize::S32)?;
+                self.context.free_reg(base);
+                self.emit_get_interned_func_ref(id, rt)?;
+            }
+            WasmStorageType::Val(WasmValType::Ref(rt)) => {
+                let dst = self.emit_gc_ref_read(collector, rt, base, offset)?;
+                self.context.free_reg(base);
+                self.context
+                    .stack
+                    .push(TypedReg::new(WasmValType::Ref(rt), dst).into());
+            }
+            WasmStorageType::Val(ty) => {
+                let dst = self.context.reg_for_type(ty, self.masm)?;
+                self.masm.load(addr, writable!(dst), ty.try_into()?)?;
+                self.context.free_reg(base);
+                self.context.stack.push(TypedReg::new(ty, dst).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes the field of type `ty` at `base + offset`, in a freshly
+    /// allocated object, with `val`. Function references must already be
+    /// interned.
+    fn emit_gc_init(
+        &mut self,
+        collector: Collector,
+        base: Reg,
+        offset: u32,
+        ty: WasmStorageType,
+        val: Reg,
+    ) -> Result<()> {
+        match ty {
+            WasmStorageType::Val(WasmValType::Ref(rt)) if rt.is_vmgcref_type() => {
+                self.emit_gc_ref_init(collector, rt, base, offset, val)
+            }
+            _ => self.masm.store(
+                val.into(),
+                self.masm.address_at_reg(base, offset)?,
+                Self::gc_storage_size(ty)?,
+            ),
+        }
+    }
+
+    /// Writes `val` to the field of type `ty` at `base + offset`. Function
+    /// references must already be interned.
+    ///
+    /// See [`Self::emit_gc_ref_write`] for the requirements of writing GC
+    /// references.
+    fn emit_gc_write(
+        &mut self,
+        collector: Collector,
+        base: Reg,
+        offset: u32,
+        ty: WasmStorageType,
+        val: Reg,
+        live: &[TypedReg],
+    ) -> Result<()> {
+        match ty {
+            WasmStorageType::Val(WasmValType::Ref(rt)) if rt.is_vmgcref_type() => {
+                self.emit_gc_ref_write(collector, rt, base, offset, val, live)
+            }
+            _ => self.masm.store(
+                val.into(),
+                self.masm.address_at_reg(base, offset)?,
+                Self::gc_storage_size(ty)?,
+            ),
+        }
+    }
+
+    /// Returns whether the given storage type is a function reference, which
+    /// needs to be interned before being stored in the GC heap.
+    fn is_func_ref_storage(ty: WasmStorageType) -> bool {
+        matches!(ty, WasmStorageType::Val(WasmValType::Ref(rt)) if rt.heap_type.top() == WasmHeapTopType::Func)
+    }
+
+    /// Returns whether writing a field of the given type may call into the
+    /// runtime, in which case the value stack must be spilled beforehand.
+    fn needs_spill_for_write(ty: WasmStorageType) -> bool {
+        matches!(ty, WasmStorageType::Val(WasmValType::Ref(_)))
+    }
+
+    /// Drops the last `n` values of the value stack.
+    fn drop_stack_vals(&mut self, n: usize) -> Result<()> {
+        self.context.drop_last(n, |regalloc, val| match val {
+            Val::Reg(tr) => Ok(regalloc.free(tr.reg)),
+            Val::Memory(m) => self.masm.free_stack(m.slot.size),
+            _ => Ok(()),
+        })
+    }
+
+    /// Interns the function reference at the given value stack index,
+    /// pushing its `FuncRefTableId` to the value stack.
+    fn emit_intern_func_ref(&mut self, index: usize) -> Result<()> {
+        // Null function references are always interned as `0`.
+        if let Some(Val::I64(0)) = self.context.stack.inner().get(index) {
+            self.context.stack.push(Val::i32(0));
+            return Ok(());
+        }
+        let builtin = self
+            .env
+            .builtins
+            .intern_func_ref_for_gc_heap::<M::ABI, M::Ptr>()?;
+        let func_ref = self.load_stack_val(index)?;
+        self.context.stack.push(func_ref.into());
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )
+    }
+
+    /// Interns the function references among the top `tys.len()` values of
+    /// the value stack, whose storage types are `tys`, returning the value
+    /// stack index holding the value to store for each of them.
+    fn emit_intern_func_refs(&mut self, tys: &[WasmStorageType]) -> Result<Vec<usize>> {
+        let base = self.context.stack.ensure_index_at(tys.len())?;
+        let mut indices = Vec::with_capacity(tys.len());
+        for (i, ty) in tys.iter().enumerate() {
+            if Self::is_func_ref_storage(*ty) {
+                self.emit_intern_func_ref(base + i)?;
+                indices.push(self.context.stack.len() - 1);
+            } else {
+                indices.push(base + i);
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Resolves the function reference with the `FuncRefTableId` in `id`,
+    /// pushing it to the value stack with type `ty`.
+    ///
+    /// Frees `id`.
+    fn emit_get_interned_func_ref(&mut self, id: Reg, ty: WasmRefType) -> Result<()> {
+        let expected = match ty.heap_type {
+            WasmHeapType::NoFunc => {
+                self.masm
+                    .mov(writable!(id), RegImm::i64(0), OperandSize::S64)?;
+                self.context
+                    .stack
+                    .push(TypedReg::new(WasmValType::Ref(ty), id).into());
+                return Ok(());
+            }
+            WasmHeapType::ConcreteFunc(idx) => {
+                idx.unwrap_module_type_index().as_u32().try_into()?
+            }
+            // Untyped function references are requested with the reserved
+            // type index.
+            _ => Val::i32(ModuleInternedTypeIndex::reserved_value().as_bits() as i32),
+        };
+        let builtin = self
+            .env
+            .builtins
+            .get_interned_func_ref::<M::ABI, M::Ptr>()?;
+        self.context.stack.push(TypedReg::i32(id).into());
+        self.context.stack.push(expected);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        self.emit_retype_top(WasmValType::Ref(ty))
+    }
+
+    /// Allocates an uninitialized object through the `gc_alloc_raw` libcall,
+    /// returning the register holding the reference to it.
+    #[cfg(any(feature = "gc-drc", feature = "gc-generational"))]
+    fn emit_gc_alloc_raw(
+        &mut self,
+        kind: VMGcKind,
+        ty: ModuleInternedTypeIndex,
+        size: Val,
+        align: u32,
+    ) -> Result<Reg> {
+        let builtin = self.env.builtins.gc_alloc_raw::<M::ABI, M::Ptr>()?;
+        self.context.stack.extend([
+            Val::i32(kind.as_u32() as i32),
+            ty.as_u32().try_into()?,
+            size,
+            align.try_into()?,
+        ]);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        Ok(self.context.pop_to_reg(self.masm, None)?.reg)
+    }
+
+    #[cfg(not(any(feature = "gc-drc", feature = "gc-generational")))]
+    fn emit_gc_alloc_raw(
+        &mut self,
+        _kind: VMGcKind,
+        _ty: ModuleInternedTypeIndex,
+        _size: Val,
+        _align: u32,
+    ) -> Result<Reg> {
+        bail!(CodeGenError::unsupported_gc_collector())
+    }
+
+    /// Allocates an object through `alloc` and initializes its fields, at
+    /// the given offsets and with the given types, with the top
+    /// `fields.len()` values of the value stack, which are dropped.
+    fn emit_alloc_and_init(
+        &mut self,
+        collector: Collector,
+        fields: &[(u32, WasmStorageType)],
+        alloc: impl FnOnce(&mut Self) -> Result<Reg>,
+    ) -> Result<Reg> {
+        let base = self.context.stack.ensure_index_at(fields.len())?;
+        let tys = fields.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+        let indices = self.emit_intern_func_refs(&tys)?;
+        let obj = alloc(self)?;
+
+        let addr = self.context.any_gpr(self.masm)?;
+        self.emit_new_object_addr(obj, addr)?;
+        for ((offset, ty), index) in fields.iter().zip(indices) {
+            let val = self.load_stack_val(index)?;
+            self.emit_gc_init(collector, addr, *offset, *ty, val.reg)?;
+            self.context.free_reg(val);
+        }
+        self.context.free_reg(addr);
+
+        let len = self.context.stack.len();
+        self.drop_stack_vals(len - base)?;
+        Ok(obj)
+    }
+
+    /// Returns the default value for the given storage type.
+    fn default_val(ty: WasmStorageType) -> Result<Val> {
+        Ok(match ty {
+            WasmStorageType::I8 | WasmStorageType::I16 => Val::i32(0),
+            WasmStorageType::Val(ty) => match ty {
+                WasmValType::I32 => Val::i32(0),
+                WasmValType::I64 => Val::i64(0),
+                WasmValType::F32 => Val::f32(Ieee32::from(0.0)),
+                WasmValType::F64 => Val::f64(Ieee64::from(0.0)),
+                WasmValType::V128 => Val::v128(0),
+                WasmValType::Ref(rt) => match rt.heap_type.top() {
+                    WasmHeapTopType::Func => Val::i64(0),
+                    WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                        Val::i32(0)
+                    }
+                    _ => bail!(CodeGenError::unsupported_wasm_type()),
+                },
+            },
+        })
+    }
+
+    /// Emits `struct.new` or, if `default` is set, `struct.new_default`.
+    pub fn emit_struct_new(&mut self, type_index: TypeIndex, default: bool) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let layout = self.struct_layout(collector, interned)?;
+        let types = self.env.types;
+        let struct_ty = types.unwrap_struct(interned)?;
+        let fields = struct_ty
+            .fields
+            .iter()
+            .zip(layout.fields.iter())
+            .map(|(field, layout)| (layout.offset, field.element_type))
+            .collect::<Vec<_>>();
+
+        if default {
+            for (_, ty) in &fields {
+                self.context.stack.push(Self::default_val(*ty)?);
+            }
+        }
+
+        let align = Self::gc_alloc_align(collector, layout.align);
+        let obj = self.emit_alloc_and_init(collector, &fields, |this| {
+            this.emit_gc_alloc_raw(
+                VMGcKind::StructRef,
+                interned,
+                layout.size.try_into()?,
+                align,
+            )
+        })?;
+        let ty = WasmValType::Ref(WasmRefType {
+            nullable: false,
+            heap_type: WasmHeapType::ConcreteStruct(type_index_to_engine_or_module(interned)),
+        });
+        self.context.stack.push(TypedReg::new(ty, obj).into());
+        Ok(())
+    }
+
+    /// Emits `struct.get`, `struct.get_s` and `struct.get_u`.
+    pub fn emit_struct_get(
+        &mut self,
+        type_index: TypeIndex,
+        field_index: u32,
+        signed: bool,
+    ) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let layout = self.struct_layout(collector, interned)?;
+        let types = self.env.types;
+        let ty = types.unwrap_struct(interned)?.fields[field_index as usize].element_type;
+        let offset = layout.fields[field_index as usize].offset;
+        let size = wasmtime_environ::byte_size_of_wasm_ty_in_gc_heap(&ty);
+
+        let obj = self.context.pop_to_reg(self.masm, None)?;
+        self.masm.trapz(obj.reg, TRAP_NULL_REFERENCE)?;
+        let addr = self.context.any_gpr(self.masm)?;
+        self.emit_gc_object_addr(obj.reg, offset, size, addr)?;
+        self.context.free_reg(obj);
+        self.emit_gc_read(collector, addr, offset, ty, signed)
+    }
+
+    /// Emits `struct.set`.
+    pub fn emit_struct_set(&mut self, type_index: TypeIndex, field_index: u32) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let layout = self.struct_layout(collector, interned)?;
+        let types = self.env.types;
+        let ty = types.unwrap_struct(interned)?.fields[field_index as usize].element_type;
+        let offset = layout.fields[field_index as usize].offset;
+        let size = wasmtime_environ::byte_size_of_wasm_ty_in_gc_heap(&ty);
+
+        self.emit_prepare_field_value(ty)?;
+        let val = self.context.pop_to_reg(self.masm, None)?;
+        let obj = self.context.pop_to_reg(self.masm, None)?;
+        self.masm.trapz(obj.reg, TRAP_NULL_REFERENCE)?;
+        let addr = self.context.any_gpr(self.masm)?;
+        self.emit_gc_object_addr(obj.reg, offset, size, addr)?;
+        self.context.free_reg(obj);
+        self.emit_gc_write(collector, addr, offset, ty, val.reg, &[])?;
+        self.context.free_reg(addr);
+        self.context.free_reg(val);
+        Ok(())
+    }
+
+    /// Prepares the value at the top of the value stack to be written to a
+    /// field of type `ty`: function references are replaced by their
+    /// interned id and the value stack is spilled if the write may call into
+    /// the runtime.
+    fn emit_prepare_field_value(&mut self, ty: WasmStorageType) -> Result<()> {
+        if Self::is_func_ref_storage(ty) {
+            let index = self.context.stack.ensure_index_at(1)?;
+            self.emit_intern_func_ref(index)?;
+            // Replace the function reference with its id.
+            let id = self.context.pop_to_reg(self.masm, None)?;
+            self.drop_stack_vals(1)?;
+            self.context.stack.push(id.into());
+        } else if Self::needs_spill_for_write(ty) {
+            self.context.spill(self.masm)?;
+        }
+        Ok(())
+    }
+
+    /// Emits `array.new` or, if `default` is set, `array.new_default`.
+    pub fn emit_array_new(&mut self, type_index: TypeIndex, default: bool) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let (layout, elem_ty) = self.array_layout(collector, interned)?;
+        let len_offset = Self::gc_layouts(collector).array_length_field_offset();
+
+        if default {
+            let at = self.context.stack.ensure_index_at(1)?;
+            self.context
+                .stack
+                .insert_many(at, &[Self::default_val(elem_ty)?]);
+        }
+
+        // The value stack contains `[elem, len]`.
+        let len_index = self.context.stack.ensure_index_at(1)?;
+        let elem_index = if Self::is_func_ref_storage(elem_ty) {
+            self.emit_intern_func_ref(len_index - 1)?;
+            self.context.stack.len() - 1
+        } else {
+            len_index - 1
+        };
+
+        let size = self.load_stack_val(len_index)?;
+        self.emit_array_size(size.reg, &layout)?;
+        let obj = self.emit_gc_alloc_raw(
+            VMGcKind::ArrayRef,
+            interned,
+            TypedReg::i32(size.reg).into(),
+            Self::gc_alloc_align(collector, layout.align),
+        )?;
+
+        // Store the length and fill the elements.
+        let cursor = self.context.any_gpr(self.masm)?;
+        self.emit_new_object_addr(obj, cursor)?;
+        let len = self.load_stack_val(len_index)?;
+        self.masm.store(
+            len.reg.into(),
+            self.masm.address_at_reg(cursor, len_offset)?,
+            OperandSize::S32,
+        )?;
+        let end = len.reg;
+        self.masm
+            .mov(writable!(end), end.into(), OperandSize::S32)?;
+        self.masm.mul(
+            writable!(end),
+            end,
+            RegImm::i64(layout.elem_size.into()),
+            OperandSize::S64,
+        )?;
+        self.masm.add(
+            writable!(cursor),
+            cursor,
+            RegImm::i64(layout.base_size.into()),
+            OperandSize::S64,
+        )?;
+        self.masm
+            .add(writable!(end), end, cursor.into(), OperandSize::S64)?;
+        let val = self.load_stack_val(elem_index)?;
+
+        let (head, done) = (self.masm.get_label()?, self.masm.get_label()?);
+        self.masm.bind(head)?;
+        self.masm
+            .branch(IntCmpKind::Eq, cursor, end.into(), done, OperandSize::S64)?;
+        self.emit_gc_init(collector, cursor, 0, elem_ty, val.reg)?;
+        self.masm.add(
+            writable!(cursor),
+            cursor,
+            RegImm::i64(layout.elem_size.into()),
+            OperandSize::S64,
+        )?;
+        self.masm.jmp(head)?;
+        self.masm.bind(done)?;
+
+        self.context.free_reg(cursor);
+        self.context.free_reg(len);
+        self.context.free_reg(val);
+        let n = self.context.stack.len() - (len_index - 1);
+        self.drop_stack_vals(n)?;
+        self.push_array_ref(obj, interned);
+        Ok(())
+    }
+
+    /// Emits `array.new_fixed`.
+    pub fn emit_array_new_fixed(&mut self, type_index: TypeIndex, len: u32) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let (layout, elem_ty) = self.array_layout(collector, interned)?;
+        let len_offset = Self::gc_layouts(collector).array_length_field_offset();
+
+        // The length is initialized as any other field.
+        let mut fields = (0..len)
+            .map(|i| (layout.elem_offset(i), elem_ty))
+            .collect::<Vec<_>>();
+        fields.push((len_offset, WasmStorageType::Val(WasmValType::I32)));
+        self.context.stack.push(len.try_into()?);
+
+        let align = Self::gc_alloc_align(collector, layout.align);
+        let obj = self.emit_alloc_and_init(collector, &fields, |this| {
+            this.emit_gc_alloc_raw(
+                VMGcKind::ArrayRef,
+                interned,
+                layout.size_for_len(len).try_into()?,
+                align,
+            )
+        })?;
+        self.push_array_ref(obj, interned);
+        Ok(())
+    }
+
+    /// Pushes the given register as a non-null reference to the given array
+    /// type.
+    fn push_array_ref(&mut self, obj: Reg, ty: ModuleInternedTypeIndex) {
+        let ty = WasmValType::Ref(WasmRefType {
+            nullable: false,
+            heap_type: WasmHeapType::ConcreteArray(type_index_to_engine_or_module(ty)),
+        });
+        self.context.stack.push(TypedReg::new(ty, obj).into());
+    }
+
+    /// Emits `array.new_data` and `array.new_elem`, which are implemented by
+    /// libcalls.
+    fn emit_array_new_segment(
+        &mut self,
+        type_index: TypeIndex,
+        segment: u32,
+        data: bool,
+    ) -> Result<()> {
+        self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let builtin = if data {
+            self.env.builtins.array_new_data::<M::ABI, M::Ptr>()?
+        } else {
+            self.env.builtins.array_new_elem::<M::ABI, M::Ptr>()?
+        };
+        // The value stack contains `[offset, len]`.
+        let at = self.context.stack.ensure_index_at(2)?;
+        self.context
+            .stack
+            .insert_many(at, &[interned.as_u32().try_into()?, segment.try_into()?]);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        let obj = self.context.pop_to_reg(self.masm, None)?;
+        self.push_array_ref(obj.reg, interned);
+        Ok(())
+    }
+
+    /// Emits `array.new_data`.
+    pub fn emit_array_new_data(&mut self, type_index: TypeIndex, data_index: u32) -> Result<()> {
+        self.emit_array_new_segment(type_index, data_index, true)
+    }
+
+    /// Emits `array.new_elem`.
+    pub fn emit_array_new_elem(&mut self, type_index: TypeIndex, elem_index: u32) -> Result<()> {
+        self.emit_array_new_segment(type_index, elem_index, false)
+    }
+
+    /// Emits `array.get`, `array.get_s` and `array.get_u`.
+    pub fn emit_array_get(&mut self, type_index: TypeIndex, signed: bool) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let (layout, elem_ty) = self.array_layout(collector, interned)?;
+
+        let index = self.context.pop_to_reg(self.masm, None)?;
+        let array = self.context.pop_to_reg(self.masm, None)?;
+        self.emit_array_elem_addr(collector, array.reg, index.reg, &layout)?;
+        self.context.free_reg(array);
+        self.emit_gc_read(collector, index.reg, 0, elem_ty, signed)
+    }
+
+    /// Emits `array.set`.
+    pub fn emit_array_set(&mut self, type_index: TypeIndex) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let (layout, elem_ty) = self.array_layout(collector, interned)?;
+
+        self.emit_prepare_field_value(elem_ty)?;
+        let val = self.context.pop_to_reg(self.masm, None)?;
+        let index = self.context.pop_to_reg(self.masm, None)?;
+        let array = self.context.pop_to_reg(self.masm, None)?;
+        self.emit_array_elem_addr(collector, array.reg, index.reg, &layout)?;
+        self.context.free_reg(array);
+        self.emit_gc_write(collector, index.reg, 0, elem_ty, val.reg, &[])?;
+        self.context.free_reg(index);
+        self.context.free_reg(val);
+        Ok(())
+    }
+
+    /// Emits `array.len`.
+    pub fn emit_array_len(&mut self) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let array = self.context.pop_to_reg(self.masm, None)?;
+        let len = self.context.any_gpr(self.masm)?;
+        self.emit_load_array_len(collector, array.reg, len)?;
+        self.context.free_reg(array);
+        self.context.stack.push(TypedReg::i32(len).into());
+        Ok(())
+    }
+
+    /// Emits `array.fill`.
+    pub fn emit_array_fill(&mut self, type_index: TypeIndex) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let (layout, elem_ty) = self.array_layout(collector, interned)?;
+
+        // The value stack contains `[array, index, val, n]`. Filling the
+        // array is done in a loop in which the write barrier might call into
+        // the runtime, so the value stack is spilled upfront.
+        let n_index = self.context.stack.ensure_index_at(1)?;
+        let val_index = if Self::is_func_ref_storage(elem_ty) {
+            self.emit_intern_func_ref(n_index - 1)?;
+            self.context.stack.len() - 1
+        } else {
+            n_index - 1
+        };
+        self.context.spill(self.masm)?;
+
+        let array = self.load_stack_val(n_index - 3)?;
+        let cursor = self.load_stack_val(n_index - 2)?;
+        let end = self.load_stack_val(n_index)?;
+
+        // Bounds check the filled range.
+        let len = self.context.any_gpr(self.masm)?;
+        self.emit_load_array_len(collector, array.reg, len)?;
+        self.masm.checked_uadd(
+            writable!(end.reg),
+            end.reg,
+            cursor.reg.into(),
+            OperandSize::S32,
+            TRAP_ARRAY_OUT_OF_BOUNDS,
+        )?;
+        self.masm.cmp(end.reg, len.into(), OperandSize::S32)?;
+        self.masm
+            .trapif(IntCmpKind::GtU, TRAP_ARRAY_OUT_OF_BOUNDS)?;
+        self.context.free_reg(len);
+
+        self.emit_array_elem_offset(cursor.reg, &layout)?;
+        self.emit_gc_dynamic_addr(array.reg, cursor.reg, 0)?;
+        self.emit_array_elem_offset(end.reg, &layout)?;
+        self.emit_gc_dynamic_addr(array.reg, end.reg, 0)?;
+        self.context.free_reg(array);
+
+        let val = self.load_stack_val(val_index)?;
+        let cursor = TypedReg::i64(cursor.reg);
+        let end = TypedReg::i64(end.reg);
+        let (head, done) = (self.masm.get_label()?, self.masm.get_label()?);
+        self.masm.bind(head)?;
+        self.masm.branch(
+            IntCmpKind::Eq,
+            cursor.reg,
+            end.reg.into(),
+            done,
+            OperandSize::S64,
+        )?;
+        self.emit_gc_write(
+            collector,
+            cursor.reg,
+            0,
+            elem_ty,
+            val.reg,
+            &[cursor, end, val],
+        )?;
+        self.masm.add(
+            writable!(cursor.reg),
+            cursor.reg,
+            RegImm::i64(layout.elem_size.into()),
+            OperandSize::S64,
+        )?;
+        self.masm.jmp(head)?;
+        self.masm.bind(done)?;
+
+        self.context.free_reg(cursor);
+        self.context.free_reg(end);
+        self.context.free_reg(val);
+        let n = self.context.stack.len() - (n_index - 3);
+        self.drop_stack_vals(n)
+    }
+
+    /// Emits `array.copy`, which is implemented by a libcall.
+    pub fn emit_array_copy(&mut self) -> Result<()> {
+        self.gc_collector()?;
+        let builtin = self.env.builtins.array_copy::<M::ABI, M::Ptr>()?;
+        // The value stack already contains the libcall arguments in order:
+        // `[dst, dst_index, src, src_index, len]`.
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        self.context.pop_and_free(self.masm)
+    }
+
+    /// Emits `array.init_data` and `array.init_elem`, which are implemented
+    /// by libcalls.
+    fn emit_array_init_segment(
+        &mut self,
+        type_index: TypeIndex,
+        segment: u32,
+        data: bool,
+    ) -> Result<()> {
+        self.gc_collector()?;
+        let interned = self.interned_type_index(type_index);
+        let builtin = if data {
+            self.env.builtins.array_init_data::<M::ABI, M::Ptr>()?
+        } else {
+            self.env.builtins.array_init_elem::<M::ABI, M::Ptr>()?
+        };
+        // The value stack contains `[array, dst, offset, len]`; the libcall
+        // expects the type index first and the segment index before the
+        // segment offset.
+        let at = self.context.stack.ensure_index_at(4)?;
+        self.context
+            .stack
+            .insert_many(at + 2, &[segment.try_into()?]);
+        self.context
+            .stack
+            .insert_many(at, &[interned.as_u32().try_into()?]);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        self.context.pop_and_free(self.masm)
+    }
+
+    /// Emits `array.init_data`.
+    pub fn emit_array_init_data(&mut self, type_index: TypeIndex, data_index: u32) -> Result<()> {
+        self.emit_array_init_segment(type_index, data_index, true)
+    }
+
+    /// Emits `array.init_elem`.
+    pub fn emit_array_init_elem(&mut self, type_index: TypeIndex, elem_index: u32) -> Result<()> {
+        self.emit_array_init_segment(type_index, elem_index, false)
+    }
+
+    /// Tests whether the reference at the top of the value stack, which is
+    /// left untouched, is of type `ty`, returning a register holding the
+    /// `i32` result.
+    fn emit_ref_test_impl(&mut self, ty: WasmRefType) -> Result<Reg> {
+        let val_ty = self
+            .context
+            .stack
+            .peek()
+            .ok_or_else(|| CodeGenError::missing_values_in_stack())?
+            .ty();
+        let size: OperandSize = val_ty.try_into()?;
+        let top = self.context.stack.ensure_index_at(1)?;
+
+        // Bottom types only contain the null reference.
+        if ty.heap_type.is_bottom() {
+            let res = self.load_stack_val(top)?.reg;
+            if ty.nullable {
+                self.masm
+                    .cmp_with_set(writable!(res), RegImm::i64(0), IntCmpKind::Eq, size)?;
+            } else {
+                self.masm
+                    .mov(writable!(res), RegImm::i32(0), OperandSize::S32)?;
+            }
+            return Ok(res);
+        }
+
+        // Top types contain all the references of their hierarchy.
+        if ty.heap_type.is_top() {
+            let res = self.load_stack_val(top)?.reg;
+            if ty.nullable {
+                self.masm
+                    .mov(writable!(res), RegImm::i32(1), OperandSize::S32)?;
+            } else {
+                self.masm
+                    .cmp_with_set(writable!(res), RegImm::i64(0), IntCmpKind::Ne, size)?;
+            }
+            return Ok(res);
+        }
+
+        if ty.heap_type == WasmHeapType::I31 {
+            let res = self.load_stack_val(top)?.reg;
+            if ty.nullable {
+                let is_null = self.load_stack_val(top)?.reg;
+                self.masm.cmp_with_set(
+                    writable!(is_null),
+                    RegImm::i32(0),
+                    IntCmpKind::Eq,
+                    OperandSize::S32,
+                )?;
+                self.masm.and(
+                    writable!(res),
+                    res,
+                    RegImm::i32(I31_DISCRIMINANT as i32),
+                    OperandSize::S32,
+                )?;
+                self.masm
+                    .or(writable!(res), res, is_null.into(), OperandSize::S32)?;
+                self.context.free_reg(is_null);
+            } else {
+                self.masm.and(
+                    writable!(res),
+                    res,
+                    RegImm::i32(I31_DISCRIMINANT as i32),
+                    OperandSize::S32,
+                )?;
+            }
+            return Ok(res);
+        }
+
+        let concrete = matches!(
+            ty.heap_type,
+            WasmHeapType::ConcreteStruct(_)
+                | WasmHeapType::ConcreteArray(_)
+                | WasmHeapType::ConcreteFunc(_)
+        );
+        let is_subtype = self.env.builtins.is_subtype::<M::ABI, M::Ptr>()?;
+        // The subtype check of concrete types might call into the runtime,
+        // in which case the result is produced in the libcall's result
+        // register.
+        let res = if concrete {
+            self.context.spill(self.masm)?;
+            self.context.reg(
+                is_subtype.sig().results.unwrap_singleton().unwrap_reg(),
+                self.masm,
+            )?
+        } else {
+            self.context.any_gpr(self.masm)?
+        };
+        let val = self.load_stack_val(top)?.reg;
+        let tmp = self.context.any_gpr(self.masm)?;
+        let done = self.masm.get_label()?;
+
+        self.masm.mov(
+            writable!(res),
+            RegImm::i32(ty.nullable as i32),
+            OperandSize::S32,
+        )?;
+        self.masm
+            .branch(IntCmpKind::Eq, val, val.into(), done, size)?;
+
+        if ty.heap_type.top() == WasmHeapTopType::Any {
+            let i31_result = matches!(
+                ty.heap_type,
+                WasmHeapType::Any | WasmHeapType::Eq | WasmHeapType::I31
+            );
+            self.masm.mov(
+                writable!(res),
+                RegImm::i32(i31_result as i32),
+                OperandSize::S32,
+            )?;
+            self.masm
+                .mov(writable!(tmp), val.into(), OperandSize::S32)?;
+            self.masm.and(
+                writable!(tmp),
+                tmp,
+                RegImm::i32(I31_DISCRIMINANT as i32),
+                OperandSize::S32,
+            )?;
+            self.masm
+                .branch(IntCmpKind::Ne, tmp, tmp.into(), done, OperandSize::S32)?;
+        }
+
+        let expected_kind = match ty.heap_type {
+            WasmHeapType::Eq => Some(VMGcKind::EqRef),
+            WasmHeapType::Struct => Some(VMGcKind::StructRef),
+            WasmHeapType::Array => Some(VMGcKind::ArrayRef),
+            _ => None,
+        };
+        if let Some(kind) = expected_kind {
+            self.emit_gc_object_addr(val, VM_GC_HEADER_KIND_OFFSET, VM_GC_KIND_SIZE.into(), tmp)?;
+            self.masm.load(
+                self.masm.address_at_reg(tmp, VM_GC_HEADER_KIND_OFFSET)?,
+                writable!(tmp),
+                OperandSize::S32,
+            )?;
+            self.masm.and(
+                writable!(tmp),
+                tmp,
+                RegImm::i32(kind.as_u32() as i32),
+                OperandSize::S32,
+            )?;
+            self.masm.cmp_with_set(
+                writable!(tmp),
+                RegImm::i32(kind.as_u32() as i32),
+                IntCmpKind::Eq,
+                OperandSize::S32,
+            )?;
+            self.masm
+                .mov(writable!(res), tmp.into(), OperandSize::S32)?;
+        } else {
+            let expected = match ty.heap_type {
+                WasmHeapType::ConcreteStruct(idx)
+                | WasmHeapType::ConcreteArray(idx)
+                | WasmHeapType::ConcreteFunc(idx) => idx.unwrap_module_type_index(),
+                _ => bail!(CodeGenError::unsupported_wasm_type()),
+            };
+            let type_id_size = self.env.vmoffsets.size_of_vmshared_type_index();
+            // Load the actual type of the reference.
+            if ty.heap_type.top() == WasmHeapTopType::Func {
+                let offset = self.env.vmoffsets.ptr.vm_func_ref_type_index();
+                self.masm.load(
+                    self.masm.address_at_reg(val, offset.into())?,
+                    writable!(tmp),
+                    OperandSize::S32,
+                )?;
+            } else {
+                self.emit_gc_object_addr(
+                    val,
+                    VM_GC_HEADER_TYPE_INDEX_OFFSET,
+                    type_id_size.into(),
+                    tmp,
+                )?;
+                self.masm.load(
+                    self.masm
+                        .address_at_reg(tmp, VM_GC_HEADER_TYPE_INDEX_OFFSET)?,
+                    writable!(tmp),
+                    OperandSize::S32,
+                )?;
+            }
+            // Load the engine type of the expected type.
+            let type_ids = self.env.vmoffsets.ptr.vmctx_type_ids_array();
+            let offset = expected
+                .as_u32()
+                .checked_mul(type_id_size.into())
+                .ok_or_else(|| CodeGenError::unexpected_operand_size())?;
+            self.masm
+                .load_ptr(self.masm.address_at_vmctx(type_ids.into())?, writable!(val))?;
+            self.masm.load(
+                self.masm.address_at_reg(val, offset)?,
+                writable!(val),
+                OperandSize::S32,
+            )?;
+
+            // Fast path: same types.
+            self.masm
+                .mov(writable!(res), RegImm::i32(1), OperandSize::S32)?;
+            self.masm
+                .branch(IntCmpKind::Eq, tmp, val.into(), done, OperandSize::S32)?;
+
+            // Slow path: full subtype check in the runtime.
+            self.context.free_reg(res);
+            self.context.stack.push(TypedReg::i32(tmp).into());
+            self.context.stack.push(TypedReg::i32(val).into());
+            FnCall::emit::<M>(
+                &mut self.env,
+                self.masm,
+                &mut self.context,
+                Callee::Builtin(is_subtype),
+            )?;
+            // Keep the result register reserved, it's released by the
+            // caller.
+            let result = self
+                .context
+                .stack
+                .pop()
+                .ok_or_else(|| CodeGenError::missing_values_in_stack())?;
+            ensure!(
+                result.is_reg() && result.unwrap_reg().reg == res,
+                CodeGenError::invalid_sp_offset()
+            );
+            self.context.reg(tmp, self.masm)?;
+            self.context.reg(val, self.masm)?;
+        }
+
+        self.masm.bind(done)?;
+        self.context.free_reg(val);
+        self.context.free_reg(tmp);
+        Ok(res)
+    }
+
+    /// Emits `ref.test`.
+    pub fn emit_ref_test(&mut self, ty: WasmRefType) -> Result<()> {
+        let res = self.emit_ref_test_impl(ty)?;
+        self.drop_stack_vals(1)?;
+        self.context.stack.push(TypedReg::i32(res).into());
+        Ok(())
+    }
+
+    /// Emits `ref.cast`.
+    pub fn emit_ref_cast(&mut self, ty: WasmRefType) -> Result<()> {
+        let res = self.emit_ref_test_impl(ty)?;
+        self.masm.trapz(res, TRAP_CAST_FAILURE)?;
+        self.context.free_reg(res);
+        self.emit_retype_top(WasmValType::Ref(ty))
+    }
+
+    /// Pushes the condition of `br_on_cast` or, if `fail` is set, of
+    /// `br_on_cast_fail` to the value stack, keeping the tested reference.
+    pub fn emit_br_on_cast_cond(&mut self, ty: WasmRefType, fail: bool) -> Result<()> {
+        let res = self.emit_ref_test_impl(ty)?;
+        if fail {
+            self.masm
+                .xor(writable!(res), res, RegImm::i32(1), OperandSize::S32)?;
+        }
+        self.context.stack.push(TypedReg::i32(res).into());
+        Ok(())
+    }
+
+    /// Emits `table.get` for tables of GC references.
+    pub fn emit_gc_table_get(&mut self, table_index: TableIndex, ty: WasmRefType) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let table_data = self.env.resolve_table_data(table_index);
+        let index = self.context.pop_to_reg(self.masm, None)?;
+        let base = self.context.any_gpr(self.masm)?;
+        self.emit_compute_table_elem_addr(index.reg, base, &table_data)?;
+        self.context.free_reg(index);
+        let dst = self.emit_gc_ref_read(collector, ty, base, 0)?;
+        self.context.free_reg(base);
+        self.context
+            .stack
+            .push(TypedReg::new(WasmValType::Ref(ty), dst).into());
+        Ok(())
+    }
+
+    /// Emits `table.set` for tables of GC references.
+    pub fn emit_gc_table_set(&mut self, table_index: TableIndex, ty: WasmRefType) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let table_data = self.env.resolve_table_data(table_index);
+        self.context.spill(self.masm)?;
+        let val = self.context.pop_to_reg(self.masm, None)?;
+        let index = self.context.pop_to_reg(self.masm, None)?;
+        let base = self.context.any_gpr(self.masm)?;
+        self.emit_compute_table_elem_addr(index.reg, base, &table_data)?;
+        self.context.free_reg(index);
+        self.emit_gc_ref_write(collector, ty, base, 0, val.reg, &[])?;
+        self.context.free_reg(base);
+        self.context.free_reg(val);
+        Ok(())
+    }
+
+    /// Returns the `table.grow` libcall for tables of GC references.
+    pub fn gc_table_grow_builtin(&mut self) -> Result<BuiltinFunction> {
+        self.gc_collector()?;
+        self.env.builtins.table_grow_gc_ref::<M::ABI, M::Ptr>()
+    }
+
+    /// Returns the `table.fill` libcall for tables of GC references.
+    pub fn gc_table_fill_builtin(&mut self) -> Result<BuiltinFunction> {
+        self.gc_collector()?;
+        self.env.builtins.table_fill_gc_ref::<M::ABI, M::Ptr>()
+    }
+
+    /// Emits `global.get` for globals holding GC references.
+    pub fn emit_gc_global_get(&mut self, index: GlobalIndex, ty: WasmRefType) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let (_, base, offset) = self.emit_get_global_addr(index)?;
+        let dst = self.emit_gc_ref_read(collector, ty, base, offset)?;
+        self.context.free_reg(base);
+        self.context
+            .stack
+            .push(TypedReg::new(WasmValType::Ref(ty), dst).into());
+        Ok(())
+    }
+
+    /// Emits `global.set` for globals holding GC references.
+    pub fn emit_gc_global_set(&mut self, index: GlobalIndex, ty: WasmRefType) -> Result<()> {
+        let collector = self.gc_collector()?;
+        self.context.spill(self.masm)?;
+        let val = self.context.pop_to_reg(self.masm, None)?;
+        let (_, base, offset) = self.emit_get_global_addr(index)?;
+        self.emit_gc_ref_write(collector, ty, base, offset, val.reg, &[])?;
+        self.context.free_reg(base);
+        self.context.free_reg(val);
+        Ok(())
+    }
+
+    /// Returns the layout of the exception objects thrown with the given tag,
+    /// along with the types of their payload.
+    fn exn_layout(
+        &self,
+        collector: Collector,
+        tag: TagIndex,
+    ) -> Result<(GcExceptionLayout, Vec<WasmValType>)> {
+        let signature = self.env.translation.module.tags[tag].signature;
+        let params = self.env.types[signature.unwrap_module_type_index()]
+            .unwrap_func()
+            .params()
+            .to_vec();
+        let ty = WasmExnType {
+            func_ty: signature,
+            fields: params
+                .iter()
+                .map(|ty| WasmFieldType {
+                    element_type: WasmStorageType::Val(*ty),
+                    mutable: false,
+                })
+                .collect(),
+        };
+        Ok((Self::gc_layouts(collector).exn_layout(&ty), params))
+    }
+
+    /// Emits `throw`: allocates an exception object for the given tag, with
+    /// the payload at the top of the value stack, and throws it.
+    pub fn emit_throw(&mut self, tag: TagIndex) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let (layout, params) = self.exn_layout(collector, tag)?;
+        let fields = params
+            .iter()
+            .zip(layout.fields.iter())
+            .map(|(ty, field)| (field.offset, WasmStorageType::Val(*ty)))
+            .collect::<Vec<_>>();
+        let builtin = self.env.builtins.alloc_exn::<M::ABI, M::Ptr>()?;
+        let exn = self.emit_alloc_and_init(collector, &fields, |this| {
+            this.context.stack.push(tag.as_u32().try_into()?);
+            FnCall::emit::<M>(
+                &mut this.env,
+                this.masm,
+                &mut this.context,
+                Callee::Builtin(builtin),
+            )?;
+            Ok(this.context.pop_to_reg(this.masm, None)?.reg)
+        })?;
+        self.context
+            .stack
+            .push(TypedReg::new(exn_ref_type(), exn).into());
+        self.emit_throw_ref()
+    }
+
+    /// Emits `throw_ref` for the exception reference at the top of the value
+    /// stack.
+    ///
+    /// The `throw_ref` libcall never returns: it either unwinds to the
+    /// handler of the exception or traps.
+    pub fn emit_throw_ref(&mut self) -> Result<()> {
+        self.gc_collector()?;
+        let builtin = self.env.builtins.throw_ref::<M::ABI, M::Ptr>()?;
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        self.context.pop_and_free(self.masm)
+    }
+
+    /// Pops the non-null exception reference at the top of the value stack
+    /// and pushes whether it was thrown with the given tag.
+    pub fn emit_exn_tag_matches(&mut self, tag: TagIndex) -> Result<()> {
+        self.gc_collector()?;
+        let builtin = self.env.builtins.exn_tag_matches::<M::ABI, M::Ptr>()?;
+        self.context.stack.push(tag.as_u32().try_into()?);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )
+    }
+
+    /// Pushes the payload of the exception object, thrown with the given
+    /// tag, referenced by the value at the given value stack index.
+    pub fn emit_exn_payload(&mut self, tag: TagIndex, exn_index: usize) -> Result<()> {
+        let collector = self.gc_collector()?;
+        let (layout, params) = self.exn_layout(collector, tag)?;
+        for (ty, field) in params.iter().zip(layout.fields.iter()) {
+            let ty = WasmStorageType::Val(*ty);
+            let size = wasmtime_environ::byte_size_of_wasm_ty_in_gc_heap(&ty);
+            // Reading a field may call into the runtime, so the reference is
+            // reloaded from the value stack for each of them.
+            let exn = self.load_stack_val(exn_index)?;
+            let addr = self.context.any_gpr(self.masm)?;
+            self.emit_gc_object_addr(exn.reg, field.offset, size, addr)?;
+            self.context.free_reg(exn);
+            self.emit_gc_read(collector, addr, field.offset, ty, false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the type of the references to thrown exceptions.
+fn exn_ref_type() -> WasmValType {
+    WasmValType::Ref(WasmRefType {
+        nullable: false,
+        heap_type: WasmHeapType::Exn,
+    })
+}
+
+/// Wraps a module-interned type index into the representation used by
+/// [`WasmHeapType`].
+fn type_index_to_engine_or_module(
+    index: ModuleInternedTypeIndex,
+) -> wasmtime_environ::EngineOrModuleTypeIndex {
+    wasmtime_environ::EngineOrModuleTypeIndex::Module(index)
+}
@@ -20,10 +20,12 @@ use wasmparser::{
     BinaryReader, FuncValidator, MemArg, Operator, OperatorsReader, ValidatorResources,
     VisitOperator, VisitSimdOperator,
 };
-use wasmtime_cranelift::{TRAP_BAD_SIGNATURE, TRAP_HEAP_MISALIGNED, TRAP_TABLE_OUT_OF_BOUNDS};
+use wasmtime_cranelift::{
+    TRAP_BAD_SIGNATURE, TRAP_HEAP_MISALIGNED, TRAP_NULL_REFERENCE, TRAP_TABLE_OUT_OF_BOUNDS,
+};
 use wasmtime_environ::{
-    FUNCREF_MASK, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables, TypeIndex, WasmHeapType,
-    WasmValType,
+    FUNCREF_MASK, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables, TypeIndex,
+    WasmHeapTopType, WasmRefType, WasmValType,
 };
 
 mod context;
@@ -37,6 +39,9 @@ pub(crate) use control::*;
 mod builtin;
 pub use builtin::*;
 pub(crate) mod bounds;
+mod exceptions;
+pub(crate) use exceptions::*;
+mod gc;
 
 use bounds::{Bounds, ImmOffset, Index};
 
@@ -107,6 +112,9 @@ where
     // we see fit.
     pub control_frames: SmallVec<[ControlStackFrame; 64]>,
 
+    /// The `try_table`s enclosing the code being compiled, innermost last.
+    pub try_tables: Vec<TryTable>,
+
     /// Information about the source code location.
     pub source_location: SourceLocation,
 
@@ -137,6 +145,7 @@ where
             tunables,
             source_location: Default::default(),
             control_frames: Default::default(),
+            try_tables: Default::default(),
             // Empty functions should consume at least 1 fuel unit.
             fuel_consumed: 1,
             phase: PhantomData,
@@ -198,6 +207,7 @@ where
             tunables: self.tunables,
             source_location: self.source_location,
             control_frames: self.control_frames,
+            try_tables: self.try_tables,
             fuel_consumed: self.fuel_consumed,
             phase: PhantomData,
         })
@@ -218,10 +228,15 @@ where
                         I32 | I64 | F32 | F64 | V128 => {
                             self.masm.store((*reg).into(), addr, (*ty).try_into()?)?;
                         }
-                        Ref(rt) => match rt.heap_type {
-                            WasmHeapType::Func | WasmHeapType::Extern => {
+                        Ref(rt) => match rt.heap_type.top() {
+                            WasmHeapTopType::Func => {
                                 self.masm.store_ptr(*reg, addr)?;
                             }
+                            WasmHeapTopType::Any
+                            | WasmHeapTopType::Extern
+                            | WasmHeapTopType::Exn => {
+                                self.masm.store((*reg).into(), addr, OperandSize::S32)?;
+                            }
                             _ => bail!(CodeGenError::unsupported_wasm_type()),
                         },
                     }
@@ -230,6 +245,17 @@ where
                 _ => {}
             }
         }
+
+        // Copy the stack arguments holding GC references to the current
+        // frame.
+        for (src, dst) in &self.context.frame.gc_ref_stack_args {
+            let src = self.masm.local_address(src)?;
+            let dst = self.masm.local_address(dst)?;
+            self.masm.with_scratch::<IntScratch, _>(|masm, scratch| {
+                masm.load(src, scratch.writable(), OperandSize::S32)?;
+                masm.store(scratch.inner().into(), dst, OperandSize::S32)
+            })?;
+        }
         Ok(())
     }
 }
@@ -319,6 +345,8 @@ where
 
         self.maybe_emit_epoch_check()?;
 
+        self.maybe_emit_tier_up_check()?;
+
         // Once we have emitted the epilogue and reserved stack space for the locals, we push the
         // base control flow block.
         self.control_frames.push(ControlStackFrame::block(
@@ -373,7 +401,7 @@ where
         fn visit_op_when_unreachable(op: &Operator) -> bool {
             use Operator::*;
             match op {
-                If { .. } | Block { .. } | Loop { .. } | Else | End => true,
+                If { .. } | Block { .. } | Loop { .. } | TryTable { .. } | Else | End => true,
                 _ => false,
             }
         }
@@ -500,6 +528,47 @@ where
         anyhow::Ok(())
     }
 
+    /// Emits a tail call to the given callee.
+    ///
+    /// If the callee's stack arguments don't fit in the current function's
+    /// incoming argument area, a regular call followed by a return is
+    /// emitted instead.
+    pub fn emit_return_call(&mut self, callee: Callee) -> Result<()> {
+        if FnCall::emit_return_call::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            callee.clone(),
+            &self.sig,
+        )? {
+            self.context.reachable = false;
+            // Set the implicit outermost frame as target to perform the
+            // necessary stack clean up.
+            self.control_frames[0].set_as_target();
+            return Ok(());
+        }
+
+        FnCall::emit::<M>(&mut self.env, self.masm, &mut self.context, callee)?;
+        let outermost = &mut self.control_frames[0];
+        self.context
+            .br::<_, _, UnconditionalBranch>(outermost, self.masm, |masm, cx, frame| {
+                frame.pop_abi_results::<M, _>(cx, masm, |results, _, _| {
+                    Ok(results.ret_area().copied())
+                })
+            })
+    }
+
+    /// Pops the reference at the top of the value stack, traps if it's null
+    /// and pushes it back as a non-nullable reference.
+    pub fn emit_ref_as_non_null(&mut self) -> Result<()> {
+        let top = self.context.pop_to_reg(self.masm, None)?;
+        self.masm.trapz(top.reg, TRAP_NULL_REFERENCE)?;
+        self.context
+            .stack
+            .push(TypedReg::new(non_null(top.ty)?, top.reg).into());
+        Ok(())
+    }
+
     /// Emit the usual function end instruction sequence.
     fn emit_end(&mut self) -> Result<()> {
         // The implicit body block is treated a normal block (it pushes results
@@ -1231,6 +1300,85 @@ where
         Ok(())
     }
 
+    /// Checks if tiered compilation is enabled and emits a series of
+    /// instructions that decrement the tier-up countdown in `VMStoreContext`,
+    /// calling the `tier_up` builtin to sample the current function once the
+    /// countdown has expired.
+    pub fn maybe_emit_tier_up_check(&mut self) -> Result<()> {
+        if !self.tunables.tiered_compilation {
+            return Ok(());
+        }
+
+        // The continuation branch if the countdown hasn't expired yet.
+        let cont = self.masm.get_label()?;
+        let tier_up = self.env.builtins.tier_up::<M::ABI, M::Ptr>()?;
+
+        // As with the fuel and epoch checks, ensure that the registers used
+        // here aren't needed by the builtin call below.
+        let (store_context_reg, countdown_reg) = self
+            .context
+            .without::<Result<(Reg, Reg)>, M, _>(&tier_up.sig().regs, self.masm, |cx, masm| {
+                Ok((cx.any_gpr(masm)?, cx.any_gpr(masm)?))
+            })??;
+
+        let store_context_offset = self.env.vmoffsets.ptr.vmctx_store_context();
+        let countdown_offset = self.env.vmoffsets.ptr.vmstore_context_tier_up_countdown();
+        self.masm.load_ptr(
+            self.masm
+                .address_at_vmctx(u32::from(store_context_offset))?,
+            writable!(store_context_reg),
+        )?;
+        self.masm.load(
+            self.masm
+                .address_at_reg(store_context_reg, u32::from(countdown_offset))?,
+            writable!(countdown_reg),
+            // The countdown is an i32.
+            OperandSize::S32,
+        )?;
+        self.masm.sub(
+            writable!(countdown_reg),
+            countdown_reg,
+            RegImm::i32(1),
+            OperandSize::S32,
+        )?;
+        self.masm.store(
+            countdown_reg.into(),
+            self.masm
+                .address_at_reg(store_context_reg, u32::from(countdown_offset))?,
+            OperandSize::S32,
+        )?;
+
+        // Spill locals and registers to avoid conflicts at the control flow
+        // merge below.
+        self.context.spill(self.masm)?;
+        self.masm.branch(
+            IntCmpKind::GtS,
+            countdown_reg,
+            RegImm::i32(0),
+            cont,
+            OperandSize::S32,
+        )?;
+
+        // Countdown expired branch.
+        self.context
+            .stack
+            .extend([self.env.func_index.as_u32().try_into()?]);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(tier_up.clone()),
+        )?;
+        self.context.pop_and_free(self.masm)?;
+
+        // Countdown not expired branch.
+        self.masm.bind(cont)?;
+
+        self.context.free_reg(store_context_reg);
+        self.context.free_reg(countdown_reg);
+        Ok(())
+    }
+
     fn emit_load_epoch_deadline_and_counter(
         &mut self,
         epoch_deadline_reg: Reg,
@@ -1366,8 +1514,10 @@ where
             | Operator::Return
             | Operator::CallIndirect { .. }
             | Operator::Call { .. }
+            | Operator::CallRef { .. }
             | Operator::ReturnCall { .. }
-            | Operator::ReturnCallIndirect { .. } => self.emit_fuel_increment(),
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. } => self.emit_fuel_increment(),
             _ => Ok(()),
         }
     }
@@ -1629,3 +1779,14 @@ pub fn control_index(depth: u32, control_length: usize) -> Result<usize> {
         .checked_sub(depth as usize)
         .ok_or_else(|| anyhow!(CodeGenError::control_frame_expected()))
 }
+
+/// Returns the non-nullable counterpart of the given reference type.
+pub(crate) fn non_null(ty: WasmValType) -> Result<WasmValType> {
+    match ty {
+        WasmValType::Ref(rt) => Ok(WasmValType::Ref(WasmRefType {
+            nullable: false,
+            ..rt
+        })),
+        _ => bail!(CodeGenError::unsupported_wasm_type()),
+    }
+}
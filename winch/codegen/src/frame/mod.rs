@@ -93,13 +93,21 @@ pub(crate) struct Frame<P: CodeGenPhase> {
 
     /// The slot holding the address of the results area.
     pub results_base_slot: Option<LocalSlot>,
+
+    /// Stack arguments holding GC references, paired with the frame local
+    /// slot they are copied to in the prologue.
+    ///
+    /// GC references must live in the current frame for the collector to be
+    /// able to find them through the function's stack maps; the incoming
+    /// argument area belongs to the caller's frame.
+    pub gc_ref_stack_args: SmallVec<[(LocalSlot, LocalSlot); 2]>,
     marker: PhantomData<P>,
 }
 
 impl Frame<Prologue> {
     /// Allocate a new [`Frame`].
     pub fn new<A: ABI>(sig: &ABISig, defined_locals: &DefinedLocals) -> Result<Frame<Prologue>> {
-        let (special_locals, mut wasm_locals, defined_locals_start) =
+        let (special_locals, mut wasm_locals, gc_ref_stack_args, defined_locals_start) =
             Self::compute_arg_slots::<A>(sig)?;
 
         // The defined locals have a zero-based offset by default
@@ -156,6 +164,7 @@ impl Frame<Prologue> {
                 defined_locals_start..(defined_locals_start + defined_locals.stack_size),
             ),
             results_base_slot,
+            gc_ref_stack_args,
             marker: PhantomData,
         })
     }
@@ -174,11 +183,19 @@ impl Frame<Prologue> {
             locals_size: self.locals_size,
             defined_locals_range: self.defined_locals_range,
             results_base_slot: self.results_base_slot,
+            gc_ref_stack_args: self.gc_ref_stack_args,
             marker: PhantomData,
         }
     }
 
-    fn compute_arg_slots<A: ABI>(sig: &ABISig) -> Result<(SpecialLocals, WasmLocals, u32)> {
+    fn compute_arg_slots<A: ABI>(
+        sig: &ABISig,
+    ) -> Result<(
+        SpecialLocals,
+        WasmLocals,
+        SmallVec<[(LocalSlot, LocalSlot); 2]>,
+        u32,
+    )> {
         // Go over the function ABI-signature and
         // calculate the stack slots.
         //
@@ -193,6 +210,10 @@ impl Frame<Prologue> {
         //     The slot is calculated by accumulating into the `next_frame_size`
         //     the size + alignment of the type that the register is holding.
         //
+        //  Stack holding a GC reference =>
+        //     Treated as a register argument, the argument is copied to its
+        //     slot in the prologue.
+        //
         //  NOTE
         //      This implementation takes inspiration from SpiderMonkey's implementation
         //      to calculate local slots for function arguments
@@ -225,11 +246,26 @@ impl Frame<Prologue> {
             .map(|arg| Self::abi_arg_slot(&arg, &mut next_stack, arg_base_offset))
             .expect("Slot for VMContext");
 
+        let mut gc_ref_stack_args = SmallVec::new();
         let slots: WasmLocals = params_iter
-            .map(|arg| Self::abi_arg_slot(&arg, &mut next_stack, arg_base_offset))
+            .map(|arg| match arg {
+                ABIOperand::Stack { ty, offset, size } if ty.is_vmgcref_type() => {
+                    next_stack = align_to(next_stack, *size) + *size;
+                    let slot = LocalSlot::new(*ty, next_stack);
+                    gc_ref_stack_args
+                        .push((LocalSlot::stack_arg(*ty, offset + arg_base_offset), slot));
+                    slot
+                }
+                _ => Self::abi_arg_slot(&arg, &mut next_stack, arg_base_offset),
+            })
             .collect();
 
-        Ok(([callee_vmctx, caller_vmctx], slots, next_stack))
+        Ok((
+            [callee_vmctx, caller_vmctx],
+            slots,
+            gc_ref_stack_args,
+            next_stack,
+        ))
     }
 
     fn abi_arg_slot(arg: &ABIOperand, next_stack: &mut u32, arg_base_offset: u32) -> LocalSlot {
@@ -263,6 +299,12 @@ impl Frame<Emission> {
             .unwrap_or_else(|| panic!(" Expected WebAssembly local at slot: {index}"))
     }
 
+    /// Returns an iterator over the [`LocalSlot`]s of all the WebAssembly
+    /// locals.
+    pub fn wasm_locals(&self) -> impl Iterator<Item = &LocalSlot> {
+        self.wasm_locals.iter()
+    }
+
     /// Get the [`LocalSlot`] for a special local.
     ///
     /// # Panics
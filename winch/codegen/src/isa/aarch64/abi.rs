@@ -4,7 +4,7 @@ use crate::abi::{ABI, ABIOperand, ABIParams, ABIResults, ABISig, ParamsOrReturns
 use crate::codegen::CodeGenError;
 use crate::isa::{CallingConvention, reg::Reg};
 use anyhow::{Result, bail};
-use wasmtime_environ::{WasmHeapType, WasmValType};
+use wasmtime_environ::{WasmHeapTopType, WasmValType};
 
 #[derive(Default)]
 pub(crate) struct Aarch64ABI;
@@ -130,9 +130,13 @@ impl ABI for Aarch64ABI {
 
     fn sizeof(ty: &WasmValType) -> u8 {
         match ty {
-            WasmValType::Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func => Self::word_bytes(),
-                ht => unimplemented!("Support for WasmHeapType: {ht}"),
+            WasmValType::Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func => Self::word_bytes(),
+                // GC references are 32-bit indices into the GC heap.
+                WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                    Self::word_bytes() / 2
+                }
+                _ => unimplemented!("Support for WasmHeapType: {}", rt.heap_type),
             },
             WasmValType::F64 | WasmValType::I64 => Self::word_bytes(),
             WasmValType::F32 | WasmValType::I32 => Self::word_bytes() / 2,
@@ -158,10 +162,11 @@ impl Aarch64ABI {
                 (index_env.next_fpr().map(regs::vreg), ty)
             }
 
-            ty @ WasmValType::Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func | WasmHeapType::Extern => {
-                    (index_env.next_gpr().map(regs::xreg), ty)
-                }
+            ty @ WasmValType::Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func
+                | WasmHeapTopType::Any
+                | WasmHeapTopType::Extern
+                | WasmHeapTopType::Exn => (index_env.next_gpr().map(regs::xreg), ty),
                 _ => bail!(CodeGenError::unsupported_wasm_type()),
             },
 
@@ -13,6 +13,7 @@ use crate::{
 };
 
 use cranelift_codegen::PatchRegion;
+use cranelift_codegen::binemit::Reloc;
 use cranelift_codegen::isa::aarch64::inst::emit::{enc_arith_rrr, enc_move_wide, enc_movk};
 use cranelift_codegen::isa::aarch64::inst::{
     ASIMDFPModImm, FpuToIntOp, MoveWideConst, NZCV, UImm5,
@@ -124,6 +125,13 @@ impl Assembler {
     }
 
     fn emit_with_island(&mut self, inst: Inst, needed_space: u32) {
+        self.maybe_emit_island(needed_space);
+        inst.emit(&mut self.buffer, &self.emit_info, &mut self.emit_state);
+    }
+
+    /// Emits an island, jumping over it, if one is needed before emitting
+    /// `needed_space` bytes of code.
+    fn maybe_emit_island(&mut self, needed_space: u32) {
         if self.buffer.island_needed(needed_space) {
             let label = self.buffer.get_label();
             let jmp = Inst::Jump {
@@ -135,7 +143,6 @@ impl Assembler {
             self.buffer
                 .bind_label(label, self.emit_state.ctrl_plane_mut());
         }
-        inst.emit(&mut self.buffer, &self.emit_info, &mut self.emit_state);
     }
 
     /// Adds a constant to the constant pool, returning its address.
@@ -1104,6 +1111,26 @@ impl Assembler {
         })
     }
 
+    /// Emit a jump to a locally defined function through an index, used to
+    /// perform tail calls.
+    pub fn jmp_with_name(&mut self, name: UserExternalNameRef) {
+        self.maybe_emit_island(4);
+        // `b #0`, whose 26-bit offset is patched through the same relocation
+        // used by direct calls.
+        self.buffer
+            .add_reloc(Reloc::Arm64Call, &ExternalName::user(name), 0);
+        self.buffer.put4(0b000101 << 26);
+    }
+
+    /// Emit a jump to a function whose address is stored in the `callee`
+    /// register, used to perform tail calls.
+    pub fn jmp_with_reg(&mut self, callee: Reg) {
+        self.emit(Inst::IndirectBr {
+            rn: callee.into(),
+            targets: vec![],
+        });
+    }
+
     /// Load the min value for an integer of size out_size, as a floating-point
     /// of size `in-size`, into register `rd`.
     fn min_fp_value(
@@ -201,28 +201,26 @@ impl Masm for MacroAssembler {
         // Sync the real stack pointer with the value of the shadow stack
         // pointer.
         self.move_shadow_sp_to_sp();
-
-        // Pop the shadow stack pointer. It's assumed that at this point
-        // `sp_offset` is 0 and therefore the real stack pointer should be
-        // 16-byte aligned.
-        let addr = Address::post_indexed_from_sp(SHADOW_STACK_POINTER_SLOT_SIZE as i64);
-        self.asm.uload(
-            addr,
-            writable!(regs::shadow_sp()),
-            OperandSize::S64,
-            TRUSTED_FLAGS,
-        );
-
-        // Restore the link register and frame pointer.
-        let lr = regs::lr();
-        let fp = regs::fp();
-        let addr = Address::post_indexed_from_sp(16);
-
-        self.asm.ldp(fp, lr, addr);
+        self.pop_frame();
         self.asm.ret();
         Ok(())
     }
 
+    fn return_call(&mut self, callee: CalleeKind) -> Result<()> {
+        // Point the stack pointer to the saved shadow stack pointer, right
+        // below the frame pointer, regardless of the current stack pointer
+        // offset.
+        let imm = Imm12::maybe_from_u64(SHADOW_STACK_POINTER_SLOT_SIZE.into()).unwrap();
+        self.asm
+            .sub_ir(imm, regs::fp(), writable!(regs::sp()), OperandSize::S64);
+        self.pop_frame();
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.jmp_with_reg(reg),
+            CalleeKind::Direct(idx) => self.asm.jmp_with_name(idx),
+        };
+        Ok(())
+    }
+
     fn reserve_stack(&mut self, bytes: u32) -> Result<()> {
         if bytes == 0 {
             return Ok(());
@@ -290,6 +288,10 @@ impl Masm for MacroAssembler {
         Ok(())
     }
 
+    fn landing_pad(&mut self, _sp_offset: SPOffset, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
     fn local_address(&mut self, local: &LocalSlot) -> Result<Address> {
         let (reg, offset) = local
             .addressed_from_sp()
@@ -380,8 +382,14 @@ impl Masm for MacroAssembler {
     fn call(
         &mut self,
         stack_args_size: u32,
+        exception_handler: Option<MachLabel>,
         mut load_callee: impl FnMut(&mut Self) -> Result<(CalleeKind, CallingConvention)>,
     ) -> Result<u32> {
+        // Cranelift's AArch64 backend doesn't record exception handlers at
+        // call sites.
+        if exception_handler.is_some() {
+            bail!(CodeGenError::unimplemented_masm_instruction());
+        }
         let alignment: u32 = <Self::ABI as abi::ABI>::call_stack_align().into();
         let addend: u32 = <Self::ABI as abi::ABI>::initial_frame_size().into();
         let delta = calculate_frame_adjustment(self.sp_offset()?.as_u32(), addend, alignment);
@@ -1593,6 +1601,28 @@ impl MacroAssembler {
     //
     // This function must also be called at the function epilogue, since the
     // stack pointer is used to restore the current function frame.
+    /// Pops the shadow stack pointer, the frame pointer and the link
+    /// register, assuming that the stack pointer points to the saved shadow
+    /// stack pointer.
+    fn pop_frame(&mut self) {
+        // Pop the shadow stack pointer. It's assumed that at this point the
+        // real stack pointer is 16-byte aligned.
+        let addr = Address::post_indexed_from_sp(SHADOW_STACK_POINTER_SLOT_SIZE as i64);
+        self.asm.uload(
+            addr,
+            writable!(regs::shadow_sp()),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+
+        // Restore the link register and frame pointer.
+        let lr = regs::lr();
+        let fp = regs::fp();
+        let addr = Address::post_indexed_from_sp(16);
+
+        self.asm.ldp(fp, lr, addr);
+    }
+
     fn move_shadow_sp_to_sp(&mut self) {
         let shadow_sp = regs::shadow_sp();
         let sp = writable!(regs::sp());
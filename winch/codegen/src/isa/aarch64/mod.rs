@@ -4,7 +4,7 @@ use crate::{
     abi::{ABI, wasm_sig},
     codegen::{CodeGen, CodeGenContext, FuncEnv, TypeConverter},
     frame::{DefinedLocals, Frame},
-    isa::{Builder, TargetIsa},
+    isa::{Builder, CompiledFunctionOutput, TargetIsa},
     masm::MacroAssembler,
     regalloc::RegAlloc,
     stack::Stack,
@@ -17,7 +17,9 @@ use masm::MacroAssembler as Aarch64Masm;
 use target_lexicon::Triple;
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType,
+};
 
 mod abi;
 mod address;
@@ -82,6 +84,7 @@ impl TargetIsa for Aarch64 {
 
     fn compile_function(
         &self,
+        index: DefinedFuncIndex,
         sig: &WasmFuncType,
         body: &FunctionBody,
         translation: &ModuleTranslation,
@@ -89,7 +92,7 @@ impl TargetIsa for Aarch64 {
         builtins: &mut BuiltinFunctions,
         validator: &mut FuncValidator<ValidatorResources>,
         tunables: &Tunables,
-    ) -> Result<CompiledFunction> {
+    ) -> Result<CompiledFunctionOutput> {
         let pointer_bytes = self.pointer_bytes();
         let vmoffsets = VMOffsets::new(pointer_bytes, &translation.module);
         let mut body = body.get_binary_reader();
@@ -99,6 +102,7 @@ impl TargetIsa for Aarch64 {
 
         let env = FuncEnv::new(
             &vmoffsets,
+            index,
             translation,
             types,
             builtins,
@@ -117,11 +121,15 @@ impl TargetIsa for Aarch64 {
         body_codegen.emit(body, validator)?;
         let names = body_codegen.env.take_name_map();
         let base = body_codegen.source_location.base;
-        Ok(CompiledFunction::new(
-            masm.finalize(base)?,
-            names,
-            self.function_alignment(),
-        ))
+        let stack_maps = body_codegen.env.take_stack_maps();
+        let needs_gc_heap = body_codegen.env.needs_gc_heap;
+        let mut function =
+            CompiledFunction::new(masm.finalize(base)?, names, self.function_alignment());
+        function.set_stack_maps(stack_maps);
+        Ok(CompiledFunctionOutput {
+            function,
+            needs_gc_heap,
+        })
     }
 
     fn text_section_builder(&self, num_funcs: usize) -> Box<dyn TextSectionBuilder> {
@@ -12,7 +12,9 @@ use std::{
 use target_lexicon::{Architecture, Triple};
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, WasmFuncType,
+};
 
 #[cfg(feature = "x64")]
 pub(crate) mod x64;
@@ -20,6 +22,9 @@ pub(crate) mod x64;
 #[cfg(feature = "arm64")]
 pub(crate) mod aarch64;
 
+#[cfg(feature = "riscv64")]
+pub(crate) mod riscv64;
+
 pub(crate) mod reg;
 
 macro_rules! isa_builder {
@@ -37,6 +42,14 @@ macro_rules! isa_builder {
 
 pub type Builder = IsaBuilder<Result<Box<dyn TargetIsa>>>;
 
+/// The output of compiling a function.
+pub struct CompiledFunctionOutput {
+    /// The compiled function.
+    pub function: CompiledFunction,
+    /// Whether or not the function accesses the GC heap.
+    pub needs_gc_heap: bool,
+}
+
 /// Look for an ISA builder for the given target triple.
 pub fn lookup(triple: Triple) -> Result<Builder> {
     match triple.architecture {
@@ -46,6 +59,9 @@ pub fn lookup(triple: Triple) -> Result<Builder> {
         Architecture::Aarch64 { .. } => {
             isa_builder!(aarch64, (feature = "arm64"), triple)
         }
+        Architecture::Riscv64 { .. } => {
+            isa_builder!(riscv64, (feature = "riscv64"), triple)
+        }
 
         _ => Err(anyhow!(LookupError::Unsupported)),
     }
@@ -168,6 +184,7 @@ pub trait TargetIsa: Send + Sync {
     /// Compile a function.
     fn compile_function(
         &self,
+        index: DefinedFuncIndex,
         sig: &WasmFuncType,
         body: &FunctionBody,
         translation: &ModuleTranslation,
@@ -175,7 +192,7 @@ pub trait TargetIsa: Send + Sync {
         builtins: &mut BuiltinFunctions,
         validator: &mut FuncValidator<ValidatorResources>,
         tunables: &Tunables,
-    ) -> Result<CompiledFunction>;
+    ) -> Result<CompiledFunctionOutput>;
 
     /// Get the default calling convention of the underlying target triple.
     fn default_call_conv(&self) -> CallConv {
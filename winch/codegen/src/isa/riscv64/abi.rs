@@ -0,0 +1,338 @@
+use super::regs;
+use crate::RegIndexEnv;
+use crate::abi::{ABI, ABIOperand, ABIParams, ABIResults, ABISig, ParamsOrReturns, align_to};
+use crate::codegen::CodeGenError;
+use crate::isa::{CallingConvention, reg::Reg};
+use anyhow::{Result, bail};
+use wasmtime_environ::{WasmHeapTopType, WasmValType};
+
+#[derive(Default)]
+pub(crate) struct Riscv64ABI;
+
+/// Index of the first argument register, for both register classes:
+/// `a0` (`x10`) for general purpose registers and `fa0` (`f10`) for floating
+/// point registers.
+const ARG_REG_BASE: u8 = 10;
+
+impl ABI for Riscv64ABI {
+    // TODO change to 16 once SIMD is supported
+    fn stack_align() -> u8 {
+        8
+    }
+
+    fn call_stack_align() -> u8 {
+        16
+    }
+
+    fn arg_base_offset() -> u8 {
+        // Two 8-byte slots:
+        // * One for the return address
+        // * One for the frame pointer
+        //
+        // ┌──────────┬───────── Argument base
+        // │   RA     │
+        // │          │
+        // ├──────────┼
+        // │          │
+        // │   FP     │
+        // └──────────┴ -> 16
+        16
+    }
+
+    fn initial_frame_size() -> u8 {
+        // The initial frame size is equal to the space allocated to save the
+        // return address and the frame pointer.
+        Self::arg_base_offset()
+    }
+
+    fn word_bits() -> u8 {
+        64
+    }
+
+    fn sig_from(
+        params: &[WasmValType],
+        returns: &[WasmValType],
+        call_conv: &CallingConvention,
+    ) -> Result<ABISig> {
+        assert!(call_conv.is_systemv() || call_conv.is_default());
+        // The first element tracks the general purpose register index, capped
+        // at 7 (a0-a7).
+        // The second element tracks the floating point register index, capped
+        // at 7 (fa0-fa7).
+        // Follows
+        // https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-cc.adoc
+        // except for floating point arguments, which are never passed in
+        // general purpose registers.
+        let mut params_index_env = RegIndexEnv::with_limits_per_class(8, 8);
+        let results = Self::abi_results(returns, call_conv)?;
+        let params =
+            ABIParams::from::<_, Self>(params, 0, results.on_stack(), |ty, stack_offset| {
+                Self::to_abi_operand(
+                    ty,
+                    stack_offset,
+                    &mut params_index_env,
+                    call_conv,
+                    ParamsOrReturns::Params,
+                )
+            })?;
+
+        Ok(ABISig::new(*call_conv, params, results))
+    }
+
+    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> Result<ABIResults> {
+        assert!(call_conv.is_systemv() || call_conv.is_default());
+        // Use absolute count for results given that for Winch's
+        // default CallingConvention only one register is used for results
+        // independent of the register class.
+        // In the case of 2+ results, the rest are passed in the stack,
+        // similar to how Wasmtime handles multi-value returns.
+        let mut returns_index_env = RegIndexEnv::with_absolute_limit(1);
+
+        ABIResults::from(returns, call_conv, |ty, stack_offset| {
+            Self::to_abi_operand(
+                ty,
+                stack_offset,
+                &mut returns_index_env,
+                call_conv,
+                ParamsOrReturns::Returns,
+            )
+        })
+    }
+
+    fn vmctx_reg() -> Reg {
+        regs::vmctx()
+    }
+
+    fn stack_slot_size() -> u8 {
+        Self::word_bytes()
+    }
+
+    fn sizeof(ty: &WasmValType) -> u8 {
+        match ty {
+            WasmValType::Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func => Self::word_bytes(),
+                // GC references are 32-bit indices into the GC heap.
+                WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                    Self::word_bytes() / 2
+                }
+                _ => unimplemented!("Support for WasmHeapType: {}", rt.heap_type),
+            },
+            WasmValType::F64 | WasmValType::I64 => Self::word_bytes(),
+            WasmValType::F32 | WasmValType::I32 => Self::word_bytes() / 2,
+            WasmValType::V128 => Self::word_bytes() * 2,
+        }
+    }
+}
+
+impl Riscv64ABI {
+    fn to_abi_operand(
+        wasm_arg: &WasmValType,
+        stack_offset: u32,
+        index_env: &mut RegIndexEnv,
+        call_conv: &CallingConvention,
+        params_or_returns: ParamsOrReturns,
+    ) -> Result<(ABIOperand, u32)> {
+        let (reg, ty) = match wasm_arg {
+            ty @ (WasmValType::I32 | WasmValType::I64) => (
+                index_env.next_gpr().map(|i| regs::xreg(ARG_REG_BASE + i)),
+                ty,
+            ),
+
+            ty @ (WasmValType::F32 | WasmValType::F64) => (
+                index_env.next_fpr().map(|i| regs::freg(ARG_REG_BASE + i)),
+                ty,
+            ),
+
+            ty @ WasmValType::Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func
+                | WasmHeapTopType::Any
+                | WasmHeapTopType::Extern
+                | WasmHeapTopType::Exn => (
+                    index_env.next_gpr().map(|i| regs::xreg(ARG_REG_BASE + i)),
+                    ty,
+                ),
+                _ => bail!(CodeGenError::unsupported_wasm_type()),
+            },
+
+            _ => bail!(CodeGenError::unsupported_wasm_type()),
+        };
+
+        let ty_size = <Self as ABI>::sizeof(wasm_arg);
+        let default = || {
+            let arg = ABIOperand::stack_offset(stack_offset, *ty, ty_size as u32);
+            let slot_size = Self::stack_slot_size();
+            // Stack slots for parameters are aligned to a fixed slot size,
+            // in the case of Riscv64, 8 bytes.
+            // For the non-default calling convention, stack slots for
+            // return values are type-sized aligned.
+            // For the default calling convention, we don't type-size align,
+            // given that results on the stack must match spills generated
+            // from within the compiler, which are not type-size aligned.
+            let next_stack = if params_or_returns == ParamsOrReturns::Params {
+                align_to(stack_offset, slot_size as u32) + (slot_size as u32)
+            } else if call_conv.is_default() {
+                stack_offset + (ty_size as u32)
+            } else {
+                align_to(stack_offset, ty_size as u32) + (ty_size as u32)
+            };
+            (arg, next_stack)
+        };
+        Ok(reg.map_or_else(default, |reg| {
+            (ABIOperand::reg(reg, *ty, ty_size as u32), stack_offset)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Riscv64ABI;
+    use crate::{
+        abi::{ABI, ABIOperand},
+        isa::CallingConvention,
+        isa::reg::Reg,
+        isa::riscv64::regs,
+    };
+    use wasmtime_environ::{
+        WasmFuncType,
+        WasmValType::{self, *},
+    };
+
+    use anyhow::Result;
+
+    #[test]
+    fn xreg_abi_sig() -> Result<()> {
+        let wasm_sig = WasmFuncType::new(
+            [I32, I64, I32, I64, I32, I32, I64, I32, I64].into(),
+            [].into(),
+        );
+
+        let sig = Riscv64ABI::sig(&wasm_sig, &CallingConvention::Default)?;
+        let params = sig.params;
+
+        match_reg_arg(params.get(0).unwrap(), I32, regs::xreg(10));
+        match_reg_arg(params.get(1).unwrap(), I64, regs::xreg(11));
+        match_reg_arg(params.get(2).unwrap(), I32, regs::xreg(12));
+        match_reg_arg(params.get(3).unwrap(), I64, regs::xreg(13));
+        match_reg_arg(params.get(4).unwrap(), I32, regs::xreg(14));
+        match_reg_arg(params.get(5).unwrap(), I32, regs::xreg(15));
+        match_reg_arg(params.get(6).unwrap(), I64, regs::xreg(16));
+        match_reg_arg(params.get(7).unwrap(), I32, regs::xreg(17));
+        match_stack_arg(params.get(8).unwrap(), I64, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn freg_abi_sig() -> Result<()> {
+        let wasm_sig = WasmFuncType::new(
+            [F32, F64, F32, F64, F32, F32, F64, F32, F64].into(),
+            [].into(),
+        );
+
+        let sig = Riscv64ABI::sig(&wasm_sig, &CallingConvention::Default)?;
+        let params = sig.params;
+
+        match_reg_arg(params.get(0).unwrap(), F32, regs::freg(10));
+        match_reg_arg(params.get(1).unwrap(), F64, regs::freg(11));
+        match_reg_arg(params.get(2).unwrap(), F32, regs::freg(12));
+        match_reg_arg(params.get(3).unwrap(), F64, regs::freg(13));
+        match_reg_arg(params.get(4).unwrap(), F32, regs::freg(14));
+        match_reg_arg(params.get(5).unwrap(), F32, regs::freg(15));
+        match_reg_arg(params.get(6).unwrap(), F64, regs::freg(16));
+        match_reg_arg(params.get(7).unwrap(), F32, regs::freg(17));
+        match_stack_arg(params.get(8).unwrap(), F64, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_abi_sig() -> Result<()> {
+        let wasm_sig = WasmFuncType::new(
+            [F32, I32, I64, F64, I32, F32, F64, F32, F64].into(),
+            [].into(),
+        );
+
+        let sig = Riscv64ABI::sig(&wasm_sig, &CallingConvention::Default)?;
+        let params = sig.params;
+
+        match_reg_arg(params.get(0).unwrap(), F32, regs::freg(10));
+        match_reg_arg(params.get(1).unwrap(), I32, regs::xreg(10));
+        match_reg_arg(params.get(2).unwrap(), I64, regs::xreg(11));
+        match_reg_arg(params.get(3).unwrap(), F64, regs::freg(11));
+        match_reg_arg(params.get(4).unwrap(), I32, regs::xreg(12));
+        match_reg_arg(params.get(5).unwrap(), F32, regs::freg(12));
+        match_reg_arg(params.get(6).unwrap(), F64, regs::freg(13));
+        match_reg_arg(params.get(7).unwrap(), F32, regs::freg(14));
+        match_reg_arg(params.get(8).unwrap(), F64, regs::freg(15));
+        Ok(())
+    }
+
+    #[test]
+    fn int_abi_sig_multi_returns() -> Result<()> {
+        let wasm_sig = WasmFuncType::new(
+            [I32, I64, I32, I64, I32, I32].into(),
+            [I32, I32, I32].into(),
+        );
+
+        let sig = Riscv64ABI::sig(&wasm_sig, &CallingConvention::Default)?;
+        let params = sig.params;
+        let results = sig.results;
+
+        match_reg_arg(params.get(0).unwrap(), I32, regs::xreg(11));
+        match_reg_arg(params.get(1).unwrap(), I64, regs::xreg(12));
+        match_reg_arg(params.get(2).unwrap(), I32, regs::xreg(13));
+        match_reg_arg(params.get(3).unwrap(), I64, regs::xreg(14));
+        match_reg_arg(params.get(4).unwrap(), I32, regs::xreg(15));
+        match_reg_arg(params.get(5).unwrap(), I32, regs::xreg(16));
+
+        match_stack_arg(results.get(0).unwrap(), I32, 4);
+        match_stack_arg(results.get(1).unwrap(), I32, 0);
+        match_reg_arg(results.get(2).unwrap(), I32, regs::xreg(10));
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_abi_sig_multi_returns() -> Result<()> {
+        let wasm_sig = WasmFuncType::new(
+            [F32, I32, I64, F64, I32].into(),
+            [I32, F32, I32, F32, I64].into(),
+        );
+
+        let sig = Riscv64ABI::sig(&wasm_sig, &CallingConvention::Default)?;
+        let params = sig.params;
+        let results = sig.results;
+
+        match_reg_arg(params.get(0).unwrap(), F32, regs::freg(10));
+        match_reg_arg(params.get(1).unwrap(), I32, regs::xreg(11));
+        match_reg_arg(params.get(2).unwrap(), I64, regs::xreg(12));
+        match_reg_arg(params.get(3).unwrap(), F64, regs::freg(11));
+        match_reg_arg(params.get(4).unwrap(), I32, regs::xreg(13));
+
+        match_stack_arg(results.get(0).unwrap(), I32, 12);
+        match_stack_arg(results.get(1).unwrap(), F32, 8);
+        match_stack_arg(results.get(2).unwrap(), I32, 4);
+        match_stack_arg(results.get(3).unwrap(), F32, 0);
+        match_reg_arg(results.get(4).unwrap(), I64, regs::xreg(10));
+        Ok(())
+    }
+
+    #[track_caller]
+    fn match_reg_arg(abi_arg: &ABIOperand, expected_ty: WasmValType, expected_reg: Reg) {
+        match abi_arg {
+            &ABIOperand::Reg { reg, ty, .. } => {
+                assert_eq!(reg, expected_reg);
+                assert_eq!(ty, expected_ty);
+            }
+            stack => panic!("Expected reg argument, got {stack:?}"),
+        }
+    }
+
+    #[track_caller]
+    fn match_stack_arg(abi_arg: &ABIOperand, expected_ty: WasmValType, expected_offset: u32) {
+        match abi_arg {
+            &ABIOperand::Stack { offset, ty, .. } => {
+                assert_eq!(offset, expected_offset);
+                assert_eq!(ty, expected_ty);
+            }
+            reg => panic!("Expected reg argument, got {reg:?}"),
+        }
+    }
+}
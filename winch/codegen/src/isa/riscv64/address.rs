@@ -0,0 +1,37 @@
+//! Riscv64 addressing mode.
+
+use cranelift_codegen::isa::riscv64::inst::AMode;
+
+use crate::reg::Reg;
+
+/// Memory address representation: a base register with an arbitrary offset.
+/// Potentially gets lowered into multiple instructions during code emission
+/// depending on the offset.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Address {
+    /// Base register.
+    base: Reg,
+    /// Offset.
+    offset: i64,
+}
+
+impl Address {
+    /// Create register and arbitrary offset addressing mode.
+    pub fn offset(base: Reg, offset: i64) -> Self {
+        Self { base, offset }
+    }
+
+    /// Returns the register base and immediate offset of the given [`Address`].
+    pub fn unwrap_offset(&self) -> (Reg, i64) {
+        (self.base, self.offset)
+    }
+}
+
+// Conversions between `winch-codegen`'s addressing mode representation
+// and `cranelift-codegen`s addressing mode representation for riscv64.
+
+impl From<Address> for AMode {
+    fn from(addr: Address) -> Self {
+        AMode::RegOffset(addr.base.into(), addr.offset)
+    }
+}
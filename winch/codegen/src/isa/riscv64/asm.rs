@@ -0,0 +1,605 @@
+//! Assembler library implementation for Riscv64.
+use super::{address::Address, regs};
+use crate::CallingConvention;
+use crate::masm::{Imm, IntCmpKind, OperandSize};
+use crate::{
+    constant_pool::ConstantPool,
+    reg::{Reg, WritableReg, writable},
+};
+
+use cranelift_codegen::PatchRegion;
+use cranelift_codegen::binemit::Reloc;
+use cranelift_codegen::isa::riscv64::inst::{enc_auipc, enc_jalr, encode_i_type, encode_r_type};
+use cranelift_codegen::{
+    Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, MachInstEmitState, MachLabel,
+    Writable,
+    ir::{
+        ExternalName, MemFlags, SourceLoc, TrapCode, UserExternalNameRef, condcodes::CondCode,
+        types,
+    },
+    isa::riscv64::{
+        inst::{
+            AluOPRRI, AluOPRRR, CondBrTarget, FRM, FpuOPRR, FpuOPRRR, FpuOPWidth, Imm12, Imm20,
+            Inst, IntCC, IntegerCompare, LoadOP, StoreOP,
+            emit::{EmitInfo, EmitState},
+        },
+        settings as riscv64_settings,
+    },
+    settings,
+};
+
+impl From<IntCmpKind> for IntCC {
+    fn from(value: IntCmpKind) -> Self {
+        match value {
+            IntCmpKind::Eq => IntCC::Equal,
+            IntCmpKind::Ne => IntCC::NotEqual,
+            IntCmpKind::LtS => IntCC::SignedLessThan,
+            IntCmpKind::LtU => IntCC::UnsignedLessThan,
+            IntCmpKind::GtS => IntCC::SignedGreaterThan,
+            IntCmpKind::GtU => IntCC::UnsignedGreaterThan,
+            IntCmpKind::LeS => IntCC::SignedLessThanOrEqual,
+            IntCmpKind::LeU => IntCC::UnsignedLessThanOrEqual,
+            IntCmpKind::GeS => IntCC::SignedGreaterThanOrEqual,
+            IntCmpKind::GeU => IntCC::UnsignedGreaterThanOrEqual,
+        }
+    }
+}
+
+impl From<OperandSize> for FpuOPWidth {
+    fn from(size: OperandSize) -> Self {
+        match size {
+            OperandSize::S32 => Self::S,
+            OperandSize::S64 => Self::D,
+            s => panic!("Invalid floating-point operand size {s:?}"),
+        }
+    }
+}
+
+/// Low level assembler implementation for Riscv64.
+pub(crate) struct Assembler {
+    /// The machine instruction buffer.
+    buffer: MachBuffer<Inst>,
+    /// Constant emission information.
+    emit_info: EmitInfo,
+    /// Emission state.
+    emit_state: EmitState,
+    /// Constant pool.
+    pool: ConstantPool,
+}
+
+impl Assembler {
+    /// Create a new Riscv64 assembler.
+    pub fn new(shared_flags: settings::Flags, isa_flags: riscv64_settings::Flags) -> Self {
+        Self {
+            buffer: MachBuffer::<Inst>::new(),
+            emit_state: Default::default(),
+            emit_info: EmitInfo::new(shared_flags, isa_flags),
+            pool: ConstantPool::new(),
+        }
+    }
+}
+
+impl Assembler {
+    /// Return the emitted code.
+    pub fn finalize(mut self, loc: Option<SourceLoc>) -> MachBufferFinalized<Final> {
+        let stencil = self
+            .buffer
+            .finish(&self.pool.constants(), self.emit_state.ctrl_plane_mut());
+        stencil.apply_base_srcloc(loc.unwrap_or_default())
+    }
+
+    fn emit(&mut self, inst: Inst) {
+        self.emit_with_island(inst, Inst::worst_case_size());
+    }
+
+    fn emit_with_island(&mut self, inst: Inst, needed_space: u32) {
+        self.maybe_emit_island(needed_space);
+        inst.emit(&mut self.buffer, &self.emit_info, &mut self.emit_state);
+    }
+
+    /// Emits an island, jumping over it, if one is needed before emitting
+    /// `needed_space` bytes of code.
+    fn maybe_emit_island(&mut self, needed_space: u32) {
+        if self.buffer.island_needed(needed_space) {
+            let label = self.buffer.get_label();
+            let jmp = Inst::Jal { label };
+            jmp.emit(&mut self.buffer, &self.emit_info, &mut self.emit_state);
+            self.buffer
+                .emit_island(needed_space, self.emit_state.ctrl_plane_mut());
+            self.buffer
+                .bind_label(label, self.emit_state.ctrl_plane_mut());
+        }
+    }
+
+    /// Store a register.
+    pub fn str(&mut self, src: Reg, addr: Address, size: OperandSize, flags: MemFlags) {
+        use OperandSize::*;
+        let op = match (src.is_int(), size) {
+            (_, S8) => StoreOP::Sb,
+            (_, S16) => StoreOP::Sh,
+            (true, S32) => StoreOP::Sw,
+            (false, S32) => StoreOP::Fsw,
+            (true, S64) => StoreOP::Sd,
+            (false, S64) => StoreOP::Fsd,
+            (_, S128) => unreachable!(),
+        };
+
+        self.emit(Inst::Store {
+            to: addr.into(),
+            op,
+            flags,
+            src: src.into(),
+        });
+    }
+
+    /// Load a signed register.
+    pub fn sload(&mut self, addr: Address, rd: WritableReg, size: OperandSize, flags: MemFlags) {
+        self.ldr(addr, rd, size, true, flags);
+    }
+
+    /// Load an unsigned register.
+    pub fn uload(&mut self, addr: Address, rd: WritableReg, size: OperandSize, flags: MemFlags) {
+        self.ldr(addr, rd, size, false, flags);
+    }
+
+    /// Load address into a register.
+    fn ldr(
+        &mut self,
+        addr: Address,
+        rd: WritableReg,
+        size: OperandSize,
+        signed: bool,
+        flags: MemFlags,
+    ) {
+        use OperandSize::*;
+        let op = match (rd.to_reg().is_int(), signed, size) {
+            (true, true, S8) => LoadOP::Lb,
+            (true, false, S8) => LoadOP::Lbu,
+            (true, true, S16) => LoadOP::Lh,
+            (true, false, S16) => LoadOP::Lhu,
+            (true, true, S32) => LoadOP::Lw,
+            (true, false, S32) => LoadOP::Lwu,
+            (true, _, S64) => LoadOP::Ld,
+            (false, _, S32) => LoadOP::Flw,
+            (false, _, S64) => LoadOP::Fld,
+            (_, _, s) => panic!("Unsupported load of size {s:?}"),
+        };
+
+        self.emit(Inst::Load {
+            rd: rd.map(Into::into),
+            op,
+            flags,
+            from: addr.into(),
+        });
+    }
+
+    /// Load a constant into a register.
+    ///
+    /// 32-bit integers are zero-extended to the full register width;
+    /// floating-point constants are materialized through an integer
+    /// temporary register.
+    pub fn mov_ir(&mut self, rd: WritableReg, imm: Imm) {
+        match imm {
+            Imm::I32(v) => {
+                // Load the sign-extended value, which is cheaper to
+                // materialize, and clear the upper bits if needed.
+                self.load_constant(rd, v as i32 as i64 as u64);
+                if (v as i32) < 0 {
+                    self.zext32(rd.to_reg(), rd);
+                }
+            }
+            Imm::I64(v) => self.load_constant(rd, v),
+            Imm::F32(v) => self.fmov_from_bits(rd, v.into(), OperandSize::S32),
+            Imm::F64(v) => self.fmov_from_bits(rd, v, OperandSize::S64),
+            Imm::V128(_) => unreachable!(),
+        }
+    }
+
+    /// Load a 64-bit constant into an integer register.
+    fn load_constant(&mut self, rd: WritableReg, value: u64) {
+        for inst in Inst::load_constant_u64(rd.map(Into::into), value) {
+            self.emit(inst);
+        }
+    }
+
+    /// Move the floating-point value represented by `bits` into `rd`.
+    fn fmov_from_bits(&mut self, rd: WritableReg, bits: u64, size: OperandSize) {
+        let src = if bits == 0 {
+            regs::zero()
+        } else {
+            let tmp = regs::emit_tmp1();
+            self.load_constant(writable!(tmp), bits);
+            tmp
+        };
+        self.fpu_rr(FpuOPRR::FmvFmtX, src, rd, size, FRM::RNE);
+    }
+
+    /// Register to register move.
+    pub fn mov_rr(&mut self, rm: Reg, rd: WritableReg) {
+        self.emit(Inst::Mov {
+            rd: rd.map(Into::into),
+            rm: rm.into(),
+            ty: types::I64,
+        });
+    }
+
+    /// Floating point register to register move.
+    pub fn fmov_rr(&mut self, rm: Reg, rd: WritableReg, size: OperandSize) {
+        let ty = match size {
+            OperandSize::S32 => types::F32,
+            OperandSize::S64 => types::F64,
+            _ => unreachable!(),
+        };
+        self.emit(Inst::Mov {
+            rd: rd.map(Into::into),
+            rm: rm.into(),
+            ty,
+        });
+    }
+
+    /// Integer register to register ALU operation.
+    pub fn alu_rrr(&mut self, op: AluOPRRR, rs1: Reg, rs2: Reg, rd: WritableReg) {
+        self.emit(Inst::AluRRR {
+            alu_op: op,
+            rd: rd.map(Into::into),
+            rs1: rs1.into(),
+            rs2: rs2.into(),
+        });
+    }
+
+    /// Integer register and immediate ALU operation.
+    pub fn alu_rri(&mut self, op: AluOPRRI, rs: Reg, imm12: Imm12, rd: WritableReg) {
+        self.emit(Inst::AluRRImm12 {
+            alu_op: op,
+            rd: rd.map(Into::into),
+            rs: rs.into(),
+            imm12,
+        });
+    }
+
+    /// Floating point register to register ALU operation.
+    pub fn fpu_rrr(
+        &mut self,
+        op: FpuOPRRR,
+        rs1: Reg,
+        rs2: Reg,
+        rd: WritableReg,
+        size: OperandSize,
+    ) {
+        // For the sign injection, min/max and comparison instructions the
+        // rounding mode field is part of the opcode.
+        let frm = match op {
+            FpuOPRRR::Fsgnjn | FpuOPRRR::Fmax | FpuOPRRR::Flt => FRM::RTZ,
+            FpuOPRRR::Fsgnjx | FpuOPRRR::Feq => FRM::RDN,
+            _ => FRM::RNE,
+        };
+        self.emit(Inst::FpuRRR {
+            alu_op: op,
+            width: size.into(),
+            frm,
+            rd: rd.map(Into::into),
+            rs1: rs1.into(),
+            rs2: rs2.into(),
+        });
+    }
+
+    /// Floating point unary operation, which includes conversions and moves
+    /// between register classes. `size` is the size of the floating-point
+    /// operand.
+    pub fn fpu_rr(&mut self, op: FpuOPRR, rs: Reg, rd: WritableReg, size: OperandSize, frm: FRM) {
+        self.emit(Inst::FpuRR {
+            alu_op: op,
+            width: size.into(),
+            frm,
+            rd: rd.map(Into::into),
+            rs: rs.into(),
+        });
+    }
+
+    /// Integer extension.
+    pub fn extend(&mut self, rn: Reg, rd: WritableReg, signed: bool, from_bits: u8) {
+        self.emit(Inst::Extend {
+            rd: rd.map(Into::into),
+            rn: rn.into(),
+            signed,
+            from_bits,
+            to_bits: 64,
+        });
+    }
+
+    /// Zero-extend the lower 32 bits of `rn` into `rd`.
+    pub fn zext32(&mut self, rn: Reg, rd: WritableReg) {
+        self.extend(rn, rd, false, 32);
+    }
+
+    /// Sign-extend the lower 32 bits of `rn` into `rd`.
+    pub fn sext32(&mut self, rn: Reg, rd: WritableReg) {
+        self.alu_rri(AluOPRRI::Addiw, rn, Imm12::from_i16(0), rd);
+    }
+
+    /// Return instruction.
+    pub fn ret(&mut self) {
+        self.emit(Inst::Ret);
+    }
+
+    /// An unconditional branch.
+    pub fn jmp(&mut self, target: MachLabel) {
+        self.emit(Inst::Jal { label: target });
+    }
+
+    /// A conditional branch.
+    pub fn jmp_if(&mut self, cmp: IntegerCompare, taken: MachLabel) {
+        self.emit(Inst::CondBr {
+            taken: CondBrTarget::Label(taken),
+            not_taken: CondBrTarget::Fallthrough,
+            kind: cmp,
+        });
+    }
+
+    /// Emits a jump table sequence, which also performs the bounds check
+    /// against the number of `targets`, jumping to `default` if the index is
+    /// out of bounds.
+    pub fn jmp_table(
+        &mut self,
+        targets: &[MachLabel],
+        default: MachLabel,
+        index: Reg,
+        tmp1: Reg,
+        tmp2: Reg,
+    ) {
+        let mut labels = Vec::with_capacity(targets.len() + 1);
+        labels.push(default);
+        labels.extend_from_slice(targets);
+        // The instruction takes care of emitting an island, if needed, for
+        // the whole sequence.
+        self.emit_with_island(
+            Inst::BrTable {
+                index: index.into(),
+                tmp1: Writable::from_reg(tmp1.into()),
+                tmp2: Writable::from_reg(tmp2.into()),
+                targets: labels,
+            },
+            0,
+        );
+    }
+
+    /// If the condition is true, moves `rs` into `rd`.
+    pub fn csel(&mut self, cmp: IntegerCompare, rs: Reg, rd: WritableReg) {
+        let skip = self.buffer.get_label();
+        self.jmp_if(
+            IntegerCompare {
+                kind: cmp.kind.complement(),
+                ..cmp
+            },
+            skip,
+        );
+        // Moving all 64 bits of a floating-point register is valid for
+        // both single and double precision values.
+        let ty = if rs.is_int() { types::I64 } else { types::F64 };
+        self.emit(Inst::Mov {
+            rd: rd.map(Into::into),
+            rm: rs.into(),
+            ty,
+        });
+        self.buffer
+            .bind_label(skip, self.emit_state.ctrl_plane_mut());
+    }
+
+    /// Permanently Undefined.
+    pub fn udf(&mut self, code: TrapCode) {
+        self.emit(Inst::Udf { trap_code: code });
+    }
+
+    /// Conditional trap.
+    pub fn trapif(&mut self, cmp: IntegerCompare, code: TrapCode) {
+        self.emit(Inst::TrapIf {
+            rs1: cmp.rs1,
+            rs2: cmp.rs2,
+            cc: cmp.kind,
+            trap_code: code,
+        });
+    }
+
+    /// Count leading or trailing zeros of `rs` into `sum`, through a loop
+    /// over the bits of the operand. Used when the Zbb extension is not
+    /// available.
+    pub fn cltz(
+        &mut self,
+        leading: bool,
+        rs: Reg,
+        sum: WritableReg,
+        step: WritableReg,
+        tmp: WritableReg,
+        size: OperandSize,
+    ) {
+        self.emit(Inst::Cltz {
+            leading,
+            sum: sum.map(Into::into),
+            step: step.map(Into::into),
+            tmp: tmp.map(Into::into),
+            rs: rs.into(),
+            ty: Self::int_ty(size),
+        });
+    }
+
+    /// Count the number of set bits of `rs` into `sum`, through a loop over
+    /// the bits of the operand. Used when the Zbb extension is not
+    /// available.
+    pub fn popcnt(
+        &mut self,
+        rs: Reg,
+        sum: WritableReg,
+        step: WritableReg,
+        tmp: WritableReg,
+        size: OperandSize,
+    ) {
+        self.emit(Inst::Popcnt {
+            sum: sum.map(Into::into),
+            step: step.map(Into::into),
+            tmp: tmp.map(Into::into),
+            rs: rs.into(),
+            ty: Self::int_ty(size),
+        });
+    }
+
+    fn int_ty(size: OperandSize) -> types::Type {
+        match size {
+            OperandSize::S32 => types::I32,
+            OperandSize::S64 => types::I64,
+            s => panic!("Invalid integer operand size {s:?}"),
+        }
+    }
+
+    /// Get a label from the underlying machine code buffer.
+    pub fn get_label(&mut self) -> MachLabel {
+        self.buffer.get_label()
+    }
+
+    /// Get a mutable reference to underlying
+    /// machine buffer.
+    pub fn buffer_mut(&mut self) -> &mut MachBuffer<Inst> {
+        &mut self.buffer
+    }
+
+    /// Get a reference to the underlying machine buffer.
+    pub fn buffer(&self) -> &MachBuffer<Inst> {
+        &self.buffer
+    }
+
+    /// Emit a direct call to a function defined locally and
+    /// referenced to by `name`.
+    pub fn call_with_name(&mut self, name: UserExternalNameRef, call_conv: CallingConvention) {
+        self.emit(Inst::Call {
+            info: Box::new(cranelift_codegen::CallInfo::empty(
+                ExternalName::user(name),
+                call_conv.into(),
+            )),
+        })
+    }
+
+    /// Emit an indirect call to a function whose address is
+    /// stored the `callee` register.
+    pub fn call_with_reg(&mut self, callee: Reg, call_conv: CallingConvention) {
+        self.emit(Inst::CallInd {
+            info: Box::new(cranelift_codegen::CallInfo::empty(
+                callee.into(),
+                call_conv.into(),
+            )),
+        })
+    }
+
+    /// Emit a jump to a locally defined function through an index, used to
+    /// perform tail calls.
+    pub fn jmp_with_name(&mut self, name: UserExternalNameRef) {
+        self.maybe_emit_island(8);
+        // `auipc t6, 0; jalr zero, 0(t6)`, patched through the same
+        // relocation used by direct calls.
+        let tmp = writable!(regs::emit_tmp1()).map(Into::into);
+        self.buffer
+            .add_reloc(Reloc::RiscvCallPlt, &ExternalName::user(name), 0);
+        self.buffer.put4(enc_auipc(tmp, Imm20::from_i32(0)));
+        self.buffer.put4(enc_jalr(
+            writable!(regs::zero()).map(Into::into),
+            tmp.to_reg(),
+            Imm12::from_i16(0),
+        ));
+    }
+
+    /// Emit a jump to a function whose address is stored in the `callee`
+    /// register, used to perform tail calls.
+    pub fn jmp_with_reg(&mut self, callee: Reg) {
+        self.emit(Inst::Jalr {
+            rd: writable!(regs::zero()).map(Into::into),
+            base: callee.into(),
+            offset: Imm12::from_i16(0),
+        });
+    }
+}
+
+/// Captures the region in a MachBuffer where an add-with-immediate instruction would be emitted,
+/// but the immediate is not yet known.
+pub(crate) struct PatchableAddToReg {
+    /// The region to be patched in the [`MachBuffer`]. It contains
+    /// space for 3 32-bit instructions, i.e. it's 12 bytes long.
+    region: PatchRegion,
+
+    // The destination register for the add instruction.
+    reg: Writable<Reg>,
+
+    // The temporary register used to hold the immediate value.
+    tmp: Writable<Reg>,
+}
+
+impl PatchableAddToReg {
+    /// Create a new [`PatchableAddToReg`] by capturing a region in the output
+    /// buffer containing an instruction sequence that loads an immediate into a
+    /// register `tmp`, then adds it to a register `reg`. The [`MachBuffer`]
+    /// will have that instruction sequence written to the region, though the
+    /// immediate loaded into `tmp` will be `0` until the `::finalize` method is
+    /// called.
+    pub(crate) fn new(reg: Writable<Reg>, tmp: Writable<Reg>, buf: &mut MachBuffer<Inst>) -> Self {
+        let insns = Self::add_immediate_instruction_sequence(reg, tmp, 0);
+        let open = buf.start_patchable();
+        buf.put_data(&insns);
+        let region = buf.end_patchable(open);
+
+        Self { region, reg, tmp }
+    }
+
+    fn add_immediate_instruction_sequence(
+        reg: Writable<Reg>,
+        tmp: Writable<Reg>,
+        imm: i32,
+    ) -> [u8; 12] {
+        // Split the immediate into the upper 20 bits, loaded with `lui`, and
+        // the lower 12 bits, added with `addi`; since the latter is
+        // sign-extended, the upper part is rounded up accordingly.
+        let imm_hi = (imm.wrapping_add(0x800)) >> 12;
+        let imm_lo = imm.wrapping_sub(imm_hi << 12);
+        assert!(imm_hi < (1 << 19), "Immediate {imm} doesn't fit in 32 bits");
+
+        // This is "lui tmp, imm_hi". The instruction is not exposed by the
+        // encoder, so we just repeat the opcode here.
+        let lui_insn = 0b0110111
+            | ((tmp.to_reg().hw_enc() as u32) << 7)
+            | (Imm20::from_i32(imm_hi).bits() << 12);
+
+        let tmp = tmp.map(Into::into);
+        let rd = reg.map(Into::into);
+
+        // This is "addi tmp, tmp, imm_lo".
+        let addi_insn = encode_i_type(
+            AluOPRRI::Addi.op_code(),
+            tmp,
+            AluOPRRI::Addi.funct3(),
+            tmp.to_reg(),
+            Imm12::maybe_from_i64(imm_lo.into()).unwrap(),
+        );
+
+        // This is "add rd, rd, tmp".
+        let add_insn = encode_r_type(
+            AluOPRRR::Add.op_code(),
+            rd,
+            AluOPRRR::Add.funct3(),
+            rd.to_reg(),
+            tmp.to_reg(),
+            AluOPRRR::Add.funct7(),
+        );
+
+        let mut buf = [0u8; 12];
+        buf[0..4].copy_from_slice(&lui_insn.to_le_bytes());
+        buf[4..8].copy_from_slice(&addi_insn.to_le_bytes());
+        buf[8..12].copy_from_slice(&add_insn.to_le_bytes());
+        buf
+    }
+
+    /// Patch the [`MachBuffer`] with the known constant to be added to the register. The final
+    /// value is passed in as an i32, but the instruction encoding is fixed when
+    /// [`PatchableAddToReg::new`] is called.
+    pub(crate) fn finalize(self, val: i32, buffer: &mut MachBuffer<Inst>) {
+        let insns = Self::add_immediate_instruction_sequence(self.reg, self.tmp, val);
+        let slice = self.region.patch(buffer);
+        assert_eq!(slice.len(), insns.len());
+        slice.copy_from_slice(&insns);
+    }
+}
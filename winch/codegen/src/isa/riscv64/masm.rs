@@ -0,0 +1,1978 @@
+use super::{
+    RegAlloc,
+    abi::Riscv64ABI,
+    address::Address,
+    asm::{Assembler, PatchableAddToReg},
+    regs::{self, scratch_fpr_bitset, scratch_gpr_bitset},
+};
+use crate::{
+    abi::{self, align_to, calculate_frame_adjustment, local::LocalSlot, vmctx},
+    codegen::{CodeGenContext, CodeGenError, Emission, FuncEnv, ptr_type_from_ptr_size},
+    isa::{
+        CallingConvention,
+        reg::{Reg, WritableReg, writable},
+    },
+    masm::{
+        CalleeKind, DivKind, Extend, ExtendKind, ExtractLaneKind, FloatCmpKind, FloatScratch,
+        Imm as I, IntCmpKind, IntScratch, LoadKind, MacroAssembler as Masm, MulWideKind,
+        OperandSize, RegImm, RemKind, ReplaceLaneKind, RmwOp, RoundingMode, SPOffset, Scratch,
+        ScratchType, ShiftKind, SplatKind, StackSlot, StoreKind, TRUSTED_FLAGS, TrapCode,
+        TruncKind, UNTRUSTED_FLAGS, V128AbsKind, V128AddKind, V128ConvertKind, V128ExtAddKind,
+        V128ExtMulKind, V128ExtendKind, V128MaxKind, V128MinKind, V128MulKind, V128NarrowKind,
+        V128NegKind, V128SubKind, V128TruncKind, VectorCompareKind, VectorEqualityKind, Zero,
+    },
+    stack::TypedReg,
+};
+use anyhow::{Result, anyhow, bail};
+use cranelift_codegen::{
+    Final, MachBufferFinalized, MachLabel,
+    binemit::CodeOffset,
+    ir::{MemFlags, RelSourceLoc, SourceLoc},
+    isa::riscv64::{
+        inst::{AluOPRRI, AluOPRRR, FRM, FpuOPRR, FpuOPRRR, Imm12, IntCC, IntegerCompare},
+        settings as riscv64_settings,
+    },
+    settings,
+};
+use regalloc2::RegClass;
+use wasmtime_environ::{PtrSize, WasmValType};
+use wasmtime_math::{f32_cvt_to_int_bounds, f64_cvt_to_int_bounds};
+
+/// The operands of an integer comparison.
+///
+/// RISC-V has no flags register: comparisons are performed by the
+/// instructions consuming their result, e.g. conditional branches. The
+/// operands given to [`Masm::cmp`] are therefore recorded, and the comparison
+/// is only emitted when the instruction consuming it is.
+#[derive(Copy, Clone, Debug)]
+struct Comparison {
+    /// The left-hand-side of the comparison.
+    lhs: Reg,
+    /// The right-hand-side of the comparison.
+    rhs: RegImm,
+    /// The size of the operands.
+    size: OperandSize,
+}
+
+/// Riscv64 MacroAssembler.
+pub(crate) struct MacroAssembler {
+    /// This value represents the maximum stack size seen while compiling the
+    /// function. While the function is still being compiled its value will not
+    /// be valid (the stack will grow and shrink as space is reserved and freed
+    /// during compilation), but once all instructions have been seen this value
+    /// will be the maximum stack usage seen.
+    sp_max: u32,
+
+    /// Add-with-immediate patchable instruction sequence used to add the
+    /// constant stack max to a register.
+    stack_max_use_add: Option<PatchableAddToReg>,
+
+    /// Low level assembler.
+    asm: Assembler,
+    /// ISA flags.
+    flags: riscv64_settings::Flags,
+    /// Stack pointer offset.
+    sp_offset: u32,
+    /// The target pointer size.
+    ptr_size: OperandSize,
+    /// Scratch register scope.
+    scratch_scope: RegAlloc,
+    /// The last comparison, to be consumed by the next conditional
+    /// instruction.
+    comparison: Option<Comparison>,
+}
+
+impl MacroAssembler {
+    /// Create a Riscv64 MacroAssembler.
+    pub fn new(
+        ptr_size: impl PtrSize,
+        shared_flags: settings::Flags,
+        isa_flags: riscv64_settings::Flags,
+    ) -> Result<Self> {
+        Ok(Self {
+            sp_max: 0,
+            stack_max_use_add: None,
+            asm: Assembler::new(shared_flags, isa_flags.clone()),
+            flags: isa_flags,
+            sp_offset: 0u32,
+            ptr_size: ptr_type_from_ptr_size(ptr_size.size()).try_into()?,
+            scratch_scope: RegAlloc::from(scratch_gpr_bitset(), scratch_fpr_bitset()),
+            comparison: None,
+        })
+    }
+
+    /// Add the maximum stack used to a register, recording an obligation to update the
+    /// add-with-immediate instruction emitted to use the real stack max when the masm is being
+    /// finalized.
+    fn add_stack_max(&mut self, reg: WritableReg, tmp: WritableReg) {
+        assert!(self.stack_max_use_add.is_none());
+        let patch = PatchableAddToReg::new(reg, tmp, self.asm.buffer_mut());
+        self.stack_max_use_add.replace(patch);
+    }
+}
+
+impl Masm for MacroAssembler {
+    type Address = Address;
+    type Ptr = u8;
+    type ABI = Riscv64ABI;
+
+    fn frame_setup(&mut self) -> Result<()> {
+        let sp = regs::sp();
+        let fp = regs::fp();
+
+        self.asm
+            .alu_rri(AluOPRRI::Addi, sp, Imm12::from_i16(-16), writable!(sp));
+        self.asm.str(
+            regs::ra(),
+            Address::offset(sp, 8),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+        self.asm
+            .str(fp, Address::offset(sp, 0), OperandSize::S64, TRUSTED_FLAGS);
+        self.asm.mov_rr(sp, writable!(fp));
+        Ok(())
+    }
+
+    fn check_stack(&mut self, vmctx: Reg) -> Result<()> {
+        let ptr_size_u8: u8 = self.ptr_size.bytes().try_into().unwrap();
+
+        // Similar to aarch64, the PatchableAddToReg construct is a
+        // 3-instruction sequence that loads the immediate into a second
+        // scratch register before adding it to the register holding the
+        // limit.
+        self.with_scratch::<IntScratch, _>(|masm, scratch_stk_limit| {
+            masm.with_scratch::<IntScratch, _>(|masm, scratch_tmp| {
+                masm.load_ptr(
+                    masm.address_at_reg(vmctx, ptr_size_u8.vmcontext_store_context().into())?,
+                    scratch_stk_limit.writable(),
+                )?;
+
+                masm.load_ptr(
+                    Address::offset(
+                        scratch_stk_limit.inner(),
+                        ptr_size_u8.vmstore_context_stack_limit().into(),
+                    ),
+                    scratch_stk_limit.writable(),
+                )?;
+
+                masm.add_stack_max(scratch_stk_limit.writable(), scratch_tmp.writable());
+
+                masm.asm.trapif(
+                    IntegerCompare {
+                        kind: IntCC::UnsignedLessThan,
+                        rs1: regs::sp().into(),
+                        rs2: scratch_stk_limit.inner().into(),
+                    },
+                    TrapCode::STACK_OVERFLOW,
+                );
+
+                Ok(())
+            })
+        })
+    }
+
+    fn frame_restore(&mut self) -> Result<()> {
+        debug_assert_eq!(self.sp_offset, 0);
+        self.pop_frame();
+        self.asm.ret();
+        Ok(())
+    }
+
+    fn return_call(&mut self, callee: CalleeKind) -> Result<()> {
+        // Point the stack pointer to the saved frame pointer and return
+        // address, regardless of the current stack pointer offset.
+        self.asm.mov_rr(regs::fp(), writable!(regs::sp()));
+        self.pop_frame();
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.jmp_with_reg(reg),
+            CalleeKind::Direct(idx) => self.asm.jmp_with_name(idx),
+        };
+        Ok(())
+    }
+
+    fn reserve_stack(&mut self, bytes: u32) -> Result<()> {
+        if bytes == 0 {
+            return Ok(());
+        }
+
+        self.adjust_sp(-i64::from(bytes));
+        self.increment_sp(bytes);
+        Ok(())
+    }
+
+    fn free_stack(&mut self, bytes: u32) -> Result<()> {
+        if bytes == 0 {
+            return Ok(());
+        }
+
+        self.adjust_sp(i64::from(bytes));
+        self.decrement_sp(bytes);
+        Ok(())
+    }
+
+    fn reset_stack_pointer(&mut self, offset: SPOffset) -> Result<()> {
+        self.sp_offset = offset.as_u32();
+        Ok(())
+    }
+
+    fn landing_pad(&mut self, _sp_offset: SPOffset, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn local_address(&mut self, local: &LocalSlot) -> Result<Address> {
+        let (reg, offset) = local
+            .addressed_from_sp()
+            .then(|| {
+                let offset = self.sp_offset.checked_sub(local.offset).expect(&format!(
+                    "Invalid local offset = {}; sp offset = {}",
+                    local.offset, self.sp_offset
+                ));
+                (regs::sp(), offset)
+            })
+            .unwrap_or((regs::fp(), local.offset));
+
+        Ok(Address::offset(reg, offset as i64))
+    }
+
+    fn address_from_sp(&self, offset: SPOffset) -> Result<Self::Address> {
+        Ok(Address::offset(
+            regs::sp(),
+            (self.sp_offset - offset.as_u32()) as i64,
+        ))
+    }
+
+    fn address_at_sp(&self, offset: SPOffset) -> Result<Self::Address> {
+        Ok(Address::offset(regs::sp(), offset.as_u32() as i64))
+    }
+
+    fn address_at_vmctx(&self, offset: u32) -> Result<Self::Address> {
+        Ok(Address::offset(vmctx!(Self), offset as i64))
+    }
+
+    fn store_ptr(&mut self, src: Reg, dst: Self::Address) -> Result<()> {
+        self.store(src.into(), dst, self.ptr_size)
+    }
+
+    fn store(&mut self, src: RegImm, dst: Address, size: OperandSize) -> Result<()> {
+        match src {
+            RegImm::Imm(v) => {
+                match v {
+                    I::I32(_) | I::I64(_) => {
+                        self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                            masm.asm.mov_ir(scratch.writable(), v);
+                            masm.asm.str(scratch.inner(), dst, size, TRUSTED_FLAGS);
+                        });
+                    }
+                    imm @ (I::F32(_) | I::F64(_)) => {
+                        self.with_scratch::<FloatScratch, _>(|masm, scratch| {
+                            masm.asm.mov_ir(scratch.writable(), imm);
+                            masm.asm.str(scratch.inner(), dst, size, TRUSTED_FLAGS);
+                        });
+                    }
+                    _ => bail!(CodeGenError::unsupported_wasm_type()),
+                };
+                Ok(())
+            }
+            RegImm::Reg(r) => {
+                self.asm.str(r, dst, size, TRUSTED_FLAGS);
+                Ok(())
+            }
+        }
+    }
+
+    fn wasm_store(&mut self, src: Reg, dst: Self::Address, op_kind: StoreKind) -> Result<()> {
+        match op_kind {
+            StoreKind::Operand(size) => {
+                self.asm.str(src, dst, size, UNTRUSTED_FLAGS);
+                Ok(())
+            }
+            StoreKind::Atomic(_size) => {
+                Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+            }
+            StoreKind::VectorLane(_selector) => {
+                Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+            }
+        }
+    }
+
+    fn with_scratch<T: ScratchType, R>(&mut self, f: impl FnOnce(&mut Self, Scratch) -> R) -> R {
+        let r = self
+            .scratch_scope
+            .reg_for_class(T::reg_class(), &mut |_| Ok(()))
+            .expect("Scratch register to be available");
+
+        let ret = f(self, Scratch::new(r));
+
+        self.scratch_scope.free(r);
+        ret
+    }
+
+    fn call(
+        &mut self,
+        stack_args_size: u32,
+        exception_handler: Option<MachLabel>,
+        mut load_callee: impl FnMut(&mut Self) -> Result<(CalleeKind, CallingConvention)>,
+    ) -> Result<u32> {
+        // Cranelift's riscv64 backend doesn't record exception handlers at
+        // call sites.
+        if exception_handler.is_some() {
+            bail!(CodeGenError::unimplemented_masm_instruction());
+        }
+        let alignment: u32 = <Self::ABI as abi::ABI>::call_stack_align().into();
+        let addend: u32 = <Self::ABI as abi::ABI>::initial_frame_size().into();
+        let delta = calculate_frame_adjustment(self.sp_offset()?.as_u32(), addend, alignment);
+        let aligned_args_size = align_to(stack_args_size, alignment);
+        let total_stack = delta + aligned_args_size;
+        self.reserve_stack(total_stack)?;
+        let (callee, call_conv) = load_callee(self)?;
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.call_with_reg(reg, call_conv),
+            CalleeKind::Direct(idx) => self.asm.call_with_name(idx, call_conv),
+        }
+
+        Ok(total_stack)
+    }
+
+    fn load(&mut self, src: Address, dst: WritableReg, size: OperandSize) -> Result<()> {
+        self.asm.uload(src, dst, size, TRUSTED_FLAGS);
+        Ok(())
+    }
+
+    fn load_ptr(&mut self, src: Self::Address, dst: WritableReg) -> Result<()> {
+        self.load(src, dst, self.ptr_size)
+    }
+
+    fn wasm_load(&mut self, src: Self::Address, dst: WritableReg, kind: LoadKind) -> Result<()> {
+        let size = kind.derive_operand_size();
+        match &kind {
+            LoadKind::Operand(_) => {
+                if size == OperandSize::S128 {
+                    bail!(CodeGenError::UnimplementedWasmLoadKind)
+                } else {
+                    Ok(self.asm.uload(src, dst, size, UNTRUSTED_FLAGS))
+                }
+            }
+            LoadKind::Splat(_) => bail!(CodeGenError::UnimplementedWasmLoadKind),
+            LoadKind::ScalarExtend(extend_kind) => {
+                if extend_kind.signed() {
+                    self.asm.sload(src, dst, size, UNTRUSTED_FLAGS);
+                    // Signed loads extend to 64 bits; 32-bit integers are
+                    // expected to be zero-extended instead.
+                    if extend_kind.to_bits() == 32 {
+                        self.asm.zext32(dst.to_reg(), dst);
+                    }
+                } else {
+                    self.asm.uload(src, dst, size, UNTRUSTED_FLAGS);
+                }
+
+                Ok(())
+            }
+            LoadKind::VectorExtend(_vector_extend_kind) => {
+                bail!(CodeGenError::UnimplementedWasmLoadKind)
+            }
+            LoadKind::VectorLane(_selector) => {
+                bail!(CodeGenError::unimplemented_masm_instruction())
+            }
+            LoadKind::Atomic(_, _) => bail!(CodeGenError::unimplemented_masm_instruction()),
+            LoadKind::VectorZero(_size) => {
+                bail!(CodeGenError::UnimplementedWasmLoadKind)
+            }
+        }
+    }
+
+    fn compute_addr(
+        &mut self,
+        src: Self::Address,
+        dst: WritableReg,
+        size: OperandSize,
+    ) -> Result<()> {
+        let (base, offset) = src.unwrap_offset();
+        self.add(dst, base, RegImm::i64(offset), size)
+    }
+
+    fn pop(&mut self, dst: WritableReg, size: OperandSize) -> Result<()> {
+        let addr = self.address_from_sp(SPOffset::from_u32(self.sp_offset))?;
+        self.asm.uload(addr, dst, size, TRUSTED_FLAGS);
+        self.free_stack(size.bytes())
+    }
+
+    fn sp_offset(&self) -> Result<SPOffset> {
+        Ok(SPOffset::from_u32(self.sp_offset))
+    }
+
+    fn finalize(mut self, base: Option<SourceLoc>) -> Result<MachBufferFinalized<Final>> {
+        if let Some(patch) = self.stack_max_use_add {
+            patch.finalize(i32::try_from(self.sp_max).unwrap(), self.asm.buffer_mut());
+        }
+
+        Ok(self.asm.finalize(base))
+    }
+
+    fn mov(&mut self, dst: WritableReg, src: RegImm, size: OperandSize) -> Result<()> {
+        match (src, dst) {
+            (RegImm::Imm(v), _) => match v {
+                I::I32(_) | I::I64(_) | I::F32(_) | I::F64(_) => {
+                    self.asm.mov_ir(dst, v);
+                    Ok(())
+                }
+                I::V128(_) => bail!(CodeGenError::unsupported_imm()),
+            },
+            (RegImm::Reg(rs), rd) => match (rs.class(), rd.to_reg().class()) {
+                (RegClass::Int, RegClass::Int) => {
+                    // Like on other targets, 32-bit moves clear the upper
+                    // bits of the destination.
+                    if size == OperandSize::S32 {
+                        self.asm.zext32(rs, rd);
+                    } else {
+                        self.asm.mov_rr(rs, rd);
+                    }
+                    Ok(())
+                }
+                (RegClass::Float, RegClass::Float) => Ok(self.asm.fmov_rr(rs, rd, size)),
+                (RegClass::Int, RegClass::Float) => {
+                    Ok(self.asm.fpu_rr(FpuOPRR::FmvFmtX, rs, rd, size, FRM::RNE))
+                }
+                _ => bail!(CodeGenError::invalid_operand_combination()),
+            },
+        }
+    }
+
+    fn cmov(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        cc: IntCmpKind,
+        _size: OperandSize,
+    ) -> Result<()> {
+        if src.class() != dst.to_reg().class() {
+            bail!(CodeGenError::invalid_operand_combination());
+        }
+        let comparison = self.take_comparison()?;
+        self.with_int_compare(cc, comparison, |masm, lhs, rhs| {
+            masm.asm.csel(int_compare(cc, lhs, rhs), src, dst)
+        });
+        Ok(())
+    }
+
+    fn add(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, size: OperandSize) -> Result<()> {
+        match rhs {
+            RegImm::Imm(v) => {
+                self.alu_ri(AluOPRRR::Add, Some(AluOPRRI::Addi), dst, lhs, imm_to_i64(v))
+            }
+            RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::Add, lhs, rs2, dst),
+        }
+        self.zext_if_s32(dst, size);
+        Ok(())
+    }
+
+    fn checked_uadd(
+        &mut self,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: RegImm,
+        size: OperandSize,
+        trap: TrapCode,
+    ) -> Result<()> {
+        match size {
+            OperandSize::S32 => {
+                // Both operands are zero-extended, so the addition overflows
+                // if any of the upper 32 bits of the 64-bit result is set.
+                match rhs {
+                    RegImm::Imm(v) => self.alu_ri(
+                        AluOPRRR::Add,
+                        Some(AluOPRRI::Addi),
+                        dst,
+                        lhs,
+                        (v.unwrap_as_u64() as u32).into(),
+                    ),
+                    RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::Add, lhs, rs2, dst),
+                }
+                self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                    masm.asm.alu_rri(
+                        AluOPRRI::Srli,
+                        dst.to_reg(),
+                        Imm12::from_i16(32),
+                        scratch.writable(),
+                    );
+                    masm.asm.trapif(
+                        IntegerCompare {
+                            kind: IntCC::NotEqual,
+                            rs1: scratch.inner().into(),
+                            rs2: regs::zero().into(),
+                        },
+                        trap,
+                    );
+                });
+            }
+            OperandSize::S64 => {
+                // The addition overflows if the result is less than any of
+                // the operands.
+                self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                    match rhs {
+                        RegImm::Imm(v) => masm.alu_ri(
+                            AluOPRRR::Add,
+                            Some(AluOPRRI::Addi),
+                            scratch.writable(),
+                            lhs,
+                            v.unwrap_as_u64() as i64,
+                        ),
+                        RegImm::Reg(rs2) => {
+                            masm.asm
+                                .alu_rrr(AluOPRRR::Add, lhs, rs2, scratch.writable())
+                        }
+                    }
+                    masm.asm.trapif(
+                        IntegerCompare {
+                            kind: IntCC::UnsignedLessThan,
+                            rs1: scratch.inner().into(),
+                            rs2: lhs.into(),
+                        },
+                        trap,
+                    );
+                    masm.asm.mov_rr(scratch.inner(), dst);
+                });
+            }
+            _ => bail!(CodeGenError::unexpected_operand_size()),
+        }
+        Ok(())
+    }
+
+    fn sub(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, size: OperandSize) -> Result<()> {
+        match rhs {
+            RegImm::Imm(v) => {
+                let imm = imm_to_i64(v);
+                match imm.checked_neg().and_then(Imm12::maybe_from_i64) {
+                    Some(imm12) => self.asm.alu_rri(AluOPRRI::Addi, lhs, imm12, dst),
+                    None => self.alu_ri(AluOPRRR::Sub, None, dst, lhs, imm),
+                }
+            }
+            RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::Sub, lhs, rs2, dst),
+        }
+        self.zext_if_s32(dst, size);
+        Ok(())
+    }
+
+    fn mul(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, size: OperandSize) -> Result<()> {
+        match rhs {
+            RegImm::Imm(v) => self.alu_ri(AluOPRRR::Mul, None, dst, lhs, imm_to_i64(v)),
+            RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::Mul, lhs, rs2, dst),
+        }
+        self.zext_if_s32(dst, size);
+        Ok(())
+    }
+
+    fn float_add(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, size: OperandSize) -> Result<()> {
+        self.asm.fpu_rrr(FpuOPRRR::Fadd, lhs, rhs, dst, size);
+        Ok(())
+    }
+
+    fn float_sub(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, size: OperandSize) -> Result<()> {
+        self.asm.fpu_rrr(FpuOPRRR::Fsub, lhs, rhs, dst, size);
+        Ok(())
+    }
+
+    fn float_mul(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, size: OperandSize) -> Result<()> {
+        self.asm.fpu_rrr(FpuOPRRR::Fmul, lhs, rhs, dst, size);
+        Ok(())
+    }
+
+    fn float_div(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, size: OperandSize) -> Result<()> {
+        self.asm.fpu_rrr(FpuOPRRR::Fdiv, lhs, rhs, dst, size);
+        Ok(())
+    }
+
+    fn float_min(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, size: OperandSize) -> Result<()> {
+        self.float_min_max(FpuOPRRR::Fmin, dst, lhs, rhs, size);
+        Ok(())
+    }
+
+    fn float_max(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, size: OperandSize) -> Result<()> {
+        self.float_min_max(FpuOPRRR::Fmax, dst, lhs, rhs, size);
+        Ok(())
+    }
+
+    fn float_copysign(
+        &mut self,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        size: OperandSize,
+    ) -> Result<()> {
+        self.asm.fpu_rrr(FpuOPRRR::Fsgnj, lhs, rhs, dst, size);
+        Ok(())
+    }
+
+    fn float_neg(&mut self, dst: WritableReg, size: OperandSize) -> Result<()> {
+        self.asm
+            .fpu_rrr(FpuOPRRR::Fsgnjn, dst.to_reg(), dst.to_reg(), dst, size);
+        Ok(())
+    }
+
+    fn float_abs(&mut self, dst: WritableReg, size: OperandSize) -> Result<()> {
+        self.asm
+            .fpu_rrr(FpuOPRRR::Fsgnjx, dst.to_reg(), dst.to_reg(), dst, size);
+        Ok(())
+    }
+
+    fn float_round<
+        F: FnMut(&mut FuncEnv<Self::Ptr>, &mut CodeGenContext<Emission>, &mut Self) -> Result<()>,
+    >(
+        &mut self,
+        mode: RoundingMode,
+        env: &mut FuncEnv<Self::Ptr>,
+        context: &mut CodeGenContext<Emission>,
+        size: OperandSize,
+        mut fallback: F,
+    ) -> Result<()> {
+        if self.flags.has_zfa() {
+            let frm = match mode {
+                RoundingMode::Nearest => FRM::RNE,
+                RoundingMode::Up => FRM::RUP,
+                RoundingMode::Down => FRM::RDN,
+                RoundingMode::Zero => FRM::RTZ,
+            };
+            let src = context.pop_to_reg(self, None)?;
+            self.asm.fpu_rr(
+                FpuOPRR::Fround,
+                src.into(),
+                writable!(src.into()),
+                size,
+                frm,
+            );
+            context.stack.push(src.into());
+            Ok(())
+        } else {
+            fallback(env, context, self)
+        }
+    }
+
+    fn float_sqrt(&mut self, dst: WritableReg, src: Reg, size: OperandSize) -> Result<()> {
+        self.asm.fpu_rr(FpuOPRR::Fsqrt, src, dst, size, FRM::RNE);
+        Ok(())
+    }
+
+    fn and(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, _size: OperandSize) -> Result<()> {
+        // The upper bits of 32-bit operands are zero, and so are the ones of
+        // the result.
+        match rhs {
+            RegImm::Imm(v) => {
+                self.alu_ri(AluOPRRR::And, Some(AluOPRRI::Andi), dst, lhs, imm_to_i64(v))
+            }
+            RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::And, lhs, rs2, dst),
+        }
+        Ok(())
+    }
+
+    fn or(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, size: OperandSize) -> Result<()> {
+        match rhs {
+            RegImm::Imm(v) => {
+                self.alu_ri(AluOPRRR::Or, Some(AluOPRRI::Ori), dst, lhs, imm_to_i64(v));
+                self.zext_if_s32(dst, size);
+            }
+            RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::Or, lhs, rs2, dst),
+        }
+        Ok(())
+    }
+
+    fn xor(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, size: OperandSize) -> Result<()> {
+        match rhs {
+            RegImm::Imm(v) => {
+                self.alu_ri(AluOPRRR::Xor, Some(AluOPRRI::Xori), dst, lhs, imm_to_i64(v));
+                self.zext_if_s32(dst, size);
+            }
+            RegImm::Reg(rs2) => self.asm.alu_rrr(AluOPRRR::Xor, lhs, rs2, dst),
+        }
+        Ok(())
+    }
+
+    fn shift_ir(
+        &mut self,
+        dst: WritableReg,
+        imm: I,
+        lhs: Reg,
+        kind: ShiftKind,
+        size: OperandSize,
+    ) -> Result<()> {
+        let bits = size.num_bits();
+        let amount = (imm.unwrap_as_u64() & u64::from(bits - 1)) as i16;
+        let s32 = size == OperandSize::S32;
+        let (shl, shr_u) = if s32 {
+            (AluOPRRI::Slliw, AluOPRRI::SrliW)
+        } else {
+            (AluOPRRI::Slli, AluOPRRI::Srli)
+        };
+
+        match kind {
+            ShiftKind::Shl => self.asm.alu_rri(shl, lhs, Imm12::from_i16(amount), dst),
+            ShiftKind::ShrU => self.asm.alu_rri(shr_u, lhs, Imm12::from_i16(amount), dst),
+            ShiftKind::ShrS => {
+                let op = if s32 { AluOPRRI::Sraiw } else { AluOPRRI::Srai };
+                self.asm.alu_rri(op, lhs, Imm12::from_i16(amount), dst)
+            }
+            ShiftKind::Rotl | ShiftKind::Rotr => {
+                // Rotating left by `n` is rotating right by `bits - n`.
+                let amount = match kind {
+                    ShiftKind::Rotl => (i16::from(bits) - amount) % i16::from(bits),
+                    _ => amount,
+                };
+                if self.flags.has_zbb() {
+                    let op = if s32 { AluOPRRI::Roriw } else { AluOPRRI::Rori };
+                    self.asm.alu_rri(op, lhs, Imm12::from_i16(amount), dst);
+                } else if amount == 0 {
+                    self.asm.mov_rr(lhs, dst);
+                } else {
+                    self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                        masm.asm.alu_rri(
+                            shl,
+                            lhs,
+                            Imm12::from_i16(i16::from(bits) - amount),
+                            scratch.writable(),
+                        );
+                        masm.asm.alu_rri(shr_u, lhs, Imm12::from_i16(amount), dst);
+                        masm.asm
+                            .alu_rrr(AluOPRRR::Or, dst.to_reg(), scratch.inner(), dst);
+                    });
+                }
+            }
+        }
+        self.zext_if_s32(dst, size);
+        Ok(())
+    }
+
+    fn shift(
+        &mut self,
+        context: &mut CodeGenContext<Emission>,
+        kind: ShiftKind,
+        size: OperandSize,
+    ) -> Result<()> {
+        let src = context.pop_to_reg(self, None)?;
+        let dst = context.pop_to_reg(self, None)?;
+        let s32 = size == OperandSize::S32;
+        let (shl, shr_u) = if s32 {
+            (AluOPRRR::Sllw, AluOPRRR::Srlw)
+        } else {
+            (AluOPRRR::Sll, AluOPRRR::Srl)
+        };
+        let (amount, rd) = (src.reg, writable!(dst.reg));
+
+        match kind {
+            ShiftKind::Shl => self.asm.alu_rrr(shl, dst.reg, amount, rd),
+            ShiftKind::ShrU => self.asm.alu_rrr(shr_u, dst.reg, amount, rd),
+            ShiftKind::ShrS => {
+                let op = if s32 { AluOPRRR::Sraw } else { AluOPRRR::Sra };
+                self.asm.alu_rrr(op, dst.reg, amount, rd);
+            }
+            ShiftKind::Rotl | ShiftKind::Rotr if self.flags.has_zbb() => {
+                let op = match (kind, s32) {
+                    (ShiftKind::Rotl, true) => AluOPRRR::Rolw,
+                    (ShiftKind::Rotl, false) => AluOPRRR::Rol,
+                    (_, true) => AluOPRRR::Rorw,
+                    (_, false) => AluOPRRR::Ror,
+                };
+                self.asm.alu_rrr(op, dst.reg, amount, rd);
+            }
+            ShiftKind::Rotl | ShiftKind::Rotr => {
+                // Shift amounts are taken modulo the operand size, so
+                // shifting by the negated amount shifts by `bits - amount`.
+                let (first, second) = match kind {
+                    ShiftKind::Rotl => (shl, shr_u),
+                    _ => (shr_u, shl),
+                };
+                self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                    masm.with_scratch::<IntScratch, _>(|masm, neg| {
+                        masm.asm.alu_rrr(first, dst.reg, amount, scratch.writable());
+                        masm.asm
+                            .alu_rrr(AluOPRRR::Sub, regs::zero(), amount, neg.writable());
+                        masm.asm.alu_rrr(second, dst.reg, neg.inner(), rd);
+                        masm.asm.alu_rrr(AluOPRRR::Or, dst.reg, scratch.inner(), rd);
+                    });
+                });
+            }
+        }
+        self.zext_if_s32(rd, size);
+
+        context.free_reg(src);
+        context.stack.push(dst.into());
+
+        Ok(())
+    }
+
+    fn div(
+        &mut self,
+        context: &mut CodeGenContext<Emission>,
+        kind: DivKind,
+        size: OperandSize,
+    ) -> Result<()> {
+        context.binop(self, size, |this, dividend, divisor, size| {
+            this.trap_if_zero(divisor, TrapCode::INTEGER_DIVISION_BY_ZERO);
+            let signed = matches!(kind, DivKind::Signed);
+            if signed {
+                this.trap_if_div_overflow(dividend, divisor, size);
+            }
+            let op = match (signed, size) {
+                (true, OperandSize::S32) => AluOPRRR::Divw,
+                (false, OperandSize::S32) => AluOPRRR::Divuw,
+                (true, _) => AluOPRRR::Div,
+                (false, _) => AluOPRRR::DivU,
+            };
+            this.asm.alu_rrr(op, dividend, divisor, writable!(dividend));
+            this.zext_if_s32(writable!(dividend), size);
+            match size {
+                OperandSize::S32 => Ok(TypedReg::new(WasmValType::I32, dividend)),
+                OperandSize::S64 => Ok(TypedReg::new(WasmValType::I64, dividend)),
+                _ => Err(anyhow!(CodeGenError::unexpected_operand_size())),
+            }
+        })
+    }
+
+    fn rem(
+        &mut self,
+        context: &mut CodeGenContext<Emission>,
+        kind: RemKind,
+        size: OperandSize,
+    ) -> Result<()> {
+        context.binop(self, size, |this, dividend, divisor, size| {
+            // Unlike division, the remainder of the division of the minimum
+            // integer by -1 is well defined (zero) and doesn't trap.
+            this.trap_if_zero(divisor, TrapCode::INTEGER_DIVISION_BY_ZERO);
+            let op = match (kind.is_signed(), size) {
+                (true, OperandSize::S32) => AluOPRRR::Remw,
+                (false, OperandSize::S32) => AluOPRRR::Remuw,
+                (true, _) => AluOPRRR::Rem,
+                (false, _) => AluOPRRR::RemU,
+            };
+            this.asm.alu_rrr(op, dividend, divisor, writable!(dividend));
+            this.zext_if_s32(writable!(dividend), size);
+            match size {
+                OperandSize::S32 => Ok(TypedReg::new(WasmValType::I32, dividend)),
+                OperandSize::S64 => Ok(TypedReg::new(WasmValType::I64, dividend)),
+                _ => Err(anyhow!(CodeGenError::unexpected_operand_size())),
+            }
+        })
+    }
+
+    fn zero(&mut self, reg: WritableReg) -> Result<()> {
+        self.asm.mov_ir(reg, I::i64(0));
+        Ok(())
+    }
+
+    fn popcnt(&mut self, context: &mut CodeGenContext<Emission>, size: OperandSize) -> Result<()> {
+        let src = context.pop_to_reg(self, None)?;
+        if self.flags.has_zbb() {
+            let op = match size {
+                OperandSize::S32 => AluOPRRI::Cpopw,
+                _ => AluOPRRI::Cpop,
+            };
+            self.asm
+                .alu_rri(op, src.into(), Imm12::from_i16(0), writable!(src.into()));
+            context.stack.push(src.into());
+        } else {
+            let dst = context.any_gpr(self)?;
+            self.with_scratch::<IntScratch, _>(|masm, step| {
+                masm.with_scratch::<IntScratch, _>(|masm, tmp| {
+                    masm.asm.popcnt(
+                        src.into(),
+                        writable!(dst),
+                        step.writable(),
+                        tmp.writable(),
+                        size,
+                    );
+                });
+            });
+            context.free_reg(src);
+            context.stack.push(TypedReg::new(src.ty, dst).into());
+        }
+        Ok(())
+    }
+
+    fn signed_truncate(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    ) -> Result<()> {
+        self.fpu_to_int(dst, src, src_size, dst_size, kind, true);
+        Ok(())
+    }
+
+    fn unsigned_truncate(
+        &mut self,
+        ctx: &mut CodeGenContext<Emission>,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    ) -> Result<()> {
+        let dst_ty = match dst_size {
+            OperandSize::S32 => WasmValType::I32,
+            OperandSize::S64 => WasmValType::I64,
+            _ => bail!(CodeGenError::unexpected_operand_size()),
+        };
+
+        ctx.convert_op(self, dst_ty, |masm, dst, src, dst_size| {
+            masm.fpu_to_int(writable!(dst), src, src_size, dst_size, kind, false);
+            Ok(())
+        })
+    }
+
+    fn signed_convert(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) -> Result<()> {
+        let op = match src_size {
+            OperandSize::S32 => FpuOPRR::FcvtFmtW,
+            OperandSize::S64 => FpuOPRR::FcvtFmtL,
+            _ => bail!(CodeGenError::unexpected_operand_size()),
+        };
+        self.asm.fpu_rr(op, src, dst, dst_size, FRM::RNE);
+        Ok(())
+    }
+
+    fn unsigned_convert(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        _tmp_gpr: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) -> Result<()> {
+        let op = match src_size {
+            OperandSize::S32 => FpuOPRR::FcvtFmtWu,
+            OperandSize::S64 => FpuOPRR::FcvtFmtLu,
+            _ => bail!(CodeGenError::unexpected_operand_size()),
+        };
+        self.asm.fpu_rr(op, src, dst, dst_size, FRM::RNE);
+        Ok(())
+    }
+
+    fn reinterpret_float_as_int(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        size: OperandSize,
+    ) -> Result<()> {
+        self.asm.fpu_rr(FpuOPRR::FmvXFmt, src, dst, size, FRM::RNE);
+        self.zext_if_s32(dst, size);
+        Ok(())
+    }
+
+    fn reinterpret_int_as_float(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        size: OperandSize,
+    ) -> Result<()> {
+        self.asm.fpu_rr(FpuOPRR::FmvFmtX, src, dst, size, FRM::RNE);
+        Ok(())
+    }
+
+    fn demote(&mut self, dst: WritableReg, src: Reg) -> Result<()> {
+        self.asm
+            .fpu_rr(FpuOPRR::FcvtSD, src, dst, OperandSize::S32, FRM::RNE);
+        Ok(())
+    }
+
+    fn promote(&mut self, dst: WritableReg, src: Reg) -> Result<()> {
+        self.asm
+            .fpu_rr(FpuOPRR::FcvtDS, src, dst, OperandSize::S64, FRM::RNE);
+        Ok(())
+    }
+
+    fn push(&mut self, reg: Reg, size: OperandSize) -> Result<StackSlot> {
+        self.reserve_stack(size.bytes())?;
+        let address = self.address_from_sp(SPOffset::from_u32(self.sp_offset))?;
+        self.asm.str(reg, address, size, TRUSTED_FLAGS);
+
+        Ok(StackSlot {
+            offset: SPOffset::from_u32(self.sp_offset),
+            size: size.bytes(),
+        })
+    }
+
+    fn address_at_reg(&self, reg: Reg, offset: u32) -> Result<Self::Address> {
+        Ok(Address::offset(reg, offset as i64))
+    }
+
+    fn cmp_with_set(
+        &mut self,
+        dst: WritableReg,
+        src: RegImm,
+        kind: IntCmpKind,
+        size: OperandSize,
+    ) -> Result<()> {
+        let comparison = Comparison {
+            lhs: dst.to_reg(),
+            rhs: src,
+            size,
+        };
+        self.with_int_compare(kind, comparison, |masm, rs1, rs2| {
+            let one = Imm12::from_i16(1);
+            match kind {
+                IntCmpKind::Eq => {
+                    masm.asm.alu_rrr(AluOPRRR::Xor, rs1, rs2, dst);
+                    masm.asm.alu_rri(AluOPRRI::SltiU, dst.to_reg(), one, dst);
+                }
+                IntCmpKind::Ne => {
+                    masm.asm.alu_rrr(AluOPRRR::Xor, rs1, rs2, dst);
+                    masm.asm
+                        .alu_rrr(AluOPRRR::SltU, regs::zero(), dst.to_reg(), dst);
+                }
+                IntCmpKind::LtS => masm.asm.alu_rrr(AluOPRRR::Slt, rs1, rs2, dst),
+                IntCmpKind::LtU => masm.asm.alu_rrr(AluOPRRR::SltU, rs1, rs2, dst),
+                IntCmpKind::GtS => masm.asm.alu_rrr(AluOPRRR::Slt, rs2, rs1, dst),
+                IntCmpKind::GtU => masm.asm.alu_rrr(AluOPRRR::SltU, rs2, rs1, dst),
+                IntCmpKind::LeS | IntCmpKind::LeU | IntCmpKind::GeS | IntCmpKind::GeU => {
+                    // Negate the opposite comparison.
+                    let op = if is_signed(kind) {
+                        AluOPRRR::Slt
+                    } else {
+                        AluOPRRR::SltU
+                    };
+                    let (rs1, rs2) = match kind {
+                        IntCmpKind::LeS | IntCmpKind::LeU => (rs2, rs1),
+                        _ => (rs1, rs2),
+                    };
+                    masm.asm.alu_rrr(op, rs1, rs2, dst);
+                    masm.asm.alu_rri(AluOPRRI::Xori, dst.to_reg(), one, dst);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn cmp(&mut self, src1: Reg, src2: RegImm, size: OperandSize) -> Result<()> {
+        self.comparison = Some(Comparison {
+            lhs: src1,
+            rhs: src2,
+            size,
+        });
+        Ok(())
+    }
+
+    fn float_cmp_with_set(
+        &mut self,
+        dst: WritableReg,
+        src1: Reg,
+        src2: Reg,
+        kind: FloatCmpKind,
+        size: OperandSize,
+    ) -> Result<()> {
+        let (op, rs1, rs2) = match kind {
+            FloatCmpKind::Eq | FloatCmpKind::Ne => (FpuOPRRR::Feq, src1, src2),
+            FloatCmpKind::Lt => (FpuOPRRR::Flt, src1, src2),
+            FloatCmpKind::Gt => (FpuOPRRR::Flt, src2, src1),
+            FloatCmpKind::Le => (FpuOPRRR::Fle, src1, src2),
+            FloatCmpKind::Ge => (FpuOPRRR::Fle, src2, src1),
+        };
+        self.asm.fpu_rrr(op, rs1, rs2, dst, size);
+        if matches!(kind, FloatCmpKind::Ne) {
+            self.asm
+                .alu_rri(AluOPRRI::Xori, dst.to_reg(), Imm12::from_i16(1), dst);
+        }
+        Ok(())
+    }
+
+    fn clz(&mut self, dst: WritableReg, src: Reg, size: OperandSize) -> Result<()> {
+        self.count_zeros(true, dst, src, size);
+        Ok(())
+    }
+
+    fn ctz(&mut self, dst: WritableReg, src: Reg, size: OperandSize) -> Result<()> {
+        self.count_zeros(false, dst, src, size);
+        Ok(())
+    }
+
+    fn wrap(&mut self, dst: WritableReg, src: Reg) -> Result<()> {
+        self.asm.zext32(src, dst);
+        Ok(())
+    }
+
+    fn extend(&mut self, dst: WritableReg, src: Reg, kind: ExtendKind) -> Result<()> {
+        match (kind.signed(), kind.from_bits()) {
+            (true, 32) => self.asm.sext32(src, dst),
+            (signed, from_bits) => self.asm.extend(src, dst, signed, from_bits),
+        }
+        if kind.signed() && kind.to_bits() == 32 {
+            self.asm.zext32(dst.to_reg(), dst);
+        }
+        Ok(())
+    }
+
+    fn get_label(&mut self) -> Result<MachLabel> {
+        Ok(self.asm.get_label())
+    }
+
+    fn bind(&mut self, label: MachLabel) -> Result<()> {
+        let buffer = self.asm.buffer_mut();
+        buffer.bind_label(label, &mut Default::default());
+        Ok(())
+    }
+
+    fn branch(
+        &mut self,
+        kind: IntCmpKind,
+        lhs: Reg,
+        rhs: RegImm,
+        taken: MachLabel,
+        size: OperandSize,
+    ) -> Result<()> {
+        use IntCmpKind::*;
+
+        // If the comparison kind is zero or not zero and both operands are
+        // the same register, compare against zero.
+        let rhs = match rhs {
+            RegImm::Reg(r) if (kind == Eq || kind == Ne) && r == lhs => RegImm::i64(0),
+            _ => rhs,
+        };
+        let comparison = Comparison { lhs, rhs, size };
+        self.with_int_compare(kind, comparison, |masm, lhs, rhs| {
+            masm.asm.jmp_if(int_compare(kind, lhs, rhs), taken)
+        });
+        Ok(())
+    }
+
+    fn jmp(&mut self, target: MachLabel) -> Result<()> {
+        self.asm.jmp(target);
+        Ok(())
+    }
+
+    fn unreachable(&mut self) -> Result<()> {
+        self.asm.udf(wasmtime_cranelift::TRAP_UNREACHABLE);
+        Ok(())
+    }
+
+    fn jmp_table(&mut self, targets: &[MachLabel], index: Reg, tmp: Reg) -> Result<()> {
+        // At least one default target.
+        debug_assert!(targets.len() >= 1);
+        let default_index = targets.len() - 1;
+        // NB: `Assembler::jmp_table` (and the underlying Cranelift
+        // instruction) emits the bounds checks.
+        let default = targets[default_index];
+        let rest = &targets[0..default_index];
+        self.with_scratch::<IntScratch, _>(|masm, scratch| {
+            masm.asm
+                .jmp_table(rest, default, index, scratch.inner(), tmp);
+            Ok(())
+        })
+    }
+
+    fn trap(&mut self, code: TrapCode) -> Result<()> {
+        self.asm.udf(code);
+        Ok(())
+    }
+
+    fn trapz(&mut self, src: Reg, code: TrapCode) -> Result<()> {
+        self.trap_if_zero(src, code);
+        Ok(())
+    }
+
+    fn trapif(&mut self, cc: IntCmpKind, code: TrapCode) -> Result<()> {
+        let comparison = self.take_comparison()?;
+        self.with_int_compare(cc, comparison, |masm, lhs, rhs| {
+            masm.asm.trapif(int_compare(cc, lhs, rhs), code)
+        });
+        Ok(())
+    }
+
+    fn start_source_loc(&mut self, loc: RelSourceLoc) -> Result<(CodeOffset, RelSourceLoc)> {
+        Ok(self.asm.buffer_mut().start_srcloc(loc))
+    }
+
+    fn end_source_loc(&mut self) -> Result<()> {
+        self.asm.buffer_mut().end_srcloc();
+        Ok(())
+    }
+
+    fn current_code_offset(&self) -> Result<CodeOffset> {
+        Ok(self.asm.buffer().cur_offset())
+    }
+
+    fn add128(
+        &mut self,
+        dst_lo: WritableReg,
+        dst_hi: WritableReg,
+        lhs_lo: Reg,
+        lhs_hi: Reg,
+        rhs_lo: Reg,
+        rhs_hi: Reg,
+    ) -> Result<()> {
+        let _ = (dst_lo, dst_hi, lhs_lo, lhs_hi, rhs_lo, rhs_hi);
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn sub128(
+        &mut self,
+        dst_lo: WritableReg,
+        dst_hi: WritableReg,
+        lhs_lo: Reg,
+        lhs_hi: Reg,
+        rhs_lo: Reg,
+        rhs_hi: Reg,
+    ) -> Result<()> {
+        let _ = (dst_lo, dst_hi, lhs_lo, lhs_hi, rhs_lo, rhs_hi);
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn mul_wide(
+        &mut self,
+        context: &mut CodeGenContext<Emission>,
+        kind: MulWideKind,
+    ) -> Result<()> {
+        let _ = (context, kind);
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn splat(&mut self, _context: &mut CodeGenContext<Emission>, _size: SplatKind) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn shuffle(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _lanes: [u8; 16]) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn swizzle(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn atomic_rmw(
+        &mut self,
+        _context: &mut CodeGenContext<Emission>,
+        _addr: Self::Address,
+        _size: OperandSize,
+        _op: RmwOp,
+        _flags: MemFlags,
+        _extend: Option<Extend<Zero>>,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn extract_lane(
+        &mut self,
+        _src: Reg,
+        _dst: WritableReg,
+        _lane: u8,
+        _kind: ExtractLaneKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn replace_lane(
+        &mut self,
+        _src: RegImm,
+        _dst: WritableReg,
+        _lane: u8,
+        _kind: ReplaceLaneKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn atomic_cas(
+        &mut self,
+        _context: &mut CodeGenContext<Emission>,
+        _addr: Self::Address,
+        _size: OperandSize,
+        _flags: MemFlags,
+        _extend: Option<Extend<Zero>>,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_eq(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: VectorEqualityKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_ne(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: VectorEqualityKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_lt(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: VectorCompareKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_le(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: VectorCompareKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_gt(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: VectorCompareKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_ge(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: VectorCompareKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_not(&mut self, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn fence(&mut self) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_and(&mut self, _src1: Reg, _src2: Reg, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_and_not(&mut self, _src1: Reg, _src2: Reg, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_or(&mut self, _src1: Reg, _src2: Reg, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_xor(&mut self, _src1: Reg, _src2: Reg, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_bitselect(
+        &mut self,
+        _src1: Reg,
+        _src2: Reg,
+        _mask: Reg,
+        _dst: WritableReg,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_any_true(&mut self, _src: Reg, _dst: WritableReg) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_convert(&mut self, _src: Reg, _dst: WritableReg, _kind: V128ConvertKind) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_narrow(
+        &mut self,
+        _src1: Reg,
+        _src2: Reg,
+        _dst: WritableReg,
+        _kind: V128NarrowKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_demote(&mut self, _src: Reg, _dst: WritableReg) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_promote(&mut self, _src: Reg, _dst: WritableReg) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_extend(&mut self, _src: Reg, _dst: WritableReg, _kind: V128ExtendKind) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_add(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _kind: V128AddKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_sub(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _kind: V128SubKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_mul(
+        &mut self,
+        _context: &mut CodeGenContext<Emission>,
+        _kind: V128MulKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_abs(&mut self, _src: Reg, _dst: WritableReg, _kind: V128AbsKind) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_neg(&mut self, _op: WritableReg, _kind: V128NegKind) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_shift(
+        &mut self,
+        _context: &mut CodeGenContext<Emission>,
+        _lane_width: OperandSize,
+        _shift_kind: ShiftKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_q15mulr_sat_s(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _size: OperandSize,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_all_true(&mut self, _src: Reg, _dst: WritableReg, _size: OperandSize) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_bitmask(&mut self, _src: Reg, _dst: WritableReg, _size: OperandSize) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_trunc(
+        &mut self,
+        _context: &mut CodeGenContext<Emission>,
+        _kind: V128TruncKind,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_min(
+        &mut self,
+        _src1: Reg,
+        _src2: Reg,
+        _dst: WritableReg,
+        _kind: V128MinKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_max(
+        &mut self,
+        _src1: Reg,
+        _src2: Reg,
+        _dst: WritableReg,
+        _kind: V128MaxKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_extmul(
+        &mut self,
+        _context: &mut CodeGenContext<Emission>,
+        _kind: V128ExtMulKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_extadd_pairwise(
+        &mut self,
+        _src: Reg,
+        _dst: WritableReg,
+        _kind: V128ExtAddKind,
+    ) -> Result<()> {
+        Err(anyhow!(CodeGenError::unimplemented_masm_instruction()))
+    }
+
+    fn v128_dot(&mut self, _lhs: Reg, _rhs: Reg, _dst: WritableReg) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_popcnt(&mut self, _context: &mut CodeGenContext<Emission>) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_avgr(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _size: OperandSize,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_div(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _size: OperandSize,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_sqrt(&mut self, _src: Reg, _dst: WritableReg, _size: OperandSize) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_ceil(&mut self, _src: Reg, _dst: WritableReg, _size: OperandSize) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_floor(&mut self, _src: Reg, _dst: WritableReg, _size: OperandSize) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_nearest(&mut self, _src: Reg, _dst: WritableReg, _size: OperandSize) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_pmin(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _size: OperandSize,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+
+    fn v128_pmax(
+        &mut self,
+        _lhs: Reg,
+        _rhs: Reg,
+        _dst: WritableReg,
+        _size: OperandSize,
+    ) -> Result<()> {
+        bail!(CodeGenError::unimplemented_masm_instruction())
+    }
+}
+
+impl MacroAssembler {
+    fn increment_sp(&mut self, bytes: u32) {
+        self.sp_offset += bytes;
+
+        // NOTE: we use `max` here to track the largest stack allocation in `sp_max`. Once we have
+        // seen the entire function, this value will represent the maximum size for the stack
+        // frame.
+        self.sp_max = self.sp_max.max(self.sp_offset);
+    }
+
+    fn decrement_sp(&mut self, bytes: u32) {
+        self.sp_offset -= bytes;
+    }
+
+    /// Adds `bytes` to the stack pointer.
+    fn adjust_sp(&mut self, bytes: i64) {
+        let sp = regs::sp();
+        match Imm12::maybe_from_i64(bytes) {
+            Some(imm12) => self.asm.alu_rri(AluOPRRI::Addi, sp, imm12, writable!(sp)),
+            None => self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                masm.asm.mov_ir(scratch.writable(), I::i64(bytes));
+                masm.asm
+                    .alu_rrr(AluOPRRR::Add, sp, scratch.inner(), writable!(sp));
+            }),
+        }
+    }
+
+    /// Pops the frame pointer and the return address, assuming that the
+    /// stack pointer points to the saved frame pointer.
+    fn pop_frame(&mut self) {
+        let sp = regs::sp();
+        self.asm.uload(
+            Address::offset(sp, 8),
+            writable!(regs::ra()),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+        self.asm.uload(
+            Address::offset(sp, 0),
+            writable!(regs::fp()),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+        self.asm
+            .alu_rri(AluOPRRI::Addi, sp, Imm12::from_i16(16), writable!(sp));
+    }
+
+    /// Helper to emit an ALU operation with an immediate operand, using the
+    /// immediate form of the instruction, if any, when the immediate fits in
+    /// 12 bits, or materializing the immediate in a scratch register
+    /// otherwise.
+    fn alu_ri(
+        &mut self,
+        op: AluOPRRR,
+        op_imm: Option<AluOPRRI>,
+        dst: WritableReg,
+        lhs: Reg,
+        imm: i64,
+    ) {
+        match (op_imm, Imm12::maybe_from_i64(imm)) {
+            (Some(op_imm), Some(imm12)) => self.asm.alu_rri(op_imm, lhs, imm12, dst),
+            _ => self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                masm.asm.mov_ir(scratch.writable(), I::i64(imm));
+                masm.asm.alu_rrr(op, lhs, scratch.inner(), dst);
+            }),
+        }
+    }
+
+    /// Clears the upper 32 bits of `dst` if the operation is 32 bits wide,
+    /// keeping the invariant that 32-bit values are zero-extended in their
+    /// registers.
+    fn zext_if_s32(&mut self, dst: WritableReg, size: OperandSize) {
+        if size == OperandSize::S32 {
+            self.asm.zext32(dst.to_reg(), dst);
+        }
+    }
+
+    /// Returns the comparison recorded by the last call to [`Masm::cmp`].
+    fn take_comparison(&mut self) -> Result<Comparison> {
+        self.comparison
+            .take()
+            .ok_or_else(|| anyhow!(CodeGenError::invalid_operand_combination()))
+    }
+
+    /// Lowers the operands of `comparison` into registers, which are handed
+    /// to `f` to emit the instruction consuming the comparison.
+    ///
+    /// 32-bit values are zero-extended in their registers, which is enough
+    /// for equality and unsigned comparisons; for signed comparisons the
+    /// operands are sign-extended into scratch registers. Immediates, unless
+    /// zero, are also materialized into scratch registers.
+    fn with_int_compare(
+        &mut self,
+        kind: IntCmpKind,
+        comparison: Comparison,
+        f: impl FnOnce(&mut Self, Reg, Reg),
+    ) {
+        let signed = is_signed(kind);
+        let sext = signed && comparison.size == OperandSize::S32;
+        let mut scratches = vec![];
+
+        let lhs = if sext {
+            let scratch = self.acquire_scratch(&mut scratches);
+            self.asm.sext32(comparison.lhs, writable!(scratch));
+            scratch
+        } else {
+            comparison.lhs
+        };
+
+        let rhs = match comparison.rhs {
+            RegImm::Reg(rhs) if sext => {
+                let scratch = self.acquire_scratch(&mut scratches);
+                self.asm.sext32(rhs, writable!(scratch));
+                scratch
+            }
+            RegImm::Reg(rhs) => rhs,
+            RegImm::Imm(imm) => {
+                let value = match (comparison.size, imm) {
+                    (OperandSize::S32, _) if signed => imm.unwrap_as_u64() as u32 as i32 as i64,
+                    (OperandSize::S32, _) => imm.unwrap_as_u64() as u32 as i64,
+                    (_, I::I32(v)) => v as i32 as i64,
+                    _ => imm.unwrap_as_u64() as i64,
+                };
+                if value == 0 {
+                    regs::zero()
+                } else {
+                    let scratch = self.acquire_scratch(&mut scratches);
+                    self.asm.mov_ir(writable!(scratch), I::i64(value));
+                    scratch
+                }
+            }
+        };
+
+        f(self, lhs, rhs);
+
+        for scratch in scratches {
+            self.scratch_scope.free(scratch);
+        }
+    }
+
+    /// Acquires an integer scratch register, recording it in `acquired`;
+    /// used when the number of scratch registers needed is only known at
+    /// emission time.
+    fn acquire_scratch(&mut self, acquired: &mut Vec<Reg>) -> Reg {
+        let scratch = self
+            .scratch_scope
+            .reg_for_class(RegClass::Int, &mut |_| Ok(()))
+            .expect("Scratch register to be available");
+        acquired.push(scratch);
+        scratch
+    }
+
+    /// Traps with `code` if `src` is zero.
+    fn trap_if_zero(&mut self, src: Reg, code: TrapCode) {
+        self.asm.trapif(
+            IntegerCompare {
+                kind: IntCC::Equal,
+                rs1: src.into(),
+                rs2: regs::zero().into(),
+            },
+            code,
+        );
+    }
+
+    /// Traps if the signed division of `dividend` by `divisor` overflows,
+    /// i.e. if the dividend is the minimum integer and the divisor is -1.
+    fn trap_if_div_overflow(&mut self, dividend: Reg, divisor: Reg, size: OperandSize) {
+        let done = self.asm.get_label();
+        self.with_scratch::<IntScratch, _>(|masm, scratch0| {
+            masm.with_scratch::<IntScratch, _>(|masm, scratch1| {
+                // The divisor is -1 only if adding one to it yields zero.
+                let add = match size {
+                    OperandSize::S32 => AluOPRRI::Addiw,
+                    _ => AluOPRRI::Addi,
+                };
+                masm.asm
+                    .alu_rri(add, divisor, Imm12::from_i16(1), scratch0.writable());
+                masm.asm.jmp_if(
+                    IntegerCompare {
+                        kind: IntCC::NotEqual,
+                        rs1: scratch0.inner().into(),
+                        rs2: regs::zero().into(),
+                    },
+                    done,
+                );
+
+                let dividend = match size {
+                    OperandSize::S32 => {
+                        // `lui` sign-extends the minimum 32-bit integer, so
+                        // compare it against the sign-extended dividend.
+                        masm.asm
+                            .mov_ir(scratch0.writable(), I::i64(i32::MIN.into()));
+                        masm.asm.sext32(dividend, scratch1.writable());
+                        scratch1.inner()
+                    }
+                    _ => {
+                        masm.asm.mov_ir(scratch0.writable(), I::i64(i64::MIN));
+                        dividend
+                    }
+                };
+                masm.asm.trapif(
+                    IntegerCompare {
+                        kind: IntCC::Equal,
+                        rs1: dividend.into(),
+                        rs2: scratch0.inner().into(),
+                    },
+                    TrapCode::INTEGER_OVERFLOW,
+                );
+            });
+        });
+        self.asm
+            .buffer_mut()
+            .bind_label(done, &mut Default::default());
+    }
+
+    /// Emits a floating-point minimum or maximum operation, following
+    /// WebAssembly's semantics: if any of the operands is NaN, the result is
+    /// a canonical NaN, which `fmin` and `fmax` don't produce.
+    fn float_min_max(
+        &mut self,
+        op: FpuOPRRR,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        size: OperandSize,
+    ) {
+        let nan = self.asm.get_label();
+        let done = self.asm.get_label();
+        self.with_scratch::<IntScratch, _>(|masm, scratch0| {
+            masm.with_scratch::<IntScratch, _>(|masm, scratch1| {
+                masm.asm
+                    .fpu_rrr(FpuOPRRR::Feq, lhs, lhs, scratch0.writable(), size);
+                masm.asm
+                    .fpu_rrr(FpuOPRRR::Feq, rhs, rhs, scratch1.writable(), size);
+                masm.asm.alu_rrr(
+                    AluOPRRR::And,
+                    scratch0.inner(),
+                    scratch1.inner(),
+                    scratch0.writable(),
+                );
+                masm.asm.jmp_if(
+                    IntegerCompare {
+                        kind: IntCC::Equal,
+                        rs1: scratch0.inner().into(),
+                        rs2: regs::zero().into(),
+                    },
+                    nan,
+                );
+            });
+        });
+        self.asm.fpu_rrr(op, lhs, rhs, dst, size);
+        self.asm.jmp(done);
+        // Adding the operands propagates the NaN.
+        self.asm
+            .buffer_mut()
+            .bind_label(nan, &mut Default::default());
+        self.asm.fpu_rrr(FpuOPRRR::Fadd, lhs, rhs, dst, size);
+        self.asm
+            .buffer_mut()
+            .bind_label(done, &mut Default::default());
+    }
+
+    /// Converts the floating-point value in `src` into an integer, truncating
+    /// it.
+    fn fpu_to_int(
+        &mut self,
+        dst: WritableReg,
+        src: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+        signed: bool,
+    ) {
+        let op = match (signed, dst_size) {
+            (true, OperandSize::S32) => FpuOPRR::FcvtWFmt,
+            (false, OperandSize::S32) => FpuOPRR::FcvtWuFmt,
+            (true, _) => FpuOPRR::FcvtLFmt,
+            (false, _) => FpuOPRR::FcvtLuFmt,
+        };
+
+        if kind.is_unchecked() {
+            // Confusingly, when `kind` is `Unchecked` is when we actually
+            // need to perform the checks:
+            // - check if fp is NaN
+            // - check bounds
+            self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                masm.asm
+                    .fpu_rrr(FpuOPRRR::Feq, src, src, scratch.writable(), src_size);
+                masm.trap_if_zero(scratch.inner(), TrapCode::BAD_CONVERSION_TO_INTEGER);
+
+                masm.with_scratch::<FloatScratch, _>(|masm, bound| {
+                    let (min, max) = match src_size {
+                        OperandSize::S32 => {
+                            let (min, max) =
+                                f32_cvt_to_int_bounds(signed, dst_size.num_bits().into());
+                            (I::f32(min.to_bits()), I::f32(max.to_bits()))
+                        }
+                        _ => {
+                            let (min, max) =
+                                f64_cvt_to_int_bounds(signed, dst_size.num_bits().into());
+                            (I::f64(min.to_bits()), I::f64(max.to_bits()))
+                        }
+                    };
+
+                    for (bound_value, lhs, rhs) in
+                        [(min, src, bound.inner()), (max, bound.inner(), src)]
+                    {
+                        masm.asm.mov_ir(bound.writable(), bound_value);
+                        masm.asm
+                            .fpu_rrr(FpuOPRRR::Fle, lhs, rhs, scratch.writable(), src_size);
+                        masm.asm.trapif(
+                            IntegerCompare {
+                                kind: IntCC::NotEqual,
+                                rs1: scratch.inner().into(),
+                                rs2: regs::zero().into(),
+                            },
+                            TrapCode::INTEGER_OVERFLOW,
+                        );
+                    }
+                });
+            });
+            self.asm.fpu_rr(op, src, dst, src_size, FRM::RTZ);
+        } else {
+            // The conversion saturates out-of-bounds values, but NaN must be
+            // converted to zero.
+            self.asm.fpu_rr(op, src, dst, src_size, FRM::RTZ);
+            self.with_scratch::<IntScratch, _>(|masm, scratch| {
+                masm.asm
+                    .fpu_rrr(FpuOPRRR::Feq, src, src, scratch.writable(), src_size);
+                masm.asm.alu_rrr(
+                    AluOPRRR::Sub,
+                    regs::zero(),
+                    scratch.inner(),
+                    scratch.writable(),
+                );
+                masm.asm
+                    .alu_rrr(AluOPRRR::And, dst.to_reg(), scratch.inner(), dst);
+            });
+        }
+        self.zext_if_s32(dst, dst_size);
+    }
+
+    /// Counts the leading (`leading == true`) or trailing zeros of `src`.
+    fn count_zeros(&mut self, leading: bool, dst: WritableReg, src: Reg, size: OperandSize) {
+        if self.flags.has_zbb() {
+            let op = match (leading, size) {
+                (true, OperandSize::S32) => AluOPRRI::Clzw,
+                (true, _) => AluOPRRI::Clz,
+                (false, OperandSize::S32) => AluOPRRI::Ctzw,
+                (false, _) => AluOPRRI::Ctz,
+            };
+            self.asm.alu_rri(op, src, Imm12::from_i16(0), dst);
+        } else {
+            // The pseudo-instruction requires the destination to be different
+            // from the source.
+            self.with_scratch::<IntScratch, _>(|masm, sum| {
+                masm.with_scratch::<IntScratch, _>(|masm, step| {
+                    masm.asm.cltz(
+                        leading,
+                        src,
+                        sum.writable(),
+                        step.writable(),
+                        writable!(regs::emit_tmp1()),
+                        size,
+                    );
+                });
+                masm.asm.mov_rr(sum.inner(), dst);
+            });
+        }
+    }
+}
+
+/// Returns `true` for signed comparisons.
+fn is_signed(kind: IntCmpKind) -> bool {
+    matches!(
+        kind,
+        IntCmpKind::LtS | IntCmpKind::GtS | IntCmpKind::LeS | IntCmpKind::GeS
+    )
+}
+
+/// Builds the integer comparison of `kind` between `lhs` and `rhs`.
+fn int_compare(kind: IntCmpKind, lhs: Reg, rhs: Reg) -> IntegerCompare {
+    IntegerCompare {
+        kind: kind.into(),
+        rs1: lhs.into(),
+        rs2: rhs.into(),
+    }
+}
+
+/// Returns the value of an integer immediate, sign-extending 32-bit
+/// immediates.
+fn imm_to_i64(imm: I) -> i64 {
+    match imm {
+        I::I32(v) => v as i32 as i64,
+        _ => imm.unwrap_as_u64() as i64,
+    }
+}
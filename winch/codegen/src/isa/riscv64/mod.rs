@@ -0,0 +1,163 @@
+use self::regs::{fpr_bit_set, gpr_bit_set};
+use crate::{
+    BuiltinFunctions,
+    abi::{ABI, wasm_sig},
+    codegen::{CodeGen, CodeGenContext, FuncEnv, TypeConverter},
+    frame::{DefinedLocals, Frame},
+    isa::{Builder, CompiledFunctionOutput, TargetIsa},
+    masm::MacroAssembler,
+    regalloc::RegAlloc,
+    stack::Stack,
+};
+use anyhow::Result;
+use cranelift_codegen::settings::{self, Flags};
+use cranelift_codegen::{Final, MachBufferFinalized, isa::riscv64::settings as riscv64_settings};
+use cranelift_codegen::{MachTextSectionBuilder, TextSectionBuilder};
+use masm::MacroAssembler as Riscv64Masm;
+use target_lexicon::Triple;
+use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
+use wasmtime_cranelift::CompiledFunction;
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType,
+};
+
+mod abi;
+mod address;
+mod asm;
+mod masm;
+mod regs;
+
+/// Create an ISA from the given triple.
+pub(crate) fn isa_builder(triple: Triple) -> Builder {
+    Builder::new(
+        triple,
+        riscv64_settings::builder(),
+        |triple, shared_flags, settings| {
+            let isa_flags = riscv64_settings::Flags::new(&shared_flags, settings);
+            let isa = Riscv64::new(triple, shared_flags, isa_flags);
+            Ok(Box::new(isa))
+        },
+    )
+}
+
+/// Riscv64 ISA.
+pub(crate) struct Riscv64 {
+    /// The target triple.
+    triple: Triple,
+    /// ISA specific flags.
+    isa_flags: riscv64_settings::Flags,
+    /// Shared flags.
+    shared_flags: Flags,
+}
+
+impl Riscv64 {
+    /// Create an Riscv64 ISA.
+    pub fn new(triple: Triple, shared_flags: Flags, isa_flags: riscv64_settings::Flags) -> Self {
+        Self {
+            isa_flags,
+            shared_flags,
+            triple,
+        }
+    }
+}
+
+impl TargetIsa for Riscv64 {
+    fn name(&self) -> &'static str {
+        "riscv64"
+    }
+
+    fn triple(&self) -> &Triple {
+        &self.triple
+    }
+
+    fn flags(&self) -> &settings::Flags {
+        &self.shared_flags
+    }
+
+    fn isa_flags(&self) -> Vec<settings::Value> {
+        self.isa_flags.iter().collect()
+    }
+
+    fn is_branch_protection_enabled(&self) -> bool {
+        false
+    }
+
+    fn compile_function(
+        &self,
+        index: DefinedFuncIndex,
+        sig: &WasmFuncType,
+        body: &FunctionBody,
+        translation: &ModuleTranslation,
+        types: &ModuleTypesBuilder,
+        builtins: &mut BuiltinFunctions,
+        validator: &mut FuncValidator<ValidatorResources>,
+        tunables: &Tunables,
+    ) -> Result<CompiledFunctionOutput> {
+        let pointer_bytes = self.pointer_bytes();
+        let vmoffsets = VMOffsets::new(pointer_bytes, &translation.module);
+        let mut body = body.get_binary_reader();
+        let mut masm = Riscv64Masm::new(
+            pointer_bytes,
+            self.shared_flags.clone(),
+            self.isa_flags.clone(),
+        )?;
+        let stack = Stack::new();
+        let abi_sig = wasm_sig::<abi::Riscv64ABI>(sig)?;
+
+        let env = FuncEnv::new(
+            &vmoffsets,
+            index,
+            translation,
+            types,
+            builtins,
+            self,
+            abi::Riscv64ABI::ptr_type(),
+        );
+        let type_converter = TypeConverter::new(env.translation, env.types);
+        let defined_locals =
+            DefinedLocals::new::<abi::Riscv64ABI>(&type_converter, &mut body, validator)?;
+        let frame = Frame::new::<abi::Riscv64ABI>(&abi_sig, &defined_locals)?;
+        let regalloc = RegAlloc::from(gpr_bit_set(), fpr_bit_set());
+        let codegen_context = CodeGenContext::new(regalloc, stack, frame, &vmoffsets);
+        let codegen = CodeGen::new(tunables, &mut masm, codegen_context, env, abi_sig);
+
+        let mut body_codegen = codegen.emit_prologue()?;
+        body_codegen.emit(body, validator)?;
+        let names = body_codegen.env.take_name_map();
+        let base = body_codegen.source_location.base;
+        let stack_maps = body_codegen.env.take_stack_maps();
+        let needs_gc_heap = body_codegen.env.needs_gc_heap;
+        let mut function =
+            CompiledFunction::new(masm.finalize(base)?, names, self.function_alignment());
+        function.set_stack_maps(stack_maps);
+        Ok(CompiledFunctionOutput {
+            function,
+            needs_gc_heap,
+        })
+    }
+
+    fn text_section_builder(&self, num_funcs: usize) -> Box<dyn TextSectionBuilder> {
+        Box::new(MachTextSectionBuilder::<
+            cranelift_codegen::isa::riscv64::inst::Inst,
+        >::new(num_funcs))
+    }
+
+    fn function_alignment(&self) -> u32 {
+        // See `cranelift_codegen::isa::TargetIsa::function_alignment`.
+        4
+    }
+
+    fn emit_unwind_info(
+        &self,
+        _result: &MachBufferFinalized<Final>,
+        _kind: cranelift_codegen::isa::unwind::UnwindInfoKind,
+    ) -> Result<Option<cranelift_codegen::isa::unwind::UnwindInfo>> {
+        // TODO: should fill this in with an actual implementation
+        Ok(None)
+    }
+
+    fn page_size_align_log2(&self) -> u8 {
+        debug_assert_eq!(1 << 12, 0x1000);
+        12
+    }
+}
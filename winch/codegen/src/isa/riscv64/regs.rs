@@ -0,0 +1,154 @@
+//! Riscv64 register definition.
+
+use crate::isa::reg::Reg;
+use crate::regset::RegBitSet;
+use regalloc2::{PReg, RegClass};
+
+/// FPR index bound.
+const MAX_FPR: u32 = 32;
+/// GPR index bound.
+const MAX_GPR: u32 = 32;
+
+/// Construct a X-register from an index.
+pub(crate) const fn xreg(num: u8) -> Reg {
+    assert!((num as u32) < MAX_GPR);
+    Reg::new(PReg::new(num as usize, RegClass::Int))
+}
+
+/// Construct a F-register from an index.
+pub(crate) const fn freg(num: u8) -> Reg {
+    assert!((num as u32) < MAX_FPR);
+    Reg::new(PReg::new(num as usize, RegClass::Float))
+}
+
+/// Zero register.
+pub(crate) const fn zero() -> Reg {
+    xreg(0)
+}
+
+/// Return address register.
+pub(crate) const fn ra() -> Reg {
+    xreg(1)
+}
+
+/// Stack pointer register.
+pub(crate) const fn sp() -> Reg {
+    xreg(2)
+}
+
+/// Global pointer register.
+const fn gp() -> Reg {
+    xreg(3)
+}
+
+/// Thread pointer register.
+const fn tp() -> Reg {
+    xreg(4)
+}
+
+/// Frame pointer register.
+pub(crate) const fn fp() -> Reg {
+    xreg(8)
+}
+
+/// The VM context register.
+pub(crate) const fn vmctx() -> Reg {
+    xreg(9)
+}
+
+/// First scratch register.
+const fn scratch0() -> Reg {
+    xreg(28)
+}
+
+/// Second scratch register.
+const fn scratch1() -> Reg {
+    xreg(29)
+}
+
+/// Temporary registers used by Cranelift's riscv64 emitter when expanding
+/// pseudo-instructions (e.g. large immediate offsets, `BrTable`, `Popcnt` or
+/// `Cltz`); they must never hold a value across instructions.
+const fn emit_tmp0() -> Reg {
+    xreg(30)
+}
+
+/// See [`emit_tmp0`]. The assembler also uses it as a temporary within short
+/// instruction sequences which don't involve the pseudo-instructions above,
+/// e.g. to materialize floating-point constants.
+pub(crate) const fn emit_tmp1() -> Reg {
+    xreg(31)
+}
+
+/// Floating point scratch register.
+const fn float_scratch() -> Reg {
+    freg(31)
+}
+
+/// Bitmask for non-allocatable GPR.
+const NON_ALLOCATABLE_GPR: u32 = (1 << zero().hw_enc())
+    | (1 << ra().hw_enc())
+    | (1 << sp().hw_enc())
+    | (1 << gp().hw_enc())
+    | (1 << tp().hw_enc())
+    | (1 << fp().hw_enc())
+    | (1 << vmctx().hw_enc())
+    | (1 << scratch0().hw_enc())
+    | (1 << scratch1().hw_enc())
+    | (1 << emit_tmp0().hw_enc())
+    | (1 << emit_tmp1().hw_enc());
+/// Bitmask to represent the available general purpose registers.
+const ALLOCATABLE_GPR: u32 = u32::MAX & !NON_ALLOCATABLE_GPR;
+
+/// Bitmask for non-allocatable FPR.
+/// All FPRs but the scratch register are allocatable, f10..=f17 are generally
+/// used for params and results.
+const NON_ALLOCATABLE_FPR: u32 = 1 << float_scratch().hw_enc();
+/// Bitmask to represent the available floating point registers.
+const ALLOCATABLE_FPR: u32 = u32::MAX & !NON_ALLOCATABLE_FPR;
+
+/// Allocatable scratch general purpose registers.
+const ALLOCATABLE_SCRATCH_GPR: u32 = (1 << scratch0().hw_enc()) | (1 << scratch1().hw_enc());
+/// Non-allocatable scratch general purpose registers.
+const NON_ALLOCATABLE_SCRATCH_GPR: u32 = u32::MAX & !ALLOCATABLE_SCRATCH_GPR;
+
+/// Allocatable scratch floating point registers.
+const ALLOCATABLE_SCRATCH_FPR: u32 = 1 << float_scratch().hw_enc();
+/// Non-allocatable scratch floating point registers.
+const NON_ALLOCATABLE_SCRATCH_FPR: u32 = u32::MAX & !ALLOCATABLE_SCRATCH_FPR;
+
+/// Bitset for allocatable general purpose registers.
+pub fn gpr_bit_set() -> RegBitSet {
+    RegBitSet::int(
+        ALLOCATABLE_GPR.into(),
+        NON_ALLOCATABLE_GPR.into(),
+        usize::try_from(MAX_GPR).unwrap(),
+    )
+}
+
+/// Bitset for allocatable floating point registers.
+pub fn fpr_bit_set() -> RegBitSet {
+    RegBitSet::float(
+        ALLOCATABLE_FPR.into(),
+        NON_ALLOCATABLE_FPR.into(),
+        usize::try_from(MAX_FPR).unwrap(),
+    )
+}
+
+/// Bitset for allocatable scratch general purpose registers.
+pub fn scratch_gpr_bitset() -> RegBitSet {
+    RegBitSet::int(
+        ALLOCATABLE_SCRATCH_GPR.into(),
+        NON_ALLOCATABLE_SCRATCH_GPR.into(),
+        usize::try_from(MAX_GPR).unwrap(),
+    )
+}
+
+/// Bitset for allocatable scratch floating point registers.
+pub fn scratch_fpr_bitset() -> RegBitSet {
+    RegBitSet::float(
+        ALLOCATABLE_SCRATCH_FPR.into(),
+        NON_ALLOCATABLE_SCRATCH_FPR.into(),
+        usize::try_from(MAX_FPR).unwrap(),
+    )
+}
@@ -6,7 +6,7 @@ use crate::{
     isa::{CallingConvention, reg::Reg},
 };
 use anyhow::{Result, bail};
-use wasmtime_environ::{WasmHeapType, WasmValType};
+use wasmtime_environ::{WasmHeapTopType, WasmValType};
 
 #[derive(Default)]
 pub(crate) struct X64ABI;
@@ -114,9 +114,13 @@ impl ABI for X64ABI {
 
     fn sizeof(ty: &WasmValType) -> u8 {
         match ty {
-            WasmValType::Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func | WasmHeapType::Extern => Self::word_bytes(),
-                ht => unimplemented!("Support for WasmHeapType: {ht}"),
+            WasmValType::Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func => Self::word_bytes(),
+                // GC references are 32-bit indices into the GC heap.
+                WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                    Self::word_bytes() / 2
+                }
+                _ => unimplemented!("Support for WasmHeapType: {}", rt.heap_type),
             },
             WasmValType::F64 | WasmValType::I64 => Self::word_bytes(),
             WasmValType::F32 | WasmValType::I32 => Self::word_bytes() / 2,
@@ -134,8 +138,11 @@ impl X64ABI {
         params_or_returns: ParamsOrReturns,
     ) -> Result<(ABIOperand, u32)> {
         let (reg, ty) = match wasm_arg {
-            ty @ WasmValType::Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func | WasmHeapType::Extern => (
+            ty @ WasmValType::Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func
+                | WasmHeapTopType::Any
+                | WasmHeapTopType::Extern
+                | WasmHeapTopType::Exn => (
                     Self::int_reg_for(index_env.next_gpr(), call_conv, params_or_returns),
                     ty,
                 ),
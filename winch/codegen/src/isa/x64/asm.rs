@@ -11,7 +11,8 @@ use crate::{
 };
 use cranelift_codegen::{
     CallInfo, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, MachInstEmitState,
-    MachLabel, PatchRegion, Writable,
+    MachLabel, PatchRegion, TryCallInfo, Writable,
+    binemit::Reloc,
     ir::{ExternalName, MemFlags, SourceLoc, TrapCode, Type, UserExternalNameRef, types},
     isa::{
         unwind::UnwindInst,
@@ -1599,17 +1600,90 @@ impl Assembler {
     }
 
     /// Emit a call to an unknown location through a register.
-    pub fn call_with_reg(&mut self, cc: CallingConvention, callee: Reg) {
+    ///
+    /// If `exception_handler` is given, the call site is registered in the
+    /// exception table, unwinding to the handler label.
+    pub fn call_with_reg(
+        &mut self,
+        cc: CallingConvention,
+        callee: Reg,
+        exception_handler: Option<MachLabel>,
+    ) {
+        let mut info = CallInfo::empty(RegMem::reg(callee.into()), cc.into());
+        let continuation = self.try_call_info(&mut info, exception_handler);
         self.emit(Inst::CallUnknown {
-            info: Box::new(CallInfo::empty(RegMem::reg(callee.into()), cc.into())),
+            info: Box::new(info),
         });
+        self.bind_continuation(continuation);
     }
 
     /// Emit a call to a locally defined function through an index.
-    pub fn call_with_name(&mut self, cc: CallingConvention, name: UserExternalNameRef) {
+    ///
+    /// See [`Assembler::call_with_reg`] for the meaning of
+    /// `exception_handler`.
+    pub fn call_with_name(
+        &mut self,
+        cc: CallingConvention,
+        name: UserExternalNameRef,
+        exception_handler: Option<MachLabel>,
+    ) {
+        let mut info = CallInfo::empty(ExternalName::user(name), cc.into());
+        let continuation = self.try_call_info(&mut info, exception_handler);
         self.emit(Inst::CallKnown {
-            info: Box::new(CallInfo::empty(ExternalName::user(name), cc.into())),
+            info: Box::new(info),
+        });
+        self.bind_continuation(continuation);
+    }
+
+    /// Turns the given call into a try-call if an exception handler is
+    /// given, returning the label of the normal-return continuation.
+    ///
+    /// Winch resolves tags in the landing pad itself, so the handler is
+    /// registered as a catch-all.
+    fn try_call_info<T>(
+        &mut self,
+        info: &mut CallInfo<T>,
+        exception_handler: Option<MachLabel>,
+    ) -> Option<MachLabel> {
+        let handler = exception_handler?;
+        let continuation = self.buffer.get_label();
+        info.try_call_info = Some(TryCallInfo {
+            continuation,
+            exception_dests: Box::new([(None.into(), handler)]),
         });
+        Some(continuation)
+    }
+
+    /// Binds the normal-return continuation of a try-call, which directly
+    /// follows the call.
+    fn bind_continuation(&mut self, continuation: Option<MachLabel>) {
+        if let Some(label) = continuation {
+            self.buffer.bind_label(label, &mut Default::default());
+        }
+    }
+
+    /// Emit a jump to a function whose address is stored in the `callee`
+    /// register, used to perform tail calls.
+    pub fn jmp_with_reg(&mut self, callee: Reg) {
+        let inst = asm::inst::jmpq_m::new(callee).into();
+        self.emit(Inst::External { inst });
+    }
+
+    /// Emit a jump to a locally defined function through an index, used to
+    /// perform tail calls.
+    pub fn jmp_with_name(&mut self, name: UserExternalNameRef) {
+        // The jump is not to a label within this function but to another
+        // function, so the displacement is resolved through a relocation, in
+        // the same way as in direct calls.
+        let inst = asm::inst::jmp_d32::new(0).into();
+        self.emit(Inst::External { inst });
+        let offset = self.buffer.cur_offset();
+        self.buffer.add_reloc_at_offset(
+            offset - 4,
+            Reloc::X86CallPCRel4,
+            &ExternalName::user(name),
+            -4,
+        );
     }
 
     /// Emits a conditional jump to the given label.
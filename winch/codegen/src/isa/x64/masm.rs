@@ -222,6 +222,21 @@ impl Masm for MacroAssembler {
         Ok(())
     }
 
+    fn landing_pad(&mut self, sp_offset: SPOffset, dst: WritableReg) -> Result<()> {
+        self.asm.mov_rr(rbp(), writable!(rsp()), OperandSize::S64);
+        if sp_offset.as_u32() > 0 {
+            self.asm.sub_ir(
+                i32::try_from(sp_offset.as_u32())?,
+                writable!(rsp()),
+                OperandSize::S64,
+            );
+        }
+        self.sp_offset = sp_offset.as_u32();
+        // The exception object is passed in the first payload register.
+        self.asm.mov_rr(regs::rax(), dst, OperandSize::S32);
+        Ok(())
+    }
+
     fn local_address(&mut self, local: &LocalSlot) -> Result<Address> {
         let (reg, offset) = if local.addressed_from_sp() {
             let offset = self
@@ -325,6 +340,7 @@ impl Masm for MacroAssembler {
     fn call(
         &mut self,
         stack_args_size: u32,
+        exception_handler: Option<MachLabel>,
         mut load_callee: impl FnMut(&mut Self) -> Result<(CalleeKind, CallingConvention)>,
     ) -> Result<u32> {
         let alignment: u32 = <Self::ABI as abi::ABI>::call_stack_align().into();
@@ -335,12 +351,24 @@ impl Masm for MacroAssembler {
         self.reserve_stack(total_stack)?;
         let (callee, cc) = load_callee(self)?;
         match callee {
-            CalleeKind::Indirect(reg) => self.asm.call_with_reg(cc, reg),
-            CalleeKind::Direct(idx) => self.asm.call_with_name(cc, idx),
+            CalleeKind::Indirect(reg) => self.asm.call_with_reg(cc, reg, exception_handler),
+            CalleeKind::Direct(idx) => self.asm.call_with_name(cc, idx, exception_handler),
         };
         Ok(total_stack)
     }
 
+    fn return_call(&mut self, callee: CalleeKind) -> Result<()> {
+        // Discard the current frame, which leaves the stack pointer pointing
+        // to the return address of the current function.
+        self.asm.mov_rr(rbp(), writable!(rsp()), OperandSize::S64);
+        self.asm.pop_r(writable!(rbp()));
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.jmp_with_reg(reg),
+            CalleeKind::Direct(idx) => self.asm.jmp_with_name(idx),
+        };
+        Ok(())
+    }
+
     fn load_ptr(&mut self, src: Self::Address, dst: WritableReg) -> Result<()> {
         self.load(src, dst, self.ptr_size)
     }
@@ -5,7 +5,7 @@ use crate::{
 
 use crate::frame::{DefinedLocals, Frame};
 use crate::isa::x64::masm::MacroAssembler as X64Masm;
-use crate::isa::{Builder, TargetIsa};
+use crate::isa::{Builder, CompiledFunctionOutput, TargetIsa};
 use crate::masm::MacroAssembler;
 use crate::regalloc::RegAlloc;
 use crate::stack::Stack;
@@ -16,7 +16,9 @@ use cranelift_codegen::{MachTextSectionBuilder, TextSectionBuilder};
 use target_lexicon::Triple;
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType,
+};
 
 use self::regs::{fpr_bit_set, gpr_bit_set};
 
@@ -85,6 +87,7 @@ impl TargetIsa for X64 {
 
     fn compile_function(
         &self,
+        index: DefinedFuncIndex,
         sig: &WasmFuncType,
         body: &FunctionBody,
         translation: &ModuleTranslation,
@@ -92,7 +95,7 @@ impl TargetIsa for X64 {
         builtins: &mut BuiltinFunctions,
         validator: &mut FuncValidator<ValidatorResources>,
         tunables: &Tunables,
-    ) -> Result<CompiledFunction> {
+    ) -> Result<CompiledFunctionOutput> {
         let pointer_bytes = self.pointer_bytes();
         let vmoffsets = VMOffsets::new(pointer_bytes, &translation.module);
 
@@ -108,6 +111,7 @@ impl TargetIsa for X64 {
 
         let env = FuncEnv::new(
             &vmoffsets,
+            index,
             translation,
             types,
             builtins,
@@ -128,11 +132,15 @@ impl TargetIsa for X64 {
         let base = body_codegen.source_location.base;
 
         let names = body_codegen.env.take_name_map();
-        Ok(CompiledFunction::new(
-            masm.finalize(base)?,
-            names,
-            self.function_alignment(),
-        ))
+        let stack_maps = body_codegen.env.take_stack_maps();
+        let needs_gc_heap = body_codegen.env.needs_gc_heap;
+        let mut function =
+            CompiledFunction::new(masm.finalize(base)?, names, self.function_alignment());
+        function.set_stack_maps(stack_maps);
+        Ok(CompiledFunctionOutput {
+            function,
+            needs_gc_heap,
+        })
     }
 
     fn text_section_builder(&self, num_funcs: usize) -> Box<dyn TextSectionBuilder> {
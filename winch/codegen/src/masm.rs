@@ -11,7 +11,7 @@ use cranelift_codegen::{
     ir::{Endianness, MemFlags, RelSourceLoc, SourceLoc, UserExternalNameRef},
 };
 use std::{fmt::Debug, ops::Range};
-use wasmtime_environ::{PtrSize, WasmHeapType, WasmRefType, WasmValType};
+use wasmtime_environ::{PtrSize, WasmHeapTopType, WasmValType};
 
 pub(crate) use cranelift_codegen::ir::TrapCode;
 
@@ -1416,6 +1416,13 @@ pub(crate) trait MacroAssembler {
     /// when dealing with unreachable code.
     fn reset_stack_pointer(&mut self, offset: SPOffset) -> Result<()>;
 
+    /// Emit the entry of an exception landing pad.
+    ///
+    /// The unwinder only restores the frame pointer when resuming at a
+    /// landing pad, so the stack pointer is rederived from it, to the given
+    /// offset, and the thrown exception object is moved to `dst`.
+    fn landing_pad(&mut self, sp_offset: SPOffset, dst: WritableReg) -> Result<()>;
+
     /// Get the address of a local slot.
     fn local_address(&mut self, local: &LocalSlot) -> Result<Self::Address>;
 
@@ -1438,12 +1445,26 @@ pub(crate) trait MacroAssembler {
     fn address_at_reg(&self, reg: Reg, offset: u32) -> Result<Self::Address>;
 
     /// Emit a function call to either a local or external function.
+    ///
+    /// If an `exception_handler` is given, the call site is registered in
+    /// the exception table so that Wasm exceptions thrown by the callee
+    /// unwind to it.
     fn call(
         &mut self,
         stack_args_size: u32,
+        exception_handler: Option<MachLabel>,
         f: impl FnMut(&mut Self) -> Result<(CalleeKind, CallingConvention)>,
     ) -> Result<u32>;
 
+    /// Emit a tail call to either a local or external function.
+    ///
+    /// Tears down the current frame, regardless of the current stack pointer
+    /// offset, and jumps to the callee, which returns directly to the caller
+    /// of the current function. The callee's stack arguments, if any, are
+    /// expected to have been written to the current function's incoming
+    /// argument area.
+    fn return_call(&mut self, callee: CalleeKind) -> Result<()>;
+
     /// Acquire a scratch register and execute the given callback.
     fn with_scratch<T: ScratchType, R>(&mut self, f: impl FnOnce(&mut Self, Scratch) -> R) -> R;
 
@@ -1455,12 +1476,18 @@ pub(crate) trait MacroAssembler {
         f: impl FnOnce(&mut Self, Scratch) -> R,
     ) -> R {
         match ty {
-            WasmValType::I32
-            | WasmValType::I64
-            | WasmValType::Ref(WasmRefType {
-                heap_type: WasmHeapType::Func,
-                ..
-            }) => self.with_scratch::<IntScratch, _>(f),
+            WasmValType::I32 | WasmValType::I64 => self.with_scratch::<IntScratch, _>(f),
+            WasmValType::Ref(rt)
+                if matches!(
+                    rt.heap_type.top(),
+                    WasmHeapTopType::Func
+                        | WasmHeapTopType::Any
+                        | WasmHeapTopType::Extern
+                        | WasmHeapTopType::Exn
+                ) =>
+            {
+                self.with_scratch::<IntScratch, _>(f)
+            }
             WasmValType::F32 | WasmValType::F64 | WasmValType::V128 => {
                 self.with_scratch::<FloatScratch, _>(f)
             }
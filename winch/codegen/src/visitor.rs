@@ -4,13 +4,13 @@
 //! which validates and dispatches to the corresponding
 //! machine code emitter.
 
-use crate::abi::RetArea;
+use crate::abi::{ABIResults, RetArea};
 use crate::codegen::{
-    Callee, CodeGen, CodeGenError, ConditionalBranch, ControlStackFrame, Emission, FnCall,
-    UnconditionalBranch, control_index,
+    Callee, CodeGen, CodeGenContext, CodeGenError, ConditionalBranch, ControlStackFrame, Emission,
+    FnCall, UnconditionalBranch, control_index, non_null,
 };
 use crate::masm::{
-    AtomicWaitKind, DivKind, Extend, ExtractLaneKind, FloatCmpKind, IntCmpKind, LoadKind,
+    AtomicWaitKind, DivKind, Extend, ExtractLaneKind, FloatCmpKind, Imm, IntCmpKind, LoadKind,
     MacroAssembler, MulWideKind, OperandSize, RegImm, RemKind, ReplaceLaneKind, RmwOp,
     RoundingMode, SPOffset, ShiftKind, Signed, SplatKind, SplatLoadKind, StoreKind, TruncKind,
     V128AbsKind, V128AddKind, V128ConvertKind, V128ExtAddKind, V128ExtMulKind, V128ExtendKind,
@@ -24,12 +24,12 @@ use anyhow::{Result, anyhow, bail, ensure};
 use regalloc2::RegClass;
 use smallvec::{SmallVec, smallvec};
 use wasmparser::{
-    BlockType, BrTable, Ieee32, Ieee64, MemArg, V128, VisitOperator, VisitSimdOperator,
+    BlockType, BrTable, Ieee32, Ieee64, MemArg, TryTable, V128, VisitOperator, VisitSimdOperator,
 };
-use wasmtime_cranelift::TRAP_INDIRECT_CALL_TO_NULL;
+use wasmtime_cranelift::{TRAP_INDIRECT_CALL_TO_NULL, TRAP_NULL_REFERENCE};
 use wasmtime_environ::{
-    FUNCREF_INIT_BIT, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, TypeIndex, WasmHeapType,
-    WasmValType,
+    FUNCREF_INIT_BIT, FuncIndex, GlobalIndex, I31_DISCRIMINANT, MemoryIndex, TableIndex, TagIndex,
+    TypeIndex, WasmHeapTopType, WasmHeapType, WasmRefType, WasmValType,
 };
 
 /// A macro to define unsupported WebAssembly operators.
@@ -207,6 +207,49 @@ macro_rules! def_unsupported {
     (emit Drop $($rest:tt)*) => {};
     (emit BrTable $($rest:tt)*) => {};
     (emit CallIndirect $($rest:tt)*) => {};
+    (emit ReturnCall $($rest:tt)*) => {};
+    (emit ReturnCallIndirect $($rest:tt)*) => {};
+    (emit CallRef $($rest:tt)*) => {};
+    (emit ReturnCallRef $($rest:tt)*) => {};
+    (emit RefNull $($rest:tt)*) => {};
+    (emit RefIsNull $($rest:tt)*) => {};
+    (emit RefFunc $($rest:tt)*) => {};
+    (emit RefAsNonNull $($rest:tt)*) => {};
+    (emit BrOnNull $($rest:tt)*) => {};
+    (emit BrOnNonNull $($rest:tt)*) => {};
+    (emit TypedSelect $($rest:tt)*) => {};
+    (emit RefEq $($rest:tt)*) => {};
+    (emit StructNew $($rest:tt)*) => {};
+    (emit StructNewDefault $($rest:tt)*) => {};
+    (emit StructGet $($rest:tt)*) => {};
+    (emit StructGetS $($rest:tt)*) => {};
+    (emit StructGetU $($rest:tt)*) => {};
+    (emit StructSet $($rest:tt)*) => {};
+    (emit ArrayNew $($rest:tt)*) => {};
+    (emit ArrayNewDefault $($rest:tt)*) => {};
+    (emit ArrayNewFixed $($rest:tt)*) => {};
+    (emit ArrayNewData $($rest:tt)*) => {};
+    (emit ArrayNewElem $($rest:tt)*) => {};
+    (emit ArrayGet $($rest:tt)*) => {};
+    (emit ArrayGetS $($rest:tt)*) => {};
+    (emit ArrayGetU $($rest:tt)*) => {};
+    (emit ArraySet $($rest:tt)*) => {};
+    (emit ArrayLen $($rest:tt)*) => {};
+    (emit ArrayFill $($rest:tt)*) => {};
+    (emit ArrayCopy $($rest:tt)*) => {};
+    (emit ArrayInitData $($rest:tt)*) => {};
+    (emit ArrayInitElem $($rest:tt)*) => {};
+    (emit RefTestNonNull $($rest:tt)*) => {};
+    (emit RefTestNullable $($rest:tt)*) => {};
+    (emit RefCastNonNull $($rest:tt)*) => {};
+    (emit RefCastNullable $($rest:tt)*) => {};
+    (emit BrOnCast $($rest:tt)*) => {};
+    (emit BrOnCastFail $($rest:tt)*) => {};
+    (emit AnyConvertExtern $($rest:tt)*) => {};
+    (emit ExternConvertAny $($rest:tt)*) => {};
+    (emit RefI31 $($rest:tt)*) => {};
+    (emit I31GetS $($rest:tt)*) => {};
+    (emit I31GetU $($rest:tt)*) => {};
     (emit TableInit $($rest:tt)*) => {};
     (emit TableCopy $($rest:tt)*) => {};
     (emit TableGet $($rest:tt)*) => {};
@@ -255,6 +298,9 @@ macro_rules! def_unsupported {
     (emit V128Load $($rest:tt)*) => {};
     (emit V128Store $($rest:tt)*) => {};
     (emit I64Add128 $($rest:tt)*) => {};
+    (emit TryTable $($rest:tt)*) => {};
+    (emit Throw $($rest:tt)*) => {};
+    (emit ThrowRef $($rest:tt)*) => {};
     (emit I64Sub128 $($rest:tt)*) => {};
     (emit I64MulWideS $($rest:tt)*) => {};
     (emit I64MulWideU $($rest:tt)*) => {};
@@ -1444,7 +1490,9 @@ where
     }
 
     fn visit_end(&mut self) -> Self::Output {
-        if !self.context.reachable {
+        if self.is_try_table_end() {
+            self.emit_try_table_end()
+        } else if !self.context.reachable {
             self.handle_unreachable_end()
         } else {
             let mut control = self.pop_control_frame()?;
@@ -1602,8 +1650,11 @@ where
         let slot = context.frame.get_wasm_local(index);
         match slot.ty {
             I32 | I64 | F32 | F64 | V128 => context.stack.push(Val::local(index, slot.ty)),
-            Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func => context.stack.push(Val::local(index, slot.ty)),
+            Ref(rt) => match rt.heap_type.top() {
+                WasmHeapTopType::Func
+                | WasmHeapTopType::Any
+                | WasmHeapTopType::Extern
+                | WasmHeapTopType::Exn => context.stack.push(Val::local(index, slot.ty)),
                 _ => bail!(CodeGenError::unsupported_wasm_type()),
             },
         }
@@ -1618,7 +1669,9 @@ where
     }
 
     fn visit_call(&mut self, index: u32) -> Self::Output {
-        let callee = self.env.callee_from_index(FuncIndex::from_u32(index));
+        let callee = self
+            .env
+            .callee_from_index(FuncIndex::from_u32(index), self.tunables);
         FnCall::emit::<M>(&mut self.env, self.masm, &mut self.context, callee)?;
         Ok(())
     }
@@ -1655,6 +1708,51 @@ where
         Ok(())
     }
 
+    fn visit_return_call(&mut self, index: u32) -> Self::Output {
+        let callee = self
+            .env
+            .callee_from_index(FuncIndex::from_u32(index), self.tunables);
+        self.emit_return_call(callee)
+    }
+
+    fn visit_return_call_indirect(&mut self, type_index: u32, table_index: u32) -> Self::Output {
+        // See the comment in `visit_call_indirect` about spilling.
+        self.context.spill(self.masm)?;
+
+        let type_index = TypeIndex::from_u32(type_index);
+        let table_index = TableIndex::from_u32(table_index);
+
+        self.emit_lazy_init_funcref(table_index)?;
+
+        let funcref_ptr = self
+            .context
+            .stack
+            .peek()
+            .map(|v| v.unwrap_reg())
+            .ok_or_else(|| CodeGenError::missing_values_in_stack())?;
+        self.masm
+            .trapz(funcref_ptr.into(), TRAP_INDIRECT_CALL_TO_NULL)?;
+        self.emit_typecheck_funcref(funcref_ptr.into(), type_index)?;
+
+        let callee = self.env.funcref(type_index);
+        self.emit_return_call(callee)
+    }
+
+    fn visit_call_ref(&mut self, type_index: u32) -> Self::Output {
+        // The function reference is statically known to be of the right
+        // type, so only a null check is needed.
+        self.emit_ref_as_non_null()?;
+        let callee = self.env.funcref(TypeIndex::from_u32(type_index));
+        FnCall::emit::<M>(&mut self.env, self.masm, &mut self.context, callee)?;
+        Ok(())
+    }
+
+    fn visit_return_call_ref(&mut self, type_index: u32) -> Self::Output {
+        self.emit_ref_as_non_null()?;
+        let callee = self.env.funcref(TypeIndex::from_u32(type_index));
+        self.emit_return_call(callee)
+    }
+
     fn visit_table_init(&mut self, elem: u32, table: u32) -> Self::Output {
         let at = self.context.stack.ensure_index_at(3)?;
 
@@ -1691,10 +1789,23 @@ where
     fn visit_table_get(&mut self, table: u32) -> Self::Output {
         let table_index = TableIndex::from_u32(table);
         let table = self.env.table(table_index);
-        let heap_type = table.ref_type.heap_type;
-
-        match heap_type {
-            WasmHeapType::Func => self.emit_lazy_init_funcref(table_index),
+        let ref_type = table.ref_type;
+
+        match ref_type.heap_type.top() {
+            WasmHeapTopType::Func => {
+                self.emit_lazy_init_funcref(table_index)?;
+                // The lazy-init builtin produces a raw pointer; type it with
+                // the table's element type so that typed reference
+                // instructions can consume it.
+                let funcref = self.context.pop_to_reg(self.masm, None)?;
+                self.context
+                    .stack
+                    .push(TypedReg::new(WasmValType::Ref(ref_type), funcref.reg).into());
+                Ok(())
+            }
+            WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                self.emit_gc_table_get(table_index, ref_type)
+            }
             _ => Err(anyhow!(CodeGenError::unsupported_wasm_type())),
         }
     }
@@ -1702,8 +1813,11 @@ where
     fn visit_table_grow(&mut self, table: u32) -> Self::Output {
         let table_index = TableIndex::from_u32(table);
         let table_ty = self.env.table(table_index);
-        let builtin = match table_ty.ref_type.heap_type {
-            WasmHeapType::Func => self.env.builtins.table_grow_func_ref::<M::ABI, M::Ptr>()?,
+        let builtin = match table_ty.ref_type.heap_type.top() {
+            WasmHeapTopType::Func => self.env.builtins.table_grow_func_ref::<M::ABI, M::Ptr>()?,
+            WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                self.gc_table_grow_builtin()?
+            }
             _ => bail!(CodeGenError::unsupported_wasm_type()),
         };
 
@@ -1736,12 +1850,13 @@ where
         let table_index = TableIndex::from_u32(table);
         let table_ty = self.env.table(table_index);
 
-        ensure!(
-            table_ty.ref_type.heap_type == WasmHeapType::Func,
-            CodeGenError::unsupported_wasm_type()
-        );
-
-        let builtin = self.env.builtins.table_fill_func_ref::<M::ABI, M::Ptr>()?;
+        let builtin = match table_ty.ref_type.heap_type.top() {
+            WasmHeapTopType::Func => self.env.builtins.table_fill_func_ref::<M::ABI, M::Ptr>()?,
+            WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                self.gc_table_fill_builtin()?
+            }
+            _ => bail!(CodeGenError::unsupported_wasm_type()),
+        };
 
         let at = self.context.stack.ensure_index_at(3)?;
 
@@ -1759,9 +1874,9 @@ where
         let ptr_type = self.env.ptr_type();
         let table_index = TableIndex::from_u32(table);
         let table_data = self.env.resolve_table_data(table_index);
-        let table = self.env.table(table_index);
-        match table.ref_type.heap_type {
-            WasmHeapType::Func => {
+        let ref_type = self.env.table(table_index).ref_type;
+        match ref_type.heap_type.top() {
+            WasmHeapTopType::Func => {
                 ensure!(
                     self.tunables.table_lazy_init,
                     CodeGenError::unsupported_table_eager_init()
@@ -1786,6 +1901,9 @@ where
                 self.context.free_reg(base);
                 Ok(())
             }
+            WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                self.emit_gc_table_set(table_index, ref_type)
+            }
             _ => Err(anyhow!(CodeGenError::unsupported_wasm_type())),
         }
     }
@@ -1943,7 +2061,22 @@ where
         )?);
 
         self.maybe_emit_epoch_check()?;
-        self.maybe_emit_fuel_check()
+        self.maybe_emit_fuel_check()?;
+        self.maybe_emit_tier_up_check()
+    }
+
+    fn visit_try_table(&mut self, try_table: TryTable) -> Self::Output {
+        self.emit_try_table(try_table)
+    }
+
+    fn visit_throw(&mut self, tag_index: u32) -> Self::Output {
+        self.emit_throw(TagIndex::from_u32(tag_index))?;
+        self.visit_unreachable()
+    }
+
+    fn visit_throw_ref(&mut self) -> Self::Output {
+        self.emit_throw_ref()?;
+        self.visit_unreachable()
     }
 
     fn visit_br(&mut self, depth: u32) -> Self::Output {
@@ -1976,22 +2109,7 @@ where
             frame.top_abi_results::<M, _>(
                 &mut self.context,
                 self.masm,
-                |results, context, masm| {
-                    // In the case of `br_if` there's a possibility that we'll
-                    // exit early from the block or fallthrough, for
-                    // a fallthrough, we cannot rely on the pre-computed return area;
-                    // it must be recalculated so that any values that are
-                    // generated are correctly placed near the current stack
-                    // pointer.
-                    if results.on_stack() {
-                        let stack_consumed = context.stack.sizeof(results.stack_operands_len());
-                        let base = masm.sp_offset()?.as_u32() - stack_consumed;
-                        let offs = base + results.size();
-                        Ok(Some(RetArea::sp(SPOffset::from_u32(offs))))
-                    } else {
-                        Ok(None)
-                    }
-                },
+                conditional_branch_ret_area,
             )?;
             top
         };
@@ -2022,6 +2140,92 @@ where
         Ok(())
     }
 
+    fn visit_br_on_null(&mut self, depth: u32) -> Self::Output {
+        let index = control_index(depth, self.control_frames.len())?;
+        let frame = &mut self.control_frames[index];
+        frame.set_as_target();
+
+        // The reference is not part of the results of the branch, so it's
+        // popped before handling them, in the same way as the condition of
+        // `br_if`.
+        let top = self.context.without::<Result<TypedReg>, M, _>(
+            frame.results::<M>()?.regs(),
+            self.masm,
+            |ctx, masm| ctx.pop_to_reg(masm, None),
+        )??;
+        self.context.spill(self.masm)?;
+        frame.top_abi_results::<M, _>(&mut self.context, self.masm, conditional_branch_ret_area)?;
+
+        let current_sp_offset = self.masm.sp_offset()?;
+        let unbalanced = frame.unbalanced(self.masm)?;
+        let (label, cmp) = if unbalanced {
+            (self.masm.get_label()?, IntCmpKind::Ne)
+        } else {
+            (*frame.label(), IntCmpKind::Eq)
+        };
+
+        self.masm
+            .branch(cmp, top.reg, top.reg.into(), label, top.ty.try_into()?)?;
+
+        if unbalanced {
+            self.context
+                .br::<_, _, ConditionalBranch>(frame, self.masm, |_, _, _| Ok(()))?;
+            self.masm.reset_stack_pointer(current_sp_offset)?;
+            self.masm.bind(label)?;
+        }
+
+        // On fallthrough the reference is known to be non-null.
+        self.context
+            .stack
+            .push(TypedReg::new(non_null(top.ty)?, top.reg).into());
+        Ok(())
+    }
+
+    fn visit_br_on_non_null(&mut self, depth: u32) -> Self::Output {
+        let index = control_index(depth, self.control_frames.len())?;
+        let frame = &mut self.control_frames[index];
+        frame.set_as_target();
+
+        // The reference is the last result of the branch, so it's kept in the
+        // value stack and a copy of it is used as the branch condition.
+        let cond = self.context.without::<Result<Reg>, M, _>(
+            frame.results::<M>()?.regs(),
+            self.masm,
+            |ctx, masm| {
+                let top = ctx.pop_to_reg(masm, None)?;
+                let cond = ctx.any_gpr(masm)?;
+                masm.mov(writable!(cond), top.reg.into(), top.ty.try_into()?)?;
+                ctx.stack
+                    .push(TypedReg::new(non_null(top.ty)?, top.reg).into());
+                Ok(cond)
+            },
+        )??;
+        self.context.spill(self.masm)?;
+        frame.top_abi_results::<M, _>(&mut self.context, self.masm, conditional_branch_ret_area)?;
+
+        let current_sp_offset = self.masm.sp_offset()?;
+        let unbalanced = frame.unbalanced(self.masm)?;
+        let (label, cmp) = if unbalanced {
+            (self.masm.get_label()?, IntCmpKind::Eq)
+        } else {
+            (*frame.label(), IntCmpKind::Ne)
+        };
+
+        self.masm
+            .branch(cmp, cond, cond.into(), label, OperandSize::S64)?;
+        self.context.free_reg(cond);
+
+        if unbalanced {
+            self.context
+                .br::<_, _, ConditionalBranch>(frame, self.masm, |_, _, _| Ok(()))?;
+            self.masm.reset_stack_pointer(current_sp_offset)?;
+            self.masm.bind(label)?;
+        }
+
+        // On fallthrough the reference is null and it's dropped.
+        self.context.pop_and_free(self.masm)
+    }
+
     fn visit_br_table(&mut self, targets: BrTable<'a>) -> Self::Output {
         // +1 to account for the default target.
         let len = targets.len() + 1;
@@ -2154,6 +2358,9 @@ where
 
     fn visit_global_get(&mut self, global_index: u32) -> Self::Output {
         let index = GlobalIndex::from_u32(global_index);
+        if let Some(ty) = self.gc_ref_global_type(index) {
+            return self.emit_gc_global_get(index, ty);
+        }
         let (ty, base, offset) = self.emit_get_global_addr(index)?;
         let addr = self.masm.address_at_reg(base, offset)?;
         let dst = self.context.reg_for_type(ty, self.masm)?;
@@ -2167,6 +2374,9 @@ where
 
     fn visit_global_set(&mut self, global_index: u32) -> Self::Output {
         let index = GlobalIndex::from_u32(global_index);
+        if let Some(ty) = self.gc_ref_global_type(index) {
+            return self.emit_gc_global_set(index, ty);
+        }
         let (ty, base, offset) = self.emit_get_global_addr(index)?;
         let addr = self.masm.address_at_reg(base, offset)?;
 
@@ -2207,6 +2417,291 @@ where
         Ok(())
     }
 
+    fn visit_ref_null(&mut self, hty: wasmparser::HeapType) -> Self::Output {
+        let heap_type = self.env.convert_heap_type(hty)?;
+        let ty = WasmValType::Ref(WasmRefType {
+            nullable: true,
+            heap_type,
+        });
+        let reg = self.context.reg_for_type(ty, self.masm)?;
+        self.masm
+            .mov(writable!(reg), RegImm::i64(0), ty.try_into()?)?;
+        self.context.stack.push(TypedReg::new(ty, reg).into());
+        Ok(())
+    }
+
+    fn visit_ref_is_null(&mut self) -> Self::Output {
+        // GC references are 32 bits wide, while function references are
+        // pointers.
+        let size: OperandSize = self
+            .context
+            .stack
+            .peek()
+            .ok_or_else(|| CodeGenError::missing_values_in_stack())?
+            .ty()
+            .try_into()?;
+        self.context.unop(self.masm, |masm, reg| {
+            masm.cmp_with_set(writable!(reg), RegImm::i64(0), IntCmpKind::Eq, size)?;
+            Ok(TypedReg::i32(reg))
+        })
+    }
+
+    fn visit_ref_func(&mut self, function_index: u32) -> Self::Output {
+        let builtin = self.env.builtins.ref_func::<M::ABI, M::Ptr>()?;
+        self.context.stack.extend([function_index.try_into()?]);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(builtin),
+        )?;
+        // The builtin returns a pointer, which is the representation of
+        // function references.
+        let funcref = self.context.pop_to_reg(self.masm, None)?;
+        let ty = WasmValType::Ref(WasmRefType {
+            nullable: false,
+            heap_type: WasmHeapType::Func,
+        });
+        self.context
+            .stack
+            .push(TypedReg::new(ty, funcref.reg).into());
+        Ok(())
+    }
+
+    fn visit_ref_as_non_null(&mut self) -> Self::Output {
+        self.emit_ref_as_non_null()
+    }
+
+    fn visit_typed_select(&mut self, _ty: wasmparser::ValType) -> Self::Output {
+        // The operand sizes are derived from the types in the value stack.
+        self.visit_select()
+    }
+
+    fn visit_ref_eq(&mut self) -> Self::Output {
+        // Only references to the `eq` hierarchy can be compared, which are
+        // represented as 32-bit GC references.
+        self.cmp_i32s(IntCmpKind::Eq)
+    }
+
+    fn visit_struct_new(&mut self, struct_type_index: u32) -> Self::Output {
+        self.emit_struct_new(TypeIndex::from_u32(struct_type_index), false)
+    }
+
+    fn visit_struct_new_default(&mut self, struct_type_index: u32) -> Self::Output {
+        self.emit_struct_new(TypeIndex::from_u32(struct_type_index), true)
+    }
+
+    fn visit_struct_get(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.emit_struct_get(TypeIndex::from_u32(struct_type_index), field_index, false)
+    }
+
+    fn visit_struct_get_s(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.emit_struct_get(TypeIndex::from_u32(struct_type_index), field_index, true)
+    }
+
+    fn visit_struct_get_u(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.emit_struct_get(TypeIndex::from_u32(struct_type_index), field_index, false)
+    }
+
+    fn visit_struct_set(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.emit_struct_set(TypeIndex::from_u32(struct_type_index), field_index)
+    }
+
+    fn visit_array_new(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_new(TypeIndex::from_u32(array_type_index), false)
+    }
+
+    fn visit_array_new_default(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_new(TypeIndex::from_u32(array_type_index), true)
+    }
+
+    fn visit_array_new_fixed(&mut self, array_type_index: u32, array_size: u32) -> Self::Output {
+        self.emit_array_new_fixed(TypeIndex::from_u32(array_type_index), array_size)
+    }
+
+    fn visit_array_new_data(
+        &mut self,
+        array_type_index: u32,
+        array_data_index: u32,
+    ) -> Self::Output {
+        self.emit_array_new_data(TypeIndex::from_u32(array_type_index), array_data_index)
+    }
+
+    fn visit_array_new_elem(
+        &mut self,
+        array_type_index: u32,
+        array_elem_index: u32,
+    ) -> Self::Output {
+        self.emit_array_new_elem(TypeIndex::from_u32(array_type_index), array_elem_index)
+    }
+
+    fn visit_array_get(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_get(TypeIndex::from_u32(array_type_index), false)
+    }
+
+    fn visit_array_get_s(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_get(TypeIndex::from_u32(array_type_index), true)
+    }
+
+    fn visit_array_get_u(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_get(TypeIndex::from_u32(array_type_index), false)
+    }
+
+    fn visit_array_set(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_set(TypeIndex::from_u32(array_type_index))
+    }
+
+    fn visit_array_len(&mut self) -> Self::Output {
+        self.emit_array_len()
+    }
+
+    fn visit_array_fill(&mut self, array_type_index: u32) -> Self::Output {
+        self.emit_array_fill(TypeIndex::from_u32(array_type_index))
+    }
+
+    fn visit_array_copy(&mut self, _dst: u32, _src: u32) -> Self::Output {
+        self.emit_array_copy()
+    }
+
+    fn visit_array_init_data(
+        &mut self,
+        array_type_index: u32,
+        array_data_index: u32,
+    ) -> Self::Output {
+        self.emit_array_init_data(TypeIndex::from_u32(array_type_index), array_data_index)
+    }
+
+    fn visit_array_init_elem(
+        &mut self,
+        array_type_index: u32,
+        array_elem_index: u32,
+    ) -> Self::Output {
+        self.emit_array_init_elem(TypeIndex::from_u32(array_type_index), array_elem_index)
+    }
+
+    fn visit_ref_test_non_null(&mut self, hty: wasmparser::HeapType) -> Self::Output {
+        let heap_type = self.env.convert_heap_type(hty)?;
+        self.emit_ref_test(WasmRefType {
+            nullable: false,
+            heap_type,
+        })
+    }
+
+    fn visit_ref_test_nullable(&mut self, hty: wasmparser::HeapType) -> Self::Output {
+        let heap_type = self.env.convert_heap_type(hty)?;
+        self.emit_ref_test(WasmRefType {
+            nullable: true,
+            heap_type,
+        })
+    }
+
+    fn visit_ref_cast_non_null(&mut self, hty: wasmparser::HeapType) -> Self::Output {
+        let heap_type = self.env.convert_heap_type(hty)?;
+        self.emit_ref_cast(WasmRefType {
+            nullable: false,
+            heap_type,
+        })
+    }
+
+    fn visit_ref_cast_nullable(&mut self, hty: wasmparser::HeapType) -> Self::Output {
+        let heap_type = self.env.convert_heap_type(hty)?;
+        self.emit_ref_cast(WasmRefType {
+            nullable: true,
+            heap_type,
+        })
+    }
+
+    fn visit_br_on_cast(
+        &mut self,
+        relative_depth: u32,
+        from_ref_type: wasmparser::RefType,
+        to_ref_type: wasmparser::RefType,
+    ) -> Self::Output {
+        let from = self.env.convert_ref_type(from_ref_type)?;
+        let to = self.env.convert_ref_type(to_ref_type)?;
+        self.emit_br_on_cast_cond(to, false)?;
+        self.visit_br_if(relative_depth)?;
+        // On fallthrough, the cast failed: the reference can only be null if
+        // the target type isn't nullable.
+        self.emit_retype_top(WasmValType::Ref(WasmRefType {
+            nullable: from.nullable && !to.nullable,
+            heap_type: from.heap_type,
+        }))
+    }
+
+    fn visit_br_on_cast_fail(
+        &mut self,
+        relative_depth: u32,
+        _from_ref_type: wasmparser::RefType,
+        to_ref_type: wasmparser::RefType,
+    ) -> Self::Output {
+        let to = self.env.convert_ref_type(to_ref_type)?;
+        self.emit_br_on_cast_cond(to, true)?;
+        self.visit_br_if(relative_depth)?;
+        // On fallthrough, the cast succeeded.
+        self.emit_retype_top(WasmValType::Ref(to))
+    }
+
+    fn visit_any_convert_extern(&mut self) -> Self::Output {
+        // Both hierarchies share the same representation.
+        let ty = self
+            .context
+            .stack
+            .peek()
+            .ok_or_else(|| CodeGenError::missing_values_in_stack())?
+            .ty();
+        self.emit_retype_top(WasmValType::Ref(WasmRefType {
+            nullable: ty.unwrap_ref_type().nullable,
+            heap_type: WasmHeapType::Any,
+        }))
+    }
+
+    fn visit_extern_convert_any(&mut self) -> Self::Output {
+        let ty = self
+            .context
+            .stack
+            .peek()
+            .ok_or_else(|| CodeGenError::missing_values_in_stack())?
+            .ty();
+        self.emit_retype_top(WasmValType::Ref(WasmRefType {
+            nullable: ty.unwrap_ref_type().nullable,
+            heap_type: WasmHeapType::Extern,
+        }))
+    }
+
+    fn visit_ref_i31(&mut self) -> Self::Output {
+        self.context.unop(self.masm, |masm, reg| {
+            masm.shift_ir(
+                writable!(reg),
+                Imm::i32(1),
+                reg,
+                ShiftKind::Shl,
+                OperandSize::S32,
+            )?;
+            masm.or(
+                writable!(reg),
+                reg,
+                RegImm::i32(I31_DISCRIMINANT as i32),
+                OperandSize::S32,
+            )?;
+            Ok(TypedReg::new(
+                WasmValType::Ref(WasmRefType {
+                    nullable: false,
+                    heap_type: WasmHeapType::I31,
+                }),
+                reg,
+            ))
+        })
+    }
+
+    fn visit_i31_get_s(&mut self) -> Self::Output {
+        self.emit_i31_get(ShiftKind::ShrS)
+    }
+
+    fn visit_i31_get_u(&mut self) -> Self::Output {
+        self.emit_i31_get(ShiftKind::ShrU)
+    }
+
     fn visit_i32_load(&mut self, memarg: MemArg) -> Self::Output {
         self.emit_wasm_load(
             &memarg,
@@ -4614,6 +5109,46 @@ where
                 Ok(TypedReg::i32(dst)) // Return value for comparisons is an `i32`.
             })
     }
+
+    /// Returns the type of the given global if it holds GC references, which
+    /// are accessed through the collector's barriers.
+    fn gc_ref_global_type(&mut self, index: GlobalIndex) -> Option<WasmRefType> {
+        match self.env.resolve_global(index).ty {
+            WasmValType::Ref(rt) if rt.is_vmgcref_type() => Some(rt),
+            _ => None,
+        }
+    }
+
+    /// Extracts the 31-bit integer of the `i31ref` at the top of the value
+    /// stack, sign- or zero-extending it depending on the given shift kind.
+    fn emit_i31_get(&mut self, kind: ShiftKind) -> Result<()> {
+        self.context.unop(self.masm, |masm, reg| {
+            masm.trapz(reg, TRAP_NULL_REFERENCE)?;
+            masm.shift_ir(writable!(reg), Imm::i32(1), reg, kind, OperandSize::S32)?;
+            Ok(TypedReg::i32(reg))
+        })
+    }
+}
+
+/// Calculates the return area of a conditional branch.
+///
+/// In the case of conditional branches there's a possibility that we'll exit
+/// early from the block or fallthrough, for a fallthrough, we cannot rely on
+/// the pre-computed return area; it must be recalculated so that any values
+/// that are generated are correctly placed near the current stack pointer.
+fn conditional_branch_ret_area<M: MacroAssembler>(
+    results: &ABIResults,
+    context: &mut CodeGenContext<Emission>,
+    masm: &mut M,
+) -> Result<Option<RetArea>> {
+    if results.on_stack() {
+        let stack_consumed = context.stack.sizeof(results.stack_operands_len());
+        let base = masm.sp_offset()?.as_u32() - stack_consumed;
+        let offs = base + results.size();
+        Ok(Some(RetArea::sp(SPOffset::from_u32(offs))))
+    } else {
+        Ok(None)
+    }
 }
 
 impl TryFrom<WasmValType> for OperandSize {
@@ -4624,13 +5159,16 @@ impl TryFrom<WasmValType> for OperandSize {
             WasmValType::I64 | WasmValType::F64 => OperandSize::S64,
             WasmValType::V128 => OperandSize::S128,
             WasmValType::Ref(rt) => {
-                match rt.heap_type {
+                match rt.heap_type.top() {
                     // TODO: Hardcoded size, assuming 64-bit support only. Once
                     // Wasmtime supports 32-bit architectures, this will need
                     // to be updated in such a way that the calculation of the
                     // OperandSize will depend on the target's  pointer size.
-                    WasmHeapType::Func => OperandSize::S64,
-                    WasmHeapType::Extern => OperandSize::S64,
+                    WasmHeapTopType::Func => OperandSize::S64,
+                    // GC references are 32-bit indices into the GC heap.
+                    WasmHeapTopType::Any | WasmHeapTopType::Extern | WasmHeapTopType::Exn => {
+                        OperandSize::S32
+                    }
                     _ => bail!(CodeGenError::unsupported_wasm_type()),
                 }
             }